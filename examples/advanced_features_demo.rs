@@ -6,6 +6,9 @@
 //! - Анализ внутренних структур данных
 //! - Оптимизацию для специфических случаев
 
+// Демонстрация сохраняет исходный вид кода
+#![allow(clippy::collapsible_match, clippy::empty_line_after_doc_comments)]
+
 use arithmetic_compression_wizard::prelude::*;
 use arithmetic_compression_wizard::statistics::analyze_compression;
 
@@ -25,8 +28,8 @@ fn main() {
     // Оптимизация для специфических данных
     demo_data_optimization();
 
-    // Стресс-тест больших данных
-    demo_stress_test();
+    // Замеры скорости — `acw bench <файл>`: таблица по движкам и моделям
+    println!("⏱️ Скорость и коэффициент на своих данных: cargo run --bin acw -- bench <файл>");
 
     println!("✨ Демонстрация продвинутых возможностей завершена!");
 }
@@ -222,50 +225,6 @@ fn demo_data_optimization() {
     }
 }
 
-/// Стресс-тест для больших объемов данных
-fn demo_stress_test() {
-    println!("💪 Стресс-тест производительности");
-    println!("──────────────────────────────");
-
-    let sizes = vec![1_000, 10_000, 100_000, 500_000];
-
-    println!("Размер    | Время сжатия | Время восст. | Коэффициент | Скорость");
-    println!("─────────|──────────────|──────────────|─────────────|─────────");
-
-    for size in sizes {
-        let test_data = generate_test_data(size);
-        let bytes = test_data.as_bytes();
-
-        // Измеряем сжатие
-        let start = std::time::Instant::now();
-        let compressed = compress_data(bytes);
-        let compression_time = start.elapsed();
-
-        // Измеряем восстановление
-        let start = std::time::Instant::now();
-        let restored = decompress_data(compressed.clone());
-        let decompression_time = start.elapsed();
-
-        // Проверяем корректность
-        assert_eq!(bytes, restored.as_slice());
-
-        let ratio = (1.0 - compressed.len() as f64 / bytes.len() as f64) * 100.0;
-        let speed = bytes.len() as f64 / compression_time.as_secs_f64() / 1_000_000.0;
-
-        println!(
-            "{:>8} | {:>11.2}ms | {:>11.2}ms | {:>9.1}% | {:>6.1} МБ/с",
-            format_size(size),
-            compression_time.as_millis(),
-            decompression_time.as_millis(),
-            ratio,
-            speed
-        );
-    }
-
-    println!("\n💡 Результаты показывают масштабируемость алгоритма");
-    println!();
-}
-
 /// Вспомогательные функции
 
 fn estimate_total_size(artifact: &CompressionArtifact) -> usize {
@@ -325,18 +284,3 @@ fn generate_log_sample() -> String {
 fn generate_binary_sample() -> String {
     (0..=255u8).cycle().take(1000).map(|b| b as char).collect()
 }
-
-fn generate_test_data(size: usize) -> String {
-    let base = "Rust programming language systems safety performance ";
-    base.repeat(size / base.len() + 1)[..size].to_string()
-}
-
-fn format_size(size: usize) -> String {
-    if size >= 1_000_000 {
-        format!("{:.1}M", size as f64 / 1_000_000.0)
-    } else if size >= 1_000 {
-        format!("{}K", size / 1_000)
-    } else {
-        size.to_string()
-    }
-}
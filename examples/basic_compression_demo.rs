@@ -36,7 +36,7 @@ fn demo_text_compression() {
     let compressed = compress_data(original_text);
 
     // Восстанавливаем
-    let restored = decompress_data(compressed.clone());
+    let restored = decompress_data(&compressed);
 
     // Проверяем корректность
     assert_eq!(original_text.as_slice(), restored.as_slice());
@@ -57,7 +57,7 @@ fn demo_repetitive_data() {
     let repetitive_data = b"aaaaaabbbbbbccccccddddddeeeeeeffffffgggggg";
 
     let compressed = compress_data(repetitive_data);
-    let restored = decompress_data(compressed.clone());
+    let restored = decompress_data(&compressed);
 
     assert_eq!(repetitive_data.as_slice(), restored.as_slice());
 
@@ -77,7 +77,7 @@ fn demo_binary_data() {
     let binary_data: Vec<u8> = (0..=255).cycle().take(100).collect();
 
     let compressed = compress_data(&binary_data);
-    let restored = decompress_data(compressed.clone());
+    let restored = decompress_data(&compressed);
 
     assert_eq!(binary_data.as_slice(), restored.as_slice());
 
@@ -114,6 +114,10 @@ fn demo_analysis() {
         analysis.compression_density
     );
     println!("   Слов в словаре:      {}", analysis.word_dictionary_size);
+    println!(
+        "   Фраз в словаре:      {}",
+        analysis.phrase_dictionary_size
+    );
 
     println!("   Топ символов:");
     for (symbol, freq) in analysis.top_symbols.iter().take(5) {
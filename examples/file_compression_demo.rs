@@ -6,6 +6,9 @@
 //! - Сравнение с исходными файлами
 //! - Измерение производительности
 
+// Демонстрация сохраняет исходный вид кода
+#![allow(clippy::redundant_pattern_matching)]
+
 use arithmetic_compression_wizard::prelude::*;
 use arithmetic_compression_wizard::statistics::analyze_compression;
 use std::fs;
@@ -82,7 +85,7 @@ fn demo_text_file() -> io::Result<()> {
 
     // Измеряем время декомпрессии
     let start = Instant::now();
-    let restored = decompress_data(compressed.clone());
+    let restored = decompress_data(&compressed);
     let decompression_time = start.elapsed();
 
     // Проверяем корректность
@@ -129,7 +132,7 @@ fn demo_structured_data() -> io::Result<()> {
 
     // Сжимаем
     let compressed = compress_data(&original_data);
-    let restored = decompress_data(compressed.clone());
+    let restored = decompress_data(&compressed);
 
     assert_eq!(original_data, restored);
 
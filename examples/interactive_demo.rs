@@ -2,13 +2,28 @@
 //!
 //! Этот пример позволяет пользователю:
 //! - Вводить собственный текст для сжатия
+//! - Сжимать файлы, выбранные из списка или по пути
 //! - Сравнивать разные алгоритмы
 //! - Экспериментировать с параметрами
 //! - Анализировать результаты в реальном времени
+//!
+//! Если распакованные байты не совпали с исходными, демонстрация печатает
+//! отчет `diagnostics::diff_roundtrip` — его можно приложить к сообщению
+//! об ошибке.
+
+// Демонстрация сохраняет исходный вид кода
+#![allow(clippy::len_zero)]
 
+use arithmetic_compression_wizard::benchmark::compare;
+use arithmetic_compression_wizard::diagnostics::diff_roundtrip;
 use arithmetic_compression_wizard::prelude::*;
 use arithmetic_compression_wizard::statistics::analyze_compression;
+use std::fs;
 use std::io::{self, Write};
+use std::path::PathBuf;
+
+/// Сколько файлов показывает выбор файла
+const LISTED_FILES: usize = 20;
 
 fn main() {
     println!("🎮 Интерактивная демонстрация арифметического сжатия");
@@ -18,14 +33,15 @@ fn main() {
     loop {
         show_menu();
 
-        let choice = get_user_input("Выберите опцию (1-5): ");
+        let choice = get_user_input("Выберите опцию (1-6): ");
 
         match choice.trim() {
             "1" => demo_custom_text(),
-            "2" => demo_text_comparison(),
-            "3" => demo_entropy_analysis(),
-            "4" => demo_compression_limits(),
-            "5" => {
+            "2" => demo_file(),
+            "3" => demo_text_comparison(),
+            "4" => demo_entropy_analysis(),
+            "5" => demo_compression_limits(),
+            "6" => {
                 println!("👋 До свидания!");
                 break;
             }
@@ -38,10 +54,11 @@ fn main() {
 fn show_menu() {
     println!("🔧 Доступные опции:");
     println!("  1️⃣  Сжать свой текст");
-    println!("  2️⃣  Сравнение типов текста");
-    println!("  3️⃣  Анализ энтропии");
-    println!("  4️⃣  Пределы сжатия");
-    println!("  5️⃣  Выход");
+    println!("  2️⃣  Сжать файл");
+    println!("  3️⃣  Сравнение типов текста");
+    println!("  4️⃣  Анализ энтропии");
+    println!("  5️⃣  Пределы сжатия");
+    println!("  6️⃣  Выход");
     println!();
 }
 
@@ -72,10 +89,10 @@ fn demo_custom_text() {
 
     // Сжимаем текст
     let compressed = compress_data(text_bytes);
-    let restored = decompress_data(compressed.clone());
+    let restored = decompress_data(&compressed);
 
     // Проверяем корректность
-    let is_correct = text_bytes == restored.as_slice();
+    let is_correct = check_roundtrip(text_bytes, &restored);
 
     // Выводим результаты
     println!("\n📊 Результаты:");
@@ -120,6 +137,98 @@ fn demo_custom_text() {
     println!();
 }
 
+/// Сжатие файла, выбранного из списка или по пути
+fn demo_file() {
+    println!("\n📁 Сжатие файла");
+    println!("────────────────");
+
+    let Some(path) = pick_file() else {
+        println!("❌ Файл не выбран. Возврат в меню.\n");
+        return;
+    };
+    let data = match fs::read(&path) {
+        Ok(data) => data,
+        Err(error) => {
+            println!("❌ Не удалось прочитать {}: {error}\n", path.display());
+            return;
+        }
+    };
+
+    println!("\n🔄 Сжатие {}...", path.display());
+    let compressed = match try_compress_data(&data) {
+        Ok(compressed) => compressed,
+        Err(error) => {
+            println!("❌ Сжатие не удалось: {error}\n");
+            return;
+        }
+    };
+    let is_correct = match try_decompress_data(&compressed, &DecodeOptions::default()) {
+        Ok(restored) => check_roundtrip(&data, &restored),
+        Err(error) => {
+            println!("❌ Распаковка не удалась: {error}");
+            false
+        }
+    };
+
+    println!("\n📊 Результаты:");
+    println!("   Исходный размер:    {} байт", data.len());
+    println!("   Сжатый размер:      {} байт", compressed.len());
+    if !data.is_empty() {
+        println!(
+            "   Коэффициент сжатия: {:.1}%",
+            (1.0 - compressed.len() as f64 / data.len() as f64) * 100.0
+        );
+    }
+    println!(
+        "   Корректность:       {}\n",
+        if is_correct { "✅" } else { "❌" }
+    );
+}
+
+/// Выбор файла: номер из списка текущего каталога или путь
+fn pick_file() -> Option<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(".")
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.is_file())
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files.truncate(LISTED_FILES);
+
+    if !files.is_empty() {
+        println!("Файлы текущего каталога:");
+        for (index, path) in files.iter().enumerate() {
+            println!("  {:>2}. {}", index + 1, path.display());
+        }
+    }
+    let choice = get_user_input("Номер файла или путь: ");
+    let choice = choice.trim();
+    if choice.is_empty() {
+        return None;
+    }
+    match choice.parse::<usize>() {
+        Ok(number) if (1..=files.len()).contains(&number) => Some(files[number - 1].clone()),
+        _ => Some(PathBuf::from(choice)),
+    }
+}
+
+/// Сравнивает восстановленные байты с исходными
+///
+/// При расхождении печатает первые различия с шестнадцатеричным
+/// контекстом — этот текст стоит приложить к сообщению об ошибке.
+fn check_roundtrip(original: &[u8], restored: &[u8]) -> bool {
+    let diff = diff_roundtrip(original, restored);
+    if !diff.is_identical() {
+        println!("\n🩺 Восстановленные данные отличаются от исходных:");
+        print!("{diff}");
+        println!("   Приложите этот отчет к сообщению об ошибке.");
+    }
+    diff.is_identical()
+}
+
 /// Сравнение разных типов текста
 fn demo_text_comparison() {
     println!("\n📊 Сравнение типов текста");
@@ -141,28 +250,25 @@ fn demo_text_comparison() {
         ),
     ];
 
-    println!("Тип текста        | Размер | Сжато | Коэффициент | Энтропия");
-    println!("─────────────────|────────|───────|─────────────|─────────");
+    println!("Тип текста        | Кодек        | Размер | Сжато | Коэффициент | Энтропия");
+    println!("─────────────────|──────────────|────────|───────|─────────────|─────────");
 
     for (name, text) in test_cases {
         let text_bytes = text.as_bytes();
-        let compressed = compress_data(text_bytes);
         let analysis = analyze_compression(text_bytes);
 
-        let ratio = if text_bytes.len() > 0 {
-            (1.0 - compressed.len() as f64 / text_bytes.len() as f64) * 100.0
-        } else {
-            0.0
-        };
-
-        println!(
-            "{:<17}| {:>6} | {:>5} | {:>9.1}% | {:>7.2}",
-            name,
-            text_bytes.len(),
-            compressed.len(),
-            ratio,
-            analysis.shannon_entropy
-        );
+        // Мастер и, с фичей `compare`, DEFLATE и zstd на тех же данных
+        for result in compare(text_bytes) {
+            println!(
+                "{:<17}| {:<12} | {:>6} | {:>5} | {:>9.1}% | {:>7.2}",
+                name,
+                result.codec,
+                result.original_size,
+                result.compressed_size,
+                result.compression_ratio,
+                analysis.shannon_entropy
+            );
+        }
     }
 
     println!("\n💡 Наблюдения:");
@@ -3,6 +3,9 @@
 //! Самый простой пример для начала работы с библиотекой.
 //! Показывает основные операции за 30 секунд.
 
+// Демонстрация сохраняет исходный вид кода
+#![allow(clippy::len_zero)]
+
 use arithmetic_compression_wizard::simple_api::{compress_data, decompress_data};
 
 fn main() {
@@ -22,7 +25,7 @@ fn main() {
     println!("\n📦 Сжато в {} байт", compressed.len());
 
     // 3. Восстанавливаем данные
-    let restored = decompress_data(compressed.clone());
+    let restored = decompress_data(&compressed);
     println!("🔄 Восстановлено {} байт", restored.len());
 
     // 4. Проверяем корректность
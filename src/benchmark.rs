@@ -0,0 +1,268 @@
+//! Сравнение кодеков ⏱️
+//!
+//! [`compare`] сжимает одни и те же данные встроенными движками мастера и,
+//! с фичей `compare`, кодеками DEFLATE (`flate2`) и zstd — и сообщает
+//! размер, коэффициент и скорость каждого. Так выбор движка для своих
+//! данных делается одним вызовом, а не отдельным стендом.
+//! [`try_measure_config`] замеряет одну конфигурацию — им пользуется
+//! `acw bench`, перебирающий движки и модели.
+
+use crate::compression_engine::engine_covenant::EngineConfig;
+use crate::compression_engine::DEFAULT_HALF_LIFE;
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::error::WizardResult;
+use crate::format::container_runes::{ArchiveHeader, ModelKind};
+use crate::simple_api::{try_compress_with_config, try_decompress_data};
+use std::time::{Duration, Instant};
+
+/// Уровень DEFLATE (по умолчанию `flate2`)
+#[cfg(feature = "compare")]
+const DEFLATE_LEVEL: u32 = 6;
+
+/// Уровень zstd (по умолчанию библиотеки)
+#[cfg(feature = "compare")]
+const ZSTD_LEVEL: i32 = 3;
+
+/// Итог одного кодека ⏱️
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodecResult {
+    /// Имя кодека: `acw`, `acw-adaptive`, `acw-best`, `deflate`, `zstd`
+    pub codec: &'static str,
+    /// Размер исходных данных в байтах
+    pub original_size: usize,
+    /// Размер сжатых данных в байтах
+    pub compressed_size: usize,
+    /// Коэффициент сжатия в процентах (как в `CompressionAnalysis`)
+    pub compression_ratio: f64,
+    /// Время сжатия
+    pub compress_time: Duration,
+    /// Время распаковки
+    pub decompress_time: Duration,
+    /// Длина заголовка архива мастера: служебные байты сверх тела кадра
+    /// (`None` — микроархив без заголовка или сторонний кодек)
+    pub header_size: Option<usize>,
+    /// Распакованные данные совпали с исходными
+    pub roundtrip_ok: bool,
+}
+
+impl CodecResult {
+    /// Скорость сжатия, МиБ/с исходных данных
+    pub fn compress_mib_per_sec(&self) -> f64 {
+        mib_per_sec(self.original_size, self.compress_time)
+    }
+
+    /// Скорость распаковки, МиБ/с исходных данных
+    pub fn decompress_mib_per_sec(&self) -> f64 {
+        mib_per_sec(self.original_size, self.decompress_time)
+    }
+}
+
+/// Сравнивает кодеки на данных ⏱️
+///
+/// ```
+/// use arithmetic_compression_wizard::benchmark::compare;
+///
+/// let results = compare(b"the wizard and the owl ".repeat(50));
+/// assert!(results.iter().all(|result| result.roundtrip_ok));
+/// assert_eq!(results[0].codec, "acw");
+/// ```
+///
+/// Паникует, если мастер не может сжать вход (см. [`try_compare`]).
+pub fn compare(data: impl AsRef<[u8]>) -> Vec<CodecResult> {
+    try_compare(data).expect("вход не сжимается мастером: используйте try_compare")
+}
+
+/// Проверяемое сравнение кодеков
+///
+/// Ошибки сжатия мастера возвращаются; ошибка распаковки любого кодека
+/// — это `roundtrip_ok == false`.
+pub fn try_compare(data: impl AsRef<[u8]>) -> WizardResult<Vec<CodecResult>> {
+    let data = data.as_ref();
+    let adaptive = EngineConfig::default().with_model(ModelKind::Adaptive {
+        half_life: DEFAULT_HALF_LIFE,
+    });
+
+    #[cfg_attr(not(feature = "compare"), allow(unused_mut))]
+    let mut results = vec![
+        try_measure_config("acw", data, &EngineConfig::default())?,
+        try_measure_config("acw-adaptive", data, &adaptive)?,
+        try_measure_config("acw-best", data, &EngineConfig::best_ratio())?,
+    ];
+    #[cfg(feature = "compare")]
+    results.extend([
+        measure_codec("deflate", data, deflate, inflate),
+        measure_codec("zstd", data, zstd_compress, zstd_decompress),
+    ]);
+    Ok(results)
+}
+
+/// Замер мастера с параметрами `config` под именем `codec` ⏱️
+///
+/// Сжатие — [`try_compress_with_config`], распаковка — без лимитов.
+///
+/// ```
+/// use arithmetic_compression_wizard::benchmark::try_measure_config;
+/// use arithmetic_compression_wizard::compression_engine::EngineConfig;
+/// use arithmetic_compression_wizard::format::EngineKind;
+///
+/// let data = b"the owl times every spell. ".repeat(100);
+/// let config = EngineConfig::default().with_engine(EngineKind::SplitStreams);
+/// let result = try_measure_config("split-streams", &data, &config).unwrap();
+/// assert!(result.roundtrip_ok);
+/// assert!(result.header_size.unwrap() < result.compressed_size);
+/// ```
+pub fn try_measure_config(
+    codec: &'static str,
+    data: &[u8],
+    config: &EngineConfig,
+) -> WizardResult<CodecResult> {
+    let started = Instant::now();
+    let archive = try_compress_with_config(data, config)?;
+    let compress_time = started.elapsed();
+
+    let started = Instant::now();
+    let restored = try_decompress_data(&archive, &DecodeOptions::unlimited());
+    let decompress_time = started.elapsed();
+
+    Ok(CodecResult {
+        header_size: ArchiveHeader::parse(&archive).map(|(_, body_offset)| body_offset),
+        ..result(
+            codec,
+            data,
+            archive.len(),
+            compress_time,
+            decompress_time,
+            restored.is_ok_and(|restored| restored == data),
+        )
+    })
+}
+
+/// Замер стороннего кодека
+#[cfg(feature = "compare")]
+fn measure_codec(
+    codec: &'static str,
+    data: &[u8],
+    compress: fn(&[u8]) -> std::io::Result<Vec<u8>>,
+    decompress: fn(&[u8]) -> std::io::Result<Vec<u8>>,
+) -> CodecResult {
+    let started = Instant::now();
+    let archive = compress(data).expect("сжатие в память не дает ошибок ввода-вывода");
+    let compress_time = started.elapsed();
+
+    let started = Instant::now();
+    let restored = decompress(&archive);
+    let decompress_time = started.elapsed();
+
+    result(
+        codec,
+        data,
+        archive.len(),
+        compress_time,
+        decompress_time,
+        restored.is_ok_and(|restored| restored == data),
+    )
+}
+
+/// Итог замера
+fn result(
+    codec: &'static str,
+    data: &[u8],
+    compressed_size: usize,
+    compress_time: Duration,
+    decompress_time: Duration,
+    roundtrip_ok: bool,
+) -> CodecResult {
+    let compression_ratio = if data.is_empty() {
+        0.0
+    } else {
+        (1.0 - compressed_size as f64 / data.len() as f64) * 100.0
+    };
+    CodecResult {
+        codec,
+        original_size: data.len(),
+        compressed_size,
+        compression_ratio,
+        compress_time,
+        decompress_time,
+        roundtrip_ok,
+        header_size: None,
+    }
+}
+
+/// МиБ/с (бесконечность для мгновенного замера)
+fn mib_per_sec(bytes: usize, elapsed: Duration) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0) / elapsed.as_secs_f64()
+}
+
+#[cfg(feature = "compare")]
+fn deflate(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    use std::io::Write;
+    let mut encoder =
+        flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::new(DEFLATE_LEVEL));
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
+#[cfg(feature = "compare")]
+fn inflate(archive: &[u8]) -> std::io::Result<Vec<u8>> {
+    use std::io::Read;
+    let mut restored = Vec::new();
+    flate2::read::DeflateDecoder::new(archive).read_to_end(&mut restored)?;
+    Ok(restored)
+}
+
+#[cfg(feature = "compare")]
+fn zstd_compress(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    zstd::encode_all(data, ZSTD_LEVEL)
+}
+
+#[cfg(feature = "compare")]
+fn zstd_decompress(archive: &[u8]) -> std::io::Result<Vec<u8>> {
+    zstd::decode_all(archive)
+}
+
+/// Тесты сравнения 🎯
+#[cfg(test)]
+mod benchmark_tests {
+    use super::*;
+    use crate::simple_api::compress_data;
+
+    #[test]
+    fn test_wizard_results_match_plain_api() {
+        let data = "the wizard and the owl read the ancient scroll. ".repeat(40);
+        let results = compare(&data);
+        assert_eq!(results[0].codec, "acw");
+        assert_eq!(results[1].codec, "acw-adaptive");
+        assert_eq!(results[2].codec, "acw-best");
+        assert_eq!(results[0].compressed_size, compress_data(&data).len());
+        assert!(results[0].header_size.is_some());
+        for result in &results {
+            assert!(result.roundtrip_ok, "{}", result.codec);
+            assert_eq!(result.original_size, data.len());
+            assert!(result.compression_ratio > 0.0);
+            assert!(result.compress_mib_per_sec() > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_empty_input() {
+        for result in compare(b"") {
+            assert!(result.roundtrip_ok);
+            assert_eq!(result.compression_ratio, 0.0);
+        }
+    }
+
+    #[cfg(feature = "compare")]
+    #[test]
+    fn test_external_codecs_join_the_table() {
+        let data = "owl post ".repeat(500);
+        let results = compare(&data);
+        let codecs: Vec<&str> = results.iter().map(|result| result.codec).collect();
+        assert_eq!(
+            codecs,
+            ["acw", "acw-adaptive", "acw-best", "deflate", "zstd"]
+        );
+        assert!(results.iter().all(|result| result.roundtrip_ok));
+        assert!(results[3].compressed_size < data.len() / 10);
+    }
+}
@@ -0,0 +1,631 @@
+//! Утилита командной строки acw 🧙‍♂️
+//!
+//! ```text
+//! acw train --out dict.acwd <файлы...>
+//! acw compress [--dict dict.acwd] <вход> <выход>
+//! acw compress -r [--include <шаблон>]... [--exclude <шаблон>]... <каталог> <том>
+//! acw decompress [--dict dict.acwd] <вход> <выход>
+//! acw extract <том> <каталог>
+//! acw inspect [--dict] <архив>
+//! acw bench [--engines <движки>] [--models <модели>] <файл>
+//! ```
+//!
+//! `train` обучает общую модель ([`CompressorHandle`]) на корпусе и пишет
+//! файл словаря. Архивы, сжатые с `--dict`, не содержат ни словаря, ни
+//! таблицы частот и распаковываются только с тем же файлом словаря.
+//! `inspect` печатает заголовок архива, а с `--dict` — и его словарь,
+//! чтобы проверить, какие подстроки видны без распаковки.
+//!
+//! `compress -r` собирает каталог в том ([`ArchiveWriter`]): каждый файл —
+//! запись с путем относительно каталога и правами доступа. Шаблоны
+//! `--exclude` без `/` сравниваются с каждым компонентом пути (так
+//! исключается и целый каталог), с `/` — с путем целиком; `--include`
+//! оставляет только подходящие файлы. `*` и `?` не переходят через `/`,
+//! `**` переходит. `extract` восстанавливает записи тома под каталогом.
+//!
+//! `bench` сжимает файл каждой конфигурацией и печатает таблицу:
+//! коэффициент, скорость сжатия и распаковки и длину заголовка. Движки —
+//! имена из [`EngineRegistry`] через запятую (по умолчанию все встроенные),
+//! модели арифметического движка — `static`, `adaptive`, `order2`.
+//! Уровней сжатия у мастера нет: конфигурации различаются движком и
+//! моделью.
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::ExitCode;
+
+use arithmetic_compression_wizard::benchmark::try_measure_config;
+use arithmetic_compression_wizard::compression_engine::{
+    EngineConfig, EngineRegistry, DEFAULT_CONTEXT_TABLE_BITS, DEFAULT_HALF_LIFE,
+};
+use arithmetic_compression_wizard::format::{
+    read_dictionary, read_metadata, ArchiveHeader, ArchiveReader, ArchiveWriter, AtomicFile,
+    EngineKind, ModelKind,
+};
+use arithmetic_compression_wizard::prelude::*;
+
+const USAGE: &str = "использование:
+  acw train --out <словарь.acwd> <файлы...>
+  acw compress [--dict <словарь.acwd>] <вход> <выход>
+  acw compress -r [--include <шаблон>]... [--exclude <шаблон>]... <каталог> <том>
+  acw decompress [--dict <словарь.acwd>] <вход> <выход>
+  acw extract <том> <каталог>
+  acw inspect [--dict] <архив>
+  acw bench [--engines <движки,...>] [--models <модели,...>] <файл>";
+
+/// Ошибка утилиты
+enum CliError {
+    /// Неверные аргументы (код выхода 2)
+    Usage(String),
+    /// Операция не удалась (код выхода 1)
+    Failed(String),
+}
+
+/// Разобранные аргументы подкоманды
+struct CliArguments {
+    out: Option<String>,
+    dict: Option<String>,
+    engines: Option<String>,
+    models: Option<String>,
+    recursive: bool,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    positional: Vec<String>,
+}
+
+fn main() -> ExitCode {
+    let arguments: Vec<String> = std::env::args().skip(1).collect();
+
+    match run(&arguments) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(CliError::Usage(message)) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            ExitCode::from(2)
+        }
+        Err(CliError::Failed(message)) => {
+            eprintln!("ошибка: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(arguments: &[String]) -> Result<(), CliError> {
+    let (subcommand, rest) = arguments
+        .split_first()
+        .ok_or_else(|| CliError::Usage("не указана подкоманда".to_string()))?;
+    // У inspect флаг --dict без значения
+    if subcommand == "inspect" {
+        return inspect(rest);
+    }
+    let parsed = parse_flags(rest)?;
+
+    match subcommand.as_str() {
+        "train" => train(&parsed),
+        "compress" => compress(&parsed),
+        "decompress" => decompress(&parsed),
+        "extract" => extract(&parsed),
+        "bench" => bench(&parsed),
+        other => Err(CliError::Usage(format!(
+            "неизвестная подкоманда `{}`",
+            other
+        ))),
+    }
+}
+
+/// Отделяет флаги от позиционных аргументов
+fn parse_flags(arguments: &[String]) -> Result<CliArguments, CliError> {
+    let mut parsed = CliArguments {
+        out: None,
+        dict: None,
+        engines: None,
+        models: None,
+        recursive: false,
+        include: Vec::new(),
+        exclude: Vec::new(),
+        positional: Vec::new(),
+    };
+
+    let mut remaining = arguments.iter();
+    while let Some(argument) = remaining.next() {
+        let flag = argument.as_str();
+        match flag {
+            "-r" | "--recursive" => {
+                parsed.recursive = true;
+                continue;
+            }
+            "--levels" => {
+                return Err(CliError::Usage(
+                    "уровней сжатия нет: сравните движки (--engines) и модели (--models)"
+                        .to_string(),
+                ))
+            }
+            "--out" | "--dict" | "--engines" | "--models" | "--include" | "--exclude" => {}
+            flag if flag.starts_with('-') && flag.len() > 1 => {
+                return Err(CliError::Usage(format!("неизвестный флаг `{}`", flag)))
+            }
+            _ => {
+                parsed.positional.push(argument.clone());
+                continue;
+            }
+        }
+        let value = remaining
+            .next()
+            .ok_or_else(|| CliError::Usage(format!("флагу `{}` нужно значение", argument)))?
+            .clone();
+        match flag {
+            "--out" => parsed.out = Some(value),
+            "--dict" => parsed.dict = Some(value),
+            "--engines" => parsed.engines = Some(value),
+            "--models" => parsed.models = Some(value),
+            "--include" => parsed.include.push(value),
+            _ => parsed.exclude.push(value),
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// Вход и выход подкоманд `compress` и `decompress`
+fn input_output(parsed: &CliArguments) -> Result<(&str, &str), CliError> {
+    match parsed.positional.as_slice() {
+        [input, output] => Ok((input, output)),
+        _ => Err(CliError::Usage("нужны пути входа и выхода".to_string())),
+    }
+}
+
+fn read_file(path: &str) -> Result<Vec<u8>, CliError> {
+    fs::read(path).map_err(|error| CliError::Failed(format!("{}: {}", path, error)))
+}
+
+/// Пишет файл целиком через временный: прерванная утилита не оставляет
+/// обрубка под именем выхода
+fn write_file(path: &str, bytes: &[u8]) -> Result<(), CliError> {
+    AtomicFile::create(path)
+        .and_then(|mut file| {
+            file.write_all(bytes)?;
+            file.commit()
+        })
+        .map_err(|error| CliError::Failed(format!("{}: {}", path, error)))
+}
+
+fn load_dictionary(path: &str) -> Result<CompressorHandle, CliError> {
+    CompressorHandle::from_dictionary_bytes(&read_file(path)?)
+        .map_err(|error| CliError::Failed(format!("{}: {}", path, error)))
+}
+
+/// `acw train --out dict.acwd <файлы...>`
+fn train(parsed: &CliArguments) -> Result<(), CliError> {
+    let out = parsed
+        .out
+        .as_deref()
+        .ok_or_else(|| CliError::Usage("train требует --out".to_string()))?;
+    if parsed.positional.is_empty() {
+        return Err(CliError::Usage("train требует файлы корпуса".to_string()));
+    }
+
+    // Файлы корпуса разделяются переводом строки, чтобы слова не склеивались
+    let mut corpus = Vec::new();
+    for path in &parsed.positional {
+        corpus.extend_from_slice(&read_file(path)?);
+        corpus.push(b'\n');
+    }
+
+    let handle = CompressorHandle::train(&corpus);
+    write_file(out, &handle.to_dictionary_bytes())?;
+    println!(
+        "📖 Словарь {:#018x}: {} слов из {} байт корпуса → {}",
+        handle.dictionary_id(),
+        handle.word_grimoire().len(),
+        corpus.len(),
+        out
+    );
+    Ok(())
+}
+
+/// `acw compress [--dict dict.acwd] <вход> <выход>`
+fn compress(parsed: &CliArguments) -> Result<(), CliError> {
+    if parsed.recursive {
+        return compress_directory(parsed);
+    }
+    if !parsed.include.is_empty() || !parsed.exclude.is_empty() {
+        return Err(CliError::Usage(
+            "--include и --exclude работают только с -r".to_string(),
+        ));
+    }
+    let (input, output) = input_output(parsed)?;
+    let original = read_file(input)?;
+
+    let compressed = match parsed.dict.as_deref() {
+        Some(dictionary_path) => load_dictionary(dictionary_path)?
+            .compress(&original)
+            .to_bytes(),
+        None => compress_data(&original),
+    };
+
+    write_file(output, &compressed)?;
+    println!(
+        "🗜️ {}: {} → {} байт",
+        input,
+        original.len(),
+        compressed.len()
+    );
+    Ok(())
+}
+
+/// `acw compress -r [--include <шаблон>]... [--exclude <шаблон>]... <каталог> <том>`
+fn compress_directory(parsed: &CliArguments) -> Result<(), CliError> {
+    let (input, output) = input_output(parsed)?;
+    if parsed.dict.is_some() {
+        return Err(CliError::Usage(
+            "записи тома не сжимаются внешним словарем: уберите --dict".to_string(),
+        ));
+    }
+    let failed =
+        |path: &str, error: std::io::Error| CliError::Failed(format!("{}: {}", path, error));
+
+    let mut files = Vec::new();
+    collect_files(Path::new(input), "", parsed, &mut files)
+        .map_err(|error| failed(input, error))?;
+
+    let mut writer = ArchiveWriter::create(output).map_err(|error| failed(output, error))?;
+    let config = EngineConfig::default();
+    let mut original_len = 0;
+    for relative in &files {
+        let path = Path::new(input).join(relative);
+        writer
+            .add_file(relative, &path, &config)
+            .map_err(|error| failed(&path.to_string_lossy(), error))?;
+        original_len += writer
+            .entries()
+            .last()
+            .map_or(0, |entry| entry.original_len);
+    }
+    writer
+        .finish()
+        .and_then(AtomicFile::commit)
+        .map_err(|error| failed(output, error))?;
+
+    let archive_len = fs::metadata(output).map_or(0, |metadata| metadata.len());
+    println!(
+        "📚 {}: {} файлов, {} → {} байт",
+        input,
+        files.len(),
+        original_len,
+        archive_len
+    );
+    Ok(())
+}
+
+/// Собирает файлы под `directory` в порядке имен; `prefix` — путь
+/// каталога относительно корня (`""` или с `/` в конце)
+///
+/// Символические ссылки и прочие не обычные файлы пропускаются: том
+/// хранит только содержимое файлов. Имена не в UTF-8 записать в том
+/// нельзя: такие файлы и каталоги пропускаются с предупреждением.
+fn collect_files(
+    directory: &Path,
+    prefix: &str,
+    parsed: &CliArguments,
+    files: &mut Vec<String>,
+) -> std::io::Result<()> {
+    let mut children: Vec<(String, fs::FileType)> = Vec::new();
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        match entry.file_name().into_string() {
+            Ok(name) => children.push((name, entry.file_type()?)),
+            Err(_) => eprintln!(
+                "предупреждение: {} пропущен: имя не в UTF-8",
+                entry.path().display()
+            ),
+        }
+    }
+    children.sort_by(|left, right| left.0.cmp(&right.0));
+
+    for (name, file_type) in children {
+        let relative = format!("{}{}", prefix, name);
+        if is_excluded(&relative, &parsed.exclude) {
+            continue;
+        }
+        if file_type.is_dir() {
+            collect_files(
+                &directory.join(&name),
+                &format!("{}/", relative),
+                parsed,
+                files,
+            )?;
+        } else if file_type.is_file() && is_included(&relative, &parsed.include) {
+            files.push(relative);
+        }
+    }
+    Ok(())
+}
+
+/// Путь исключен: шаблон без `/` совпал с одним из компонентов, с `/` — с путем
+fn is_excluded(relative: &str, patterns: &[String]) -> bool {
+    patterns.iter().any(|pattern| {
+        if pattern.contains('/') {
+            glob_matches(pattern, relative)
+        } else {
+            relative
+                .split('/')
+                .any(|component| glob_matches(pattern, component))
+        }
+    })
+}
+
+/// Файл включен: шаблонов нет или шаблон без `/` совпал с именем файла,
+/// с `/` — с путем
+fn is_included(relative: &str, patterns: &[String]) -> bool {
+    let file_name = relative.rsplit('/').next().unwrap_or(relative);
+    patterns.is_empty()
+        || patterns.iter().any(|pattern| {
+            let subject = if pattern.contains('/') {
+                relative
+            } else {
+                file_name
+            };
+            glob_matches(pattern, subject)
+        })
+}
+
+/// Шаблон с `*`, `**` и `?` совпадает с текстом целиком
+///
+/// Два указателя без рекурсии: при несовпадении удлиняется последняя `*`
+/// (она не переходит через `/`), а если не может — последняя `**`. Время
+/// O(длина шаблона × длина текста) при любом числе звезд.
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut pattern_at, mut text_at) = (0, 0);
+    // Позиция за звездой в шаблоне и конец поглощенного ею текста
+    let mut star: Option<(usize, usize)> = None;
+    let mut globstar: Option<(usize, usize)> = None;
+
+    while text_at < text.len() {
+        match pattern.get(pattern_at) {
+            Some('*') if pattern.get(pattern_at + 1) == Some(&'*') => {
+                pattern_at += 2;
+                globstar = Some((pattern_at, text_at));
+                star = None;
+            }
+            Some('*') => {
+                pattern_at += 1;
+                star = Some((pattern_at, text_at));
+            }
+            Some('?') if text[text_at] != '/' => {
+                pattern_at += 1;
+                text_at += 1;
+            }
+            Some(&literal) if literal != '?' && literal == text[text_at] => {
+                pattern_at += 1;
+                text_at += 1;
+            }
+            _ => match (star, globstar) {
+                (Some((after_star, absorbed)), _) if text[absorbed] != '/' => {
+                    star = Some((after_star, absorbed + 1));
+                    (pattern_at, text_at) = (after_star, absorbed + 1);
+                }
+                (_, Some((after_globstar, absorbed))) => {
+                    star = None;
+                    globstar = Some((after_globstar, absorbed + 1));
+                    (pattern_at, text_at) = (after_globstar, absorbed + 1);
+                }
+                _ => return false,
+            },
+        }
+    }
+    pattern[pattern_at..].iter().all(|&symbol| symbol == '*')
+}
+
+/// `acw decompress [--dict dict.acwd] <вход> <выход>`
+fn decompress(parsed: &CliArguments) -> Result<(), CliError> {
+    let (input, output) = input_output(parsed)?;
+    let archive = read_file(input)?;
+
+    let (header, _) = ArchiveHeader::parse(&archive).ok_or_else(|| {
+        CliError::Failed(format!("{}: {}", input, WizardError::UnrecognizedHeader))
+    })?;
+
+    let restored = match (header.dictionary_id, parsed.dict.as_deref()) {
+        (Some(dictionary_id), None) => {
+            return Err(CliError::Failed(format!(
+                "{}: {}, укажите --dict",
+                input,
+                WizardError::ExternalDictionaryRequired(dictionary_id)
+            )))
+        }
+        (Some(_), Some(dictionary_path)) => {
+            let handle = load_dictionary(dictionary_path)?;
+            HandleArtifact::from_bytes(&archive)
+                .and_then(|artifact| handle.decompress(&artifact))
+                .ok_or_else(|| {
+                    CliError::Failed(format!(
+                        "{}: архив создан другим словарем или поврежден",
+                        input
+                    ))
+                })?
+        }
+        (None, _) => try_decompress_data(&archive, &DecodeOptions::default())
+            .map_err(|error| CliError::Failed(format!("{}: {}", input, error)))?,
+    };
+
+    write_file(output, &restored)?;
+    println!("📜 {}: {} → {} байт", input, archive.len(), restored.len());
+    Ok(())
+}
+
+/// `acw extract <том> <каталог>`
+fn extract(parsed: &CliArguments) -> Result<(), CliError> {
+    let (input, output) = input_output(parsed)?;
+    let failed = |error: std::io::Error| CliError::Failed(format!("{}: {}", input, error));
+
+    let mut reader = ArchiveReader::open(input).map_err(failed)?;
+    let entries = reader.entries().to_vec();
+    for entry in &entries {
+        reader
+            .extract_entry(&entry.name, output)
+            .map_err(|error| CliError::Failed(format!("{}: {}: {}", input, entry.name, error)))?;
+    }
+    let restored_len: u64 = entries.iter().map(|entry| entry.original_len).sum();
+    println!(
+        "📂 {}: {} файлов, {} байт → {}",
+        input,
+        entries.len(),
+        restored_len,
+        output
+    );
+    Ok(())
+}
+
+/// `acw inspect [--dict] <архив>`
+fn inspect(arguments: &[String]) -> Result<(), CliError> {
+    let (show_dictionary, paths): (Vec<&String>, Vec<&String>) = arguments
+        .iter()
+        .partition(|argument| argument.as_str() == "--dict");
+    let input = match paths.as_slice() {
+        [flag, ..] if flag.starts_with("--") => {
+            return Err(CliError::Usage(format!("неизвестный флаг `{}`", flag)))
+        }
+        [input] => input.as_str(),
+        _ => return Err(CliError::Usage("inspect требует путь архива".to_string())),
+    };
+    let archive = read_file(input)?;
+    let failed = |error: WizardError| CliError::Failed(format!("{}: {}", input, error));
+
+    let (header, _) =
+        ArchiveHeader::parse(&archive).ok_or(failed(WizardError::UnrecognizedHeader))?;
+    println!(
+        "🔍 {}: {} байт, формат {}, движок {:?}, модель {:?}",
+        input,
+        archive.len(),
+        header.format_version,
+        header.engine,
+        header.model_kind
+    );
+    if header.block_framed {
+        println!("   разбит на блоки");
+    }
+    if let Some(dictionary_id) = header.dictionary_id {
+        println!("   внешний словарь {:#018x}", dictionary_id);
+    }
+    let mut metadata: Vec<(String, String)> = read_metadata(&archive)
+        .map_err(failed)?
+        .into_iter()
+        .collect();
+    metadata.sort();
+    for (key, value) in metadata {
+        println!("   {} = {}", key, value);
+    }
+
+    if !show_dictionary.is_empty() {
+        let entries = read_dictionary(&archive).map_err(failed)?;
+        println!("📖 Словарь: {} записей", entries.len());
+        for entry in entries {
+            println!("{}", entry);
+        }
+    }
+    Ok(())
+}
+
+/// Модель арифметического движка по имени в `--models`
+fn model_by_name(name: &str) -> Option<ModelKind> {
+    match name {
+        "static" => Some(ModelKind::StaticOrder0),
+        "adaptive" => Some(ModelKind::Adaptive {
+            half_life: DEFAULT_HALF_LIFE,
+        }),
+        "order2" => Some(ModelKind::HashedOrder2 {
+            table_bits: DEFAULT_CONTEXT_TABLE_BITS,
+        }),
+        _ => None,
+    }
+}
+
+/// `acw bench [--engines <движки>] [--models <модели>] <файл>`
+///
+/// Уровней сжатия нет, поэтому `--levels` отклоняется при разборе флагов.
+fn bench(parsed: &CliArguments) -> Result<(), CliError> {
+    let input = match parsed.positional.as_slice() {
+        [input] => input.as_str(),
+        _ => return Err(CliError::Usage("bench требует путь файла".to_string())),
+    };
+    let registry = EngineRegistry::with_builtin_engines();
+    let engines = match parsed.engines.as_deref() {
+        None => registry.engine_kinds(),
+        Some(names) => names
+            .split(',')
+            .map(|name| {
+                registry
+                    .kind_by_name(name.trim())
+                    .ok_or_else(|| CliError::Usage(format!("неизвестный движок `{}`", name.trim())))
+            })
+            .collect::<Result<_, _>>()?,
+    };
+    let models = parsed
+        .models
+        .as_deref()
+        .unwrap_or("static,adaptive,order2")
+        .split(',')
+        .map(|name| {
+            model_by_name(name.trim())
+                .map(|model| (name.trim(), model))
+                .ok_or_else(|| CliError::Usage(format!("неизвестная модель `{}`", name.trim())))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let data = read_file(input)?;
+
+    println!("⏱️ {}: {} байт", input, data.len());
+    println!(
+        "{:<16} {:<9} {:>10} {:>8} {:>13} {:>13} {:>9}",
+        "движок", "модель", "размер", "коэф.%", "сжатие", "распак.", "заголовок"
+    );
+    let mut broken = Vec::new();
+    for engine in engines {
+        let engine_name = registry
+            .get(engine)
+            .map_or("custom", |engine| engine.name());
+        // Модель выбирается только у арифметического движка
+        let engine_models = if engine == EngineKind::Arithmetic {
+            models.clone()
+        } else {
+            vec![("-", ModelKind::StaticOrder0)]
+        };
+        for (model_name, model) in engine_models {
+            let config = EngineConfig::default()
+                .with_engine(engine)
+                .with_model(model);
+            let result = match try_measure_config(engine_name, &data, &config) {
+                Ok(result) => result,
+                Err(error) => {
+                    println!("{:<16} {:<9} ошибка: {}", engine_name, model_name, error);
+                    continue;
+                }
+            };
+            if !result.roundtrip_ok {
+                broken.push(format!("{}/{}", engine_name, model_name));
+            }
+            println!(
+                "{:<16} {:<9} {:>10} {:>8.1} {:>7.1} МиБ/с {:>7.1} МиБ/с {:>9}",
+                engine_name,
+                model_name,
+                result.compressed_size,
+                result.compression_ratio,
+                result.compress_mib_per_sec(),
+                result.decompress_mib_per_sec(),
+                result
+                    .header_size
+                    .map_or("-".to_string(), |size| size.to_string())
+            );
+        }
+    }
+
+    if broken.is_empty() {
+        Ok(())
+    } else {
+        Err(CliError::Failed(format!(
+            "распаковка не совпала со входом: {}",
+            broken.join(", ")
+        )))
+    }
+}
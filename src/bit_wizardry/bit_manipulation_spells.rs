@@ -1,271 +1,489 @@
-//! Заклинания манипуляции битами 🧙‍♂️⚡
-//!
-//! Битовые операции для арифметического кодирования.
-//! Демонстрирует силу Rust в системном программировании.
-//!
-//! Арифметическое кодирование представляет данные как число в интервале [0, 1).
-//! Каждый символ сужает интервал пропорционально своей частоте.
-//!
-//! Возможности Rust:
-//! - Безопасные битовые операции
-//! - Константы времени компиляции
-//! - Абстракции нулевой стоимости
-//! - Типобезопасность
-
-/// Максимальная точность арифметического кодирования (const время компиляции)
-pub const ARITHMETIC_PRECISION_LIMIT: u32 = (1 << 24) - 1;
-/// Первая четверть
-pub const FIRST_QTR: u32 = (ARITHMETIC_PRECISION_LIMIT / 4) + 1;
-/// Половина
-pub const HALF: u32 = 2 * FIRST_QTR;
-/// Третья четверть
-pub const THIRD_QTR: u32 = 3 * FIRST_QTR;
-
-/// Писатель битовой магии - превращает байты в сжатые потоки ✨
-/// Использует параметры времени жизни для операций без копирования
-pub struct BitMagicWriter<'enchanted_output> {
-    mystical_output_scroll: &'enchanted_output mut Vec<u8>,
-    bit_accumulation_cauldron: u8,
-    bits_brewing_count: u8,
-    pending_mystical_bits: u32,
-}
-
-impl<'enchanted_output> BitMagicWriter<'enchanted_output> {
-    /// Конструктор (ассоциированная функция)
-    pub fn conjure_new(mystical_output_scroll: &'enchanted_output mut Vec<u8>) -> Self {
-        Self {
-            mystical_output_scroll,
-            bit_accumulation_cauldron: 0,
-            bits_brewing_count: 0,
-            pending_mystical_bits: 0,
-        }
-    }
-
-    /// Записывает один бит в выходной поток
-    pub fn write_bit(&mut self, bit: u8) {
-        self.bit_accumulation_cauldron = (self.bit_accumulation_cauldron << 1) | (bit & 1);
-        self.bits_brewing_count += 1;
-
-        if self.bits_brewing_count == 8 {
-            self.mystical_output_scroll
-                .push(self.bit_accumulation_cauldron);
-            self.bit_accumulation_cauldron = 0;
-            self.bits_brewing_count = 0;
-        }
-    }
-
-    /// Выводит бит и обрабатывает любые ожидающие следующие биты
-    pub fn output_bit(&mut self, bit: u8) {
-        self.write_bit(bit);
-
-        // Вывод ожидающих битов
-        for _ in 0..self.pending_mystical_bits {
-            self.write_bit(1 - bit);
-        }
-        self.pending_mystical_bits = 0;
-    }
-
-    /// Выводит бит и обрабатывает ожидающие биты
-    pub fn bit_plus_follow(&mut self, bit: u8) {
-        self.output_bit(bit);
-        for _ in 0..self.pending_mystical_bits {
-            self.output_bit(1 - bit);
-        }
-        self.pending_mystical_bits = 0;
-    }
-
-    /// Завершает сжатие и сбрасывает биты
-    pub fn complete_compression_ritual(mut self) {
-        // Финальные биты
-        self.pending_mystical_bits += 1;
-        if self.pending_mystical_bits > 0 {
-            self.bit_plus_follow(1);
-        }
-
-        // Дополнение последнего байта
-        if self.bits_brewing_count > 0 {
-            self.bit_accumulation_cauldron <<= 8 - self.bits_brewing_count;
-            self.mystical_output_scroll
-                .push(self.bit_accumulation_cauldron);
-        }
-    }
-
-    /// Основное заклинание арифметического кодирования 🎯
-    /// Кодирует символ, сужая интервал (изменяемая ссылка на self)
-    pub fn encode_mystical_symbol(
-        &mut self,
-        current_low: &mut u32,
-        current_high: &mut u32,
-        symbol_frequency_start: u32,
-        symbol_frequency_end: u32,
-        total_frequency_mass: u32,
-    ) {
-        let range = (*current_high as u64) - (*current_low as u64) + 1;
-
-        *current_high = (*current_low as u64
-            + (range * symbol_frequency_end as u64) / total_frequency_mass as u64
-            - 1) as u32;
-        *current_low = (*current_low as u64
-            + (range * symbol_frequency_start as u64) / total_frequency_mass as u64)
-            as u32;
-
-        self.normalize(current_low, current_high);
-    }
-
-    /// Нормализует интервал арифметического кодирования во время кодирования
-    pub fn normalize(&mut self, low: &mut u32, high: &mut u32) {
-        loop {
-            if *high < HALF {
-                self.bit_plus_follow(0);
-            } else if *low >= HALF {
-                self.bit_plus_follow(1);
-                *low -= HALF;
-                *high -= HALF;
-            } else if *low >= FIRST_QTR && *high < THIRD_QTR {
-                self.pending_mystical_bits += 1;
-                *low -= FIRST_QTR;
-                *high -= FIRST_QTR;
-            } else {
-                break;
-            }
-
-            *low = 2 * *low;
-            *high = 2 * *high + 1;
-        }
-    }
-}
-
-/// Читатель битовой магии - восстанавливает данные из сжатых потоков 🔮
-pub struct BitMagicReader {
-    compressed_mystical_scroll: Vec<u8>,
-    byte_pos: usize,
-    bit_pos: u8,
-    interval_position_tracker: u32,
-}
-
-impl BitMagicReader {
-    /// Конструктор (принимает владение Vec<u8>)
-    pub fn conjure_from_scroll(compressed_mystical_scroll: Vec<u8>) -> Self {
-        let mut mystical_reader = Self {
-            compressed_mystical_scroll,
-            byte_pos: 0,
-            bit_pos: 0,
-            interval_position_tracker: 0,
-        };
-
-        // Инициализация первыми 24 битами
-        for _ in 0..24 {
-            mystical_reader.interval_position_tracker = (mystical_reader.interval_position_tracker
-                << 1)
-                | (mystical_reader.read_bit() as u32);
-        }
-
-        mystical_reader
-    }
-
-    /// Читает один бит из потока
-    pub fn read_bit(&mut self) -> u8 {
-        if self.byte_pos >= self.compressed_mystical_scroll.len() {
-            return 0;
-        }
-
-        let bit = (self.compressed_mystical_scroll[self.byte_pos] >> (7 - self.bit_pos)) & 1;
-        self.bit_pos += 1;
-
-        if self.bit_pos == 8 {
-            self.bit_pos = 0;
-            self.byte_pos += 1;
-        }
-
-        bit
-    }
-
-    /// Декодирует целевое значение (возвращает вычисленное значение)
-    pub fn decode_mystical_target(
-        &self,
-        total_frequency_mass: u32,
-        current_low: u32,
-        current_high: u32,
-    ) -> u32 {
-        let range = (current_high as u64) - (current_low as u64) + 1;
-        (((self.interval_position_tracker as u64 - current_low as u64 + 1)
-            * total_frequency_mass as u64
-            - 1)
-            / range) as u32
-    }
-
-    /// Обновляет интервалы (изменяемый метод)
-    pub fn update_mystical_intervals(
-        &mut self,
-        current_low: &mut u32,
-        current_high: &mut u32,
-        symbol_frequency_start: u32,
-        symbol_frequency_end: u32,
-        total_frequency_mass: u32,
-    ) {
-        let range = (*current_high as u64) - (*current_low as u64) + 1;
-
-        *current_high = (*current_low as u64
-            + (range * symbol_frequency_end as u64) / total_frequency_mass as u64
-            - 1) as u32;
-        *current_low = (*current_low as u64
-            + (range * symbol_frequency_start as u64) / total_frequency_mass as u64)
-            as u32;
-
-        self.normalize(current_low, current_high);
-    }
-
-    /// Нормализует интервал при декодировании
-    pub fn normalize(&mut self, low: &mut u32, high: &mut u32) {
-        loop {
-            if *high < HALF {
-                // Ничего не делать
-            } else if *low >= HALF {
-                self.interval_position_tracker -= HALF;
-                *low -= HALF;
-                *high -= HALF;
-            } else if *low >= FIRST_QTR && *high < THIRD_QTR {
-                self.interval_position_tracker -= FIRST_QTR;
-                *low -= FIRST_QTR;
-                *high -= FIRST_QTR;
-            } else {
-                break;
-            }
-
-            *low = 2 * *low;
-            *high = 2 * *high + 1;
-            self.interval_position_tracker =
-                2 * self.interval_position_tracker + (self.read_bit() as u32);
-        }
-    }
-
-    /// Показывает внутреннее состояние (инспекция)
-    pub fn reveal_mystical_position(&self) -> u32 {
-        self.interval_position_tracker
-    }
-}
-
-/// Тесты битовых операций 🎯
-#[cfg(test)]
-mod mystical_bit_tests {
-    use super::*;
-
-    #[test]
-    fn test_bit_writer_basic_functionality() {
-        let mut output_scroll = Vec::new();
-        let writer = BitMagicWriter::conjure_new(&mut output_scroll);
-
-        writer.complete_compression_ritual();
-
-        assert!(!output_scroll.is_empty());
-    }
-
-    #[test]
-    fn test_bit_reader_initialization() {
-        let test_data = vec![0xFF, 0x00, 0xFF, 0x00];
-        let reader = BitMagicReader::conjure_from_scroll(test_data);
-
-        let position = reader.reveal_mystical_position();
-        assert!(position > 0); // Загрузка начальных битов при инициализации
-    }
-}
+//! Заклинания манипуляции битами 🧙‍♂️⚡
+//!
+//! Битовые операции для арифметического кодирования.
+//! Демонстрирует силу Rust в системном программировании.
+//!
+//! Арифметическое кодирование представляет данные как число в интервале [0, 1).
+//! Каждый символ сужает интервал пропорционально своей частоте.
+//!
+//! Возможности Rust:
+//! - Безопасные битовые операции
+//! - Константы времени компиляции
+//! - Абстракции нулевой стоимости
+//! - Типобезопасность
+
+use std::borrow::Cow;
+
+/// Максимальная точность арифметического кодирования (const время компиляции)
+pub const ARITHMETIC_PRECISION_LIMIT: u32 = (1 << 24) - 1;
+/// Первая четверть
+pub const FIRST_QTR: u32 = (ARITHMETIC_PRECISION_LIMIT / 4) + 1;
+/// Половина
+pub const HALF: u32 = 2 * FIRST_QTR;
+/// Третья четверть
+pub const THIRD_QTR: u32 = 3 * FIRST_QTR;
+
+/// Предел точности 32-битного кодера
+pub const ARITHMETIC_PRECISION_LIMIT_32: u32 = u32::MAX;
+
+/// Разрядность регистров интервала 📏
+///
+/// 24 бита — исходный кодер (форматы версий 0 и 1). 32 бита сужают
+/// ошибку округления интервалов и допускают общую массу частот до 2^30,
+/// что заметно на скошенных распределениях с большим алфавитом.
+///
+/// Переносы обрабатываются без отдельного счетчика переноса: если интервал
+/// застрял вокруг середины, откладываем биты (`pending`) и выводим их
+/// инвертированными после следующего определенного бита.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CoderPrecision {
+    /// 24-битные регистры (устаревшие архивы)
+    Bits24,
+    /// 32-битные регистры
+    #[default]
+    Bits32,
+}
+
+impl CoderPrecision {
+    /// Количество бит в регистрах интервала
+    pub const fn register_bits(self) -> u32 {
+        match self {
+            CoderPrecision::Bits24 => 24,
+            CoderPrecision::Bits32 => 32,
+        }
+    }
+
+    /// Верхняя граница интервала
+    pub const fn limit(self) -> u32 {
+        match self {
+            CoderPrecision::Bits24 => ARITHMETIC_PRECISION_LIMIT,
+            CoderPrecision::Bits32 => ARITHMETIC_PRECISION_LIMIT_32,
+        }
+    }
+
+    /// Первая четверть
+    pub const fn first_qtr(self) -> u32 {
+        self.limit() / 4 + 1
+    }
+
+    /// Половина
+    pub const fn half(self) -> u32 {
+        2 * self.first_qtr()
+    }
+
+    /// Третья четверть
+    pub const fn third_qtr(self) -> u32 {
+        3 * self.first_qtr()
+    }
+
+    /// Максимальная общая масса частот, при которой ни один символ не теряет интервал
+    pub const fn max_total_frequency(self) -> u64 {
+        self.first_qtr() as u64
+    }
+}
+
+/// Сужает интервал `[low, high]` до доли `[start, end)` из `total` 📐
+///
+/// Вся арифметика кодера — в фиксированной ширине: произведение
+/// `range × end` не больше `2^32 × 2^30` и считается в `u64`, а не в
+/// `usize`, поэтому поток одинаков на 32- и 64-битных платформах. Для
+/// интервала, прошедшего проверки кодера (`start < end ≤ total`,
+/// `total ≤ max_total_frequency`), результат умещается в `u32`; иные
+/// значения (поврежденный поток у декодера) усекаются явно и одинаково
+/// везде, а проверяемые пути декодера отвергают такой поток.
+fn narrow_interval(low: u32, high: u32, start: u32, end: u32, total: u32) -> (u32, u32) {
+    let range = (high as u64).wrapping_sub(low as u64).wrapping_add(1);
+    let total = total.max(1) as u64;
+    let narrowed_high = (low as u64)
+        .wrapping_add(range.wrapping_mul(end as u64) / total)
+        .wrapping_sub(1);
+    let narrowed_low = (low as u64).wrapping_add(range.wrapping_mul(start as u64) / total);
+    (narrowed_low as u32, narrowed_high as u32)
+}
+
+/// Писатель битовой магии - превращает байты в сжатые потоки ✨
+/// Использует параметры времени жизни для операций без копирования
+pub struct BitMagicWriter<'enchanted_output> {
+    mystical_output_scroll: &'enchanted_output mut Vec<u8>,
+    bit_accumulation_cauldron: u8,
+    bits_brewing_count: u8,
+    pending_mystical_bits: u32,
+    precision: CoderPrecision,
+}
+
+impl<'enchanted_output> BitMagicWriter<'enchanted_output> {
+    /// Конструктор (ассоциированная функция), 24-битные регистры
+    pub fn conjure_new(mystical_output_scroll: &'enchanted_output mut Vec<u8>) -> Self {
+        Self::conjure_with_precision(mystical_output_scroll, CoderPrecision::Bits24)
+    }
+
+    /// Конструктор с заданной разрядностью регистров
+    pub fn conjure_with_precision(
+        mystical_output_scroll: &'enchanted_output mut Vec<u8>,
+        precision: CoderPrecision,
+    ) -> Self {
+        Self {
+            mystical_output_scroll,
+            bit_accumulation_cauldron: 0,
+            bits_brewing_count: 0,
+            pending_mystical_bits: 0,
+            precision,
+        }
+    }
+
+    /// Записывает один бит в выходной поток
+    pub fn write_bit(&mut self, bit: u8) {
+        self.bit_accumulation_cauldron = (self.bit_accumulation_cauldron << 1) | (bit & 1);
+        self.bits_brewing_count += 1;
+
+        if self.bits_brewing_count == 8 {
+            self.mystical_output_scroll
+                .push(self.bit_accumulation_cauldron);
+            self.bit_accumulation_cauldron = 0;
+            self.bits_brewing_count = 0;
+        }
+    }
+
+    /// Выводит бит и обрабатывает любые ожидающие следующие биты
+    pub fn output_bit(&mut self, bit: u8) {
+        self.write_bit(bit);
+
+        // Вывод ожидающих битов
+        for _ in 0..self.pending_mystical_bits {
+            self.write_bit(1 - bit);
+        }
+        self.pending_mystical_bits = 0;
+    }
+
+    /// Выводит бит и обрабатывает ожидающие биты
+    pub fn bit_plus_follow(&mut self, bit: u8) {
+        self.output_bit(bit);
+        for _ in 0..self.pending_mystical_bits {
+            self.output_bit(1 - bit);
+        }
+        self.pending_mystical_bits = 0;
+    }
+
+    /// Завершает сжатие и сбрасывает биты
+    pub fn complete_compression_ritual(mut self) {
+        // Финальные биты
+        self.pending_mystical_bits += 1;
+        if self.pending_mystical_bits > 0 {
+            self.bit_plus_follow(1);
+        }
+
+        // Дополнение последнего байта
+        if self.bits_brewing_count > 0 {
+            self.bit_accumulation_cauldron <<= 8 - self.bits_brewing_count;
+            self.mystical_output_scroll
+                .push(self.bit_accumulation_cauldron);
+        }
+    }
+
+    /// Основное заклинание арифметического кодирования 🎯
+    /// Кодирует символ, сужая интервал (изменяемая ссылка на self)
+    pub fn encode_mystical_symbol(
+        &mut self,
+        current_low: &mut u32,
+        current_high: &mut u32,
+        symbol_frequency_start: u32,
+        symbol_frequency_end: u32,
+        total_frequency_mass: u32,
+    ) {
+        (*current_low, *current_high) = narrow_interval(
+            *current_low,
+            *current_high,
+            symbol_frequency_start,
+            symbol_frequency_end,
+            total_frequency_mass,
+        );
+        self.normalize(current_low, current_high);
+    }
+
+    /// Нормализует интервал арифметического кодирования во время кодирования
+    pub fn normalize(&mut self, low: &mut u32, high: &mut u32) {
+        let (first_qtr, half, third_qtr) = (
+            self.precision.first_qtr(),
+            self.precision.half(),
+            self.precision.third_qtr(),
+        );
+
+        loop {
+            if *high < half {
+                self.bit_plus_follow(0);
+            } else if *low >= half {
+                self.bit_plus_follow(1);
+                *low -= half;
+                *high -= half;
+            } else if *low >= first_qtr && *high < third_qtr {
+                self.pending_mystical_bits += 1;
+                *low -= first_qtr;
+                *high -= first_qtr;
+            } else {
+                break;
+            }
+
+            // Здесь high < half, поэтому удвоение не выходит за предел
+            *low *= 2;
+            *high = 2 * *high + 1;
+        }
+    }
+}
+
+/// Читатель битовой магии - восстанавливает данные из сжатых потоков 🔮
+///
+/// Поток хранится как `Cow`: читатель может владеть буфером
+/// или читать прямо из сериализованного архива без копирования.
+pub struct BitMagicReader<'scroll> {
+    compressed_mystical_scroll: Cow<'scroll, [u8]>,
+    byte_pos: usize,
+    bit_pos: u8,
+    interval_position_tracker: u32,
+    precision: CoderPrecision,
+}
+
+impl<'scroll> BitMagicReader<'scroll> {
+    /// Конструктор (принимает владение Vec<u8>), 24-битные регистры
+    pub fn conjure_from_scroll(compressed_mystical_scroll: Vec<u8>) -> Self {
+        Self::conjure_with_precision(compressed_mystical_scroll, CoderPrecision::Bits24)
+    }
+
+    /// Конструктор с заданной разрядностью регистров
+    pub fn conjure_with_precision(
+        compressed_mystical_scroll: Vec<u8>,
+        precision: CoderPrecision,
+    ) -> Self {
+        Self::conjure_from_cow(Cow::Owned(compressed_mystical_scroll), precision)
+    }
+
+    /// Конструктор, читающий заимствованный поток без копирования
+    pub fn conjure_borrowed(
+        compressed_mystical_scroll: &'scroll [u8],
+        precision: CoderPrecision,
+    ) -> Self {
+        Self::conjure_from_cow(Cow::Borrowed(compressed_mystical_scroll), precision)
+    }
+
+    /// Общий конструктор для владеющего и заимствованного потока
+    fn conjure_from_cow(
+        compressed_mystical_scroll: Cow<'scroll, [u8]>,
+        precision: CoderPrecision,
+    ) -> Self {
+        let mut mystical_reader = Self {
+            compressed_mystical_scroll,
+            byte_pos: 0,
+            bit_pos: 0,
+            interval_position_tracker: 0,
+            precision,
+        };
+
+        // Инициализация первыми битами (по разрядности регистров)
+        for _ in 0..precision.register_bits() {
+            mystical_reader.interval_position_tracker = (mystical_reader.interval_position_tracker
+                << 1)
+                | (mystical_reader.read_bit() as u32);
+        }
+
+        mystical_reader
+    }
+
+    /// Читает один бит из потока
+    pub fn read_bit(&mut self) -> u8 {
+        if self.byte_pos >= self.compressed_mystical_scroll.len() {
+            return 0;
+        }
+
+        let bit = (self.compressed_mystical_scroll[self.byte_pos] >> (7 - self.bit_pos)) & 1;
+        self.bit_pos += 1;
+
+        if self.bit_pos == 8 {
+            self.bit_pos = 0;
+            self.byte_pos += 1;
+        }
+
+        bit
+    }
+
+    /// Декодирует целевое значение (возвращает вычисленное значение)
+    pub fn decode_mystical_target(
+        &self,
+        total_frequency_mass: u32,
+        current_low: u32,
+        current_high: u32,
+    ) -> u32 {
+        // Позиция вне интервала (поврежденный поток) дает одинаковый на всех
+        // платформах мусор; его отвергает `try_decode_mystical_target`
+        let range = (current_high as u64).wrapping_sub(current_low as u64) + 1;
+        ((self.interval_position_tracker as u64)
+            .wrapping_sub(current_low as u64)
+            .wrapping_add(1)
+            .wrapping_mul(total_frequency_mass as u64)
+            .wrapping_sub(1)
+            / range) as u32
+    }
+
+    /// Проверяемый вариант [`decode_mystical_target`](Self::decode_mystical_target)
+    ///
+    /// Возвращает `None`, если позиция потока вышла за интервал или
+    /// целевое значение не попадает в `[0, total_frequency_mass)` —
+    /// признак поврежденного потока или таблицы частот.
+    pub fn try_decode_mystical_target(
+        &self,
+        total_frequency_mass: u32,
+        current_low: u32,
+        current_high: u32,
+    ) -> Option<u32> {
+        let position = self.interval_position_tracker;
+        if total_frequency_mass == 0 || position < current_low || position > current_high {
+            return None;
+        }
+
+        let target = self.decode_mystical_target(total_frequency_mass, current_low, current_high);
+        (target < total_frequency_mass).then_some(target)
+    }
+
+    /// Обновляет интервалы (изменяемый метод)
+    pub fn update_mystical_intervals(
+        &mut self,
+        current_low: &mut u32,
+        current_high: &mut u32,
+        symbol_frequency_start: u32,
+        symbol_frequency_end: u32,
+        total_frequency_mass: u32,
+    ) {
+        (*current_low, *current_high) = narrow_interval(
+            *current_low,
+            *current_high,
+            symbol_frequency_start,
+            symbol_frequency_end,
+            total_frequency_mass,
+        );
+        self.normalize(current_low, current_high);
+    }
+
+    /// Нормализует интервал при декодировании
+    pub fn normalize(&mut self, low: &mut u32, high: &mut u32) {
+        let (first_qtr, half, third_qtr) = (
+            self.precision.first_qtr(),
+            self.precision.half(),
+            self.precision.third_qtr(),
+        );
+
+        loop {
+            // Позиция поврежденного потока может выйти за интервал: ее
+            // арифметика явно циклическая, чтобы не паниковать
+            if *high < half {
+                // Ничего не делать
+            } else if *low >= half {
+                self.interval_position_tracker = self.interval_position_tracker.wrapping_sub(half);
+                *low -= half;
+                *high -= half;
+            } else if *low >= first_qtr && *high < third_qtr {
+                self.interval_position_tracker =
+                    self.interval_position_tracker.wrapping_sub(first_qtr);
+                *low -= first_qtr;
+                *high -= first_qtr;
+            } else {
+                break;
+            }
+
+            *low *= 2;
+            *high = 2 * *high + 1;
+            self.interval_position_tracker = self
+                .interval_position_tracker
+                .wrapping_mul(2)
+                .wrapping_add(self.read_bit() as u32);
+        }
+    }
+
+    /// Показывает внутреннее состояние (инспекция)
+    pub fn reveal_mystical_position(&self) -> u32 {
+        self.interval_position_tracker
+    }
+
+    /// Смещение байта, который читается сейчас
+    ///
+    /// Регистр позиции заполнен на разрядность вперед, поэтому смещение
+    /// опережает последний декодированный символ на 3–4 байта. После конца
+    /// потока равно его длине.
+    pub fn byte_position(&self) -> usize {
+        self.byte_pos
+    }
+}
+
+/// Тесты битовых операций 🎯
+#[cfg(test)]
+mod mystical_bit_tests {
+    use super::*;
+
+    #[test]
+    fn test_bit_writer_basic_functionality() {
+        let mut output_scroll = Vec::new();
+        let writer = BitMagicWriter::conjure_new(&mut output_scroll);
+
+        writer.complete_compression_ritual();
+
+        assert!(!output_scroll.is_empty());
+    }
+
+    #[test]
+    fn test_bit_reader_initialization() {
+        let test_data = vec![0xFF, 0x00, 0xFF, 0x00];
+        let reader = BitMagicReader::conjure_from_scroll(test_data);
+
+        let position = reader.reveal_mystical_position();
+        assert!(position > 0); // Загрузка начальных битов при инициализации
+    }
+
+    #[test]
+    fn test_precision_quarters() {
+        assert_eq!(CoderPrecision::Bits24.half(), HALF);
+        assert_eq!(CoderPrecision::Bits24.first_qtr(), FIRST_QTR);
+        assert_eq!(CoderPrecision::Bits32.first_qtr(), 1 << 30);
+        assert_eq!(CoderPrecision::Bits32.third_qtr(), 3 << 30);
+    }
+
+    #[test]
+    fn test_position_outside_interval_does_not_overflow() {
+        // Декодер снимает не тот символ, что указала позиция: позиция
+        // выходит за интервал, но арифметика не паникует и в отладке
+        for precision in [CoderPrecision::Bits24, CoderPrecision::Bits32] {
+            let mut reader = BitMagicReader::conjure_with_precision(vec![0xFF; 64], precision);
+            let (mut low, mut high) = (0u32, precision.limit());
+            for _ in 0..200 {
+                reader.decode_mystical_target(3, low, high);
+                reader.update_mystical_intervals(&mut low, &mut high, 0, 1, 3);
+            }
+            assert_eq!(reader.try_decode_mystical_target(3, low, high), None);
+        }
+    }
+
+    #[test]
+    fn test_skewed_distribution_roundtrip_in_both_precisions() {
+        // Сильно скошенное распределение: символ 0 почти всегда, символ 1 редко
+        let total = 1u32 << 20;
+        let table = [(0u32, 0u32, total - 1), (1, total - 1, total)];
+        let symbols: Vec<usize> = (0..5000).map(|i| usize::from(i % 997 == 0)).collect();
+
+        for precision in [CoderPrecision::Bits24, CoderPrecision::Bits32] {
+            let mut output = Vec::new();
+            let mut writer = BitMagicWriter::conjure_with_precision(&mut output, precision);
+            let (mut low, mut high) = (0u32, precision.limit());
+            for &symbol in &symbols {
+                let (_, start, end) = table[symbol];
+                writer.encode_mystical_symbol(&mut low, &mut high, start, end, total);
+            }
+            writer.complete_compression_ritual();
+
+            let mut reader = BitMagicReader::conjure_with_precision(output, precision);
+            let (mut low, mut high) = (0u32, precision.limit());
+            for &expected in &symbols {
+                let target = reader.decode_mystical_target(total, low, high);
+                let symbol = usize::from(target >= total - 1);
+                assert_eq!(symbol, expected);
+                let (_, start, end) = table[symbol];
+                reader.update_mystical_intervals(&mut low, &mut high, start, end, total);
+            }
+        }
+    }
+}
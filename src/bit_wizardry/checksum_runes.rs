@@ -0,0 +1,73 @@
+//! Руны контрольных сумм 🔏
+//!
+//! Детерминированное хеширование без внешних зависимостей.
+//! Используется для идентификаторов словарей и моделей.
+
+/// Начальное значение FNV-1a (64 бита)
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+/// Простое число FNV (64 бита)
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Потоковый хешер FNV-1a 🧮
+///
+/// Одинаковые байты дают одинаковый хеш на любой платформе.
+#[derive(Debug, Clone, Copy)]
+pub struct RuneHasher {
+    state: u64,
+}
+
+impl RuneHasher {
+    /// Создает хешер в начальном состоянии
+    pub fn conjure_new() -> Self {
+        Self {
+            state: FNV_OFFSET_BASIS,
+        }
+    }
+
+    /// Добавляет байты к хешу
+    pub fn absorb(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.state ^= byte as u64;
+            self.state = self.state.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    /// Возвращает итоговый хеш
+    pub fn finish(&self) -> u64 {
+        self.state
+    }
+}
+
+impl Default for RuneHasher {
+    fn default() -> Self {
+        Self::conjure_new()
+    }
+}
+
+/// Хеширует срез байтов за один вызов
+pub fn rune_hash(bytes: &[u8]) -> u64 {
+    let mut hasher = RuneHasher::conjure_new();
+    hasher.absorb(bytes);
+    hasher.finish()
+}
+
+/// Тесты контрольных сумм 🎯
+#[cfg(test)]
+mod checksum_rune_tests {
+    use super::*;
+
+    #[test]
+    fn test_known_fnv_vectors() {
+        assert_eq!(rune_hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(rune_hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn test_streaming_matches_one_shot() {
+        let mut hasher = RuneHasher::conjure_new();
+        hasher.absorb(b"hello ");
+        hasher.absorb(b"world");
+
+        assert_eq!(hasher.finish(), rune_hash(b"hello world"));
+    }
+}
@@ -0,0 +1,286 @@
+//! Энтропийный кодер 🎲
+//!
+//! Арифметический кодер без собственной модели: на каждом шаге вызывающий
+//! передает распределение сам. Подходит для предсказателей любого рода —
+//! PPM, контекстного смешивания, вероятностей нейросети, — и для
+//! сэмплирования: декодирование случайных байтов дает выборку из модели.
+//!
+//! Распределение задается накопительными частотами: символу `i` достается
+//! полуинтервал `[cumulative[i], cumulative[i + 1])`, а `cumulative[0] = 0`.
+//! Общая масса (последний элемент) не должна превышать
+//! [`CoderPrecision::max_total_frequency`], и у каждого кодируемого символа
+//! частота должна быть ненулевой.
+//!
+//! ```
+//! use arithmetic_compression_wizard::bit_wizardry::{
+//!     ArithmeticDecoder, ArithmeticEncoder, CoderPrecision,
+//! };
+//!
+//! // Три символа с вероятностями 1/2, 1/4, 1/4
+//! let cumulative = [0, 2, 3, 4];
+//! let message = [0usize, 2, 1, 0, 0];
+//!
+//! let mut stream = Vec::new();
+//! let mut encoder = ArithmeticEncoder::new(&mut stream, CoderPrecision::Bits32);
+//! for &symbol in &message {
+//!     encoder.encode_symbol(&cumulative, symbol).unwrap();
+//! }
+//! encoder.finish();
+//!
+//! let mut decoder = ArithmeticDecoder::new(&stream, CoderPrecision::Bits32);
+//! let decoded: Vec<usize> = (0..message.len())
+//!     .map(|_| decoder.decode_symbol(&cumulative).unwrap())
+//!     .collect();
+//! assert_eq!(decoded, message);
+//! ```
+
+use super::bit_manipulation_spells::{BitMagicReader, BitMagicWriter, CoderPrecision};
+use crate::error::{WizardError, WizardResult};
+
+/// Проверяет интервал символа `[cumulative_low, cumulative_high)` из массы `total`
+fn check_interval(
+    cumulative_low: u32,
+    cumulative_high: u32,
+    total: u32,
+    precision: CoderPrecision,
+) -> WizardResult<()> {
+    check_total(total, precision)?;
+    if cumulative_low >= cumulative_high {
+        return Err(WizardError::InvalidDistribution("пустой интервал символа"));
+    }
+    if cumulative_high > total {
+        return Err(WizardError::InvalidDistribution(
+            "интервал символа выходит за общую массу",
+        ));
+    }
+    Ok(())
+}
+
+/// Проверяет общую массу распределения
+fn check_total(total: u32, precision: CoderPrecision) -> WizardResult<()> {
+    if total == 0 {
+        return Err(WizardError::InvalidDistribution("нулевая общая масса"));
+    }
+    if total as u64 > precision.max_total_frequency() {
+        return Err(WizardError::InvalidDistribution(
+            "общая масса превышает точность кодера",
+        ));
+    }
+    Ok(())
+}
+
+/// Общая масса накопительного распределения
+fn cumulative_total(cumulative: &[u32]) -> WizardResult<u32> {
+    match cumulative {
+        [0, .., total] => Ok(*total),
+        [_, _, ..] => Err(WizardError::InvalidDistribution(
+            "накопительные частоты должны начинаться с нуля",
+        )),
+        _ => Err(WizardError::InvalidDistribution(
+            "распределение без символов",
+        )),
+    }
+}
+
+/// Арифметический кодер с внешней моделью ✍️
+pub struct ArithmeticEncoder<'out> {
+    writer: BitMagicWriter<'out>,
+    interval_low: u32,
+    interval_high: u32,
+    precision: CoderPrecision,
+}
+
+impl<'out> ArithmeticEncoder<'out> {
+    /// Кодер, дописывающий поток в `output`
+    pub fn new(output: &'out mut Vec<u8>, precision: CoderPrecision) -> Self {
+        Self {
+            writer: BitMagicWriter::conjure_with_precision(output, precision),
+            interval_low: 0,
+            interval_high: precision.limit(),
+            precision,
+        }
+    }
+
+    /// Разрядность регистров
+    pub fn precision(&self) -> CoderPrecision {
+        self.precision
+    }
+
+    /// Кодирует символ с интервалом `[cumulative_low, cumulative_high)` из массы `total`
+    pub fn encode(
+        &mut self,
+        cumulative_low: u32,
+        cumulative_high: u32,
+        total: u32,
+    ) -> WizardResult<()> {
+        check_interval(cumulative_low, cumulative_high, total, self.precision)?;
+        self.writer.encode_mystical_symbol(
+            &mut self.interval_low,
+            &mut self.interval_high,
+            cumulative_low,
+            cumulative_high,
+            total,
+        );
+        Ok(())
+    }
+
+    /// Кодирует символ `symbol` по накопительному распределению
+    pub fn encode_symbol(&mut self, cumulative: &[u32], symbol: usize) -> WizardResult<()> {
+        let total = cumulative_total(cumulative)?;
+        if symbol + 1 >= cumulative.len() {
+            return Err(WizardError::InvalidDistribution("символ вне распределения"));
+        }
+        self.encode(cumulative[symbol], cumulative[symbol + 1], total)
+    }
+
+    /// Завершает поток
+    pub fn finish(self) {
+        self.writer.complete_compression_ritual();
+    }
+}
+
+/// Арифметический декодер с внешней моделью 🔍
+///
+/// Распределения должны совпадать с теми, что получал кодер на тех же шагах.
+/// После конца потока декодер читает нули, поэтому число символов
+/// хранится отдельно.
+pub struct ArithmeticDecoder<'scroll> {
+    reader: BitMagicReader<'scroll>,
+    interval_low: u32,
+    interval_high: u32,
+    precision: CoderPrecision,
+}
+
+impl<'scroll> ArithmeticDecoder<'scroll> {
+    /// Декодер, читающий поток без копирования
+    pub fn new(stream: &'scroll [u8], precision: CoderPrecision) -> Self {
+        Self {
+            reader: BitMagicReader::conjure_borrowed(stream, precision),
+            interval_low: 0,
+            interval_high: precision.limit(),
+            precision,
+        }
+    }
+
+    /// Разрядность регистров
+    pub fn precision(&self) -> CoderPrecision {
+        self.precision
+    }
+
+    /// Смещение в потоке, до которого дочитал декодер
+    pub fn stream_position(&self) -> usize {
+        self.reader.byte_position()
+    }
+
+    /// Позиция следующего символа в `[0, total)`
+    ///
+    /// За вызовом должен следовать [`consume`](Self::consume) с интервалом
+    /// символа, в который попала позиция.
+    pub fn decode_target(&self, total: u32) -> WizardResult<u32> {
+        check_total(total, self.precision)?;
+        self.reader
+            .try_decode_mystical_target(total, self.interval_low, self.interval_high)
+            .ok_or(WizardError::CorruptPayload)
+    }
+
+    /// Снимает с потока символ с интервалом `[cumulative_low, cumulative_high)`
+    pub fn consume(
+        &mut self,
+        cumulative_low: u32,
+        cumulative_high: u32,
+        total: u32,
+    ) -> WizardResult<()> {
+        check_interval(cumulative_low, cumulative_high, total, self.precision)?;
+        self.reader.update_mystical_intervals(
+            &mut self.interval_low,
+            &mut self.interval_high,
+            cumulative_low,
+            cumulative_high,
+            total,
+        );
+        Ok(())
+    }
+
+    /// Декодирует символ по накопительному распределению
+    pub fn decode_symbol(&mut self, cumulative: &[u32]) -> WizardResult<usize> {
+        let total = cumulative_total(cumulative)?;
+        let target = self.decode_target(total)?;
+
+        // Последний символ, интервал которого начинается не позже позиции
+        let symbol = cumulative
+            .partition_point(|&start| start <= target)
+            .saturating_sub(1);
+        match cumulative.get(symbol + 1) {
+            Some(&end) if cumulative[symbol] <= target && target < end => {
+                self.consume(cumulative[symbol], end, total)?;
+                Ok(symbol)
+            }
+            _ => Err(WizardError::InvalidDistribution(
+                "накопительные частоты не возрастают",
+            )),
+        }
+    }
+}
+
+/// Тесты энтропийного кодера 🎯
+#[cfg(test)]
+mod entropy_coder_tests {
+    use super::*;
+
+    #[test]
+    fn test_adaptive_model_roundtrip() {
+        // Модель меняется на каждом шаге: так работают PPM и нейросети
+        let message: Vec<usize> = (0..2000).map(|i| (i * i + i / 7) % 5).collect();
+        let distribution_at = |counts: &[u32; 5]| {
+            let mut cumulative = vec![0u32];
+            for &count in counts {
+                cumulative.push(cumulative.last().unwrap() + count);
+            }
+            cumulative
+        };
+
+        for precision in [CoderPrecision::Bits24, CoderPrecision::Bits32] {
+            let mut stream = Vec::new();
+            let mut encoder = ArithmeticEncoder::new(&mut stream, precision);
+            let mut counts = [1u32; 5];
+            for &symbol in &message {
+                encoder
+                    .encode_symbol(&distribution_at(&counts), symbol)
+                    .unwrap();
+                counts[symbol] += 1;
+            }
+            encoder.finish();
+
+            let mut decoder = ArithmeticDecoder::new(&stream, precision);
+            let mut counts = [1u32; 5];
+            for &expected in &message {
+                let symbol = decoder.decode_symbol(&distribution_at(&counts)).unwrap();
+                assert_eq!(symbol, expected);
+                counts[symbol] += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_distributions_are_rejected() {
+        let mut stream = Vec::new();
+        let mut encoder = ArithmeticEncoder::new(&mut stream, CoderPrecision::Bits32);
+
+        assert!(encoder.encode_symbol(&[], 0).is_err());
+        assert!(encoder.encode_symbol(&[1, 2], 0).is_err());
+        assert!(encoder.encode_symbol(&[0, 2, 2], 1).is_err());
+        assert!(encoder.encode_symbol(&[0, 2, 4], 2).is_err());
+        assert!(encoder.encode(0, 1, 0).is_err());
+        assert_eq!(
+            encoder.encode(0, 1, u32::MAX),
+            Err(WizardError::InvalidDistribution(
+                "общая масса превышает точность кодера"
+            ))
+        );
+
+        let decoder = ArithmeticDecoder::new(&[], CoderPrecision::Bits24);
+        assert!(decoder.decode_target(1 << 23).is_err());
+        let mut decoder = ArithmeticDecoder::new(&[0x80], CoderPrecision::Bits32);
+        assert!(decoder.decode_symbol(&[0, 5, 3]).is_err());
+    }
+}
@@ -2,11 +2,22 @@
 //! Низкоуровневые операции для арифметического кодирования
 
 pub mod bit_manipulation_spells;
+pub mod checksum_runes;
+pub mod entropy_coder;
 
 // Экспорт основных типов и констант
 
 pub use bit_manipulation_spells::{
     BitMagicReader,             // Читатель битовых потоков
     BitMagicWriter,             // Писатель битовых потоков
+    CoderPrecision,             // Разрядность регистров кодера
     ARITHMETIC_PRECISION_LIMIT, // Предел точности арифметического кодирования
 };
+pub use checksum_runes::{
+    rune_hash,  // Хеш среза байтов
+    RuneHasher, // Потоковый хешер
+};
+pub use entropy_coder::{
+    ArithmeticDecoder, // Декодер с внешней моделью
+    ArithmeticEncoder, // Кодер с внешней моделью
+};
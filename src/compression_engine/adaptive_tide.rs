@@ -0,0 +1,382 @@
+//! Приливная модель: адаптивные частоты со старением 🌊
+//!
+//! Статическая таблица частот описывает файл целиком: если распределение
+//! меняется по ходу (исходный код, за которым идут base64-блоки), коды
+//! подходят обеим частям плохо. Адаптивная модель начинает с единичных
+//! частот всего алфавита и после каждого символа прибавляет ему
+//! `FREQUENCY_STEP`; каждые `half_life` символов все частоты делятся
+//! пополам. Так вес прошлого убывает экспоненциально, и модель
+//! переучивается за несколько периодов полураспада.
+//!
+//! Кодер и декодер повторяют одни и те же обновления, поэтому таблица
+//! частот в архив не пишется. Период хранится в заголовке
+//! (см. [`ModelKind::Adaptive`]).
+//!
+//! Тело кадра: `[словарь][фразы][число символов u64][длина u32][битовый поток]`.
+
+use super::compression_conjurer::{alphabet_size, symbolize_manuscript};
+use super::engine_covenant::{EngineConfig, Frame, DEADLINE_CHECK_SYMBOLS};
+use crate::bit_wizardry::bit_manipulation_spells::CoderPrecision;
+use crate::bit_wizardry::entropy_coder::{ArithmeticDecoder, ArithmeticEncoder};
+use crate::decompression_oracle::decode_wards::{validate_phrase_codex, DecodeOptions};
+use crate::decompression_oracle::decompression_sage::SymbolScribe;
+use crate::error::{WizardError, WizardResult};
+use crate::format::artifact_scroll::{
+    check_frame_layout, read_coded_stream, read_grimoire_sections, write_coded_stream,
+    write_grimoire_sections, ScrollCursor,
+};
+use crate::format::container_runes::{ArchiveHeader, EngineKind, ModelKind};
+use crate::telemetry::{record_compression, Stopwatch};
+
+/// Период полураспада по умолчанию (в символах)
+pub const DEFAULT_HALF_LIFE: u32 = 4096;
+
+/// Прибавка к частоте встреченного символа
+const FREQUENCY_STEP: u32 = 32;
+
+/// Адаптивная модель нулевого порядка со старением 🌊
+///
+/// Накопительные частоты хранятся в дереве Фенвика: поиск интервала
+/// и обновление стоят `O(log n)` даже для алфавита со словарем.
+#[derive(Debug, Clone)]
+pub(crate) struct AgingModel {
+    frequencies: Vec<u32>,
+    fenwick: Vec<u32>,
+    total: u32,
+    half_life: u32,
+    since_aging: u32,
+    max_total: u32,
+}
+
+impl AgingModel {
+    /// Модель с единичными частотами `alphabet` символов
+    ///
+    /// Алфавит должен оставлять место для прибавок: не больше
+    /// половины допустимой массы кодера.
+    pub(crate) fn new(
+        alphabet: usize,
+        half_life: u32,
+        precision: CoderPrecision,
+    ) -> WizardResult<Self> {
+        Self::with_frequencies(vec![1; alphabet], half_life, precision)
+    }
+
+    /// Модель с заданными начальными частотами (априорным распределением)
+    ///
+    /// Частоты должны быть ненулевыми, а их сумма — не больше половины
+    /// допустимой массы кодера.
+    pub(crate) fn with_frequencies(
+        frequencies: Vec<u32>,
+        half_life: u32,
+        precision: CoderPrecision,
+    ) -> WizardResult<Self> {
+        let max_total = precision.max_total_frequency();
+        let total: u64 = frequencies.iter().map(|&frequency| frequency as u64).sum();
+        if frequencies.is_empty() || frequencies.contains(&0) || total > max_total / 2 {
+            return Err(WizardError::InvalidDistribution(
+                "алфавит не умещается в точность кодера",
+            ));
+        }
+        let mut model = Self {
+            fenwick: vec![0; frequencies.len() + 1],
+            frequencies,
+            total: total as u32,
+            half_life: half_life.max(1),
+            since_aging: 0,
+            max_total: max_total as u32,
+        };
+        model.rebuild_fenwick();
+        Ok(model)
+    }
+
+    /// Общая масса частот
+    pub(crate) fn total(&self) -> u32 {
+        self.total
+    }
+
+    /// Интервал `[low, high)` символа
+    pub(crate) fn interval(&self, symbol: usize) -> (u32, u32) {
+        let low = self.prefix_sum(symbol);
+        (low, low + self.frequencies[symbol])
+    }
+
+    /// Символ, в интервал которого попадает `target < total`
+    pub(crate) fn symbol_at(&self, target: u32) -> usize {
+        let mut position = 0;
+        let mut remaining = target;
+        let mut step = (self.frequencies.len() + 1).next_power_of_two() / 2;
+        while step > 0 {
+            let next = position + step;
+            if next < self.fenwick.len() && self.fenwick[next] <= remaining {
+                position = next;
+                remaining -= self.fenwick[next];
+            }
+            step /= 2;
+        }
+        position
+    }
+
+    /// Учитывает закодированный символ и при необходимости старит частоты
+    pub(crate) fn observe(&mut self, symbol: usize) {
+        self.frequencies[symbol] += FREQUENCY_STEP;
+        self.total += FREQUENCY_STEP;
+        let mut node = symbol + 1;
+        while node < self.fenwick.len() {
+            self.fenwick[node] += FREQUENCY_STEP;
+            node += node & node.wrapping_neg();
+        }
+
+        self.since_aging += 1;
+        if self.since_aging >= self.half_life || self.total > self.max_total - FREQUENCY_STEP {
+            self.age();
+        }
+    }
+
+    /// Делит все частоты пополам (частоты остаются ненулевыми)
+    fn age(&mut self) {
+        for frequency in &mut self.frequencies {
+            *frequency = frequency.div_ceil(2);
+        }
+        self.total = self.frequencies.iter().sum();
+        self.since_aging = 0;
+        self.rebuild_fenwick();
+    }
+
+    /// Сумма частот символов `0..end`
+    fn prefix_sum(&self, end: usize) -> u32 {
+        let mut sum = 0;
+        let mut node = end;
+        while node > 0 {
+            sum += self.fenwick[node];
+            node &= node - 1;
+        }
+        sum
+    }
+
+    /// Строит дерево Фенвика по частотам за `O(n)`
+    fn rebuild_fenwick(&mut self) {
+        self.fenwick[0] = 0;
+        self.fenwick[1..].copy_from_slice(&self.frequencies);
+        for node in 1..self.fenwick.len() {
+            let parent = node + (node & node.wrapping_neg());
+            if parent < self.fenwick.len() {
+                self.fenwick[parent] += self.fenwick[node];
+            }
+        }
+    }
+}
+
+/// Сжимает данные в кадр арифметического движка с адаптивной моделью
+///
+/// Словарь ищется так же, как для статической модели.
+pub(crate) fn try_weave_adaptive_frame(
+    data: &[u8],
+    half_life: u32,
+    config: &EngineConfig,
+) -> WizardResult<Frame> {
+    let half_life = half_life.max(1);
+    let stopwatch = Stopwatch::start();
+    let (symbols, word_grimoire, phrase_codex) = symbolize_manuscript(data, config);
+    config.check_deadline()?;
+    let alphabet = alphabet_size(word_grimoire.len()) + phrase_codex.len();
+
+    let precision = CoderPrecision::default();
+    let mut model = AgingModel::new(alphabet, half_life, precision)?;
+    let mut bit_stream = Vec::new();
+    let mut encoder = ArithmeticEncoder::new(&mut bit_stream, precision);
+    for (position, &symbol) in symbols.iter().enumerate() {
+        if position % DEADLINE_CHECK_SYMBOLS == DEADLINE_CHECK_SYMBOLS - 1 {
+            config.check_deadline()?;
+        }
+        let (low, high) = model.interval(symbol as usize);
+        encoder.encode(low, high, model.total())?;
+        model.observe(symbol as usize);
+    }
+    encoder.finish();
+    record_compression(
+        "adaptive",
+        stopwatch,
+        data.len(),
+        &symbols,
+        bit_stream.len(),
+    );
+
+    let mut body = Vec::new();
+    write_grimoire_sections(&mut body, &word_grimoire, &phrase_codex);
+    write_coded_stream(&mut body, symbols.len() as u64, &bit_stream);
+
+    Ok(Frame {
+        header: ArchiveHeader::current(ModelKind::Adaptive { half_life }, None),
+        body,
+    })
+}
+
+/// Проверяемая распаковка тела с адаптивной моделью
+pub(crate) fn try_unweave_adaptive_body(
+    header: &ArchiveHeader,
+    body: &[u8],
+    options: &DecodeOptions,
+) -> WizardResult<Vec<u8>> {
+    try_unweave_adaptive_prefix(header, body, usize::MAX, options)
+}
+
+/// Распаковка первых `limit` байтов тела с адаптивной моделью
+///
+/// Модель обновляется по ходу декодирования, поэтому остановка после
+/// нужного числа байтов не требует остатка потока.
+pub(crate) fn try_unweave_adaptive_prefix(
+    header: &ArchiveHeader,
+    body: &[u8],
+    limit: usize,
+    options: &DecodeOptions,
+) -> WizardResult<Vec<u8>> {
+    check_frame_layout(header, EngineKind::Arithmetic)?;
+    let ModelKind::Adaptive { half_life } = header.model_kind else {
+        return Err(WizardError::UnsupportedModel(header.model_kind.to_byte()));
+    };
+    if header.alphabet_map.is_some() {
+        return Err(WizardError::CorruptFrequencyTable(
+            "атлас алфавита не применяется к адаптивной модели",
+        ));
+    }
+
+    let mut cursor = ScrollCursor::new(body, 0);
+    let (word_grimoire, phrase_codex) = read_grimoire_sections(&mut cursor, header, options)?;
+    validate_phrase_codex(&phrase_codex, word_grimoire.len())?;
+    let (symbol_count, bit_stream) = read_coded_stream(&mut cursor)?;
+
+    // Каждый символ дает хотя бы один байт результата
+    options.reserve_output(symbol_count.min(limit as u64))?;
+
+    let alphabet = alphabet_size(word_grimoire.len()) + phrase_codex.len();
+    options.check_table_entries(alphabet)?;
+    let precision = header.coder_precision();
+    let mut model = AgingModel::new(alphabet, half_life, precision)
+        .map_err(|_| WizardError::CorruptFrequencyTable("алфавит больше точности кодера"))?;
+
+    let mut decoder = ArithmeticDecoder::new(bit_stream, precision);
+    let mut scribe = SymbolScribe::new(&word_grimoire, &phrase_codex, limit, options);
+    scribe.reserve(symbol_count.min(bit_stream.len() as u64 * 8));
+    for _ in 0..symbol_count {
+        let target = decoder.decode_target(model.total())?;
+        let symbol = model.symbol_at(target);
+        let (low, high) = model.interval(symbol);
+        decoder.consume(low, high, model.total())?;
+        model.observe(symbol);
+        if !scribe.write(symbol as u32)? {
+            break;
+        }
+    }
+
+    Ok(scribe.finish())
+}
+
+/// Тесты приливной модели 🎯
+#[cfg(test)]
+mod adaptive_tide_tests {
+    use super::*;
+    use crate::simple_api::{compress_data, compress_data_adaptive, decompress_data};
+
+    /// Исходный код, за которым идет длинный base64-блок
+    fn code_then_base64() -> Vec<u8> {
+        let mut data = Vec::new();
+        for line in 0..300 {
+            data.extend_from_slice(
+                format!(
+                    "    let value_{} = compute(input, {});\n",
+                    line % 17,
+                    line % 5
+                )
+                .as_bytes(),
+            );
+        }
+        const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let mut state = 0x2545_F491u32;
+        for position in 0..12_000 {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            data.push(BASE64[(state % 64) as usize]);
+            if position % 76 == 75 {
+                data.push(b'\n');
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn test_model_intervals_follow_observations() {
+        let mut model = AgingModel::new(5, 2, CoderPrecision::Bits32).unwrap();
+        assert_eq!(model.total(), 5);
+        assert_eq!(model.interval(3), (3, 4));
+
+        model.observe(3);
+        assert_eq!(model.interval(3), (3, 4 + FREQUENCY_STEP));
+        assert_eq!(model.symbol_at(3 + FREQUENCY_STEP), 3);
+        assert_eq!(model.symbol_at(4 + FREQUENCY_STEP), 4);
+
+        // Второй символ завершает период: частоты делятся пополам
+        model.observe(0);
+        assert_eq!(model.frequencies, vec![17, 1, 1, 17, 1]);
+        for target in 0..model.total() {
+            let symbol = model.symbol_at(target);
+            let (low, high) = model.interval(symbol);
+            assert!(low <= target && target < high);
+        }
+    }
+
+    #[test]
+    fn test_shifting_distribution_beats_static_model() {
+        let data = code_then_base64();
+        let adaptive = compress_data_adaptive(&data, 1024);
+        let static_model = compress_data(&data);
+        assert_eq!(decompress_data(&adaptive), data);
+        assert!(
+            adaptive.len() < static_model.len(),
+            "{} против {}",
+            adaptive.len(),
+            static_model.len()
+        );
+
+        for (data, half_life) in [(&b""[..], 1), (b"a", 0), (b"abcabcabc", 1), (b"x", 7)] {
+            let archive = compress_data_adaptive(data, half_life);
+            assert_eq!(decompress_data(&archive), data);
+        }
+    }
+
+    #[test]
+    fn test_damaged_adaptive_archives_rejected() {
+        let archive = compress_data_adaptive(code_then_base64(), DEFAULT_HALF_LIFE);
+        let (header, body_offset) = ArchiveHeader::parse(&archive).unwrap();
+        assert_eq!(
+            header.model_kind,
+            ModelKind::Adaptive {
+                half_life: DEFAULT_HALF_LIFE
+            }
+        );
+
+        let body = &archive[body_offset..];
+        assert!(try_unweave_adaptive_body(
+            &header,
+            &body[..body.len() / 2],
+            &DecodeOptions::default()
+        )
+        .is_err());
+        assert!(try_unweave_adaptive_body(
+            &header,
+            body,
+            &DecodeOptions::default().with_max_output_bytes(100)
+        )
+        .is_err());
+
+        let static_header = ArchiveHeader::current(ModelKind::StaticOrder0, None);
+        assert_eq!(
+            try_unweave_adaptive_body(&static_header, body, &DecodeOptions::default()),
+            Err(WizardError::UnsupportedModel(0))
+        );
+        assert_eq!(
+            crate::format::parse_artifact(&archive).map(|_| ()),
+            Err(WizardError::UnsupportedModel(2))
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! Кодовые точки UTF-8 как символы 🈶
+//!
+//! Кириллица занимает в UTF-8 два байта, иероглифы — три: байтовая модель
+//! видит вместо одного символа несколько слабо связанных байтов, и
+//! ведущие байты (`0xD0`, `0xD1`, `0xE4`...) съедают долю каждого знака.
+//! Символизатор разбирает вход на кодовые точки, и модель нулевого
+//! порядка видит знак целиком.
+//!
+//! Символы: `0–0x10FFFF` — кодовая точка, `0x110000 + байт` — байт
+//! недопустимой последовательности UTF-8 как есть. Модель пустая: любой
+//! вход восстанавливается из одних символов.
+
+use super::engine_covenant::EngineConfig;
+use super::symbolizer::{Symbolization, Symbolizer};
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::error::{WizardError, WizardResult};
+
+/// Первый символ недопустимых байтов: сразу за последней кодовой точкой
+pub const INVALID_BYTE_SYMBOL_BASE: u32 = 0x11_0000;
+
+/// Символизатор кодовых точек UTF-8 🈶
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CodePointSymbolizer;
+
+impl Symbolizer for CodePointSymbolizer {
+    fn name(&self) -> &'static str {
+        "utf8"
+    }
+
+    fn symbolize(&self, data: &[u8], _config: &EngineConfig) -> Symbolization {
+        let mut symbols = Vec::with_capacity(data.len());
+        for chunk in data.utf8_chunks() {
+            symbols.extend(chunk.valid().chars().map(u32::from));
+            symbols.extend(
+                chunk
+                    .invalid()
+                    .iter()
+                    .map(|&byte| INVALID_BYTE_SYMBOL_BASE + byte as u32),
+            );
+        }
+        Symbolization {
+            symbols,
+            model: Vec::new(),
+        }
+    }
+
+    fn try_reconstruct(
+        &self,
+        symbols: &[u32],
+        model: &[u8],
+        options: &DecodeOptions,
+    ) -> WizardResult<Vec<u8>> {
+        if !model.is_empty() {
+            return Err(WizardError::CorruptPayload);
+        }
+        let restored_len = symbols.iter().try_fold(0u64, |restored_len, &symbol| {
+            Ok::<_, WizardError>(restored_len + encoded_len(symbol)? as u64)
+        })?;
+        options.reserve_output(restored_len)?;
+
+        let mut restored = Vec::with_capacity(restored_len as usize);
+        let mut buffer = [0u8; 4];
+        for &symbol in symbols {
+            match char::from_u32(symbol) {
+                Some(glyph) => {
+                    restored.extend_from_slice(glyph.encode_utf8(&mut buffer).as_bytes())
+                }
+                None => restored.push((symbol - INVALID_BYTE_SYMBOL_BASE) as u8),
+            }
+        }
+        Ok(restored)
+    }
+}
+
+/// Длина символа в байтах; суррогаты и символы за пределом — порча
+fn encoded_len(symbol: u32) -> WizardResult<usize> {
+    match char::from_u32(symbol) {
+        Some(glyph) => Ok(glyph.len_utf8()),
+        None if (INVALID_BYTE_SYMBOL_BASE..INVALID_BYTE_SYMBOL_BASE + 256).contains(&symbol) => {
+            Ok(1)
+        }
+        None => Err(WizardError::CorruptPayload),
+    }
+}
+
+/// Тесты кодовых точек 🎯
+#[cfg(test)]
+mod code_point_glyphs_tests {
+    use super::*;
+    use crate::compression_engine::engine_covenant::StructuredMode;
+    use crate::simple_api::{compress_data, compress_structured, decompress_data};
+
+    #[test]
+    fn test_code_points_beat_bytes_on_cyrillic_and_cjk() {
+        for text in [
+            "Съешь же ещё этих мягких французских булок, да выпей чаю. ".repeat(40),
+            "天地玄黄宇宙洪荒日月盈昃辰宿列张寒来暑往秋收冬藏闰余成岁律吕调阳".repeat(30),
+        ] {
+            let archive = compress_structured(&text, StructuredMode::Utf8);
+            assert!(archive.len() < compress_data(&text).len());
+            assert_eq!(decompress_data(&archive), text.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_invalid_bytes_survive() {
+        let config = EngineConfig::default();
+        for data in [
+            &b""[..],
+            b"plain ascii",
+            b"\xFF\xFE mixed \xD0",
+            "ёж\u{10FFFF}".as_bytes(),
+            b"\xED\xA0\x80 surrogate",
+            b"\xF0\x9F\x98",
+        ] {
+            let Symbolization { symbols, model } = CodePointSymbolizer.symbolize(data, &config);
+            assert!(symbols.len() <= data.len());
+            let restored =
+                CodePointSymbolizer.try_reconstruct(&symbols, &model, &DecodeOptions::default());
+            assert_eq!(restored.unwrap(), data);
+            let archive = compress_structured(data, StructuredMode::Utf8);
+            assert_eq!(decompress_data(&archive), data);
+        }
+    }
+
+    #[test]
+    fn test_damaged_code_points_rejected() {
+        let options = DecodeOptions::default();
+        for symbol in [0xD800, INVALID_BYTE_SYMBOL_BASE + 256, u32::MAX] {
+            assert_eq!(
+                CodePointSymbolizer.try_reconstruct(&[symbol], b"", &options),
+                Err(WizardError::CorruptPayload)
+            );
+        }
+        assert_eq!(
+            CodePointSymbolizer.try_reconstruct(&[65], b"x", &options),
+            Err(WizardError::CorruptPayload)
+        );
+        assert!(matches!(
+            CodePointSymbolizer.try_reconstruct(
+                &[0x4E00; 10],
+                b"",
+                &options.with_max_output_bytes(29)
+            ),
+            Err(WizardError::LimitExceeded { .. })
+        ));
+    }
+}
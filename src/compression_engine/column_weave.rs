@@ -0,0 +1,429 @@
+//! Столбцовое плетение 🧮
+//!
+//! Данные с разделителями (CSV, TSV, выгрузки таблиц) транспонируются:
+//! значения каждого столбца идут подряд и кодируются своим арифметическим
+//! потоком со своей таблицей частот. Соседние значения столбца похожи
+//! (даты, цены, категории), поэтому модель столбца много точнее общей.
+//!
+//! Разбор не проверяет синтаксис: строки делятся по `\n`, поля — по
+//! разделителю, оба вне кавычек. Вход с плавающим числом полей и вовсе
+//! не табличный вход восстанавливаются без потерь, просто хуже сжимаются.
+//!
+//! Поток столбца: значение — байты и [`VALUE_END`]; в столбце со словарем
+//! повтор уже встречавшегося значения — один символ `257 + i`.
+//!
+//! Тело кадра: `[разделитель u8][число столбцов u32]`, поток ширин строк
+//! (число полей каждой строки) и потоки столбцов `[словарь u8][поток]`,
+//! каждый поток —
+//! `[таблица частот][общая частота][длина u32][битовый поток]`.
+
+use super::engine_covenant::{CompressionEngine, EngineConfig, Frame};
+use super::split_stream_engine::write_class_stream;
+use crate::bit_wizardry::bit_manipulation_spells::CoderPrecision;
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::decompression_oracle::decompression_sage::decode_symbol_stream;
+use crate::error::{WizardError, WizardResult};
+use crate::format::artifact_scroll::{
+    check_body_header, read_coded_stream, read_frequency_section, ScrollCursor,
+};
+use crate::format::container_runes::{ArchiveHeader, EngineKind, ModelKind};
+use std::collections::HashMap;
+
+/// Конец значения в потоке столбца
+const VALUE_END: u32 = 256;
+
+/// Первый символ повтора значения
+const REPEAT_BASE: u32 = 257;
+
+/// Столбец без словаря: каждое значение байтами
+const SPELLED_COLUMN: u8 = 0;
+
+/// Столбец со словарем повторов
+const INTERNED_COLUMN: u8 = 1;
+
+/// Наибольшее число столбцов: поля дальше делят последний столбец
+pub const MAX_COLUMNS: usize = 256;
+
+/// Разделители, которые ищет [`detect_delimiter`], в порядке приоритета
+const DELIMITER_CANDIDATES: [u8; 4] = [b',', b'\t', b';', b'|'];
+
+/// Сколько строк просматривает [`detect_delimiter`]
+const DETECTION_LINES: usize = 16;
+
+/// Движок столбцового режима 🧮
+///
+/// Разделитель записывается в кадр, поэтому распаковывает любой экземпляр.
+#[derive(Debug, Clone, Copy)]
+pub struct ColumnarEngine {
+    delimiter: u8,
+}
+
+impl ColumnarEngine {
+    /// Движок с выбранным разделителем полей
+    pub fn new(delimiter: u8) -> Self {
+        Self { delimiter }
+    }
+
+    /// Разделитель полей
+    pub fn delimiter(&self) -> u8 {
+        self.delimiter
+    }
+}
+
+impl Default for ColumnarEngine {
+    /// Движок для CSV
+    fn default() -> Self {
+        Self::new(b',')
+    }
+}
+
+impl CompressionEngine for ColumnarEngine {
+    fn engine_kind(&self) -> EngineKind {
+        EngineKind::Columnar
+    }
+
+    fn name(&self) -> &'static str {
+        "columnar"
+    }
+
+    fn compress(&self, data: &[u8], _config: &EngineConfig) -> Frame {
+        let mut row_widths = Vec::new();
+        let mut columns: Vec<ColumnWriter<'_>> = Vec::new();
+        for row in split_unquoted(data, b'\n') {
+            let fields = split_unquoted(row, self.delimiter);
+            row_widths.push(fields.len() as u32);
+            for (field_index, field) in fields.into_iter().enumerate() {
+                let column_index = field_index.min(MAX_COLUMNS - 1);
+                if column_index == columns.len() {
+                    columns.push(ColumnWriter::default());
+                }
+                columns[column_index].push(field);
+            }
+        }
+
+        let mut body = vec![self.delimiter];
+        body.extend_from_slice(&(columns.len() as u32).to_le_bytes());
+        write_class_stream(&mut body, &row_widths);
+        for column in &columns {
+            column.write_to(&mut body);
+        }
+
+        Frame {
+            header: ArchiveHeader::current(ModelKind::StaticOrder0, None)
+                .with_engine(EngineKind::Columnar),
+            body,
+        }
+    }
+
+    fn try_decompress(&self, frame: &Frame, options: &DecodeOptions) -> WizardResult<Vec<u8>> {
+        try_unweave_columnar_body(&frame.header, &frame.body, options)
+    }
+}
+
+/// Угадывает разделитель по первым строкам
+///
+/// Разделитель подходит, если встречается вне кавычек в каждой из первых
+/// строк (хотя бы двух) одинаковое ненулевое число раз.
+pub fn detect_delimiter(sample: &[u8]) -> Option<u8> {
+    let rows: Vec<&[u8]> = split_unquoted(sample, b'\n')
+        .into_iter()
+        .filter(|row| !row.is_empty())
+        .take(DETECTION_LINES)
+        .collect();
+    if rows.len() < 2 {
+        return None;
+    }
+
+    DELIMITER_CANDIDATES.into_iter().find(|&delimiter| {
+        let first_width = split_unquoted(rows[0], delimiter).len();
+        first_width > 1
+            && rows
+                .iter()
+                .all(|row| split_unquoted(row, delimiter).len() == first_width)
+    })
+}
+
+/// Делит байты по `separator` вне кавычек (кусков всегда на один больше)
+fn split_unquoted(data: &[u8], separator: u8) -> Vec<&[u8]> {
+    let mut pieces = Vec::new();
+    let mut piece_start = 0;
+    let mut in_quotes = false;
+    for (position, &byte) in data.iter().enumerate() {
+        if byte == b'"' {
+            in_quotes = !in_quotes;
+        } else if byte == separator && !in_quotes {
+            pieces.push(&data[piece_start..position]);
+            piece_start = position + 1;
+        }
+    }
+    pieces.push(&data[piece_start..]);
+    pieces
+}
+
+/// Поток столбца в двух вариантах: значения байтами и со словарем повторов
+#[derive(Debug, Default)]
+struct ColumnWriter<'a> {
+    spelled: Vec<u32>,
+    interned: Vec<u32>,
+    value_ids: HashMap<&'a [u8], u32>,
+}
+
+impl<'a> ColumnWriter<'a> {
+    /// Добавляет значение: повтор — одним символом, новое — байтами
+    fn push(&mut self, value: &'a [u8]) {
+        self.spelled.extend(value.iter().map(|&byte| byte as u32));
+        self.spelled.push(VALUE_END);
+
+        let next_id = self.value_ids.len() as u32;
+        match self.value_ids.get(value) {
+            Some(&value_id) => self.interned.push(REPEAT_BASE + value_id),
+            None => {
+                self.value_ids.insert(value, next_id);
+                self.interned.extend(value.iter().map(|&byte| byte as u32));
+                self.interned.push(VALUE_END);
+            }
+        }
+    }
+
+    /// Дописывает более короткий вариант с байтом выбора
+    ///
+    /// Каждый символ повтора — запись таблицы частот, поэтому словарь
+    /// окупается только на столбцах с частыми повторами (категории).
+    fn write_to(&self, output: &mut Vec<u8>) {
+        let mut spelled = vec![SPELLED_COLUMN];
+        write_class_stream(&mut spelled, &self.spelled);
+        let mut interned = vec![INTERNED_COLUMN];
+        write_class_stream(&mut interned, &self.interned);
+        output.extend_from_slice(if interned.len() < spelled.len() {
+            &interned
+        } else {
+            &spelled
+        });
+    }
+}
+
+/// Разбор потока столбца при распаковке
+struct ColumnReader {
+    symbols: std::vec::IntoIter<u32>,
+    interned: bool,
+    values: Vec<Vec<u8>>,
+}
+
+impl ColumnReader {
+    fn new(symbols: Vec<u32>, interned: bool) -> Self {
+        Self {
+            symbols: symbols.into_iter(),
+            interned,
+            values: Vec::new(),
+        }
+    }
+
+    /// Переносит следующее значение столбца в результат
+    fn restore_value(
+        &mut self,
+        restored: &mut Vec<u8>,
+        options: &DecodeOptions,
+    ) -> WizardResult<()> {
+        match self.symbols.next() {
+            Some(repeat @ REPEAT_BASE..) if self.interned => {
+                let value = self
+                    .values
+                    .get((repeat - REPEAT_BASE) as usize)
+                    .ok_or(WizardError::CorruptPayload)?;
+                restored.extend_from_slice(value);
+                Ok(())
+            }
+            Some(mut mystical_symbol @ 0..=VALUE_END) => {
+                let mut value = Vec::new();
+                while mystical_symbol != VALUE_END {
+                    value.push(mystical_symbol as u8);
+                    mystical_symbol = match self.symbols.next() {
+                        Some(next @ 0..=VALUE_END) => next,
+                        _ => return Err(WizardError::CorruptPayload),
+                    };
+                }
+                restored.extend_from_slice(&value);
+                if self.interned {
+                    options.check_dict_entries(self.values.len() + 1)?;
+                    self.values.push(value);
+                }
+                Ok(())
+            }
+            _ => Err(WizardError::CorruptPayload),
+        }
+    }
+}
+
+/// Проверяемая распаковка тела кадра столбцового режима 🛡️
+pub(crate) fn try_unweave_columnar_body(
+    header: &ArchiveHeader,
+    body: &[u8],
+    options: &DecodeOptions,
+) -> WizardResult<Vec<u8>> {
+    check_body_header(header, EngineKind::Columnar)?;
+    if header.alphabet_map.is_some() {
+        return Err(WizardError::CorruptFrequencyTable(
+            "атлас алфавита не применяется к столбцовому режиму",
+        ));
+    }
+
+    let mut cursor = ScrollCursor::new(body, 0);
+    let delimiter = cursor.read_slice(1)?[0];
+    let column_count = cursor.read_u32()? as usize;
+    if column_count > MAX_COLUMNS {
+        return Err(WizardError::CorruptPayload);
+    }
+
+    let precision = header.coder_precision();
+    let row_widths = read_class_stream(&mut cursor, options, precision)?;
+    let mut columns = Vec::with_capacity(column_count);
+    for _ in 0..column_count {
+        let interned = match cursor.read_slice(1)?[0] {
+            SPELLED_COLUMN => false,
+            INTERNED_COLUMN => true,
+            _ => return Err(WizardError::CorruptPayload),
+        };
+        let symbols = read_class_stream(&mut cursor, options, precision)?;
+        columns.push(ColumnReader::new(symbols, interned));
+    }
+    if cursor.remaining() != 0 {
+        return Err(WizardError::CorruptPayload);
+    }
+
+    let mut restored = Vec::new();
+    for (row_index, &row_width) in row_widths.iter().enumerate() {
+        if row_width == 0 {
+            return Err(WizardError::CorruptPayload);
+        }
+        if row_index > 0 {
+            restored.push(b'\n');
+        }
+        for field_index in 0..row_width as usize {
+            if field_index > 0 {
+                restored.push(delimiter);
+            }
+            columns
+                .get_mut(field_index.min(MAX_COLUMNS - 1))
+                .ok_or(WizardError::CorruptPayload)?
+                .restore_value(&mut restored, options)?;
+            options.check_output(restored.len() as u64)?;
+        }
+    }
+
+    if columns
+        .iter_mut()
+        .any(|column| column.symbols.next().is_some())
+    {
+        return Err(WizardError::CorruptPayload);
+    }
+    Ok(restored)
+}
+
+/// Читает и декодирует поток одного класса
+fn read_class_stream(
+    cursor: &mut ScrollCursor<'_>,
+    options: &DecodeOptions,
+    precision: CoderPrecision,
+) -> WizardResult<Vec<u32>> {
+    let frequency_codex = read_frequency_section(cursor, options)?;
+    let (total_frequency, compressed_bit_stream) = read_coded_stream(cursor)?;
+    // Ширина строки и символ значения дают хотя бы байт результата
+    // (перевод строки, разделитель или байт значения)
+    options.reserve_output(total_frequency)?;
+    decode_symbol_stream(
+        compressed_bit_stream,
+        &frequency_codex,
+        total_frequency,
+        total_frequency,
+        precision,
+    )
+}
+
+/// Тесты столбцового плетения 🎯
+#[cfg(test)]
+mod column_weave_tests {
+    use super::*;
+    use crate::simple_api::compress_data;
+
+    fn exported_dataset() -> Vec<u8> {
+        let mut csv = String::from("id,region,price,date\n");
+        for row in 0..3000 {
+            csv.push_str(&format!(
+                "{},{},{}.{:02},2023-{:02}-{:02}\n",
+                1000 + row,
+                ["north", "south", "east", "west"][row % 4],
+                row * 7 % 90,
+                row % 100,
+                row % 12 + 1,
+                row % 28 + 1
+            ));
+        }
+        csv.into_bytes()
+    }
+
+    #[test]
+    fn test_dataset_roundtrip_and_beat_plain_model() {
+        let csv = exported_dataset();
+        let engine = ColumnarEngine::default();
+        let frame = engine.compress(&csv, &EngineConfig::default());
+        assert_eq!(
+            engine
+                .try_decompress(&frame, &DecodeOptions::default())
+                .unwrap(),
+            csv
+        );
+        assert!(
+            frame.to_bytes().len() * 5 < compress_data(&csv).len() * 4,
+            "{} против {}",
+            frame.to_bytes().len(),
+            compress_data(&csv).len()
+        );
+    }
+
+    #[test]
+    fn test_irregular_input_roundtrip() {
+        let many_columns = ",".repeat(MAX_COLUMNS + 10) + "\n1,2";
+        let cases: [&[u8]; 6] = [
+            b"",
+            b"\n\n",
+            b"a\tb\n\"quoted\tcell\nwith newline\"\tc\n1\t2\t3\t4",
+            b"\"unterminated\tquote\n1\t2",
+            &[0, 9, 255, 10, 9, 9, 0],
+            many_columns.as_bytes(),
+        ];
+        let engine = ColumnarEngine::new(b'\t');
+        for data in cases {
+            let frame = engine.compress(data, &EngineConfig::default());
+            assert_eq!(ColumnarEngine::default().decompress(&frame), data);
+        }
+    }
+
+    #[test]
+    fn test_damaged_frame_is_rejected() {
+        let frame =
+            ColumnarEngine::default().compress(&exported_dataset(), &EngineConfig::default());
+        let options = DecodeOptions::default();
+
+        let mut truncated = frame.clone();
+        truncated.body.truncate(truncated.body.len() - 1);
+        assert!(ColumnarEngine::default()
+            .try_decompress(&truncated, &options)
+            .is_err());
+
+        let mut too_wide = frame.clone();
+        too_wide.body[1..5].copy_from_slice(&(MAX_COLUMNS as u32 + 1).to_le_bytes());
+        assert_eq!(
+            ColumnarEngine::default().try_decompress(&too_wide, &options),
+            Err(WizardError::CorruptPayload)
+        );
+    }
+
+    #[test]
+    fn test_delimiter_detection() {
+        assert_eq!(detect_delimiter(&exported_dataset()), Some(b','));
+        assert_eq!(detect_delimiter(b"a\tb,c\nd\te\n"), Some(b'\t'));
+        assert_eq!(detect_delimiter(b"\"x;y\";z\n1;2\n"), Some(b';'));
+        assert_eq!(detect_delimiter(b"plain text, mostly\nprose\n"), None);
+        assert_eq!(detect_delimiter(b"single,line"), None);
+    }
+}
@@ -1,301 +1,1676 @@
-//! Модуль арифметического сжатия
-//!
-//! Реализует алгоритм арифметического кодирования с оптимизацией словаря.
-//! Преобразует данные в компактное представление с восстановимостью.
-
-use crate::bit_wizardry::bit_manipulation_spells::{BitMagicWriter, ARITHMETIC_PRECISION_LIMIT};
-use std::collections::HashMap;
-
-/// Результат сжатия - содержит все данные для восстановления
-#[derive(Debug, Clone)]
-pub struct CompressionArtifact {
-    /// Таблица частот: (id символа, частота, накопительная позиция)
-    pub mystical_frequency_codex: Vec<(u32, u64, u64)>,
-    /// Общее количество символов
-    pub total_frequency_essence: u64,
-    /// Сжатый битовый поток
-    pub compressed_bit_stream: Vec<u8>,
-    /// Словарь часто встречающихся слов
-    pub mystical_word_grimoire: Vec<String>,
-}
-
-/// Сжимает данные с помощью арифметического кодирования
-///
-/// Алгоритм:
-/// 1. Строит словарь часто встречающихся слов
-/// 2. Преобразует текст в символы (байты + ссылки на слова)
-/// 3. Анализирует частоты для таблицы вероятностей
-/// 4. Выполняет арифметическое кодирование
-pub fn weave_compression_spell(original_manuscript: &[u8]) -> CompressionArtifact {
-    // Находим выгодные слова для словаря
-    let mystical_word_grimoire = discover_profitable_word_enchantments(original_manuscript);
-
-    // Преобразуем текст в символы
-    let symbolic_incantations =
-        transform_manuscript_to_symbols(original_manuscript, &mystical_word_grimoire);
-
-    // Анализируем частоты
-    let frequency_analysis_results = analyze_symbolic_frequencies(&symbolic_incantations);
-
-    // Выполняем арифметическое кодирование
-    let mut compressed_bit_stream = Vec::new();
-    let mut bit_conjurer = BitMagicWriter::conjure_new(&mut compressed_bit_stream);
-
-    let mut interval_low = 0u32;
-    let mut interval_high = ARITHMETIC_PRECISION_LIMIT;
-
-    // Кодируем каждый символ
-    for mystical_symbol in symbolic_incantations {
-        if let Some((_, symbol_frequency, cumulative_start)) = frequency_analysis_results
-            .frequency_entries
-            .iter()
-            .find(|&&(symbol_id, _, _)| symbol_id == mystical_symbol)
-        {
-            let symbol_start = *cumulative_start as u32;
-            let symbol_end = (*cumulative_start + *symbol_frequency) as u32;
-            let total_mass = frequency_analysis_results.total_frequency_mass as u32;
-
-            bit_conjurer.encode_mystical_symbol(
-                &mut interval_low,
-                &mut interval_high,
-                symbol_start,
-                symbol_end,
-                total_mass,
-            );
-        }
-    }
-
-    bit_conjurer.complete_compression_ritual();
-    CompressionArtifact {
-        mystical_frequency_codex: frequency_analysis_results.frequency_entries,
-        total_frequency_essence: frequency_analysis_results.total_frequency_mass,
-        compressed_bit_stream,
-        mystical_word_grimoire,
-    }
-}
-
-/// Результат анализа частот
-#[derive(Debug)]
-struct FrequencyAnalysisWisdom {
-    /// (символ, частота, накопительная позиция)
-    frequency_entries: Vec<(u32, u64, u64)>,
-    /// Общая сумма частот
-    total_frequency_mass: u64,
-}
-
-/// Находит слова, выгодные для включения в словарь
-///
-/// Критерии отбора:
-/// - Частота > 3 вхождений
-/// - Экономия: длина × частота > длина + 4 (накладные расходы)
-/// - Учитывается регистр
-fn discover_profitable_word_enchantments(manuscript_bytes: &[u8]) -> Vec<String> {
-    // Для маленьких файлов словарь неэффективен
-    #[cfg(not(test))]
-    if manuscript_bytes.len() < 1000 {
-        return Vec::new();
-    }
-
-    let manuscript_text = String::from_utf8_lossy(manuscript_bytes);
-    let mut word_frequency_almanac = HashMap::new();
-    let mut current_word_buffer = String::new();
-
-    // Разбиваем на слова по ASCII буквам
-    for mystical_character in manuscript_text.chars() {
-        if mystical_character.is_ascii_alphabetic() || mystical_character == '\'' {
-            current_word_buffer.push(mystical_character);
-        } else {
-            if current_word_buffer.len() >= 3 {
-                *word_frequency_almanac
-                    .entry(current_word_buffer.clone())
-                    .or_insert(0u64) += 1;
-            }
-            current_word_buffer.clear();
-        }
-    }
-    if current_word_buffer.len() >= 3 {
-        *word_frequency_almanac
-            .entry(current_word_buffer)
-            .or_insert(0u64) += 1;
-    }
-
-    // Отбираем выгодные слова
-    let mut profitable_word_candidates: Vec<(String, u64, i64)> = word_frequency_almanac
-        .into_iter()
-        .filter_map(|(enchanted_word, occurrence_frequency)| {
-            // Вычисляем экономию
-            let compression_savings = (enchanted_word.len() as i64 * occurrence_frequency as i64)
-                - (enchanted_word.len() as i64 + 4);
-
-            if occurrence_frequency > 3 && compression_savings > 0 {
-                Some((enchanted_word, occurrence_frequency, compression_savings))
-            } else {
-                None
-            }
-        })
-        .collect();
-
-    profitable_word_candidates
-        .sort_by_key(|(_, _, compression_savings)| std::cmp::Reverse(*compression_savings));
-
-    profitable_word_candidates.truncate(25);
-
-    let selected_word_grimoire: Vec<String> = profitable_word_candidates
-        .iter()
-        .map(|(enchanted_word, _, _)| enchanted_word.clone())
-        .collect();
-
-    // Отладочный вывод
-    if !selected_word_grimoire.is_empty() {
-        println!("Найдено {} полезных слов:", selected_word_grimoire.len());
-
-        for (spell_index, (word, frequency, savings)) in
-            profitable_word_candidates.iter().enumerate().take(10)
-        {
-            println!(
-                "  {}: '{}' ({}x, {} байт экономии)",
-                spell_index, word, frequency, savings
-            );
-        }
-    }
-
-    selected_word_grimoire
-}
-
-/// Преобразует текст в символы, заменяя слова ссылками на словарь
-///
-/// Кодирование:
-/// - 0-255: обычные байты
-/// - 256+: ссылки на словарь (256 + индекс)
-fn transform_manuscript_to_symbols(manuscript_bytes: &[u8], word_grimoire: &[String]) -> Vec<u32> {
-    let mut symbolic_sequence = Vec::new();
-    let mut byte_position = 0;
-
-    while byte_position < manuscript_bytes.len() {
-        let mut word_spell_discovered = false;
-
-        // Пытаемся найти слово, если встретили букву
-        if manuscript_bytes[byte_position].is_ascii_alphabetic()
-            || manuscript_bytes[byte_position] == b'\''
-        {
-            // Проверяем каждое слово из словаря
-            for (grimoire_index, mystical_word) in word_grimoire.iter().enumerate() {
-                let word_bytes = mystical_word.as_bytes();
-
-                if byte_position + word_bytes.len() <= manuscript_bytes.len() {
-                    let mut perfect_word_match = true;
-
-                    // Сравниваем побайтно
-                    for (offset, &expected_byte) in word_bytes.iter().enumerate() {
-                        if manuscript_bytes[byte_position + offset] != expected_byte {
-                            perfect_word_match = false;
-                            break;
-                        }
-                    }
-
-                    // Проверяем границы слова
-                    if perfect_word_match {
-                        let word_end_position = byte_position + word_bytes.len();
-
-                        let valid_word_start = byte_position == 0
-                            || !manuscript_bytes[byte_position - 1].is_ascii_alphabetic();
-                        let valid_word_end = word_end_position >= manuscript_bytes.len()
-                            || !manuscript_bytes[word_end_position].is_ascii_alphabetic();
-
-                        if valid_word_start && valid_word_end {
-                            // Заменяем ссылкой на словарь
-                            symbolic_sequence.push(256u32 + grimoire_index as u32);
-                            byte_position += word_bytes.len();
-                            word_spell_discovered = true;
-                            break;
-                        }
-                    }
-                }
-            }
-        }
-
-        // Если слово не найдено, добавляем байт как есть
-        if !word_spell_discovered {
-            symbolic_sequence.push(manuscript_bytes[byte_position] as u32);
-            byte_position += 1;
-        }
-    }
-
-    symbolic_sequence
-}
-
-/// Строит таблицу частот для арифметического кодирования 🔍📊
-/// Использует эффективное заимствование срезов без копирования данных
-fn analyze_symbolic_frequencies(symbolic_incantations: &[u32]) -> FrequencyAnalysisWisdom {
-    // Подсчет частот
-    let mut symbol_frequency_map = HashMap::new();
-
-    for &mystical_symbol in symbolic_incantations {
-        *symbol_frequency_map.entry(mystical_symbol).or_insert(0u64) += 1;
-    }
-
-    // Сортировка для детерминированности
-    let mut frequency_pairs: Vec<(u32, u64)> = symbol_frequency_map.into_iter().collect();
-    frequency_pairs.sort_by_key(|&(symbol_id, _)| symbol_id);
-
-    // Общая сумма
-    let total_frequency_mass: u64 = frequency_pairs
-        .iter()
-        .map(|&(_, frequency)| frequency)
-        .sum();
-
-    // Накопительная таблица для интервалов
-    let mut cumulative_position = 0u64;
-    let frequency_entries: Vec<(u32, u64, u64)> = frequency_pairs
-        .iter()
-        .map(|&(symbol_id, frequency_count)| {
-            let current_position = cumulative_position;
-            cumulative_position += frequency_count;
-            (symbol_id, frequency_count, current_position)
-        })
-        .collect();
-
-    // Готовая структура данных
-    FrequencyAnalysisWisdom {
-        frequency_entries,
-        total_frequency_mass,
-    }
-}
-
-/// Тесты алгоритмов сжатия 🎯
-#[cfg(test)]
-mod compression_conjurer_tests {
-    use super::*;
-
-    /// Проверка словаря
-    #[test]
-    fn test_word_discovery_functionality() {
-        let sample_text = b"the quick brown fox jumps over the lazy dog the end the beginning the world the universe the magic the power";
-        let discovered_words = discover_profitable_word_enchantments(sample_text);
-
-        // "the" должно попасть в словарь
-        assert!(discovered_words.contains(&"the".to_string()));
-    }
-
-    /// Проверка символьного кодирования
-    #[test]
-    fn test_symbol_transformation() {
-        let test_data = b"hello world hello";
-        let word_dict = vec!["hello".to_string()];
-        let symbols = transform_manuscript_to_symbols(test_data, &word_dict);
-
-        // Ссылки на словарь (256+) и обычные байты
-        assert!(symbols.contains(&256)); // "hello"
-        assert!(symbols.contains(&32)); // пробел
-    }
-
-    /// Проверка подсчета частот
-    #[test]
-    fn test_frequency_analysis() {
-        let symbols = vec![65u32, 66u32, 65u32]; // A, B, A
-        let analysis = analyze_symbolic_frequencies(&symbols);
-
-        assert_eq!(analysis.total_frequency_mass, 3);
-        assert_eq!(analysis.frequency_entries.len(), 2);
-    }
-}
+//! Модуль арифметического сжатия
+//!
+//! Реализует алгоритм арифметического кодирования с оптимизацией словаря.
+//! Преобразует данные в компактное представление с восстановимостью.
+
+use super::engine_covenant::{EngineConfig, ParseStrategy};
+use super::frequency_census::{byte_histogram, symbol_census};
+use super::grimoire_trie::GrimoireTrie;
+use super::grimoire_ward::DenyList;
+use super::phrase_weaver::weave_phrase_enchantments;
+use super::word_frontier::WordBoundaries;
+use super::word_sketch::{sketch_word_counts, WordCounting};
+use crate::bit_wizardry::bit_manipulation_spells::CoderPrecision;
+use crate::bit_wizardry::entropy_coder::ArithmeticEncoder;
+use crate::error::{LimitKind, WizardError, WizardResult};
+use crate::format::artifact_scroll::{check_length_field, offset_within};
+use crate::telemetry::{record_compression, Stopwatch};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Первый символ ссылок на словарь
+pub(crate) const WORD_SYMBOL_BASE: u32 = 256;
+
+/// Результат сжатия - содержит все данные для восстановления
+#[derive(Debug, Clone)]
+pub struct CompressionArtifact {
+    /// Таблица частот: (id символа, частота, накопительная позиция)
+    pub mystical_frequency_codex: Vec<(u32, u64, u64)>,
+    /// Общее количество символов
+    pub total_frequency_essence: u64,
+    /// Сжатый битовый поток
+    pub compressed_bit_stream: Vec<u8>,
+    /// Словарь часто встречающихся слов
+    pub mystical_word_grimoire: Vec<String>,
+    /// Фразы: частые последовательности слов словаря (символы `256 + 2n + индекс`)
+    pub mystical_phrase_codex: Vec<Vec<u32>>,
+}
+
+/// Сжимает данные с помощью арифметического кодирования
+///
+/// Алгоритм:
+/// 1. Строит словарь часто встречающихся слов
+/// 2. Преобразует текст в символы (байты + ссылки на слова)
+/// 3. Заменяет частые цепочки слов ссылками на фразы
+/// 4. Анализирует частоты для таблицы вероятностей
+/// 5. Выполняет арифметическое кодирование (32-битные регистры)
+///
+/// Паникует, если символов больше, чем допускает точность кодера
+/// (около 2^30) — для таких входов используйте [`try_weave_compression_spell`].
+pub fn weave_compression_spell(original_manuscript: impl AsRef<[u8]>) -> CompressionArtifact {
+    weave_compression_spell_with_counting(original_manuscript, WordCounting::Exact)
+}
+
+/// Проверяемое сжатие 🛡️
+///
+/// Вход, чья таблица частот не умещается в точность кодера, дает
+/// [`WizardError::LimitExceeded`] вместо паники или порчи интервалов.
+pub fn try_weave_compression_spell(
+    original_manuscript: impl AsRef<[u8]>,
+) -> WizardResult<CompressionArtifact> {
+    try_weave_compression_spell_with_counting(original_manuscript, WordCounting::Exact)
+}
+
+/// Сжатие с выбранным способом подсчета слов для словаря
+///
+/// `WordCounting::Sketch` ограничивает память поиска словаря на больших входах.
+pub fn weave_compression_spell_with_counting(
+    original_manuscript: impl AsRef<[u8]>,
+    word_counting: WordCounting,
+) -> CompressionArtifact {
+    try_weave_compression_spell_with_counting(original_manuscript, word_counting)
+        .expect("вход больше точности кодера: используйте try_weave_compression_spell")
+}
+
+/// Проверяемое сжатие с выбранным способом подсчета слов
+pub fn try_weave_compression_spell_with_counting(
+    original_manuscript: impl AsRef<[u8]>,
+    word_counting: WordCounting,
+) -> WizardResult<CompressionArtifact> {
+    try_weave_with_discovery(
+        original_manuscript.as_ref(),
+        &EngineConfig::default().with_word_counting(word_counting),
+    )
+}
+
+/// Сжатие, чей словарь не содержит слов из запретного списка 🚫
+///
+/// Словарь хранится в заголовке открытым текстом; запрещенные слова
+/// кодируются байтами внутри арифметического потока.
+pub fn weave_compression_spell_with_deny_list(
+    original_manuscript: impl AsRef<[u8]>,
+    deny_list: &DenyList,
+) -> CompressionArtifact {
+    try_weave_with_discovery(
+        original_manuscript.as_ref(),
+        &EngineConfig::default().with_deny_list(deny_list.clone()),
+    )
+    .expect("вход больше точности кодера: используйте try_weave_compression_spell")
+}
+
+/// Проверяемое сжатие с параметрами поиска словаря
+///
+/// Вход длиннее [`MAX_LENGTH_FIELD`](crate::format::MAX_LENGTH_FIELD) отвергается до поиска словаря.
+pub(crate) fn try_weave_with_discovery(
+    original_manuscript: &[u8],
+    config: &EngineConfig,
+) -> WizardResult<CompressionArtifact> {
+    check_length_field(original_manuscript.len())?;
+    let stopwatch = Stopwatch::start();
+    let (symbolic_incantations, mystical_word_grimoire, mystical_phrase_codex) =
+        symbolize_manuscript(original_manuscript, config);
+    config.check_deadline()?;
+
+    // Анализируем частоты
+    let frequency_analysis_results = analyze_symbolic_frequencies(&symbolic_incantations)?;
+
+    // Выполняем арифметическое кодирование
+    let compressed_bit_stream = encode_symbol_stream(
+        &symbolic_incantations,
+        &frequency_analysis_results.frequency_entries,
+        frequency_analysis_results.total_frequency_mass,
+        CoderPrecision::default(),
+    )?;
+    record_compression(
+        "static",
+        stopwatch,
+        original_manuscript.len(),
+        &symbolic_incantations,
+        compressed_bit_stream.len(),
+    );
+
+    Ok(CompressionArtifact {
+        mystical_frequency_codex: frequency_analysis_results.frequency_entries,
+        total_frequency_essence: frequency_analysis_results.total_frequency_mass,
+        compressed_bit_stream,
+        mystical_word_grimoire,
+        mystical_phrase_codex,
+    })
+}
+
+/// Слова словаря: заданные вручную или найденные поиском
+///
+/// Ручной словарь заимствуется из `config`: в артефакт после
+/// [`prune_unused_words`] копируются только совпавшие слова.
+pub(crate) fn choose_word_grimoire<'a>(
+    original_manuscript: &[u8],
+    config: &'a EngineConfig,
+) -> Cow<'a, [String]> {
+    match &config.grimoire {
+        Some(grimoire) => Cow::Borrowed(grimoire.words()),
+        None => Cow::Owned(discover_word_enchantments_within_budget(
+            original_manuscript,
+            &config.word_counting,
+            &config.deny_list,
+            config.dictionary_budget_bytes,
+            &config.word_boundaries,
+        )),
+    }
+}
+
+/// Превращает исходник в поток символов со словарем и фразами
+///
+/// Общие шаги 1–3 для всех движков со словарем.
+pub(crate) fn symbolize_manuscript(
+    original_manuscript: &[u8],
+    config: &EngineConfig,
+) -> (Vec<u32>, Vec<String>, Vec<Vec<u32>>) {
+    // Находим выгодные слова для словаря
+    let mystical_word_grimoire = choose_word_grimoire(original_manuscript, config);
+
+    // Преобразуем текст в символы по тем же границам слов
+    let symbolic_incantations = match config.parse_strategy {
+        ParseStrategy::Greedy => transform_manuscript_to_symbols_within(
+            original_manuscript,
+            &mystical_word_grimoire,
+            &config.word_boundaries,
+        ),
+        ParseStrategy::Optimal => transform_manuscript_to_symbols_optimal(
+            original_manuscript,
+            &mystical_word_grimoire,
+            &config.word_boundaries,
+        ),
+    };
+
+    // Слова, не совпавшие ни разу из-за границ, не попадают в архив
+    let (symbolic_incantations, mystical_word_grimoire) =
+        prune_unused_words(symbolic_incantations, mystical_word_grimoire);
+
+    // Второй уровень словаря: фразы из 2–3 слов
+    let (symbolic_incantations, mystical_phrase_codex) =
+        weave_phrase_enchantments(symbolic_incantations, mystical_word_grimoire.len());
+
+    (
+        symbolic_incantations,
+        mystical_word_grimoire,
+        mystical_phrase_codex,
+    )
+}
+
+/// Измеренная польза слова словаря 📏
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordEffectiveness {
+    /// Индекс слова в словаре
+    pub grimoire_index: usize,
+    /// Сколько раз слово заменено ссылкой (с пробелом и без, включая фразы)
+    pub occurrences: u64,
+    /// Сколько из них поглотили пробел после слова
+    pub with_trailing_space: u64,
+    /// Сколько байтов исходника заменили ссылки (включая поглощенные пробелы)
+    pub replaced_bytes: u64,
+    /// Биты, потраченные кодером на ссылки (по итоговой модели)
+    pub coded_bits: f64,
+    /// Итоговая экономия в байтах с учетом места слова в заголовке
+    pub bytes_saved: f64,
+}
+
+impl CompressionArtifact {
+    /// Измеряет пользу каждого слова словаря после кодирования
+    ///
+    /// Стоимость ссылки — ее идеальная длина кода `log2(total / freq)`
+    /// в итоговой модели; арифметический кодер достигает ее с точностью
+    /// до пары битов на весь поток. Стоимость фразы делится поровну
+    /// между входящими в нее словами.
+    pub fn word_effectiveness(&self) -> Vec<WordEffectiveness> {
+        let grimoire_len = self.mystical_word_grimoire.len();
+        let total = self.total_frequency_essence as f64;
+        let mut report: Vec<WordEffectiveness> = self
+            .mystical_word_grimoire
+            .iter()
+            .enumerate()
+            .map(|(grimoire_index, _)| WordEffectiveness {
+                grimoire_index,
+                occurrences: 0,
+                with_trailing_space: 0,
+                replaced_bytes: 0,
+                coded_bits: 0.0,
+                bytes_saved: 0.0,
+            })
+            .collect();
+
+        let mut words_in_symbol = Vec::new();
+        for &(symbol_id, frequency, _) in &self.mystical_frequency_codex {
+            words_in_symbol.clear();
+            collect_symbol_words(
+                symbol_id,
+                grimoire_len,
+                &self.mystical_phrase_codex,
+                &mut words_in_symbol,
+            );
+            let symbol_bits = frequency as f64 * (total / frequency as f64).log2();
+
+            for &(index, trailing_space) in &words_in_symbol {
+                let Some(entry) = report.get_mut(index) else {
+                    continue;
+                };
+                let word_len = self.mystical_word_grimoire[index].len() as u64;
+                entry.occurrences += frequency;
+                entry.with_trailing_space += frequency * trailing_space as u64;
+                entry.replaced_bytes += frequency * (word_len + trailing_space as u64);
+                entry.coded_bits += symbol_bits / words_in_symbol.len() as f64;
+            }
+        }
+
+        for (entry, word) in report.iter_mut().zip(&self.mystical_word_grimoire) {
+            let header_cost = (word.len() + 4) as f64;
+            entry.bytes_saved = entry.replaced_bytes as f64 - entry.coded_bits / 8.0 - header_cost;
+        }
+        report
+    }
+}
+
+/// Собирает слова, из которых состоит символ (фразы раскрываются)
+///
+/// Фраза может ссылаться только на символы меньше своего,
+/// поэтому раскрытие всегда конечно.
+pub(crate) fn collect_symbol_words(
+    symbol: u32,
+    grimoire_len: usize,
+    phrase_codex: &[Vec<u32>],
+    words: &mut Vec<(usize, bool)>,
+) {
+    match interpret_symbol(symbol, grimoire_len) {
+        SymbolMeaning::Byte(_) => {}
+        SymbolMeaning::Word {
+            index,
+            trailing_space,
+        } => words.push((index, trailing_space)),
+        SymbolMeaning::Phrase { index } => {
+            for &element in phrase_codex.get(index).into_iter().flatten() {
+                if element < symbol {
+                    collect_symbol_words(element, grimoire_len, phrase_codex, words);
+                }
+            }
+        }
+    }
+}
+
+/// Число ссылок на каждое слово словаря в потоке символов 🔢
+///
+/// Фраза считается ссылкой на каждое свое слово при каждом повторе.
+pub(crate) fn word_reference_counts(
+    symbolic_incantations: &[u32],
+    grimoire_len: usize,
+    phrase_codex: &[Vec<u32>],
+) -> Vec<u64> {
+    let mut references = vec![0u64; grimoire_len];
+    let mut words_in_symbol = Vec::new();
+    for &symbol in symbolic_incantations {
+        words_in_symbol.clear();
+        collect_symbol_words(symbol, grimoire_len, phrase_codex, &mut words_in_symbol);
+        for &(index, _) in &words_in_symbol {
+            references[index] += 1;
+        }
+    }
+    references
+}
+
+/// Удаляет из словаря слова без единой ссылки и перенумеровывает символы
+///
+/// Эвристика отбора не знает правил границ слова, поэтому часть слов
+/// может ни разу не совпасть — их незачем хранить в заголовке. Вызывается
+/// до поиска фраз: поток еще состоит из байтов и слов.
+pub(crate) fn prune_unused_words<'a>(
+    symbolic_incantations: Vec<u32>,
+    word_grimoire: impl Into<Cow<'a, [String]>>,
+) -> (Vec<u32>, Vec<String>) {
+    let word_grimoire = word_grimoire.into();
+    let grimoire_len = word_grimoire.len();
+    let references = word_reference_counts(&symbolic_incantations, grimoire_len, &[]);
+    if references.iter().all(|&count| count > 0) {
+        return (symbolic_incantations, word_grimoire.into_owned());
+    }
+
+    // Новые индексы сохраняют порядок слов
+    let mut new_index = vec![0usize; grimoire_len];
+    let mut retained = 0;
+    for (old_index, &count) in references.iter().enumerate() {
+        if count > 0 {
+            new_index[old_index] = retained;
+            retained += 1;
+        }
+    }
+    let mut referenced = references.iter().map(|&count| count > 0);
+    let pruned_grimoire: Vec<String> = match word_grimoire {
+        Cow::Owned(words) => words
+            .into_iter()
+            .filter(|_| referenced.next() == Some(true))
+            .collect(),
+        Cow::Borrowed(words) => words
+            .iter()
+            .filter(|_| referenced.next() == Some(true))
+            .cloned()
+            .collect(),
+    };
+
+    let pruned_len = pruned_grimoire.len();
+    let remapped_symbols = symbolic_incantations
+        .into_iter()
+        .map(|symbol| match interpret_symbol(symbol, grimoire_len) {
+            SymbolMeaning::Word {
+                index,
+                trailing_space,
+            } => word_symbol(new_index[index], pruned_len, trailing_space),
+            _ => symbol,
+        })
+        .collect();
+
+    (remapped_symbols, pruned_grimoire)
+}
+
+/// Кодирует последовательность символов по готовой таблице частот
+///
+/// Символы, отсутствующие в таблице, пропускаются. Границы интервалов
+/// переводятся в u32 с проверкой: масса больше точности кодера дает
+/// [`WizardError::LimitExceeded`], а не усеченные интервалы.
+pub(crate) fn encode_symbol_stream(
+    symbolic_incantations: &[u32],
+    frequency_entries: &[(u32, u64, u64)],
+    total_frequency_mass: u64,
+    precision: CoderPrecision,
+) -> WizardResult<Vec<u8>> {
+    let mut compressed_bit_stream = Vec::new();
+    encode_symbol_stream_into(
+        symbolic_incantations,
+        frequency_entries,
+        total_frequency_mass,
+        precision,
+        &mut compressed_bit_stream,
+    )?;
+    Ok(compressed_bit_stream)
+}
+
+/// Кодирует символы в конец `compressed_bit_stream`
+pub(crate) fn encode_symbol_stream_into(
+    symbolic_incantations: &[u32],
+    frequency_entries: &[(u32, u64, u64)],
+    total_frequency_mass: u64,
+    precision: CoderPrecision,
+    compressed_bit_stream: &mut Vec<u8>,
+) -> WizardResult<()> {
+    check_frequency_mass(total_frequency_mass, precision)?;
+    let total = total_frequency_mass as u32;
+
+    let mut entropy_coder = ArithmeticEncoder::new(compressed_bit_stream, precision);
+
+    // Кодируем каждый символ
+    for &mystical_symbol in symbolic_incantations {
+        if let Some(&(_, symbol_frequency, cumulative_start)) = frequency_entries
+            .iter()
+            .find(|&&(symbol_id, _, _)| symbol_id == mystical_symbol)
+        {
+            let cumulative_end = cumulative_start
+                .checked_add(symbol_frequency)
+                .filter(|&end| end <= total_frequency_mass)
+                .ok_or(WizardError::InvalidDistribution(
+                    "интервал символа выходит за общую массу",
+                ))?;
+            entropy_coder.encode(cumulative_start as u32, cumulative_end as u32, total)?;
+        }
+    }
+
+    entropy_coder.finish();
+    Ok(())
+}
+
+/// Проверяет, что общая масса частот умещается в точность кодера
+fn check_frequency_mass(total_frequency_mass: u64, precision: CoderPrecision) -> WizardResult<()> {
+    let allowed = precision.max_total_frequency();
+    if total_frequency_mass > allowed {
+        return Err(WizardError::LimitExceeded {
+            kind: LimitKind::FrequencyMass,
+            requested: total_frequency_mass,
+            allowed,
+        });
+    }
+    Ok(())
+}
+
+/// Результат анализа частот
+#[derive(Debug)]
+pub(crate) struct FrequencyAnalysisWisdom {
+    /// (символ, частота, накопительная позиция)
+    pub(crate) frequency_entries: Vec<(u32, u64, u64)>,
+    /// Общая сумма частот
+    pub(crate) total_frequency_mass: u64,
+}
+
+/// Находит слова, выгодные для включения в словарь
+///
+/// Критерии отбора:
+/// - Частота ≥ 2 вхождений
+/// - Экономия по энтропии литералов (см. [`LiteralCensus`]) больше
+///   роста заголовка
+/// - Учитывается регистр
+///
+/// [`WordCounting::Sketch`] ограничивает память на больших входах ценой
+/// приближенных частот. Слова из `deny_list` не рассматриваются вовсе.
+pub(crate) fn discover_profitable_word_enchantments(
+    manuscript_bytes: &[u8],
+    word_counting: &WordCounting,
+    deny_list: &DenyList,
+) -> Vec<String> {
+    discover_word_enchantments_within_budget(
+        manuscript_bytes,
+        word_counting,
+        deny_list,
+        None,
+        &WordBoundaries::default(),
+    )
+}
+
+/// Находит слова словаря: 25 самых выгодных или отбор по бюджету 💰
+///
+/// С бюджетом (см. [`EngineConfig::dictionary_budget_bytes`](super::EngineConfig::dictionary_budget_bytes))
+/// работает [`select_words_within_budget`]. Слова выделяются политикой
+/// `word_boundaries` — той же, что потом подставляет их в текст.
+pub(crate) fn discover_word_enchantments_within_budget(
+    manuscript_bytes: &[u8],
+    word_counting: &WordCounting,
+    deny_list: &DenyList,
+    dictionary_budget_bytes: Option<usize>,
+    word_boundaries: &WordBoundaries,
+) -> Vec<String> {
+    discover_scored_words(
+        manuscript_bytes,
+        word_counting,
+        deny_list,
+        dictionary_budget_bytes,
+        word_boundaries,
+    )
+    .into_iter()
+    .map(|(word, _)| word)
+    .collect()
+}
+
+/// Находит слова словаря вместе с оценкой экономии каждого в байтах
+///
+/// Порядок и отбор — как у [`discover_word_enchantments_within_budget`].
+pub(crate) fn discover_scored_words(
+    manuscript_bytes: &[u8],
+    word_counting: &WordCounting,
+    deny_list: &DenyList,
+    dictionary_budget_bytes: Option<usize>,
+    word_boundaries: &WordBoundaries,
+) -> Vec<(String, i64)> {
+    // Для маленьких файлов словарь неэффективен
+    #[cfg(not(test))]
+    if manuscript_bytes.len() < 1000 {
+        return Vec::new();
+    }
+
+    // Разбиваем на слова по политике границ; слова — срезы исходного
+    // буфера, строки создаются только для отобранных
+    let enchanted_words = word_boundaries
+        .words(manuscript_bytes)
+        .filter(|enchanted_word| enchanted_word.len() >= 3)
+        .filter(|enchanted_word| deny_list.is_empty() || !deny_list.denies(enchanted_word));
+
+    // Частота слова и сколько раз за ним идет пробел: пробел поглощается
+    // символом слова
+    let word_frequencies: Vec<WordTally> = match word_counting {
+        WordCounting::Exact => {
+            let mut word_frequency_almanac: HashMap<&[u8], (u64, u64)> = HashMap::new();
+            for enchanted_word in enchanted_words {
+                let word_end =
+                    offset_within(manuscript_bytes, enchanted_word) + enchanted_word.len();
+                let tally = word_frequency_almanac.entry(enchanted_word).or_default();
+                tally.0 += 1;
+                tally.1 += (manuscript_bytes.get(word_end) == Some(&b' ')) as u64;
+            }
+            word_frequency_almanac
+                .into_iter()
+                .map(|(word, (frequency, spaced))| WordTally {
+                    word,
+                    frequency,
+                    spaced,
+                })
+                .collect()
+        }
+        // Скетч не видит соседей слова: пробелы не учитываются
+        WordCounting::Sketch(sketch_params) => sketch_word_counts(enchanted_words, sketch_params)
+            .into_iter()
+            .map(|(word, frequency)| WordTally {
+                word,
+                frequency,
+                spaced: 0,
+            })
+            .collect(),
+    };
+
+    let profitable_word_candidates = match dictionary_budget_bytes {
+        Some(budget_bytes) => {
+            select_words_within_budget(manuscript_bytes, word_frequencies, budget_bytes)
+        }
+        None => select_most_profitable_words(manuscript_bytes, word_frequencies),
+    };
+
+    // Отобранные слова не печатаются: их экономию по запросу дают
+    // ModelReport и word_effectiveness
+    profitable_word_candidates
+        .into_iter()
+        .map(|(enchanted_word, _, compression_savings)| {
+            (
+                String::from_utf8_lossy(enchanted_word).into_owned(),
+                compression_savings,
+            )
+        })
+        .collect()
+}
+
+/// Число слов словаря без бюджета
+const DEFAULT_GRIMOIRE_WORDS: usize = 25;
+
+/// Предельное число слов при отборе по бюджету
+///
+/// В шестнадцать раз меньше предела словаря при распаковке
+/// ([`DecodeOptions::DEFAULT_MAX_DICT_ENTRIES`](crate::decompression_oracle::decode_wards::DecodeOptions::DEFAULT_MAX_DICT_ENTRIES)),
+/// так что архив с таким словарем читается с настройками по умолчанию.
+pub(crate) const MAX_BUDGETED_WORDS: usize = 4096;
+
+/// Байты заголовка на слово сверх его букв: длина `u32`
+pub(crate) const GRIMOIRE_ENTRY_OVERHEAD: usize = 4;
+
+/// Байты строки таблицы частот кадра: `[символ u32][частота u64][начало u64]`
+const FREQUENCY_ROW_COST: f64 = 20.0;
+
+/// Слово входа с частотой 🧮
+#[derive(Debug, Clone, Copy)]
+struct WordTally<'a> {
+    word: &'a [u8],
+    /// Сколько раз слово встретилось
+    frequency: u64,
+    /// Сколько из них с пробелом сразу после слова
+    spaced: u64,
+}
+
+/// Оценка выгоды слов по энтропии нулевого порядка 📐
+///
+/// Поток из `n` символов с частотами `cᵢ` стоит `n·log2 n − Σ cᵢ·log2 cᵢ`
+/// бит. Слово длины `l` с частотой `f`, за которым `s` раз идет пробел,
+/// убирает `f` вхождений каждой своей буквы и `s` пробелов из таблицы
+/// литералов, добавляет символы слова с частотами `s` и `f − s` и
+/// укорачивает поток до `n − f·l − s + f` символов.
+///
+/// Заголовок считается точно по раскладке кадра: буквы слова и длина
+/// `u32`, строка таблицы частот на каждый из двух символов слова, который
+/// встречается, минус строки литералов, которые слово вытеснило целиком.
+#[derive(Debug, Clone, PartialEq)]
+struct LiteralCensus {
+    histogram: [u64; 256],
+    symbol_count: u64,
+}
+
+impl LiteralCensus {
+    fn new(manuscript_bytes: &[u8]) -> Self {
+        Self {
+            histogram: byte_histogram(manuscript_bytes),
+            symbol_count: manuscript_bytes.len() as u64,
+        }
+    }
+
+    /// Литералы, которые заменяет слово: буквы и поглощенные пробелы
+    fn replaced_literals(tally: &WordTally) -> Vec<(u8, u64)> {
+        let mut letter_counts: Vec<(u8, u64)> = Vec::with_capacity(tally.word.len() + 1);
+        let letters = tally
+            .word
+            .iter()
+            .map(|&letter| (letter, tally.frequency))
+            .chain((tally.spaced > 0).then_some((b' ', tally.spaced)));
+        for (letter, count) in letters {
+            match letter_counts.iter_mut().find(|(known, _)| *known == letter) {
+                Some((_, total)) => *total += count,
+                None => letter_counts.push((letter, count)),
+            }
+        }
+        letter_counts
+    }
+
+    /// Убирает из переписи литералы слова
+    ///
+    /// Приближенная частота может превышать оставшиеся литералы: убирается
+    /// не больше, чем есть, и ровно это возвращает [`Self::restore`].
+    fn remove(&mut self, tally: &WordTally) -> CensusRemoval {
+        let letters = Self::replaced_literals(tally)
+            .into_iter()
+            .map(|(letter, count)| {
+                let slot = &mut self.histogram[letter as usize];
+                let taken = count.min(*slot);
+                *slot -= taken;
+                (letter, taken)
+            })
+            .collect();
+        let removed = tally.frequency * tally.word.len() as u64 + tally.spaced;
+        let symbol_count_after = (self.symbol_count + tally.frequency).saturating_sub(removed);
+        let symbols = self.symbol_count - symbol_count_after;
+        self.symbol_count = symbol_count_after;
+        CensusRemoval { letters, symbols }
+    }
+
+    /// Возвращает в перепись убранное [`Self::remove`]
+    fn restore(&mut self, removal: &CensusRemoval) {
+        for &(letter, taken) in &removal.letters {
+            self.histogram[letter as usize] += taken;
+        }
+        self.symbol_count += removal.symbols;
+    }
+
+    /// Изменение размера архива в байтах, если слово войдет в словарь
+    /// (положительное — архив уменьшится)
+    fn word_savings(&self, tally: &WordTally) -> f64 {
+        let removed = tally.frequency * tally.word.len() as u64 + tally.spaced;
+        if removed > self.symbol_count || tally.spaced > tally.frequency {
+            // Приближенный подсчет завысил частоту
+            return f64::NEG_INFINITY;
+        }
+        let symbol_count_after = self.symbol_count - removed + tally.frequency;
+
+        let mut saved_bits = weighted_log2(self.symbol_count) - weighted_log2(symbol_count_after)
+            + weighted_log2(tally.spaced)
+            + weighted_log2(tally.frequency - tally.spaced);
+        let mut freed_rows = 0;
+        for (letter, count) in Self::replaced_literals(tally) {
+            let before = self.histogram[letter as usize];
+            let after = before.saturating_sub(count);
+            saved_bits += weighted_log2(after) - weighted_log2(before);
+            freed_rows += (before > 0 && after == 0) as u32;
+        }
+
+        let word_rows = (tally.spaced > 0) as u32 + (tally.spaced < tally.frequency) as u32;
+        let header_bytes = (tally.word.len() + GRIMOIRE_ENTRY_OVERHEAD) as f64
+            + (word_rows as f64 - freed_rows as f64) * FREQUENCY_ROW_COST;
+        saved_bits / 8.0 - header_bytes
+    }
+}
+
+/// Что слово фактически убрало из переписи литералов
+#[derive(Debug)]
+struct CensusRemoval {
+    /// Буквы и пробел с убранным числом вхождений
+    letters: Vec<(u8, u64)>,
+    /// На сколько сократился поток символов
+    symbols: u64,
+}
+
+/// `x·log2 x` (ноль для нуля)
+fn weighted_log2(count: u64) -> f64 {
+    if count == 0 {
+        0.0
+    } else {
+        count as f64 * (count as f64).log2()
+    }
+}
+
+/// Слово-кандидат с оценкой экономии в байтах
+#[derive(Debug, Clone, Copy)]
+struct WordCandidate<'a> {
+    tally: WordTally<'a>,
+    savings: f64,
+}
+
+/// Выгодные слова с оценкой экономии в байтах (первый проход)
+///
+/// Каждое слово оценивается по исходной переписи литералов, независимо
+/// от остальных.
+fn estimate_word_candidates<'a>(
+    literal_census: &LiteralCensus,
+    word_frequencies: Vec<WordTally<'a>>,
+) -> Vec<WordCandidate<'a>> {
+    word_frequencies
+        .into_iter()
+        .filter(|tally| tally.frequency >= 2)
+        .filter_map(|tally| {
+            let savings = literal_census.word_savings(&tally);
+            (savings > 0.0).then_some(WordCandidate { tally, savings })
+        })
+        .collect()
+}
+
+/// Пересчитывает выгоду отобранных слов вместе (второй проход) 🔁
+///
+/// Выгода каждого слова — по переписи, из которой уже убраны остальные
+/// отобранные слова: общие буквы дешевеют, а строку литерала освобождает
+/// только последнее слово, которое его использует. Слова, переставшие
+/// окупаться, отбрасываются; пока отбрасывается хоть одно, пересчет
+/// повторяется.
+fn rescore_together(literal_census: &LiteralCensus, selected: &mut Vec<WordCandidate<'_>>) {
+    loop {
+        let mut census_with_all = literal_census.clone();
+        let mut removals: Vec<CensusRemoval> = selected
+            .iter()
+            .map(|candidate| census_with_all.remove(&candidate.tally))
+            .collect();
+        for (candidate, removal) in selected.iter_mut().zip(&mut removals) {
+            census_with_all.restore(removal);
+            candidate.savings = census_with_all.word_savings(&candidate.tally);
+            *removal = census_with_all.remove(&candidate.tally);
+        }
+        let before = selected.len();
+        selected.retain(|candidate| candidate.savings > 0.0);
+        if selected.len() == before {
+            return;
+        }
+    }
+}
+
+/// Отобранные слова по убыванию выгоды, равные — по алфавиту
+fn ranked_selection<'a>(mut selected: Vec<WordCandidate<'a>>) -> Vec<(&'a [u8], u64, i64)> {
+    selected.sort_by(|left, right| {
+        right
+            .savings
+            .total_cmp(&left.savings)
+            .then_with(|| left.tally.word.cmp(right.tally.word))
+    });
+    selected
+        .into_iter()
+        .map(|candidate| {
+            (
+                candidate.tally.word,
+                candidate.tally.frequency,
+                candidate.savings as i64,
+            )
+        })
+        .collect()
+}
+
+/// Самые выгодные слова без бюджета: не больше 25
+fn select_most_profitable_words<'a>(
+    manuscript_bytes: &[u8],
+    word_frequencies: Vec<WordTally<'a>>,
+) -> Vec<(&'a [u8], u64, i64)> {
+    let literal_census = LiteralCensus::new(manuscript_bytes);
+    let mut candidates = estimate_word_candidates(&literal_census, word_frequencies);
+    // Равные по выгоде — по алфавиту, чтобы словарь не зависел от обхода HashMap
+    candidates.sort_by(|left, right| {
+        right
+            .savings
+            .total_cmp(&left.savings)
+            .then_with(|| left.tally.word.cmp(right.tally.word))
+    });
+    candidates.truncate(DEFAULT_GRIMOIRE_WORDS);
+
+    rescore_together(&literal_census, &mut candidates);
+    ranked_selection(candidates)
+}
+
+/// Отбор слов в пределах бюджета заголовка 💰
+///
+/// Бюджет (длина и буквы слова) заполняется жадно: сначала слова с
+/// наибольшей экономией на байт заголовка.
+fn select_words_within_budget<'a>(
+    manuscript_bytes: &[u8],
+    word_frequencies: Vec<WordTally<'a>>,
+    budget_bytes: usize,
+) -> Vec<(&'a [u8], u64, i64)> {
+    let header_bytes = |enchanted_word: &[u8]| enchanted_word.len() + GRIMOIRE_ENTRY_OVERHEAD;
+    let literal_census = LiteralCensus::new(manuscript_bytes);
+    let mut candidates = estimate_word_candidates(&literal_census, word_frequencies);
+
+    // Самые выгодные на байт заголовка первыми; равные — по алфавиту
+    let density =
+        |candidate: &WordCandidate| candidate.savings / header_bytes(candidate.tally.word) as f64;
+    candidates.sort_by(|left, right| {
+        density(right)
+            .total_cmp(&density(left))
+            .then_with(|| left.tally.word.cmp(right.tally.word))
+    });
+
+    let mut remaining_budget = budget_bytes;
+    let mut selected_words: Vec<WordCandidate> = Vec::new();
+    for candidate in candidates {
+        if selected_words.len() == MAX_BUDGETED_WORDS {
+            break;
+        }
+        if header_bytes(candidate.tally.word) <= remaining_budget {
+            remaining_budget -= header_bytes(candidate.tally.word);
+            selected_words.push(candidate);
+        }
+    }
+
+    rescore_together(&literal_census, &mut selected_words);
+    ranked_selection(selected_words)
+}
+
+/// Смысл символа при восстановлении 🔤
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SymbolMeaning {
+    /// Обычный байт
+    Byte(u8),
+    /// Слово словаря, возможно с пробелом после него
+    Word {
+        /// Индекс в словаре
+        index: usize,
+        /// За словом следует пробел
+        trailing_space: bool,
+    },
+    /// Фраза (индекс не проверен: фраз может быть меньше)
+    Phrase {
+        /// Индекс в таблице фраз
+        index: usize,
+    },
+}
+
+/// Символ ссылки на слово словаря
+///
+/// Для словаря из `n` слов: `256 + индекс` — слово,
+/// `256 + n + индекс` — слово с пробелом после него.
+pub(crate) fn word_symbol(grimoire_index: usize, grimoire_len: usize, trailing_space: bool) -> u32 {
+    let spaced_offset = if trailing_space { grimoire_len } else { 0 };
+    WORD_SYMBOL_BASE + (spaced_offset + grimoire_index) as u32
+}
+
+/// Символ ссылки на фразу: `256 + 2n + индекс`
+pub(crate) fn phrase_symbol(phrase_index: usize, grimoire_len: usize) -> u32 {
+    WORD_SYMBOL_BASE + (2 * grimoire_len + phrase_index) as u32
+}
+
+/// Размер алфавита: байты, слова и слова с пробелом
+pub(crate) fn alphabet_size(grimoire_len: usize) -> usize {
+    WORD_SYMBOL_BASE as usize + 2 * grimoire_len
+}
+
+/// Расшифровывает символ для словаря из `grimoire_len` слов
+pub(crate) fn interpret_symbol(symbol: u32, grimoire_len: usize) -> SymbolMeaning {
+    if symbol < WORD_SYMBOL_BASE {
+        return SymbolMeaning::Byte(symbol as u8);
+    }
+
+    let reference = (symbol - WORD_SYMBOL_BASE) as usize;
+    if reference < grimoire_len {
+        SymbolMeaning::Word {
+            index: reference,
+            trailing_space: false,
+        }
+    } else if reference < 2 * grimoire_len {
+        SymbolMeaning::Word {
+            index: reference - grimoire_len,
+            trailing_space: true,
+        }
+    } else {
+        SymbolMeaning::Phrase {
+            index: reference - 2 * grimoire_len,
+        }
+    }
+}
+
+/// Преобразует текст в символы, заменяя слова ссылками на словарь
+///
+/// Кодирование (для словаря из `n` слов):
+/// - 0-255: обычные байты
+/// - 256..256+n: ссылки на словарь (256 + индекс)
+/// - 256+n..256+2n: слово и пробел после него одним символом
+pub(crate) fn transform_manuscript_to_symbols(
+    manuscript_bytes: &[u8],
+    word_grimoire: &[String],
+) -> Vec<u32> {
+    transform_manuscript_to_symbols_within(
+        manuscript_bytes,
+        word_grimoire,
+        &WordBoundaries::default(),
+    )
+}
+
+/// Жадная подстановка слов в границах политики `word_boundaries`
+pub(crate) fn transform_manuscript_to_symbols_within(
+    manuscript_bytes: &[u8],
+    word_grimoire: &[String],
+    word_boundaries: &WordBoundaries,
+) -> Vec<u32> {
+    let mut symbolic_sequence = Vec::new();
+    transform_manuscript_into(
+        manuscript_bytes,
+        word_grimoire,
+        word_boundaries,
+        &mut symbolic_sequence,
+    );
+    symbolic_sequence
+}
+
+/// Жадная подстановка слов с записью символов в конец `symbolic_sequence`
+///
+/// Без словаря символы — просто байты: дерево слов не строится.
+pub(crate) fn transform_manuscript_into(
+    manuscript_bytes: &[u8],
+    word_grimoire: &[String],
+    word_boundaries: &WordBoundaries,
+    symbolic_sequence: &mut Vec<u32>,
+) {
+    if word_grimoire.is_empty() {
+        symbolic_sequence.extend(manuscript_bytes.iter().map(|&byte| byte as u32));
+        return;
+    }
+
+    let grimoire_trie = GrimoireTrie::new(word_grimoire);
+    let mut byte_position = 0;
+
+    while byte_position < manuscript_bytes.len() {
+        let current_byte = manuscript_bytes[byte_position];
+        let discovered_word = word_boundaries
+            .is_word_start(manuscript_bytes, byte_position)
+            .then(|| {
+                grimoire_trie.find_word(manuscript_bytes, byte_position, |word_end_position| {
+                    word_boundaries.is_word_end(manuscript_bytes, word_end_position)
+                })
+            })
+            .flatten();
+
+        match discovered_word {
+            Some((grimoire_index, word_end_position)) => {
+                // Пробел после слова поглощается тем же символом
+                let trailing_space = manuscript_bytes.get(word_end_position) == Some(&b' ');
+
+                // Заменяем ссылкой на словарь
+                symbolic_sequence.push(word_symbol(
+                    grimoire_index,
+                    word_grimoire.len(),
+                    trailing_space,
+                ));
+                byte_position = word_end_position + trailing_space as usize;
+            }
+            None => {
+                // Если слово не найдено, добавляем байт как есть
+                symbolic_sequence.push(current_byte as u32);
+                byte_position += 1;
+            }
+        }
+    }
+}
+
+/// Оптимальный разбор текста на слова словаря и байты 🧭
+///
+/// Цена символа — `log2(n / частота)` по символам жадного разбора (символ,
+/// которого жадный разбор не дал, считается встреченным полраза). Кратчайший
+/// путь по позициям текста перебирает байт и каждое подходящее слово — с
+/// пробелом после него и без. Из жадного и оптимального разборов остается
+/// тот, чья энтропия вместе с таблицей частот меньше.
+pub(crate) fn transform_manuscript_to_symbols_optimal(
+    manuscript_bytes: &[u8],
+    word_grimoire: &[String],
+    word_boundaries: &WordBoundaries,
+) -> Vec<u32> {
+    let greedy_symbols =
+        transform_manuscript_to_symbols_within(manuscript_bytes, word_grimoire, word_boundaries);
+    if word_grimoire.is_empty() {
+        return greedy_symbols;
+    }
+
+    let greedy_census: HashMap<u32, u64> = symbol_census(&greedy_symbols).into_iter().collect();
+    let symbol_count = greedy_symbols.len().max(1) as f64;
+    let symbol_bits = |symbol: u32| {
+        let frequency = greedy_census
+            .get(&symbol)
+            .map_or(0.5, |&count| count as f64);
+        (symbol_count / frequency).log2()
+    };
+
+    // Лучшая цена пути до позиции и последний шаг: (откуда, символ)
+    let grimoire_trie = GrimoireTrie::new(word_grimoire);
+    let mut path_bits = vec![f64::INFINITY; manuscript_bytes.len() + 1];
+    let mut last_step = vec![(0usize, 0u32); manuscript_bytes.len() + 1];
+    path_bits[0] = 0.0;
+
+    for byte_position in 0..manuscript_bytes.len() {
+        let bits_so_far = path_bits[byte_position];
+        let mut relax = |next_position: usize, symbol: u32| {
+            let candidate_bits = bits_so_far + symbol_bits(symbol);
+            if candidate_bits < path_bits[next_position] {
+                path_bits[next_position] = candidate_bits;
+                last_step[next_position] = (byte_position, symbol);
+            }
+        };
+
+        relax(byte_position + 1, manuscript_bytes[byte_position] as u32);
+        if !word_boundaries.is_word_start(manuscript_bytes, byte_position) {
+            continue;
+        }
+        grimoire_trie.for_each_word(
+            manuscript_bytes,
+            byte_position,
+            |grimoire_index, word_end_position| {
+                if !word_boundaries.is_word_end(manuscript_bytes, word_end_position) {
+                    return;
+                }
+                relax(
+                    word_end_position,
+                    word_symbol(grimoire_index, word_grimoire.len(), false),
+                );
+                if manuscript_bytes.get(word_end_position) == Some(&b' ') {
+                    relax(
+                        word_end_position + 1,
+                        word_symbol(grimoire_index, word_grimoire.len(), true),
+                    );
+                }
+            },
+        );
+    }
+
+    let mut optimal_symbols = Vec::new();
+    let mut byte_position = manuscript_bytes.len();
+    while byte_position > 0 {
+        let (previous_position, symbol) = last_step[byte_position];
+        optimal_symbols.push(symbol);
+        byte_position = previous_position;
+    }
+    optimal_symbols.reverse();
+
+    if estimated_stream_bits(&optimal_symbols) < estimated_stream_bits(&greedy_symbols) {
+        optimal_symbols
+    } else {
+        greedy_symbols
+    }
+}
+
+/// Оценка размера потока в битах: энтропия и строки таблицы частот
+fn estimated_stream_bits(symbols: &[u32]) -> f64 {
+    let census = symbol_census(symbols);
+    let entropy_bits = weighted_log2(symbols.len() as u64)
+        - census
+            .iter()
+            .map(|&(_, count)| weighted_log2(count))
+            .sum::<f64>();
+    entropy_bits + census.len() as f64 * FREQUENCY_ROW_COST * 8.0
+}
+
+/// Строит таблицу частот для арифметического кодирования 🔍📊
+/// Использует эффективное заимствование срезов без копирования данных
+pub(crate) fn analyze_symbolic_frequencies(
+    symbolic_incantations: &[u32],
+) -> WizardResult<FrequencyAnalysisWisdom> {
+    // Подсчет частот (по возрастанию id, с фичей `rayon` — параллельно)
+    let frequency_pairs = symbol_census(symbolic_incantations);
+    build_frequency_table(&frequency_pairs, CoderPrecision::default())
+}
+
+/// Строит накопительную таблицу частот с проверками 🛡️
+///
+/// Символы должны идти строго по возрастанию id (повтор — ошибка),
+/// частоты — быть ненулевыми, число записей — умещаться в счетчик u32
+/// раздела частот, а накопительные суммы — в точность кодера без
+/// переполнения u64.
+pub(crate) fn build_frequency_table(
+    frequency_pairs: &[(u32, u64)],
+    precision: CoderPrecision,
+) -> WizardResult<FrequencyAnalysisWisdom> {
+    let mut frequency_entries = Vec::with_capacity(frequency_pairs.len());
+    let total_frequency_mass =
+        build_frequency_table_into(frequency_pairs, precision, &mut frequency_entries)?;
+    Ok(FrequencyAnalysisWisdom {
+        frequency_entries,
+        total_frequency_mass,
+    })
+}
+
+/// Строит накопительную таблицу в `frequency_entries` (прежние записи
+/// удаляются) и возвращает общую массу частот
+pub(crate) fn build_frequency_table_into(
+    frequency_pairs: &[(u32, u64)],
+    precision: CoderPrecision,
+    frequency_entries: &mut Vec<(u32, u64, u64)>,
+) -> WizardResult<u64> {
+    if frequency_pairs.len() > u32::MAX as usize {
+        return Err(WizardError::LimitExceeded {
+            kind: LimitKind::TableEntries,
+            requested: frequency_pairs.len() as u64,
+            allowed: u32::MAX as u64,
+        });
+    }
+
+    // Накопительная таблица для интервалов
+    let mut cumulative_position = 0u64;
+    frequency_entries.clear();
+    frequency_entries.reserve(frequency_pairs.len());
+    for (index, &(symbol_id, frequency_count)) in frequency_pairs.iter().enumerate() {
+        if index > 0 && frequency_pairs[index - 1].0 >= symbol_id {
+            return Err(WizardError::CorruptFrequencyTable(
+                "символы повторяются или не упорядочены",
+            ));
+        }
+        if frequency_count == 0 {
+            return Err(WizardError::CorruptFrequencyTable(
+                "нулевая частота символа",
+            ));
+        }
+
+        frequency_entries.push((symbol_id, frequency_count, cumulative_position));
+        cumulative_position =
+            cumulative_position
+                .checked_add(frequency_count)
+                .ok_or(WizardError::LimitExceeded {
+                    kind: LimitKind::FrequencyMass,
+                    requested: u64::MAX,
+                    allowed: precision.max_total_frequency(),
+                })?;
+    }
+    check_frequency_mass(cumulative_position, precision)?;
+    Ok(cumulative_position)
+}
+
+/// Тесты алгоритмов сжатия 🎯
+#[cfg(test)]
+mod compression_conjurer_tests {
+    use super::*;
+    use crate::compression_engine::word_frontier::{NoBoundary, UnicodeBoundary};
+
+    /// Проверка словаря
+    #[test]
+    fn test_word_discovery_functionality() {
+        let sample_text = b"the quick brown fox jumps over the lazy dog the end the beginning the world the universe the magic the power";
+        let discovered_words = discover_profitable_word_enchantments(
+            sample_text,
+            &WordCounting::Exact,
+            &DenyList::default(),
+        );
+
+        // "the" должно попасть в словарь
+        assert!(discovered_words.contains(&"the".to_string()));
+    }
+
+    /// Словарь повторяющегося текста
+    #[test]
+    fn test_word_discovery_on_repeated_text() {
+        let sample_text = "the quick brown fox jumps over the lazy dog the end the beginning the world the universe the magic the power. ".repeat(10);
+        let discovered_words = discover_profitable_word_enchantments(
+            sample_text.as_bytes(),
+            &WordCounting::Exact,
+            &DenyList::default(),
+        );
+
+        assert!(discovered_words.contains(&"the".to_string()));
+    }
+
+    /// Проверка символьного кодирования
+    #[test]
+    fn test_symbol_transformation() {
+        let test_data = b"hello world hello";
+        let word_dict = vec!["hello".to_string()];
+        let symbols = transform_manuscript_to_symbols(test_data, &word_dict);
+
+        // Ссылки на словарь (256+) и обычные байты
+        let mut expected = vec![257u32]; // "hello " одним символом
+        expected.extend(b"world ".iter().map(|&byte| byte as u32));
+        expected.push(256); // "hello" в конце, без пробела
+        assert_eq!(symbols, expected);
+    }
+
+    /// Неиспользованные слова удаляются, символы перенумеровываются
+    #[test]
+    fn test_unused_words_are_pruned() {
+        let text = b"hatter wand hatter wand";
+        // "hat" не совпадает внутри "hatter" из-за границ слова
+        let grimoire = vec!["hat".to_string(), "wand".to_string(), "hatter".to_string()];
+        let symbols = transform_manuscript_to_symbols(text, &grimoire);
+
+        let (pruned_symbols, pruned_grimoire) = prune_unused_words(symbols, grimoire);
+
+        assert_eq!(
+            pruned_grimoire,
+            vec!["wand".to_string(), "hatter".to_string()]
+        );
+        assert_eq!(
+            pruned_symbols,
+            transform_manuscript_to_symbols(text, &pruned_grimoire)
+        );
+    }
+
+    /// В архив попадают только слова, на которые есть ссылки
+    #[test]
+    fn test_every_stored_word_is_referenced() {
+        let mut text =
+            b"the wizard and the owl read the scroll while the wizard sleeps. ".repeat(20);
+        text.extend_from_slice(&b"thereafter theirs thesis ".repeat(10));
+        for config in [
+            EngineConfig::default(),
+            EngineConfig::default().with_parse_strategy(ParseStrategy::Optimal),
+            EngineConfig::default().with_grimoire(
+                crate::compression_engine::Grimoire::from_entries([
+                    "wizard", "owl", "never", "scroll", "absent",
+                ]),
+            ),
+        ] {
+            let artifact = try_weave_with_discovery(&text, &config).unwrap();
+            let symbols = transform_manuscript_to_symbols(&text, &artifact.mystical_word_grimoire);
+            let references =
+                word_reference_counts(&symbols, artifact.mystical_word_grimoire.len(), &[]);
+            assert!(!references.is_empty());
+            assert!(references.iter().all(|&count| count > 0), "{references:?}");
+            assert!(artifact
+                .word_effectiveness()
+                .iter()
+                .all(|entry| entry.occurrences > 0));
+        }
+
+        // Фраза ссылается на каждое свое слово
+        let len = 2;
+        let phrase = vec![word_symbol(0, len, true), word_symbol(1, len, false)];
+        let symbols = [
+            phrase_symbol(0, len),
+            phrase_symbol(0, len),
+            word_symbol(1, len, false),
+        ];
+        assert_eq!(word_reference_counts(&symbols, len, &[phrase]), [2, 3]);
+    }
+
+    /// Польза слов измеряется по итоговой модели
+    #[test]
+    fn test_word_effectiveness() {
+        let artifact = weave_compression_spell(
+            b"magic words and magic spells and magic wands and magic hats and magic",
+        );
+        let report = artifact.word_effectiveness();
+
+        assert_eq!(report.len(), artifact.mystical_word_grimoire.len());
+        let magic = artifact
+            .mystical_word_grimoire
+            .iter()
+            .position(|word| word == "magic")
+            .unwrap();
+        assert_eq!(report[magic].occurrences, 5);
+        assert_eq!(report[magic].replaced_bytes, 5 * 5 + 4);
+        assert!(report.iter().all(|entry| entry.occurrences > 0));
+        assert!(report[magic].coded_bits > 0.0);
+    }
+
+    /// Польза слов в повторяющемся тексте
+    #[test]
+    fn test_word_effectiveness_on_repeated_text() {
+        let artifact = weave_compression_spell(
+            "magic words and magic spells and magic wands and magic hats and magic. ".repeat(10),
+        );
+        let report = artifact.word_effectiveness();
+
+        let magic = artifact
+            .mystical_word_grimoire
+            .iter()
+            .position(|word| word == "magic")
+            .unwrap();
+        assert_eq!(report[magic].occurrences, 50);
+        // 40 вхождений поглощают пробел после слова
+        assert_eq!(report[magic].replaced_bytes, 50 * 5 + 40);
+    }
+
+    /// Проверка расшифровки символов
+    #[test]
+    fn test_symbol_interpretation() {
+        assert_eq!(interpret_symbol(65, 2), SymbolMeaning::Byte(b'A'));
+        assert_eq!(
+            interpret_symbol(word_symbol(1, 2, true), 2),
+            SymbolMeaning::Word {
+                index: 1,
+                trailing_space: true
+            }
+        );
+        assert_eq!(
+            interpret_symbol(phrase_symbol(0, 2), 2),
+            SymbolMeaning::Phrase { index: 0 }
+        );
+        assert_eq!(alphabet_size(2), 260);
+    }
+
+    /// Проверка подсчета частот
+    #[test]
+    fn test_frequency_analysis() {
+        let symbols = vec![65u32, 66u32, 65u32]; // A, B, A
+        let analysis = analyze_symbolic_frequencies(&symbols).unwrap();
+
+        assert_eq!(analysis.total_frequency_mass, 3);
+        assert_eq!(analysis.frequency_entries.len(), 2);
+    }
+
+    /// Патологические таблицы дают ошибки, а не переполнение
+    #[test]
+    fn test_frequency_table_rejects_pathological_input() {
+        let precision = CoderPrecision::default();
+        let allowed = precision.max_total_frequency();
+
+        // Масса больше точности кодера (вход в несколько гигабайт)
+        assert_eq!(
+            build_frequency_table(&[(65, allowed), (66, 1)], precision).unwrap_err(),
+            WizardError::LimitExceeded {
+                kind: LimitKind::FrequencyMass,
+                requested: allowed + 1,
+                allowed,
+            }
+        );
+        // Переполнение u64 накопительной суммы
+        assert!(matches!(
+            build_frequency_table(&[(65, u64::MAX), (66, 1)], precision),
+            Err(WizardError::LimitExceeded {
+                kind: LimitKind::FrequencyMass,
+                ..
+            })
+        ));
+        // Повтор символа и нулевая частота
+        assert!(matches!(
+            build_frequency_table(&[(65, 1), (65, 2)], precision),
+            Err(WizardError::CorruptFrequencyTable(_))
+        ));
+        assert!(matches!(
+            build_frequency_table(&[(65, 1), (66, 0)], precision),
+            Err(WizardError::CorruptFrequencyTable(_))
+        ));
+
+        // Масса, усекаемая приведением к u32, не кодируется
+        let wrapped_total = (1u64 << 32) + 2;
+        assert!(matches!(
+            encode_symbol_stream(&[65], &[(65, 1, 0)], wrapped_total, precision),
+            Err(WizardError::LimitExceeded { .. })
+        ));
+    }
+
+    /// Приближенный подсчет находит тот же словарь, что и точный
+    #[test]
+    fn test_sketch_counting_matches_exact_dictionary() {
+        use crate::compression_engine::word_sketch::SketchParams;
+        use std::collections::HashSet;
+
+        let manuscript = "the wizard and the owl read the ancient scroll twice. ".repeat(50);
+        let exact: HashSet<String> = discover_profitable_word_enchantments(
+            manuscript.as_bytes(),
+            &WordCounting::Exact,
+            &DenyList::default(),
+        )
+        .into_iter()
+        .collect();
+        let sketched: HashSet<String> = discover_profitable_word_enchantments(
+            manuscript.as_bytes(),
+            &WordCounting::Sketch(SketchParams::default()),
+            &DenyList::default(),
+        )
+        .into_iter()
+        .collect();
+        assert!(!exact.is_empty());
+        assert_eq!(sketched, exact);
+    }
+
+    /// Словарь по оценке энтропии не увеличивает архив
+    #[test]
+    fn test_selected_words_never_grow_archive() {
+        use crate::simple_api::{compress_data, compress_data_redacted};
+
+        let without_words = DenyList::new().with_matcher(|_| true);
+        for manuscript in [
+            "magic words and magic spells and magic wands and magic hats and magic".to_string(),
+            "the cat and the dog and the bird and the fish and the end".repeat(3),
+            "the wizard casts the spell. ".repeat(12),
+            "abcabc xyzxyz abcabc xyzxyz ".repeat(30),
+            "the quick brown fox jumps over the lazy dog. ".repeat(40),
+        ] {
+            let with_words = compress_data(&manuscript).len();
+            let literal_only = compress_data_redacted(&manuscript, &without_words).len();
+            assert!(with_words <= literal_only, "{manuscript:?}");
+        }
+    }
+
+    /// Оценка выгоды слова совпадает с уменьшением архива до байта
+    #[test]
+    fn test_word_savings_match_archive_bytes() {
+        use crate::compression_engine::grimoire_quill::Grimoire;
+        use crate::simple_api::try_compress_with_config;
+
+        let manuscript = "the quick brown fox jumps over the lazy dog. ".repeat(40);
+        let manuscript = manuscript.as_bytes();
+        let archive_len = |grimoire: Grimoire| {
+            try_compress_with_config(manuscript, &EngineConfig::default().with_grimoire(grimoire))
+                .unwrap()
+                .len() as f64
+        };
+        let without_words = archive_len(Grimoire::default());
+        let census = LiteralCensus::new(manuscript);
+
+        // "the" дважды на фразу: с пробелом и перед "lazy"; "dog." без пробела
+        for (word, spaced) in [("the", 80), ("fox", 40), ("over", 40), ("dog", 0)] {
+            let tally = WordTally {
+                word: word.as_bytes(),
+                frequency: 40 * (1 + (word == "the") as u64),
+                spaced,
+            };
+            let actual = without_words - archive_len(Grimoire::from_entries([word]));
+            let estimated = census.word_savings(&tally);
+            assert!(
+                (estimated - actual).abs() <= 2.0,
+                "{word}: {estimated} vs {actual}"
+            );
+        }
+    }
+
+    /// Завышенные частоты скетча не портят перепись при возврате слова
+    #[test]
+    fn test_census_restores_exactly_what_was_removed() {
+        let census = LiteralCensus::new(b"the owl and the cat. the end.");
+        // Частоты выше настоящих: букв во входе меньше, чем заменяют слова
+        let overcounted = [
+            WordTally {
+                word: b"the",
+                frequency: 9,
+                spaced: 9,
+            },
+            WordTally {
+                word: b"end",
+                frequency: 4,
+                spaced: 0,
+            },
+        ];
+
+        let mut with_both = census.clone();
+        let removals: Vec<CensusRemoval> = overcounted
+            .iter()
+            .map(|tally| with_both.remove(tally))
+            .collect();
+        assert_eq!(with_both.histogram[b'e' as usize], 0);
+
+        // Возврат и повторное удаление одного слова — как во втором проходе
+        let mut rescored = with_both.clone();
+        rescored.restore(&removals[0]);
+        rescored.remove(&overcounted[0]);
+        assert_eq!(rescored, with_both);
+
+        for removal in removals.iter().rev() {
+            with_both.restore(removal);
+        }
+        assert_eq!(with_both, census);
+    }
+
+    /// Второй проход оценивает слово рядом с остальными отобранными
+    #[test]
+    fn test_rescored_savings_match_leave_one_out() {
+        use crate::compression_engine::grimoire_quill::Grimoire;
+        use crate::simple_api::try_compress_with_config;
+
+        // Литерал между словами: цепочек слов для фраз нет
+        let manuscript = "the -quick -brown -fox -jumps -over -the -lazy -dog. ".repeat(40);
+        let manuscript = manuscript.as_bytes();
+        let words = [
+            "the", "quick", "brown", "fox", "jumps", "over", "lazy", "dog",
+        ];
+        let archive_len = |skipped: &str| {
+            let grimoire =
+                Grimoire::from_entries(words.into_iter().filter(|&word| word != skipped));
+            try_compress_with_config(manuscript, &EngineConfig::default().with_grimoire(grimoire))
+                .unwrap()
+                .len() as f64
+        };
+        let census = LiteralCensus::new(manuscript);
+        let tallies = words
+            .map(|word| {
+                let frequency = 40 * (1 + (word == "the") as u64);
+                WordTally {
+                    word: word.as_bytes(),
+                    frequency,
+                    spaced: frequency * (word != "dog") as u64,
+                }
+            })
+            .to_vec();
+
+        let mut selected = estimate_word_candidates(&census, tallies);
+        let alone: Vec<f64> = selected.iter().map(|candidate| candidate.savings).collect();
+        rescore_together(&census, &mut selected);
+        assert_eq!(selected.len(), words.len());
+
+        let with_all = archive_len("");
+        let mut worst_alone_error: f64 = 0.0;
+        for (candidate, alone) in selected.iter().zip(alone) {
+            let word = std::str::from_utf8(candidate.tally.word).unwrap();
+            let actual = archive_len(word) - with_all;
+            assert!(
+                (candidate.savings - actual).abs() <= 2.0,
+                "{word}: {} vs {actual}",
+                candidate.savings
+            );
+            worst_alone_error = worst_alone_error.max((alone - actual).abs());
+        }
+        // Поодиночке строку буквы, общей с другими словами, освобождает
+        // каждое слово: первый проход ошибается на десятки байтов
+        assert!(worst_alone_error > 40.0);
+    }
+
+    /// Оптимальный разбор находит длинное слово там, где жадный берет префикс
+    #[test]
+    fn test_optimal_parse_prefers_cheaper_overlaps() {
+        use crate::decompression_oracle::decode_wards::DecodeOptions;
+        use crate::decompression_oracle::decompression_sage::reconstruct_original_manuscript;
+
+        // Без границ слова перекрываются: «the» — префикс «there»
+        let anywhere = WordBoundaries::new(NoBoundary);
+        let manuscript = "there and here, then the theme. ".repeat(20);
+        let grimoire = vec!["the".to_string(), "there".to_string(), "here".to_string()];
+        let greedy =
+            transform_manuscript_to_symbols_within(manuscript.as_bytes(), &grimoire, &anywhere);
+        let optimal =
+            transform_manuscript_to_symbols_optimal(manuscript.as_bytes(), &grimoire, &anywhere);
+
+        assert!(optimal.len() < greedy.len());
+        assert!(estimated_stream_bits(&optimal) < estimated_stream_bits(&greedy));
+        let restored =
+            reconstruct_original_manuscript(&optimal, &grimoire, &[], &DecodeOptions::unlimited())
+                .unwrap();
+        assert_eq!(restored, manuscript.as_bytes());
+
+        // Без перекрытий оптимальный разбор совпадает с жадным
+        let plain = b"magic wand and magic hat";
+        let grimoire = vec!["magic".to_string()];
+        assert_eq!(
+            transform_manuscript_to_symbols_optimal(plain, &grimoire, &WordBoundaries::default()),
+            transform_manuscript_to_symbols(plain, &grimoire)
+        );
+    }
+
+    /// Поиск и подстановка слов видят одни и те же границы
+    #[test]
+    fn test_boundary_policy_shared_by_both_phases() {
+        use crate::simple_api::{decompress_data, try_compress_with_config};
+
+        // «don» не подставляется внутрь «don't»: апостроф — часть слова
+        let grimoire = vec!["don".to_string()];
+        let symbols = transform_manuscript_to_symbols(b"don't don", &grimoire);
+        assert_eq!(&symbols[..5], b"don't".map(u32::from));
+        assert_eq!(symbols[5..], [b' ' as u32, word_symbol(0, 1, false)]);
+
+        // Кириллица становится словами только в политике Unicode
+        let manuscript = "старый маг читает древний свиток, маг пишет свиток. ".repeat(30);
+        let config = EngineConfig::default();
+        let (_, ascii_grimoire, _) = symbolize_manuscript(manuscript.as_bytes(), &config);
+        let unicode_config = config.with_boundary_policy(UnicodeBoundary);
+        let (symbols, unicode_grimoire, _) =
+            symbolize_manuscript(manuscript.as_bytes(), &unicode_config);
+        assert!(ascii_grimoire.is_empty());
+        assert!(unicode_grimoire.iter().any(|word| word == "свиток"));
+        assert!(symbols.len() < manuscript.chars().count());
+
+        for config in [
+            unicode_config,
+            EngineConfig::default().with_boundary_policy(NoBoundary),
+        ] {
+            let archive = try_compress_with_config(&manuscript, &config).unwrap();
+            assert_eq!(decompress_data(&archive), manuscript.as_bytes());
+        }
+    }
+
+    /// Оптимальный разбор через настройки движка
+    #[test]
+    fn test_optimal_parse_engine_roundtrip() {
+        use crate::compression_engine::engine_covenant::{ArithmeticEngine, CompressionEngine};
+        use crate::simple_api::decompress_data;
+
+        let manuscript = "don don don't won't won won't can't can ".repeat(60);
+        let greedy = ArithmeticEngine.compress(manuscript.as_bytes(), &EngineConfig::default());
+        let optimal = ArithmeticEngine.compress(
+            manuscript.as_bytes(),
+            &EngineConfig::default().with_parse_strategy(ParseStrategy::Optimal),
+        );
+        assert!(optimal.to_bytes().len() <= greedy.to_bytes().len());
+        assert_eq!(decompress_data(optimal.to_bytes()), manuscript.as_bytes());
+    }
+
+    /// Текст из сотен разных частых слов (слоги по три, частоты по Ципфу)
+    fn many_word_manuscript() -> Vec<u8> {
+        const SYLLABLES: [&str; 8] = ["ka", "lo", "mi", "ru", "te", "zo", "an", "be"];
+        let vocabulary: Vec<String> = (0..512)
+            .map(|index| {
+                [index % 8, index / 8 % 8, index / 64]
+                    .iter()
+                    .map(|&syllable| SYLLABLES[syllable])
+                    .collect()
+            })
+            .collect();
+
+        let mut state = 0x9E37_79B9u32;
+        let mut manuscript = Vec::new();
+        for _ in 0..12_000 {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            // Квадрат равномерной величины сгущает выбор к началу словаря
+            let uniform = (state % 512) as usize;
+            manuscript.extend_from_slice(vocabulary[uniform * uniform / 512].as_bytes());
+            manuscript.push(if state.is_multiple_of(9) { b'\n' } else { b' ' });
+        }
+        manuscript
+    }
+
+    /// Бюджет словаря пропускает больше 25 слов и сжимает лучше
+    #[test]
+    fn test_dictionary_budget_grows_grimoire() {
+        use crate::compression_engine::engine_covenant::{ArithmeticEngine, CompressionEngine};
+        use crate::simple_api::{compress_data, decompress_data};
+
+        let manuscript = many_word_manuscript();
+        let config = EngineConfig::default().with_dictionary_budget_bytes(16 * 1024);
+        let artifact = try_weave_with_discovery(&manuscript, &config).unwrap();
+        assert!(artifact.mystical_word_grimoire.len() > 25);
+
+        let archive = ArithmeticEngine.compress(&manuscript, &config).to_bytes();
+        assert!(archive.len() < compress_data(&manuscript).len());
+        assert_eq!(decompress_data(&archive), manuscript);
+    }
+
+    /// Словарь не выходит за бюджет; нулевой бюджет — без словаря
+    #[test]
+    fn test_dictionary_budget_is_respected() {
+        let manuscript = many_word_manuscript();
+        for budget_bytes in [0, 40, 300, 2000] {
+            let words = discover_word_enchantments_within_budget(
+                &manuscript,
+                &WordCounting::Exact,
+                &DenyList::default(),
+                Some(budget_bytes),
+                &WordBoundaries::default(),
+            );
+            let grimoire_bytes: usize = words
+                .iter()
+                .map(|word| word.len() + GRIMOIRE_ENTRY_OVERHEAD)
+                .sum();
+            assert!(grimoire_bytes <= budget_bytes);
+            assert_eq!(words.is_empty(), budget_bytes == 0);
+        }
+    }
+}
@@ -0,0 +1,552 @@
+//! Дескриптор компрессора с кэшированной моделью 🗝️
+//!
+//! Обучает словарь и таблицу частот один раз и переиспользует их для
+//! множества похожих коротких сообщений (например, JSON на каждый запрос).
+//! Артефакты дескриптора не содержат ни словаря, ни таблицы частот —
+//! только ссылку на модель по идентификатору словаря.
+//!
+//! Модель сохраняется в файл словаря (`.acwd`):
+//! `[b"ACWD"][версия u8][id словаря u64][слова: кол-во u32 + (длина u32, байты)]`
+//! `[частоты: кол-во u32 + u64 на каждый символ алфавита]`.
+
+use crate::bit_wizardry::bit_manipulation_spells::CoderPrecision;
+use crate::bit_wizardry::checksum_runes::RuneHasher;
+use crate::compression_engine::compression_conjurer::{
+    alphabet_size, discover_profitable_word_enchantments, encode_symbol_stream, prune_unused_words,
+    transform_manuscript_to_symbols,
+};
+use crate::compression_engine::frequency_census::symbol_census;
+use crate::compression_engine::grimoire_quill::SharedGrimoire;
+use crate::compression_engine::grimoire_ward::DenyList;
+use crate::compression_engine::word_sketch::WordCounting;
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::decompression_oracle::decompression_sage::{
+    decode_symbol_stream, reconstruct_original_manuscript,
+};
+use crate::error::{WizardError, WizardResult};
+use crate::format::artifact_scroll::ScrollCursor;
+use crate::format::container_runes::{ArchiveHeader, ModelKind};
+use std::sync::Arc;
+
+/// Сигнатура файла словаря
+pub const DICTIONARY_MAGIC: [u8; 4] = *b"ACWD";
+
+/// Текущая версия формата файла словаря
+pub const DICTIONARY_FORMAT_VERSION: u8 = 1;
+
+/// Предел общей массы частот общей модели
+///
+/// Держит интервалы символов широкими даже у редких символов.
+pub(crate) const HANDLE_MODEL_MASS_LIMIT: u64 = 1 << 16;
+
+/// Компрессор с обученной моделью, общей для многих вызовов 🧙‍♂️
+///
+/// Модель сглажена: каждый байт 0-255 и каждое слово словаря имеют
+/// ненулевую частоту, поэтому сжимается любое сообщение, даже с
+/// символами, не встречавшимися при обучении.
+///
+/// Словарь и таблица частот лежат в общих буферах (`Arc`): клон
+/// дескриптора не копирует строки, а дескрипторы, обученные на одном
+/// [`SharedGrimoire`], делят его слова.
+#[derive(Debug, Clone)]
+pub struct CompressorHandle {
+    word_grimoire: SharedGrimoire,
+    frequency_codex: Arc<[(u32, u64, u64)]>,
+    total_frequency_mass: u64,
+    dictionary_id: u64,
+}
+
+/// Артефакт, ссылающийся на модель дескриптора 📎
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandleArtifact {
+    /// Идентификатор словаря и модели, которыми закодированы данные
+    pub dictionary_id: u64,
+    /// Количество закодированных символов
+    pub symbol_count: u32,
+    /// Сжатый битовый поток
+    pub compressed_bit_stream: Vec<u8>,
+}
+
+impl CompressorHandle {
+    /// Обучает словарь и модель частот на образце данных
+    pub fn train(training_manuscript: impl AsRef<[u8]>) -> Self {
+        Self::train_with_counting(training_manuscript, WordCounting::Exact)
+    }
+
+    /// Обучает модель, выбирая способ подсчета слов
+    ///
+    /// `WordCounting::Sketch` ограничивает память обучения на больших корпусах.
+    pub fn train_with_counting(
+        training_manuscript: impl AsRef<[u8]>,
+        word_counting: WordCounting,
+    ) -> Self {
+        let training_manuscript = training_manuscript.as_ref();
+        let word_grimoire = discover_profitable_word_enchantments(
+            training_manuscript,
+            &word_counting,
+            &DenyList::default(),
+        );
+        Self::train_with_discovered(training_manuscript, word_grimoire)
+    }
+
+    /// Обучает модель, не помещая в словарь слов из запретного списка 🚫
+    ///
+    /// Файл словаря хранит слова открытым текстом, как и заголовок архива.
+    pub fn train_with_deny_list(
+        training_manuscript: impl AsRef<[u8]>,
+        deny_list: &DenyList,
+    ) -> Self {
+        let training_manuscript = training_manuscript.as_ref();
+        let word_grimoire = discover_profitable_word_enchantments(
+            training_manuscript,
+            &WordCounting::Exact,
+            deny_list,
+        );
+        Self::train_with_discovered(training_manuscript, word_grimoire)
+    }
+
+    /// Обучает модель на найденном поиском словаре
+    ///
+    /// Слова, ни разу не совпавшие в образце, в файл словаря не попадают.
+    fn train_with_discovered(training_manuscript: &[u8], word_grimoire: Vec<String>) -> Self {
+        let symbolic_incantations =
+            transform_manuscript_to_symbols(training_manuscript, &word_grimoire);
+        let (symbolic_incantations, word_grimoire) =
+            prune_unused_words(symbolic_incantations, word_grimoire);
+        Self::from_observed_frequencies(word_grimoire, symbol_census(&symbolic_incantations))
+    }
+
+    /// Обучает модель частот на образце с заранее известным словарем
+    ///
+    /// Словарь сохраняется целиком: слово, которого нет в образце, может
+    /// встретиться в сообщениях. [`SharedGrimoire`] (например,
+    /// [`Grimoire::shared_words`](super::Grimoire::shared_words)) не
+    /// копируется: дескрипторы с разными частотами делят одни строки.
+    ///
+    /// ```
+    /// use arithmetic_compression_wizard::compression_engine::{CompressorHandle, Grimoire};
+    /// use std::sync::Arc;
+    ///
+    /// let grimoire = Grimoire::from_entries(["invoice", "customer", "shipment"]);
+    /// let tenant_a = CompressorHandle::train_with_grimoire(b"invoice invoice customer", grimoire.shared_words());
+    /// let tenant_b = CompressorHandle::train_with_grimoire(b"shipment customer", grimoire.shared_words());
+    /// assert!(Arc::ptr_eq(&tenant_a.shared_grimoire(), &tenant_b.shared_grimoire()));
+    /// assert_ne!(tenant_a.dictionary_id(), tenant_b.dictionary_id());
+    /// ```
+    pub fn train_with_grimoire(
+        training_manuscript: impl AsRef<[u8]>,
+        word_grimoire: impl Into<SharedGrimoire>,
+    ) -> Self {
+        let training_manuscript = training_manuscript.as_ref();
+        let word_grimoire = word_grimoire.into();
+        let symbolic_incantations =
+            transform_manuscript_to_symbols(training_manuscript, &word_grimoire);
+        // Только счетчики: масса модели масштабируется ниже
+        let observed = symbol_census(&symbolic_incantations);
+        Self::from_observed_frequencies(word_grimoire, observed)
+    }
+
+    /// Строит сглаженную модель по наблюденным частотам символов алфавита
+    ///
+    /// Символы вне алфавита словаря (фразы) должны быть уже разложены на слова.
+    pub(crate) fn from_observed_frequencies(
+        word_grimoire: impl Into<SharedGrimoire>,
+        observed: impl IntoIterator<Item = (u32, u64)>,
+    ) -> Self {
+        let word_grimoire = word_grimoire.into();
+        // Сглаживание: каждый возможный символ получает хотя бы единицу
+        let mut smoothed_frequencies = vec![1u64; alphabet_size(word_grimoire.len())];
+        for (symbol_id, frequency) in observed {
+            smoothed_frequencies[symbol_id as usize] += frequency;
+        }
+
+        // Масштабирование под точность кодера
+        let raw_total: u64 = smoothed_frequencies.iter().sum();
+        if raw_total > HANDLE_MODEL_MASS_LIMIT {
+            for frequency in smoothed_frequencies.iter_mut() {
+                *frequency = (*frequency * HANDLE_MODEL_MASS_LIMIT / raw_total).max(1);
+            }
+        }
+        Self::from_smoothed_frequencies(word_grimoire, &smoothed_frequencies)
+    }
+
+    /// Строит модель по готовым ненулевым частотам всего алфавита
+    ///
+    /// Общая масса частот должна умещаться в точность кодера.
+    pub(crate) fn from_smoothed_frequencies(
+        word_grimoire: impl Into<SharedGrimoire>,
+        smoothed_frequencies: &[u64],
+    ) -> Self {
+        let word_grimoire = word_grimoire.into();
+        let mut cumulative_position = 0u64;
+        let frequency_codex: Arc<[(u32, u64, u64)]> = smoothed_frequencies
+            .iter()
+            .enumerate()
+            .map(|(symbol_id, &frequency)| {
+                let current_position = cumulative_position;
+                cumulative_position += frequency;
+                (symbol_id as u32, frequency, current_position)
+            })
+            .collect();
+
+        let dictionary_id = fingerprint_model(&word_grimoire, &frequency_codex);
+
+        Self {
+            word_grimoire,
+            frequency_codex,
+            total_frequency_mass: cumulative_position,
+            dictionary_id,
+        }
+    }
+
+    /// Идентификатор словаря и модели
+    pub fn dictionary_id(&self) -> u64 {
+        self.dictionary_id
+    }
+
+    /// Обученный словарь
+    pub fn word_grimoire(&self) -> &[String] {
+        &self.word_grimoire
+    }
+
+    /// Общий буфер словаря без копирования строк
+    pub fn shared_grimoire(&self) -> SharedGrimoire {
+        Arc::clone(&self.word_grimoire)
+    }
+
+    /// Частоты модели по символам алфавита: байты `0..=255`, затем слова
+    /// словаря, затем слова с пробелом
+    pub fn symbol_frequencies(&self) -> Vec<u64> {
+        self.frequency_codex
+            .iter()
+            .map(|&(_, frequency, _)| frequency)
+            .collect()
+    }
+
+    /// Сериализует модель в файл словаря
+    pub fn to_dictionary_bytes(&self) -> Vec<u8> {
+        let mut result = Vec::new();
+        result.extend_from_slice(&DICTIONARY_MAGIC);
+        result.push(DICTIONARY_FORMAT_VERSION);
+        result.extend_from_slice(&self.dictionary_id.to_le_bytes());
+
+        result.extend_from_slice(&(self.word_grimoire.len() as u32).to_le_bytes());
+        for word in self.word_grimoire.iter() {
+            result.extend_from_slice(&(word.len() as u32).to_le_bytes());
+            result.extend_from_slice(word.as_bytes());
+        }
+
+        result.extend_from_slice(&(self.frequency_codex.len() as u32).to_le_bytes());
+        for &(_, frequency, _) in self.frequency_codex.iter() {
+            result.extend_from_slice(&frequency.to_le_bytes());
+        }
+        result
+    }
+
+    /// Загружает модель из файла словаря 📖
+    ///
+    /// Идентификатор пересчитывается по содержимому и сверяется с записанным,
+    /// поэтому поврежденный файл не даст молча неверную модель.
+    pub fn from_dictionary_bytes(dictionary_bytes: &[u8]) -> WizardResult<Self> {
+        let options = DecodeOptions::default();
+        if !dictionary_bytes.starts_with(&DICTIONARY_MAGIC) {
+            return Err(WizardError::UnrecognizedHeader);
+        }
+
+        let mut cursor = ScrollCursor::new(dictionary_bytes, DICTIONARY_MAGIC.len());
+        let version = cursor.read_slice(1)?[0];
+        if version != DICTIONARY_FORMAT_VERSION {
+            return Err(WizardError::UnsupportedVersion(version));
+        }
+        let stored_id = cursor.read_u64()?;
+
+        // Словарь
+        let word_count = cursor.read_u32()? as usize;
+        options.check_dict_entries(word_count)?;
+        let mut word_grimoire = Vec::with_capacity(word_count.min(cursor.remaining() / 4));
+        let mut grimoire_bytes = 0;
+        for _ in 0..word_count {
+            let word_len = cursor.read_u32()? as usize;
+            grimoire_bytes = options.check_word_bytes(grimoire_bytes, word_len)?;
+            word_grimoire.push(String::from_utf8_lossy(cursor.read_slice(word_len)?).into_owned());
+        }
+
+        // Таблица частот: по одной частоте на каждый символ алфавита
+        let frequency_count = cursor.read_u32()? as usize;
+        options.check_table_entries(frequency_count)?;
+        if frequency_count != alphabet_size(word_grimoire.len()) {
+            return Err(WizardError::CorruptFrequencyTable(
+                "размер таблицы не совпадает со словарем",
+            ));
+        }
+
+        let mut frequency_codex = Vec::with_capacity(frequency_count);
+        let mut cumulative_position = 0u64;
+        for symbol_id in 0..frequency_count {
+            let frequency = cursor.read_u64()?;
+            if frequency == 0 {
+                return Err(WizardError::CorruptFrequencyTable("нулевая частота"));
+            }
+            frequency_codex.push((symbol_id as u32, frequency, cumulative_position));
+            cumulative_position = cumulative_position.checked_add(frequency).ok_or(
+                WizardError::CorruptFrequencyTable("переполнение суммы частот"),
+            )?;
+        }
+        if cumulative_position > CoderPrecision::default().max_total_frequency() {
+            return Err(WizardError::CorruptFrequencyTable(
+                "общая масса превышает точность кодера",
+            ));
+        }
+
+        if fingerprint_model(&word_grimoire, &frequency_codex) != stored_id {
+            return Err(WizardError::CorruptFrequencyTable(
+                "идентификатор словаря не совпадает с содержимым",
+            ));
+        }
+
+        Ok(Self {
+            word_grimoire: word_grimoire.into(),
+            frequency_codex: frequency_codex.into(),
+            total_frequency_mass: cumulative_position,
+            dictionary_id: stored_id,
+        })
+    }
+
+    /// Сжимает сообщение общей моделью без повторного поиска слов
+    pub fn compress(&self, message: impl AsRef<[u8]>) -> HandleArtifact {
+        let symbolic_incantations =
+            transform_manuscript_to_symbols(message.as_ref(), &self.word_grimoire);
+        let compressed_bit_stream = encode_symbol_stream(
+            &symbolic_incantations,
+            &self.frequency_codex,
+            self.total_frequency_mass,
+            CoderPrecision::default(),
+        )
+        .expect("масса модели дескриптора ограничена HANDLE_MODEL_MASS_LIMIT");
+
+        HandleArtifact {
+            dictionary_id: self.dictionary_id,
+            symbol_count: symbolic_incantations.len() as u32,
+            compressed_bit_stream,
+        }
+    }
+
+    /// Восстанавливает сообщение
+    ///
+    /// Возвращает `None`, если артефакт создан другой моделью или поврежден.
+    pub fn decompress(&self, artifact: &HandleArtifact) -> Option<Vec<u8>> {
+        self.try_decompress(artifact, &DecodeOptions::unlimited())
+            .ok()
+    }
+
+    /// Проверяемое восстановление с лимитами `options`
+    ///
+    /// Артефакт другой модели дает [`WizardError::ExternalDictionaryRequired`]
+    /// с идентификатором нужного словаря.
+    pub fn try_decompress(
+        &self,
+        artifact: &HandleArtifact,
+        options: &DecodeOptions,
+    ) -> WizardResult<Vec<u8>> {
+        if artifact.dictionary_id != self.dictionary_id {
+            return Err(WizardError::ExternalDictionaryRequired(
+                artifact.dictionary_id,
+            ));
+        }
+
+        // Каждый символ дает хотя бы один байт результата
+        options.reserve_output(artifact.symbol_count as u64)?;
+
+        let decoded_symbols = decode_symbol_stream(
+            &artifact.compressed_bit_stream,
+            &self.frequency_codex[..],
+            self.total_frequency_mass,
+            artifact.symbol_count as u64,
+            CoderPrecision::default(),
+        )?;
+
+        reconstruct_original_manuscript(&decoded_symbols, &self.word_grimoire, &[], options)
+    }
+}
+
+impl HandleArtifact {
+    /// Сериализует артефакт: [заголовок с id словаря][число символов u32][данные]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let header = ArchiveHeader::current(ModelKind::SharedOrder0, Some(self.dictionary_id));
+
+        let mut result =
+            Vec::with_capacity(header.encoded_len() + 4 + self.compressed_bit_stream.len());
+        header.write_to(&mut result);
+        result.extend_from_slice(&self.symbol_count.to_le_bytes());
+        result.extend_from_slice(&self.compressed_bit_stream);
+        result
+    }
+
+    /// Разбирает артефакт, созданный через [`HandleArtifact::to_bytes`]
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (header, body_offset) = ArchiveHeader::parse(bytes)?;
+        if header.model_kind != ModelKind::SharedOrder0 {
+            return None;
+        }
+
+        let dictionary_id = header.dictionary_id?;
+        let count_bytes = bytes.get(body_offset..body_offset + 4)?;
+        let symbol_count = u32::from_le_bytes(count_bytes.try_into().ok()?);
+
+        Some(Self {
+            dictionary_id,
+            symbol_count,
+            compressed_bit_stream: bytes[body_offset + 4..].to_vec(),
+        })
+    }
+}
+
+/// Вычисляет идентификатор модели по словарю и таблице частот
+fn fingerprint_model(word_grimoire: &[String], frequency_codex: &[(u32, u64, u64)]) -> u64 {
+    let mut hasher = RuneHasher::conjure_new();
+
+    hasher.absorb(&(word_grimoire.len() as u32).to_le_bytes());
+    for word in word_grimoire {
+        hasher.absorb(&(word.len() as u32).to_le_bytes());
+        hasher.absorb(word.as_bytes());
+    }
+
+    for &(symbol_id, frequency, _) in frequency_codex {
+        hasher.absorb(&symbol_id.to_le_bytes());
+        hasher.absorb(&frequency.to_le_bytes());
+    }
+
+    hasher.finish()
+}
+
+/// Тесты дескриптора компрессора 🎯
+#[cfg(test)]
+mod compressor_handle_tests {
+    use super::*;
+
+    const TRAINING_SAMPLE: &[u8] = b"{\"user\":\"alice\",\"status\":\"active\"} \
+        {\"user\":\"bob\",\"status\":\"active\"} {\"user\":\"carol\",\"status\":\"blocked\"} \
+        {\"user\":\"dave\",\"status\":\"active\"} {\"user\":\"erin\",\"status\":\"active\"}";
+
+    #[test]
+    fn test_many_messages_roundtrip_with_shared_model() {
+        let handle = CompressorHandle::train(TRAINING_SAMPLE);
+
+        let messages: [&[u8]; 3] = [
+            b"{\"user\":\"frank\",\"status\":\"active\"}",
+            b"{\"user\":\"grace\",\"status\":\"blocked\"}",
+            b"",
+        ];
+
+        for message in messages {
+            let artifact = handle.compress(message);
+            assert_eq!(artifact.dictionary_id, handle.dictionary_id());
+            assert_eq!(handle.decompress(&artifact).unwrap(), message);
+        }
+    }
+
+    #[test]
+    fn test_unseen_bytes_are_still_encodable() {
+        let handle = CompressorHandle::train(TRAINING_SAMPLE);
+        let message = b"\x00\xff\x7f binary tail";
+
+        let artifact = handle.compress(message);
+        assert_eq!(handle.decompress(&artifact).unwrap(), message);
+    }
+
+    #[test]
+    fn test_foreign_artifact_is_rejected() {
+        let handle = CompressorHandle::train(TRAINING_SAMPLE);
+        let other = CompressorHandle::train(b"completely different training text text text text");
+
+        let artifact = other.compress(b"text");
+        assert!(handle.decompress(&artifact).is_none());
+    }
+
+    #[test]
+    fn test_handles_share_grimoire_strings() {
+        let handle = CompressorHandle::train(TRAINING_SAMPLE);
+        let copy = handle.clone();
+        assert!(Arc::ptr_eq(
+            &copy.shared_grimoire(),
+            &handle.shared_grimoire()
+        ));
+        assert!(Arc::ptr_eq(&copy.frequency_codex, &handle.frequency_codex));
+
+        // Переобучение частот на том же словаре не копирует строки
+        let retrained =
+            CompressorHandle::train_with_grimoire(b"{\"user\":\"zed\"}", handle.shared_grimoire());
+        assert!(Arc::ptr_eq(
+            &retrained.shared_grimoire(),
+            &handle.shared_grimoire()
+        ));
+        assert_ne!(retrained.dictionary_id(), handle.dictionary_id());
+        let artifact = retrained.compress(b"{\"user\":\"amy\",\"status\":\"active\"}");
+        assert_eq!(
+            retrained.decompress(&artifact).unwrap(),
+            b"{\"user\":\"amy\",\"status\":\"active\"}"
+        );
+    }
+
+    #[test]
+    fn test_discovered_words_without_matches_are_pruned() {
+        // "hat" не совпадает внутри "hatter" из-за границ слова
+        let sample = b"hatter wand hatter wand hatter wand";
+        let grimoire = vec!["hat".to_string(), "wand".to_string(), "hatter".to_string()];
+        let pruned = CompressorHandle::train_with_discovered(sample, grimoire.clone());
+        assert_eq!(pruned.word_grimoire(), ["wand", "hatter"]);
+
+        // Заданный словарь сохраняется целиком
+        let whole = CompressorHandle::train_with_grimoire(sample, grimoire);
+        assert_eq!(whole.word_grimoire().len(), 3);
+        assert!(pruned.to_dictionary_bytes().len() < whole.to_dictionary_bytes().len());
+        let artifact = pruned.compress(sample);
+        assert_eq!(pruned.decompress(&artifact).unwrap(), sample);
+    }
+
+    #[test]
+    fn test_dictionary_file_roundtrip() {
+        let handle = CompressorHandle::train(TRAINING_SAMPLE);
+        let dictionary_bytes = handle.to_dictionary_bytes();
+        let loaded = CompressorHandle::from_dictionary_bytes(&dictionary_bytes).unwrap();
+
+        assert_eq!(loaded.dictionary_id(), handle.dictionary_id());
+        assert_eq!(loaded.word_grimoire(), handle.word_grimoire());
+        let artifact = handle.compress(b"{\"user\":\"ivan\"}");
+        assert_eq!(
+            loaded.decompress(&artifact).unwrap(),
+            b"{\"user\":\"ivan\"}"
+        );
+    }
+
+    #[test]
+    fn test_damaged_dictionary_file_is_rejected() {
+        let dictionary_bytes = CompressorHandle::train(TRAINING_SAMPLE).to_dictionary_bytes();
+
+        let mut skewed = dictionary_bytes.clone();
+        let last = skewed.len() - 8;
+        skewed[last] ^= 0b10;
+        assert_eq!(
+            CompressorHandle::from_dictionary_bytes(&skewed).unwrap_err(),
+            WizardError::CorruptFrequencyTable("идентификатор словаря не совпадает с содержимым")
+        );
+        assert!(matches!(
+            CompressorHandle::from_dictionary_bytes(&dictionary_bytes[..20]),
+            Err(WizardError::Truncated { .. })
+        ));
+        assert_eq!(
+            CompressorHandle::from_dictionary_bytes(b"ACW\x05").unwrap_err(),
+            WizardError::UnrecognizedHeader
+        );
+    }
+
+    #[test]
+    fn test_artifact_bytes_roundtrip() {
+        let handle = CompressorHandle::train(TRAINING_SAMPLE);
+        let artifact = handle.compress(b"{\"user\":\"heidi\"}");
+
+        let restored = HandleArtifact::from_bytes(&artifact.to_bytes()).unwrap();
+        assert_eq!(restored, artifact);
+        assert!(HandleArtifact::from_bytes(&[1, 2, 3]).is_none());
+    }
+}
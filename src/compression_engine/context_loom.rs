@@ -0,0 +1,455 @@
+//! Ткацкий станок контекстов: модель второго порядка 🕸️
+//!
+//! Модели нулевого порядка не знают, что после «th» почти всегда идет
+//! «e». Здесь символ предсказывается по двум предыдущим символам потока
+//! (байтам, словам словаря или фразам). Каждый контекст хранит несколько
+//! встреченных после него символов со счетчиками; незнакомый символ
+//! кодируется уходом (escape, частота ухода — число различных символов
+//! контекста, как в PPM-C) к контексту из одного символа, а оттуда — к
+//! адаптивной модели нулевого порядка [`AgingModel`].
+//!
+//! Контексты обоих порядков хешируются в одну таблицу из `2^table_bits`
+//! ячеек (см. [`ModelKind::HashedOrder2`]). Ячейка помнит свой контекст
+//! целиком: чужой контекст с тем же хешем вытесняет прежний, а не
+//! смешивает с ним счетчики. Ячейки заводятся по мере появления
+//! контекстов, поэтому память декодера растет не быстрее числа символов.
+//!
+//! Тело кадра то же, что у адаптивной модели:
+//! `[словарь][фразы][число символов u64][длина u32][битовый поток]`.
+
+use super::adaptive_tide::{AgingModel, DEFAULT_HALF_LIFE};
+use super::compression_conjurer::{alphabet_size, symbolize_manuscript};
+use super::engine_covenant::{EngineConfig, Frame, DEADLINE_CHECK_SYMBOLS};
+use crate::bit_wizardry::bit_manipulation_spells::CoderPrecision;
+use crate::bit_wizardry::entropy_coder::{ArithmeticDecoder, ArithmeticEncoder};
+use crate::decompression_oracle::decode_wards::{validate_phrase_codex, DecodeOptions};
+use crate::decompression_oracle::decompression_sage::SymbolScribe;
+use crate::error::{WizardError, WizardResult};
+use crate::format::artifact_scroll::{
+    check_frame_layout, read_coded_stream, read_grimoire_sections, write_coded_stream,
+    write_grimoire_sections, ScrollCursor,
+};
+use crate::format::container_runes::{
+    ArchiveHeader, EngineKind, ModelKind, MAX_CONTEXT_TABLE_BITS,
+};
+use crate::telemetry::{record_compression, Stopwatch};
+use std::collections::HashMap;
+
+/// Размер таблицы контекстов по умолчанию: 2^20 ячеек
+pub const DEFAULT_CONTEXT_TABLE_BITS: u8 = 20;
+
+/// Сколько символов помнит одна ячейка контекста
+const SLOT_SYMBOLS: usize = 12;
+
+/// Сумма счетчиков ячейки, после которой они делятся пополам
+const SLOT_RESCALE_TOTAL: u32 = 1 << 10;
+
+/// Контекст из одного символа: старшая половина ключа, которой нет у символов
+const ORDER1_MARKER: u64 = (u32::MAX as u64) << 32;
+
+/// Ячейка таблицы: контекст и символы, встреченные после него
+#[derive(Debug, Clone)]
+struct ContextSlot {
+    /// Ключ контекста (два символа или символ с меткой первого порядка)
+    context: u64,
+    /// Встреченные символы в порядке появления
+    symbols: [u32; SLOT_SYMBOLS],
+    /// Счетчики символов (ненулевые)
+    counts: [u32; SLOT_SYMBOLS],
+    /// Число занятых мест
+    len: usize,
+}
+
+impl ContextSlot {
+    /// Пустая ячейка контекста
+    fn new(context: u64) -> Self {
+        Self {
+            context,
+            symbols: [0; SLOT_SYMBOLS],
+            counts: [0; SLOT_SYMBOLS],
+            len: 0,
+        }
+    }
+
+    /// Общая масса: счетчики и уход (по числу различных символов)
+    fn total(&self) -> u32 {
+        self.counts[..self.len].iter().sum::<u32>() + self.len as u32
+    }
+
+    /// Место символа в ячейке
+    fn position(&self, symbol: u32) -> Option<usize> {
+        self.symbols[..self.len]
+            .iter()
+            .position(|&known| known == symbol)
+    }
+
+    /// Интервал `[low, high)` места; место `len` — уход
+    fn interval(&self, position: usize) -> (u32, u32) {
+        let low = self.counts[..position].iter().sum::<u32>();
+        let width = if position == self.len {
+            self.len as u32
+        } else {
+            self.counts[position]
+        };
+        (low, low + width)
+    }
+
+    /// Место (или уход, `len`), в интервал которого попадает `target`
+    fn position_at(&self, target: u32) -> usize {
+        let mut high = 0;
+        for (position, &count) in self.counts[..self.len].iter().enumerate() {
+            high += count;
+            if target < high {
+                return position;
+            }
+        }
+        self.len
+    }
+
+    /// Учитывает символ: прибавка к счетчику или новое место
+    ///
+    /// В полной ячейке новый символ вытесняет первый из самых редких.
+    fn observe(&mut self, symbol: u32) {
+        match self.position(symbol) {
+            Some(position) => self.counts[position] += 1,
+            None if self.len < SLOT_SYMBOLS => {
+                self.symbols[self.len] = symbol;
+                self.counts[self.len] = 1;
+                self.len += 1;
+            }
+            None => {
+                let rarest = (0..SLOT_SYMBOLS)
+                    .min_by_key(|&position| self.counts[position])
+                    .unwrap_or(0);
+                self.symbols[rarest] = symbol;
+                self.counts[rarest] = 1;
+            }
+        }
+
+        if self.total() > SLOT_RESCALE_TOTAL {
+            for count in &mut self.counts[..self.len] {
+                *count = count.div_ceil(2);
+            }
+        }
+    }
+}
+
+/// Хешированная модель второго порядка с уходами 🕸️
+#[derive(Debug, Clone)]
+pub(crate) struct ContextLoom {
+    /// Ячейки по индексу в таблице (заводятся при первом контексте)
+    slots: HashMap<u32, ContextSlot>,
+    /// Модель нулевого порядка для символов, незнакомых обоим контекстам
+    fallback: AgingModel,
+    /// Сдвиг хеша к индексу из `table_bits` бит
+    index_shift: u32,
+    /// Два предыдущих символа: `[предпоследний, последний]`
+    history: [u32; 2],
+}
+
+impl ContextLoom {
+    /// Модель для алфавита из `alphabet` символов
+    pub(crate) fn new(
+        alphabet: usize,
+        table_bits: u8,
+        precision: CoderPrecision,
+    ) -> WizardResult<Self> {
+        if !(1..=MAX_CONTEXT_TABLE_BITS).contains(&table_bits) {
+            return Err(WizardError::InvalidDistribution(
+                "размер таблицы контекстов вне допустимого",
+            ));
+        }
+        Ok(Self {
+            slots: HashMap::new(),
+            fallback: AgingModel::new(alphabet, DEFAULT_HALF_LIFE, precision)?,
+            index_shift: 64 - table_bits as u32,
+            history: [0; 2],
+        })
+    }
+
+    /// Ключи контекстов по убыванию порядка
+    fn contexts(&self) -> [u64; 2] {
+        let [older, last] = self.history;
+        [
+            (older as u64) << 32 | last as u64,
+            ORDER1_MARKER | last as u64,
+        ]
+    }
+
+    /// Индекс ячейки контекста в таблице
+    fn slot_index(&self, context: u64) -> u32 {
+        (context.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> self.index_shift) as u32
+    }
+
+    /// Ячейка контекста, если она помнит именно этот контекст
+    fn slot(&self, context: u64) -> Option<&ContextSlot> {
+        self.slots
+            .get(&self.slot_index(context))
+            .filter(|slot| slot.context == context && slot.len > 0)
+    }
+
+    /// Кодирует символ: уходы по контекстам и интервал там, где он знаком
+    pub(crate) fn encode(
+        &mut self,
+        symbol: u32,
+        encoder: &mut ArithmeticEncoder<'_>,
+    ) -> WizardResult<()> {
+        for context in self.contexts() {
+            let Some(slot) = self.slot(context) else {
+                continue;
+            };
+            let position = slot.position(symbol).unwrap_or(slot.len);
+            let (low, high) = slot.interval(position);
+            encoder.encode(low, high, slot.total())?;
+            if position < slot.len {
+                self.observe(symbol);
+                return Ok(());
+            }
+        }
+
+        let (low, high) = self.fallback.interval(symbol as usize);
+        encoder.encode(low, high, self.fallback.total())?;
+        self.fallback.observe(symbol as usize);
+        self.observe(symbol);
+        Ok(())
+    }
+
+    /// Декодирует символ теми же шагами, что и [`encode`](Self::encode)
+    pub(crate) fn decode(&mut self, decoder: &mut ArithmeticDecoder<'_>) -> WizardResult<u32> {
+        for context in self.contexts() {
+            let Some(slot) = self.slot(context) else {
+                continue;
+            };
+            let total = slot.total();
+            let position = slot.position_at(decoder.decode_target(total)?);
+            let (low, high) = slot.interval(position);
+            decoder.consume(low, high, total)?;
+            if position < slot.len {
+                let symbol = slot.symbols[position];
+                self.observe(symbol);
+                return Ok(symbol);
+            }
+        }
+
+        let total = self.fallback.total();
+        let symbol = self.fallback.symbol_at(decoder.decode_target(total)?);
+        let (low, high) = self.fallback.interval(symbol);
+        decoder.consume(low, high, total)?;
+        self.fallback.observe(symbol);
+        self.observe(symbol as u32);
+        Ok(symbol as u32)
+    }
+
+    /// Учитывает символ в обоих контекстах и сдвигает историю
+    fn observe(&mut self, symbol: u32) {
+        for context in self.contexts() {
+            let index = self.slot_index(context);
+            let slot = self
+                .slots
+                .entry(index)
+                .or_insert_with(|| ContextSlot::new(context));
+            if slot.context != context {
+                *slot = ContextSlot::new(context);
+            }
+            slot.observe(symbol);
+        }
+        self.history = [self.history[1], symbol];
+    }
+}
+
+/// Сжимает данные в кадр арифметического движка с моделью второго порядка
+///
+/// Словарь ищется так же, как для статической модели.
+pub(crate) fn try_weave_context_frame(
+    data: &[u8],
+    table_bits: u8,
+    config: &EngineConfig,
+) -> WizardResult<Frame> {
+    let stopwatch = Stopwatch::start();
+    let (symbols, word_grimoire, phrase_codex) = symbolize_manuscript(data, config);
+    config.check_deadline()?;
+    let alphabet = alphabet_size(word_grimoire.len()) + phrase_codex.len();
+
+    let precision = CoderPrecision::default();
+    let mut model = ContextLoom::new(alphabet, table_bits, precision)?;
+    let mut bit_stream = Vec::new();
+    let mut encoder = ArithmeticEncoder::new(&mut bit_stream, precision);
+    for (position, &symbol) in symbols.iter().enumerate() {
+        if position % DEADLINE_CHECK_SYMBOLS == DEADLINE_CHECK_SYMBOLS - 1 {
+            config.check_deadline()?;
+        }
+        model.encode(symbol, &mut encoder)?;
+    }
+    encoder.finish();
+    record_compression("order2", stopwatch, data.len(), &symbols, bit_stream.len());
+
+    let mut body = Vec::new();
+    write_grimoire_sections(&mut body, &word_grimoire, &phrase_codex);
+    write_coded_stream(&mut body, symbols.len() as u64, &bit_stream);
+
+    Ok(Frame {
+        header: ArchiveHeader::current(ModelKind::HashedOrder2 { table_bits }, None),
+        body,
+    })
+}
+
+/// Проверяемая распаковка тела с моделью второго порядка
+pub(crate) fn try_unweave_context_body(
+    header: &ArchiveHeader,
+    body: &[u8],
+    options: &DecodeOptions,
+) -> WizardResult<Vec<u8>> {
+    try_unweave_context_prefix(header, body, usize::MAX, options)
+}
+
+/// Распаковка первых `limit` байтов тела с моделью второго порядка
+pub(crate) fn try_unweave_context_prefix(
+    header: &ArchiveHeader,
+    body: &[u8],
+    limit: usize,
+    options: &DecodeOptions,
+) -> WizardResult<Vec<u8>> {
+    check_frame_layout(header, EngineKind::Arithmetic)?;
+    let ModelKind::HashedOrder2 { table_bits } = header.model_kind else {
+        return Err(WizardError::UnsupportedModel(header.model_kind.to_byte()));
+    };
+    if header.alphabet_map.is_some() {
+        return Err(WizardError::CorruptFrequencyTable(
+            "атлас алфавита не применяется к модели второго порядка",
+        ));
+    }
+
+    let mut cursor = ScrollCursor::new(body, 0);
+    let (word_grimoire, phrase_codex) = read_grimoire_sections(&mut cursor, header, options)?;
+    validate_phrase_codex(&phrase_codex, word_grimoire.len())?;
+    let (symbol_count, bit_stream) = read_coded_stream(&mut cursor)?;
+
+    // Каждый символ дает хотя бы один байт результата
+    options.reserve_output(symbol_count.min(limit as u64))?;
+
+    let alphabet = alphabet_size(word_grimoire.len()) + phrase_codex.len();
+    options.check_table_entries(alphabet)?;
+    let precision = header.coder_precision();
+    let mut model = ContextLoom::new(alphabet, table_bits, precision)
+        .map_err(|_| WizardError::CorruptFrequencyTable("алфавит больше точности кодера"))?;
+
+    let mut decoder = ArithmeticDecoder::new(bit_stream, precision);
+    let mut scribe = SymbolScribe::new(&word_grimoire, &phrase_codex, limit, options);
+    scribe.reserve(symbol_count.min(bit_stream.len() as u64 * 8));
+    for _ in 0..symbol_count {
+        let symbol = model.decode(&mut decoder)?;
+        if !scribe.write(symbol)? {
+            break;
+        }
+    }
+
+    Ok(scribe.finish())
+}
+
+/// Тесты станка контекстов 🎯
+#[cfg(test)]
+mod context_loom_tests {
+    use super::*;
+    use crate::simple_api::{compress_data, compress_data_adaptive, decompress_data};
+    use crate::simple_api::{decompress_prefix, try_compress_with_config};
+
+    /// Английский текст с устойчивыми сочетаниями букв
+    fn prose() -> Vec<u8> {
+        let sentences = [
+            "the wizard opened the old book and read the spell aloud. ",
+            "then the owl flew over the tower while the moon rose. ",
+            "there was nothing in the archive that the apprentice could not find. ",
+            "whether the weather held or not, the scrolls were sorted by evening. ",
+        ];
+        (0..200)
+            .map(|index| sentences[(index * 7 + index / 3) % sentences.len()])
+            .collect::<String>()
+            .into_bytes()
+    }
+
+    #[test]
+    fn test_slot_escapes_and_evicts() {
+        let mut slot = ContextSlot::new(42);
+        assert_eq!(slot.total(), 0);
+
+        slot.observe(7);
+        slot.observe(7);
+        slot.observe(9);
+        // Счетчики 2 и 1, уход — по числу различных символов
+        assert_eq!(slot.total(), 5);
+        assert_eq!(slot.interval(0), (0, 2));
+        assert_eq!(slot.interval(slot.len), (3, 5));
+        assert_eq!(slot.position_at(2), 1);
+        assert_eq!(slot.position_at(4), slot.len);
+
+        for symbol in 100..100 + SLOT_SYMBOLS as u32 {
+            slot.observe(symbol);
+        }
+        // Самый частый символ пережил вытеснение, редкий 9 — нет
+        assert_eq!(slot.len, SLOT_SYMBOLS);
+        assert!(slot.position(7).is_some());
+        assert!(slot.position(9).is_none());
+    }
+
+    #[test]
+    fn test_order2_beats_order0_on_prose() {
+        let data = prose();
+        let config = EngineConfig::best_ratio();
+        let archive = try_compress_with_config(&data, &config).unwrap();
+        assert_eq!(decompress_data(&archive), data);
+        assert_eq!(decompress_prefix(&archive, 100), &data[..100]);
+
+        for baseline in [
+            compress_data(&data),
+            compress_data_adaptive(&data, DEFAULT_HALF_LIFE),
+        ] {
+            assert!(
+                archive.len() < baseline.len(),
+                "{} против {}",
+                archive.len(),
+                baseline.len()
+            );
+        }
+
+        // Крошечная таблица вытесняет контексты, но остается обратимой
+        for (data, table_bits) in [(&data[..], 1), (b"", 4), (b"a", 8), (b"abababab", 2)] {
+            let config = EngineConfig::default().with_model(ModelKind::HashedOrder2 { table_bits });
+            let archive = try_compress_with_config(data, &config).unwrap();
+            assert_eq!(decompress_data(&archive), data);
+        }
+    }
+
+    #[test]
+    fn test_damaged_context_archives_rejected() {
+        let config = EngineConfig::default().with_model(ModelKind::HashedOrder2 { table_bits: 12 });
+        let archive = try_compress_with_config(prose(), &config).unwrap();
+        let (header, body_offset) = ArchiveHeader::parse(&archive).unwrap();
+        assert_eq!(
+            header.model_kind,
+            ModelKind::HashedOrder2 { table_bits: 12 }
+        );
+
+        let body = &archive[body_offset..];
+        let options = DecodeOptions::default();
+        assert!(try_unweave_context_body(&header, &body[..body.len() / 2], &options).is_err());
+        assert!(try_unweave_context_body(
+            &header,
+            body,
+            &options.clone().with_max_output_bytes(100)
+        )
+        .is_err());
+
+        let static_header = ArchiveHeader::current(ModelKind::StaticOrder0, None);
+        assert_eq!(
+            try_unweave_context_body(&static_header, body, &options),
+            Err(WizardError::UnsupportedModel(0))
+        );
+        assert_eq!(
+            crate::format::parse_artifact(&archive).map(|_| ()),
+            Err(WizardError::UnsupportedModel(3))
+        );
+        assert!(
+            ContextLoom::new(256, MAX_CONTEXT_TABLE_BITS + 1, CoderPrecision::default()).is_err()
+        );
+    }
+}
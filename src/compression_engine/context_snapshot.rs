@@ -0,0 +1,164 @@
+//! Снимок контекста сжатия 📸
+//!
+//! Словарь и модель частот, извлеченные из готового архива, для сжатия
+//! следующей версии похожих данных (очередная ревизия файла, свежая
+//! выгрузка той же таблицы). Новый архив не несет ни словаря, ни таблицы
+//! частот — только идентификатор снимка, поэтому выигрывает тем больше,
+//! чем меньше данные. Полноценной дельта-компрессии снимок не заменяет:
+//! совпадающие фрагменты не ищутся, переиспользуется только статистика.
+//!
+//! Снимок — это [`CompressorHandle`], обученный по частотам архива;
+//! распаковка требует тот же снимок (его можно сохранить в файл словаря).
+
+use super::compression_conjurer::{alphabet_size, CompressionArtifact, WORD_SYMBOL_BASE};
+use super::compressor_handle::{CompressorHandle, HandleArtifact};
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::error::WizardResult;
+use crate::format::artifact_scroll::parse_artifact_with_options;
+
+/// Снимок словаря и модели прошлого сжатия 📸
+#[derive(Debug, Clone)]
+pub struct ContextSnapshot {
+    handle: CompressorHandle,
+}
+
+impl ContextSnapshot {
+    /// Снимок готового артефакта
+    ///
+    /// Частоты фраз переносятся на составляющие их слова: общая модель
+    /// фраз не использует.
+    pub fn from_artifact(artifact: &CompressionArtifact) -> Self {
+        let grimoire_len = artifact.mystical_word_grimoire.len();
+        let alphabet_len = alphabet_size(grimoire_len) as u32;
+
+        let observed =
+            artifact
+                .mystical_frequency_codex
+                .iter()
+                .flat_map(|&(symbol_id, frequency, _)| {
+                    let members: Vec<u32> = if symbol_id < alphabet_len {
+                        vec![symbol_id]
+                    } else {
+                        let phrase_index =
+                            (symbol_id - WORD_SYMBOL_BASE) as usize - 2 * grimoire_len;
+                        artifact
+                            .mystical_phrase_codex
+                            .get(phrase_index)
+                            .map(|phrase| {
+                                phrase
+                                    .iter()
+                                    .copied()
+                                    .filter(|&member| member < alphabet_len)
+                                    .collect()
+                            })
+                            .unwrap_or_default()
+                    };
+                    members.into_iter().map(move |member| (member, frequency))
+                });
+
+        Self {
+            handle: CompressorHandle::from_observed_frequencies(
+                artifact.mystical_word_grimoire.clone(),
+                observed,
+            ),
+        }
+    }
+
+    /// Снимок сериализованного архива (проверяется лимитами `options`)
+    pub fn from_archive(
+        archive_bytes: impl AsRef<[u8]>,
+        options: &DecodeOptions,
+    ) -> WizardResult<Self> {
+        let (artifact, _) = parse_artifact_with_options(archive_bytes.as_ref(), options)?;
+        Ok(Self::from_artifact(&artifact))
+    }
+
+    /// Идентификатор снимка, записываемый в заголовок новых архивов
+    pub fn snapshot_id(&self) -> u64 {
+        self.handle.dictionary_id()
+    }
+
+    /// Сжимает следующую версию данных моделью снимка
+    pub fn compress(&self, data: impl AsRef<[u8]>) -> HandleArtifact {
+        self.handle.compress(data)
+    }
+
+    /// Восстанавливает данные, сжатые этим снимком
+    ///
+    /// Возвращает `None`, если артефакт создан другим снимком или поврежден.
+    pub fn decompress(&self, artifact: &HandleArtifact) -> Option<Vec<u8>> {
+        self.handle.decompress(artifact)
+    }
+
+    /// Дескриптор с моделью снимка (для сохранения в файл словаря)
+    pub fn handle(&self) -> &CompressorHandle {
+        &self.handle
+    }
+
+    /// Превращает снимок в дескриптор
+    pub fn into_handle(self) -> CompressorHandle {
+        self.handle
+    }
+}
+
+impl From<CompressorHandle> for ContextSnapshot {
+    /// Снимок из загруженного файла словаря
+    fn from(handle: CompressorHandle) -> Self {
+        Self { handle }
+    }
+}
+
+/// Тесты снимка контекста 🎯
+#[cfg(test)]
+mod context_snapshot_tests {
+    use super::*;
+    use crate::compression_engine::compression_conjurer::weave_compression_spell;
+    use crate::simple_api::compress_data;
+
+    fn revision(version: usize) -> String {
+        (0..40)
+            .map(|line| {
+                format!("setting_{line} = enabled # revision {version}, owner: wizard team\n")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_next_revision_compresses_smaller() {
+        let snapshot =
+            ContextSnapshot::from_archive(compress_data(revision(1)), &DecodeOptions::default())
+                .unwrap();
+
+        let next = revision(2);
+        let warm = snapshot.compress(&next).to_bytes();
+        assert!(
+            warm.len() < compress_data(&next).len(),
+            "{} против {}",
+            warm.len(),
+            compress_data(&next).len()
+        );
+
+        let artifact = HandleArtifact::from_bytes(&warm).unwrap();
+        assert_eq!(artifact.dictionary_id, snapshot.snapshot_id());
+        assert_eq!(snapshot.decompress(&artifact).unwrap(), next.as_bytes());
+    }
+
+    #[test]
+    fn test_snapshot_survives_dictionary_file() {
+        let snapshot = ContextSnapshot::from_artifact(&weave_compression_spell(revision(1)));
+        let restored = ContextSnapshot::from(
+            CompressorHandle::from_dictionary_bytes(&snapshot.handle().to_dictionary_bytes())
+                .unwrap(),
+        );
+        assert_eq!(restored.snapshot_id(), snapshot.snapshot_id());
+
+        let artifact = snapshot.compress("unseen bytes: ~~~ 日本");
+        assert_eq!(
+            restored.decompress(&artifact).unwrap(),
+            "unseen bytes: ~~~ 日本".as_bytes()
+        );
+
+        let foreign = ContextSnapshot::from_artifact(&weave_compression_spell("other data"));
+        assert!(foreign.decompress(&artifact).is_none());
+    }
+}
@@ -0,0 +1,315 @@
+//! Дедупликация фрагментов 🧩
+//!
+//! Образы дисков и резервные копии повторяют целые мегабайты, а словарь
+//! слов видит только короткие слова и фразы. Стадия
+//! [`PipelineStage::Dedup`](super::PipelineStage::Dedup) режет вход на
+//! фрагменты по содержимому: скользящий хеш (gear) последних 64 байтов
+//! ставит границу там, где его старшие биты нулевые, поэтому вставка
+//! в начале входа сдвигает лишь ближайшую границу, а не все следующие.
+//! Одинаковые фрагменты хранятся один раз и сжимаются словарным
+//! арифметическим движком блоками, а вход становится списком ссылок.
+//!
+//! Тело кадра: `[длина входа u64][фрагментов u32][уникальных u32]`,
+//! длины уникальных фрагментов (`u32`), ссылки фрагментов на уникальные
+//! (`u32`) и архив блоков с уникальными фрагментами подряд.
+
+use super::engine_covenant::{ArithmeticEngine, CompressionEngine, EngineConfig, Frame};
+use crate::decompression_oracle::block_conductor::try_decode_blocks;
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::error::{WizardError, WizardResult};
+use crate::format::artifact_scroll::{check_body_header, ScrollCursor};
+use crate::format::block_ledger::{read_block_ledger, write_block_archive, DEFAULT_BLOCK_SIZE};
+use crate::format::container_runes::{ArchiveHeader, EngineKind, ModelKind};
+use crate::simple_api::try_decompress_frame;
+use std::collections::HashMap;
+
+/// Минимальная длина фрагмента (кроме последнего)
+const MIN_CHUNK: usize = 2 * 1024;
+
+/// Максимальная длина фрагмента
+const MAX_CHUNK: usize = 64 * 1024;
+
+/// Граница — нулевые 13 старших битов хеша: в среднем 8 КиБ сверх минимума
+const CUT_SHIFT: u32 = 64 - 13;
+
+/// Случайные слова скользящего хеша для каждого байта
+const GEAR: [u64; 256] = gear_table();
+
+/// Таблица gear-хеша из генератора splitmix64
+const fn gear_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut state = 0u64;
+    let mut index = 0;
+    while index < table.len() {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut mixed = state;
+        mixed = (mixed ^ (mixed >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        mixed = (mixed ^ (mixed >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        table[index] = mixed ^ (mixed >> 31);
+        index += 1;
+    }
+    table
+}
+
+/// Длина следующего фрагмента в начале `data`
+fn next_cut(data: &[u8]) -> usize {
+    if data.len() <= MIN_CHUNK {
+        return data.len();
+    }
+    let end = data.len().min(MAX_CHUNK);
+    let mut hash = 0u64;
+    for (position, &byte) in data.iter().enumerate().take(end).skip(MIN_CHUNK) {
+        // Сдвиг влево вытесняет байты старше 64 позиций
+        hash = (hash << 1).wrapping_add(GEAR[byte as usize]);
+        if hash >> CUT_SHIFT == 0 {
+            return position + 1;
+        }
+    }
+    end
+}
+
+/// Режет вход на фрагменты по содержимому
+fn split_chunks(data: &[u8]) -> Vec<&[u8]> {
+    let mut chunks = Vec::with_capacity(data.len() / MIN_CHUNK + 1);
+    let mut rest = data;
+    while !rest.is_empty() {
+        let (chunk, tail) = rest.split_at(next_cut(rest));
+        chunks.push(chunk);
+        rest = tail;
+    }
+    chunks
+}
+
+/// Движок дедупликации фрагментов 🧩
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DedupEngine;
+
+impl CompressionEngine for DedupEngine {
+    fn engine_kind(&self) -> EngineKind {
+        EngineKind::Dedup
+    }
+
+    fn name(&self) -> &'static str {
+        "dedup"
+    }
+
+    fn compress(&self, data: &[u8], config: &EngineConfig) -> Frame {
+        let chunks = split_chunks(data);
+        let mut unique_chunks: Vec<&[u8]> = Vec::new();
+        let mut unique_index: HashMap<&[u8], u32> = HashMap::new();
+        let references: Vec<u32> = chunks
+            .iter()
+            .map(|&chunk| {
+                *unique_index.entry(chunk).or_insert_with(|| {
+                    unique_chunks.push(chunk);
+                    (unique_chunks.len() - 1) as u32
+                })
+            })
+            .collect();
+
+        let store_archive = weave_store(&unique_chunks.concat(), config);
+
+        let mut body =
+            Vec::with_capacity(16 + 4 * (unique_chunks.len() + chunks.len()) + store_archive.len());
+        body.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        body.extend_from_slice(&(chunks.len() as u32).to_le_bytes());
+        body.extend_from_slice(&(unique_chunks.len() as u32).to_le_bytes());
+        for chunk in &unique_chunks {
+            body.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+        }
+        for reference in &references {
+            body.extend_from_slice(&reference.to_le_bytes());
+        }
+        body.extend_from_slice(&store_archive);
+
+        Frame {
+            header: ArchiveHeader::current(ModelKind::StaticOrder0, None)
+                .with_engine(EngineKind::Dedup),
+            body,
+        }
+    }
+
+    fn try_decompress(&self, frame: &Frame, options: &DecodeOptions) -> WizardResult<Vec<u8>> {
+        try_undedup_body(&frame.header, &frame.body, options)
+    }
+}
+
+/// Проверяемая распаковка тела кадра дедупликации 🛡️
+pub(crate) fn try_undedup_body(
+    header: &ArchiveHeader,
+    body: &[u8],
+    options: &DecodeOptions,
+) -> WizardResult<Vec<u8>> {
+    check_body_header(header, EngineKind::Dedup)?;
+    if header.alphabet_map.is_some() {
+        return Err(WizardError::CorruptFrequencyTable(
+            "атлас алфавита не применяется к дедупликации",
+        ));
+    }
+
+    let mut cursor = ScrollCursor::new(body, 0);
+    let restored_len = cursor.read_u64()?;
+    let chunk_count = cursor.read_u32()? as usize;
+    let unique_count = cursor.read_u32()? as usize;
+    options.reserve_output(restored_len)?;
+    // Таблицы читаются срезами тела: длина проверена до выделения памяти
+    let chunk_lengths = cursor.read_slice(4 * unique_count)?;
+    let references = cursor.read_slice(4 * chunk_count)?;
+
+    let mut chunk_offsets = Vec::with_capacity(unique_count + 1);
+    let mut store_len = 0u64;
+    chunk_offsets.push(0usize);
+    for chunk_len in chunk_lengths.chunks_exact(4) {
+        store_len += u32::from_le_bytes(chunk_len.try_into().expect("срез из 4 байтов")) as u64;
+        // Каждый уникальный фрагмент входит в результат хотя бы раз
+        if store_len > restored_len {
+            return Err(WizardError::CorruptPayload);
+        }
+        chunk_offsets.push(store_len as usize);
+    }
+
+    let store_offset = cursor.position();
+    let store = try_unweave_store(&body[store_offset..], options)
+        .map_err(|error| error.shifted(store_offset))?;
+    if store.len() as u64 != store_len {
+        return Err(WizardError::CorruptPayload);
+    }
+
+    let mut restored = Vec::with_capacity(restored_len as usize);
+    for reference in references.chunks_exact(4) {
+        let index = u32::from_le_bytes(reference.try_into().expect("срез из 4 байтов"));
+        let chunk = match chunk_offsets.get(index as usize..index as usize + 2) {
+            Some(&[start, end]) => &store[start..end],
+            _ => return Err(WizardError::CorruptPayload),
+        };
+        if (restored.len() + chunk.len()) as u64 > restored_len {
+            return Err(WizardError::CorruptPayload);
+        }
+        restored.extend_from_slice(chunk);
+    }
+    if restored.len() as u64 != restored_len {
+        return Err(WizardError::CorruptPayload);
+    }
+    Ok(restored)
+}
+
+/// Сжимает хранилище словарным движком блоками
+pub(crate) fn weave_store(store: &[u8], config: &EngineConfig) -> Vec<u8> {
+    let blocks: Vec<(u64, Vec<u8>)> = store
+        .chunks(DEFAULT_BLOCK_SIZE)
+        .map(|block| {
+            (
+                block.len() as u64,
+                ArithmeticEngine.compress(block, config).to_bytes(),
+            )
+        })
+        .collect();
+    write_block_archive(&blocks)
+}
+
+/// Распаковывает архив блоков хранилища
+pub(crate) fn try_unweave_store(
+    store_archive: &[u8],
+    options: &DecodeOptions,
+) -> WizardResult<Vec<u8>> {
+    let (header, body_offset) = ArchiveHeader::try_parse(store_archive)?;
+    let blocks = read_block_ledger(&header, &store_archive[body_offset..], options)
+        .map_err(|error| error.shifted(body_offset))?;
+    try_decode_blocks(store_archive, &blocks, options, |frame, options| {
+        // Только словарный движок: вложенная стадия раздула бы стек
+        match ArchiveHeader::parse(frame) {
+            Some((header, _)) if header.engine == EngineKind::Arithmetic => {
+                try_decompress_frame(frame, options)
+            }
+            Some((header, _)) => Err(WizardError::UnsupportedEngine(header.engine.to_byte())),
+            None => Err(WizardError::UnrecognizedHeader),
+        }
+    })
+}
+
+/// Тесты дедупликации 🎯
+#[cfg(test)]
+mod dedup_tests {
+    use super::*;
+    use crate::compression_engine::engine_covenant::PipelineStage;
+    use crate::simple_api::{compress_data, compress_with_stage, decompress_data};
+
+    /// Псевдослучайные (несжимаемые) байты
+    fn noise(len: usize, seed: u32) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect()
+    }
+
+    /// Образ диска: один несжимаемый регион, повторенный со вставками
+    fn disk_image() -> Vec<u8> {
+        let region = noise(160 * 1024, 0x2545_F491);
+        let mut image = Vec::new();
+        for copy in 0..8u8 {
+            image.extend_from_slice(&[copy; 37]);
+            image.extend_from_slice(&region);
+        }
+        image
+    }
+
+    #[test]
+    fn test_repeated_regions_shrink_beyond_dictionary() {
+        let image = disk_image();
+        let deduplicated = compress_with_stage(&image, PipelineStage::Dedup);
+        assert_eq!(decompress_data(&deduplicated), image);
+        assert!(deduplicated.len() < image.len() / 3);
+
+        // Шум несжимаем для словаря: выигрыш дает только дедупликация
+        let region = &image[37..37 + 160 * 1024];
+        assert!(compress_data(region).len() > region.len() * 9 / 10);
+
+        let chunks = split_chunks(&image);
+        assert!(chunks.iter().all(|chunk| chunk.len() <= MAX_CHUNK));
+        assert_eq!(chunks.concat(), image);
+    }
+
+    #[test]
+    fn test_any_bytes_roundtrip() {
+        let all_bytes: Vec<u8> = (0..=255).collect();
+        let text = "the owl carries scrolls between towers. ".repeat(500);
+        for data in [&b""[..], b"\x01", &all_bytes, text.as_bytes()] {
+            let archive = compress_with_stage(data, PipelineStage::Dedup);
+            assert_eq!(decompress_data(&archive), data);
+        }
+    }
+
+    #[test]
+    fn test_damaged_frame_is_rejected() {
+        let journal = "owl post: scroll delivered to the north tower\n".repeat(2000);
+        let frame = DedupEngine.compress(journal.as_bytes(), &EngineConfig::default());
+        let options = DecodeOptions::default();
+        assert!(matches!(
+            DedupEngine.try_decompress(&frame, &options.clone().with_max_output_bytes(1024)),
+            Err(WizardError::LimitExceeded { .. })
+        ));
+
+        // Ссылка на несуществующий фрагмент
+        let unique_count = u32::from_le_bytes(frame.body[12..16].try_into().unwrap()) as usize;
+        let first_reference = 16 + 4 * unique_count;
+        let mut dangling = frame.clone();
+        dangling.body[first_reference..first_reference + 4]
+            .copy_from_slice(&(unique_count as u32).to_le_bytes());
+        assert_eq!(
+            DedupEngine.try_decompress(&dangling, &options),
+            Err(WizardError::CorruptPayload)
+        );
+
+        let mut truncated = frame;
+        truncated.body.truncate(first_reference);
+        assert!(matches!(
+            DedupEngine.try_decompress(&truncated, &options),
+            Err(WizardError::Truncated { .. })
+        ));
+    }
+}
@@ -0,0 +1,333 @@
+//! Кузница дельт 🩹
+//!
+//! Патч описывает новую версию данных относительно базы, известной обеим
+//! сторонам (предыдущая версия файла в резервной копии). База служит
+//! неявным словарем: совпадающие участки кодируются копиями
+//! `(смещение в базе, длина)`, остальное — литералами.
+//!
+//! Раскладка патча:
+//! `[b"ACWP"][версия u8][id базы u64][длина базы u64][длина результата u64][упаковка u8][команды]`.
+//! Команды — `[кол-во команд u32]`, затем
+//! `[длина литерала u32][смещение копии u64][длина копии u32]` на каждую
+//! команду и все литералы подряд. Команда выдает свой литерал, затем копию.
+//! Команды хранятся как есть или, если так короче, обычным архивом
+//! [`compress_data`](crate::simple_api::compress_data): у мелких патчей
+//! таблица частот архива длиннее самих команд.
+
+use crate::bit_wizardry::checksum_runes::rune_hash;
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::error::{WizardError, WizardResult};
+use crate::format::artifact_scroll::ScrollCursor;
+use crate::simple_api::{compress_data, try_decompress_data};
+use std::collections::HashMap;
+
+/// Сигнатура патча
+pub const DELTA_MAGIC: [u8; 4] = *b"ACWP";
+
+/// Текущая версия формата патча
+pub const DELTA_FORMAT_VERSION: u8 = 1;
+
+/// Минимальная длина копии: короче дешевле литерал
+const DELTA_MIN_MATCH: usize = 16;
+
+/// Размер команды: длина литерала u32 + смещение u64 + длина копии u32
+const DELTA_OP_LEN: usize = 4 + 8 + 4;
+
+/// Заголовок патча до упаковки команд
+const DELTA_HEADER_LEN: usize = DELTA_MAGIC.len() + 1 + 8 + 8 + 8;
+
+/// Команды хранятся без сжатия
+const COMMANDS_RAW: u8 = 0;
+
+/// Команды сжаты в архив
+const COMMANDS_ARCHIVED: u8 = 1;
+
+/// Команда патча: литерал, затем копия из базы
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct DeltaOp {
+    literal_len: usize,
+    copy_offset: usize,
+    copy_len: usize,
+}
+
+/// Строит патч, превращающий `base` в `target` 🩹
+pub(crate) fn forge_delta(base: &[u8], target: &[u8]) -> Vec<u8> {
+    let (ops, literals) = find_delta_ops(base, target);
+
+    let mut commands = Vec::with_capacity(4 + ops.len() * DELTA_OP_LEN + literals.len());
+    commands.extend_from_slice(&(ops.len() as u32).to_le_bytes());
+    for op in &ops {
+        commands.extend_from_slice(&(op.literal_len as u32).to_le_bytes());
+        commands.extend_from_slice(&(op.copy_offset as u64).to_le_bytes());
+        commands.extend_from_slice(&(op.copy_len as u32).to_le_bytes());
+    }
+    commands.extend_from_slice(&literals);
+
+    let mut patch = Vec::with_capacity(DELTA_HEADER_LEN + commands.len() / 2);
+    patch.extend_from_slice(&DELTA_MAGIC);
+    patch.push(DELTA_FORMAT_VERSION);
+    patch.extend_from_slice(&rune_hash(base).to_le_bytes());
+    patch.extend_from_slice(&(base.len() as u64).to_le_bytes());
+    patch.extend_from_slice(&(target.len() as u64).to_le_bytes());
+
+    let archived = compress_data(&commands);
+    if archived.len() < commands.len() {
+        patch.push(COMMANDS_ARCHIVED);
+        patch.extend_from_slice(&archived);
+    } else {
+        patch.push(COMMANDS_RAW);
+        patch.extend_from_slice(&commands);
+    }
+    patch
+}
+
+/// Восстанавливает данные из базы и патча 🛡️
+///
+/// Патч от другой базы отклоняется с [`WizardError::DeltaBaseMismatch`]
+/// до распаковки команд. Размер результата проверяется лимитами `options`.
+pub(crate) fn try_apply_delta(
+    base: &[u8],
+    patch: &[u8],
+    options: &DecodeOptions,
+) -> WizardResult<Vec<u8>> {
+    if !patch.starts_with(&DELTA_MAGIC) {
+        return Err(WizardError::UnrecognizedHeader);
+    }
+    let mut cursor = ScrollCursor::new(patch, DELTA_MAGIC.len());
+    let version = cursor.read_slice(1)?[0];
+    if version != DELTA_FORMAT_VERSION {
+        return Err(WizardError::UnsupportedVersion(version));
+    }
+
+    let base_id = cursor.read_u64()?;
+    let base_len = cursor.read_u64()?;
+    if base_id != rune_hash(base) || base_len != base.len() as u64 {
+        return Err(WizardError::DeltaBaseMismatch(base_id));
+    }
+    let target_len = cursor.read_u64()?;
+    options.reserve_output(target_len)?;
+    let target_len = target_len as usize;
+
+    // Команд не больше, чем копий минимальной длины, плюс завершающая
+    let max_ops = target_len / DELTA_MIN_MATCH + 1;
+    let commands_limit = max_ops
+        .saturating_mul(DELTA_OP_LEN)
+        .saturating_add(target_len.saturating_add(4));
+    let packing = cursor.read_slice(1)?[0];
+    let packed = &patch[cursor.position()..];
+    let commands = match packing {
+        COMMANDS_RAW if packed.len() <= commands_limit => packed.to_vec(),
+        COMMANDS_RAW => return Err(WizardError::CorruptDelta("команды длиннее результата")),
+        COMMANDS_ARCHIVED => try_decompress_data(
+            packed,
+            &options.clone().with_max_output_bytes(commands_limit),
+        )?,
+        _ => return Err(WizardError::CorruptDelta("неизвестная упаковка команд")),
+    };
+
+    let mut commands = ScrollCursor::new(&commands, 0);
+    let op_count = commands.read_u32()? as usize;
+    if op_count > max_ops {
+        return Err(WizardError::CorruptDelta(
+            "команд больше, чем позволяет длина",
+        ));
+    }
+    let mut ops = Vec::with_capacity(op_count);
+    for _ in 0..op_count {
+        ops.push(DeltaOp {
+            literal_len: commands.read_u32()? as usize,
+            copy_offset: commands.read_u64()? as usize,
+            copy_len: commands.read_u32()? as usize,
+        });
+    }
+    let literals = commands.read_slice(commands.remaining())?;
+
+    let mut restored = Vec::with_capacity(target_len);
+    let mut literal_position: usize = 0;
+    for op in ops {
+        let literal = literals
+            .get(literal_position..literal_position.saturating_add(op.literal_len))
+            .ok_or(WizardError::CorruptDelta("литерал за пределами команд"))?;
+        literal_position += op.literal_len;
+        let copy = base
+            .get(op.copy_offset..op.copy_offset.saturating_add(op.copy_len))
+            .ok_or(WizardError::CorruptDelta("копия за пределами базы"))?;
+        if restored.len() + literal.len() + copy.len() > target_len {
+            return Err(WizardError::CorruptDelta("команды длиннее результата"));
+        }
+        restored.extend_from_slice(literal);
+        restored.extend_from_slice(copy);
+    }
+
+    if literal_position != literals.len() || restored.len() != target_len {
+        return Err(WizardError::CorruptDelta(
+            "длина результата не совпадает с заголовком",
+        ));
+    }
+    Ok(restored)
+}
+
+/// Жадный поиск копий из базы
+///
+/// Окна базы длины [`DELTA_MIN_MATCH`] индексируются по первому
+/// вхождению. Сначала проверяется продолжение предыдущей копии (правка
+/// той же длины), затем индекс; найденная копия растет в обе стороны.
+fn find_delta_ops(base: &[u8], target: &[u8]) -> (Vec<DeltaOp>, Vec<u8>) {
+    let mut window_index: HashMap<u64, usize> = HashMap::new();
+    if base.len() >= DELTA_MIN_MATCH {
+        for offset in 0..=base.len() - DELTA_MIN_MATCH {
+            window_index
+                .entry(rune_hash(&base[offset..offset + DELTA_MIN_MATCH]))
+                .or_insert(offset);
+        }
+    }
+
+    let mut ops = Vec::new();
+    let mut literals = Vec::new();
+    let mut literal_start = 0;
+    let mut expected_offset = 0;
+    let mut position = 0;
+
+    while position + DELTA_MIN_MATCH <= target.len() {
+        let window = &target[position..position + DELTA_MIN_MATCH];
+        let pending_literal = position - literal_start;
+        let continued = expected_offset + pending_literal;
+        let candidate = if base.get(continued..continued + DELTA_MIN_MATCH) == Some(window) {
+            Some(continued)
+        } else {
+            window_index
+                .get(&rune_hash(window))
+                .copied()
+                .filter(|&offset| &base[offset..offset + DELTA_MIN_MATCH] == window)
+        };
+
+        let Some(mut copy_offset) = candidate else {
+            position += 1;
+            continue;
+        };
+
+        // Рост назад за счет еще не выданного литерала
+        let mut copy_start = position;
+        while copy_start > literal_start
+            && copy_offset > 0
+            && base[copy_offset - 1] == target[copy_start - 1]
+        {
+            copy_start -= 1;
+            copy_offset -= 1;
+        }
+        // Рост вперед
+        let mut copy_len = position - copy_start + DELTA_MIN_MATCH;
+        while copy_start + copy_len < target.len()
+            && copy_offset + copy_len < base.len()
+            && base[copy_offset + copy_len] == target[copy_start + copy_len]
+        {
+            copy_len += 1;
+        }
+
+        literals.extend_from_slice(&target[literal_start..copy_start]);
+        ops.push(DeltaOp {
+            literal_len: copy_start - literal_start,
+            copy_offset,
+            copy_len,
+        });
+        position = copy_start + copy_len;
+        literal_start = position;
+        expected_offset = copy_offset + copy_len;
+    }
+
+    if literal_start < target.len() {
+        literals.extend_from_slice(&target[literal_start..]);
+        ops.push(DeltaOp {
+            literal_len: target.len() - literal_start,
+            copy_offset: 0,
+            copy_len: 0,
+        });
+    }
+
+    (ops, literals)
+}
+
+/// Тесты кузницы дельт 🎯
+#[cfg(test)]
+mod delta_forge_tests {
+    use super::*;
+
+    fn base_version() -> Vec<u8> {
+        (0..300)
+            .map(|line| format!("record {line}: status=active, quota={}\n", line * 7))
+            .collect::<String>()
+            .into_bytes()
+    }
+
+    #[test]
+    fn test_small_edit_gives_small_patch() {
+        let base = base_version();
+        let edited = String::from_utf8(base.clone())
+            .unwrap()
+            .replace("record 150: status=active", "record 150: status=frozen")
+            .replace("record 42:", "record 42 (renamed):")
+            + "record 300: status=new\n";
+
+        let patch = forge_delta(&base, edited.as_bytes());
+        assert!(
+            patch.len() < 300,
+            "патч {} байт против {} байт данных",
+            patch.len(),
+            edited.len()
+        );
+        assert_eq!(
+            try_apply_delta(&base, &patch, &DecodeOptions::default()).unwrap(),
+            edited.as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_edge_cases_roundtrip() {
+        let base = base_version();
+        let cases: [(&[u8], &[u8]); 5] = [
+            (&[], &[]),
+            (&[], b"fresh data without base"),
+            (&base, &[]),
+            (b"short", b"short"),
+            (&base, &base),
+        ];
+        for (base, target) in cases {
+            let patch = forge_delta(base, target);
+            assert_eq!(
+                try_apply_delta(base, &patch, &DecodeOptions::default()).unwrap(),
+                target
+            );
+        }
+    }
+
+    #[test]
+    fn test_wrong_base_and_damage_are_rejected() {
+        let base = base_version();
+        let patch = forge_delta(&base, b"record 1: status=active, quota=7\n");
+
+        let mut other_base = base.clone();
+        other_base[0] ^= 1;
+        assert_eq!(
+            try_apply_delta(&other_base, &patch, &DecodeOptions::default()),
+            Err(WizardError::DeltaBaseMismatch(rune_hash(&base)))
+        );
+
+        // Заголовок обещает больше данных, чем дают команды
+        let mut lying = patch.clone();
+        lying[DELTA_HEADER_LEN - 8..DELTA_HEADER_LEN].copy_from_slice(&1000u64.to_le_bytes());
+        assert!(matches!(
+            try_apply_delta(&base, &lying, &DecodeOptions::default()),
+            Err(WizardError::CorruptDelta(_))
+        ));
+
+        let limited = DecodeOptions::default().with_max_output_bytes(4);
+        assert!(matches!(
+            try_apply_delta(&base, &patch, &limited),
+            Err(WizardError::LimitExceeded { .. })
+        ));
+        assert_eq!(
+            try_apply_delta(&base, b"ACW", &DecodeOptions::default()),
+            Err(WizardError::UnrecognizedHeader)
+        );
+    }
+}
@@ -0,0 +1,564 @@
+//! Завет движков ⚖️
+//!
+//! Общий интерфейс кодеков: каждый движок превращает байты в кадр
+//! [`Frame`] и обратно. Код движка хранится в заголовке кадра, поэтому
+//! новые движки (Хаффман, rANS, хранение без сжатия) добавляются
+//! без изменения формата.
+
+use super::adaptive_tide::{try_unweave_adaptive_body, try_weave_adaptive_frame};
+use super::compression_conjurer::try_weave_with_discovery;
+use super::context_loom::{
+    try_unweave_context_body, try_weave_context_frame, DEFAULT_CONTEXT_TABLE_BITS,
+};
+use super::grimoire_quill::Grimoire;
+use super::grimoire_ward::DenyList;
+use super::preset_tomes::{try_unweave_preset_body, Preset};
+use super::stored_vault::StoredEngine;
+use super::word_frontier::{BoundaryPolicy, WordBoundaries};
+use super::word_sketch::WordCounting;
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::decompression_oracle::decompression_sage::try_unweave_artifact_ref;
+use crate::error::{WizardError, WizardResult};
+use crate::format::artifact_scroll::{
+    check_length_field, parse_artifact_ref_body, serialize_artifact_parts, ScrollOptions,
+};
+use crate::format::container_runes::{ArchiveHeader, EngineKind, ModelKind};
+use std::time::{Duration, Instant};
+
+/// Через сколько символов кодирования адаптивные модели сверяются со сроком
+pub(crate) const DEADLINE_CHECK_SYMBOLS: usize = 1 << 16;
+
+/// Параметры сжатия для движков ⚙️
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineConfig {
+    /// Параметры сериализации кадра
+    pub scroll_options: ScrollOptions,
+    /// Движок результата для [`EngineRegistry::transcode`](super::EngineRegistry::transcode)
+    pub engine: EngineKind,
+    /// Подсчет слов при поиске словаря
+    pub word_counting: WordCounting,
+    /// Слова, которые поиск словаря не должен помещать в заголовок
+    pub deny_list: DenyList,
+    /// Словарь, заданный вручную: поиск слов пропускается
+    ///
+    /// Бюджет словаря, способ подсчета и запретный список к нему не
+    /// применяются; в архив попадают только встретившиеся во входе слова.
+    pub grimoire: Option<Grimoire>,
+    /// Модель арифметического движка: статическая или адаптивная
+    ///
+    /// Общая модель (`SharedOrder0`) требует `CompressorHandle` и здесь
+    /// равнозначна статической.
+    pub model: ModelKind,
+    /// Бюджет словаря в байтах заголовка (`None` — не больше 25 слов)
+    ///
+    /// С бюджетом слова отбираются моделью стоимости: слово попадает в
+    /// словарь, если экономия в теле превышает рост заголовка, а самые
+    /// выгодные на байт заголовка слова занимают бюджет первыми.
+    pub dictionary_budget_bytes: Option<usize>,
+    /// Разбор текста на слова словаря и байты
+    pub parse_strategy: ParseStrategy,
+    /// Распаковывать свежий кадр и сверять его со входом
+    ///
+    /// Для резервных копий: ошибка кодера обнаруживается до того, как
+    /// архив записан, ценой повторного прохода распаковки. Несовпадение —
+    /// [`WizardError::VerificationFailed`].
+    pub verify_roundtrip: bool,
+    /// Границы слов: общие для поиска словаря и подстановки слов
+    pub word_boundaries: WordBoundaries,
+    /// Встроенный словарь арифметического движка
+    ///
+    /// С пресетом кадр кодируется общей моделью пресета (см. [`Preset`]):
+    /// в архиве нет ни словаря, ни таблицы частот, а поиск слов, модель и
+    /// параметры сериализации не применяются.
+    pub preset: Option<Preset>,
+    /// Воспроизводимые кадры: одинаковые данные — одинаковые байты
+    ///
+    /// Включено по умолчанию. Без него [`try_compress_with_config`](crate::simple_api::try_compress_with_config)
+    /// ставит на кадр штамп времени создания и версии библиотеки
+    /// (см. [`Provenance`](crate::format::Provenance)).
+    pub reproducible: bool,
+    /// Выбирать движок и модель по пробе входа
+    ///
+    /// [`try_compress_with_config`](crate::simple_api::try_compress_with_config)
+    /// заменяет `engine` и `model` решением
+    /// [`probe_model`](super::probe_model) и записывает его в метаданные
+    /// архива под [`AUTO_MODEL_METADATA_KEY`](super::AUTO_MODEL_METADATA_KEY).
+    pub auto_model: bool,
+    /// Срок сжатия: после него кадры хранятся как есть
+    ///
+    /// Для путей, где задержка важнее коэффициента. Кадр, который
+    /// арифметический движок кодирует в момент срока, завершается кадром
+    /// хранения (движок сверяется со сроком после поиска словаря и по ходу
+    /// кодирования адаптивных моделей), а следующие кадры и блоки
+    /// хранятся сразу. Архив остается верным, только хуже сжатым.
+    pub deadline: Option<Instant>,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self {
+            scroll_options: ScrollOptions::default(),
+            engine: EngineKind::default(),
+            word_counting: WordCounting::default(),
+            deny_list: DenyList::default(),
+            grimoire: None,
+            model: ModelKind::default(),
+            dictionary_budget_bytes: None,
+            parse_strategy: ParseStrategy::default(),
+            verify_roundtrip: false,
+            word_boundaries: WordBoundaries::default(),
+            preset: None,
+            reproducible: true,
+            auto_model: false,
+            deadline: None,
+        }
+    }
+}
+
+impl EngineConfig {
+    /// Параметры со встроенным словарем 📦
+    pub fn preset(preset: Preset) -> Self {
+        Self::default().with_preset(preset)
+    }
+
+    /// Параметры наибольшего сжатия 🏆
+    ///
+    /// Модель второго порядка с таблицей в
+    /// [`DEFAULT_CONTEXT_TABLE_BITS`](super::DEFAULT_CONTEXT_TABLE_BITS) бит
+    /// (около сотни байтов памяти на каждый встреченный контекст) и
+    /// оптимальный разбор на слова. Медленнее параметров по умолчанию.
+    pub fn best_ratio() -> Self {
+        Self::default()
+            .with_model(ModelKind::HashedOrder2 {
+                table_bits: DEFAULT_CONTEXT_TABLE_BITS,
+            })
+            .with_parse_strategy(ParseStrategy::Optimal)
+    }
+
+    /// Параметры с автоматическим выбором движка и модели 🔮
+    ///
+    /// ```
+    /// use arithmetic_compression_wizard::compression_engine::{EngineConfig, AUTO_MODEL_METADATA_KEY};
+    /// use arithmetic_compression_wizard::format::read_metadata;
+    /// use arithmetic_compression_wizard::simple_api::{decompress_data, try_compress_with_config};
+    ///
+    /// let data = b"let the probe pick the model. ".repeat(100);
+    /// let archive = try_compress_with_config(&data, &EngineConfig::auto_model()).unwrap();
+    /// assert_eq!(decompress_data(&archive), data);
+    /// let decision = &read_metadata(&archive).unwrap()[AUTO_MODEL_METADATA_KEY];
+    /// assert!(decision.starts_with("engine=arithmetic"));
+    /// ```
+    pub fn auto_model() -> Self {
+        Self::default().with_auto_model(true)
+    }
+
+    /// Задает встроенный словарь
+    pub fn with_preset(mut self, preset: Preset) -> Self {
+        self.preset = Some(preset);
+        self
+    }
+
+    /// Задает способ подсчета слов
+    pub fn with_word_counting(mut self, word_counting: WordCounting) -> Self {
+        self.word_counting = word_counting;
+        self
+    }
+
+    /// Задает запретный список слов словаря
+    pub fn with_deny_list(mut self, deny_list: DenyList) -> Self {
+        self.deny_list = deny_list;
+        self
+    }
+
+    /// Задает словарь вручную вместо поиска слов
+    pub fn with_grimoire(mut self, grimoire: Grimoire) -> Self {
+        self.grimoire = Some(grimoire);
+        self
+    }
+
+    /// Задает бюджет словаря в байтах заголовка
+    pub fn with_dictionary_budget_bytes(mut self, dictionary_budget_bytes: usize) -> Self {
+        self.dictionary_budget_bytes = Some(dictionary_budget_bytes);
+        self
+    }
+
+    /// Задает разбор текста на слова словаря
+    pub fn with_parse_strategy(mut self, parse_strategy: ParseStrategy) -> Self {
+        self.parse_strategy = parse_strategy;
+        self
+    }
+
+    /// Включает сверку свежего кадра со входом
+    pub fn with_verify_roundtrip(mut self, verify_roundtrip: bool) -> Self {
+        self.verify_roundtrip = verify_roundtrip;
+        self
+    }
+
+    /// Включает или выключает воспроизводимость кадров
+    pub fn with_reproducible(mut self, reproducible: bool) -> Self {
+        self.reproducible = reproducible;
+        self
+    }
+
+    /// Включает или выключает автоматический выбор движка и модели
+    pub fn with_auto_model(mut self, auto_model: bool) -> Self {
+        self.auto_model = auto_model;
+        self
+    }
+
+    /// Задает срок сжатия ⏳
+    ///
+    /// ```
+    /// use arithmetic_compression_wizard::compression_engine::EngineConfig;
+    /// use arithmetic_compression_wizard::format::{peek_header, EngineKind};
+    /// use arithmetic_compression_wizard::simple_api::{decompress_data, try_compress_with_config};
+    /// use std::time::Instant;
+    ///
+    /// let data = b"the request path cannot wait for the owl. ".repeat(200);
+    /// let config = EngineConfig::default().with_deadline(Instant::now());
+    /// let archive = try_compress_with_config(&data, &config).unwrap();
+    /// assert_eq!(peek_header(&archive).unwrap().engine, EngineKind::Stored);
+    /// assert_eq!(decompress_data(&archive), data);
+    /// ```
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Задает срок сжатия через `budget` от текущего момента
+    ///
+    /// Срок, не представимый как [`Instant`], не ставится.
+    pub fn with_time_budget(mut self, budget: Duration) -> Self {
+        self.deadline = Instant::now().checked_add(budget);
+        self
+    }
+
+    /// Срок сжатия задан и уже истек
+    pub fn deadline_passed(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// [`WizardError::DeadlineExceeded`], если срок сжатия истек
+    pub(crate) fn check_deadline(&self) -> WizardResult<()> {
+        if self.deadline_passed() {
+            Err(WizardError::DeadlineExceeded)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Задает политику границ слов
+    pub fn with_boundary_policy(mut self, policy: impl BoundaryPolicy + 'static) -> Self {
+        self.word_boundaries = WordBoundaries::new(policy);
+        self
+    }
+
+    /// Задает модель арифметического движка
+    pub fn with_model(mut self, model: ModelKind) -> Self {
+        self.model = model;
+        self
+    }
+
+    /// Задает движок результата
+    pub fn with_engine(mut self, engine: EngineKind) -> Self {
+        self.engine = engine;
+        self
+    }
+
+    /// Задает параметры сериализации
+    pub fn with_scroll_options(mut self, scroll_options: ScrollOptions) -> Self {
+        self.scroll_options = scroll_options;
+        self
+    }
+}
+
+/// Разбор текста на слова словаря 🧭
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ParseStrategy {
+    /// Жадный разбор: в каждой позиции берется первое подходящее слово
+    /// словаря
+    #[default]
+    Greedy,
+    /// Кратчайший путь по оценке числа бит
+    ///
+    /// Когда слова словаря перекрываются («the» и «there» без границ слов,
+    /// см. [`NoBoundary`](super::NoBoundary)), жадный разбор
+    /// может выбрать худший вариант. Оптимальный разбор — динамическое
+    /// программирование по позициям текста с ценами символов из жадного
+    /// разбора; из двух разборов остается более дешевый по оценке. Медленнее
+    /// жадного, поэтому выбирается явно для сильного сжатия.
+    Optimal,
+}
+
+/// Структурный режим предобработки 🧩
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum StructuredMode {
+    /// Без предобработки: встроенный арифметический движок
+    #[default]
+    Plain,
+    /// JSON: ключи, строки, числа и пунктуация раздельными потоками
+    Json,
+    /// Журналы: шаблоны повторяющихся строк и их переменные поля
+    LogTemplates,
+    /// Таблицы с разделителем (CSV, TSV): каждый столбец своей моделью
+    Delimited {
+        /// Разделитель полей (`b','`, `b'\t'`...)
+        delimiter: u8,
+    },
+    /// UTF-16 и 16-битные отсчеты: пары байтов little-endian как символы
+    Wide16,
+    /// Текст UTF-8: кодовые точки как символы
+    Utf8,
+}
+
+impl StructuredMode {
+    /// Движок, реализующий режим
+    pub fn engine_kind(self) -> EngineKind {
+        match self {
+            StructuredMode::Plain => EngineKind::Arithmetic,
+            StructuredMode::Json => EngineKind::StructuredJson,
+            StructuredMode::LogTemplates => EngineKind::LogTemplates,
+            StructuredMode::Delimited { .. } => EngineKind::Columnar,
+            StructuredMode::Wide16 => EngineKind::Wide16,
+            StructuredMode::Utf8 => EngineKind::Utf8,
+        }
+    }
+}
+
+/// Стадия предобработки байтов перед энтропийным кодером 🪜
+///
+/// В отличие от [`StructuredMode`], стадия не разбирает синтаксис, а
+/// меняет саму единицу моделирования.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PipelineStage {
+    /// Битовые плоскости: биты `k` всех байтов — своя плоскость со своей
+    /// моделью (битовые карты, фильтры Блума, булевы столбцы)
+    BitPlanes,
+    /// Дедупликация: фрагменты по содержимому, повторы хранятся один раз
+    /// (образы дисков, резервные копии)
+    Dedup,
+    /// Длинные повторы: блоки от 4 КиБ, встреченные раньше, становятся
+    /// ссылками назад (повторяющиеся секторы образов дисков)
+    LongRepeats,
+}
+
+impl PipelineStage {
+    /// Движок, реализующий стадию
+    pub fn engine_kind(self) -> EngineKind {
+        match self {
+            PipelineStage::BitPlanes => EngineKind::BitPlanes,
+            PipelineStage::Dedup => EngineKind::Dedup,
+            PipelineStage::LongRepeats => EngineKind::LongRepeats,
+        }
+    }
+}
+
+/// Кадр: заголовок с кодом движка и тело, понятное только этому движку 🎞️
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// Заголовок кадра
+    pub header: ArchiveHeader,
+    /// Тело кадра
+    pub body: Vec<u8>,
+}
+
+impl Frame {
+    /// Движок, которым закодирован кадр
+    pub fn engine(&self) -> EngineKind {
+        self.header.engine
+    }
+
+    /// Сериализует кадр
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result = Vec::with_capacity(self.header.encoded_len() + self.body.len());
+        self.header.write_to(&mut result);
+        result.extend_from_slice(&self.body);
+        result
+    }
+
+    /// Разбирает кадр (тело не проверяется)
+    pub fn from_bytes(frame_bytes: &[u8]) -> WizardResult<Self> {
+        let (header, body_offset) =
+            ArchiveHeader::parse(frame_bytes).ok_or(WizardError::UnrecognizedHeader)?;
+        Ok(Self {
+            header,
+            body: frame_bytes[body_offset..].to_vec(),
+        })
+    }
+}
+
+/// Движок сжатия 🔌
+pub trait CompressionEngine: Send + Sync {
+    /// Код движка в заголовке кадра
+    fn engine_kind(&self) -> EngineKind;
+
+    /// Человекочитаемое имя
+    fn name(&self) -> &'static str;
+
+    /// Сжимает данные в кадр
+    fn compress(&self, data: &[u8], config: &EngineConfig) -> Frame;
+
+    /// Проверяемая распаковка кадра (не паникует)
+    fn try_decompress(&self, frame: &Frame, options: &DecodeOptions) -> WizardResult<Vec<u8>>;
+
+    /// Распаковка доверенного кадра
+    ///
+    /// Паникует на поврежденных данных — для недоверенного ввода
+    /// используйте [`CompressionEngine::try_decompress`].
+    fn decompress(&self, frame: &Frame) -> Vec<u8> {
+        self.try_decompress(frame, &DecodeOptions::unlimited())
+            .expect("кадр поврежден: используйте try_decompress")
+    }
+}
+
+/// Сжатие движком со сверкой, если ее требует `config` 🔍
+///
+/// Кадр распаковывается тем же движком без лимитов; ошибка распаковки
+/// тоже считается несовпадением.
+pub(crate) fn compress_verified(
+    engine: &dyn CompressionEngine,
+    data: &[u8],
+    config: &EngineConfig,
+) -> WizardResult<Frame> {
+    let frame = engine.compress(data, config);
+    if config.verify_roundtrip {
+        match engine.try_decompress(&frame, &DecodeOptions::unlimited()) {
+            Ok(restored) if restored == data => {}
+            _ => return Err(WizardError::VerificationFailed),
+        }
+    }
+    Ok(frame)
+}
+
+/// Арифметический движок со словарем 🧮
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ArithmeticEngine;
+
+impl CompressionEngine for ArithmeticEngine {
+    fn engine_kind(&self) -> EngineKind {
+        EngineKind::Arithmetic
+    }
+
+    fn name(&self) -> &'static str {
+        "arithmetic"
+    }
+
+    fn compress(&self, data: &[u8], config: &EngineConfig) -> Frame {
+        if config.deadline_passed() {
+            return StoredEngine.compress(data, config);
+        }
+        if let Some(preset) = config.preset {
+            check_length_field(data.len())
+                .expect("вход больше точности кодера: разбейте вход на блоки");
+            return Frame::from_bytes(&preset.handle().compress(data).to_bytes())
+                .expect("артефакт дескриптора имеет заголовок");
+        }
+        let woven = match config.model {
+            ModelKind::Adaptive { half_life } => try_weave_adaptive_frame(data, half_life, config),
+            ModelKind::HashedOrder2 { table_bits } => {
+                try_weave_context_frame(data, table_bits, config)
+            }
+            ModelKind::StaticOrder0 | ModelKind::SharedOrder0 => {
+                try_weave_with_discovery(data, config).map(|artifact| {
+                    let (header, body) =
+                        serialize_artifact_parts(&artifact, &config.scroll_options);
+                    Frame { header, body }
+                })
+            }
+        };
+        match woven {
+            Ok(frame) => frame,
+            // Срок истек посреди кадра: вход уходит кадром хранения
+            Err(WizardError::DeadlineExceeded) => StoredEngine.compress(data, config),
+            Err(error) => panic!("{error}: разбейте вход на блоки"),
+        }
+    }
+
+    fn try_decompress(&self, frame: &Frame, options: &DecodeOptions) -> WizardResult<Vec<u8>> {
+        if frame.header.dictionary_id.is_some() {
+            return try_unweave_preset_body(&frame.header, &frame.body, options);
+        }
+        match frame.header.model_kind {
+            ModelKind::Adaptive { .. } => {
+                return try_unweave_adaptive_body(&frame.header, &frame.body, options);
+            }
+            ModelKind::HashedOrder2 { .. } => {
+                return try_unweave_context_body(&frame.header, &frame.body, options);
+            }
+            ModelKind::StaticOrder0 | ModelKind::SharedOrder0 => {}
+        }
+        let artifact_ref = parse_artifact_ref_body(&frame.header, &frame.body, options)?;
+        try_unweave_artifact_ref(&artifact_ref, options)
+    }
+}
+
+/// Тесты завета движков 🎯
+#[cfg(test)]
+mod engine_covenant_tests {
+    use super::*;
+    use crate::simple_api::{compress_data, decompress_data};
+
+    #[test]
+    fn test_arithmetic_frame_matches_simple_api() {
+        let data = b"frames carry their engine in the header";
+        let frame = ArithmeticEngine.compress(data, &EngineConfig::default());
+
+        assert_eq!(frame.engine(), EngineKind::Arithmetic);
+        assert_eq!(frame.to_bytes(), compress_data(data));
+        assert_eq!(decompress_data(frame.to_bytes()), data);
+
+        let parsed = Frame::from_bytes(&frame.to_bytes()).unwrap();
+        assert_eq!(ArithmeticEngine.decompress(&parsed), data);
+    }
+
+    #[test]
+    fn test_expired_deadline_finishes_frame_as_stored() {
+        let data = b"the deadline arrives before the owl. ".repeat(100);
+        let expired = EngineConfig::default().with_deadline(Instant::now());
+        assert!(expired.deadline_passed());
+        assert_eq!(
+            try_weave_with_discovery(&data, &expired).unwrap_err(),
+            WizardError::DeadlineExceeded
+        );
+        assert_eq!(
+            try_weave_adaptive_frame(&data, 64, &expired).unwrap_err(),
+            WizardError::DeadlineExceeded
+        );
+        assert_eq!(
+            try_weave_context_frame(&data, 12, &expired).unwrap_err(),
+            WizardError::DeadlineExceeded
+        );
+
+        for model in [
+            ModelKind::StaticOrder0,
+            ModelKind::Adaptive { half_life: 64 },
+            ModelKind::HashedOrder2 { table_bits: 12 },
+        ] {
+            let frame = ArithmeticEngine.compress(&data, &expired.clone().with_model(model));
+            assert_eq!(frame.engine(), EngineKind::Stored);
+            assert_eq!(decompress_data(frame.to_bytes()), data);
+        }
+
+        // Далекий срок не мешает сжатию
+        let relaxed = EngineConfig::default().with_time_budget(Duration::from_secs(3600));
+        assert!(!relaxed.deadline_passed());
+        let frame = ArithmeticEngine.compress(&data, &relaxed);
+        assert_eq!(frame.engine(), EngineKind::Arithmetic);
+        assert_eq!(frame.to_bytes(), compress_data(&data));
+    }
+
+    #[test]
+    fn test_foreign_frame_is_rejected() {
+        let frame = Frame {
+            header: ArchiveHeader::current(ModelKind::StaticOrder0, None)
+                .with_engine(EngineKind::Custom(200)),
+            body: b"not arithmetic".to_vec(),
+        };
+
+        assert_eq!(
+            ArithmeticEngine.try_decompress(&frame, &DecodeOptions::default()),
+            Err(WizardError::UnsupportedEngine(200))
+        );
+    }
+}
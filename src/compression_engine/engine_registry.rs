@@ -0,0 +1,426 @@
+//! Реестр движков 📚
+//!
+//! Сопоставляет код движка из заголовка кадра с реализацией
+//! [`CompressionEngine`]. Встроенный арифметический движок регистрируется
+//! по умолчанию, сторонние движки используют коды 128–255.
+
+use super::code_point_glyphs::CodePointSymbolizer;
+use super::column_weave::ColumnarEngine;
+use super::dedup::DedupEngine;
+use super::engine_covenant::{
+    compress_verified, ArithmeticEngine, CompressionEngine, EngineConfig, Frame,
+};
+use super::json_lens::JsonStructureEngine;
+use super::log_templates::LogTemplateSymbolizer;
+use super::model_blend::MixEngine;
+use super::plane_sieve::BitPlaneEngine;
+use super::repeat_echo::LongRepeatEngine;
+use super::split_stream_engine::SplitStreamEngine;
+use super::stored_vault::StoredEngine;
+use super::symbolizer::SymbolizerEngine;
+use super::wide_glyphs::WideSymbolizer;
+use crate::decompression_oracle::block_conductor::decode_block;
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::error::{WizardError, WizardResult};
+use crate::format::block_ledger::{read_block_ledger, write_block_archive};
+use crate::format::container_runes::{ArchiveHeader, EngineKind};
+use crate::format::metadata_runes::{attach_metadata, read_metadata};
+
+/// Реестр движков сжатия 📚
+pub struct EngineRegistry {
+    engines: Vec<Box<dyn CompressionEngine>>,
+}
+
+impl EngineRegistry {
+    /// Пустой реестр
+    pub fn empty() -> Self {
+        Self {
+            engines: Vec::new(),
+        }
+    }
+
+    /// Реестр со встроенными движками
+    pub fn with_builtin_engines() -> Self {
+        let mut registry = Self::empty();
+        registry.register(Box::new(ArithmeticEngine));
+        registry.register(Box::new(SplitStreamEngine));
+        registry.register(Box::new(JsonStructureEngine));
+        registry.register(Box::new(SymbolizerEngine::new(
+            EngineKind::LogTemplates,
+            LogTemplateSymbolizer,
+        )));
+        registry.register(Box::new(ColumnarEngine::default()));
+        registry.register(Box::new(StoredEngine));
+        registry.register(Box::new(SymbolizerEngine::new(
+            EngineKind::Wide16,
+            WideSymbolizer,
+        )));
+        registry.register(Box::new(BitPlaneEngine));
+        registry.register(Box::new(DedupEngine));
+        registry.register(Box::new(MixEngine));
+        registry.register(Box::new(SymbolizerEngine::new(
+            EngineKind::Utf8,
+            CodePointSymbolizer,
+        )));
+        registry.register(Box::new(LongRepeatEngine));
+        registry
+    }
+
+    /// Регистрирует движок
+    ///
+    /// Возвращает ранее зарегистрированный движок с тем же кодом.
+    pub fn register(
+        &mut self,
+        engine: Box<dyn CompressionEngine>,
+    ) -> Option<Box<dyn CompressionEngine>> {
+        let kind = engine.engine_kind();
+        match self
+            .engines
+            .iter_mut()
+            .find(|existing| existing.engine_kind() == kind)
+        {
+            Some(existing) => Some(std::mem::replace(existing, engine)),
+            None => {
+                self.engines.push(engine);
+                None
+            }
+        }
+    }
+
+    /// Движок по коду
+    pub fn get(&self, kind: EngineKind) -> Option<&dyn CompressionEngine> {
+        self.engines
+            .iter()
+            .find(|engine| engine.engine_kind() == kind)
+            .map(|engine| engine.as_ref())
+    }
+
+    /// Код движка по имени ([`CompressionEngine::name`])
+    pub fn kind_by_name(&self, name: &str) -> Option<EngineKind> {
+        self.engines
+            .iter()
+            .find(|engine| engine.name() == name)
+            .map(|engine| engine.engine_kind())
+    }
+
+    /// Коды зарегистрированных движков
+    pub fn engine_kinds(&self) -> Vec<EngineKind> {
+        self.engines
+            .iter()
+            .map(|engine| engine.engine_kind())
+            .collect()
+    }
+
+    /// Сжимает данные выбранным движком
+    ///
+    /// С [`EngineConfig::verify_roundtrip`] кадр перед возвратом
+    /// распаковывается и сверяется со входом.
+    pub fn compress(
+        &self,
+        kind: EngineKind,
+        data: impl AsRef<[u8]>,
+        config: &EngineConfig,
+    ) -> WizardResult<Frame> {
+        let engine = self
+            .get(kind)
+            .ok_or(WizardError::UnsupportedEngine(kind.to_byte()))?;
+        compress_verified(engine, data.as_ref(), config)
+    }
+
+    /// Распаковывает сериализованный кадр движком из его заголовка
+    pub fn try_decompress(
+        &self,
+        frame_bytes: impl AsRef<[u8]>,
+        options: &DecodeOptions,
+    ) -> WizardResult<Vec<u8>> {
+        let frame = Frame::from_bytes(frame_bytes.as_ref())?;
+        let engine = self
+            .get(frame.engine())
+            .ok_or(WizardError::UnsupportedEngine(frame.engine().to_byte()))?;
+        engine.try_decompress(&frame, options)
+    }
+
+    /// Перекодирует архив движком `target.engine` с параметрами `target` 🔁
+    ///
+    /// Каждый кадр распаковывается движком из своего заголовка и сразу
+    /// сжимается заново. Архив из блоков сохраняет число и границы блоков,
+    /// метаданные архива переносятся в результат.
+    /// Архивы с внешним словарем требуют словарь и отвергаются.
+    pub fn transcode(
+        &self,
+        archive_bytes: impl AsRef<[u8]>,
+        target: &EngineConfig,
+        options: &DecodeOptions,
+    ) -> WizardResult<Vec<u8>> {
+        let archive_bytes = archive_bytes.as_ref();
+        let target_engine = self
+            .get(target.engine)
+            .ok_or(WizardError::UnsupportedEngine(target.engine.to_byte()))?;
+        let (header, body_offset) =
+            ArchiveHeader::parse(archive_bytes).ok_or(WizardError::UnrecognizedHeader)?;
+
+        let metadata = read_metadata(archive_bytes)?;
+
+        let transcoded = if header.block_framed {
+            let decode_frame =
+                |frame: &[u8], options: &DecodeOptions| self.try_decompress(frame, options);
+            let blocks = read_block_ledger(&header, &archive_bytes[body_offset..], options)?
+                .iter()
+                .map(|block| {
+                    let data = decode_block(block, options, &decode_frame)?;
+                    let frame = compress_verified(target_engine, &data, target)?;
+                    Ok((block.original_len, frame.to_bytes()))
+                })
+                .collect::<WizardResult<Vec<_>>>()?;
+            write_block_archive(&blocks)
+        } else {
+            let data = self.try_decompress(archive_bytes, options)?;
+            compress_verified(target_engine, &data, target)?.to_bytes()
+        };
+
+        if metadata.is_empty() {
+            return Ok(transcoded);
+        }
+        attach_metadata(transcoded, &metadata)
+    }
+}
+
+impl Default for EngineRegistry {
+    fn default() -> Self {
+        Self::with_builtin_engines()
+    }
+}
+
+/// Тесты реестра движков 🎯
+#[cfg(test)]
+mod engine_registry_tests {
+    use super::*;
+    use crate::format::block_ledger::parse_block_ledger;
+    use crate::format::container_runes::ModelKind;
+    use crate::simple_api::{
+        compress_data, compress_data_in_blocks, compress_with_metadata, decompress_data,
+    };
+    use std::collections::HashMap;
+
+    /// Сторонний движок: хранит данные без сжатия
+    struct ThirdPartyStoredEngine;
+
+    impl CompressionEngine for ThirdPartyStoredEngine {
+        fn engine_kind(&self) -> EngineKind {
+            EngineKind::Custom(200)
+        }
+
+        fn name(&self) -> &'static str {
+            "stored"
+        }
+
+        fn compress(&self, data: &[u8], _config: &EngineConfig) -> Frame {
+            Frame {
+                header: ArchiveHeader::current(ModelKind::StaticOrder0, None)
+                    .with_engine(self.engine_kind()),
+                body: data.to_vec(),
+            }
+        }
+
+        fn try_decompress(&self, frame: &Frame, options: &DecodeOptions) -> WizardResult<Vec<u8>> {
+            options.reserve_output(frame.body.len() as u64)?;
+            Ok(frame.body.clone())
+        }
+    }
+
+    #[test]
+    fn test_builtin_registry_roundtrip() {
+        let registry = EngineRegistry::default();
+        let frame = registry
+            .compress(
+                EngineKind::Arithmetic,
+                b"registry magic",
+                &EngineConfig::default(),
+            )
+            .unwrap();
+
+        let restored = registry
+            .try_decompress(frame.to_bytes(), &DecodeOptions::default())
+            .unwrap();
+        assert_eq!(restored, b"registry magic");
+        assert_eq!(
+            registry.kind_by_name("long-repeats"),
+            Some(EngineKind::LongRepeats)
+        );
+        assert_eq!(registry.kind_by_name("huffman"), None);
+    }
+
+    #[test]
+    fn test_third_party_engine_dispatch() {
+        let mut registry = EngineRegistry::default();
+        let frame = ThirdPartyStoredEngine.compress(b"raw", &EngineConfig::default());
+
+        assert_eq!(
+            registry.try_decompress(frame.to_bytes(), &DecodeOptions::default()),
+            Err(WizardError::UnsupportedEngine(200))
+        );
+
+        assert!(registry
+            .register(Box::new(ThirdPartyStoredEngine))
+            .is_none());
+        assert!(registry
+            .register(Box::new(ThirdPartyStoredEngine))
+            .is_some());
+        assert_eq!(
+            registry.engine_kinds(),
+            vec![
+                EngineKind::Arithmetic,
+                EngineKind::SplitStreams,
+                EngineKind::StructuredJson,
+                EngineKind::LogTemplates,
+                EngineKind::Columnar,
+                EngineKind::Stored,
+                EngineKind::Wide16,
+                EngineKind::BitPlanes,
+                EngineKind::Dedup,
+                EngineKind::Mix,
+                EngineKind::Utf8,
+                EngineKind::LongRepeats,
+                EngineKind::Custom(200)
+            ]
+        );
+        assert_eq!(
+            registry
+                .try_decompress(frame.to_bytes(), &DecodeOptions::default())
+                .unwrap(),
+            b"raw"
+        );
+    }
+
+    /// Движок с ошибкой: теряет последний байт при распаковке
+    struct LossyEngine;
+
+    impl CompressionEngine for LossyEngine {
+        fn engine_kind(&self) -> EngineKind {
+            EngineKind::Custom(201)
+        }
+
+        fn name(&self) -> &'static str {
+            "lossy"
+        }
+
+        fn compress(&self, data: &[u8], config: &EngineConfig) -> Frame {
+            ThirdPartyStoredEngine.compress(data, config)
+        }
+
+        fn try_decompress(&self, frame: &Frame, _options: &DecodeOptions) -> WizardResult<Vec<u8>> {
+            Ok(frame.body[..frame.body.len().saturating_sub(1)].to_vec())
+        }
+    }
+
+    #[test]
+    fn test_verify_roundtrip_catches_broken_engine() {
+        let mut registry = EngineRegistry::default();
+        registry.register(Box::new(LossyEngine));
+        let verified = EngineConfig::default().with_verify_roundtrip(true);
+
+        assert!(registry
+            .compress(EngineKind::Custom(201), b"backup", &EngineConfig::default())
+            .is_ok());
+        assert_eq!(
+            registry.compress(EngineKind::Custom(201), b"backup", &verified),
+            Err(WizardError::VerificationFailed)
+        );
+
+        // Исправные движки проходят сверку и дают те же кадры
+        let data = "nightly backup of the tower library. ".repeat(20);
+        for kind in [
+            EngineKind::Arithmetic,
+            EngineKind::SplitStreams,
+            EngineKind::Stored,
+        ] {
+            assert_eq!(
+                registry.compress(kind, &data, &verified).unwrap(),
+                registry
+                    .compress(kind, &data, &EngineConfig::default())
+                    .unwrap()
+            );
+        }
+        let archive = compress_data_in_blocks(&data, 200);
+        let target = verified.with_engine(EngineKind::Custom(201));
+        assert_eq!(
+            registry.transcode(&archive, &target, &DecodeOptions::default()),
+            Err(WizardError::VerificationFailed)
+        );
+    }
+
+    #[test]
+    fn test_transcode_between_engines() {
+        let registry = EngineRegistry::default();
+        let data = "the wizard and the owl read the scroll. ".repeat(30);
+        let options = DecodeOptions::default();
+        let to_split = EngineConfig::default().with_engine(EngineKind::SplitStreams);
+
+        let split = registry
+            .transcode(compress_data(&data), &to_split, &options)
+            .unwrap();
+        assert_eq!(
+            Frame::from_bytes(&split).unwrap().engine(),
+            EngineKind::SplitStreams
+        );
+        assert_eq!(decompress_data(&split), data.as_bytes());
+
+        let back = registry
+            .transcode(&split, &EngineConfig::default(), &options)
+            .unwrap();
+        assert_eq!(
+            Frame::from_bytes(&back).unwrap().engine(),
+            EngineKind::Arithmetic
+        );
+        assert_eq!(decompress_data(&back), data.as_bytes());
+
+        assert_eq!(
+            registry.transcode(
+                &split,
+                &EngineConfig::default().with_engine(EngineKind::Custom(200)),
+                &options
+            ),
+            Err(WizardError::UnsupportedEngine(200))
+        );
+    }
+
+    #[test]
+    fn test_transcode_keeps_block_boundaries() {
+        let registry = EngineRegistry::default();
+        let data = "line of the scroll\n".repeat(200);
+        let archive = compress_data_in_blocks(&data, 1000);
+        let to_split = EngineConfig::default().with_engine(EngineKind::SplitStreams);
+
+        let transcoded = registry
+            .transcode(&archive, &to_split, &DecodeOptions::default())
+            .unwrap();
+        let original_blocks = parse_block_ledger(&archive, &DecodeOptions::default()).unwrap();
+        let blocks = parse_block_ledger(&transcoded, &DecodeOptions::default()).unwrap();
+
+        assert_eq!(blocks.len(), original_blocks.len());
+        for (block, original) in blocks.iter().zip(&original_blocks) {
+            assert_eq!(block.original_len, original.original_len);
+            assert_eq!(
+                Frame::from_bytes(block.frame).unwrap().engine(),
+                EngineKind::SplitStreams
+            );
+        }
+        assert_eq!(decompress_data(&transcoded), data.as_bytes());
+    }
+
+    #[test]
+    fn test_transcode_preserves_metadata() {
+        let metadata = HashMap::from([("filename".to_string(), "scroll.txt".to_string())]);
+        let archive = compress_with_metadata("metadata survives transcoding", &metadata);
+        let to_split = EngineConfig::default().with_engine(EngineKind::SplitStreams);
+
+        let transcoded = EngineRegistry::default()
+            .transcode(&archive, &to_split, &DecodeOptions::default())
+            .unwrap();
+        assert_eq!(read_metadata(&transcoded).unwrap(), metadata);
+        assert_eq!(
+            decompress_data(&transcoded),
+            b"metadata survives transcoding"
+        );
+    }
+}
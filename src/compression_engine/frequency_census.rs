@@ -0,0 +1,269 @@
+//! Перепись частот 🧮
+//!
+//! Гистограммы байтов и символов для построения модели. С фичей `rayon`
+//! большие входы делятся на куски, гистограммы кусков считаются
+//! параллельно и складываются. С фичей `simd` байты (и литералы в
+//! потоке символов) считаются в четыре независимые таблицы, по восемь
+//! байтов за чтение: соседние одинаковые байты не ждут друг друга на
+//! одном счетчике. Результат не зависит от фич: частоты возвращаются по
+//! возрастанию id символа.
+
+use std::collections::HashMap;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+/// Размер куска для параллельного подсчета (в элементах)
+///
+/// Входы короче двух кусков считаются в одном потоке: запуск задач
+/// дороже самого подсчета.
+#[cfg_attr(not(feature = "rayon"), allow(dead_code))]
+pub(crate) const CENSUS_CHUNK_LEN: usize = 1 << 16;
+
+/// Число независимых таблиц счетчиков в подсчете с фичей `simd`
+const COUNTER_LANES: usize = 4;
+
+/// Сколько элементов считается в счетчики u32 до сброса в итог
+///
+/// Каждая таблица получает не больше четверти элементов отрезка,
+/// поэтому счетчики u32 не переполняются.
+const LANE_FLUSH_LEN: usize = 1 << 30;
+
+/// Гистограмма байтов
+pub(crate) fn byte_histogram(data: &[u8]) -> [u64; 256] {
+    #[cfg(feature = "rayon")]
+    if data.len() >= 2 * CENSUS_CHUNK_LEN {
+        return data
+            .par_chunks(CENSUS_CHUNK_LEN)
+            .map(count_bytes)
+            .reduce(|| [0; 256], merge_byte_histograms);
+    }
+
+    count_bytes(data)
+}
+
+/// Энтропия Шеннона байтов, бит на байт (ноль для пустого входа)
+pub(crate) fn shannon_entropy(data: &[u8]) -> f64 {
+    histogram_entropy(&byte_histogram(data))
+}
+
+/// Энтропия Шеннона по гистограмме байтов, бит на байт
+pub(crate) fn histogram_entropy(histogram: &[u64; 256]) -> f64 {
+    let total = histogram.iter().sum::<u64>() as f64;
+    histogram
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / total;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Частоты символов по возрастанию id символа
+pub(crate) fn symbol_census(symbols: &[u32]) -> Vec<(u32, u64)> {
+    #[cfg(feature = "rayon")]
+    if symbols.len() >= 2 * CENSUS_CHUNK_LEN {
+        let merged = symbols
+            .par_chunks(CENSUS_CHUNK_LEN)
+            .map(count_symbols)
+            .reduce(HashMap::new, merge_symbol_counts);
+        return sorted_counts(merged);
+    }
+
+    sorted_counts(count_symbols(symbols))
+}
+
+/// Частоты символов в готовые буферы (в одном потоке)
+///
+/// `counts` и `frequency_pairs` очищаются и заполняются заново, сохраняя
+/// емкость; результат в `frequency_pairs` тот же, что у [`symbol_census`].
+pub(crate) fn symbol_census_into(
+    symbols: &[u32],
+    counts: &mut HashMap<u32, u64>,
+    frequency_pairs: &mut Vec<(u32, u64)>,
+) {
+    counts.clear();
+    count_symbols_into(symbols, counts);
+    frequency_pairs.clear();
+    frequency_pairs.extend(counts.iter().map(|(&symbol_id, &count)| (symbol_id, count)));
+    frequency_pairs.sort_unstable_by_key(|&(symbol_id, _)| symbol_id);
+}
+
+/// Гистограмма байтов одного куска
+fn count_bytes(chunk: &[u8]) -> [u64; 256] {
+    if cfg!(feature = "simd") {
+        count_bytes_in_lanes(chunk)
+    } else {
+        count_bytes_scalar(chunk)
+    }
+}
+
+/// Гистограмма байтов по одному счетчику на байт
+fn count_bytes_scalar(chunk: &[u8]) -> [u64; 256] {
+    let mut histogram = [0u64; 256];
+    for &byte in chunk {
+        histogram[byte as usize] += 1;
+    }
+    histogram
+}
+
+/// Гистограмма байтов в [`COUNTER_LANES`] таблицах, по восемь байтов за чтение
+fn count_bytes_in_lanes(chunk: &[u8]) -> [u64; 256] {
+    let mut histogram = [0u64; 256];
+    for segment in chunk.chunks(LANE_FLUSH_LEN) {
+        let mut lanes = [[0u32; 256]; COUNTER_LANES];
+        let mut words = segment.chunks_exact(8);
+        for word in &mut words {
+            for (position, &byte) in word.iter().enumerate() {
+                lanes[position % COUNTER_LANES][byte as usize] += 1;
+            }
+        }
+        for &byte in words.remainder() {
+            lanes[0][byte as usize] += 1;
+        }
+        merge_lanes(&mut histogram, &lanes);
+    }
+    histogram
+}
+
+/// Частоты символов одного куска
+fn count_symbols(chunk: &[u32]) -> HashMap<u32, u64> {
+    let mut counts = HashMap::new();
+    count_symbols_into(chunk, &mut counts);
+    counts
+}
+
+/// Добавляет частоты символов куска в `counts`
+fn count_symbols_into(chunk: &[u32], counts: &mut HashMap<u32, u64>) {
+    if cfg!(feature = "simd") {
+        count_symbols_in_lanes(chunk, counts);
+    } else {
+        for &mystical_symbol in chunk {
+            *counts.entry(mystical_symbol).or_insert(0u64) += 1;
+        }
+    }
+}
+
+/// Литералы (id < 256) — в плотные таблицы, остальные символы — в `counts`
+fn count_symbols_in_lanes(chunk: &[u32], counts: &mut HashMap<u32, u64>) {
+    let mut literals = [0u64; 256];
+    for segment in chunk.chunks(LANE_FLUSH_LEN) {
+        let mut lanes = [[0u32; 256]; COUNTER_LANES];
+        let mut groups = segment.chunks_exact(COUNTER_LANES);
+        for group in &mut groups {
+            for (lane, &mystical_symbol) in lanes.iter_mut().zip(group) {
+                match u8::try_from(mystical_symbol) {
+                    Ok(byte) => lane[byte as usize] += 1,
+                    Err(_) => *counts.entry(mystical_symbol).or_insert(0u64) += 1,
+                }
+            }
+        }
+        for &mystical_symbol in groups.remainder() {
+            match u8::try_from(mystical_symbol) {
+                Ok(byte) => lanes[0][byte as usize] += 1,
+                Err(_) => *counts.entry(mystical_symbol).or_insert(0u64) += 1,
+            }
+        }
+        merge_lanes(&mut literals, &lanes);
+    }
+    for (byte, count) in (0u32..).zip(literals) {
+        if count > 0 {
+            *counts.entry(byte).or_insert(0) += count;
+        }
+    }
+}
+
+/// Складывает таблицы счетчиков в итоговую гистограмму
+fn merge_lanes(histogram: &mut [u64; 256], lanes: &[[u32; 256]; COUNTER_LANES]) {
+    for lane in lanes {
+        for (total, &count) in histogram.iter_mut().zip(lane) {
+            *total += u64::from(count);
+        }
+    }
+}
+
+/// Складывает гистограммы байтов двух кусков
+#[cfg(feature = "rayon")]
+fn merge_byte_histograms(mut left: [u64; 256], right: [u64; 256]) -> [u64; 256] {
+    for (total, count) in left.iter_mut().zip(right) {
+        *total += count;
+    }
+    left
+}
+
+/// Складывает частоты символов двух кусков (меньшая таблица вливается в большую)
+#[cfg(feature = "rayon")]
+fn merge_symbol_counts(
+    mut left: HashMap<u32, u64>,
+    mut right: HashMap<u32, u64>,
+) -> HashMap<u32, u64> {
+    if left.len() < right.len() {
+        std::mem::swap(&mut left, &mut right);
+    }
+    for (mystical_symbol, count) in right {
+        *left.entry(mystical_symbol).or_insert(0) += count;
+    }
+    left
+}
+
+/// Сортирует частоты для детерминированности
+fn sorted_counts(counts: HashMap<u32, u64>) -> Vec<(u32, u64)> {
+    let mut frequency_pairs: Vec<(u32, u64)> = counts.into_iter().collect();
+    frequency_pairs.sort_unstable_by_key(|&(symbol_id, _)| symbol_id);
+    frequency_pairs
+}
+
+/// Тесты переписи частот 🎯
+#[cfg(test)]
+mod frequency_census_tests {
+    use super::*;
+
+    #[test]
+    fn test_large_inputs_match_single_chunk_counts() {
+        // Больше двух кусков: с фичей `rayon` считается параллельно
+        let len = 2 * CENSUS_CHUNK_LEN + 12_345;
+        let data: Vec<u8> = (0..len).map(|i| (i * 31 % 251) as u8).collect();
+        let symbols: Vec<u32> = (0..len as u32).map(|i| i.wrapping_mul(i) % 700).collect();
+
+        assert_eq!(byte_histogram(&data), count_bytes(&data));
+        assert_eq!(byte_histogram(&data).iter().sum::<u64>(), len as u64);
+
+        let census = symbol_census(&symbols);
+        assert_eq!(census, sorted_counts(count_symbols(&symbols)));
+        assert!(census.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert_eq!(
+            census.iter().map(|&(_, count)| count).sum::<u64>(),
+            len as u64
+        );
+    }
+
+    #[test]
+    fn test_lane_counts_match_scalar_counts() {
+        // Длины с остатками после групп по восемь и по четыре
+        for len in [0, 1, 7, 8, 9, 1000, 4099] {
+            let data: Vec<u8> = (0..len).map(|i| (i * 131 % 256) as u8).collect();
+            assert_eq!(count_bytes_in_lanes(&data), count_bytes_scalar(&data));
+
+            let mut symbols: Vec<u32> = data.iter().map(|&byte| u32::from(byte)).collect();
+            symbols.extend((0..len as u32 / 3).map(|i| 256 + i % 40));
+            let mut in_lanes = HashMap::new();
+            count_symbols_in_lanes(&symbols, &mut in_lanes);
+            let mut scalar = HashMap::new();
+            for &symbol in &symbols {
+                *scalar.entry(symbol).or_insert(0u64) += 1;
+            }
+            assert_eq!(in_lanes, scalar);
+        }
+
+        // Одинаковые байты подряд — худший случай для одного счетчика
+        let run = vec![b'z'; 12_345];
+        assert_eq!(count_bytes_in_lanes(&run)[b'z' as usize], 12_345);
+    }
+
+    #[test]
+    fn test_empty_input() {
+        assert_eq!(byte_histogram(&[]), [0; 256]);
+        assert!(symbol_census(&[]).is_empty());
+    }
+}
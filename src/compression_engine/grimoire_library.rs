@@ -0,0 +1,247 @@
+//! Библиотека словарей 📚
+//!
+//! Архивы общей модели (см. [`CompressorHandle`]) хранят только
+//! идентификатор словаря. [`DictionaryRegistry`] — общий для потоков
+//! кэш обученных словарей: приложение регистрирует их при старте или
+//! по мере загрузки файлов `.acwd`, а распаковка находит словарь по
+//! идентификатору из заголовка архива.
+//!
+//! Клоны реестра разделяют одно хранилище (`Arc<RwLock<..>>`): чтения
+//! идут параллельно, запись блокирует только на время вставки.
+
+use super::compressor_handle::{CompressorHandle, HandleArtifact};
+use crate::decompression_oracle::block_conductor::try_decode_blocks;
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::error::{WizardError, WizardResult};
+use crate::format::block_ledger::read_block_ledger;
+use crate::format::container_runes::ArchiveHeader;
+use crate::simple_api::try_decompress_frame;
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// Потокобезопасный реестр словарей по идентификатору 📚
+///
+/// ```
+/// use arithmetic_compression_wizard::compression_engine::{CompressorHandle, DictionaryRegistry};
+///
+/// let handle = CompressorHandle::train(b"status active status blocked status active");
+/// let archive = handle.compress(b"status active").to_bytes();
+///
+/// let registry = DictionaryRegistry::new();
+/// registry.register(handle);
+/// assert_eq!(registry.decompress(&archive), b"status active");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DictionaryRegistry {
+    dictionaries: Arc<RwLock<HashMap<u64, Arc<CompressorHandle>>>>,
+}
+
+impl DictionaryRegistry {
+    /// Пустой реестр
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Регистрирует словарь под его идентификатором
+    ///
+    /// Возвращает идентификатор. Словарь с тем же идентификатором
+    /// заменяется: идентификатор — отпечаток модели, так что замена
+    /// равнозначна.
+    pub fn register(&self, handle: CompressorHandle) -> u64 {
+        let dictionary_id = handle.dictionary_id();
+        self.dictionaries
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(dictionary_id, Arc::new(handle));
+        dictionary_id
+    }
+
+    /// Загружает и регистрирует файл словаря (`.acwd`)
+    pub fn register_bytes(&self, dictionary_bytes: &[u8]) -> WizardResult<u64> {
+        Ok(self.register(CompressorHandle::from_dictionary_bytes(dictionary_bytes)?))
+    }
+
+    /// Словарь по идентификатору
+    pub fn get(&self, dictionary_id: u64) -> Option<Arc<CompressorHandle>> {
+        self.dictionaries
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&dictionary_id)
+            .cloned()
+    }
+
+    /// Убирает словарь из реестра
+    pub fn remove(&self, dictionary_id: u64) -> Option<Arc<CompressorHandle>> {
+        self.dictionaries
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&dictionary_id)
+    }
+
+    /// Зарегистрированные идентификаторы по возрастанию
+    pub fn dictionary_ids(&self) -> Vec<u64> {
+        let mut dictionary_ids: Vec<u64> = self
+            .dictionaries
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .keys()
+            .copied()
+            .collect();
+        dictionary_ids.sort_unstable();
+        dictionary_ids
+    }
+
+    /// Число зарегистрированных словарей
+    pub fn len(&self) -> usize {
+        self.dictionaries
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Реестр пуст
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Распаковка доверенного архива
+    ///
+    /// Паникует на поврежденных данных и незарегистрированных словарях —
+    /// для недоверенного ввода используйте [`DictionaryRegistry::try_decompress`].
+    pub fn decompress(&self, archive_bytes: impl AsRef<[u8]>) -> Vec<u8> {
+        self.try_decompress(archive_bytes, &DecodeOptions::unlimited())
+            .expect("архив поврежден или словарь не зарегистрирован: используйте try_decompress")
+    }
+
+    /// Проверяемая распаковка любого архива 🛡️
+    ///
+    /// Кадры общей модели распаковываются словарем из реестра или
+    /// встроенным [`Preset`](super::Preset) (если нет ни того, ни другого —
+    /// [`WizardError::ExternalDictionaryRequired`]), остальные —
+    /// как в [`try_decompress_data`](crate::simple_api::try_decompress_data).
+    /// Архивы из блоков могут смешивать оба вида кадров.
+    pub fn try_decompress(
+        &self,
+        archive_bytes: impl AsRef<[u8]>,
+        options: &DecodeOptions,
+    ) -> WizardResult<Vec<u8>> {
+        let archive_bytes = archive_bytes.as_ref();
+        let (header, body_offset) = ArchiveHeader::try_parse(archive_bytes)?;
+
+        if header.block_framed {
+            let blocks = read_block_ledger(&header, &archive_bytes[body_offset..], options)
+                .map_err(|error| error.shifted(body_offset))?;
+            return try_decode_blocks(archive_bytes, &blocks, options, |frame, options| {
+                self.try_decompress_frame(frame, options)
+            });
+        }
+        self.try_decompress_frame(archive_bytes, options)
+    }
+
+    /// Распаковка одного кадра: словарем из реестра или встроенным движком
+    fn try_decompress_frame(&self, frame: &[u8], options: &DecodeOptions) -> WizardResult<Vec<u8>> {
+        let (header, _) = ArchiveHeader::try_parse(frame)?;
+        let Some(dictionary_id) = header.dictionary_id else {
+            return try_decompress_frame(frame, options);
+        };
+
+        let Some(handle) = self.get(dictionary_id) else {
+            // Встроенный пресет или ExternalDictionaryRequired
+            return try_decompress_frame(frame, options);
+        };
+        let artifact = HandleArtifact::from_bytes(frame).ok_or(WizardError::CorruptPayload)?;
+        handle.try_decompress(&artifact, options)
+    }
+}
+
+/// Тесты библиотеки словарей 🎯
+#[cfg(test)]
+mod grimoire_library_tests {
+    use super::*;
+    use crate::format::block_ledger::write_block_archive;
+    use crate::simple_api::compress_data;
+    use std::thread;
+
+    const ORDERS: &[u8] = b"order shipped order pending order shipped order delivered \
+        order shipped order pending order cancelled order shipped";
+    const METRICS: &[u8] = b"cpu load memory usage cpu load disk usage cpu load \
+        memory usage network usage cpu load memory usage";
+
+    #[test]
+    fn test_registry_decodes_by_dictionary_id() {
+        let orders = CompressorHandle::train(ORDERS);
+        let metrics = CompressorHandle::train(METRICS);
+        let registry = DictionaryRegistry::new();
+        let orders_id = registry.register(orders.clone());
+        registry
+            .register_bytes(&metrics.to_dictionary_bytes())
+            .unwrap();
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.get(orders_id).unwrap().dictionary_id(), orders_id);
+
+        let orders_archive = orders.compress(b"order shipped").to_bytes();
+        let metrics_archive = metrics.compress(b"cpu load").to_bytes();
+        assert_eq!(registry.decompress(&orders_archive), b"order shipped");
+        assert_eq!(registry.decompress(&metrics_archive), b"cpu load");
+        assert_eq!(registry.decompress(compress_data(b"plain")), b"plain");
+
+        // Блоки разных словарей и обычные кадры в одном архиве
+        let mixed = write_block_archive(&[
+            (13, orders_archive.clone()),
+            (5, compress_data(b"plain")),
+            (8, metrics_archive),
+        ]);
+        assert_eq!(registry.decompress(&mixed), b"order shippedplaincpu load");
+
+        assert!(registry.remove(orders_id).is_some());
+        assert_eq!(
+            registry
+                .try_decompress(&orders_archive, &DecodeOptions::default())
+                .map_err(|error| error.root_cause().clone()),
+            Err(WizardError::ExternalDictionaryRequired(orders_id))
+        );
+        assert_eq!(registry.dictionary_ids(), vec![metrics.dictionary_id()]);
+    }
+
+    #[test]
+    fn test_registry_is_shared_between_threads() {
+        let registry = DictionaryRegistry::new();
+        let handle = CompressorHandle::train(ORDERS);
+        let archive = handle.compress(b"order pending").to_bytes();
+
+        let writer = registry.clone();
+        thread::spawn(move || writer.register(handle))
+            .join()
+            .unwrap();
+
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let registry = registry.clone();
+                let archive = archive.clone();
+                thread::spawn(move || registry.decompress(&archive))
+            })
+            .collect();
+        for reader in readers {
+            assert_eq!(reader.join().unwrap(), b"order pending");
+        }
+    }
+
+    #[test]
+    fn test_damaged_shared_archives_rejected() {
+        let handle = CompressorHandle::train(ORDERS);
+        let registry = DictionaryRegistry::new();
+        registry.register(handle.clone());
+
+        let archive = handle.compress(b"order shipped order shipped").to_bytes();
+        assert!(matches!(
+            registry.try_decompress(&archive, &DecodeOptions::default().with_max_output_bytes(3)),
+            Err(WizardError::LimitExceeded { .. })
+        ));
+        let header_len = ArchiveHeader::parse(&archive).unwrap().1;
+        assert_eq!(
+            registry.try_decompress(&archive[..header_len], &DecodeOptions::default()),
+            Err(WizardError::CorruptPayload)
+        );
+        assert!(registry.register_bytes(b"ACWD").is_err());
+    }
+}
@@ -0,0 +1,279 @@
+//! Словарь, записанный вручную ✒️
+//!
+//! Поиск словаря угадывает повторяющиеся слова по самому входу и на
+//! коротких сообщениях часто не находит ничего. Приложение же обычно
+//! заранее знает свои повторы: ключевые слова протокола, имена
+//! перечислений, заголовки. [`Grimoire`] перечисляет их явно, и сжатие с
+//! [`EngineConfig::with_grimoire`](super::EngineConfig::with_grimoire)
+//! пропускает поиск. В архив попадают только слова, которые встретились
+//! во входе: распаковка словаря не требует.
+//!
+//! Словари, обученные на разных данных ([`Grimoire::train`]), сводятся в
+//! один общий [`Grimoire::merge`]: например, запасной словарь для всех
+//! арендаторов из словарей каждого.
+//!
+//! Слова хранятся в [`SharedGrimoire`]: копии словаря, настроек сжатия и
+//! дескрипторов, обученных на нем
+//! ([`CompressorHandle::train_with_grimoire`](super::CompressorHandle::train_with_grimoire)),
+//! делят одни строки, а сжатие копирует в артефакт только совпавшие слова.
+
+use super::compression_conjurer::{discover_scored_words, GRIMOIRE_ENTRY_OVERHEAD};
+use super::engine_covenant::EngineConfig;
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Слова словаря в общем неизменяемом буфере 🔗
+///
+/// Клон — счетчик ссылок, а не копия строк.
+pub type SharedGrimoire = Arc<[String]>;
+
+/// Словарь слов, заданный вызывающим ✒️
+///
+/// Слова подставляются по тем же границам слов, что и найденные поиском
+/// (см. [`EngineConfig::word_boundaries`](super::EngineConfig::word_boundaries)),
+/// а запретный список к ним не применяется: выбор сделан явно.
+///
+/// ```
+/// use arithmetic_compression_wizard::compression_engine::{EngineConfig, Grimoire};
+/// use arithmetic_compression_wizard::simple_api::{decompress_data, try_compress_with_config};
+///
+/// let grimoire = Grimoire::from_entries(vec!["CONNECT", "ACKNOWLEDGE", "DISCONNECT"]);
+/// let config = EngineConfig::default().with_grimoire(grimoire);
+/// let message = b"CONNECT ACKNOWLEDGE CONNECT ACKNOWLEDGE DISCONNECT";
+/// let archive = try_compress_with_config(message, &config).unwrap();
+/// assert_eq!(decompress_data(&archive), message);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Grimoire {
+    words: SharedGrimoire,
+    /// Оценка экономии каждого слова в байтах (0 — не измерена)
+    savings: Arc<[u64]>,
+}
+
+impl Grimoire {
+    /// Словарь из слов в заданном порядке
+    ///
+    /// Пустые слова и повторы пропускаются. Слова длиннее
+    /// [`DecodeOptions::DEFAULT_MAX_WORD_BYTES`] и слова сверх
+    /// [`DecodeOptions::DEFAULT_MAX_DICT_ENTRIES`] тоже пропускаются:
+    /// архив с ними отверг бы декодер с лимитами по умолчанию.
+    pub fn from_entries(entries: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self::from_scored_entries(entries.into_iter().map(|entry| (entry.into(), 0)))
+    }
+
+    /// Обучает словарь на образце данных поиском слов из `config` 🎓
+    ///
+    /// Применяются способ подсчета, запретный список, бюджет и границы
+    /// слов из `config`; у каждого слова запоминается оценка экономии
+    /// на образце — по ней [`merge`](Self::merge) ранжирует слова.
+    pub fn train(sample: impl AsRef<[u8]>, config: &EngineConfig) -> Self {
+        let scored_words = discover_scored_words(
+            sample.as_ref(),
+            &config.word_counting,
+            &config.deny_list,
+            config.dictionary_budget_bytes,
+            &config.word_boundaries,
+        );
+        Self::from_scored_entries(
+            scored_words
+                .into_iter()
+                .map(|(word, savings)| (word, savings.max(0) as u64)),
+        )
+    }
+
+    /// Сводит несколько словарей в один в пределах бюджета заголовка 🔀
+    ///
+    /// Экономия слова складывается по всем словарям, где оно есть.
+    /// Бюджет (буквы слова и длина `u32`, как у
+    /// [`EngineConfig::dictionary_budget_bytes`](super::EngineConfig::dictionary_budget_bytes))
+    /// заполняется жадно: сначала слова с наибольшей суммарной экономией на
+    /// байт заголовка, при равной — встреченные в большем числе словарей,
+    /// затем по алфавиту. В результате слова идут по убыванию суммарной
+    /// экономии. Слова, заданные вручную, не измерены и занимают остаток
+    /// бюджета после измеренных.
+    ///
+    /// ```
+    /// use arithmetic_compression_wizard::compression_engine::Grimoire;
+    ///
+    /// let tenant_a = Grimoire::from_entries(["invoice", "customer"]);
+    /// let tenant_b = Grimoire::from_entries(["invoice", "shipment"]);
+    /// let global = Grimoire::merge(&[tenant_a, tenant_b], 24);
+    /// assert_eq!(global.words(), ["invoice", "customer"]);
+    /// ```
+    pub fn merge(grimoires: &[Grimoire], budget_bytes: usize) -> Self {
+        // Слово → (суммарная экономия, число словарей)
+        let mut aggregate: HashMap<&str, (u64, usize)> = HashMap::new();
+        for grimoire in grimoires {
+            for (word, &savings) in grimoire.words.iter().zip(grimoire.savings.iter()) {
+                let (total_savings, grimoire_count) = aggregate.entry(word).or_default();
+                *total_savings = total_savings.saturating_add(savings);
+                *grimoire_count += 1;
+            }
+        }
+
+        let header_bytes = |word: &str| word.len() + GRIMOIRE_ENTRY_OVERHEAD;
+        let density =
+            |word: &str, total_savings: u64| total_savings as f64 / header_bytes(word) as f64;
+        let mut candidates: Vec<(&str, u64, usize)> = aggregate
+            .into_iter()
+            .map(|(word, (total_savings, grimoire_count))| (word, total_savings, grimoire_count))
+            .collect();
+        candidates.sort_by(|left, right| {
+            density(right.0, right.1)
+                .total_cmp(&density(left.0, left.1))
+                .then_with(|| right.2.cmp(&left.2))
+                .then_with(|| left.0.cmp(right.0))
+        });
+
+        let mut remaining_budget = budget_bytes;
+        let mut selected = Vec::new();
+        for (word, total_savings, _) in candidates {
+            if header_bytes(word) <= remaining_budget {
+                remaining_budget -= header_bytes(word);
+                selected.push((word.to_string(), total_savings));
+            }
+        }
+        // Устойчивая сортировка: равные по экономии сохраняют порядок отбора
+        selected.sort_by_key(|&(_, total_savings)| std::cmp::Reverse(total_savings));
+        Self::from_scored_entries(selected)
+    }
+
+    /// Словарь из слов с оценками экономии, с теми же пропусками, что у
+    /// [`from_entries`](Self::from_entries)
+    fn from_scored_entries(entries: impl IntoIterator<Item = (String, u64)>) -> Self {
+        let mut words = Vec::new();
+        let mut word_savings = Vec::new();
+        for (word, savings) in entries {
+            if words.len() == DecodeOptions::DEFAULT_MAX_DICT_ENTRIES {
+                break;
+            }
+            if word.is_empty()
+                || word.len() > DecodeOptions::DEFAULT_MAX_WORD_BYTES
+                || words.contains(&word)
+            {
+                continue;
+            }
+            words.push(word);
+            word_savings.push(savings);
+        }
+        Self {
+            words: words.into(),
+            savings: word_savings.into(),
+        }
+    }
+
+    /// Слова словаря по порядку
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+
+    /// Общий буфер слов без копирования строк
+    pub fn shared_words(&self) -> SharedGrimoire {
+        Arc::clone(&self.words)
+    }
+
+    /// Оценки экономии слов в байтах в порядке [`words`](Self::words)
+    ///
+    /// У слов, заданных вручную, оценка нулевая.
+    pub fn savings(&self) -> &[u64] {
+        &self.savings
+    }
+
+    /// Число слов
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    /// Словарь пуст: вход кодируется одними байтами
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+}
+
+/// Тесты ручного словаря 🎯
+#[cfg(test)]
+mod grimoire_quill_tests {
+    use super::*;
+    use crate::format::read_dictionary;
+    use crate::simple_api::{compress_data, decompress_data, try_compress_with_config};
+
+    #[test]
+    fn test_entries_are_cleaned() {
+        let grimoire = Grimoire::from_entries(["GET", "", "POST", "GET", "DELETE"]);
+        assert_eq!(grimoire.words(), ["GET", "POST", "DELETE"]);
+        assert_eq!(grimoire.len(), 3);
+        assert!(Grimoire::from_entries(Vec::<String>::new()).is_empty());
+    }
+
+    #[test]
+    fn test_manual_words_skip_discovery() {
+        // Короткое сообщение: поиск словаря на нем ничего не находит
+        let message = b"status=ACKNOWLEDGED status=ACKNOWLEDGED status=RETRANSMITTING";
+        let grimoire = Grimoire::from_entries(["ACKNOWLEDGED", "RETRANSMITTING", "unused"]);
+        let config = EngineConfig::default().with_grimoire(grimoire);
+
+        let archive = try_compress_with_config(message, &config).unwrap();
+        assert_eq!(decompress_data(&archive), message);
+        assert!(archive.len() < compress_data(message).len());
+
+        // Слово, которого нет во входе, в архив не попадает
+        let words: Vec<Vec<u8>> = read_dictionary(&archive)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.bytes)
+            .collect();
+        assert_eq!(words, [&b"ACKNOWLEDGED"[..], b"RETRANSMITTING"]);
+
+        let empty = EngineConfig::default().with_grimoire(Grimoire::default());
+        let archive = try_compress_with_config(message, &empty).unwrap();
+        assert!(read_dictionary(&archive).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_merge_ranks_by_aggregate_savings() {
+        let sales = "the invoice for the customer lists every invoice line. ".repeat(40);
+        let logistics = "the shipment left before the invoice, the shipment is late. ".repeat(40);
+        let config = EngineConfig::default();
+        let tenants = [
+            Grimoire::train(&sales, &config),
+            Grimoire::train(&logistics, &config),
+        ];
+        for tenant in &tenants {
+            assert!(tenant.words().iter().any(|word| word == "invoice"));
+            assert!(tenant.savings().iter().all(|&savings| savings > 0));
+        }
+
+        // «invoice» есть у обоих: его экономия складывается
+        let merged = Grimoire::merge(&tenants, 1024);
+        let savings_of = |grimoire: &Grimoire, word: &str| {
+            let index = grimoire.words().iter().position(|entry| entry == word);
+            index.map(|index| grimoire.savings()[index])
+        };
+        assert_eq!(
+            savings_of(&merged, "invoice"),
+            Some(
+                savings_of(&tenants[0], "invoice").unwrap()
+                    + savings_of(&tenants[1], "invoice").unwrap()
+            )
+        );
+        assert!(merged.savings().windows(2).all(|pair| pair[0] >= pair[1]));
+        let mut words = merged.words().to_vec();
+        words.sort();
+        words.dedup();
+        assert_eq!(words.len(), merged.len());
+
+        // Бюджет на одно слово: остается лучшее на байт заголовка
+        let single = Grimoire::merge(&tenants, "invoice".len() + 4);
+        assert_eq!(single.len(), 1);
+        assert!(Grimoire::merge(&tenants, 0).is_empty());
+        assert!(Grimoire::merge(&[], 1024).is_empty());
+
+        // Общий словарь сжимает данные обоих арендаторов
+        let global = EngineConfig::default().with_grimoire(merged);
+        for sample in [&sales, &logistics] {
+            let archive = try_compress_with_config(sample, &global).unwrap();
+            assert_eq!(decompress_data(&archive), sample.as_bytes());
+        }
+    }
+}
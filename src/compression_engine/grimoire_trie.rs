@@ -0,0 +1,119 @@
+//! Префиксное дерево словаря 🌳
+//!
+//! Поиск слова в позиции текста проходит дерево по байтам текста один
+//! раз, а не сравнивает текст с каждым словом словаря. Поэтому словарь
+//! может расти до тысяч слов (см.
+//! [`EngineConfig::dictionary_budget_bytes`](super::EngineConfig::dictionary_budget_bytes))
+//! без замедления преобразования в символы.
+
+use std::collections::HashMap;
+
+/// Корень дерева
+const ROOT: u32 = 0;
+
+/// Префиксное дерево слов словаря 🌳
+#[derive(Debug, Clone, Default)]
+pub(crate) struct GrimoireTrie {
+    /// Переходы `(узел, байт) → узел`
+    edges: HashMap<(u32, u8), u32>,
+    /// Наименьший индекс слова, которое заканчивается в узле
+    word_at: Vec<Option<usize>>,
+}
+
+impl GrimoireTrie {
+    /// Дерево из слов словаря (пустые слова не совпадают никогда)
+    pub(crate) fn new(word_grimoire: &[String]) -> Self {
+        let mut trie = Self {
+            edges: HashMap::new(),
+            word_at: vec![None],
+        };
+        for (grimoire_index, word) in word_grimoire.iter().enumerate() {
+            let mut node = ROOT;
+            for &byte in word.as_bytes() {
+                let next_node = trie.word_at.len() as u32;
+                node = *trie.edges.entry((node, byte)).or_insert(next_node);
+                if node == next_node {
+                    trie.word_at.push(None);
+                }
+            }
+            // При повторах побеждает первое слово, как при переборе словаря
+            if node != ROOT {
+                trie.word_at[node as usize].get_or_insert(grimoire_index);
+            }
+        }
+        trie
+    }
+
+    /// Слово с наименьшим индексом, которое начинается в `start` и
+    /// проходит проверку `accept_end` (по позиции конца слова)
+    ///
+    /// Возвращает индекс слова и позицию его конца.
+    pub(crate) fn find_word(
+        &self,
+        text: &[u8],
+        start: usize,
+        mut accept_end: impl FnMut(usize) -> bool,
+    ) -> Option<(usize, usize)> {
+        let mut best: Option<(usize, usize)> = None;
+        self.for_each_word(text, start, |grimoire_index, end| {
+            if best.is_none_or(|(best_index, _)| grimoire_index < best_index) && accept_end(end) {
+                best = Some((grimoire_index, end));
+            }
+        });
+        best
+    }
+
+    /// Все слова, которые начинаются в `start`, от коротких к длинным
+    ///
+    /// `visit` получает индекс слова и позицию его конца.
+    pub(crate) fn for_each_word(
+        &self,
+        text: &[u8],
+        start: usize,
+        mut visit: impl FnMut(usize, usize),
+    ) {
+        let mut node = ROOT;
+        for (offset, &byte) in text[start..].iter().enumerate() {
+            let Some(&next_node) = self.edges.get(&(node, byte)) else {
+                break;
+            };
+            node = next_node;
+            if let Some(grimoire_index) = self.word_at[node as usize] {
+                visit(grimoire_index, start + offset + 1);
+            }
+        }
+    }
+}
+
+/// Тесты префиксного дерева 🎯
+#[cfg(test)]
+mod grimoire_trie_tests {
+    use super::*;
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|word| word.to_string()).collect()
+    }
+
+    #[test]
+    fn test_smallest_index_wins() {
+        let trie = GrimoireTrie::new(&words(&["wizardry", "wizard", "wiz", "wizard"]));
+        let text = b"wizardry";
+        assert_eq!(trie.find_word(text, 0, |_| true), Some((0, 8)));
+        // Конец «wizardry» отвергнут — остается «wizard» с меньшим индексом, чем «wiz»
+        assert_eq!(trie.find_word(text, 0, |end| end != 8), Some((1, 6)));
+        assert_eq!(trie.find_word(text, 0, |end| end == 3), Some((2, 3)));
+        assert_eq!(trie.find_word(text, 1, |_| true), None);
+        assert_eq!(
+            GrimoireTrie::new(&words(&[""])).find_word(text, 0, |_| true),
+            None
+        );
+    }
+
+    #[test]
+    fn test_every_overlapping_word_is_visited() {
+        let trie = GrimoireTrie::new(&words(&["don't", "don", "do"]));
+        let mut found = Vec::new();
+        trie.for_each_word(b"don't stop", 0, |index, end| found.push((index, end)));
+        assert_eq!(found, vec![(2, 2), (1, 3), (0, 5)]);
+    }
+}
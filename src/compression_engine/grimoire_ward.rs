@@ -0,0 +1,165 @@
+//! Запретный список словаря 🚫
+//!
+//! Слова словаря хранятся в заголовке открытым текстом — даже если
+//! архив потом шифруется, словарь виден всякому, кто прочтет начало
+//! файла (см. [`read_dictionary`](crate::format::read_dictionary)).
+//! [`DenyList`] перечисляет то, что поиск слов никогда не должен
+//! поместить в словарь: такие слова кодируются байтами внутри
+//! арифметического потока.
+
+use std::fmt;
+use std::sync::Arc;
+
+/// Проверка слова, заданная вызывающим (например, регулярным выражением)
+type WordMatcher = Arc<dyn Fn(&[u8]) -> bool + Send + Sync>;
+
+/// Запретный список слов словаря 🚫
+///
+/// ```
+/// use arithmetic_compression_wizard::compression_engine::DenyList;
+///
+/// let deny_list = DenyList::new()
+///     .with_literal("swordfish")
+///     .with_matcher(|word| word.starts_with(b"tok"));
+/// assert!(deny_list.denies(b"swordfish"));
+/// assert!(deny_list.denies(b"token"));
+/// assert!(!deny_list.denies(b"wizard"));
+/// ```
+#[derive(Clone, Default)]
+pub struct DenyList {
+    literals: Vec<Vec<u8>>,
+    matchers: Vec<WordMatcher>,
+}
+
+impl DenyList {
+    /// Пустой список: словарь не ограничен
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Запрещает слова, которые содержат `pattern` или входят в него
+    ///
+    /// Слова словаря — буквенные, поэтому секрет `api_key=XYZsecret`
+    /// запрещает и `XYZsecret`, и `api`, и `key`. Пустой шаблон
+    /// ничего не запрещает.
+    pub fn with_literal(mut self, pattern: impl AsRef<[u8]>) -> Self {
+        if !pattern.as_ref().is_empty() {
+            self.literals.push(pattern.as_ref().to_vec());
+        }
+        self
+    }
+
+    /// Запрещает слова, для которых `matcher` возвращает `true`
+    ///
+    /// Подходит для регулярных выражений из сторонних крейтов:
+    /// `with_matcher(move |word| regex.is_match(word))`.
+    pub fn with_matcher(mut self, matcher: impl Fn(&[u8]) -> bool + Send + Sync + 'static) -> Self {
+        self.matchers.push(Arc::new(matcher));
+        self
+    }
+
+    /// Список ничего не запрещает
+    pub fn is_empty(&self) -> bool {
+        self.literals.is_empty() && self.matchers.is_empty()
+    }
+
+    /// Слово нельзя помещать в словарь
+    pub fn denies(&self, word: &[u8]) -> bool {
+        self.literals
+            .iter()
+            .any(|pattern| contains(word, pattern) || contains(pattern, word))
+            || self.matchers.iter().any(|matcher| matcher(word))
+    }
+}
+
+impl fmt::Debug for DenyList {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Шаблоны не печатаются: это и есть секреты
+        formatter
+            .debug_struct("DenyList")
+            .field("literals", &self.literals.len())
+            .field("matchers", &self.matchers.len())
+            .finish()
+    }
+}
+
+impl PartialEq for DenyList {
+    /// Проверки сравниваются по адресу: одна и та же `Arc`
+    fn eq(&self, other: &Self) -> bool {
+        self.literals == other.literals
+            && self.matchers.len() == other.matchers.len()
+            && self
+                .matchers
+                .iter()
+                .zip(&other.matchers)
+                .all(|(left, right)| Arc::ptr_eq(left, right))
+    }
+}
+
+impl Eq for DenyList {}
+
+/// `haystack` содержит `needle`
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    !needle.is_empty()
+        && haystack
+            .windows(needle.len())
+            .any(|window| window == needle)
+}
+
+/// Тесты запретного списка 🎯
+#[cfg(test)]
+mod grimoire_ward_tests {
+    use super::*;
+    use crate::compression_engine::compression_conjurer::weave_compression_spell_with_deny_list;
+    use crate::format::read_dictionary;
+    use crate::simple_api::{compress_data, compress_data_redacted, decompress_data};
+
+    fn leaky_config() -> String {
+        "database password swordfish, backup password swordfish, \
+         replica password swordfish, standby password swordfish\n"
+            .repeat(12)
+    }
+
+    #[test]
+    fn test_literal_patterns_match_both_ways() {
+        let deny_list = DenyList::new()
+            .with_literal("api_key=XYZsecret")
+            .with_literal("");
+        assert!(deny_list.denies(b"XYZsecret"));
+        assert!(deny_list.denies(b"key"));
+        assert!(deny_list.clone().with_literal("sword").denies(b"swordfish"));
+        assert!(!deny_list.denies(b"wizard"));
+        assert!(!DenyList::new().denies(b"anything"));
+        assert!(DenyList::new().is_empty());
+    }
+
+    #[test]
+    fn test_denied_words_never_reach_the_grimoire() {
+        let config = leaky_config();
+        let leaked = read_dictionary(compress_data(&config)).unwrap();
+        assert!(leaked.iter().any(|entry| entry.bytes == b"swordfish"));
+
+        let deny_list = DenyList::new()
+            .with_literal("swordfish")
+            .with_matcher(|word| word == b"password");
+        let redacted = compress_data_redacted(&config, &deny_list);
+        let entries = read_dictionary(&redacted).unwrap();
+        assert!(!entries.is_empty());
+        assert!(entries.iter().all(|entry| !deny_list.denies(&entry.bytes)));
+        assert_eq!(decompress_data(&redacted), config.as_bytes());
+
+        let artifact = weave_compression_spell_with_deny_list(&config, &deny_list);
+        assert!(!artifact
+            .mystical_word_grimoire
+            .iter()
+            .any(|word| deny_list.denies(word.as_bytes())));
+    }
+
+    #[test]
+    fn test_debug_hides_patterns() {
+        let deny_list = DenyList::new().with_literal("swordfish");
+        assert!(!format!("{:?}", deny_list).contains("swordfish"));
+        assert_eq!(deny_list.clone(), deny_list);
+        assert_ne!(deny_list.clone().with_matcher(|_| false), deny_list);
+    }
+}
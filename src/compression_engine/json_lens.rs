@@ -0,0 +1,413 @@
+//! Линза JSON 🔍
+//!
+//! Структурный режим для JSON: лексер делит вход на классы символов, и
+//! каждый класс кодируется своим арифметическим потоком со своей таблицей
+//! частот:
+//!
+//! - скелет — пунктуация, пробелы, `true`/`false`/`null` и ключи между
+//!   соседними значениями; одинаковые фрагменты скелета (в JSON Lines —
+//!   `, "status": ` каждой записи) становятся одним символом;
+//! - строковые значения — повторы тоже становятся одним символом;
+//! - числа — байты с завершающим символом.
+//!
+//! Скелет и строки — «интернированные» классы: поток ссылок (`0` — новая
+//! запись, `1 + i` — повтор записи `i`) и поток словаря с байтами новых
+//! записей, каждая с завершающим символом.
+//!
+//! Лексер не проверяет синтаксис: любой вход (битый JSON, произвольные
+//! байты) восстанавливается без потерь, просто хуже сжимается.
+//!
+//! Тело кадра — пять потоков: ссылки скелета, словарь скелета, ссылки
+//! строк, словарь строк, числа; каждый —
+//! `[таблица частот][общая частота][длина u32][битовый поток]`.
+
+use super::engine_covenant::{CompressionEngine, EngineConfig, Frame};
+use super::split_stream_engine::write_class_stream;
+use crate::bit_wizardry::bit_manipulation_spells::CoderPrecision;
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::decompression_oracle::decompression_sage::decode_symbol_stream;
+use crate::error::{WizardError, WizardResult};
+use crate::format::artifact_scroll::{
+    check_body_header, read_coded_stream, read_frequency_section, ScrollCursor,
+};
+use crate::format::container_runes::{ArchiveHeader, EngineKind, ModelKind};
+use std::collections::HashMap;
+
+/// Завершение записи словаря: за фрагментом скелета следует строка
+/// (в словаре строк — просто конец строки)
+const BEFORE_STRING: u32 = 256;
+
+/// Завершение фрагмента скелета: следует число
+const BEFORE_NUMBER: u32 = 257;
+
+/// Завершение фрагмента скелета: конец входа
+const END_OF_INPUT: u32 = 258;
+
+/// Конец числа в потоке чисел
+const NUMBER_END: u32 = 256;
+
+/// Ссылка на новую запись словаря
+const NEW_ENTRY: u32 = 0;
+
+/// Движок структурного режима JSON 🔍
+///
+/// Атлас алфавита и подсчет слов (`EngineConfig`) не применяются.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonStructureEngine;
+
+impl CompressionEngine for JsonStructureEngine {
+    fn engine_kind(&self) -> EngineKind {
+        EngineKind::StructuredJson
+    }
+
+    fn name(&self) -> &'static str {
+        "structured-json"
+    }
+
+    fn compress(&self, data: &[u8], _config: &EngineConfig) -> Frame {
+        let streams = lex_json(data);
+
+        let mut body = Vec::new();
+        for class_stream in [
+            &streams.skeleton.references,
+            &streams.skeleton.dictionary,
+            &streams.strings.references,
+            &streams.strings.dictionary,
+            &streams.numbers,
+        ] {
+            write_class_stream(&mut body, class_stream);
+        }
+
+        Frame {
+            header: ArchiveHeader::current(ModelKind::StaticOrder0, None)
+                .with_engine(EngineKind::StructuredJson),
+            body,
+        }
+    }
+
+    fn try_decompress(&self, frame: &Frame, options: &DecodeOptions) -> WizardResult<Vec<u8>> {
+        try_unweave_json_body(&frame.header, &frame.body, options)
+    }
+}
+
+/// Интернированный класс: ссылки и словарь новых записей
+#[derive(Debug, Default)]
+struct InternedStream<'a> {
+    references: Vec<u32>,
+    dictionary: Vec<u32>,
+    entry_ids: HashMap<(&'a [u8], u32), u32>,
+}
+
+impl<'a> InternedStream<'a> {
+    /// Добавляет запись: повтор — ссылкой, новую — в словарь
+    fn push(&mut self, content: &'a [u8], terminator: u32) {
+        let next_id = self.entry_ids.len() as u32;
+        match self.entry_ids.get(&(content, terminator)) {
+            Some(&entry_id) => self.references.push(entry_id + 1),
+            None => {
+                self.entry_ids.insert((content, terminator), next_id);
+                self.references.push(NEW_ENTRY);
+                self.dictionary
+                    .extend(content.iter().map(|&byte| byte as u32));
+                self.dictionary.push(terminator);
+            }
+        }
+    }
+}
+
+/// Потоки классов символов
+#[derive(Debug, Default)]
+struct JsonStreams<'a> {
+    skeleton: InternedStream<'a>,
+    strings: InternedStream<'a>,
+    numbers: Vec<u32>,
+}
+
+/// Делит вход на классы символов без проверки синтаксиса
+///
+/// Строка — ключ (часть скелета), если после нее и пробелов идет `:`.
+/// Незакрытая кавычка остается байтом скелета.
+fn lex_json(data: &[u8]) -> JsonStreams<'_> {
+    let mut streams = JsonStreams::default();
+    let mut fragment_start = 0;
+    let mut position = 0;
+
+    while position < data.len() {
+        let byte = data[position];
+        if byte == b'"' {
+            if let Some(closing) = find_string_end(data, position + 1) {
+                if !is_followed_by_colon(data, closing + 1) {
+                    streams
+                        .skeleton
+                        .push(&data[fragment_start..position], BEFORE_STRING);
+                    streams
+                        .strings
+                        .push(&data[position + 1..closing], BEFORE_STRING);
+                    fragment_start = closing + 1;
+                }
+                position = closing + 1;
+                continue;
+            }
+        } else if byte == b'-' || byte.is_ascii_digit() {
+            let end = data[position..]
+                .iter()
+                .position(|&next| !is_number_byte(next))
+                .map_or(data.len(), |offset| position + offset);
+            streams
+                .skeleton
+                .push(&data[fragment_start..position], BEFORE_NUMBER);
+            streams
+                .numbers
+                .extend(data[position..end].iter().map(|&byte| byte as u32));
+            streams.numbers.push(NUMBER_END);
+            fragment_start = end;
+            position = end;
+            continue;
+        }
+        position += 1;
+    }
+
+    streams.skeleton.push(&data[fragment_start..], END_OF_INPUT);
+    streams
+}
+
+/// Позиция закрывающей кавычки строки, начинающейся с `start`
+fn find_string_end(data: &[u8], start: usize) -> Option<usize> {
+    let mut position = start;
+    while position < data.len() {
+        match data[position] {
+            b'\\' => position += 2,
+            b'"' => return Some(position),
+            _ => position += 1,
+        }
+    }
+    None
+}
+
+/// Идет ли после `position` (и пробелов) двоеточие
+fn is_followed_by_colon(data: &[u8], position: usize) -> bool {
+    data[position.min(data.len())..]
+        .iter()
+        .find(|byte| !byte.is_ascii_whitespace())
+        == Some(&b':')
+}
+
+/// Байт, продолжающий число
+fn is_number_byte(byte: u8) -> bool {
+    byte.is_ascii_digit() || matches!(byte, b'-' | b'+' | b'.' | b'e' | b'E')
+}
+
+/// Проверяемая распаковка тела кадра структурного режима JSON 🛡️
+pub(crate) fn try_unweave_json_body(
+    header: &ArchiveHeader,
+    body: &[u8],
+    options: &DecodeOptions,
+) -> WizardResult<Vec<u8>> {
+    check_body_header(header, EngineKind::StructuredJson)?;
+    if header.alphabet_map.is_some() {
+        return Err(WizardError::CorruptFrequencyTable(
+            "атлас алфавита не применяется к структурному режиму",
+        ));
+    }
+
+    let precision = header.coder_precision();
+    let mut cursor = ScrollCursor::new(body, 0);
+    let mut class_streams = Vec::with_capacity(5);
+    for _ in 0..5 {
+        class_streams.push(read_class_stream(&mut cursor, options, precision)?);
+    }
+    if cursor.remaining() != 0 {
+        return Err(WizardError::CorruptPayload);
+    }
+    let numbers = class_streams.pop().unwrap_or_default();
+    let string_dictionary = class_streams.pop().unwrap_or_default();
+    let string_references = class_streams.pop().unwrap_or_default();
+    let skeleton_dictionary = class_streams.pop().unwrap_or_default();
+    let skeleton_references = class_streams.pop().unwrap_or_default();
+
+    let mut skeleton = InternedReader::new(&skeleton_dictionary, options);
+    let mut strings = InternedReader::new(&string_dictionary, options);
+    let mut string_references = string_references.into_iter();
+    let mut numbers = numbers.into_iter();
+    let mut restored = Vec::new();
+    let mut reached_end = false;
+
+    for reference in skeleton_references {
+        if reached_end {
+            return Err(WizardError::CorruptPayload);
+        }
+        let (fragment, terminator) = skeleton.resolve(reference)?;
+        restored.extend_from_slice(fragment);
+        match terminator {
+            BEFORE_STRING => {
+                let reference = string_references
+                    .next()
+                    .ok_or(WizardError::CorruptPayload)?;
+                let (content, _) = strings.resolve(reference)?;
+                restored.push(b'"');
+                restored.extend_from_slice(content);
+                restored.push(b'"');
+            }
+            BEFORE_NUMBER => loop {
+                match numbers.next() {
+                    Some(NUMBER_END) => break,
+                    Some(byte @ 0..=255) => restored.push(byte as u8),
+                    _ => return Err(WizardError::CorruptPayload),
+                }
+            },
+            END_OF_INPUT => reached_end = true,
+            _ => return Err(WizardError::CorruptPayload),
+        }
+        options.check_output(restored.len() as u64)?;
+    }
+
+    if !reached_end
+        || string_references.next().is_some()
+        || numbers.next().is_some()
+        || !skeleton.is_exhausted()
+        || !strings.is_exhausted()
+    {
+        return Err(WizardError::CorruptPayload);
+    }
+    Ok(restored)
+}
+
+/// Читает и декодирует поток одного класса
+fn read_class_stream(
+    cursor: &mut ScrollCursor<'_>,
+    options: &DecodeOptions,
+    precision: CoderPrecision,
+) -> WizardResult<Vec<u32>> {
+    let frequency_codex = read_frequency_section(cursor, options)?;
+    let (total_frequency, compressed_bit_stream) = read_coded_stream(cursor)?;
+    // Ссылка или символ словаря дают хотя бы байт результата, кроме
+    // пустых записей, которых не больше, чем завершающих символов
+    options.reserve_output(total_frequency)?;
+    decode_symbol_stream(
+        compressed_bit_stream,
+        &frequency_codex,
+        total_frequency,
+        total_frequency,
+        precision,
+    )
+}
+
+/// Разбор интернированного класса при распаковке
+struct InternedReader<'a> {
+    dictionary: std::slice::Iter<'a, u32>,
+    entries: Vec<(Vec<u8>, u32)>,
+    options: &'a DecodeOptions,
+}
+
+impl<'a> InternedReader<'a> {
+    fn new(dictionary: &'a [u32], options: &'a DecodeOptions) -> Self {
+        Self {
+            dictionary: dictionary.iter(),
+            entries: Vec::new(),
+            options,
+        }
+    }
+
+    /// Запись по ссылке: новая читается из словаря
+    fn resolve(&mut self, reference: u32) -> WizardResult<(&[u8], u32)> {
+        if reference == NEW_ENTRY {
+            self.options.check_dict_entries(self.entries.len() + 1)?;
+            let mut content = Vec::new();
+            loop {
+                match self.dictionary.next() {
+                    Some(&byte @ 0..=255) => content.push(byte as u8),
+                    Some(&terminator) => {
+                        self.entries.push((content, terminator));
+                        break;
+                    }
+                    None => return Err(WizardError::CorruptPayload),
+                }
+            }
+        }
+
+        let entry_index = match reference {
+            NEW_ENTRY => self.entries.len() - 1,
+            _ => reference as usize - 1,
+        };
+        self.entries
+            .get(entry_index)
+            .map(|(content, terminator)| (content.as_slice(), *terminator))
+            .ok_or(WizardError::CorruptPayload)
+    }
+
+    /// Весь словарь разобран
+    fn is_exhausted(&self) -> bool {
+        self.dictionary.len() == 0
+    }
+}
+
+/// Тесты линзы JSON 🎯
+#[cfg(test)]
+mod json_lens_tests {
+    use super::*;
+    use crate::simple_api::compress_data;
+
+    fn json_lines() -> Vec<u8> {
+        (0..200)
+            .map(|record| {
+                format!(
+                    "{{\"user_id\": {record}, \"status\": \"active\", \"score\": {}.5, \"tags\": [\"a{}\", null, true]}}\n",
+                    record * 37 % 1000,
+                    record % 7
+                )
+            })
+            .collect::<String>()
+            .into_bytes()
+    }
+
+    #[test]
+    fn test_json_records_roundtrip_and_beat_plain_model() {
+        let data = json_lines();
+        let frame = JsonStructureEngine.compress(&data, &EngineConfig::default());
+        assert_eq!(
+            JsonStructureEngine
+                .try_decompress(&frame, &DecodeOptions::default())
+                .unwrap(),
+            data
+        );
+        assert!(
+            frame.to_bytes().len() * 2 < compress_data(&data).len(),
+            "{} против {}",
+            frame.to_bytes().len(),
+            compress_data(&data).len()
+        );
+    }
+
+    #[test]
+    fn test_arbitrary_bytes_roundtrip() {
+        let cases: [&[u8]; 6] = [
+            b"",
+            b"{\"unterminated: 1",
+            b"\"escaped \\\" quote\" : \"tail\\",
+            b"-12e+5-- 3..4 \"k\"\n:\"v\"",
+            &[0, 255, b'"', 0x80, b':', 7],
+            b"plain text without any json at all",
+        ];
+        for data in cases {
+            let frame = JsonStructureEngine.compress(data, &EngineConfig::default());
+            assert_eq!(JsonStructureEngine.decompress(&frame), data);
+        }
+    }
+
+    #[test]
+    fn test_damaged_frame_is_rejected() {
+        let frame = JsonStructureEngine.compress(&json_lines(), &EngineConfig::default());
+
+        let mut truncated = frame.clone();
+        truncated.body.truncate(truncated.body.len() / 2);
+        assert!(JsonStructureEngine
+            .try_decompress(&truncated, &DecodeOptions::default())
+            .is_err());
+
+        let limited = DecodeOptions::default().with_max_output_bytes(100);
+        assert!(matches!(
+            JsonStructureEngine.try_decompress(&frame, &limited),
+            Err(WizardError::LimitExceeded { .. })
+        ));
+    }
+}
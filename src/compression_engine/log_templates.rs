@@ -0,0 +1,296 @@
+//! Шаблоны строк журналов 🪵
+//!
+//! Серверные журналы состоят из строк немногих видов:
+//! `2023-01-01 12:00:01 INFO Server started on port 8080`. Символизатор
+//! делит строку на слова по пробелам; слова с цифрами (даты, время,
+//! порты, идентификаторы) считаются переменными полями, остальное —
+//! шаблоном. Шаблон, встретившийся хотя бы дважды, кодируется одним
+//! символом, за которым следуют байты полей.
+//!
+//! Символы: `0–255` — байт, `256 + b` — последний байт поля `b`,
+//! `512 + i` — шаблон `i`. Строки с редкими шаблонами остаются байтами.
+//!
+//! Модель: `[кол-во шаблонов u32]`, затем для каждого
+//! `[кол-во полей u32]` и `кол-во полей + 1` литералов `[длина u32][байты]`.
+
+use super::engine_covenant::EngineConfig;
+use super::symbolizer::{Symbolization, Symbolizer};
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::error::{WizardError, WizardResult};
+use crate::format::artifact_scroll::ScrollCursor;
+use std::collections::HashMap;
+
+/// Первый символ «последний байт поля»
+const FIELD_LAST_BASE: u32 = 256;
+
+/// Первый символ шаблона
+const TEMPLATE_BASE: u32 = 512;
+
+/// Символизатор шаблонов строк журналов 🪵
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogTemplateSymbolizer;
+
+/// Строка, разобранная на литералы шаблона и переменные поля
+struct ParsedLine<'a> {
+    literals: Vec<&'a [u8]>,
+    fields: Vec<&'a [u8]>,
+}
+
+impl Symbolizer for LogTemplateSymbolizer {
+    fn name(&self) -> &'static str {
+        "log-templates"
+    }
+
+    fn symbolize(&self, data: &[u8], _config: &EngineConfig) -> Symbolization {
+        let lines: Vec<(&[u8], ParsedLine<'_>)> = data
+            .split_inclusive(|&byte| byte == b'\n')
+            .map(|line| (line, parse_line(line)))
+            .collect();
+
+        let mut template_counts: HashMap<&[&[u8]], usize> = HashMap::new();
+        for (_, parsed) in &lines {
+            *template_counts.entry(&parsed.literals).or_insert(0) += 1;
+        }
+
+        let mut template_ids: HashMap<&[&[u8]], u32> = HashMap::new();
+        let mut model = vec![0; 4];
+        let mut symbols = Vec::with_capacity(data.len());
+        for (line, parsed) in &lines {
+            let literals = parsed.literals.as_slice();
+            // Шаблон без литералов не дал бы байтов сам по себе
+            let worth_template =
+                template_counts[literals] > 1 && literals.iter().any(|literal| !literal.is_empty());
+            if !worth_template {
+                symbols.extend(line.iter().map(|&byte| byte as u32));
+                continue;
+            }
+
+            let next_id = template_ids.len() as u32;
+            let template_id = *template_ids.entry(literals).or_insert_with(|| {
+                write_template(&mut model, literals);
+                next_id
+            });
+            symbols.push(TEMPLATE_BASE + template_id);
+            for field in &parsed.fields {
+                let (&last, head) = field.split_last().expect("поле не бывает пустым");
+                symbols.extend(head.iter().map(|&byte| byte as u32));
+                symbols.push(FIELD_LAST_BASE + last as u32);
+            }
+        }
+        model[..4].copy_from_slice(&(template_ids.len() as u32).to_le_bytes());
+
+        Symbolization { symbols, model }
+    }
+
+    fn try_reconstruct(
+        &self,
+        symbols: &[u32],
+        model: &[u8],
+        options: &DecodeOptions,
+    ) -> WizardResult<Vec<u8>> {
+        let templates = read_templates(model, options)?;
+
+        let mut symbols = symbols.iter();
+        let mut restored = Vec::new();
+        while let Some(&mystical_symbol) = symbols.next() {
+            match mystical_symbol {
+                0..=255 => restored.push(mystical_symbol as u8),
+                FIELD_LAST_BASE..TEMPLATE_BASE => return Err(WizardError::CorruptPayload),
+                _ => {
+                    let literals = templates
+                        .get((mystical_symbol - TEMPLATE_BASE) as usize)
+                        .ok_or(WizardError::CorruptPayload)?;
+                    restored.extend_from_slice(literals[0]);
+                    for literal in &literals[1..] {
+                        restore_field(&mut symbols, &mut restored)?;
+                        restored.extend_from_slice(literal);
+                    }
+                }
+            }
+            options.check_output(restored.len() as u64)?;
+        }
+        Ok(restored)
+    }
+}
+
+/// Делит строку на литералы и поля: слово с цифрой — поле
+fn parse_line(line: &[u8]) -> ParsedLine<'_> {
+    let mut literals = Vec::new();
+    let mut fields = Vec::new();
+    let mut literal_start = 0;
+    let mut position = 0;
+
+    while position < line.len() {
+        if line[position].is_ascii_whitespace() {
+            position += 1;
+            continue;
+        }
+        let word_end = line[position..]
+            .iter()
+            .position(u8::is_ascii_whitespace)
+            .map_or(line.len(), |offset| position + offset);
+        if line[position..word_end].iter().any(u8::is_ascii_digit) {
+            literals.push(&line[literal_start..position]);
+            fields.push(&line[position..word_end]);
+            literal_start = word_end;
+        }
+        position = word_end;
+    }
+
+    literals.push(&line[literal_start..]);
+    ParsedLine { literals, fields }
+}
+
+/// Дописывает шаблон в модель
+fn write_template(model: &mut Vec<u8>, literals: &[&[u8]]) {
+    model.extend_from_slice(&(literals.len() as u32 - 1).to_le_bytes());
+    for literal in literals {
+        model.extend_from_slice(&(literal.len() as u32).to_le_bytes());
+        model.extend_from_slice(literal);
+    }
+}
+
+/// Читает шаблоны модели: литералы каждого шаблона
+pub(crate) fn read_templates<'a>(
+    model: &'a [u8],
+    options: &DecodeOptions,
+) -> WizardResult<Vec<Vec<&'a [u8]>>> {
+    let mut cursor = ScrollCursor::new(model, 0);
+    let template_count = cursor.read_u32()? as usize;
+    options.check_dict_entries(template_count)?;
+
+    let mut templates = Vec::with_capacity(template_count.min(cursor.remaining() / 8));
+    for _ in 0..template_count {
+        let field_count = cursor.read_u32()? as usize;
+        // Каждый литерал занимает хотя бы 4 байта модели
+        if field_count >= cursor.remaining() / 4 {
+            return Err(WizardError::CorruptPayload);
+        }
+        let mut literals = Vec::with_capacity(field_count + 1);
+        for _ in 0..=field_count {
+            let literal_len = cursor.read_u32()? as usize;
+            literals.push(cursor.read_slice(literal_len)?);
+        }
+        templates.push(literals);
+    }
+
+    if cursor.remaining() != 0 {
+        return Err(WizardError::CorruptPayload);
+    }
+    Ok(templates)
+}
+
+/// Переносит байты поля до его последнего байта включительно
+fn restore_field<'a>(
+    symbols: &mut impl Iterator<Item = &'a u32>,
+    restored: &mut Vec<u8>,
+) -> WizardResult<()> {
+    loop {
+        match symbols.next() {
+            Some(&byte @ 0..=255) => restored.push(byte as u8),
+            Some(&last @ FIELD_LAST_BASE..TEMPLATE_BASE) => {
+                restored.push((last - FIELD_LAST_BASE) as u8);
+                return Ok(());
+            }
+            _ => return Err(WizardError::CorruptPayload),
+        }
+    }
+}
+
+/// Тесты шаблонов журналов 🎯
+#[cfg(test)]
+mod log_templates_tests {
+    use super::*;
+    use crate::compression_engine::engine_covenant::CompressionEngine;
+    use crate::compression_engine::symbolizer::SymbolizerEngine;
+    use crate::format::container_runes::EngineKind;
+    use crate::simple_api::compress_data;
+
+    fn server_log() -> Vec<u8> {
+        let mut log = String::new();
+        for second in 0..120 {
+            log.push_str(&format!(
+                "2023-01-01 12:{:02}:{:02} INFO Server started on port {}\n",
+                second / 60,
+                second % 60,
+                8000 + second % 7
+            ));
+            log.push_str(&format!(
+                "2023-01-01 12:{:02}:{:02} WARN Slow request id={} took {}ms\n",
+                second / 60,
+                second % 60,
+                second * 31,
+                second % 13 * 17
+            ));
+        }
+        log.push_str("shutting down\n");
+        log.into_bytes()
+    }
+
+    #[test]
+    fn test_templates_roundtrip_and_beat_plain_model() {
+        let log = server_log();
+        let symbolization = LogTemplateSymbolizer.symbolize(&log, &EngineConfig::default());
+        assert!(symbolization.symbols.contains(&(TEMPLATE_BASE + 1)));
+        assert!(!symbolization.symbols.contains(&(TEMPLATE_BASE + 2)));
+
+        let engine = SymbolizerEngine::new(EngineKind::LogTemplates, LogTemplateSymbolizer);
+        let frame = engine.compress(&log, &EngineConfig::default());
+        assert_eq!(
+            engine
+                .try_decompress(&frame, &DecodeOptions::default())
+                .unwrap(),
+            log
+        );
+        assert!(
+            frame.to_bytes().len() < compress_data(&log).len(),
+            "{} против {}",
+            frame.to_bytes().len(),
+            compress_data(&log).len()
+        );
+    }
+
+    #[test]
+    fn test_irregular_input_roundtrip() {
+        let cases: [&[u8]; 5] = [
+            b"",
+            b"42\n42\n42",
+            b"a 1\na 2\n\n\na 3 \t\r\nb",
+            &[0, 200, b'7', b' ', 0xff, b'\n', 0, 200, b'8', b' ', 0xff],
+            b"no digits here\nno digits here\n",
+        ];
+        for data in cases {
+            let symbolization = LogTemplateSymbolizer.symbolize(data, &EngineConfig::default());
+            assert_eq!(
+                LogTemplateSymbolizer
+                    .try_reconstruct(
+                        &symbolization.symbols,
+                        &symbolization.model,
+                        &DecodeOptions::default()
+                    )
+                    .unwrap(),
+                data
+            );
+        }
+    }
+
+    #[test]
+    fn test_corrupt_model_and_symbols_rejected() {
+        let symbolization =
+            LogTemplateSymbolizer.symbolize(&server_log(), &EngineConfig::default());
+        let options = DecodeOptions::default();
+
+        let mut truncated = symbolization.model.clone();
+        truncated.pop();
+        assert!(LogTemplateSymbolizer
+            .try_reconstruct(&symbolization.symbols, &truncated, &options)
+            .is_err());
+
+        for symbols in [vec![300], vec![TEMPLATE_BASE + 9], vec![TEMPLATE_BASE, 65]] {
+            assert_eq!(
+                LogTemplateSymbolizer.try_reconstruct(&symbols, &symbolization.model, &options),
+                Err(WizardError::CorruptPayload)
+            );
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! Микрозаклинание: архивы крошечных сообщений 🐜
+//!
+//! Заголовок, словарь и таблица частот обычного архива занимают десятки
+//! байтов — больше, чем само сообщение MQTT или ключ кэша. Микроархив
+//! состоит из одного байта-метки и арифметического потока адаптивной
+//! модели нулевого порядка, встроенной в библиотеку: таблица частот
+//! не хранится, а конец сообщения кодируется символом `END_OF_MESSAGE`.
+//!
+//! Априорная модель отдает печатному ASCII больший вес, поэтому текстовые
+//! ключи и короткий JSON не раздуваются даже без повторов.
+//!
+//! Раскладка: `[MICRO_MARKER][битовый поток]`. Модель и метка заморожены:
+//! изменение модели требует новой метки, иначе старые микроархивы
+//! распакуются неверно.
+//!
+//! Метка не конфликтует с другими форматами: архивы с заголовком
+//! начинаются с `b"ACW"`, а устаревший архив версии 0 с таким первым
+//! байтом объявлял бы 181+ слов и был бы длиннее `MICRO_MAX_ARCHIVE_LEN`.
+
+use super::adaptive_tide::AgingModel;
+use crate::bit_wizardry::bit_manipulation_spells::CoderPrecision;
+use crate::bit_wizardry::entropy_coder::{ArithmeticDecoder, ArithmeticEncoder};
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::error::{WizardError, WizardResult};
+
+/// Байт-метка микроархива
+pub const MICRO_MARKER: u8 = 0xB5;
+
+/// Самое длинное сообщение, которое кодируется микроархивом
+pub const MICRO_INPUT_LIMIT: usize = 64;
+
+/// Микроархив всегда короче (с запасом на худший случай)
+pub(crate) const MICRO_MAX_ARCHIVE_LEN: usize = 256;
+
+/// Символ конца сообщения
+const END_OF_MESSAGE: usize = 256;
+
+/// Априорный вес печатного ASCII
+const PRINTABLE_WEIGHT: u32 = 16;
+
+/// Модели микроархива не стареют: сообщения короче периода
+const MICRO_HALF_LIFE: u32 = u32::MAX;
+
+/// Встроенная модель микроархива
+fn micro_model() -> AgingModel {
+    let frequencies = (0..=END_OF_MESSAGE)
+        .map(|symbol| match symbol {
+            0x20..=0x7E | 0x0A => PRINTABLE_WEIGHT,
+            _ => 1,
+        })
+        .collect();
+    AgingModel::with_frequencies(frequencies, MICRO_HALF_LIFE, CoderPrecision::Bits32)
+        .expect("встроенная модель умещается в точность кодера")
+}
+
+/// Кодирует сообщение микроархивом
+///
+/// `None` для сообщений длиннее [`MICRO_INPUT_LIMIT`].
+pub(crate) fn weave_micro_archive(message: &[u8]) -> Option<Vec<u8>> {
+    if message.len() > MICRO_INPUT_LIMIT {
+        return None;
+    }
+
+    let mut archive = vec![MICRO_MARKER];
+    let mut model = micro_model();
+    let mut encoder = ArithmeticEncoder::new(&mut archive, CoderPrecision::Bits32);
+    let symbols = message
+        .iter()
+        .map(|&byte| byte as usize)
+        .chain([END_OF_MESSAGE]);
+    for symbol in symbols {
+        let (low, high) = model.interval(symbol);
+        encoder
+            .encode(low, high, model.total())
+            .expect("интервалы встроенной модели корректны");
+        model.observe(symbol);
+    }
+    encoder.finish();
+    Some(archive)
+}
+
+/// Байты похожи на микроархив
+pub(crate) fn is_micro_archive(archive_bytes: &[u8]) -> bool {
+    archive_bytes.first() == Some(&MICRO_MARKER) && archive_bytes.len() < MICRO_MAX_ARCHIVE_LEN
+}
+
+/// Проверяемая распаковка микроархива
+pub(crate) fn try_unweave_micro_archive(
+    archive_bytes: &[u8],
+    options: &DecodeOptions,
+) -> WizardResult<Vec<u8>> {
+    if !is_micro_archive(archive_bytes) {
+        return Err(WizardError::UnrecognizedHeader);
+    }
+
+    let mut model = micro_model();
+    let mut decoder = ArithmeticDecoder::new(&archive_bytes[1..], CoderPrecision::Bits32);
+    let mut restored = Vec::new();
+    loop {
+        let target = decoder.decode_target(model.total())?;
+        let symbol = model.symbol_at(target);
+        let (low, high) = model.interval(symbol);
+        decoder.consume(low, high, model.total())?;
+        if symbol == END_OF_MESSAGE {
+            return Ok(restored);
+        }
+        // Мусорный поток может не дойти до конца сообщения
+        if restored.len() == MICRO_INPUT_LIMIT {
+            return Err(WizardError::CorruptPayload);
+        }
+        options.check_output(restored.len() as u64 + 1)?;
+        restored.push(symbol as u8);
+        model.observe(symbol);
+    }
+}
+
+/// Тесты микроархивов 🎯
+#[cfg(test)]
+mod micro_spell_tests {
+    use super::*;
+    use crate::simple_api::{
+        compress_compact, compress_data, decompress_data, try_decompress_data,
+    };
+
+    #[test]
+    fn test_tiny_messages_stay_tiny() {
+        let messages: [&[u8]; 5] = [
+            b"",
+            b"user:42:session",
+            b"{\"temp\":21.5,\"hum\":40}",
+            &[0, 1, 2, 255, 254],
+            &[b'z'; MICRO_INPUT_LIMIT],
+        ];
+        for message in messages {
+            let archive = compress_compact(message);
+            assert_eq!(archive[0], MICRO_MARKER);
+            if message.iter().all(u8::is_ascii_graphic) {
+                assert!(archive.len() <= message.len() + 3, "{:?}", message);
+            }
+            assert!(archive.len() < compress_data(message).len());
+            assert_eq!(decompress_data(&archive), message);
+        }
+
+        // Длинный вход — обычный архив
+        let long = "longer than a micro archive allows ".repeat(3);
+        assert_eq!(compress_compact(&long), compress_data(&long));
+        assert!(weave_micro_archive(long.as_bytes()).is_none());
+    }
+
+    #[test]
+    fn test_micro_layout_is_frozen() {
+        // Изменение модели или метки ломает уже выпущенные микроархивы
+        let archive = weave_micro_archive(b"cache:key").unwrap();
+        assert_eq!(archive, FROZEN_CACHE_KEY);
+        assert_eq!(decompress_data(FROZEN_CACHE_KEY), b"cache:key");
+    }
+
+    const FROZEN_CACHE_KEY: &[u8] = &[0xB5, 0xAA, 0x56, 0xAF, 0x73, 0xEE, 0xA5, 0x10, 0x67, 0x50];
+
+    #[test]
+    fn test_damaged_micro_archives_rejected() {
+        let options = DecodeOptions::default();
+        let archive = weave_micro_archive(b"sensor/7/temperature").unwrap();
+        assert!(try_decompress_data(&archive, &options.clone().with_max_output_bytes(4)).is_err());
+
+        for garbage in [&[MICRO_MARKER][..], &[MICRO_MARKER, 0xFF, 0xFF, 0xFF, 0xFF]] {
+            // Мусор либо отвергается, либо дает не больше лимита байтов
+            if let Ok(restored) = try_decompress_data(garbage, &options) {
+                assert!(restored.len() <= MICRO_INPUT_LIMIT);
+            }
+        }
+        let mut oversized = vec![MICRO_MARKER];
+        oversized.resize(MICRO_MAX_ARCHIVE_LEN, 0);
+        assert!(try_unweave_micro_archive(&oversized, &options).is_err());
+    }
+}
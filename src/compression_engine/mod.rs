@@ -1,11 +1,142 @@
 //! Модуль движка сжатия 🚀
 //! Основные алгоритмы компрессии данных
 
+pub mod adaptive_tide;
+pub mod code_point_glyphs;
+pub mod column_weave;
 pub mod compression_conjurer;
+pub mod compressor_handle;
+pub mod context_loom;
+pub mod context_snapshot;
+pub mod dedup;
+pub mod delta_forge;
+pub mod engine_covenant;
+pub mod engine_registry;
+pub mod frequency_census;
+pub mod grimoire_library;
+pub mod grimoire_quill;
+pub mod grimoire_trie;
+pub mod grimoire_ward;
+pub mod json_lens;
+pub mod log_templates;
+pub mod micro_spell;
+pub mod model_augury;
+pub mod model_blend;
+pub mod model_courier;
+pub mod phrase_weaver;
+pub mod plane_sieve;
+pub mod preset_tomes;
+pub mod repeat_echo;
+pub mod scratch_cauldron;
+pub mod split_stream_engine;
+pub mod stored_vault;
+pub mod symbolizer;
+pub mod token_glyphs;
+pub mod wide_glyphs;
+pub mod word_frontier;
+pub mod word_sketch;
 
 // Экспорт основных типов и функций
 
+pub use adaptive_tide::DEFAULT_HALF_LIFE; // Период полураспада адаптивной модели
+pub use code_point_glyphs::CodePointSymbolizer; // Кодовые точки UTF-8 как символы
+pub use column_weave::{
+    detect_delimiter, // Угадывание разделителя таблицы
+    ColumnarEngine,   // Движок столбцового режима
+};
 pub use compression_conjurer::{
-    weave_compression_spell, // Главная функция сжатия
-    CompressionArtifact,     // Результат сжатия
+    try_weave_compression_spell,               // Проверяемое сжатие
+    try_weave_compression_spell_with_counting, // Проверяемое сжатие с подсчетом слов
+    weave_compression_spell,                   // Главная функция сжатия
+    weave_compression_spell_with_counting,     // Сжатие с выбранным подсчетом слов
+    weave_compression_spell_with_deny_list,    // Сжатие с запретным списком словаря
+    CompressionArtifact,                       // Результат сжатия
+};
+pub use compressor_handle::{
+    CompressorHandle,          // Компрессор с общей моделью
+    HandleArtifact,            // Артефакт со ссылкой на модель
+    DICTIONARY_FORMAT_VERSION, // Версия файла словаря
+    DICTIONARY_MAGIC,          // Сигнатура файла словаря
+};
+pub use context_loom::DEFAULT_CONTEXT_TABLE_BITS; // Размер таблицы контекстов второго порядка
+pub use context_snapshot::ContextSnapshot; // Снимок словаря и модели прошлого сжатия
+pub use dedup::DedupEngine; // Дедупликация фрагментов по содержимому
+pub use delta_forge::{
+    DELTA_FORMAT_VERSION, // Версия формата патча
+    DELTA_MAGIC,          // Сигнатура патча
+};
+pub use engine_covenant::{
+    ArithmeticEngine,  // Встроенный арифметический движок
+    CompressionEngine, // Интерфейс движка
+    EngineConfig,      // Параметры сжатия
+    Frame,             // Кадр с кодом движка
+    ParseStrategy,     // Разбор текста на слова словаря
+    PipelineStage,     // Стадия предобработки байтов
+    StructuredMode,    // Структурный режим предобработки
+};
+pub use engine_registry::EngineRegistry; // Реестр движков
+pub use grimoire_library::DictionaryRegistry; // Общий кэш словарей по идентификатору
+pub use grimoire_quill::{
+    Grimoire,       // Словарь, заданный вручную
+    SharedGrimoire, // Слова словаря в общем буфере
+};
+pub use grimoire_ward::DenyList; // Запретный список словаря
+pub use json_lens::JsonStructureEngine; // Движок структурного режима JSON
+pub use log_templates::LogTemplateSymbolizer; // Шаблоны строк журналов
+pub use micro_spell::{
+    MICRO_INPUT_LIMIT, // Предел входа микроархива
+    MICRO_MARKER,      // Байт-метка микроархива
+};
+pub use model_augury::{
+    probe_model,             // Выбор движка и модели по пробе входа
+    ModelChoice,             // Решение автоматического выбора
+    AUTO_MODEL_METADATA_KEY, // Ключ метаданных с решением
+    PROBE_WINDOW_BYTES,      // Длина окна пробы
+};
+pub use model_blend::MixEngine; // Смешение литеральной модели и модели слов (экспериментально)
+pub use model_courier::{
+    MODEL_JSON_FORMAT,  // Значение поля format JSON модели
+    MODEL_JSON_VERSION, // Версия схемы JSON модели
+};
+pub use plane_sieve::BitPlaneEngine; // Битовые плоскости со своими моделями
+pub use preset_tomes::Preset; // Встроенные словари для частых видов данных
+pub use repeat_echo::{
+    LongRepeatEngine, // Длинные повторы как ссылки назад
+    REPEAT_WINDOW,    // Окно поиска длинных повторов
+};
+pub use scratch_cauldron::{
+    try_weave_compression_spell_with_scratch, // Проверяемое сжатие с буферами котла
+    weave_compression_spell_with_scratch,     // Сжатие с буферами котла
+    ScratchSpace,                             // Переиспользуемые буферы сжатия
+};
+pub use split_stream_engine::SplitStreamEngine; // Раздельные потоки литералов и ссылок
+pub use stored_vault::{
+    CompressOutcome, // Итог сжатия с порогом выгоды
+    StoredEngine,    // Хранение без сжатия
+};
+pub use symbolizer::{
+    Symbolization,    // Символы и модель
+    Symbolizer,       // Интерфейс символизатора
+    SymbolizerEngine, // Движок из символизатора
+    WordSymbolizer,   // Встроенный символизатор слов
+};
+pub use token_glyphs::{
+    compress_symbols,       // Сжатие потока id токенов
+    decompress_symbols,     // Распаковка потока id токенов
+    try_compress_symbols,   // Проверяемое сжатие потока id
+    try_decompress_symbols, // Проверяемая распаковка потока id
+    TOKEN_FORMAT_VERSION,   // Версия раскладки потока токенов
+    TOKEN_MAGIC,            // Сигнатура потока токенов
+};
+pub use wide_glyphs::WideSymbolizer;
+pub use word_frontier::{
+    AsciiBoundary,   // Латинские буквы и апостроф
+    BoundaryPolicy,  // Правило границ слов
+    NoBoundary,      // Подстановка слов без границ
+    UnicodeBoundary, // Буквы любого алфавита
+    WordBoundaries,  // Политика границ в настройках
 };
+pub use word_sketch::{
+    SketchParams, // Параметры приближенного подсчета
+    WordCounting, // Способ подсчета слов
+}; // Пары байтов как символы
@@ -0,0 +1,262 @@
+//! Авгурия модели: выбор движка и модели по пробе входа 🔮
+//!
+//! Какая модель окупится, видно по небольшой пробе: начало и конец входа
+//! (по [`PROBE_WINDOW_BYTES`]) дают энтропию нулевого порядка, выигрыш
+//! контекста первого порядка ([`bigram_matrix`]) и экономию словаря,
+//! который нашел бы поиск слов. [`probe_model`] сравнивает их:
+//!
+//! - почти восемь бит на байт — вход уже сжат, кадр хранения;
+//! - контекст экономит больше словаря, а вход достаточно длинный, чтобы
+//!   окупить прогрев модели, — `ModelKind::HashedOrder2`;
+//! - иначе статическая модель со словарем.
+//!
+//! [`EngineConfig::auto_model`](super::EngineConfig::auto_model) включает
+//! выбор в [`try_compress_with_config`](crate::simple_api::try_compress_with_config),
+//! а решение записывается в метаданные архива под
+//! [`AUTO_MODEL_METADATA_KEY`].
+
+use super::compression_conjurer::discover_scored_words;
+use super::context_loom::DEFAULT_CONTEXT_TABLE_BITS;
+use super::engine_covenant::EngineConfig;
+use super::engine_registry::EngineRegistry;
+use crate::format::container_runes::{EngineKind, ModelKind, MAX_CONTEXT_TABLE_BITS};
+use crate::statistics::bigram_matrix;
+
+/// Ключ метаданных с решением автоматического выбора
+pub const AUTO_MODEL_METADATA_KEY: &str = "acw.auto_model";
+
+/// Длина каждого из двух окон пробы: начала и конца входа
+pub const PROBE_WINDOW_BYTES: usize = 32 * 1024;
+
+/// Энтропия нулевого порядка, с которой вход считается уже сжатым
+const INCOMPRESSIBLE_BITS_PER_BYTE: f64 = 7.9;
+
+/// Наименьший выигрыш контекста, бит на байт, ради которого стоит
+/// включать модель второго порядка
+const MIN_CONTEXT_GAIN: f64 = 0.5;
+
+/// Наименьший вход модели второго порядка: на коротком она не успевает
+/// обучиться
+const MIN_CONTEXT_INPUT_BYTES: usize = 8 * 1024;
+
+/// Решение автоматического выбора и оценки, на которых оно основано 🔮
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelChoice {
+    /// Выбранный движок
+    pub engine: EngineKind,
+    /// Выбранная модель арифметического движка
+    pub model: ModelKind,
+    /// Энтропия нулевого порядка пробы, бит на байт
+    pub order0_entropy: f64,
+    /// Выигрыш контекста первого порядка на пробе, бит на байт
+    pub order1_gain: f64,
+    /// Оценка экономии словаря на пробе, байт
+    pub dictionary_savings: u64,
+}
+
+impl ModelChoice {
+    /// Параметры `config` с выбранными движком и моделью
+    ///
+    /// Прочие параметры (словарь, разбор, воспроизводимость) сохраняются;
+    /// флаг автоматического выбора снимается.
+    pub fn apply(&self, config: &EngineConfig) -> EngineConfig {
+        config
+            .clone()
+            .with_engine(self.engine)
+            .with_model(self.model)
+            .with_auto_model(false)
+    }
+
+    /// Запись решения для метаданных архива
+    ///
+    /// ```
+    /// use arithmetic_compression_wizard::compression_engine::{probe_model, EngineConfig};
+    ///
+    /// let choice = probe_model(&[0u8; 100], &EngineConfig::default());
+    /// assert_eq!(
+    ///     choice.describe(),
+    ///     "engine=arithmetic model=static-order0 order0=0.00 order1_gain=0.00 dictionary=0"
+    /// );
+    /// ```
+    pub fn describe(&self) -> String {
+        let registry = EngineRegistry::with_builtin_engines();
+        let engine = registry
+            .get(self.engine)
+            .map_or("custom", |engine| engine.name());
+        let model = match self.model {
+            ModelKind::StaticOrder0 => "static-order0".to_string(),
+            ModelKind::SharedOrder0 => "shared-order0".to_string(),
+            ModelKind::Adaptive { half_life } => format!("adaptive/{half_life}"),
+            ModelKind::HashedOrder2 { table_bits } => format!("hashed-order2/{table_bits}"),
+        };
+        format!(
+            "engine={engine} model={model} order0={:.2} order1_gain={:.2} dictionary={}",
+            self.order0_entropy, self.order1_gain, self.dictionary_savings
+        )
+    }
+
+    /// Самое длинное решение [`probe_model`]: по нему оценивается рост
+    /// архива
+    pub(crate) fn widest() -> Self {
+        Self {
+            engine: EngineKind::Arithmetic,
+            model: ModelKind::HashedOrder2 {
+                table_bits: MAX_CONTEXT_TABLE_BITS,
+            },
+            order0_entropy: 8.0,
+            order1_gain: 8.0,
+            dictionary_savings: u64::MAX,
+        }
+    }
+}
+
+/// Выбирает движок и модель по пробе начала и конца `data` 🔮
+///
+/// Словарь оценивается поиском слов с параметрами `config` (подсчет,
+/// запретный список, бюджет, границы слов). Вход не длиннее двух окон
+/// пробы изучается целиком.
+///
+/// ```
+/// use arithmetic_compression_wizard::compression_engine::{probe_model, EngineConfig};
+/// use arithmetic_compression_wizard::format::EngineKind;
+///
+/// let mut state = 7u32;
+/// let noise: Vec<u8> = (0..20_000)
+///     .map(|_| {
+///         state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+///         (state >> 16) as u8
+///     })
+///     .collect();
+/// assert_eq!(probe_model(&noise, &EngineConfig::default()).engine, EngineKind::Stored);
+/// ```
+pub fn probe_model(data: impl AsRef<[u8]>, config: &EngineConfig) -> ModelChoice {
+    let data = data.as_ref();
+    let sample = if data.len() <= 2 * PROBE_WINDOW_BYTES {
+        data.to_vec()
+    } else {
+        [
+            &data[..PROBE_WINDOW_BYTES],
+            &data[data.len() - PROBE_WINDOW_BYTES..],
+        ]
+        .concat()
+    };
+
+    let matrix = bigram_matrix(&sample);
+    let order0_entropy = matrix.order0_entropy();
+    let order1_gain = matrix.order1_gain();
+    let dictionary_savings: u64 = discover_scored_words(
+        &sample,
+        &config.word_counting,
+        &config.deny_list,
+        config.dictionary_budget_bytes,
+        &config.word_boundaries,
+    )
+    .iter()
+    .map(|&(_, savings)| savings.max(0) as u64)
+    .sum();
+
+    let context_savings = (order1_gain * sample.len() as f64 / 8.0) as u64;
+    let (engine, model) = if order0_entropy >= INCOMPRESSIBLE_BITS_PER_BYTE {
+        (EngineKind::Stored, ModelKind::StaticOrder0)
+    } else if data.len() >= MIN_CONTEXT_INPUT_BYTES
+        && order1_gain >= MIN_CONTEXT_GAIN
+        && context_savings > dictionary_savings
+    {
+        let model = ModelKind::HashedOrder2 {
+            table_bits: DEFAULT_CONTEXT_TABLE_BITS,
+        };
+        (EngineKind::Arithmetic, model)
+    } else {
+        (EngineKind::Arithmetic, ModelKind::StaticOrder0)
+    };
+
+    ModelChoice {
+        engine,
+        model,
+        order0_entropy,
+        order1_gain,
+        dictionary_savings,
+    }
+}
+
+/// Тесты авгурии модели 🎯
+#[cfg(test)]
+mod model_augury_tests {
+    use super::*;
+    use crate::format::read_metadata;
+    use crate::simple_api::{decompress_data, max_compressed_len, try_compress_with_config};
+
+    fn noise(len: usize) -> Vec<u8> {
+        let mut state = 0x9E37_79B9u32;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                (state >> 24) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_probe_picks_model_by_content() {
+        let config = EngineConfig::default();
+        assert_eq!(
+            probe_model(noise(50_000), &config).engine,
+            EngineKind::Stored
+        );
+
+        // Буквы без повторяющихся слов: помогает только контекст
+        let mut state = 1u32;
+        let syllables: Vec<u8> = (0..40_000)
+            .flat_map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                let consonant = b"bdfgklmnprstvz"[(state >> 16) as usize % 14];
+                let vowel = b"aeiou"[(state >> 24) as usize % 5];
+                [consonant, vowel]
+            })
+            .collect();
+        let choice = probe_model(&syllables, &config);
+        assert_eq!(
+            choice.model,
+            ModelKind::HashedOrder2 {
+                table_bits: DEFAULT_CONTEXT_TABLE_BITS
+            }
+        );
+        assert!(choice.order1_gain > MIN_CONTEXT_GAIN);
+
+        // Короткий вход не окупает прогрев контекстной модели
+        let short = probe_model(&syllables[..2000], &config);
+        assert_eq!(short.model, ModelKind::StaticOrder0);
+        assert_eq!(short.engine, EngineKind::Arithmetic);
+    }
+
+    #[test]
+    fn test_auto_model_records_decision_in_metadata() {
+        let config = EngineConfig::auto_model();
+        let mut data = b"the probe reads the head and the tail. ".repeat(400);
+        data.extend(noise(100_000));
+
+        let archive = try_compress_with_config(&data, &config).unwrap();
+        assert_eq!(decompress_data(&archive), data);
+        assert!(archive.len() <= max_compressed_len(data.len(), &config));
+        let metadata = read_metadata(&archive).unwrap();
+        assert_eq!(
+            metadata[AUTO_MODEL_METADATA_KEY],
+            probe_model(&data, &config).describe()
+        );
+
+        // Шум целиком: кадр хранения и граница длины с метаданными
+        let data = noise(3000);
+        let archive = try_compress_with_config(&data, &config).unwrap();
+        assert_eq!(decompress_data(&archive), data);
+        assert!(archive.len() <= max_compressed_len(data.len(), &config));
+        assert!(
+            read_metadata(&archive).unwrap()[AUTO_MODEL_METADATA_KEY].starts_with("engine=stored")
+        );
+
+        // Без автоматического выбора метаданных нет
+        let plain = try_compress_with_config(&data, &EngineConfig::default()).unwrap();
+        assert!(read_metadata(&plain).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,397 @@
+//! Смешение моделей: литералы и слова (экспериментально) 🧪
+//!
+//! Байтовая модель хорошо предсказывает сочетания букв («q» → «u»), а
+//! модель слов — продолжение знакомого слова и слово после слова. Ни одна
+//! не лучше другой везде, поэтому движок [`MixEngine`] кодирует байты
+//! побитно и на каждом бите смешивает обе оценки логистическим
+//! смесителем (CM-lite): вероятности переводятся в логиты, складываются с
+//! весами, а веса после каждого бита сдвигаются в сторону модели, которая
+//! ошиблась меньше. Отдельный набор весов — на каждую позицию бита в байте.
+//!
+//! - Литеральная модель: контекст — предыдущий байт и уже известные биты
+//!   текущего (таблица 256 × 256).
+//! - Модель слов: контекст — хеш текущего слова до этой позиции и
+//!   предыдущего слова (хешированная таблица из `2^WORD_TABLE_BITS` ячеек).
+//!
+//! Вся арифметика целочисленная, поэтому кодер и декодер на любой
+//! платформе получают одни и те же вероятности. Таблиц в архиве нет.
+//! Движок экспериментальный: параметры моделей могут поменяться вместе
+//! с кодом движка.
+//!
+//! Тело кадра: `[длина входа u64][длина u32][битовый поток]`.
+
+use super::engine_covenant::{CompressionEngine, EngineConfig, Frame};
+use crate::bit_wizardry::bit_manipulation_spells::CoderPrecision;
+use crate::bit_wizardry::entropy_coder::{ArithmeticDecoder, ArithmeticEncoder};
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::error::{WizardError, WizardResult};
+use crate::format::artifact_scroll::{check_body_header, ScrollCursor};
+use crate::format::container_runes::{ArchiveHeader, EngineKind, ModelKind};
+
+/// Размер хешированной таблицы модели слов, бит
+const WORD_TABLE_BITS: u32 = 20;
+
+/// Разрядность вероятностей: вероятность единицы из `1 << PROBABILITY_BITS`
+const PROBABILITY_BITS: u32 = 12;
+
+/// Масса вероятностей для кодера
+const PROBABILITY_TOTAL: u32 = 1 << PROBABILITY_BITS;
+
+/// Скорость обучения предсказателей бита (сдвиг)
+const PREDICTOR_RATE: u32 = 4;
+
+/// Скорость обучения смесителя
+const MIXER_RATE: i32 = 6;
+
+/// Предел модуля веса смесителя (64 в единицах 16 дробных бит)
+const WEIGHT_LIMIT: i32 = 1 << 22;
+
+/// Число смешиваемых моделей
+const MODEL_COUNT: usize = 2;
+
+/// Узлы логистической кривой через каждые 128 единиц логита
+const SQUASH_KNOTS: [i32; 33] = [
+    1, 2, 3, 6, 10, 16, 27, 45, 73, 120, 194, 310, 488, 747, 1101, 1546, 2047, 2549, 2994, 3348,
+    3607, 3785, 3901, 3975, 4024, 4050, 4068, 4079, 4085, 4089, 4092, 4093, 4094,
+];
+
+/// Логистическая функция: логит (±2047, 8 дробных бит) → вероятность (12 бит)
+fn squash(logit: i32) -> i32 {
+    if logit > 2047 {
+        return PROBABILITY_TOTAL as i32 - 1;
+    }
+    if logit < -2047 {
+        return 1;
+    }
+    let weight = logit & 127;
+    let knot = ((logit >> 7) + 16) as usize;
+    (SQUASH_KNOTS[knot] * (128 - weight) + SQUASH_KNOTS[knot + 1] * weight + 64) >> 7
+}
+
+/// Таблица обратной функции: вероятность (12 бит) → логит
+fn stretch_table() -> Vec<i16> {
+    let mut table = vec![2047i16; PROBABILITY_TOTAL as usize];
+    let mut next = 0;
+    for logit in -2047..=2047 {
+        let probability = squash(logit) as usize;
+        while next <= probability {
+            table[next] = logit as i16;
+            next += 1;
+        }
+    }
+    table
+}
+
+/// Смесь литеральной модели и модели слов 🧪
+#[derive(Debug, Clone)]
+struct BlendModel {
+    /// Вероятности единицы литеральной модели (16 бит)
+    literal: Vec<u16>,
+    /// Вероятности единицы модели слов (16 бит)
+    word: Vec<u16>,
+    /// Веса смесителя по позиции бита (16 дробных бит)
+    weights: [[i32; MODEL_COUNT]; 8],
+    /// Логиты моделей на текущем бите
+    inputs: [i32; MODEL_COUNT],
+    /// Ячейки моделей на текущем бите
+    cells: [usize; MODEL_COUNT],
+    /// Смешанная вероятность единицы на текущем бите
+    mixed: i32,
+    /// Логит по вероятности
+    stretch: Vec<i16>,
+    /// Предыдущий байт
+    previous_byte: u8,
+    /// Известные биты текущего байта с ведущей единицей (1..=255)
+    partial: u32,
+    /// Позиция бита в байте (0 — старший)
+    bit_position: usize,
+    /// Хеш текущего слова до этой позиции
+    word_hash: u32,
+    /// Хеш предыдущего слова
+    previous_word_hash: u32,
+}
+
+impl BlendModel {
+    /// Модель без истории: все вероятности — половина
+    fn new() -> Self {
+        let half = 1u16 << 15;
+        let mut model = Self {
+            literal: vec![half; 256 * 256],
+            word: vec![half; 1 << WORD_TABLE_BITS],
+            weights: [[1 << 15; MODEL_COUNT]; 8],
+            inputs: [0; MODEL_COUNT],
+            cells: [0; MODEL_COUNT],
+            mixed: PROBABILITY_TOTAL as i32 / 2,
+            stretch: stretch_table(),
+            previous_byte: 0,
+            partial: 1,
+            bit_position: 0,
+            word_hash: 0,
+            previous_word_hash: 0,
+        };
+        model.predict();
+        model
+    }
+
+    /// Вероятность единицы следующего бита (1..PROBABILITY_TOTAL)
+    fn probability(&self) -> u32 {
+        self.mixed as u32
+    }
+
+    /// Смешивает оценки моделей для текущего бита
+    fn predict(&mut self) {
+        let word_context = self
+            .word_hash
+            .wrapping_add(self.previous_word_hash.wrapping_mul(0x2F0B_3A49))
+            .wrapping_add(self.partial.wrapping_mul(0x9E37_79B1));
+        self.cells = [
+            (self.previous_byte as usize) << 8 | self.partial as usize,
+            (word_context.wrapping_mul(0x85EB_CA6B) >> (32 - WORD_TABLE_BITS)) as usize,
+        ];
+        let probabilities = [self.literal[self.cells[0]], self.word[self.cells[1]]];
+
+        let mut logit = 0i64;
+        for (model, probability) in probabilities.into_iter().enumerate() {
+            self.inputs[model] = self.stretch[(probability >> 4) as usize] as i32;
+            logit += self.weights[self.bit_position][model] as i64 * self.inputs[model] as i64;
+        }
+        let logit = (logit >> 16).clamp(-2047, 2047) as i32;
+        self.mixed = squash(logit).clamp(1, PROBABILITY_TOTAL as i32 - 1);
+    }
+
+    /// Учит модели и смеситель на закодированном бите и готовит следующий
+    fn update(&mut self, bit: u32) {
+        let error = ((bit << PROBABILITY_BITS) as i32 - self.mixed) * MIXER_RATE;
+        for (weight, input) in self.weights[self.bit_position].iter_mut().zip(self.inputs) {
+            *weight = (*weight + ((input * error) >> 10)).clamp(-WEIGHT_LIMIT, WEIGHT_LIMIT);
+        }
+        for (table, cell) in [&mut self.literal, &mut self.word]
+            .into_iter()
+            .zip(self.cells)
+        {
+            let probability = &mut table[cell];
+            if bit == 1 {
+                *probability += (u16::MAX - *probability) >> PREDICTOR_RATE;
+            } else {
+                *probability -= *probability >> PREDICTOR_RATE;
+            }
+        }
+
+        self.partial = self.partial << 1 | bit;
+        self.bit_position += 1;
+        if self.bit_position == 8 {
+            self.finish_byte((self.partial & 0xFF) as u8);
+        }
+        self.predict();
+    }
+
+    /// Сдвигает контексты после целого байта
+    fn finish_byte(&mut self, byte: u8) {
+        if byte.is_ascii_alphanumeric() || byte >= 0x80 {
+            self.word_hash = (self.word_hash ^ byte as u32).wrapping_mul(0x0100_0193);
+        } else if self.word_hash != 0 {
+            self.previous_word_hash = self.word_hash;
+            self.word_hash = 0;
+        }
+        self.previous_byte = byte;
+        self.partial = 1;
+        self.bit_position = 0;
+    }
+}
+
+/// Интервал бита: единица — `[0, p)`, ноль — `[p, total)`
+fn bit_interval(bit: u32, probability: u32) -> (u32, u32) {
+    if bit == 1 {
+        (0, probability)
+    } else {
+        (probability, PROBABILITY_TOTAL)
+    }
+}
+
+/// Движок смешения литеральной модели и модели слов (экспериментальный) 🧪
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MixEngine;
+
+impl CompressionEngine for MixEngine {
+    fn engine_kind(&self) -> EngineKind {
+        EngineKind::Mix
+    }
+
+    fn name(&self) -> &'static str {
+        "mix"
+    }
+
+    fn compress(&self, data: &[u8], _config: &EngineConfig) -> Frame {
+        let mut compressed_bit_stream = Vec::new();
+        let mut encoder =
+            ArithmeticEncoder::new(&mut compressed_bit_stream, CoderPrecision::Bits32);
+        let mut model = BlendModel::new();
+        for &byte in data {
+            for shift in (0..8).rev() {
+                let bit = (byte >> shift & 1) as u32;
+                let (low, high) = bit_interval(bit, model.probability());
+                encoder
+                    .encode(low, high, PROBABILITY_TOTAL)
+                    .expect("вероятности смесителя ограничены массой кодера");
+                model.update(bit);
+            }
+        }
+        encoder.finish();
+
+        let mut body = Vec::with_capacity(12 + compressed_bit_stream.len());
+        body.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        body.extend_from_slice(&(compressed_bit_stream.len() as u32).to_le_bytes());
+        body.extend_from_slice(&compressed_bit_stream);
+
+        Frame {
+            header: ArchiveHeader::current(ModelKind::StaticOrder0, None)
+                .with_engine(EngineKind::Mix),
+            body,
+        }
+    }
+
+    fn try_decompress(&self, frame: &Frame, options: &DecodeOptions) -> WizardResult<Vec<u8>> {
+        try_unweave_mix_body(&frame.header, &frame.body, options)
+    }
+}
+
+/// Проверяемая распаковка тела кадра смешения 🛡️
+pub(crate) fn try_unweave_mix_body(
+    header: &ArchiveHeader,
+    body: &[u8],
+    options: &DecodeOptions,
+) -> WizardResult<Vec<u8>> {
+    check_body_header(header, EngineKind::Mix)?;
+    if header.alphabet_map.is_some() {
+        return Err(WizardError::CorruptFrequencyTable(
+            "атлас алфавита не применяется к смешению моделей",
+        ));
+    }
+
+    let mut cursor = ScrollCursor::new(body, 0);
+    let restored_len = cursor.read_u64()?;
+    let stream_len = cursor.read_u32()? as usize;
+    let compressed_bit_stream = cursor.read_slice(stream_len)?;
+    if cursor.remaining() != 0 {
+        return Err(WizardError::CorruptPayload);
+    }
+    options.reserve_output(restored_len)?;
+
+    let mut restored = Vec::with_capacity(restored_len as usize);
+    let mut decoder = ArithmeticDecoder::new(compressed_bit_stream, CoderPrecision::Bits32);
+    let mut model = BlendModel::new();
+    for _ in 0..restored_len {
+        let mut byte = 0u8;
+        for _ in 0..8 {
+            let probability = model.probability();
+            let bit = u32::from(decoder.decode_target(PROBABILITY_TOTAL)? < probability);
+            let (low, high) = bit_interval(bit, probability);
+            decoder.consume(low, high, PROBABILITY_TOTAL)?;
+            byte = byte << 1 | bit as u8;
+            model.update(bit);
+        }
+        restored.push(byte);
+    }
+    Ok(restored)
+}
+
+/// Тесты смешения моделей 🎯
+#[cfg(test)]
+mod model_blend_tests {
+    use super::*;
+    use crate::simple_api::{compress_data, decompress_data, try_compress_with_config};
+
+    /// Проза с повторяющимися словами и сочетаниями букв
+    fn prose() -> Vec<u8> {
+        let sentences = [
+            "the apprentice copied the spell into the grimoire by candlelight. ",
+            "an owl watched the tower while the wizard brewed a quiet potion. ",
+            "every scroll in the archive was sorted, sealed and numbered. ",
+            "the grimoire remembered each spell the apprentice ever copied. ",
+        ];
+        (0..160)
+            .map(|index| sentences[(index * 5 + index / 4) % sentences.len()])
+            .collect::<String>()
+            .into_bytes()
+    }
+
+    /// Идеальная длина кода бита с вероятностью единицы `probability`
+    fn bit_cost(bit: u32, probability: u32) -> f64 {
+        let probability = probability as f64 / PROBABILITY_TOTAL as f64;
+        -(if bit == 1 {
+            probability
+        } else {
+            1.0 - probability
+        })
+        .log2()
+    }
+
+    #[test]
+    fn test_mix_beats_each_model_alone() {
+        let data = prose();
+        let mut model = BlendModel::new();
+        let mut costs = [0.0f64; 3];
+        for &byte in &data {
+            for shift in (0..8).rev() {
+                let bit = (byte >> shift & 1) as u32;
+                for (cost, input) in costs.iter_mut().zip(model.inputs) {
+                    *cost += bit_cost(bit, squash(input).clamp(1, 4095) as u32);
+                }
+                costs[2] += bit_cost(bit, model.probability());
+                model.update(bit);
+            }
+        }
+        let [literal, word, mixed] = costs;
+        assert!(
+            mixed < literal && mixed < word,
+            "{mixed} / {literal} / {word}"
+        );
+
+        let config = EngineConfig::default().with_engine(EngineKind::Mix);
+        let archive = try_compress_with_config(&data, &config).unwrap();
+        assert_eq!(decompress_data(&archive), data);
+        assert!(archive.len() < compress_data(&data).len());
+        assert!((archive.len() as f64) < mixed / 8.0 + 64.0);
+    }
+
+    #[test]
+    fn test_any_bytes_roundtrip() {
+        let all_bytes: Vec<u8> = (0..=255).rev().collect();
+        for data in [&b""[..], b"a", b"\xFF\x00\x80", &all_bytes] {
+            let frame = MixEngine.compress(data, &EngineConfig::default());
+            assert_eq!(MixEngine.decompress(&frame), data);
+        }
+        assert_eq!(squash(0), 2047);
+        let stretch = stretch_table();
+        for probability in [1, 100, 2048, 4000, 4095] {
+            let roundtrip = squash(stretch[probability] as i32);
+            assert!(
+                (roundtrip - probability as i32).abs() <= 40,
+                "{probability}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_damaged_frame_is_rejected() {
+        let frame = MixEngine.compress(&prose(), &EngineConfig::default());
+        let options = DecodeOptions::default();
+        assert!(matches!(
+            MixEngine.try_decompress(&frame, &options.clone().with_max_output_bytes(1024)),
+            Err(WizardError::LimitExceeded { .. })
+        ));
+
+        let mut trailing = frame.clone();
+        trailing.body.push(0);
+        assert_eq!(
+            MixEngine.try_decompress(&trailing, &options),
+            Err(WizardError::CorruptPayload)
+        );
+        let mut truncated = frame;
+        truncated.body.truncate(10);
+        assert!(matches!(
+            MixEngine.try_decompress(&truncated, &options),
+            Err(WizardError::Truncated { .. })
+        ));
+    }
+}
@@ -0,0 +1,553 @@
+//! Курьер модели: обмен таблицей частот с другими инструментами 📨
+//!
+//! Модель [`CompressorHandle`] — словарь и частоты всего алфавита — можно
+//! обучить не только в Rust: распределение посчитает задание анализа на
+//! Python, а сжимать по нему будет сервис на Rust. Курьер переносит
+//! модель в обе стороны в JSON:
+//!
+//! ```json
+//! {"format":"acw-model","version":1,"dictionary_id":"9f3c...",
+//!  "words":["user","status"],"frequencies":[1,1,...,812,40]}
+//! ```
+//!
+//! - `words` — словарь, `n` строк;
+//! - `frequencies` — `256 + 2n` неотрицательных чисел: байты `0..=255`,
+//!   затем слово `i` (символ `256 + i`), затем слово `i` с пробелом после
+//!   него (символ `256 + n + i`);
+//! - `dictionary_id` — 16 шестнадцатеричных цифр; необязателен при импорте,
+//!   а если указан, сверяется с моделью.
+//!
+//! Частоты внешнего распределения могут быть счетчиками или вероятностями.
+//! Целые счетчики с суммой не больше предела массы общей модели
+//! сохраняются как есть (нули становятся единицами: любое сообщение должно
+//! кодироваться); иначе распределение масштабируется к пределу. Поэтому
+//! экспорт и обратный импорт дают ту же модель и тот же идентификатор.
+//! Незнакомые поля пропускаются.
+//!
+//! Двоичная схема — файл словаря
+//! [`to_dictionary_bytes`](CompressorHandle::to_dictionary_bytes), описанный
+//! в [`compressor_handle`](super::compressor_handle). Его идентификатор —
+//! FNV-1a 64 над `[число слов u32][(длина u32, байты) каждого слова]
+//! [(символ u32, частота u64) каждого символа]`.
+
+use super::compression_conjurer::alphabet_size;
+use super::compressor_handle::{CompressorHandle, HANDLE_MODEL_MASS_LIMIT};
+use crate::bit_wizardry::bit_manipulation_spells::CoderPrecision;
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::error::{LimitKind, WizardError, WizardResult};
+use crate::statistics::report_scribe::json_string;
+use std::fmt::Write;
+
+/// Значение поля `format` JSON модели
+pub const MODEL_JSON_FORMAT: &str = "acw-model";
+
+/// Версия схемы JSON модели
+pub const MODEL_JSON_VERSION: u32 = 1;
+
+/// Наибольшая вложенность JSON, которую разбирает курьер
+const MAX_JSON_DEPTH: usize = 16;
+
+impl CompressorHandle {
+    /// Модель в JSON для других инструментов 📨
+    ///
+    /// ```
+    /// use arithmetic_compression_wizard::compression_engine::CompressorHandle;
+    ///
+    /// let handle = CompressorHandle::train(b"status active status blocked status active");
+    /// let json = handle.to_model_json();
+    /// assert!(json.starts_with("{\"format\":\"acw-model\",\"version\":1,"));
+    ///
+    /// // Тот же идентификатор — те же архивы
+    /// let imported = CompressorHandle::from_model_json(&json).unwrap();
+    /// assert_eq!(imported.dictionary_id(), handle.dictionary_id());
+    /// ```
+    pub fn to_model_json(&self) -> String {
+        let words: Vec<String> = self
+            .word_grimoire()
+            .iter()
+            .map(|word| json_string(word))
+            .collect();
+        let frequencies: Vec<String> = self
+            .symbol_frequencies()
+            .iter()
+            .map(u64::to_string)
+            .collect();
+
+        let mut json = String::new();
+        let _ = write!(
+            json,
+            "{{\"format\":\"{MODEL_JSON_FORMAT}\",\"version\":{MODEL_JSON_VERSION},\
+             \"dictionary_id\":\"{:016x}\",\"words\":[{}],\"frequencies\":[{}]}}",
+            self.dictionary_id(),
+            words.join(","),
+            frequencies.join(",")
+        );
+        json
+    }
+
+    /// Модель из JSON [`to_model_json`](Self::to_model_json) или внешнего
+    /// инструмента 📨
+    ///
+    /// Нарушение синтаксиса или схемы дает [`WizardError::CorruptModelJson`],
+    /// чужой `format` — [`WizardError::UnrecognizedHeader`], новая версия —
+    /// [`WizardError::UnsupportedVersion`]; ошибки частот — как у
+    /// [`from_distribution`](Self::from_distribution).
+    pub fn from_model_json(json: &str) -> WizardResult<Self> {
+        let mut reader = JsonReader {
+            bytes: json.as_bytes(),
+            position: 0,
+        };
+        let document = reader.read_value(0)?;
+        reader.skip_whitespace();
+        if reader.position != reader.bytes.len() {
+            return Err(WizardError::CorruptModelJson(
+                "лишние символы после объекта",
+            ));
+        }
+        let JsonValue::Object(fields) = document else {
+            return Err(WizardError::CorruptModelJson("ожидался объект"));
+        };
+        let field = |name: &str| {
+            fields
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value)
+        };
+
+        if field("format") != Some(&JsonValue::Text(MODEL_JSON_FORMAT.to_string())) {
+            return Err(WizardError::UnrecognizedHeader);
+        }
+        match field("version") {
+            Some(&JsonValue::Number(version)) if version == MODEL_JSON_VERSION as f64 => {}
+            Some(&JsonValue::Number(version)) if version.fract() == 0.0 && version > 0.0 => {
+                return Err(WizardError::UnsupportedVersion(version.min(255.0) as u8));
+            }
+            _ => return Err(WizardError::CorruptModelJson("нет версии схемы")),
+        }
+
+        let Some(JsonValue::Array(words)) = field("words") else {
+            return Err(WizardError::CorruptModelJson("нет массива words"));
+        };
+        let word_grimoire = words
+            .iter()
+            .map(|word| match word {
+                JsonValue::Text(word) => Ok(word.clone()),
+                _ => Err(WizardError::CorruptModelJson("слово не строка")),
+            })
+            .collect::<WizardResult<Vec<String>>>()?;
+
+        let Some(JsonValue::Array(frequencies)) = field("frequencies") else {
+            return Err(WizardError::CorruptModelJson("нет массива frequencies"));
+        };
+        let weights = frequencies
+            .iter()
+            .map(|frequency| match *frequency {
+                JsonValue::Number(weight) => Ok(weight),
+                _ => Err(WizardError::CorruptModelJson("частота не число")),
+            })
+            .collect::<WizardResult<Vec<f64>>>()?;
+
+        let handle = Self::from_distribution(word_grimoire, &weights)?;
+        match field("dictionary_id") {
+            None | Some(JsonValue::Null) => {}
+            Some(JsonValue::Text(hex)) => {
+                let stored_id = u64::from_str_radix(hex, 16)
+                    .map_err(|_| WizardError::CorruptModelJson("dictionary_id не hex"))?;
+                if stored_id != handle.dictionary_id() {
+                    return Err(WizardError::CorruptFrequencyTable(
+                        "идентификатор словаря не совпадает с содержимым",
+                    ));
+                }
+            }
+            Some(_) => return Err(WizardError::CorruptModelJson("dictionary_id не строка")),
+        }
+        Ok(handle)
+    }
+
+    /// Модель по внешнему распределению символов алфавита
+    ///
+    /// `weights` — `256 + 2n` неотрицательных весов для словаря из `n`
+    /// слов в порядке символов, как в JSON модели. Другая длина,
+    /// отрицательный или нечисловой вес дают
+    /// [`WizardError::CorruptFrequencyTable`], словарь больше лимитов
+    /// [`DecodeOptions::default`] — [`WizardError::LimitExceeded`].
+    ///
+    /// ```
+    /// use arithmetic_compression_wizard::compression_engine::CompressorHandle;
+    ///
+    /// // Вероятности, посчитанные вне библиотеки: пробел и строчные буквы
+    /// let weights: Vec<f64> = (0..256u32)
+    ///     .map(|byte| match byte as u8 {
+    ///         b' ' => 0.15,
+    ///         b'a'..=b'z' => 0.03,
+    ///         _ => 0.0,
+    ///     })
+    ///     .collect();
+    /// let handle = CompressorHandle::from_distribution(Vec::new(), &weights).unwrap();
+    /// let artifact = handle.compress(b"shared models travel well");
+    /// assert_eq!(handle.decompress(&artifact).unwrap(), b"shared models travel well");
+    /// ```
+    pub fn from_distribution(word_grimoire: Vec<String>, weights: &[f64]) -> WizardResult<Self> {
+        let options = DecodeOptions::default();
+        options.check_dict_entries(word_grimoire.len())?;
+        word_grimoire.iter().try_fold(0, |grimoire_bytes, word| {
+            options.check_word_bytes(grimoire_bytes, word.len())
+        })?;
+        if weights.len() != alphabet_size(word_grimoire.len()) {
+            return Err(WizardError::CorruptFrequencyTable(
+                "размер таблицы не совпадает со словарем",
+            ));
+        }
+        if weights
+            .iter()
+            .any(|&weight| !weight.is_finite() || weight < 0.0)
+        {
+            return Err(WizardError::CorruptFrequencyTable(
+                "отрицательная или нечисловая частота",
+            ));
+        }
+
+        // Целые счетчики в пределах массы сохраняются точно
+        let limit = HANDLE_MODEL_MASS_LIMIT as f64;
+        let exact = weights.iter().all(|weight| weight.fract() == 0.0)
+            && weights.iter().map(|weight| weight.max(1.0)).sum::<f64>() <= limit;
+        let total: f64 = weights.iter().sum();
+        let frequencies: Vec<u64> = weights
+            .iter()
+            .map(|&weight| {
+                let frequency = if exact || total == 0.0 {
+                    weight
+                } else {
+                    (weight * limit / total).round()
+                };
+                (frequency as u64).max(1)
+            })
+            .collect();
+
+        let mass: u64 = frequencies.iter().sum();
+        let allowed = CoderPrecision::default().max_total_frequency();
+        if mass > allowed {
+            return Err(WizardError::LimitExceeded {
+                kind: LimitKind::FrequencyMass,
+                requested: mass,
+                allowed,
+            });
+        }
+        Ok(Self::from_smoothed_frequencies(word_grimoire, &frequencies))
+    }
+}
+
+/// Значение JSON
+#[derive(Debug, Clone, PartialEq)]
+enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    Text(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+/// Разбор JSON без внешних зависимостей
+struct JsonReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl JsonReader<'_> {
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.position).copied()
+    }
+
+    /// Пропускает пробелы и съедает `expected`, если он следующий
+    fn eat(&mut self, expected: u8) -> bool {
+        self.skip_whitespace();
+        let found = self.peek() == Some(expected);
+        self.position += found as usize;
+        found
+    }
+
+    fn read_value(&mut self, depth: usize) -> WizardResult<JsonValue> {
+        if depth > MAX_JSON_DEPTH {
+            return Err(WizardError::CorruptModelJson(
+                "слишком глубокая вложенность",
+            ));
+        }
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'{') => self.read_object(depth),
+            Some(b'[') => self.read_array(depth),
+            Some(b'"') => self.read_string().map(JsonValue::Text),
+            Some(b'-' | b'0'..=b'9') => self.read_number(),
+            _ => self.read_literal(),
+        }
+    }
+
+    fn read_object(&mut self, depth: usize) -> WizardResult<JsonValue> {
+        self.position += 1;
+        let mut fields = Vec::new();
+        if self.eat(b'}') {
+            return Ok(JsonValue::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(b'"') {
+                return Err(WizardError::CorruptModelJson("ключ не строка"));
+            }
+            let key = self.read_string()?;
+            if !self.eat(b':') {
+                return Err(WizardError::CorruptModelJson("нет двоеточия после ключа"));
+            }
+            fields.push((key, self.read_value(depth + 1)?));
+            if self.eat(b'}') {
+                return Ok(JsonValue::Object(fields));
+            }
+            if !self.eat(b',') {
+                return Err(WizardError::CorruptModelJson("объект не закрыт"));
+            }
+        }
+    }
+
+    fn read_array(&mut self, depth: usize) -> WizardResult<JsonValue> {
+        self.position += 1;
+        let mut items = Vec::new();
+        if self.eat(b']') {
+            return Ok(JsonValue::Array(items));
+        }
+        loop {
+            items.push(self.read_value(depth + 1)?);
+            if self.eat(b']') {
+                return Ok(JsonValue::Array(items));
+            }
+            if !self.eat(b',') {
+                return Err(WizardError::CorruptModelJson("массив не закрыт"));
+            }
+        }
+    }
+
+    fn read_string(&mut self) -> WizardResult<String> {
+        self.position += 1;
+        let mut text = String::new();
+        loop {
+            let Some(byte) = self.peek() else {
+                return Err(WizardError::CorruptModelJson("строка не закрыта"));
+            };
+            match byte {
+                b'"' => {
+                    self.position += 1;
+                    return Ok(text);
+                }
+                b'\\' => {
+                    self.position += 1;
+                    let Some(kind) = self.peek() else {
+                        return Err(WizardError::CorruptModelJson("строка не закрыта"));
+                    };
+                    self.position += 1;
+                    text.push(match kind {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.read_unicode_escape()?,
+                        _ => {
+                            return Err(WizardError::CorruptModelJson("неизвестное экранирование"))
+                        }
+                    });
+                }
+                control if control < 0x20 => {
+                    return Err(WizardError::CorruptModelJson("управляющий символ в строке"));
+                }
+                _ => {
+                    // Байты до следующей кавычки или экранирования — целые символы UTF-8
+                    let run_end = self.bytes[self.position..]
+                        .iter()
+                        .position(|&byte| byte == b'"' || byte == b'\\' || byte < 0x20)
+                        .map_or(self.bytes.len(), |offset| self.position + offset);
+                    let run = std::str::from_utf8(&self.bytes[self.position..run_end])
+                        .map_err(|_| WizardError::CorruptModelJson("строка не UTF-8"))?;
+                    text.push_str(run);
+                    self.position = run_end;
+                }
+            }
+        }
+    }
+
+    /// Символ `\uXXXX` (с суррогатной парой) после съеденного `\u`
+    fn read_unicode_escape(&mut self) -> WizardResult<char> {
+        let high = self.read_hex4()?;
+        let code_point = match high {
+            0xD800..=0xDBFF => {
+                if self.bytes.get(self.position..self.position + 2) != Some(b"\\u") {
+                    return Err(WizardError::CorruptModelJson("суррогат без пары"));
+                }
+                self.position += 2;
+                let low = self.read_hex4()?;
+                if !(0xDC00..=0xDFFF).contains(&low) {
+                    return Err(WizardError::CorruptModelJson("суррогат без пары"));
+                }
+                0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+            }
+            _ => high,
+        };
+        char::from_u32(code_point).ok_or(WizardError::CorruptModelJson("суррогат без пары"))
+    }
+
+    fn read_hex4(&mut self) -> WizardResult<u32> {
+        let digits = self
+            .bytes
+            .get(self.position..self.position + 4)
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+            .ok_or(WizardError::CorruptModelJson("неверное экранирование \\u"))?;
+        self.position += 4;
+        Ok(digits)
+    }
+
+    fn read_number(&mut self) -> WizardResult<JsonValue> {
+        let start = self.position;
+        while matches!(
+            self.peek(),
+            Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')
+        ) {
+            self.position += 1;
+        }
+        std::str::from_utf8(&self.bytes[start..self.position])
+            .ok()
+            .and_then(|number| number.parse().ok())
+            .map(JsonValue::Number)
+            .ok_or(WizardError::CorruptModelJson("неверное число"))
+    }
+
+    fn read_literal(&mut self) -> WizardResult<JsonValue> {
+        for (literal, value) in [
+            (&b"null"[..], JsonValue::Null),
+            (b"true", JsonValue::Bool(true)),
+            (b"false", JsonValue::Bool(false)),
+        ] {
+            if self.bytes[self.position..].starts_with(literal) {
+                self.position += literal.len();
+                return Ok(value);
+            }
+        }
+        Err(WizardError::CorruptModelJson("неожиданный символ"))
+    }
+}
+
+/// Тесты курьера модели 🎯
+#[cfg(test)]
+mod model_courier_tests {
+    use super::*;
+
+    const TRAINING_SAMPLE: &str = "status active user alice status blocked user bob \
+        status active user carol status active user dave";
+
+    #[test]
+    fn test_json_roundtrip_keeps_model_and_archives() {
+        let handle = CompressorHandle::train(TRAINING_SAMPLE.repeat(8));
+        assert!(!handle.word_grimoire().is_empty());
+        let json = handle.to_model_json();
+        let imported = CompressorHandle::from_model_json(&json).unwrap();
+        assert_eq!(imported.dictionary_id(), handle.dictionary_id());
+        assert_eq!(imported.word_grimoire(), handle.word_grimoire());
+        assert_eq!(imported.symbol_frequencies(), handle.symbol_frequencies());
+
+        // Архив одной стороны распаковывает другая
+        let artifact = handle.compress("status blocked user erin");
+        assert_eq!(
+            imported.decompress(&artifact).unwrap(),
+            b"status blocked user erin"
+        );
+
+        // Экранирование слов и пробелы вокруг значений
+        let tricky = CompressorHandle::train_with_grimoire(
+            "say \"quoted\" path\\to\tcafé 🦀",
+            vec!["\"quoted\"".to_string(), "café 🦀".to_string()],
+        );
+        let spaced = tricky
+            .to_model_json()
+            .replace(',', " ,\n ")
+            .replace(':', " : ");
+        let imported = CompressorHandle::from_model_json(&spaced).unwrap();
+        assert_eq!(imported.word_grimoire(), tricky.word_grimoire());
+        assert_eq!(imported.dictionary_id(), tricky.dictionary_id());
+    }
+
+    #[test]
+    fn test_external_distribution_is_imported() {
+        // Задание на Python выгрузило вероятности без идентификатора
+        let mut frequencies = vec!["0".to_string(); alphabet_size(1)];
+        for byte in b"abc " {
+            frequencies[*byte as usize] = "0.2".to_string();
+        }
+        frequencies[256] = "1.5e-1".to_string();
+        frequencies[257] = "0.05".to_string();
+        let json = format!(
+            "{{\"version\":1,\"format\":\"acw-model\",\"source\":{{\"tool\":\"pandas\"}},\
+             \"words\":[\"cab\"],\"frequencies\":[{}]}}",
+            frequencies.join(",")
+        );
+        let handle = CompressorHandle::from_model_json(&json).unwrap();
+        let frequencies = handle.symbol_frequencies();
+        assert_eq!(frequencies[0], 1);
+        assert!(frequencies[b'a' as usize].abs_diff(4 * frequencies[257]) <= 4);
+        assert!(frequencies.iter().sum::<u64>() <= HANDLE_MODEL_MASS_LIMIT + 520);
+        let artifact = handle.compress("cab abc cab \x00");
+        assert_eq!(handle.decompress(&artifact).unwrap(), b"cab abc cab \x00");
+
+        // Целые счетчики сохраняются точно
+        let counts: Vec<f64> = (0..256).map(|byte| (byte % 7) as f64).collect();
+        let exact = CompressorHandle::from_distribution(Vec::new(), &counts).unwrap();
+        assert_eq!(exact.symbol_frequencies()[6], 6);
+        assert_eq!(exact.symbol_frequencies()[7], 1);
+    }
+
+    #[test]
+    fn test_damaged_model_json_is_rejected() {
+        let json = CompressorHandle::train(TRAINING_SAMPLE.repeat(8)).to_model_json();
+        let corrupt = |text: &str| {
+            matches!(
+                CompressorHandle::from_model_json(text),
+                Err(WizardError::CorruptModelJson(_))
+            )
+        };
+        assert!(corrupt(""));
+        assert!(corrupt(&json[..json.len() - 1]));
+        assert!(corrupt(&format!("{json} {{}}")));
+        assert!(corrupt("[1, 2]"));
+        assert!(corrupt(&json.replace("\"words\":[", "\"words\":[7,")));
+        assert!(corrupt(&"[".repeat(100)));
+        assert!(corrupt(
+            r#"{"format":"acw-model","version":1,"words":["\ud800"]}"#
+        ));
+
+        assert_eq!(
+            CompressorHandle::from_model_json(&json.replace("acw-model", "other")).unwrap_err(),
+            WizardError::UnrecognizedHeader
+        );
+        assert_eq!(
+            CompressorHandle::from_model_json(&json.replace("\"version\":1", "\"version\":2"))
+                .unwrap_err(),
+            WizardError::UnsupportedVersion(2)
+        );
+        for damaged in [
+            json.replace("\"frequencies\":[", "\"frequencies\":[-1,"),
+            json.replace("\"frequencies\":[", "\"frequencies\":[1,"),
+            json.replace("\"frequencies\":[1", "\"frequencies\":[2"),
+        ] {
+            assert!(matches!(
+                CompressorHandle::from_model_json(&damaged),
+                Err(WizardError::CorruptFrequencyTable(_))
+            ));
+        }
+    }
+}
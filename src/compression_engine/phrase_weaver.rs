@@ -0,0 +1,201 @@
+//! Ткач фраз 🧵
+//!
+//! Второй уровень словаря: частые цепочки из 2–3 слов (например,
+//! «the quick brown») заменяются одним символом фразы. Проза с
+//! повторяющимися предложениями выигрывает заметно больше, чем от
+//! замены отдельных слов.
+//!
+//! Фразы ищутся в уже символизированном потоке, поэтому пробелы между
+//! словами входят во фразу через символы «слово + пробел».
+
+use super::compression_conjurer::{interpret_symbol, phrase_symbol, SymbolMeaning};
+use std::collections::HashMap;
+
+/// Самая длинная фраза (в словах)
+const MAX_PHRASE_WORDS: usize = 3;
+
+/// Сколько фраз может попасть в архив
+const MAX_PHRASE_ENTRIES: usize = 32;
+
+/// Минимальное число повторов фразы
+const MIN_PHRASE_OCCURRENCES: u64 = 3;
+
+/// Находит выгодные фразы и заменяет их в потоке символов
+///
+/// Возвращает новый поток и таблицу фраз. Фразы, которые после жадной
+/// замены не встретились ни разу, в таблицу не попадают.
+pub(crate) fn weave_phrase_enchantments(
+    symbolic_incantations: Vec<u32>,
+    grimoire_len: usize,
+) -> (Vec<u32>, Vec<Vec<u32>>) {
+    let candidates = discover_phrase_candidates(&symbolic_incantations, grimoire_len);
+    if candidates.is_empty() {
+        return (symbolic_incantations, Vec::new());
+    }
+
+    let woven = apply_phrases(&symbolic_incantations, &candidates, grimoire_len);
+    prune_unused_phrases(woven, candidates, grimoire_len)
+}
+
+/// Является ли символ ссылкой на слово
+fn is_word_symbol(symbol: u32, grimoire_len: usize) -> bool {
+    matches!(
+        interpret_symbol(symbol, grimoire_len),
+        SymbolMeaning::Word { .. }
+    )
+}
+
+/// Считает цепочки слов и отбирает выгодные
+///
+/// Выгода в символах: `(длина − 1) × повторы`, минус место фразы
+/// в заголовке (байт длины и 4 байта на каждое слово).
+fn discover_phrase_candidates(symbolic_incantations: &[u32], grimoire_len: usize) -> Vec<Vec<u32>> {
+    let mut chain_almanac: HashMap<&[u32], u64> = HashMap::new();
+
+    for phrase_len in 2..=MAX_PHRASE_WORDS {
+        for window in symbolic_incantations.windows(phrase_len) {
+            if window
+                .iter()
+                .all(|&symbol| is_word_symbol(symbol, grimoire_len))
+            {
+                *chain_almanac.entry(window).or_insert(0) += 1;
+            }
+        }
+    }
+
+    let mut profitable_chains: Vec<(&[u32], i64)> = chain_almanac
+        .into_iter()
+        .filter(|&(_, occurrences)| occurrences >= MIN_PHRASE_OCCURRENCES)
+        .map(|(chain, occurrences)| {
+            let header_cost = 1 + 4 * chain.len() as i64;
+            (
+                chain,
+                (chain.len() as i64 - 1) * occurrences as i64 - header_cost,
+            )
+        })
+        .filter(|&(_, gain)| gain > 0)
+        .collect();
+
+    // Детерминированный порядок: выгода, затем сама цепочка
+    profitable_chains.sort_by(|(chain_a, gain_a), (chain_b, gain_b)| {
+        gain_b.cmp(gain_a).then_with(|| chain_a.cmp(chain_b))
+    });
+    profitable_chains.truncate(MAX_PHRASE_ENTRIES);
+
+    profitable_chains
+        .into_iter()
+        .map(|(chain, _)| chain.to_vec())
+        .collect()
+}
+
+/// Жадно заменяет фразы слева направо, длинные фразы в приоритете
+fn apply_phrases(
+    symbolic_incantations: &[u32],
+    phrases: &[Vec<u32>],
+    grimoire_len: usize,
+) -> Vec<u32> {
+    let mut by_length: Vec<usize> = (0..phrases.len()).collect();
+    by_length.sort_by_key(|&index| std::cmp::Reverse(phrases[index].len()));
+
+    let mut woven = Vec::with_capacity(symbolic_incantations.len());
+    let mut position = 0;
+    while position < symbolic_incantations.len() {
+        let remaining = &symbolic_incantations[position..];
+        match by_length
+            .iter()
+            .find(|&&index| remaining.starts_with(&phrases[index]))
+        {
+            Some(&index) => {
+                woven.push(phrase_symbol(index, grimoire_len));
+                position += phrases[index].len();
+            }
+            None => {
+                woven.push(remaining[0]);
+                position += 1;
+            }
+        }
+    }
+    woven
+}
+
+/// Удаляет фразы без единой замены и перенумеровывает символы фраз
+fn prune_unused_phrases(
+    woven: Vec<u32>,
+    phrases: Vec<Vec<u32>>,
+    grimoire_len: usize,
+) -> (Vec<u32>, Vec<Vec<u32>>) {
+    let mut phrase_used = vec![false; phrases.len()];
+    for &symbol in &woven {
+        if let SymbolMeaning::Phrase { index } = interpret_symbol(symbol, grimoire_len) {
+            phrase_used[index] = true;
+        }
+    }
+
+    let mut new_index = vec![0usize; phrases.len()];
+    let mut kept_phrases = Vec::new();
+    for (old_index, phrase) in phrases.into_iter().enumerate() {
+        if phrase_used[old_index] {
+            new_index[old_index] = kept_phrases.len();
+            kept_phrases.push(phrase);
+        }
+    }
+
+    let remapped = woven
+        .into_iter()
+        .map(|symbol| match interpret_symbol(symbol, grimoire_len) {
+            SymbolMeaning::Phrase { index } => phrase_symbol(new_index[index], grimoire_len),
+            _ => symbol,
+        })
+        .collect();
+
+    (remapped, kept_phrases)
+}
+
+/// Тесты ткача фраз 🎯
+#[cfg(test)]
+mod phrase_weaver_tests {
+    use super::*;
+    use crate::compression_engine::compression_conjurer::{
+        transform_manuscript_to_symbols, word_symbol,
+    };
+
+    fn sample_symbols() -> (Vec<u32>, usize) {
+        let grimoire = ["the", "quick", "brown"].map(String::from).to_vec();
+        let text = "the quick brown cat. ".repeat(10);
+        (
+            transform_manuscript_to_symbols(text.as_bytes(), &grimoire),
+            grimoire.len(),
+        )
+    }
+
+    #[test]
+    fn test_repeated_chain_becomes_phrase() {
+        let (symbols, grimoire_len) = sample_symbols();
+        let (woven, phrases) = weave_phrase_enchantments(symbols.clone(), grimoire_len);
+
+        let the_quick_brown = vec![
+            word_symbol(0, grimoire_len, true),
+            word_symbol(1, grimoire_len, true),
+            word_symbol(2, grimoire_len, true),
+        ];
+        assert_eq!(phrases, vec![the_quick_brown]);
+        assert_eq!(
+            woven
+                .iter()
+                .filter(|&&symbol| symbol == phrase_symbol(0, grimoire_len))
+                .count(),
+            10
+        );
+        assert_eq!(woven.len(), symbols.len() - 10 * 2);
+    }
+
+    #[test]
+    fn test_rare_chains_are_ignored() {
+        let grimoire = ["magic", "wand"].map(String::from).to_vec();
+        let symbols = transform_manuscript_to_symbols(b"magic wand and magic wand", &grimoire);
+
+        let (woven, phrases) = weave_phrase_enchantments(symbols.clone(), grimoire.len());
+        assert!(phrases.is_empty());
+        assert_eq!(woven, symbols);
+    }
+}
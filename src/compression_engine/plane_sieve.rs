@@ -0,0 +1,192 @@
+//! Сито битовых плоскостей 🪜
+//!
+//! В битовых картах, фильтрах Блума и булевых столбцах смысл несут
+//! отдельные биты, а не байты: байтовая модель видит сотни редких
+//! значений и платит за каждое строкой таблицы частот. Стадия
+//! [`PipelineStage::BitPlanes`](super::PipelineStage::BitPlanes) делит
+//! байты на восемь плоскостей — плоскость `k` состоит из битов `k` всех
+//! байтов — и кодирует каждую своей двоичной адаптивной моделью. Таблицы
+//! частот не пишутся, а модель плоскости подстраивается под участки
+//! плотных и пустых битов.
+//!
+//! Тело кадра: `[длина входа u64][длина u32][битовый поток]`; в потоке
+//! подряд идут плоскости от младшей к старшей.
+
+use super::adaptive_tide::AgingModel;
+use super::engine_covenant::{CompressionEngine, EngineConfig, Frame};
+use crate::bit_wizardry::bit_manipulation_spells::CoderPrecision;
+use crate::bit_wizardry::entropy_coder::{ArithmeticDecoder, ArithmeticEncoder};
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::error::{WizardError, WizardResult};
+use crate::format::artifact_scroll::{check_body_header, ScrollCursor};
+use crate::format::container_runes::{ArchiveHeader, EngineKind, ModelKind};
+
+/// Число плоскостей байта
+const PLANE_COUNT: u32 = 8;
+
+/// Период полураспада модели плоскости
+///
+/// Короткий период быстро переключается между пустыми и плотными
+/// участками; пустая плоскость все равно стоит доли бита на байт.
+const PLANE_HALF_LIFE: u32 = 1024;
+
+/// Движок битовых плоскостей 🪜
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BitPlaneEngine;
+
+impl CompressionEngine for BitPlaneEngine {
+    fn engine_kind(&self) -> EngineKind {
+        EngineKind::BitPlanes
+    }
+
+    fn name(&self) -> &'static str {
+        "bit-planes"
+    }
+
+    fn compress(&self, data: &[u8], _config: &EngineConfig) -> Frame {
+        let mut compressed_bit_stream = Vec::new();
+        let mut encoder =
+            ArithmeticEncoder::new(&mut compressed_bit_stream, CoderPrecision::Bits32);
+        for plane in 0..PLANE_COUNT {
+            let mut model = plane_model();
+            for &byte in data {
+                let bit = ((byte >> plane) & 1) as usize;
+                let (low, high) = model.interval(bit);
+                encoder
+                    .encode(low, high, model.total())
+                    .expect("интервалы двоичной модели корректны");
+                model.observe(bit);
+            }
+        }
+        encoder.finish();
+
+        let mut body = Vec::with_capacity(12 + compressed_bit_stream.len());
+        body.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        body.extend_from_slice(&(compressed_bit_stream.len() as u32).to_le_bytes());
+        body.extend_from_slice(&compressed_bit_stream);
+
+        Frame {
+            header: ArchiveHeader::current(ModelKind::StaticOrder0, None)
+                .with_engine(EngineKind::BitPlanes),
+            body,
+        }
+    }
+
+    fn try_decompress(&self, frame: &Frame, options: &DecodeOptions) -> WizardResult<Vec<u8>> {
+        try_unweave_bit_planes_body(&frame.header, &frame.body, options)
+    }
+}
+
+/// Двоичная модель одной плоскости
+fn plane_model() -> AgingModel {
+    AgingModel::new(2, PLANE_HALF_LIFE, CoderPrecision::Bits32)
+        .expect("двоичный алфавит умещается в точность кодера")
+}
+
+/// Проверяемая распаковка тела кадра битовых плоскостей 🛡️
+pub(crate) fn try_unweave_bit_planes_body(
+    header: &ArchiveHeader,
+    body: &[u8],
+    options: &DecodeOptions,
+) -> WizardResult<Vec<u8>> {
+    check_body_header(header, EngineKind::BitPlanes)?;
+    if header.alphabet_map.is_some() {
+        return Err(WizardError::CorruptFrequencyTable(
+            "атлас алфавита не применяется к битовым плоскостям",
+        ));
+    }
+
+    let mut cursor = ScrollCursor::new(body, 0);
+    let restored_len = cursor.read_u64()?;
+    let stream_len = cursor.read_u32()? as usize;
+    let compressed_bit_stream = cursor.read_slice(stream_len)?;
+    if cursor.remaining() != 0 {
+        return Err(WizardError::CorruptPayload);
+    }
+    options.reserve_output(restored_len)?;
+
+    let mut restored = vec![0u8; restored_len as usize];
+    let mut decoder = ArithmeticDecoder::new(compressed_bit_stream, CoderPrecision::Bits32);
+    for plane in 0..PLANE_COUNT {
+        let mut model = plane_model();
+        for byte in &mut restored {
+            let target = decoder.decode_target(model.total())?;
+            let bit = model.symbol_at(target);
+            let (low, high) = model.interval(bit);
+            decoder.consume(low, high, model.total())?;
+            *byte |= (bit as u8) << plane;
+            model.observe(bit);
+        }
+    }
+    Ok(restored)
+}
+
+/// Тесты битовых плоскостей 🎯
+#[cfg(test)]
+mod plane_sieve_tests {
+    use super::*;
+    use crate::compression_engine::engine_covenant::PipelineStage;
+    use crate::simple_api::{compress_data, compress_with_stage, decompress_data};
+
+    /// Фильтр Блума с заполнением около 3%
+    fn sparse_bloom_filter() -> Vec<u8> {
+        let mut filter = vec![0u8; 16 * 1024];
+        let mut state = 0x2545_F491u32;
+        for _ in 0..4000 {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            let bit = state as usize % (filter.len() * 8);
+            filter[bit / 8] |= 1 << (bit % 8);
+        }
+        filter
+    }
+
+    #[test]
+    fn test_bit_planes_beat_bytes_on_bitmaps() {
+        let bloom = sparse_bloom_filter();
+        let planes = compress_with_stage(&bloom, PipelineStage::BitPlanes);
+        assert!(planes.len() < compress_data(&bloom).len());
+        assert_eq!(decompress_data(&planes), bloom);
+
+        // Булев столбец: длинные серии истин и лжи
+        let flags: Vec<u8> = (0..20_000u32)
+            .map(|row| u8::from((row / 700) % 3 == 0 && row % 97 != 0))
+            .collect();
+        let planes = compress_with_stage(&flags, PipelineStage::BitPlanes);
+        assert!(planes.len() < compress_data(&flags).len());
+        assert_eq!(decompress_data(&planes), flags);
+    }
+
+    #[test]
+    fn test_any_bytes_roundtrip() {
+        let all_bytes: Vec<u8> = (0..=255).collect();
+        for data in [&b""[..], b"\x01", b"\xFF\x00\x7F", &all_bytes] {
+            let archive = compress_with_stage(data, PipelineStage::BitPlanes);
+            assert_eq!(decompress_data(&archive), data);
+        }
+    }
+
+    #[test]
+    fn test_damaged_frame_is_rejected() {
+        let frame = BitPlaneEngine.compress(&sparse_bloom_filter(), &EngineConfig::default());
+        let options = DecodeOptions::default();
+        assert!(matches!(
+            BitPlaneEngine.try_decompress(&frame, &options.clone().with_max_output_bytes(1024)),
+            Err(WizardError::LimitExceeded { .. })
+        ));
+
+        let mut trailing = frame.clone();
+        trailing.body.push(0);
+        assert_eq!(
+            BitPlaneEngine.try_decompress(&trailing, &options),
+            Err(WizardError::CorruptPayload)
+        );
+        let mut truncated = frame;
+        truncated.body.truncate(10);
+        assert!(matches!(
+            BitPlaneEngine.try_decompress(&truncated, &options),
+            Err(WizardError::Truncated { .. })
+        ));
+    }
+}
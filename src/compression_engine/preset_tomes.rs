@@ -0,0 +1,412 @@
+//! Готовые словари для частых видов данных 📦
+//!
+//! Короткое сообщение не окупает собственный словарь и таблицу частот, а
+//! обучать [`CompressorHandle`] на своем корпусе хотят не все. Пресеты —
+//! встроенные общие модели для английского текста, ключей JSON и токенов
+//! журналов, каждый за своей фичей (`preset-prose`, `preset-json`,
+//! `preset-logs`; `presets` включает все). Архив пресета хранит только
+//! идентификатор модели, как архив дескриптора, а распаковка узнает
+//! пресет по идентификатору сама — без [`DictionaryRegistry`](super::DictionaryRegistry).
+//!
+//! Модель пресета обучается один раз при первом использовании на
+//! встроенном образце с фиксированным словом. Образцы и слова заморожены:
+//! их изменение меняет идентификатор, и старые архивы перестанут
+//! распознаваться, поэтому новая модель — это новый вариант [`Preset`].
+
+use super::compressor_handle::{CompressorHandle, HandleArtifact};
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::error::{WizardError, WizardResult};
+use crate::format::artifact_scroll::ScrollCursor;
+use crate::format::container_runes::{ArchiveHeader, ModelKind, FORMAT_VERSION};
+#[cfg(any(
+    feature = "preset-prose",
+    feature = "preset-json",
+    feature = "preset-logs"
+))]
+use std::sync::OnceLock;
+
+/// Встроенный словарь 📦
+///
+/// ```
+/// # #[cfg(feature = "preset-json")] {
+/// use arithmetic_compression_wizard::compression_engine::{EngineConfig, Preset};
+/// use arithmetic_compression_wizard::simple_api::{compress_data, decompress_data, try_compress_with_config};
+///
+/// let message = br#"{"id": 7, "name": "owl", "status": "active", "created": true}"#;
+/// let archive = try_compress_with_config(message, &EngineConfig::preset(Preset::Json)).unwrap();
+/// assert!(archive.len() < compress_data(message).len());
+/// assert_eq!(decompress_data(&archive), message);
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Preset {
+    /// Английский текст: частые служебные и общие слова
+    #[cfg(feature = "preset-prose")]
+    Prose,
+    /// JSON: частые ключи и литералы
+    #[cfg(feature = "preset-json")]
+    Json,
+    /// Журналы: уровни, методы HTTP и частые слова сообщений
+    #[cfg(feature = "preset-logs")]
+    Logs,
+}
+
+impl Preset {
+    /// Все пресеты, включенные фичами сборки
+    pub const ALL: &'static [Preset] = &[
+        #[cfg(feature = "preset-prose")]
+        Preset::Prose,
+        #[cfg(feature = "preset-json")]
+        Preset::Json,
+        #[cfg(feature = "preset-logs")]
+        Preset::Logs,
+    ];
+
+    /// Общая модель пресета (обучается при первом вызове)
+    pub fn handle(self) -> &'static CompressorHandle {
+        match self {
+            #[cfg(feature = "preset-prose")]
+            Preset::Prose => {
+                static PROSE: OnceLock<CompressorHandle> = OnceLock::new();
+                PROSE.get_or_init(|| train(PROSE_WORDS, PROSE_SAMPLE))
+            }
+            #[cfg(feature = "preset-json")]
+            Preset::Json => {
+                static JSON: OnceLock<CompressorHandle> = OnceLock::new();
+                JSON.get_or_init(|| train(JSON_WORDS, JSON_SAMPLE))
+            }
+            #[cfg(feature = "preset-logs")]
+            Preset::Logs => {
+                static LOGS: OnceLock<CompressorHandle> = OnceLock::new();
+                LOGS.get_or_init(|| train(LOG_WORDS, LOG_SAMPLE))
+            }
+        }
+    }
+
+    /// Идентификатор модели в заголовке архива
+    pub fn dictionary_id(self) -> u64 {
+        self.handle().dictionary_id()
+    }
+
+    /// Пресет по идентификатору из заголовка архива
+    pub fn from_dictionary_id(dictionary_id: u64) -> Option<Preset> {
+        Preset::ALL
+            .iter()
+            .copied()
+            .find(|preset| preset.dictionary_id() == dictionary_id)
+    }
+}
+
+/// Модель пресета по словам и образцу
+#[cfg(any(
+    feature = "preset-prose",
+    feature = "preset-json",
+    feature = "preset-logs"
+))]
+fn train(words: &[&str], sample: &str) -> CompressorHandle {
+    CompressorHandle::train_with_grimoire(
+        sample,
+        words
+            .iter()
+            .map(|word| word.to_string())
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// Распаковка тела кадра пресета: `[число символов u32][поток]` 🛡️
+///
+/// Кадр без известного пресета дает
+/// [`WizardError::ExternalDictionaryRequired`].
+pub(crate) fn try_unweave_preset_body(
+    header: &ArchiveHeader,
+    body: &[u8],
+    options: &DecodeOptions,
+) -> WizardResult<Vec<u8>> {
+    if header.format_version > FORMAT_VERSION {
+        return Err(WizardError::UnsupportedVersion(header.format_version));
+    }
+    let dictionary_id = header.dictionary_id.ok_or(WizardError::CorruptPayload)?;
+    let preset = Preset::from_dictionary_id(dictionary_id)
+        .ok_or(WizardError::ExternalDictionaryRequired(dictionary_id))?;
+    if header.model_kind != ModelKind::SharedOrder0 {
+        return Err(WizardError::UnsupportedModel(header.model_kind.to_byte()));
+    }
+
+    let mut cursor = ScrollCursor::new(body, 0);
+    let symbol_count = cursor.read_u32()?;
+    let artifact = HandleArtifact {
+        dictionary_id,
+        symbol_count,
+        compressed_bit_stream: body[4..].to_vec(),
+    };
+    preset.handle().try_decompress(&artifact, options)
+}
+
+/// Слова английского текста
+#[cfg(feature = "preset-prose")]
+const PROSE_WORDS: &[&str] = &[
+    "the", "and", "that", "have", "for", "not", "with", "you", "this", "but", "his", "from",
+    "they", "say", "her", "she", "will", "one", "all", "would", "there", "their", "what", "out",
+    "about", "who", "get", "which", "when", "make", "can", "like", "time", "just", "him", "know",
+    "take", "people", "into", "year", "your", "good", "some", "could", "them", "see", "other",
+    "than", "then", "now", "look", "only", "come", "its", "over", "think", "also", "back", "after",
+    "use", "two", "how", "our", "work", "first", "well", "way", "even", "new", "want", "because",
+    "any", "these", "give", "day", "most", "were", "was", "had", "been", "has", "are", "more",
+    "very", "through", "where", "before", "should", "each", "much", "those",
+];
+
+/// Образец английского текста
+#[cfg(feature = "preset-prose")]
+const PROSE_SAMPLE: &str = "\
+The first time we met, she said that the people who work in the old house would come back \
+after the winter. I think they were right, but none of us knew how long it would take. \
+When the day came, the two of them walked through the gate and looked at the garden as if \
+they had never been away. It was a good year for the town: there was more work than anyone \
+could want, and most of the new families had found a place to live. \
+What I remember about that time is how quiet the evenings were. We would sit by the window \
+and talk about the things we wanted to make, the places we wanted to see, and the books we \
+should have read before. He was the only one who could tell a story well, and when he spoke \
+everyone in the room would listen. \
+Some of those stories were about his father, who had been a sailor and had seen more of the \
+world than any of us. Others were just for the children, about animals that could talk and \
+kings who lost their way in the forest. Each of them had a lesson, even if it was not the one \
+you expected. \
+Now, many years later, I still think about them. The house is gone, the garden is a road, \
+and the people have moved to other towns. But when I look back, I know that it was the best \
+time of my life, and I am thankful that I was there to see it. \
+If you ask me what made it so good, I would say it was the way we cared for each other. \
+Nobody had much, but everyone would give what they could, and no one was ever left alone. \
+That is something I have tried to keep with me, through all the years and all the changes \
+that came after. ";
+
+/// Частые ключи и литералы JSON
+#[cfg(feature = "preset-json")]
+const JSON_WORDS: &[&str] = &[
+    "true",
+    "false",
+    "null",
+    "name",
+    "type",
+    "status",
+    "message",
+    "error",
+    "value",
+    "items",
+    "data",
+    "user",
+    "email",
+    "created",
+    "updated",
+    "timestamp",
+    "count",
+    "total",
+    "page",
+    "limit",
+    "offset",
+    "description",
+    "title",
+    "content",
+    "version",
+    "code",
+    "result",
+    "success",
+    "request",
+    "response",
+    "token",
+    "active",
+    "enabled",
+    "config",
+    "metadata",
+    "tags",
+    "label",
+    "url",
+    "path",
+    "method",
+    "level",
+    "key",
+    "price",
+    "amount",
+    "currency",
+    "address",
+    "city",
+    "country",
+    "phone",
+    "first",
+    "last",
+    "date",
+    "time",
+    "items",
+    "parent",
+];
+
+/// Образец JSON
+#[cfg(feature = "preset-json")]
+const JSON_SAMPLE: &str = r#"{"id": 1, "name": "alpha", "type": "user", "status": "active", "email": "alpha@example.com", "created": "2024-01-01T10:00:00Z", "updated": "2024-01-02T11:30:00Z", "enabled": true, "tags": ["admin", "beta"], "metadata": null}
+{"status": "ok", "code": 200, "message": "success", "data": {"items": [{"id": 10, "title": "first", "price": 9.99, "currency": "USD"}, {"id": 11, "title": "second", "price": 19.5, "currency": "EUR"}], "total": 2, "page": 1, "limit": 20, "offset": 0}}
+{"error": {"code": 404, "message": "not found", "path": "/api/users/42", "method": "GET"}, "success": false, "timestamp": 1700000000}
+{"request": {"method": "POST", "url": "/api/orders", "token": "abc123"}, "response": {"status": 201, "result": {"id": 77, "amount": 120, "currency": "USD"}}}
+{"user": {"id": 5, "first": "Ada", "last": "Byron", "email": "ada@example.com", "phone": "+44 20 0000", "address": {"city": "London", "country": "UK"}}, "active": true, "version": 3}
+{"config": {"level": "info", "enabled": true, "limit": 100}, "description": "default settings", "label": "prod", "key": "service", "value": "api", "date": "2024-03-05", "time": "08:15:00"}
+{"items": [{"name": "apple", "count": 3, "parent": null}, {"name": "pear", "count": 0, "parent": "fruit"}], "total": 3, "updated": false, "content": "", "type": "list"}
+{"id": 2, "name": "beta", "type": "user", "status": "blocked", "email": "beta@example.com", "created": "2024-02-10T09:00:00Z", "enabled": false, "tags": [], "metadata": {"source": "import"}}
+"#;
+
+/// Частые токены журналов
+#[cfg(feature = "preset-logs")]
+const LOG_WORDS: &[&str] = &[
+    "INFO",
+    "WARN",
+    "ERROR",
+    "DEBUG",
+    "TRACE",
+    "GET",
+    "POST",
+    "PUT",
+    "DELETE",
+    "HTTP",
+    "request",
+    "response",
+    "connection",
+    "timeout",
+    "failed",
+    "started",
+    "completed",
+    "user",
+    "session",
+    "status",
+    "latency",
+    "error",
+    "service",
+    "server",
+    "client",
+    "retry",
+    "closed",
+    "opened",
+    "duration",
+    "bytes",
+    "handler",
+    "worker",
+    "queue",
+    "job",
+    "processing",
+    "received",
+    "sent",
+    "from",
+    "for",
+    "with",
+    "the",
+    "id",
+    "host",
+    "port",
+    "api",
+    "health",
+    "check",
+    "database",
+    "query",
+    "cache",
+    "miss",
+    "hit",
+];
+
+/// Образец журнала
+#[cfg(feature = "preset-logs")]
+const LOG_SAMPLE: &str = "\
+2024-05-01T12:00:01Z INFO server started on host 0.0.0.0 port 8080
+2024-05-01T12:00:02Z INFO worker 1 started processing queue default
+2024-05-01T12:00:03Z DEBUG connection opened from client 10.0.0.5
+2024-05-01T12:00:03Z INFO GET /api/users HTTP/1.1 status 200 latency 12ms bytes 512
+2024-05-01T12:00:04Z INFO POST /api/orders HTTP/1.1 status 201 latency 48ms bytes 128
+2024-05-01T12:00:05Z WARN request timeout for user 42 session abc retry 1
+2024-05-01T12:00:06Z ERROR database query failed with error connection closed
+2024-05-01T12:00:06Z INFO retry 2 for job 17 in queue default
+2024-05-01T12:00:07Z DEBUG cache miss for key user:42
+2024-05-01T12:00:07Z DEBUG cache hit for key user:7
+2024-05-01T12:00:08Z INFO GET /health HTTP/1.1 status 200 latency 1ms bytes 2
+2024-05-01T12:00:09Z INFO job 17 completed duration 350ms
+2024-05-01T12:00:10Z INFO PUT /api/users/42 HTTP/1.1 status 204 latency 20ms bytes 0
+2024-05-01T12:00:11Z WARN health check latency 900ms for service payments
+2024-05-01T12:00:12Z ERROR handler failed for request 9f3a with status 500
+2024-05-01T12:00:13Z INFO DELETE /api/sessions/abc HTTP/1.1 status 200 latency 5ms bytes 16
+2024-05-01T12:00:14Z DEBUG response sent to client 10.0.0.5 bytes 2048
+2024-05-01T12:00:15Z INFO connection closed from client 10.0.0.5
+2024-05-01T12:00:16Z TRACE received 64 bytes from server api
+2024-05-01T12:00:17Z INFO worker 2 completed processing job 18 duration 120ms
+";
+
+/// Тесты пресетов 🎯
+#[cfg(all(
+    test,
+    feature = "preset-prose",
+    feature = "preset-json",
+    feature = "preset-logs"
+))]
+mod preset_tomes_tests {
+    use super::*;
+    use crate::compression_engine::engine_covenant::EngineConfig;
+    use crate::compression_engine::DictionaryRegistry;
+    use crate::simple_api::{compress_data, decompress_data, try_compress_with_config};
+
+    #[test]
+    fn test_presets_beat_plain_archives_on_small_messages() {
+        let messages = [
+            (
+                Preset::Prose,
+                &b"I think that the people would come back after the day."[..],
+            ),
+            (
+                Preset::Json,
+                br#"{"id": 9, "status": "active", "email": "owl@example.com"}"#,
+            ),
+            (
+                Preset::Logs,
+                b"2024-06-01T00:00:00Z ERROR request timeout for user 7 retry 3\n",
+            ),
+        ];
+        for (preset, message) in messages {
+            let archive = try_compress_with_config(message, &EngineConfig::preset(preset)).unwrap();
+            assert!(
+                archive.len() * 2 < compress_data(message).len(),
+                "{preset:?}"
+            );
+            assert_eq!(decompress_data(&archive), message);
+            // Реестр без словарей тоже распознает пресет
+            assert_eq!(DictionaryRegistry::new().decompress(&archive), message);
+        }
+    }
+
+    #[test]
+    fn test_preset_ids_are_distinct_and_stable() {
+        let ids: Vec<u64> = Preset::ALL
+            .iter()
+            .map(|preset| preset.dictionary_id())
+            .collect();
+        assert_eq!(ids.len(), 3);
+        assert!(ids[0] != ids[1] && ids[1] != ids[2] && ids[0] != ids[2]);
+        for &preset in Preset::ALL {
+            assert_eq!(
+                Preset::from_dictionary_id(preset.dictionary_id()),
+                Some(preset)
+            );
+        }
+        assert_eq!(Preset::from_dictionary_id(0xDEAD_BEEF), None);
+    }
+
+    #[test]
+    fn test_damaged_preset_frames() {
+        let config = EngineConfig::default().with_preset(Preset::Json);
+        let archive = try_compress_with_config(br#"{"name": "owl"}"#, &config).unwrap();
+        let (header, body_offset) = ArchiveHeader::parse(&archive).unwrap();
+        let options = DecodeOptions::default();
+        assert!(matches!(
+            try_unweave_preset_body(&header, &archive[body_offset..body_offset + 2], &options),
+            Err(WizardError::Truncated { .. })
+        ));
+
+        let mut foreign = header;
+        foreign.dictionary_id = Some(0xDEAD_BEEF);
+        assert_eq!(
+            try_unweave_preset_body(&foreign, &archive[body_offset..], &options),
+            Err(WizardError::ExternalDictionaryRequired(0xDEAD_BEEF))
+        );
+    }
+}
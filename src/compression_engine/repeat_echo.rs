@@ -0,0 +1,359 @@
+//! Эхо длинных повторов внутри одного входа 🔁
+//!
+//! В образах дисков один и тот же блок в 4 КиБ (нулевой сектор, копия
+//! библиотеки, шаблон файловой системы) встречается сотни раз. Словарь
+//! слов видит только короткие слова, а дедупликация по содержимому —
+//! фрагменты около 10 КиБ и длиннее, поэтому одиночные повторы в 4 КиБ
+//! проходят мимо обоих. Стадия
+//! [`PipelineStage::LongRepeats`](super::PipelineStage::LongRepeats)
+//! запоминает хеш каждого выровненного блока из [`REPEAT_WINDOW`] байтов
+//! и ведет по входу скользящий хеш Рабина — Карпа того же окна: при
+//! совпадении хешей и байтов повтор продлевается в обе стороны и
+//! записывается ссылкой назад. Байты между повторами сжимаются
+//! словарным арифметическим движком блоками.
+//!
+//! Тело кадра: `[длина входа u64][повторов u32]`, на каждый повтор
+//! `[литералов перед повтором u64][начало источника u64][длина u64]` и
+//! архив блоков с литералами подряд. Источник может перекрывать сам
+//! повтор: ссылка на предыдущий блок из нулей заполняет любой их пробег.
+
+use super::dedup::{try_unweave_store, weave_store};
+use super::engine_covenant::{CompressionEngine, EngineConfig, Frame};
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::error::{WizardError, WizardResult};
+use crate::format::artifact_scroll::{check_body_header, ScrollCursor};
+use crate::format::container_runes::{ArchiveHeader, EngineKind, ModelKind};
+use std::collections::HashMap;
+
+/// Окно скользящего хеша и шаг выровненных блоков-источников
+pub const REPEAT_WINDOW: usize = 4 * 1024;
+
+/// Основание полиномиального хеша (нечетное: умножение обратимо)
+const HASH_BASE: u64 = 0x0000_0100_0000_01B3;
+
+/// `HASH_BASE` в степени `REPEAT_WINDOW - 1`: вес байта, покидающего окно
+const OUTGOING_WEIGHT: u64 = outgoing_weight();
+
+/// Длина записи повтора в теле кадра
+const REPEAT_RECORD_LEN: usize = 24;
+
+const fn outgoing_weight() -> u64 {
+    let mut weight = 1u64;
+    let mut step = 1;
+    while step < REPEAT_WINDOW {
+        weight = weight.wrapping_mul(HASH_BASE);
+        step += 1;
+    }
+    weight
+}
+
+/// Хеш окна с нуля
+fn window_hash(window: &[u8]) -> u64 {
+    window.iter().fold(0u64, |hash, &byte| {
+        hash.wrapping_mul(HASH_BASE).wrapping_add(byte as u64)
+    })
+}
+
+/// Сдвигает окно на байт: `outgoing` уходит, `incoming` входит
+fn roll_hash(hash: u64, outgoing: u8, incoming: u8) -> u64 {
+    hash.wrapping_sub((outgoing as u64).wrapping_mul(OUTGOING_WEIGHT))
+        .wrapping_mul(HASH_BASE)
+        .wrapping_add(incoming as u64)
+}
+
+/// Повтор: литералы перед ним и ссылка назад
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Repeat {
+    literal_len: usize,
+    source: usize,
+    len: usize,
+}
+
+/// Находит повторы не короче окна; источник всегда левее повтора
+fn find_repeats(data: &[u8]) -> Vec<Repeat> {
+    let mut repeats = Vec::new();
+    if data.len() < 2 * REPEAT_WINDOW {
+        return repeats;
+    }
+    // Первый выровненный блок с каждым хешем
+    let mut block_index: HashMap<u64, usize> = HashMap::new();
+    let mut next_block = 0;
+    let mut literal_start = 0;
+    let mut position = 0;
+    let mut hash = window_hash(&data[..REPEAT_WINDOW]);
+    while position + REPEAT_WINDOW <= data.len() {
+        // Источники — только блоки, целиком лежащие левее окна
+        while next_block + REPEAT_WINDOW <= position {
+            let block_hash = window_hash(&data[next_block..next_block + REPEAT_WINDOW]);
+            block_index.entry(block_hash).or_insert(next_block);
+            next_block += REPEAT_WINDOW;
+        }
+
+        let window = &data[position..position + REPEAT_WINDOW];
+        let confirmed = block_index
+            .get(&hash)
+            .copied()
+            .filter(|&source| data[source..source + REPEAT_WINDOW] == *window);
+        if let Some(mut source) = confirmed {
+            // Назад — за счет еще не записанных литералов
+            let mut start = position;
+            while start > literal_start && source > 0 && data[source - 1] == data[start - 1] {
+                start -= 1;
+                source -= 1;
+            }
+            let mut len = position + REPEAT_WINDOW - start;
+            while start + len < data.len() && data[source + len] == data[start + len] {
+                len += 1;
+            }
+            repeats.push(Repeat {
+                literal_len: start - literal_start,
+                source,
+                len,
+            });
+            position = start + len;
+            literal_start = position;
+            if position + REPEAT_WINDOW <= data.len() {
+                hash = window_hash(&data[position..position + REPEAT_WINDOW]);
+            }
+            continue;
+        }
+
+        if position + REPEAT_WINDOW < data.len() {
+            hash = roll_hash(hash, data[position], data[position + REPEAT_WINDOW]);
+        }
+        position += 1;
+    }
+    repeats
+}
+
+/// Движок длинных повторов 🔁
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LongRepeatEngine;
+
+impl CompressionEngine for LongRepeatEngine {
+    fn engine_kind(&self) -> EngineKind {
+        EngineKind::LongRepeats
+    }
+
+    fn name(&self) -> &'static str {
+        "long-repeats"
+    }
+
+    fn compress(&self, data: &[u8], config: &EngineConfig) -> Frame {
+        let repeats = find_repeats(data);
+        let mut literals = Vec::new();
+        let mut position = 0;
+        for repeat in &repeats {
+            literals.extend_from_slice(&data[position..position + repeat.literal_len]);
+            position += repeat.literal_len + repeat.len;
+        }
+        literals.extend_from_slice(&data[position..]);
+        let store_archive = weave_store(&literals, config);
+
+        let mut body =
+            Vec::with_capacity(12 + REPEAT_RECORD_LEN * repeats.len() + store_archive.len());
+        body.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        body.extend_from_slice(&(repeats.len() as u32).to_le_bytes());
+        for repeat in &repeats {
+            body.extend_from_slice(&(repeat.literal_len as u64).to_le_bytes());
+            body.extend_from_slice(&(repeat.source as u64).to_le_bytes());
+            body.extend_from_slice(&(repeat.len as u64).to_le_bytes());
+        }
+        body.extend_from_slice(&store_archive);
+
+        Frame {
+            header: ArchiveHeader::current(ModelKind::StaticOrder0, None)
+                .with_engine(EngineKind::LongRepeats),
+            body,
+        }
+    }
+
+    fn try_decompress(&self, frame: &Frame, options: &DecodeOptions) -> WizardResult<Vec<u8>> {
+        try_unweave_repeats_body(&frame.header, &frame.body, options)
+    }
+}
+
+/// Проверяемая распаковка тела кадра длинных повторов 🛡️
+pub(crate) fn try_unweave_repeats_body(
+    header: &ArchiveHeader,
+    body: &[u8],
+    options: &DecodeOptions,
+) -> WizardResult<Vec<u8>> {
+    check_body_header(header, EngineKind::LongRepeats)?;
+    if header.alphabet_map.is_some() {
+        return Err(WizardError::CorruptFrequencyTable(
+            "атлас алфавита не применяется к длинным повторам",
+        ));
+    }
+
+    let mut cursor = ScrollCursor::new(body, 0);
+    let restored_len = cursor.read_u64()?;
+    let repeat_count = cursor.read_u32()? as usize;
+    options.reserve_output(restored_len)?;
+    let records_len = repeat_count
+        .checked_mul(REPEAT_RECORD_LEN)
+        .ok_or(WizardError::CorruptPayload)?;
+    let records = cursor.read_slice(records_len)?;
+
+    let store_offset = cursor.position();
+    let literals = try_unweave_store(&body[store_offset..], options)
+        .map_err(|error| error.shifted(store_offset))?;
+    if literals.len() as u64 > restored_len {
+        return Err(WizardError::CorruptPayload);
+    }
+
+    let mut restored = Vec::with_capacity(restored_len as usize);
+    let mut literal_position = 0usize;
+    let field = |record: &[u8], index: usize| {
+        u64::from_le_bytes(
+            record[8 * index..8 * index + 8]
+                .try_into()
+                .expect("срез из 8 байтов"),
+        )
+    };
+    for record in records.chunks_exact(REPEAT_RECORD_LEN) {
+        let (literal_len, source, len) = (field(record, 0), field(record, 1), field(record, 2));
+
+        let literal_end = literal_position as u64 + literal_len;
+        if literal_end > literals.len() as u64 {
+            return Err(WizardError::CorruptPayload);
+        }
+        restored.extend_from_slice(&literals[literal_position..literal_end as usize]);
+        literal_position = literal_end as usize;
+
+        let room = restored_len - restored.len() as u64;
+        if source >= restored.len() as u64 || len > room {
+            return Err(WizardError::CorruptPayload);
+        }
+        // Перекрывающийся источник копируется частями, пока повтор растет
+        let (source, len) = (source as usize, len as usize);
+        let mut copied = 0;
+        while copied < len {
+            let available = restored.len() - (source + copied);
+            let step = available.min(len - copied);
+            restored.extend_from_within(source + copied..source + copied + step);
+            copied += step;
+        }
+    }
+    restored.extend_from_slice(&literals[literal_position..]);
+    if restored.len() as u64 != restored_len {
+        return Err(WizardError::CorruptPayload);
+    }
+    Ok(restored)
+}
+
+/// Тесты эха длинных повторов 🎯
+#[cfg(test)]
+mod repeat_echo_tests {
+    use super::*;
+    use crate::compression_engine::engine_covenant::PipelineStage;
+    use crate::simple_api::{compress_data, compress_with_stage, decompress_data};
+
+    /// Псевдослучайные (несжимаемые) байты
+    fn noise(len: usize, seed: u32) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect()
+    }
+
+    /// Образ диска: секторы в 4 КиБ, многие повторяются, есть пробег нулей
+    fn disk_image() -> Vec<u8> {
+        let sectors: Vec<Vec<u8>> = (0..6).map(|seed| noise(REPEAT_WINDOW, seed + 1)).collect();
+        let mut image = Vec::new();
+        for step in 0..64usize {
+            image.extend_from_slice(&sectors[step * 7 % sectors.len()]);
+        }
+        image.extend_from_slice(&[0u8; 10 * REPEAT_WINDOW]);
+        image.extend_from_slice(b"tail of the image");
+        image
+    }
+
+    #[test]
+    fn test_repeated_sectors_become_back_references() {
+        let image = disk_image();
+        let archive = compress_with_stage(&image, PipelineStage::LongRepeats);
+        assert_eq!(decompress_data(&archive), image);
+        // Остаются шесть уникальных секторов и немного ссылок
+        assert!(
+            archive.len() < 7 * REPEAT_WINDOW + 4096,
+            "{}",
+            archive.len()
+        );
+        assert!(compress_data(&image).len() > image.len() / 2);
+
+        // Повтор, сдвинутый относительно выравнивания, тоже находится
+        let sector = noise(3 * REPEAT_WINDOW, 99);
+        let mut shifted = sector.clone();
+        shifted.extend_from_slice(b"odd prefix");
+        shifted.extend_from_slice(&sector);
+        let repeats = find_repeats(&shifted);
+        assert_eq!(
+            repeats,
+            [Repeat {
+                literal_len: sector.len() + 10,
+                source: 0,
+                len: sector.len(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_any_bytes_roundtrip() {
+        let all_bytes: Vec<u8> = (0..=255).collect();
+        let text = "the owl carries scrolls between towers. ".repeat(500);
+        let zeros = vec![0u8; 5 * REPEAT_WINDOW + 3];
+        let mut echo = noise(REPEAT_WINDOW + 17, 5);
+        echo.extend_from_within(..);
+        for data in [
+            &b""[..],
+            b"\x01",
+            &all_bytes,
+            text.as_bytes(),
+            &zeros,
+            &echo,
+        ] {
+            let archive = compress_with_stage(data, PipelineStage::LongRepeats);
+            assert_eq!(decompress_data(&archive), data);
+        }
+    }
+
+    #[test]
+    fn test_damaged_frame_is_rejected() {
+        let image = disk_image();
+        let frame = LongRepeatEngine.compress(&image, &EngineConfig::default());
+        let options = DecodeOptions::default();
+        assert!(matches!(
+            LongRepeatEngine.try_decompress(&frame, &options.clone().with_max_output_bytes(1024)),
+            Err(WizardError::LimitExceeded { .. })
+        ));
+
+        // Ссылка вперед, за уже восстановленные байты
+        let mut forward = frame.clone();
+        forward.body[20..28].copy_from_slice(&(image.len() as u64).to_le_bytes());
+        assert_eq!(
+            LongRepeatEngine.try_decompress(&forward, &options),
+            Err(WizardError::CorruptPayload)
+        );
+
+        // Повтор длиннее входа
+        let mut overlong = frame.clone();
+        overlong.body[28..36].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert_eq!(
+            LongRepeatEngine.try_decompress(&overlong, &options),
+            Err(WizardError::CorruptPayload)
+        );
+
+        let mut truncated = frame;
+        truncated.body.truncate(30);
+        assert!(matches!(
+            LongRepeatEngine.try_decompress(&truncated, &options),
+            Err(WizardError::Truncated { .. })
+        ));
+    }
+}
@@ -0,0 +1,238 @@
+//! Котел черновиков 🫕
+//!
+//! Сервис, сжимающий миллионы мелких сообщений, тратит заметную долю
+//! времени на выделение памяти: каждый вызов заново заводит поток
+//! символов, перепись частот, таблицу частот и буфер битового потока.
+//! [`ScratchSpace`] хранит эти буферы между вызовами
+//! [`weave_compression_spell_with_scratch`]: когда их емкости хватает,
+//! сжатие не выделяет под них память. Буферы готового артефакта
+//! возвращает в котел [`ScratchSpace::recycle`].
+//!
+//! Архив получается тем же, что у
+//! [`weave_compression_spell`](super::weave_compression_spell).
+
+use super::compression_conjurer::{
+    build_frequency_table_into, choose_word_grimoire, encode_symbol_stream_into,
+    prune_unused_words, transform_manuscript_into, CompressionArtifact,
+};
+use super::engine_covenant::EngineConfig;
+use super::frequency_census::symbol_census_into;
+use super::phrase_weaver::weave_phrase_enchantments;
+use crate::bit_wizardry::bit_manipulation_spells::CoderPrecision;
+use crate::error::WizardResult;
+use crate::format::artifact_scroll::check_length_field;
+use crate::telemetry::{record_compression, Stopwatch};
+use std::collections::HashMap;
+use std::mem;
+
+/// Буферы, переиспользуемые между вызовами сжатия 🫕
+///
+/// Один котел — на один поток: сжатие берет его по `&mut`.
+#[derive(Debug, Default)]
+pub struct ScratchSpace {
+    /// Параметры сжатия по умолчанию (создаются один раз)
+    config: EngineConfig,
+    /// Поток символов
+    symbols: Vec<u32>,
+    /// Перепись частот символов
+    census: HashMap<u32, u64>,
+    /// Частоты по возрастанию id символа
+    frequency_pairs: Vec<(u32, u64)>,
+    /// Таблица частот следующего артефакта
+    frequency_entries: Vec<(u32, u64, u64)>,
+    /// Битовый поток следующего артефакта
+    bit_stream: Vec<u8>,
+}
+
+impl ScratchSpace {
+    /// Пустой котел: буферы растут по первым вызовам
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Возвращает в котел буферы артефакта, который больше не нужен ♻️
+    ///
+    /// Таблица частот и битовый поток артефакта станут буферами
+    /// следующего сжатия, если они вместительнее прежних.
+    pub fn recycle(&mut self, artifact: CompressionArtifact) {
+        if artifact.compressed_bit_stream.capacity() > self.bit_stream.capacity() {
+            self.bit_stream = artifact.compressed_bit_stream;
+        }
+        if artifact.mystical_frequency_codex.capacity() > self.frequency_entries.capacity() {
+            self.frequency_entries = artifact.mystical_frequency_codex;
+        }
+    }
+
+    /// Сколько байтов удерживают буферы котла
+    ///
+    /// Перепись частот оценивается по числу мест в таблице.
+    pub fn retained_bytes(&self) -> usize {
+        self.symbols.capacity() * mem::size_of::<u32>()
+            + self.census.capacity() * mem::size_of::<(u32, u64)>()
+            + self.frequency_pairs.capacity() * mem::size_of::<(u32, u64)>()
+            + self.frequency_entries.capacity() * mem::size_of::<(u32, u64, u64)>()
+            + self.bit_stream.capacity()
+    }
+}
+
+/// Сжатие с буферами из котла 🫕
+///
+/// Паникует там же, где [`weave_compression_spell`](super::weave_compression_spell),
+/// — для таких входов используйте [`try_weave_compression_spell_with_scratch`].
+pub fn weave_compression_spell_with_scratch(
+    original_manuscript: impl AsRef<[u8]>,
+    scratch: &mut ScratchSpace,
+) -> CompressionArtifact {
+    try_weave_compression_spell_with_scratch(original_manuscript, scratch)
+        .expect("вход больше точности кодера: используйте try_weave_compression_spell_with_scratch")
+}
+
+/// Проверяемое сжатие с буферами из котла
+///
+/// После ошибки котел остается пригодным для следующих вызовов.
+pub fn try_weave_compression_spell_with_scratch(
+    original_manuscript: impl AsRef<[u8]>,
+    scratch: &mut ScratchSpace,
+) -> WizardResult<CompressionArtifact> {
+    let original_manuscript = original_manuscript.as_ref();
+    check_length_field(original_manuscript.len())?;
+    let stopwatch = Stopwatch::start();
+    let config = &scratch.config;
+
+    // Шаги 1–3 как в symbolize_manuscript, но символы пишутся в буфер котла
+    let mystical_word_grimoire = choose_word_grimoire(original_manuscript, config);
+    let mut symbolic_incantations = mem::take(&mut scratch.symbols);
+    symbolic_incantations.clear();
+    transform_manuscript_into(
+        original_manuscript,
+        &mystical_word_grimoire,
+        &config.word_boundaries,
+        &mut symbolic_incantations,
+    );
+    let (symbolic_incantations, mystical_word_grimoire) =
+        prune_unused_words(symbolic_incantations, mystical_word_grimoire);
+    let (symbolic_incantations, mystical_phrase_codex) =
+        weave_phrase_enchantments(symbolic_incantations, mystical_word_grimoire.len());
+
+    // Частоты и кодирование — в буферы котла
+    symbol_census_into(
+        &symbolic_incantations,
+        &mut scratch.census,
+        &mut scratch.frequency_pairs,
+    );
+    let precision = CoderPrecision::default();
+    let mut frequency_codex = mem::take(&mut scratch.frequency_entries);
+    let mut compressed_bit_stream = mem::take(&mut scratch.bit_stream);
+    compressed_bit_stream.clear();
+    let encoded =
+        build_frequency_table_into(&scratch.frequency_pairs, precision, &mut frequency_codex)
+            .and_then(|total_frequency_mass| {
+                encode_symbol_stream_into(
+                    &symbolic_incantations,
+                    &frequency_codex,
+                    total_frequency_mass,
+                    precision,
+                    &mut compressed_bit_stream,
+                )
+                .map(|()| total_frequency_mass)
+            });
+
+    if encoded.is_ok() {
+        record_compression(
+            "static",
+            stopwatch,
+            original_manuscript.len(),
+            &symbolic_incantations,
+            compressed_bit_stream.len(),
+        );
+    }
+    scratch.symbols = symbolic_incantations;
+    match encoded {
+        Ok(total_frequency_essence) => Ok(CompressionArtifact {
+            mystical_frequency_codex: frequency_codex,
+            total_frequency_essence,
+            compressed_bit_stream,
+            mystical_word_grimoire,
+            mystical_phrase_codex,
+        }),
+        Err(error) => {
+            // Буферы неудачного вызова возвращаются в котел
+            scratch.frequency_entries = frequency_codex;
+            scratch.bit_stream = compressed_bit_stream;
+            Err(error)
+        }
+    }
+}
+
+/// Тесты котла черновиков 🎯
+#[cfg(test)]
+mod scratch_cauldron_tests {
+    use super::*;
+    use crate::compression_engine::compression_conjurer::weave_compression_spell;
+    use crate::decompression_oracle::decompression_sage::unweave_compression_spell;
+
+    /// Артефакты совпадают поле в поле
+    fn assert_same_artifact(left: &CompressionArtifact, right: &CompressionArtifact) {
+        assert_eq!(
+            left.mystical_frequency_codex,
+            right.mystical_frequency_codex
+        );
+        assert_eq!(left.total_frequency_essence, right.total_frequency_essence);
+        assert_eq!(left.compressed_bit_stream, right.compressed_bit_stream);
+        assert_eq!(left.mystical_word_grimoire, right.mystical_word_grimoire);
+        assert_eq!(left.mystical_phrase_codex, right.mystical_phrase_codex);
+    }
+
+    #[test]
+    fn test_scratch_matches_plain_weave() {
+        let long_text = "the owl carries scrolls between the towers of the academy. ".repeat(40);
+        let payloads: [&[u8]; 5] = [
+            b"",
+            b"{\"user\":42,\"event\":\"login\"}",
+            long_text.as_bytes(),
+            b"x",
+            b"{\"user\":7,\"event\":\"logout\"}",
+        ];
+
+        let mut scratch = ScratchSpace::new();
+        for payload in payloads {
+            let artifact = weave_compression_spell_with_scratch(payload, &mut scratch);
+            assert_same_artifact(&artifact, &weave_compression_spell(payload));
+            assert_eq!(unweave_compression_spell(artifact.clone()), payload);
+            scratch.recycle(artifact);
+        }
+    }
+
+    #[test]
+    fn test_recycled_buffers_are_reused() {
+        let mut scratch = ScratchSpace::new();
+        let first = weave_compression_spell_with_scratch(b"owl post: scroll 1", &mut scratch);
+        let stream_buffer = first.compressed_bit_stream.as_ptr();
+        let codex_buffer = first.mystical_frequency_codex.as_ptr();
+        scratch.recycle(first);
+        let retained = scratch.retained_bytes();
+        assert!(retained > 0);
+
+        // Сообщение не длиннее прежнего помещается в те же буферы
+        let second = weave_compression_spell_with_scratch(b"owl post: scroll 2", &mut scratch);
+        assert_eq!(second.compressed_bit_stream.as_ptr(), stream_buffer);
+        assert_eq!(second.mystical_frequency_codex.as_ptr(), codex_buffer);
+        scratch.recycle(second);
+        assert_eq!(scratch.retained_bytes(), retained);
+    }
+
+    #[test]
+    fn test_scratch_survives_many_calls() {
+        let mut scratch = ScratchSpace::new();
+        for message in 0..200u32 {
+            let payload = format!("event {message}: {}", "tick ".repeat(message as usize % 7));
+            let artifact = try_weave_compression_spell_with_scratch(&payload, &mut scratch)
+                .expect("мелкое сообщение сжимается");
+            assert_eq!(
+                unweave_compression_spell(artifact.clone()),
+                payload.as_bytes()
+            );
+            scratch.recycle(artifact);
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! Раздельные потоки 🔀
+//!
+//! Литералы и ссылки на словарь распределены очень по-разному, поэтому
+//! [`SplitStreamEngine`] кодирует их двумя арифметическими потоками с
+//! собственными таблицами частот. В потоке литералов кроме байтов есть
+//! символ-переключатель `LITERAL_ESCAPE`: встретив его, декодер берет
+//! следующий символ из потока ссылок.
+//!
+//! Со статическими моделями нулевого порядка идеальная длина кода та же,
+//! что у [`ArithmeticEngine`](super::ArithmeticEngine): цена переключателя
+//! и ссылки в сумме равна цене ссылки в общей таблице, а вторая таблица
+//! добавляет несколько десятков байтов. Раздельные потоки — основа для
+//! моделей, которые по-своему описывают литералы и ссылки.
+//!
+//! Тело кадра: `[словарь][фразы][литералы][ссылки]`, где каждый поток —
+//! `[таблица частот][общая частота][длина u32][битовый поток]`
+//! в раскладке [`artifact_scroll`](crate::format::artifact_scroll).
+
+use super::compression_conjurer::{
+    analyze_symbolic_frequencies, encode_symbol_stream, symbolize_manuscript, WORD_SYMBOL_BASE,
+};
+use super::engine_covenant::{CompressionEngine, EngineConfig, Frame};
+use crate::bit_wizardry::bit_manipulation_spells::CoderPrecision;
+use crate::decompression_oracle::decode_wards::{validate_phrase_codex, DecodeOptions};
+use crate::decompression_oracle::decompression_sage::{
+    decode_symbol_stream, reconstruct_original_manuscript,
+};
+use crate::error::{WizardError, WizardResult};
+use crate::format::artifact_scroll::{
+    check_body_header, read_coded_stream, read_frequency_section, read_grimoire_sections,
+    write_coded_stream, write_frequency_section, write_grimoire_sections, ScrollCursor,
+};
+use crate::format::container_runes::{ArchiveHeader, EngineKind, ModelKind};
+
+/// Символ потока литералов: «следующий символ — из потока ссылок»
+pub(crate) const LITERAL_ESCAPE: u32 = WORD_SYMBOL_BASE;
+
+/// Движок с раздельными потоками литералов и ссылок 🔀
+///
+/// Атлас алфавита (`ScrollOptions::compact_alphabet`) не применяется.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SplitStreamEngine;
+
+impl CompressionEngine for SplitStreamEngine {
+    fn engine_kind(&self) -> EngineKind {
+        EngineKind::SplitStreams
+    }
+
+    fn name(&self) -> &'static str {
+        "split-streams"
+    }
+
+    fn compress(&self, data: &[u8], config: &EngineConfig) -> Frame {
+        let (symbolic_incantations, word_grimoire, phrase_codex) =
+            symbolize_manuscript(data, config);
+        let (literals, references) = split_symbol_stream(&symbolic_incantations);
+
+        let mut body = Vec::new();
+        write_grimoire_sections(&mut body, &word_grimoire, &phrase_codex);
+        write_class_stream(&mut body, &literals);
+        write_class_stream(&mut body, &references);
+
+        Frame {
+            header: ArchiveHeader::current(ModelKind::StaticOrder0, None)
+                .with_engine(EngineKind::SplitStreams),
+            body,
+        }
+    }
+
+    fn try_decompress(&self, frame: &Frame, options: &DecodeOptions) -> WizardResult<Vec<u8>> {
+        try_unweave_split_body(&frame.header, &frame.body, options)
+    }
+}
+
+/// Кодирует поток одного класса символов со своей таблицей частот
+pub(crate) fn write_class_stream(output: &mut Vec<u8>, symbols: &[u32]) {
+    let analysis = analyze_symbolic_frequencies(symbols)
+        .expect("поток больше точности кодера: разбейте вход на блоки");
+    let compressed_bit_stream = encode_symbol_stream(
+        symbols,
+        &analysis.frequency_entries,
+        analysis.total_frequency_mass,
+        CoderPrecision::default(),
+    )
+    .expect("таблица частот построена с проверками");
+
+    write_frequency_section(output, &analysis.frequency_entries);
+    write_coded_stream(
+        output,
+        analysis.total_frequency_mass,
+        &compressed_bit_stream,
+    );
+}
+
+/// Проверяемая распаковка тела кадра с раздельными потоками 🛡️
+pub(crate) fn try_unweave_split_body(
+    header: &ArchiveHeader,
+    body: &[u8],
+    options: &DecodeOptions,
+) -> WizardResult<Vec<u8>> {
+    check_body_header(header, EngineKind::SplitStreams)?;
+    if header.alphabet_map.is_some() {
+        return Err(WizardError::CorruptFrequencyTable(
+            "атлас алфавита не применяется к раздельным потокам",
+        ));
+    }
+
+    let mut cursor = ScrollCursor::new(body, 0);
+    let (word_grimoire, phrase_codex) = read_grimoire_sections(&mut cursor, header, options)?;
+    validate_phrase_codex(&phrase_codex, word_grimoire.len())?;
+
+    let literal_codex = read_frequency_section(&mut cursor, options)?;
+    let (literal_total, literal_stream) = read_coded_stream(&mut cursor)?;
+    let reference_codex = read_frequency_section(&mut cursor, options)?;
+    let (reference_total, reference_stream) = read_coded_stream(&mut cursor)?;
+
+    // Каждый символ потока литералов дает хотя бы один байт результата,
+    // а каждой ссылке нужен свой переключатель
+    options.reserve_output(literal_total)?;
+    if reference_total > literal_total {
+        return Err(WizardError::CorruptPayload);
+    }
+
+    let precision = header.coder_precision();
+    let literals = decode_symbol_stream(
+        literal_stream,
+        &literal_codex,
+        literal_total,
+        literal_total,
+        precision,
+    )?;
+    let references = decode_symbol_stream(
+        reference_stream,
+        &reference_codex,
+        reference_total,
+        reference_total,
+        precision,
+    )?;
+
+    let symbolic_incantations = merge_symbol_streams(&literals, &references)?;
+    reconstruct_original_manuscript(
+        &symbolic_incantations,
+        &word_grimoire,
+        &phrase_codex,
+        options,
+    )
+}
+
+/// Делит символы на поток литералов (с переключателями) и поток ссылок
+pub(crate) fn split_symbol_stream(symbolic_incantations: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    let mut literals = Vec::with_capacity(symbolic_incantations.len());
+    let mut references = Vec::new();
+
+    for &mystical_symbol in symbolic_incantations {
+        if mystical_symbol < WORD_SYMBOL_BASE {
+            literals.push(mystical_symbol);
+        } else {
+            literals.push(LITERAL_ESCAPE);
+            references.push(mystical_symbol);
+        }
+    }
+
+    (literals, references)
+}
+
+/// Сплетает потоки обратно в единую последовательность символов
+///
+/// Каждая ссылка должна быть потрачена ровно одним переключателем.
+pub(crate) fn merge_symbol_streams(literals: &[u32], references: &[u32]) -> WizardResult<Vec<u32>> {
+    let mut merged = Vec::with_capacity(literals.len());
+    let mut pending_references = references.iter();
+
+    for &literal in literals {
+        let mystical_symbol = match literal {
+            LITERAL_ESCAPE => match pending_references.next() {
+                Some(&reference) if reference >= WORD_SYMBOL_BASE => reference,
+                _ => return Err(WizardError::CorruptPayload),
+            },
+            byte if byte < WORD_SYMBOL_BASE => byte,
+            _ => return Err(WizardError::CorruptPayload),
+        };
+        merged.push(mystical_symbol);
+    }
+
+    if pending_references.next().is_some() {
+        return Err(WizardError::CorruptPayload);
+    }
+    Ok(merged)
+}
+
+/// Тесты раздельных потоков 🎯
+#[cfg(test)]
+mod split_stream_engine_tests {
+    use super::*;
+
+    #[test]
+    fn test_split_merge_roundtrip() {
+        let symbols = [b'a' as u32, 300, b' ' as u32, 257, 257, b'!' as u32];
+        let (literals, references) = split_symbol_stream(&symbols);
+
+        assert_eq!(references, vec![300, 257, 257]);
+        assert_eq!(literals.iter().filter(|&&s| s == LITERAL_ESCAPE).count(), 3);
+        assert_eq!(
+            merge_symbol_streams(&literals, &references).unwrap(),
+            symbols
+        );
+    }
+
+    #[test]
+    fn test_mismatched_streams_are_rejected() {
+        let escape = LITERAL_ESCAPE;
+        assert_eq!(
+            merge_symbol_streams(&[escape, escape], &[300]),
+            Err(WizardError::CorruptPayload)
+        );
+        assert_eq!(
+            merge_symbol_streams(&[escape], &[300, 301]),
+            Err(WizardError::CorruptPayload)
+        );
+        assert_eq!(
+            merge_symbol_streams(&[escape], &[b'x' as u32]),
+            Err(WizardError::CorruptPayload)
+        );
+        assert_eq!(
+            merge_symbol_streams(&[400], &[]),
+            Err(WizardError::CorruptPayload)
+        );
+    }
+
+    #[test]
+    fn test_split_engine_roundtrip() {
+        let data = "the wizard and the owl read the scroll. ".repeat(40);
+        let frame = SplitStreamEngine.compress(data.as_bytes(), &EngineConfig::default());
+        assert_eq!(frame.engine(), EngineKind::SplitStreams);
+
+        let parsed = Frame::from_bytes(&frame.to_bytes()).unwrap();
+        assert_eq!(SplitStreamEngine.decompress(&parsed), data.as_bytes());
+        assert_eq!(
+            SplitStreamEngine
+                .decompress(&SplitStreamEngine.compress(b"", &EngineConfig::default())),
+            b""
+        );
+    }
+}
@@ -0,0 +1,147 @@
+//! Хранилище без сжатия 🗄️
+//!
+//! Случайные, уже сжатые или слишком короткие данные арифметический
+//! движок только раздувает. Кадр [`StoredEngine`] несет исходные байты
+//! как есть: заголовок помечает их как хранимые, поэтому распаковка
+//! остается единой — [`decompress_data`](crate::simple_api::decompress_data).
+//!
+//! Тело кадра: исходные байты.
+
+use super::engine_covenant::{CompressionEngine, EngineConfig, Frame};
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::error::WizardResult;
+use crate::format::artifact_scroll::check_body_header;
+use crate::format::container_runes::{ArchiveHeader, EngineKind, ModelKind};
+
+/// Движок хранения без сжатия 🗄️
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StoredEngine;
+
+impl CompressionEngine for StoredEngine {
+    fn engine_kind(&self) -> EngineKind {
+        EngineKind::Stored
+    }
+
+    fn name(&self) -> &'static str {
+        "stored"
+    }
+
+    fn compress(&self, data: &[u8], _config: &EngineConfig) -> Frame {
+        Frame {
+            header: ArchiveHeader::current(ModelKind::StaticOrder0, None)
+                .with_engine(EngineKind::Stored),
+            body: data.to_vec(),
+        }
+    }
+
+    fn try_decompress(&self, frame: &Frame, options: &DecodeOptions) -> WizardResult<Vec<u8>> {
+        try_unstore_body(&frame.header, &frame.body, options)
+    }
+}
+
+/// Проверяемая распаковка тела хранимого кадра
+pub(crate) fn try_unstore_body(
+    header: &ArchiveHeader,
+    body: &[u8],
+    options: &DecodeOptions,
+) -> WizardResult<Vec<u8>> {
+    check_body_header(header, EngineKind::Stored)?;
+    options.reserve_output(body.len() as u64)?;
+    Ok(body.to_vec())
+}
+
+/// Итог сжатия с порогом выгоды ⚖️
+///
+/// Оба варианта — готовые архивы, которые понимает
+/// [`decompress_data`](crate::simple_api::decompress_data).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompressOutcome {
+    /// Сжатие дало выигрыш не ниже порога: архив арифметического движка
+    Compressed(Vec<u8>),
+    /// Выигрыш ниже порога: исходные байты в кадре хранения
+    Stored(Vec<u8>),
+}
+
+impl CompressOutcome {
+    /// Данные сжаты, а не сохранены как есть
+    pub fn is_compressed(&self) -> bool {
+        matches!(self, CompressOutcome::Compressed(_))
+    }
+
+    /// Байты архива
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            CompressOutcome::Compressed(archive) | CompressOutcome::Stored(archive) => archive,
+        }
+    }
+
+    /// Байты архива во владение
+    pub fn into_bytes(self) -> Vec<u8> {
+        match self {
+            CompressOutcome::Compressed(archive) | CompressOutcome::Stored(archive) => archive,
+        }
+    }
+}
+
+impl AsRef<[u8]> for CompressOutcome {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+/// Тесты хранилища 🎯
+#[cfg(test)]
+mod stored_vault_tests {
+    use super::*;
+    use crate::error::WizardError;
+    use crate::simple_api::{compress_if_beneficial, decompress_data, try_decompress_data};
+
+    fn noise(len: usize) -> Vec<u8> {
+        let mut state = 0x9E37_79B9u32;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_outcome_follows_threshold() {
+        let text = "the quick brown fox jumps over the lazy dog. ".repeat(40);
+        let compressed = compress_if_beneficial(&text, 1.5);
+        assert!(compressed.is_compressed());
+        assert_eq!(decompress_data(&compressed), text.as_bytes());
+
+        let random = noise(2000);
+        let stored = compress_if_beneficial(&random, 1.05);
+        assert!(!stored.is_compressed());
+        assert_eq!(decompress_data(&stored), random);
+
+        // Порог выше любого достижимого выигрыша — всегда хранение
+        let strict = compress_if_beneficial(&text, 1000.0);
+        assert!(matches!(strict, CompressOutcome::Stored(_)));
+        assert_eq!(decompress_data(strict.into_bytes()), text.as_bytes());
+
+        assert_eq!(decompress_data(compress_if_beneficial(b"", 1.0)), b"");
+    }
+
+    #[test]
+    fn test_stored_frame_limits() {
+        let archive = StoredEngine
+            .compress(&noise(500), &EngineConfig::default())
+            .to_bytes();
+        assert!(matches!(
+            try_decompress_data(
+                &archive,
+                &DecodeOptions::default().with_max_output_bytes(100)
+            ),
+            Err(WizardError::LimitExceeded { .. })
+        ));
+
+        let frame = Frame::from_bytes(&archive).unwrap();
+        assert_eq!(StoredEngine.decompress(&frame), noise(500));
+    }
+}
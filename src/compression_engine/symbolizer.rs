@@ -0,0 +1,329 @@
+//! Символизаторы 🔤
+//!
+//! Символизатор превращает байты в поток символов и модель, нужную для
+//! обратного превращения (словарь, шаблоны строк, ключи JSON).
+//! [`SymbolizerEngine`] оборачивает любой символизатор в движок: символы
+//! кодируются общим арифметическим кодером, кадр пишется в общий
+//! контейнер, а движок регистрируется в
+//! [`EngineRegistry`](super::EngineRegistry) под своим кодом.
+//!
+//! Тело кадра: `[длина модели u32][модель][таблица частот][общая частота][длина u32][битовый поток]`
+//! в раскладке [`artifact_scroll`](crate::format::artifact_scroll); число
+//! символов равно общей частоте.
+
+use super::compression_conjurer::{
+    analyze_symbolic_frequencies, encode_symbol_stream, symbolize_manuscript,
+};
+use super::engine_covenant::{CompressionEngine, EngineConfig, Frame};
+use crate::bit_wizardry::bit_manipulation_spells::CoderPrecision;
+use crate::decompression_oracle::decode_wards::{validate_phrase_codex, DecodeOptions};
+use crate::decompression_oracle::decompression_sage::{
+    decode_symbol_stream, reconstruct_original_manuscript,
+};
+use crate::error::{WizardError, WizardResult};
+use crate::format::artifact_scroll::{
+    check_body_header, read_coded_stream, read_frequency_section, read_grimoire_sections,
+    write_coded_stream, write_frequency_section, write_grimoire_sections, ScrollCursor,
+};
+use crate::format::container_runes::{ArchiveHeader, EngineKind, ModelKind};
+
+/// Символы и модель для их восстановления 🔤
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Symbolization {
+    /// Поток символов
+    pub symbols: Vec<u32>,
+    /// Модель символизатора в его собственной раскладке
+    pub model: Vec<u8>,
+}
+
+/// Разбиение байтов на символы 🔌
+///
+/// Каждый символ должен восстанавливаться хотя бы в один байт: декодер
+/// ограничивает число символов лимитом размера результата.
+pub trait Symbolizer: Send + Sync {
+    /// Человекочитаемое имя
+    fn name(&self) -> &'static str;
+
+    /// Превращает данные в символы и модель
+    fn symbolize(&self, data: &[u8], config: &EngineConfig) -> Symbolization;
+
+    /// Проверяемое восстановление байтов (не паникует на любых символах и модели)
+    fn try_reconstruct(
+        &self,
+        symbols: &[u32],
+        model: &[u8],
+        options: &DecodeOptions,
+    ) -> WizardResult<Vec<u8>>;
+}
+
+/// Встроенный символизатор: байты, слова словаря и фразы 📚
+///
+/// Модель — словарь и таблица фраз в раскладке архива.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WordSymbolizer;
+
+impl Symbolizer for WordSymbolizer {
+    fn name(&self) -> &'static str {
+        "words"
+    }
+
+    fn symbolize(&self, data: &[u8], config: &EngineConfig) -> Symbolization {
+        let (symbols, word_grimoire, phrase_codex) = symbolize_manuscript(data, config);
+        let mut model = Vec::new();
+        write_grimoire_sections(&mut model, &word_grimoire, &phrase_codex);
+        Symbolization { symbols, model }
+    }
+
+    fn try_reconstruct(
+        &self,
+        symbols: &[u32],
+        model: &[u8],
+        options: &DecodeOptions,
+    ) -> WizardResult<Vec<u8>> {
+        let mut cursor = ScrollCursor::new(model, 0);
+        let (word_grimoire, phrase_codex) = read_grimoire_sections(
+            &mut cursor,
+            &ArchiveHeader::current(ModelKind::StaticOrder0, None),
+            options,
+        )?;
+        if cursor.remaining() != 0 {
+            return Err(WizardError::CorruptPhraseTable("байты после таблицы фраз"));
+        }
+        validate_phrase_codex(&phrase_codex, word_grimoire.len())?;
+        reconstruct_original_manuscript(symbols, &word_grimoire, &phrase_codex, options)
+    }
+}
+
+/// Движок из символизатора и общего арифметического кодера ⚙️
+#[derive(Debug, Clone, Copy)]
+pub struct SymbolizerEngine<S> {
+    engine_kind: EngineKind,
+    symbolizer: S,
+}
+
+impl<S: Symbolizer> SymbolizerEngine<S> {
+    /// Движок с кодом `engine_kind` (для сторонних — `EngineKind::Custom(128..=255)`)
+    pub fn new(engine_kind: EngineKind, symbolizer: S) -> Self {
+        Self {
+            engine_kind,
+            symbolizer,
+        }
+    }
+
+    /// Символизатор движка
+    pub fn symbolizer(&self) -> &S {
+        &self.symbolizer
+    }
+}
+
+impl<S: Symbolizer> CompressionEngine for SymbolizerEngine<S> {
+    fn engine_kind(&self) -> EngineKind {
+        self.engine_kind
+    }
+
+    fn name(&self) -> &'static str {
+        self.symbolizer.name()
+    }
+
+    fn compress(&self, data: &[u8], config: &EngineConfig) -> Frame {
+        let Symbolization { symbols, model } = self.symbolizer.symbolize(data, config);
+        let analysis = analyze_symbolic_frequencies(&symbols)
+            .expect("поток больше точности кодера: разбейте вход на блоки");
+        let compressed_bit_stream = encode_symbol_stream(
+            &symbols,
+            &analysis.frequency_entries,
+            analysis.total_frequency_mass,
+            CoderPrecision::default(),
+        )
+        .expect("таблица частот построена с проверками");
+
+        let mut body = Vec::with_capacity(4 + model.len() + compressed_bit_stream.len() + 64);
+        body.extend_from_slice(&(model.len() as u32).to_le_bytes());
+        body.extend_from_slice(&model);
+        write_frequency_section(&mut body, &analysis.frequency_entries);
+        write_coded_stream(
+            &mut body,
+            analysis.total_frequency_mass,
+            &compressed_bit_stream,
+        );
+
+        Frame {
+            header: ArchiveHeader::current(ModelKind::StaticOrder0, None)
+                .with_engine(self.engine_kind),
+            body,
+        }
+    }
+
+    fn try_decompress(&self, frame: &Frame, options: &DecodeOptions) -> WizardResult<Vec<u8>> {
+        try_unweave_symbolized_body(
+            &frame.header,
+            &frame.body,
+            self.engine_kind,
+            &self.symbolizer,
+            options,
+        )
+    }
+}
+
+/// Проверяемая распаковка тела кадра символизатора 🛡️
+pub(crate) fn try_unweave_symbolized_body(
+    header: &ArchiveHeader,
+    body: &[u8],
+    engine_kind: EngineKind,
+    symbolizer: &impl Symbolizer,
+    options: &DecodeOptions,
+) -> WizardResult<Vec<u8>> {
+    check_body_header(header, engine_kind)?;
+    if header.alphabet_map.is_some() {
+        return Err(WizardError::CorruptFrequencyTable(
+            "атлас алфавита не применяется к символизаторам",
+        ));
+    }
+
+    let mut cursor = ScrollCursor::new(body, 0);
+    let model_len = cursor.read_u32()? as usize;
+    let model = cursor.read_slice(model_len)?;
+    let frequency_codex = read_frequency_section(&mut cursor, options)?;
+    let (total_frequency, compressed_bit_stream) = read_coded_stream(&mut cursor)?;
+
+    // Каждый символ дает хотя бы один байт результата
+    options.reserve_output(total_frequency)?;
+    let symbols = decode_symbol_stream(
+        compressed_bit_stream,
+        &frequency_codex,
+        total_frequency,
+        total_frequency,
+        header.coder_precision(),
+    )?;
+    symbolizer.try_reconstruct(&symbols, model, options)
+}
+
+/// Тесты символизаторов 🎯
+#[cfg(test)]
+mod symbolizer_tests {
+    use super::*;
+    use crate::compression_engine::engine_registry::EngineRegistry;
+    use std::collections::HashMap;
+
+    /// Символизатор логов: повторяющиеся строки становятся шаблонами
+    ///
+    /// Символ `256 + i` — строка `i` модели, остальные символы — байты.
+    /// Модель: строки через `\0`.
+    struct LineTemplateSymbolizer;
+
+    impl Symbolizer for LineTemplateSymbolizer {
+        fn name(&self) -> &'static str {
+            "line-templates"
+        }
+
+        fn symbolize(&self, data: &[u8], _config: &EngineConfig) -> Symbolization {
+            let lines: Vec<&[u8]> = data.split_inclusive(|&byte| byte == b'\n').collect();
+            let mut counts: HashMap<&[u8], usize> = HashMap::new();
+            for &line in &lines {
+                *counts.entry(line).or_insert(0) += 1;
+            }
+            let mut templates: Vec<&[u8]> = counts
+                .into_iter()
+                .filter(|&(line, count)| count > 1 && !line.contains(&0))
+                .map(|(line, _)| line)
+                .collect();
+            templates.sort_unstable();
+
+            let mut symbols = Vec::new();
+            for line in lines {
+                match templates.binary_search(&line) {
+                    Ok(index) => symbols.push(256 + index as u32),
+                    Err(_) => symbols.extend(line.iter().map(|&byte| byte as u32)),
+                }
+            }
+            Symbolization {
+                symbols,
+                model: templates.join(&0),
+            }
+        }
+
+        fn try_reconstruct(
+            &self,
+            symbols: &[u32],
+            model: &[u8],
+            options: &DecodeOptions,
+        ) -> WizardResult<Vec<u8>> {
+            let templates: Vec<&[u8]> = if model.is_empty() {
+                Vec::new()
+            } else {
+                model.split(|&byte| byte == 0).collect()
+            };
+            let mut restored = Vec::new();
+            for &symbol in symbols {
+                match symbol {
+                    0..=255 => restored.push(symbol as u8),
+                    _ => restored.extend_from_slice(
+                        templates
+                            .get(symbol as usize - 256)
+                            .ok_or(WizardError::CorruptPayload)?,
+                    ),
+                }
+                options.check_output(restored.len() as u64)?;
+            }
+            Ok(restored)
+        }
+    }
+
+    fn sample_log() -> Vec<u8> {
+        let mut log = String::new();
+        for request in 0..30 {
+            log.push_str("GET /health 200 OK\n");
+            log.push_str(&format!("GET /users/{request} 200 OK\n"));
+        }
+        log.into_bytes()
+    }
+
+    #[test]
+    fn test_word_symbolizer_engine_roundtrip() {
+        let engine = SymbolizerEngine::new(EngineKind::Custom(140), WordSymbolizer);
+        let data = b"the wizard and the owl and the wand and the hat";
+        let frame = engine.compress(data, &EngineConfig::default());
+
+        assert_eq!(
+            engine
+                .try_decompress(&frame, &DecodeOptions::default())
+                .unwrap(),
+            data
+        );
+        assert_eq!(engine.name(), "words");
+
+        let empty = engine.compress(b"", &EngineConfig::default());
+        assert!(engine.decompress(&empty).is_empty());
+    }
+
+    #[test]
+    fn test_custom_symbolizer_in_registry() {
+        let mut registry = EngineRegistry::default();
+        registry.register(Box::new(SymbolizerEngine::new(
+            EngineKind::Custom(141),
+            LineTemplateSymbolizer,
+        )));
+
+        let log = sample_log();
+        let frame = registry
+            .compress(EngineKind::Custom(141), &log, &EngineConfig::default())
+            .unwrap();
+        let restored = registry
+            .try_decompress(frame.to_bytes(), &DecodeOptions::default())
+            .unwrap();
+        assert_eq!(restored, log);
+
+        // Кадр чужого движка и обрезанное тело отклоняются
+        let words = SymbolizerEngine::new(EngineKind::Custom(142), WordSymbolizer);
+        assert_eq!(
+            words.try_decompress(&frame, &DecodeOptions::default()),
+            Err(WizardError::UnsupportedEngine(141))
+        );
+        let mut truncated = frame.clone();
+        truncated.body.truncate(3);
+        assert!(matches!(
+            registry.try_decompress(truncated.to_bytes(), &DecodeOptions::default()),
+            Err(WizardError::Truncated { .. })
+        ));
+    }
+}
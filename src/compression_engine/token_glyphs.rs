@@ -0,0 +1,222 @@
+//! Глифы токенов: сжатие потоков целых символов 🔢
+//!
+//! Наборы данных языковых моделей хранятся уже разбитыми на токены: поток
+//! id словаря BPE, а не байтов. Перевод таких id в байты и обратно
+//! заставляет байтовую символизацию заново искать структуру, которую
+//! токенизатор уже нашел. [`compress_symbols`] кодирует id вызывающего
+//! напрямую — статической моделью нулевого порядка, той же таблицей
+//! частот и тем же арифметическим кодером, что и основной движок, но без
+//! словаря, фраз и разбора байтов.
+//!
+//! Раскладка: `[TOKEN_MAGIC][TOKEN_FORMAT_VERSION u8][размер алфавита u32]
+//! [число символов u64][таблица частот][общая частота u64][длина потока u32]
+//! [битовый поток]`. Таблица частот — та же, что в архивах: записи
+//! `[символ u32][частота u64][начало u64]` только встреченных символов.
+//! Поток токенов — не архив байтов: его распаковывает только
+//! [`decompress_symbols`].
+
+use super::compression_conjurer::{analyze_symbolic_frequencies, encode_symbol_stream};
+use crate::bit_wizardry::bit_manipulation_spells::CoderPrecision;
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::decompression_oracle::decompression_sage::decode_symbols_while;
+use crate::error::{WizardError, WizardResult};
+use crate::format::artifact_scroll::{
+    check_length_field, read_coded_stream, read_frequency_section, write_coded_stream,
+    write_frequency_section, ScrollCursor,
+};
+use crate::format::artifact_view::CodexView;
+
+/// Сигнатура потока токенов
+pub const TOKEN_MAGIC: [u8; 4] = *b"ACWT";
+
+/// Версия раскладки потока токенов
+pub const TOKEN_FORMAT_VERSION: u8 = 1;
+
+/// Сжимает поток id из алфавита `0..alphabet_size` 🔢
+///
+/// Паникует на id вне алфавита и на потоках больше точности кодера;
+/// проверяемый вариант — [`try_compress_symbols`].
+///
+/// ```
+/// use arithmetic_compression_wizard::compression_engine::{compress_symbols, decompress_symbols};
+///
+/// // Токены BPE из словаря на 50 257 id
+/// let tokens: Vec<u32> = [464, 2068, 7586, 21831, 18045, 625, 262, 16931, 3290, 13]
+///     .repeat(200);
+/// let packed = compress_symbols(&tokens, 50_257);
+/// assert!(packed.len() < tokens.len());
+/// assert_eq!(decompress_symbols(&packed), tokens);
+/// ```
+pub fn compress_symbols(symbols: &[u32], alphabet_size: u32) -> Vec<u8> {
+    try_compress_symbols(symbols, alphabet_size).expect("поток токенов не сжимается")
+}
+
+/// Проверяемое сжатие потока id
+///
+/// id не меньше `alphabet_size` дает [`WizardError::InvalidDistribution`],
+/// таблица частот больше точности кодера —
+/// [`WizardError::LimitExceeded`], битовый поток длиннее поля `u32` —
+/// [`WizardError::InputTooLarge`]. Длинные потоки сжимайте частями.
+pub fn try_compress_symbols(symbols: &[u32], alphabet_size: u32) -> WizardResult<Vec<u8>> {
+    if symbols.iter().any(|&symbol| symbol >= alphabet_size) {
+        return Err(WizardError::InvalidDistribution("символ вне алфавита"));
+    }
+
+    let frequencies = analyze_symbolic_frequencies(symbols)?;
+    let bit_stream = encode_symbol_stream(
+        symbols,
+        &frequencies.frequency_entries,
+        frequencies.total_frequency_mass,
+        CoderPrecision::default(),
+    )?;
+    check_length_field(bit_stream.len())?;
+
+    let mut packed = Vec::with_capacity(
+        TOKEN_MAGIC.len() + 17 + frequencies.frequency_entries.len() * 20 + bit_stream.len() + 16,
+    );
+    packed.extend_from_slice(&TOKEN_MAGIC);
+    packed.push(TOKEN_FORMAT_VERSION);
+    packed.extend_from_slice(&alphabet_size.to_le_bytes());
+    packed.extend_from_slice(&(symbols.len() as u64).to_le_bytes());
+    write_frequency_section(&mut packed, &frequencies.frequency_entries);
+    write_coded_stream(&mut packed, frequencies.total_frequency_mass, &bit_stream);
+    Ok(packed)
+}
+
+/// Распаковывает поток [`compress_symbols`]
+///
+/// Паникует на поврежденном потоке; проверяемый вариант —
+/// [`try_decompress_symbols`].
+pub fn decompress_symbols(packed: impl AsRef<[u8]>) -> Vec<u32> {
+    try_decompress_symbols(packed, &DecodeOptions::default())
+        .expect("не удалось распаковать поток токенов")
+}
+
+/// Проверяемая распаковка потока id
+///
+/// Лимит результата [`DecodeOptions`] считается в байтах: четыре на id.
+/// Чужая сигнатура дает [`WizardError::UnrecognizedHeader`], новая
+/// версия — [`WizardError::UnsupportedVersion`], символ таблицы вне
+/// алфавита — [`WizardError::CorruptFrequencyTable`].
+pub fn try_decompress_symbols(
+    packed: impl AsRef<[u8]>,
+    options: &DecodeOptions,
+) -> WizardResult<Vec<u32>> {
+    let packed = packed.as_ref();
+    let mut cursor = ScrollCursor::new(packed, 0);
+    if cursor.read_slice(TOKEN_MAGIC.len())? != TOKEN_MAGIC {
+        return Err(WizardError::UnrecognizedHeader);
+    }
+    let version = cursor.read_slice(1)?[0];
+    if version != TOKEN_FORMAT_VERSION {
+        return Err(WizardError::UnsupportedVersion(version));
+    }
+    let alphabet_size = cursor.read_u32()?;
+    let symbol_count = cursor.read_u64()?;
+    options.reserve_output(symbol_count.saturating_mul(4))?;
+
+    let frequency_codex = read_frequency_section(&mut cursor, options)?;
+    if (0..frequency_codex.len()).any(|index| frequency_codex.entry(index).0 >= alphabet_size) {
+        return Err(WizardError::CorruptFrequencyTable(
+            "символ таблицы вне алфавита",
+        ));
+    }
+    let (total_frequency_mass, bit_stream) = read_coded_stream(&mut cursor)?;
+
+    let mut symbols = Vec::with_capacity(symbol_count.min(1 << 20) as usize);
+    decode_symbols_while(
+        bit_stream,
+        &frequency_codex,
+        total_frequency_mass,
+        symbol_count,
+        CoderPrecision::default(),
+        |symbol| {
+            symbols.push(symbol);
+            Ok(true)
+        },
+    )?;
+    Ok(symbols)
+}
+
+/// Тесты глифов токенов 🎯
+#[cfg(test)]
+mod token_glyphs_tests {
+    use super::*;
+    use crate::error::LimitKind;
+    use crate::simple_api::{compress_data, try_decompress_data};
+
+    fn tokens() -> Vec<u32> {
+        let mut state = 17u32;
+        (0..50_000)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                // Частые токены и длинный хвост большого словаря
+                match (state >> 16) % 8 {
+                    0..=5 => (state >> 8) % 40,
+                    _ => 100_000 + (state >> 4) % 400,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_symbols_roundtrip_without_bytes() {
+        let tokens = tokens();
+        let packed = compress_symbols(&tokens, 200_000);
+        assert_eq!(&packed[..4], TOKEN_MAGIC);
+        assert_eq!(decompress_symbols(&packed), tokens);
+
+        // Те же id четырьмя байтами каждый сжимаются хуже
+        let as_bytes: Vec<u8> = tokens.iter().flat_map(|id| id.to_le_bytes()).collect();
+        assert!(packed.len() < compress_data(&as_bytes).len());
+
+        for edge in [&[][..], &[0], &[199_999, 199_999, 0]] {
+            assert_eq!(decompress_symbols(compress_symbols(edge, 200_000)), edge);
+        }
+        assert_eq!(
+            try_compress_symbols(&[3, 7], 7),
+            Err(WizardError::InvalidDistribution("символ вне алфавита"))
+        );
+        assert_eq!(decompress_symbols(compress_symbols(&[], 0)), []);
+    }
+
+    #[test]
+    fn test_damaged_token_streams_are_rejected() {
+        let options = DecodeOptions::default();
+        let packed = compress_symbols(&tokens(), 200_000);
+
+        // Поток токенов не архив байтов, и наоборот
+        assert!(try_decompress_data(&packed, &options).is_err());
+        assert_eq!(
+            try_decompress_symbols(compress_data(b"bytes"), &options),
+            Err(WizardError::UnrecognizedHeader)
+        );
+
+        let mut newer = packed.clone();
+        newer[4] = TOKEN_FORMAT_VERSION + 1;
+        assert_eq!(
+            try_decompress_symbols(&newer, &options),
+            Err(WizardError::UnsupportedVersion(TOKEN_FORMAT_VERSION + 1))
+        );
+
+        // Алфавит меньше символов таблицы
+        let mut shrunk = packed.clone();
+        shrunk[5..9].copy_from_slice(&50u32.to_le_bytes());
+        assert!(matches!(
+            try_decompress_symbols(&shrunk, &options),
+            Err(WizardError::CorruptFrequencyTable(_))
+        ));
+
+        assert!(matches!(
+            try_decompress_symbols(&packed, &options.clone().with_max_output_bytes(100)),
+            Err(WizardError::LimitExceeded {
+                kind: LimitKind::OutputBytes,
+                requested: 200_000,
+                ..
+            })
+        ));
+        for cut in [3, 12, 40, packed.len() - 1] {
+            assert!(try_decompress_symbols(&packed[..cut], &options).is_err());
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//! Широкие символы: пары байтов как один символ 🀄
+//!
+//! В тексте UTF-16 и 16-битных отсчетах датчиков байты идут парами:
+//! байтовая модель разрывает пару и теряет связь старшего байта с младшим
+//! (у кириллицы в UTF-16 LE каждый второй байт — `0x04`). Символизатор
+//! читает вход как `u16` в порядке little-endian, и модель нулевого
+//! порядка видит символ целиком.
+//!
+//! Символы: `0–65535` — пара байтов. Модель: хвостовой байт входа
+//! нечетной длины (пустая для четной).
+
+use super::engine_covenant::EngineConfig;
+use super::symbolizer::{Symbolization, Symbolizer};
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::error::{WizardError, WizardResult};
+
+/// Символизатор пар байтов little-endian 🀄
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WideSymbolizer;
+
+impl Symbolizer for WideSymbolizer {
+    fn name(&self) -> &'static str {
+        "wide16"
+    }
+
+    fn symbolize(&self, data: &[u8], _config: &EngineConfig) -> Symbolization {
+        let pairs = data.chunks_exact(2);
+        let model = pairs.remainder().to_vec();
+        let symbols = pairs
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]) as u32)
+            .collect();
+        Symbolization { symbols, model }
+    }
+
+    fn try_reconstruct(
+        &self,
+        symbols: &[u32],
+        model: &[u8],
+        options: &DecodeOptions,
+    ) -> WizardResult<Vec<u8>> {
+        if model.len() > 1 {
+            return Err(WizardError::CorruptPayload);
+        }
+        let restored_len = (symbols.len() as u64)
+            .saturating_mul(2)
+            .saturating_add(model.len() as u64);
+        options.reserve_output(restored_len)?;
+
+        let mut restored = Vec::with_capacity(restored_len as usize);
+        for &symbol in symbols {
+            let pair = u16::try_from(symbol).map_err(|_| WizardError::CorruptPayload)?;
+            restored.extend_from_slice(&pair.to_le_bytes());
+        }
+        restored.extend_from_slice(model);
+        Ok(restored)
+    }
+}
+
+/// Тесты широких символов 🎯
+#[cfg(test)]
+mod wide_glyphs_tests {
+    use super::*;
+    use crate::compression_engine::engine_covenant::StructuredMode;
+    use crate::simple_api::{compress_data, compress_structured, decompress_data};
+
+    fn utf16_le(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    #[test]
+    fn test_wide_mode_beats_bytes_on_utf16() {
+        let text =
+            utf16_le(&"Съешь же ещё этих мягких французских булок, да выпей чаю. ".repeat(60));
+        let wide = compress_structured(&text, StructuredMode::Wide16);
+        assert!(wide.len() < compress_data(&text).len());
+        assert_eq!(decompress_data(&wide), text);
+
+        // Пилообразный сигнал 16-битного датчика
+        let samples: Vec<u8> = (0..4000u32)
+            .flat_map(|step| (1000 + (step % 50) as u16 * 37).to_le_bytes())
+            .collect();
+        let wide = compress_structured(&samples, StructuredMode::Wide16);
+        assert!(wide.len() < compress_data(&samples).len());
+        assert_eq!(decompress_data(&wide), samples);
+    }
+
+    #[test]
+    fn test_odd_tail_survives() {
+        for data in [&b""[..], b"x", b"ab", b"abc", &[0xFF, 0xFE, 0x00]] {
+            let archive = compress_structured(data, StructuredMode::Wide16);
+            assert_eq!(decompress_data(&archive), data);
+        }
+    }
+
+    #[test]
+    fn test_damaged_wide_models_rejected() {
+        let options = DecodeOptions::default();
+        assert_eq!(
+            WideSymbolizer.try_reconstruct(&[1], b"xy", &options),
+            Err(WizardError::CorruptPayload)
+        );
+        assert_eq!(
+            WideSymbolizer.try_reconstruct(&[0x1_0000], b"", &options),
+            Err(WizardError::CorruptPayload)
+        );
+        assert!(matches!(
+            WideSymbolizer.try_reconstruct(&[7; 10], b"z", &options.with_max_output_bytes(20)),
+            Err(WizardError::LimitExceeded { .. })
+        ));
+    }
+}
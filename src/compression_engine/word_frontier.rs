@@ -0,0 +1,239 @@
+//! Границы слов словаря 🧱
+//!
+//! Поиск слов и подстановка их в текст должны одинаково понимать, где
+//! кончается слово: иначе поиск считает «don't» одним словом, а
+//! подстановка находит «don» внутри него. [`BoundaryPolicy`] решает, какие
+//! байты образуют слово, и обе фазы спрашивают одну и ту же политику из
+//! [`EngineConfig`](super::EngineConfig).
+//!
+//! Встроенные политики:
+//! - [`AsciiBoundary`] — латинские буквы и апостроф (по умолчанию);
+//! - [`UnicodeBoundary`] — буквы любого алфавита в UTF-8 и апостроф;
+//! - [`NoBoundary`] — слова ищутся как в ASCII, но подставляются и внутри
+//!   других слов (корни и составные слова).
+
+use std::fmt;
+use std::sync::Arc;
+
+/// Правило границ слов 🧱
+///
+/// ```
+/// use arithmetic_compression_wizard::compression_engine::{BoundaryPolicy, UnicodeBoundary};
+///
+/// let text = "волшебник".as_bytes();
+/// assert!(UnicodeBoundary.is_word_byte(text, 1));
+/// assert!(!UnicodeBoundary.is_word_byte(b"owl post", 3));
+/// ```
+pub trait BoundaryPolicy: Send + Sync {
+    /// Имя политики; политики с одним именем считаются равными
+    fn name(&self) -> &'static str;
+
+    /// Байт `text[position]` принадлежит слову
+    fn is_word_byte(&self, text: &[u8], position: usize) -> bool;
+
+    /// Слово словаря подставляется только целиком, а не внутри другого
+    fn requires_boundaries(&self) -> bool {
+        true
+    }
+}
+
+/// Латинские буквы и апостроф 🔤
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AsciiBoundary;
+
+impl BoundaryPolicy for AsciiBoundary {
+    fn name(&self) -> &'static str {
+        "ascii"
+    }
+
+    fn is_word_byte(&self, text: &[u8], position: usize) -> bool {
+        text[position].is_ascii_alphabetic() || text[position] == b'\''
+    }
+}
+
+/// Буквы любого алфавита в UTF-8 и апостроф 🌍
+///
+/// Байт принадлежит слову, если символ UTF-8, в который он входит, —
+/// буква. Испорченные последовательности словом не считаются.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnicodeBoundary;
+
+impl BoundaryPolicy for UnicodeBoundary {
+    fn name(&self) -> &'static str {
+        "unicode"
+    }
+
+    fn is_word_byte(&self, text: &[u8], position: usize) -> bool {
+        if text[position].is_ascii() {
+            return AsciiBoundary.is_word_byte(text, position);
+        }
+
+        // Первый байт символа не дальше трех байтов назад
+        let Some(lead) = (position.saturating_sub(3)..=position)
+            .rev()
+            .find(|&index| text[index] & 0xC0 != 0x80)
+        else {
+            return false;
+        };
+        let width = match text[lead] {
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF7 => 4,
+            _ => return false,
+        };
+        lead + width > position
+            && text
+                .get(lead..lead + width)
+                .and_then(|glyph| std::str::from_utf8(glyph).ok())
+                .and_then(|glyph| glyph.chars().next())
+                .is_some_and(char::is_alphabetic)
+    }
+}
+
+/// Без границ: слова подставляются в любом месте текста 🧩
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoBoundary;
+
+impl BoundaryPolicy for NoBoundary {
+    fn name(&self) -> &'static str {
+        "none"
+    }
+
+    fn is_word_byte(&self, text: &[u8], position: usize) -> bool {
+        AsciiBoundary.is_word_byte(text, position)
+    }
+
+    fn requires_boundaries(&self) -> bool {
+        false
+    }
+}
+
+/// Политика границ в настройках движка 🧱
+///
+/// Сравнивается и печатается по имени политики.
+#[derive(Clone)]
+pub struct WordBoundaries(Arc<dyn BoundaryPolicy>);
+
+impl WordBoundaries {
+    /// Обертка над политикой
+    pub fn new(policy: impl BoundaryPolicy + 'static) -> Self {
+        Self(Arc::new(policy))
+    }
+
+    /// Политика
+    pub fn policy(&self) -> &dyn BoundaryPolicy {
+        self.0.as_ref()
+    }
+
+    /// Слова текста для поиска словаря: непрерывные серии байтов слова
+    pub(crate) fn words<'t>(&'t self, text: &'t [u8]) -> impl Iterator<Item = &'t [u8]> + 't {
+        let mut position = 0;
+        std::iter::from_fn(move || {
+            while position < text.len() && !self.0.is_word_byte(text, position) {
+                position += 1;
+            }
+            if position == text.len() {
+                return None;
+            }
+            let word_start = position;
+            while position < text.len() && self.0.is_word_byte(text, position) {
+                position += 1;
+            }
+            Some(&text[word_start..position])
+        })
+    }
+
+    /// Слово словаря может начаться в `position`
+    pub(crate) fn is_word_start(&self, text: &[u8], position: usize) -> bool {
+        self.0.is_word_byte(text, position)
+            && (!self.0.requires_boundaries()
+                || position == 0
+                || !self.0.is_word_byte(text, position - 1))
+    }
+
+    /// Слово словаря может закончиться перед `end`
+    pub(crate) fn is_word_end(&self, text: &[u8], end: usize) -> bool {
+        !self.0.requires_boundaries() || end >= text.len() || !self.0.is_word_byte(text, end)
+    }
+}
+
+impl Default for WordBoundaries {
+    fn default() -> Self {
+        Self::new(AsciiBoundary)
+    }
+}
+
+impl fmt::Debug for WordBoundaries {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_tuple("WordBoundaries")
+            .field(&self.0.name())
+            .finish()
+    }
+}
+
+impl PartialEq for WordBoundaries {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.name() == other.0.name()
+    }
+}
+
+impl Eq for WordBoundaries {}
+
+/// Тесты границ слов 🎯
+#[cfg(test)]
+mod word_frontier_tests {
+    use super::*;
+
+    fn words_of(boundaries: &WordBoundaries, text: &str) -> Vec<String> {
+        boundaries
+            .words(text.as_bytes())
+            .map(|word| String::from_utf8(word.to_vec()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_apostrophe_is_part_of_word_on_both_sides() {
+        let ascii = WordBoundaries::default();
+        let text = b"don't stop";
+        assert_eq!(words_of(&ascii, "don't stop"), ["don't", "stop"]);
+        // «don» не кончается перед апострофом, «t» не начинается после него
+        assert!(ascii.is_word_start(text, 0) && !ascii.is_word_end(text, 3));
+        assert!(!ascii.is_word_start(text, 4));
+        assert!(ascii.is_word_end(text, 5) && ascii.is_word_end(text, 10));
+    }
+
+    #[test]
+    fn test_unicode_letters_form_words() {
+        let unicode = WordBoundaries::new(UnicodeBoundary);
+        assert_eq!(
+            words_of(&unicode, "старый маг, naïve owl — 42 λόγος"),
+            ["старый", "маг", "naïve", "owl", "λόγος"]
+        );
+        assert_eq!(
+            words_of(&WordBoundaries::default(), "naïve маг"),
+            ["na", "ve"]
+        );
+        // Испорченный UTF-8 не считается буквой
+        assert!(unicode.words(b"\xD0\xD0\xB0").eq([&b"\xD0\xB0"[..]]));
+    }
+
+    #[test]
+    fn test_no_boundary_matches_inside_words() {
+        let anywhere = WordBoundaries::new(NoBoundary);
+        let text = b"wizardry";
+        assert!(anywhere.is_word_start(text, 3) && anywhere.is_word_end(text, 6));
+        assert!(!anywhere.is_word_start(b"a wand", 1));
+        assert_eq!(words_of(&anywhere, "wizard-ry"), ["wizard", "ry"]);
+
+        assert_eq!(
+            WordBoundaries::new(NoBoundary),
+            WordBoundaries::new(NoBoundary)
+        );
+        assert_ne!(WordBoundaries::new(NoBoundary), WordBoundaries::default());
+        assert_eq!(
+            format!("{:?}", WordBoundaries::new(UnicodeBoundary)),
+            "WordBoundaries(\"unicode\")"
+        );
+    }
+}
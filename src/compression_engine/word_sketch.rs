@@ -0,0 +1,197 @@
+//! Эскиз частот слов 🗒️
+//!
+//! Точный подсчет слов держит в памяти каждое различное слово входа —
+//! на больших корпусах это сотни мегабайт. Приближенный режим считает
+//! частоты в count-min sketch фиксированного размера и хранит только
+//! `top_k` самых частых кандидатов. Оценка частоты никогда не меньше
+//! истинной, а завышение ограничено числом слов, деленным на ширину эскиза.
+//!
+//! Память режима: `width × depth` счетчиков u64 плюс `top_k` кандидатов
+//! (срезы исходного буфера, без копирования).
+
+use crate::bit_wizardry::checksum_runes::rune_hash;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+/// Во сколько раз куча может перерасти число кандидатов устаревшими записями
+const STALE_HEAP_FACTOR: usize = 4;
+
+/// Способ подсчета слов при поиске словаря ⚙️
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WordCounting {
+    /// Точный подсчет всех различных слов
+    #[default]
+    Exact,
+    /// Приближенный подсчет с ограниченной памятью
+    Sketch(SketchParams),
+}
+
+/// Параметры приближенного подсчета 📐
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SketchParams {
+    /// Счетчиков в строке эскиза
+    pub width: usize,
+    /// Строк эскиза (независимых хешей)
+    pub depth: usize,
+    /// Сколько самых частых слов отслеживается
+    pub top_k: usize,
+}
+
+impl Default for SketchParams {
+    /// 64 Ки × 4 счетчика (2 МиБ) и 4096 кандидатов
+    fn default() -> Self {
+        Self {
+            width: 1 << 16,
+            depth: 4,
+            top_k: 4096,
+        }
+    }
+}
+
+/// Count-min sketch 🗒️
+struct CountMinSketch {
+    counters: Vec<u64>,
+    width: usize,
+    depth: usize,
+}
+
+impl CountMinSketch {
+    fn new(width: usize, depth: usize) -> Self {
+        Self {
+            counters: vec![0; width * depth],
+            width,
+            depth,
+        }
+    }
+
+    /// Учитывает вхождение слова и возвращает новую оценку его частоты
+    fn increment(&mut self, word: &[u8]) -> u64 {
+        // Двойное хеширование: строка `row` использует h1 + row × h2
+        let primary = rune_hash(word);
+        let secondary = primary.rotate_left(32) | 1;
+
+        let mut estimate = u64::MAX;
+        for row in 0..self.depth {
+            let slot = primary.wrapping_add((row as u64).wrapping_mul(secondary));
+            let counter =
+                &mut self.counters[row * self.width + (slot % self.width as u64) as usize];
+            *counter += 1;
+            estimate = estimate.min(*counter);
+        }
+        estimate
+    }
+}
+
+/// Приближенные частоты самых частых слов
+///
+/// Возвращает не больше `top_k` пар (слово, оценка частоты) в произвольном порядке.
+pub(crate) fn sketch_word_counts<'a>(
+    words: impl IntoIterator<Item = &'a [u8]>,
+    params: &SketchParams,
+) -> Vec<(&'a [u8], u64)> {
+    let top_k = params.top_k.max(1);
+    let mut sketch = CountMinSketch::new(params.width.max(1), params.depth.max(1));
+    let mut candidates: HashMap<&[u8], u64> = HashMap::with_capacity(top_k + 1);
+    // Минимальная куча кандидатов; записи с устаревшей оценкой пропускаются
+    let mut eviction_heap: BinaryHeap<Reverse<(u64, &[u8])>> = BinaryHeap::new();
+
+    for word in words {
+        let estimate = sketch.increment(word);
+
+        if let Some(count) = candidates.get_mut(word) {
+            *count = estimate;
+        } else if candidates.len() < top_k {
+            candidates.insert(word, estimate);
+        } else {
+            // Оценки только растут, поэтому устаревшие записи меньше текущих
+            while let Some(&Reverse((count, weakest))) = eviction_heap.peek() {
+                if candidates.get(weakest) == Some(&count) {
+                    break;
+                }
+                eviction_heap.pop();
+            }
+            match eviction_heap.peek() {
+                Some(&Reverse((weakest_count, weakest))) if estimate > weakest_count => {
+                    eviction_heap.pop();
+                    candidates.remove(weakest);
+                    candidates.insert(word, estimate);
+                }
+                _ => continue,
+            }
+        }
+        eviction_heap.push(Reverse((estimate, word)));
+
+        if eviction_heap.len() > STALE_HEAP_FACTOR * top_k {
+            eviction_heap = candidates
+                .iter()
+                .map(|(&word, &count)| Reverse((count, word)))
+                .collect();
+        }
+    }
+
+    candidates.into_iter().collect()
+}
+
+/// Тесты эскиза частот 🎯
+#[cfg(test)]
+mod word_sketch_tests {
+    use super::*;
+
+    /// Поток слов с убывающими частотами: слово `i` встречается `200 / (i + 1)` раз
+    fn skewed_words() -> Vec<Vec<u8>> {
+        let mut words = Vec::new();
+        for rank in 0..200 {
+            for _ in 0..200 / (rank + 1) {
+                words.push(format!("word{rank}").into_bytes());
+            }
+        }
+        // Перемешиваем детерминированно
+        let len = words.len();
+        for index in 0..len {
+            words.swap(index, (index * 7919) % len);
+        }
+        words
+    }
+
+    #[test]
+    fn test_wide_sketch_counts_exactly() {
+        let words = skewed_words();
+        let params = SketchParams {
+            width: 1 << 14,
+            depth: 4,
+            top_k: 1000,
+        };
+        let mut counts = sketch_word_counts(words.iter().map(Vec::as_slice), &params);
+        counts.sort();
+
+        let mut exact: HashMap<&[u8], u64> = HashMap::new();
+        for word in &words {
+            *exact.entry(word.as_slice()).or_insert(0) += 1;
+        }
+        let mut exact: Vec<(&[u8], u64)> = exact.into_iter().collect();
+        exact.sort();
+        assert_eq!(counts, exact);
+    }
+
+    #[test]
+    fn test_narrow_sketch_keeps_heavy_hitters() {
+        let words = skewed_words();
+        let params = SketchParams {
+            width: 64,
+            depth: 3,
+            top_k: 8,
+        };
+        let counts = sketch_word_counts(words.iter().map(Vec::as_slice), &params);
+
+        assert_eq!(counts.len(), 8);
+        for rank in 0..3 {
+            let word = format!("word{rank}");
+            let (_, estimate) = counts
+                .iter()
+                .find(|(candidate, _)| *candidate == word.as_bytes())
+                .unwrap_or_else(|| panic!("{word} потеряно"));
+            // Оценка не меньше истинной частоты
+            assert!(*estimate >= 200 / (rank as u64 + 1));
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Гильдия подмастерьев: пул потоков сжатия 🧑‍🔧
+//!
+//! Веб-сервису не нужно заводить поток в каждом обработчике: задания
+//! отдаются [`CompressionPool`], а ответ приходит через канал. Очередь
+//! заданий ограничена — когда подмастерья не успевают, [`submit`]
+//! блокирует отправителя, а [`try_submit`] возвращает данные обратно,
+//! и сервис сам решает, ответить ли `503` или сжать на месте.
+//!
+//! [`submit`]: CompressionPool::submit
+//! [`try_submit`]: CompressionPool::try_submit
+
+use crate::error::WizardResult;
+use crate::simple_api::try_compress_data;
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::{self, JoinHandle};
+
+/// Функция сжатия, которую выполняют подмастерья
+type Compressor = Arc<dyn Fn(&[u8]) -> WizardResult<Vec<u8>> + Send + Sync>;
+
+/// Задание: данные и канал для ответа
+type Job = (Vec<u8>, Sender<WizardResult<Vec<u8>>>);
+
+/// Пул потоков сжатия с ограниченной очередью 🧑‍🔧
+///
+/// ```
+/// use arithmetic_compression_wizard::concurrency::CompressionPool;
+/// use arithmetic_compression_wizard::simple_api::decompress_data;
+///
+/// let pool = CompressionPool::new(2, 16);
+/// let pending = pool.submit(b"the wizard and the owl".to_vec());
+/// let archive = pending.recv().unwrap().unwrap();
+/// assert_eq!(decompress_data(&archive), b"the wizard and the owl");
+/// ```
+///
+/// При удалении пул досчитывает уже принятые задания и ждет потоки.
+#[derive(Debug)]
+pub struct CompressionPool {
+    queue: Option<SyncSender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl CompressionPool {
+    /// Пул из `workers` потоков (не меньше одного) и очередью на
+    /// `queue_depth` ожидающих заданий
+    ///
+    /// При `queue_depth == 0` задание передается из рук в руки: `submit`
+    /// ждет, пока его возьмет свободный поток.
+    pub fn new(workers: usize, queue_depth: usize) -> Self {
+        Self::with_compressor(
+            workers,
+            queue_depth,
+            Arc::new(|data: &[u8]| try_compress_data(data)),
+        )
+    }
+
+    /// Пул с произвольной функцией сжатия
+    fn with_compressor(workers: usize, queue_depth: usize, compressor: Compressor) -> Self {
+        let (queue, jobs) = mpsc::sync_channel::<Job>(queue_depth);
+        let jobs = Arc::new(Mutex::new(jobs));
+        let workers = (0..workers.max(1))
+            .map(|_| {
+                let jobs = Arc::clone(&jobs);
+                let compressor = Arc::clone(&compressor);
+                thread::spawn(move || loop {
+                    // Замок держится только на время получения задания
+                    let job = jobs.lock().unwrap_or_else(PoisonError::into_inner).recv();
+                    let Ok((data, reply)) = job else {
+                        return;
+                    };
+                    // Получатель мог уже уйти — ответ тогда просто не нужен
+                    let _ = reply.send(compressor(&data));
+                })
+            })
+            .collect();
+
+        Self {
+            queue: Some(queue),
+            workers,
+        }
+    }
+
+    /// Число потоков пула
+    pub fn worker_count(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Отдает данные на сжатие, ожидая места в очереди
+    ///
+    /// Возвращает канал, в который придет архив (как от
+    /// [`try_compress_data`]).
+    pub fn submit(&self, data: Vec<u8>) -> Receiver<WizardResult<Vec<u8>>> {
+        let (reply, pending) = mpsc::channel();
+        // Очередь закрывается только при удалении пула
+        let _ = self.queue().send((data, reply));
+        pending
+    }
+
+    /// Отдает данные на сжатие без ожидания
+    ///
+    /// Если очередь полна, данные возвращаются в `Err`.
+    pub fn try_submit(&self, data: Vec<u8>) -> Result<Receiver<WizardResult<Vec<u8>>>, Vec<u8>> {
+        let (reply, pending) = mpsc::channel();
+        match self.queue().try_send((data, reply)) {
+            Ok(()) => Ok(pending),
+            Err(TrySendError::Full((data, _)) | TrySendError::Disconnected((data, _))) => Err(data),
+        }
+    }
+
+    fn queue(&self) -> &SyncSender<Job> {
+        self.queue.as_ref().expect("очередь живет до удаления пула")
+    }
+}
+
+impl Drop for CompressionPool {
+    fn drop(&mut self) {
+        // Закрытая очередь отпускает потоки, когда задания кончатся
+        self.queue.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Тесты гильдии 🎯
+#[cfg(test)]
+mod apprentice_guild_tests {
+    use super::*;
+    use crate::simple_api::decompress_data;
+
+    #[test]
+    fn test_pool_compresses_every_job() {
+        let pool = CompressionPool::new(3, 4);
+        assert_eq!(pool.worker_count(), 3);
+
+        let messages: Vec<Vec<u8>> = (0..20)
+            .map(|index| format!("message {index} from the owl post").into_bytes())
+            .collect();
+        let pending: Vec<_> = messages
+            .iter()
+            .map(|message| pool.submit(message.clone()))
+            .collect();
+        for (message, pending) in messages.iter().zip(pending) {
+            assert_eq!(decompress_data(pending.recv().unwrap().unwrap()), *message);
+        }
+        assert_eq!(CompressionPool::new(0, 0).worker_count(), 1);
+    }
+
+    #[test]
+    fn test_full_queue_pushes_back() {
+        let (started, started_signal) = mpsc::channel();
+        let (release, gate) = mpsc::channel::<()>();
+        let started = Mutex::new(started);
+        let gate = Mutex::new(gate);
+        let pool = CompressionPool::with_compressor(
+            1,
+            1,
+            Arc::new(move |data: &[u8]| {
+                started.lock().unwrap().send(()).unwrap();
+                gate.lock().unwrap().recv().unwrap();
+                Ok(data.to_vec())
+            }),
+        );
+
+        let first = pool.submit(b"first".to_vec());
+        started_signal.recv().unwrap();
+        let second = pool.try_submit(b"second".to_vec()).unwrap();
+        assert_eq!(pool.try_submit(b"third".to_vec()).unwrap_err(), b"third");
+
+        release.send(()).unwrap();
+        release.send(()).unwrap();
+        assert_eq!(first.recv().unwrap().unwrap(), b"first");
+        assert_eq!(second.recv().unwrap().unwrap(), b"second");
+    }
+
+    #[test]
+    fn test_drop_finishes_accepted_jobs() {
+        let pool = CompressionPool::new(1, 8);
+        let pending: Vec<_> = (0..8)
+            .map(|_| pool.submit(b"late scroll".to_vec()))
+            .collect();
+        drop(pool);
+        for pending in pending {
+            assert_eq!(
+                decompress_data(pending.recv().unwrap().unwrap()),
+                b"late scroll"
+            );
+        }
+    }
+}
@@ -0,0 +1,9 @@
+//! Модуль параллельной работы 🧵
+//! Сжатие на пуле потоков для встраивания в серверы
+
+pub mod apprentice_guild;
+pub mod thread_wards;
+
+// Экспорт основных типов
+
+pub use apprentice_guild::CompressionPool; // Пул потоков сжатия с ограниченной очередью
@@ -0,0 +1,164 @@
+//! Обереги потоков: какие типы можно делить между потоками 🛡️
+//!
+//! В библиотеке нет глобального изменяемого состояния, а движки, словари
+//! и параметры — неизменяемые значения. Поэтому:
+//!
+//! - `Send + Sync` — реестры ([`EngineRegistry`], [`DictionaryRegistry`]),
+//!   параметры ([`EngineConfig`], [`DecodeOptions`]), обученные модели
+//!   ([`CompressorHandle`], [`Grimoire`], [`ContextSnapshot`]), артефакты и
+//!   ошибки. Одну ссылку можно отдать всем потокам пула или задачам
+//!   асинхронного исполнителя.
+//! - `Send` — сессии с состоянием: [`BlockStreamWriter`],
+//!   [`BlockStreamReader`], [`ArchiveWriter`], [`ArchiveReader`] (если `Send` их
+//!   внутренний поток), [`AtomicFile`], [`BitMagicReader`], [`ArithmeticEncoder`],
+//!   [`ArithmeticDecoder`], [`ScratchSpace`], [`StreamingAnalyzer`]. Сессию
+//!   можно перенести в другой поток, в том числе между точками `.await`,
+//!   но одновременно с ней работает один владелец.
+//!
+//! Гарантии проверяются при компиляции: тип, переставший быть `Send`
+//! или `Sync`, не соберется.
+
+use crate::bit_wizardry::bit_manipulation_spells::{BitMagicReader, BitMagicWriter};
+use crate::bit_wizardry::checksum_runes::RuneHasher;
+use crate::bit_wizardry::entropy_coder::{ArithmeticDecoder, ArithmeticEncoder};
+use crate::compression_engine::compression_conjurer::CompressionArtifact;
+use crate::compression_engine::compressor_handle::{CompressorHandle, HandleArtifact};
+use crate::compression_engine::context_snapshot::ContextSnapshot;
+use crate::compression_engine::engine_covenant::{EngineConfig, Frame};
+use crate::compression_engine::engine_registry::EngineRegistry;
+use crate::compression_engine::grimoire_library::DictionaryRegistry;
+use crate::compression_engine::grimoire_quill::Grimoire;
+use crate::compression_engine::scratch_cauldron::ScratchSpace;
+use crate::concurrency::apprentice_guild::CompressionPool;
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::error::WizardError;
+use crate::format::artifact_view::ArtifactRef;
+use crate::format::block_ledger::BlockRef;
+use crate::format::block_stream::{BlockStreamReader, BlockStreamWriter};
+use crate::format::container_runes::ArchiveHeader;
+use crate::format::sealed_scroll::AtomicFile;
+use crate::format::tome_binding::{ArchiveReader, ArchiveWriter};
+use crate::statistics::stream_gauge::StreamingAnalyzer;
+use std::fs::File;
+
+const _: () = {
+    const fn shared_between_threads<T: Send + Sync>() {}
+    const fn moved_between_threads<T: Send>() {}
+
+    shared_between_threads::<EngineRegistry>();
+    shared_between_threads::<DictionaryRegistry>();
+    shared_between_threads::<EngineConfig>();
+    shared_between_threads::<DecodeOptions>();
+    shared_between_threads::<CompressorHandle>();
+    shared_between_threads::<HandleArtifact>();
+    shared_between_threads::<Grimoire>();
+    shared_between_threads::<ContextSnapshot>();
+    shared_between_threads::<CompressionArtifact>();
+    shared_between_threads::<ArtifactRef<'static>>();
+    shared_between_threads::<BlockRef<'static>>();
+    shared_between_threads::<Frame>();
+    shared_between_threads::<ArchiveHeader>();
+    shared_between_threads::<WizardError>();
+    shared_between_threads::<CompressionPool>();
+
+    moved_between_threads::<BlockStreamWriter<File>>();
+    moved_between_threads::<BlockStreamReader<File>>();
+    moved_between_threads::<ArchiveWriter<File>>();
+    moved_between_threads::<ArchiveWriter<AtomicFile>>();
+    moved_between_threads::<AtomicFile>();
+    moved_between_threads::<ArchiveReader<File>>();
+    moved_between_threads::<BitMagicReader<'static>>();
+    moved_between_threads::<BitMagicWriter<'static>>();
+    moved_between_threads::<ArithmeticEncoder<'static>>();
+    moved_between_threads::<ArithmeticDecoder<'static>>();
+    moved_between_threads::<RuneHasher>();
+    moved_between_threads::<ScratchSpace>();
+    moved_between_threads::<StreamingAnalyzer>();
+};
+
+/// Тесты оберегов потоков 🎯
+#[cfg(test)]
+mod thread_wards_tests {
+    use super::*;
+    use crate::format::container_runes::EngineKind;
+    use crate::simple_api::{decompress_data, try_compress_with_config};
+    use std::io::{Read, Write};
+    use std::thread;
+
+    fn sample(worker: usize) -> Vec<u8> {
+        format!("worker {worker} casts the spell of worker {worker}. ")
+            .repeat(50 + worker)
+            .into_bytes()
+    }
+
+    #[test]
+    fn test_shared_registry_and_models_across_threads() {
+        let registry = EngineRegistry::with_builtin_engines();
+        let handle = CompressorHandle::train(sample(0));
+        let engines = [EngineKind::Arithmetic, EngineKind::Mix, EngineKind::Utf8];
+
+        thread::scope(|scope| {
+            for worker in 0..8 {
+                let (registry, handle) = (&registry, &handle);
+                scope.spawn(move || {
+                    let data = sample(worker);
+                    let config = EngineConfig::default().with_engine(engines[worker % 3]);
+                    let frame = registry.compress(config.engine, &data, &config).unwrap();
+                    assert_eq!(decompress_data(frame.to_bytes()), data);
+                    assert_eq!(handle.decompress(&handle.compress(&data)), Some(data));
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn test_sessions_move_between_threads() {
+        let data = sample(3);
+        let mut writer = BlockStreamWriter::new(Vec::new(), 512).unwrap();
+        writer.write_all(&data[..700]).unwrap();
+
+        // Сессию начинает один поток, а завершает другой
+        let archive = thread::spawn(move || {
+            writer.write_all(&data[700..]).unwrap();
+            writer.finish().unwrap()
+        })
+        .join()
+        .unwrap();
+
+        let mut reader = BlockStreamReader::new(&archive[..], DecodeOptions::default()).unwrap();
+        let mut head = [0u8; 100];
+        reader.read_exact(&mut head).unwrap();
+        let tail = thread::scope(|scope| {
+            scope
+                .spawn(move || {
+                    let mut tail = Vec::new();
+                    reader.read_to_end(&mut tail).unwrap();
+                    tail
+                })
+                .join()
+                .unwrap()
+        });
+        assert_eq!([&head[..], &tail].concat(), sample(3));
+    }
+
+    #[test]
+    fn test_concurrent_compressions_match_sequential() {
+        let config = EngineConfig::default().with_reproducible(true);
+        let sequential: Vec<Vec<u8>> = (0..6)
+            .map(|worker| try_compress_with_config(sample(worker), &config).unwrap())
+            .collect();
+        let concurrent: Vec<Vec<u8>> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..6)
+                .map(|worker| {
+                    let config = &config;
+                    scope.spawn(move || try_compress_with_config(sample(worker), config).unwrap())
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().unwrap())
+                .collect()
+        });
+        assert_eq!(concurrent, sequential);
+    }
+}
@@ -0,0 +1,292 @@
+//! Отладочный снимок потока символов 🪲
+//!
+//! Жалобу «этот файл сжимается хуже, чем ожидалось» трудно разобрать без
+//! самого файла, а файл часто нельзя показать. [`dump_symbols`] снимает
+//! промежуточное представление — поток символов после словаря и фраз, —
+//! которое и определяет коэффициент: каждый символ с его видом и числом
+//! байтов входа, которые он заменил. Слова словаря и фразы в снимке
+//! представлены только индексами и длинами, их текста там нет; байты вне
+//! слов остаются символами `0..=255` — это и есть байты входа.
+//!
+//! [`write_dump`] и [`parse_dump`] переводят снимок в текст и обратно, а
+//! [`replay`] кодирует записи статической моделью нулевого порядка так
+//! же, как [`ArithmeticEngine`](crate::compression_engine::ArithmeticEngine):
+//! битовый поток совпадает с потоком архива байт в байт.
+//!
+//! Текст снимка: строка [`DUMP_HEADER`], затем по строке на символ —
+//! `<символ> <вид> <байтов входа>`, где вид — `byte`, `word:<индекс>`,
+//! `word+space:<индекс>` или `phrase:<индекс>`.
+
+use crate::bit_wizardry::bit_manipulation_spells::CoderPrecision;
+use crate::compression_engine::compression_conjurer::{
+    analyze_symbolic_frequencies, collect_symbol_words, encode_symbol_stream, interpret_symbol,
+    symbolize_manuscript, SymbolMeaning, WORD_SYMBOL_BASE,
+};
+use crate::compression_engine::engine_covenant::EngineConfig;
+use crate::error::{WizardError, WizardResult};
+use std::fmt::Write;
+
+/// Первая строка текста снимка (с версией раскладки)
+pub const DUMP_HEADER: &str = "acw-symbols 1";
+
+/// Вид символа потока 🔤
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymbolKind {
+    /// Байт входа как есть
+    Byte,
+    /// Слово словаря
+    Word {
+        /// Индекс в словаре
+        index: u32,
+        /// Символ поглотил пробел после слова
+        trailing_space: bool,
+    },
+    /// Фраза из 2–3 слов
+    Phrase {
+        /// Индекс в таблице фраз
+        index: u32,
+    },
+}
+
+/// Символ потока в отладочном снимке 🪲
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SymbolRecord {
+    /// Символ, который получает кодер
+    pub symbol: u32,
+    /// Вид символа
+    pub kind: SymbolKind,
+    /// Сколько байтов входа заменяет символ
+    pub source_len: u64,
+}
+
+/// Поток символов `data` с параметрами словаря и разбора из `config` 🪲
+///
+/// Символы те же, что кодирует [`ArithmeticEngine`](crate::compression_engine::ArithmeticEngine)
+/// с этими параметрами; сумма [`SymbolRecord::source_len`] равна длине
+/// `data`.
+///
+/// ```
+/// use arithmetic_compression_wizard::compression_engine::EngineConfig;
+/// use arithmetic_compression_wizard::debug::{dump_symbols, parse_dump, replay, write_dump};
+///
+/// let data = b"the secret plan: the owl flies at dawn. ".repeat(30);
+/// let records = dump_symbols(&data, &EngineConfig::default());
+/// assert_eq!(records.iter().map(|record| record.source_len).sum::<u64>(), data.len() as u64);
+///
+/// // Сопровождающий получает текст снимка и повторяет кодирование
+/// let shared = write_dump(&records);
+/// assert!(!shared.contains("secret"));
+/// let replayed = replay(&parse_dump(&shared).unwrap());
+/// assert!(replayed.len() < data.len());
+/// ```
+pub fn dump_symbols(data: impl AsRef<[u8]>, config: &EngineConfig) -> Vec<SymbolRecord> {
+    let (symbols, word_grimoire, phrase_codex) = symbolize_manuscript(data.as_ref(), config);
+    let grimoire_len = word_grimoire.len();
+    let mut words_in_symbol = Vec::new();
+    symbols
+        .into_iter()
+        .map(|symbol| {
+            let (kind, source_len) = match interpret_symbol(symbol, grimoire_len) {
+                SymbolMeaning::Byte(_) => (SymbolKind::Byte, 1),
+                meaning => {
+                    words_in_symbol.clear();
+                    collect_symbol_words(symbol, grimoire_len, &phrase_codex, &mut words_in_symbol);
+                    let source_len = words_in_symbol
+                        .iter()
+                        .map(|&(index, trailing_space)| {
+                            (word_grimoire[index].len() + trailing_space as usize) as u64
+                        })
+                        .sum();
+                    (record_kind(meaning), source_len)
+                }
+            };
+            SymbolRecord {
+                symbol,
+                kind,
+                source_len,
+            }
+        })
+        .collect()
+}
+
+/// Вид записи по смыслу символа
+fn record_kind(meaning: SymbolMeaning) -> SymbolKind {
+    match meaning {
+        SymbolMeaning::Byte(_) => SymbolKind::Byte,
+        SymbolMeaning::Word {
+            index,
+            trailing_space,
+        } => SymbolKind::Word {
+            index: index as u32,
+            trailing_space,
+        },
+        SymbolMeaning::Phrase { index } => SymbolKind::Phrase {
+            index: index as u32,
+        },
+    }
+}
+
+/// Битовый поток записей под статической моделью нулевого порядка ▶️
+///
+/// Таблица частот строится по самим записям, как при сжатии. Паникует,
+/// если масса частот не умещается в точность кодера; проверяемый вариант
+/// — [`try_replay`].
+pub fn replay(records: &[SymbolRecord]) -> Vec<u8> {
+    try_replay(records).expect("масса частот больше точности кодера")
+}
+
+/// Проверяемое повторение кодирования
+///
+/// Масса частот больше точности кодера дает
+/// [`WizardError::LimitExceeded`].
+pub fn try_replay(records: &[SymbolRecord]) -> WizardResult<Vec<u8>> {
+    let symbols: Vec<u32> = records.iter().map(|record| record.symbol).collect();
+    let frequencies = analyze_symbolic_frequencies(&symbols)?;
+    encode_symbol_stream(
+        &symbols,
+        &frequencies.frequency_entries,
+        frequencies.total_frequency_mass,
+        CoderPrecision::default(),
+    )
+}
+
+/// Текст снимка для отчета об ошибке 📝
+pub fn write_dump(records: &[SymbolRecord]) -> String {
+    let mut text = String::with_capacity(DUMP_HEADER.len() + 1 + records.len() * 12);
+    text.push_str(DUMP_HEADER);
+    text.push('\n');
+    for record in records {
+        let _ = write!(text, "{} ", record.symbol);
+        let _ = match record.kind {
+            SymbolKind::Byte => write!(text, "byte"),
+            SymbolKind::Word {
+                index,
+                trailing_space: false,
+            } => write!(text, "word:{index}"),
+            SymbolKind::Word {
+                index,
+                trailing_space: true,
+            } => write!(text, "word+space:{index}"),
+            SymbolKind::Phrase { index } => write!(text, "phrase:{index}"),
+        };
+        let _ = writeln!(text, " {}", record.source_len);
+    }
+    text
+}
+
+/// Разбирает текст [`write_dump`]
+///
+/// Неизвестная версия, строка не из трех полей, байт с символом вне
+/// `0..=255` или ссылка с символом байта дают
+/// [`WizardError::CorruptSymbolDump`].
+pub fn parse_dump(text: &str) -> WizardResult<Vec<SymbolRecord>> {
+    let mut lines = text.lines();
+    if lines.next() != Some(DUMP_HEADER) {
+        return Err(WizardError::CorruptSymbolDump("нет заголовка снимка"));
+    }
+    lines
+        .filter(|line| !line.trim().is_empty())
+        .map(parse_record)
+        .collect()
+}
+
+/// Разбирает строку `<символ> <вид> <байтов входа>`
+fn parse_record(line: &str) -> WizardResult<SymbolRecord> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let [symbol, kind, source_len] = fields[..] else {
+        return Err(WizardError::CorruptSymbolDump("строка не из трех полей"));
+    };
+    let symbol: u32 = symbol
+        .parse()
+        .map_err(|_| WizardError::CorruptSymbolDump("символ не число"))?;
+    let source_len: u64 = source_len
+        .parse()
+        .map_err(|_| WizardError::CorruptSymbolDump("длина не число"))?;
+    let index = |value: &str| {
+        value
+            .parse::<u32>()
+            .map_err(|_| WizardError::CorruptSymbolDump("индекс не число"))
+    };
+    let kind = match kind.split_once(':') {
+        None if kind == "byte" => SymbolKind::Byte,
+        Some(("word", value)) => SymbolKind::Word {
+            index: index(value)?,
+            trailing_space: false,
+        },
+        Some(("word+space", value)) => SymbolKind::Word {
+            index: index(value)?,
+            trailing_space: true,
+        },
+        Some(("phrase", value)) => SymbolKind::Phrase {
+            index: index(value)?,
+        },
+        _ => return Err(WizardError::CorruptSymbolDump("неизвестный вид символа")),
+    };
+    if (kind == SymbolKind::Byte) != (symbol < WORD_SYMBOL_BASE) {
+        return Err(WizardError::CorruptSymbolDump(
+            "вид символа не согласован с символом",
+        ));
+    }
+    Ok(SymbolRecord {
+        symbol,
+        kind,
+        source_len,
+    })
+}
+
+/// Тесты отладочного снимка 🎯
+#[cfg(test)]
+mod debug_tests {
+    use super::*;
+    use crate::compression_engine::compression_conjurer::try_weave_with_discovery;
+    use crate::compression_engine::engine_covenant::ParseStrategy;
+
+    #[test]
+    fn test_replay_matches_archive_stream() {
+        let data = "the wizard reads the scroll and the owl reads the wizard. "
+            .repeat(40)
+            .into_bytes();
+        for config in [
+            EngineConfig::default(),
+            EngineConfig::default().with_parse_strategy(ParseStrategy::Optimal),
+        ] {
+            let records = dump_symbols(&data, &config);
+            assert!(records
+                .iter()
+                .any(|record| matches!(record.kind, SymbolKind::Phrase { .. })));
+            assert_eq!(
+                records.iter().map(|record| record.source_len).sum::<u64>(),
+                data.len() as u64
+            );
+
+            let shared = write_dump(&records);
+            assert!(!shared.contains("wizard"));
+            let parsed = parse_dump(&shared).unwrap();
+            assert_eq!(parsed, records);
+            let artifact = try_weave_with_discovery(&data, &config).unwrap();
+            assert_eq!(replay(&parsed), artifact.compressed_bit_stream);
+        }
+
+        assert_eq!(dump_symbols(b"", &EngineConfig::default()), []);
+        assert_eq!(parse_dump(&write_dump(&[])).unwrap(), []);
+    }
+
+    #[test]
+    fn test_damaged_dump_is_rejected() {
+        for text in [
+            "",
+            "acw-symbols 2\n97 byte 1\n",
+            "acw-symbols 1\n97 byte\n",
+            "acw-symbols 1\nx byte 1\n",
+            "acw-symbols 1\n97 glyph 1\n",
+            "acw-symbols 1\n300 byte 1\n",
+            "acw-symbols 1\n97 word:0 5\n",
+            "acw-symbols 1\n300 phrase:x 5\n",
+        ] {
+            assert!(
+                matches!(parse_dump(text), Err(WizardError::CorruptSymbolDump(_))),
+                "{text:?}"
+            );
+        }
+    }
+}
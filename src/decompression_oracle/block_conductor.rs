@@ -0,0 +1,262 @@
+//! Дирижер блоков 🎼
+//!
+//! Распаковывает независимые блоки архива (см.
+//! [`block_ledger`](crate::format::block_ledger)) на нескольких потоках и
+//! собирает результат в исходном порядке. Потоки разбирают блоки из общей
+//! очереди, поэтому крупный блок не задерживает остальных.
+//!
+//! Ошибка всегда относится к первому по порядку поврежденному блоку:
+//! после первой ошибки новые блоки не берутся, а все блоки перед ней
+//! к этому моменту уже взяты в работу и досчитываются.
+//! Ошибка помечена номером блока (см. [`ErrorLocation`](crate::error::ErrorLocation)).
+
+use super::decode_wards::DecodeOptions;
+use crate::error::{LimitKind, WizardError, WizardResult};
+use crate::format::artifact_scroll::offset_within;
+use crate::format::block_ledger::BlockRef;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+
+/// Результат распаковки блока с его номером
+type DecodedBlock = (usize, WizardResult<Vec<u8>>);
+
+/// Распаковывает блоки и склеивает результат 🎼
+///
+/// `decode_frame` распаковывает один кадр; лимит размера результата для
+/// него равен длине блока из реестра. Кадры заимствованы из
+/// `archive_bytes`: по нему место ошибки в кадре переводится в смещение
+/// от начала архива.
+pub(crate) fn try_decode_blocks<F>(
+    archive_bytes: &[u8],
+    blocks: &[BlockRef<'_>],
+    options: &DecodeOptions,
+    decode_frame: F,
+) -> WizardResult<Vec<u8>>
+where
+    F: Fn(&[u8], &DecodeOptions) -> WizardResult<Vec<u8>> + Sync,
+{
+    // Суммарная длина уже проверена лимитом при разборе реестра
+    let total_len: u64 = blocks.iter().map(|block| block.original_len).sum();
+    options.reserve_output(total_len)?;
+
+    let worker_count = options.resolved_decode_threads().min(blocks.len());
+    let mut restored = Vec::with_capacity(total_len as usize);
+
+    if worker_count <= 1 {
+        for (index, block) in blocks.iter().enumerate() {
+            let block_bytes = decode_block(block, options, &decode_frame)
+                .map_err(|error| locate_block_error(error, archive_bytes, blocks, index))?;
+            restored.extend_from_slice(&block_bytes);
+            options.report_progress(restored.len() as u64);
+        }
+        return Ok(restored);
+    }
+
+    let mut decoded: Vec<Option<WizardResult<Vec<u8>>>> = blocks.iter().map(|_| None).collect();
+    for (index, result) in decode_concurrently(blocks, options, &decode_frame, worker_count) {
+        decoded[index] = Some(result);
+    }
+
+    for (index, slot) in decoded.into_iter().enumerate() {
+        match slot {
+            Some(Ok(block_bytes)) => {
+                restored.extend_from_slice(&block_bytes);
+                options.report_progress(restored.len() as u64);
+            }
+            Some(Err(error)) => {
+                return Err(locate_block_error(error, archive_bytes, blocks, index))
+            }
+            None => return Err(WizardError::CorruptBlockTable("блок не распакован")),
+        }
+    }
+    Ok(restored)
+}
+
+/// Помечает ошибку блока `index` номером и смещением его кадра
+fn locate_block_error(
+    error: WizardError,
+    archive_bytes: &[u8],
+    blocks: &[BlockRef<'_>],
+    index: usize,
+) -> WizardError {
+    error
+        .in_frame(index)
+        .shifted(offset_within(archive_bytes, blocks[index].frame))
+}
+
+/// Раздает блоки потокам из общей очереди
+fn decode_concurrently<F>(
+    blocks: &[BlockRef<'_>],
+    options: &DecodeOptions,
+    decode_frame: &F,
+    worker_count: usize,
+) -> Vec<DecodedBlock>
+where
+    F: Fn(&[u8], &DecodeOptions) -> WizardResult<Vec<u8>> + Sync,
+{
+    let next_block = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..worker_count)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut finished = Vec::new();
+                    while !failed.load(Ordering::Relaxed) {
+                        let index = next_block.fetch_add(1, Ordering::Relaxed);
+                        let Some(block) = blocks.get(index) else {
+                            break;
+                        };
+                        let result = decode_block(block, options, decode_frame);
+                        if result.is_err() {
+                            failed.store(true, Ordering::Relaxed);
+                        }
+                        finished.push((index, result));
+                    }
+                    finished
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .flat_map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect()
+    })
+}
+
+/// Распаковывает один блок и сверяет длину с реестром
+pub(crate) fn decode_block<F>(
+    block: &BlockRef<'_>,
+    options: &DecodeOptions,
+    decode_frame: &F,
+) -> WizardResult<Vec<u8>>
+where
+    F: Fn(&[u8], &DecodeOptions) -> WizardResult<Vec<u8>>,
+{
+    let block_limit = usize::try_from(block.original_len).unwrap_or(usize::MAX);
+    let restored = match decode_frame(
+        block.frame,
+        &options.clone().with_max_output_bytes(block_limit),
+    ) {
+        Err(WizardError::LimitExceeded {
+            kind: LimitKind::OutputBytes,
+            ..
+        }) => {
+            return Err(WizardError::CorruptBlockTable(
+                "блок длиннее записи реестра",
+            ))
+        }
+        result => result?,
+    };
+
+    if restored.len() as u64 != block.original_len {
+        return Err(WizardError::CorruptBlockTable(
+            "длина блока не совпадает с реестром",
+        ));
+    }
+    Ok(restored)
+}
+
+/// Тесты дирижера блоков 🎯
+#[cfg(test)]
+mod block_conductor_tests {
+    use super::*;
+    use crate::error::DecodePhase;
+    use crate::format::block_ledger::parse_block_ledger;
+    use crate::simple_api::{compress_data, compress_data_in_blocks, try_decompress_data};
+    use crate::testkit::{corrupt, CorruptionKind};
+
+    fn sample_text() -> Vec<u8> {
+        (0..400)
+            .map(|line| format!("line {line}: the wizard and the owl read the scroll.\n"))
+            .collect::<String>()
+            .into_bytes()
+    }
+
+    #[test]
+    fn test_parallel_decode_matches_sequential() {
+        let data = sample_text();
+        let archive = compress_data_in_blocks(&data, 1000);
+        let blocks = parse_block_ledger(&archive, &DecodeOptions::default()).unwrap();
+        assert_eq!(blocks.len(), data.len().div_ceil(1000));
+
+        for threads in [1, 2, 4, 64] {
+            let options = DecodeOptions::default().with_decode_threads(threads);
+            assert_eq!(try_decompress_data(&archive, &options).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_first_broken_block_is_reported() {
+        let data = sample_text();
+        let archive = compress_data_in_blocks(&data, 1000);
+        let blocks = parse_block_ledger(&archive, &DecodeOptions::default()).unwrap();
+
+        // Портим таблицу частот второго блока
+        let damaged_frame = corrupt(
+            blocks[1].frame,
+            CorruptionKind::FrequencyTamper { entry: 0 },
+        );
+        let frame_offset = blocks[1].frame.as_ptr() as usize - archive.as_ptr() as usize;
+        let mut damaged = archive.clone();
+        damaged[frame_offset..frame_offset + damaged_frame.len()].copy_from_slice(&damaged_frame);
+
+        for threads in [1, 4] {
+            let options = DecodeOptions::default().with_decode_threads(threads);
+            let error = try_decompress_data(&damaged, &options).unwrap_err();
+            assert!(matches!(
+                error.root_cause(),
+                WizardError::CorruptFrequencyTable(_)
+            ));
+            // Место указывает на второй блок
+            let location = error.location().unwrap();
+            assert_eq!(location.frame, Some(1));
+            assert_eq!(location.phase, DecodePhase::Table);
+            assert!((frame_offset..frame_offset + damaged_frame.len()).contains(&location.offset));
+        }
+    }
+
+    #[test]
+    fn test_block_length_must_match_ledger() {
+        let frame = compress_data(b"abcdef");
+        let frames = |original_len| {
+            crate::format::block_ledger::write_block_archive(&[
+                (original_len, frame.clone()),
+                (original_len, frame.clone()),
+            ])
+        };
+        let options = DecodeOptions::default().with_decode_threads(2);
+
+        assert_eq!(
+            try_decompress_data(frames(6), &options).unwrap(),
+            b"abcdefabcdef"
+        );
+        let decode = |archive: Vec<u8>| {
+            try_decompress_data(archive, &options).map_err(|error| error.root_cause().clone())
+        };
+        assert_eq!(
+            decode(frames(7)),
+            Err(WizardError::CorruptBlockTable(
+                "длина блока не совпадает с реестром"
+            ))
+        );
+        assert_eq!(
+            decode(frames(3)),
+            Err(WizardError::CorruptBlockTable(
+                "блок длиннее записи реестра"
+            ))
+        );
+
+        // Кадр блока не может сам состоять из блоков
+        let nested = crate::format::block_ledger::write_block_archive(&[(12, frames(6))]);
+        assert!(matches!(
+            decode(nested),
+            Err(WizardError::CorruptBlockTable(_))
+        ));
+    }
+}
@@ -0,0 +1,252 @@
+//! Спасение блоков поврежденного архива 🛟
+//!
+//! Блоки архива независимы, поэтому порча одного не должна стоить
+//! остальных. [`salvage_archive`] распаковывает все, что можно, и
+//! перечисляет потерянные участки. В раскладке с маркерами
+//! синхронизации (см. [`block_ledger`](crate::format::block_ledger))
+//! после порчи записи, кадра или даже заголовка архива распаковка
+//! продолжается со следующего маркера; в остальных раскладках
+//! спасаются блоки с целым реестром.
+
+use super::block_conductor::decode_block;
+use super::decode_wards::DecodeOptions;
+use crate::error::{WizardError, WizardResult};
+use crate::format::artifact_scroll::ScrollCursor;
+use crate::format::block_ledger::{
+    find_sync_marker, read_block_ledger, read_synced_entry, BlockRef, SYNCED_BLOCK_COUNT,
+};
+use crate::format::container_runes::ArchiveHeader;
+use crate::simple_api::{try_decompress_data, try_decompress_frame};
+
+/// Потерянный участок архива 🩹
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockError {
+    /// Смещение поврежденного участка в архиве
+    pub archive_offset: usize,
+    /// Позиция в восстановленных данных, где не хватает потерянного
+    pub output_offset: usize,
+    /// Исходная длина потерянного блока, если его запись реестра цела
+    pub original_len: Option<u64>,
+    /// Причина потери
+    pub error: WizardError,
+}
+
+/// Распаковка с пропуском поврежденных блоков 🛟
+///
+/// Потерянные блоки в результат не попадают; их места и причины
+/// перечислены в порядке архива. Поврежденный архив без блоков теряется
+/// целиком.
+pub(crate) fn salvage_archive(
+    archive_bytes: &[u8],
+    options: &DecodeOptions,
+) -> (Vec<u8>, Vec<BlockError>) {
+    let mut salvage = Salvage {
+        archive_bytes,
+        options,
+        restored: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    match ArchiveHeader::parse(archive_bytes) {
+        Some((header, _)) if !header.block_framed => {
+            match try_decompress_data(archive_bytes, options) {
+                Ok(restored) => salvage.restored = restored,
+                Err(error) => {
+                    // Поврежденная сигнатура читается как архив без заголовка
+                    salvage.lose(0, None, error);
+                    salvage.resync_after(0);
+                }
+            }
+        }
+        Some((header, body_offset)) => {
+            let body = &archive_bytes[body_offset..];
+            let mut cursor = ScrollCursor::new(body, 0);
+            if cursor.read_u32() == Ok(SYNCED_BLOCK_COUNT) {
+                salvage.resync_from(body_offset + 4);
+            } else {
+                match read_block_ledger(&header, body, options) {
+                    Ok(blocks) => {
+                        for block in &blocks {
+                            salvage.decode(block);
+                        }
+                    }
+                    Err(error) => {
+                        salvage.lose(body_offset, None, error);
+                        salvage.resync_after(body_offset);
+                    }
+                }
+            }
+        }
+        None => {
+            salvage.lose(0, None, WizardError::UnrecognizedHeader);
+            salvage.resync_after(0);
+        }
+    }
+
+    (salvage.restored, salvage.errors)
+}
+
+/// Состояние спасения
+struct Salvage<'a> {
+    archive_bytes: &'a [u8],
+    options: &'a DecodeOptions,
+    restored: Vec<u8>,
+    errors: Vec<BlockError>,
+}
+
+impl Salvage<'_> {
+    /// Записывает потерю на текущей позиции результата
+    fn lose(&mut self, archive_offset: usize, original_len: Option<u64>, error: WizardError) {
+        self.errors.push(BlockError {
+            archive_offset,
+            output_offset: self.restored.len(),
+            original_len,
+            error,
+        });
+    }
+
+    /// Продолжает с первого маркера после `offset`, если он есть
+    fn resync_after(&mut self, offset: usize) {
+        if let Some(marker) = find_sync_marker(self.archive_bytes, offset + 1) {
+            self.resync_from(marker);
+        }
+    }
+
+    /// Читает записи с маркерами с позиции `offset` до завершающей
+    fn resync_from(&mut self, mut offset: usize) {
+        loop {
+            let mut cursor = ScrollCursor::new(self.archive_bytes, offset);
+            match read_synced_entry(&mut cursor) {
+                Ok(None) => return,
+                Ok(Some(block)) => {
+                    if !self.decode(&block) {
+                        return;
+                    }
+                    offset = cursor.position();
+                }
+                Err(error) => {
+                    self.lose(offset, None, error);
+                    match find_sync_marker(self.archive_bytes, offset + 1) {
+                        Some(marker) => offset = marker,
+                        None => return,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Распаковывает блок или записывает его потерю; `false` — лимит
+    /// результата или бюджет учетчика исчерпан и спасение пора остановить
+    fn decode(&mut self, block: &BlockRef<'_>) -> bool {
+        let archive_offset = block.frame.as_ptr() as usize - self.archive_bytes.as_ptr() as usize;
+        let result: WizardResult<Vec<u8>> = self
+            .options
+            .check_output((self.restored.len() as u64).saturating_add(block.original_len))
+            .and_then(|()| decode_block(block, self.options, &try_decompress_frame));
+        match result {
+            Ok(block_bytes) => self.restored.extend_from_slice(&block_bytes),
+            Err(error @ (WizardError::LimitExceeded { .. } | WizardError::BudgetDenied { .. })) => {
+                self.lose(archive_offset, Some(block.original_len), error);
+                return false;
+            }
+            Err(error) => self.lose(archive_offset, Some(block.original_len), error),
+        }
+        true
+    }
+}
+
+/// Тесты спасения блоков 🎯
+#[cfg(test)]
+mod block_salvage_tests {
+    use super::*;
+    use crate::format::block_ledger::SYNC_MARKER;
+    use crate::simple_api::{
+        compress_data, compress_data_in_blocks, compress_data_in_synced_blocks,
+    };
+
+    fn sample() -> Vec<u8> {
+        "the owl carries scrolls between towers. "
+            .repeat(50)
+            .into_bytes()
+    }
+
+    #[test]
+    fn test_synced_archive_skips_damaged_block() {
+        let data = sample();
+        let archive = compress_data_in_synced_blocks(&data, 500);
+        let options = DecodeOptions::default();
+        assert_eq!(
+            salvage_archive(&archive, &options),
+            (data.clone(), Vec::new())
+        );
+
+        // Порча кадра второго блока и записи четвертого
+        let markers: Vec<usize> = (0..archive.len())
+            .filter(|&offset| archive[offset..].starts_with(&SYNC_MARKER))
+            .collect();
+        let mut damaged = archive.clone();
+        damaged[markers[1] + 40] ^= 0xFF;
+        damaged[markers[3] + 9] ^= 0xFF;
+
+        let (restored, errors) = salvage_archive(&damaged, &options);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].output_offset, 500);
+        assert_eq!(errors[1].output_offset, 1000);
+        assert_eq!(errors[1].archive_offset, markers[3]);
+        assert_eq!(
+            errors[0].error,
+            WizardError::CorruptBlockTable("хеш блока не совпадает")
+        );
+        let mut expected = data[..500].to_vec();
+        expected.extend_from_slice(&data[1000..1500]);
+        expected.extend_from_slice(&data[2000..]);
+        assert_eq!(restored, expected);
+    }
+
+    #[test]
+    fn test_damaged_header_and_truncated_tail() {
+        let data = sample();
+        let archive = compress_data_in_synced_blocks(&data, 500);
+        let mut damaged = archive.clone();
+        damaged[0] ^= 0xFF;
+        let (restored, errors) = salvage_archive(&damaged, &DecodeOptions::default());
+        assert_eq!(restored, data);
+        // Без сигнатуры архив читается как архив без заголовка и не сходится
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].archive_offset, 0);
+
+        let (restored, errors) =
+            salvage_archive(&archive[..archive.len() - 100], &DecodeOptions::default());
+        assert_eq!(restored, &data[..1500]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].output_offset, 1500);
+    }
+
+    #[test]
+    fn test_other_layouts_lose_only_what_is_damaged() {
+        let data = sample();
+        let counted = compress_data_in_blocks(&data, 500);
+        let blocks =
+            crate::format::parse_block_ledger(&counted, &DecodeOptions::default()).unwrap();
+        let second = blocks[1].frame.as_ptr() as usize - counted.as_ptr() as usize;
+        let mut damaged = counted.clone();
+        damaged[second + blocks[1].frame.len() - 1] ^= 0xFF;
+        damaged[second + 1] ^= 0xFF;
+        let (restored, errors) = salvage_archive(&damaged, &DecodeOptions::default());
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].original_len, Some(500));
+        assert_eq!(restored.len(), data.len() - 500);
+
+        let plain = compress_data(&data);
+        let (restored, errors) =
+            salvage_archive(&plain[..plain.len() / 2], &DecodeOptions::default());
+        assert!(restored.is_empty());
+        assert!(matches!(
+            errors[..],
+            [BlockError {
+                archive_offset: 0,
+                ..
+            }]
+        ));
+    }
+}
@@ -0,0 +1,646 @@
+//! Охранные чары декодера 🛡️
+//!
+//! Лимиты и проверки для недоверенных архивов.
+//!
+//! ## Гарантия
+//! Проверяемые функции (`try_*`) не паникуют на любых входных байтах и не
+//! выделяют память сверх лимитов [`DecodeOptions`] и размера самого архива.
+//! Гарантия проверяется фаззинг-тестом `decode_ward_fuzz_tests`: он портит
+//! архивы каждого встроенного движка и каждой модели, архивы со словарем и
+//! фразами, из блоков (в том числе с метками синхронизации), с разделами
+//! метаданных, статистики, четности и штампа происхождения, а также
+//! компактные архивы, кадры общей модели и разностные патчи.
+
+use super::resource_ledger::{ResourceAccountant, ResourceHook};
+use crate::bit_wizardry::bit_manipulation_spells::CoderPrecision;
+use crate::compression_engine::compression_conjurer::phrase_symbol;
+use crate::error::{LimitKind, WizardError, WizardResult};
+use crate::format::artifact_view::CodexView;
+
+/// Параметры проверяемого декодирования ⚙️
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeOptions {
+    /// Максимальный размер восстановленных данных в байтах
+    pub max_output_bytes: usize,
+    /// Максимальное число слов в словаре архива (и отдельно — фраз)
+    pub max_dict_entries: usize,
+    /// Максимальная длина одного слова словаря в байтах
+    pub max_word_bytes: usize,
+    /// Максимальный суммарный размер слов словаря в байтах
+    pub max_dictionary_bytes: usize,
+    /// Максимальное число записей в таблице частот
+    pub max_table_entries: usize,
+    /// Число потоков для архивов из блоков (0 — по числу ядер)
+    pub decode_threads: usize,
+    /// Учетчик выделений и хода распаковки (см. [`ResourceAccountant`])
+    pub resource_hook: Option<ResourceHook>,
+}
+
+impl DecodeOptions {
+    /// Лимит размера результата по умолчанию (256 МиБ)
+    pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 256 * 1024 * 1024;
+
+    /// Лимит размера словаря по умолчанию
+    pub const DEFAULT_MAX_DICT_ENTRIES: usize = 1 << 16;
+
+    /// Лимит длины слова по умолчанию (1 МиБ — размер блока по умолчанию)
+    pub const DEFAULT_MAX_WORD_BYTES: usize = 1 << 20;
+
+    /// Лимит суммарного размера словаря по умолчанию (16 МиБ)
+    pub const DEFAULT_MAX_DICTIONARY_BYTES: usize = 16 * 1024 * 1024;
+
+    /// Лимит таблицы частот по умолчанию: все байты, оба символа каждого слова и фразы
+    pub const DEFAULT_MAX_TABLE_ENTRIES: usize = 256 + 3 * Self::DEFAULT_MAX_DICT_ENTRIES;
+
+    /// Без лимитов (только для доверенных архивов)
+    pub fn unlimited() -> Self {
+        Self {
+            max_output_bytes: usize::MAX,
+            max_dict_entries: usize::MAX,
+            max_word_bytes: usize::MAX,
+            max_dictionary_bytes: usize::MAX,
+            max_table_entries: usize::MAX,
+            decode_threads: 0,
+            resource_hook: None,
+        }
+    }
+
+    /// Задает лимит размера результата
+    pub fn with_max_output_bytes(mut self, max_output_bytes: usize) -> Self {
+        self.max_output_bytes = max_output_bytes;
+        self
+    }
+
+    /// Задает лимит размера словаря
+    pub fn with_max_dict_entries(mut self, max_dict_entries: usize) -> Self {
+        self.max_dict_entries = max_dict_entries;
+        self
+    }
+
+    /// Задает лимит длины одного слова словаря
+    pub fn with_max_word_bytes(mut self, max_word_bytes: usize) -> Self {
+        self.max_word_bytes = max_word_bytes;
+        self
+    }
+
+    /// Задает лимит суммарного размера словаря
+    pub fn with_max_dictionary_bytes(mut self, max_dictionary_bytes: usize) -> Self {
+        self.max_dictionary_bytes = max_dictionary_bytes;
+        self
+    }
+
+    /// Задает лимит таблицы частот
+    pub fn with_max_table_entries(mut self, max_table_entries: usize) -> Self {
+        self.max_table_entries = max_table_entries;
+        self
+    }
+
+    /// Задает число потоков распаковки блоков
+    pub fn with_decode_threads(mut self, decode_threads: usize) -> Self {
+        self.decode_threads = decode_threads;
+        self
+    }
+
+    /// Задает учетчик ресурсов распаковки
+    pub fn with_accountant(self, accountant: impl ResourceAccountant + 'static) -> Self {
+        self.with_resource_hook(ResourceHook::new(accountant))
+    }
+
+    /// Задает крючок учетчика, общий с другими параметрами
+    pub fn with_resource_hook(mut self, resource_hook: ResourceHook) -> Self {
+        self.resource_hook = Some(resource_hook);
+        self
+    }
+
+    /// Число потоков распаковки блоков с учетом значения по умолчанию
+    pub(crate) fn resolved_decode_threads(&self) -> usize {
+        match self.decode_threads {
+            0 => std::thread::available_parallelism().map_or(1, |threads| threads.get()),
+            threads => threads,
+        }
+    }
+
+    /// Проверяет размер результата
+    pub(crate) fn check_output(&self, requested: u64) -> WizardResult<()> {
+        check_limit(LimitKind::OutputBytes, requested, self.max_output_bytes)
+    }
+
+    /// Проверяет размер результата кадра и согласует с учетчиком буфер
+    /// под него
+    ///
+    /// Вызывается один раз на кадр с заявленной длиной или ее нижней
+    /// границей; проверки по ходу декодирования — [`check_output`](Self::check_output).
+    pub(crate) fn reserve_output(&self, requested: u64) -> WizardResult<()> {
+        self.check_output(requested)?;
+        self.charge(LimitKind::OutputBytes, requested)
+    }
+
+    /// Проверяет заявленное число слов словаря
+    pub(crate) fn check_dict_entries(&self, requested: usize) -> WizardResult<()> {
+        check_limit(
+            LimitKind::DictEntries,
+            requested as u64,
+            self.max_dict_entries,
+        )?;
+        self.charge(LimitKind::DictEntries, requested as u64)
+    }
+
+    /// Проверяет заявленную длину слова и размер словаря вместе с ним
+    ///
+    /// `grimoire_bytes` — сумма длин уже прочитанных слов; возвращает
+    /// новую сумму. Вызывается до чтения самого слова.
+    pub(crate) fn check_word_bytes(
+        &self,
+        grimoire_bytes: usize,
+        word_len: usize,
+    ) -> WizardResult<usize> {
+        check_limit(LimitKind::WordBytes, word_len as u64, self.max_word_bytes)?;
+        let grimoire_bytes = grimoire_bytes.saturating_add(word_len);
+        check_limit(
+            LimitKind::DictionaryBytes,
+            grimoire_bytes as u64,
+            self.max_dictionary_bytes,
+        )?;
+        self.charge(LimitKind::WordBytes, word_len as u64)?;
+        Ok(grimoire_bytes)
+    }
+
+    /// Проверяет заявленное число записей таблицы частот
+    pub(crate) fn check_table_entries(&self, requested: usize) -> WizardResult<()> {
+        check_limit(
+            LimitKind::TableEntries,
+            requested as u64,
+            self.max_table_entries,
+        )?;
+        self.charge(LimitKind::TableEntries, requested as u64)
+    }
+
+    /// Спрашивает учетчика о выделении, прошедшем статический лимит
+    fn charge(&self, kind: LimitKind, requested: u64) -> WizardResult<()> {
+        match &self.resource_hook {
+            Some(hook) if !hook.accountant().on_alloc(kind, requested) => {
+                Err(WizardError::BudgetDenied { kind, requested })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Сообщает учетчику, сколько байтов восстановлено
+    pub(crate) fn report_progress(&self, restored_bytes: u64) {
+        if let Some(hook) = &self.resource_hook {
+            hook.accountant().on_progress(restored_bytes);
+        }
+    }
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        Self {
+            max_output_bytes: Self::DEFAULT_MAX_OUTPUT_BYTES,
+            max_dict_entries: Self::DEFAULT_MAX_DICT_ENTRIES,
+            max_word_bytes: Self::DEFAULT_MAX_WORD_BYTES,
+            max_dictionary_bytes: Self::DEFAULT_MAX_DICTIONARY_BYTES,
+            max_table_entries: Self::DEFAULT_MAX_TABLE_ENTRIES,
+            decode_threads: 0,
+            resource_hook: None,
+        }
+    }
+}
+
+/// Сравнивает запрошенное значение с лимитом
+fn check_limit(kind: LimitKind, requested: u64, allowed: usize) -> WizardResult<()> {
+    if requested > allowed as u64 {
+        return Err(WizardError::LimitExceeded {
+            kind,
+            requested,
+            allowed: allowed as u64,
+        });
+    }
+    Ok(())
+}
+
+/// Проверяет согласованность таблицы частот
+///
+/// После проверки декодер не может выйти за пределы интервала:
+/// записи идут по возрастанию id символа, частоты ненулевые,
+/// накопительные позиции непрерывны, а общая масса умещается в точность кодера.
+pub(crate) fn validate_frequency_codex(
+    frequency_codex: &(impl CodexView + ?Sized),
+    total_frequency_mass: u64,
+    precision: CoderPrecision,
+) -> WizardResult<()> {
+    if total_frequency_mass > precision.max_total_frequency() {
+        return Err(WizardError::CorruptFrequencyTable(
+            "общая масса превышает точность кодера",
+        ));
+    }
+
+    let mut cumulative_position = 0u64;
+    let mut previous_symbol: Option<u32> = None;
+
+    for index in 0..frequency_codex.entry_count() {
+        let (symbol_id, frequency, cumulative_start) = frequency_codex.entry(index);
+        if previous_symbol.is_some_and(|previous| previous >= symbol_id) {
+            return Err(WizardError::CorruptFrequencyTable(
+                "символы не упорядочены или повторяются",
+            ));
+        }
+        if frequency == 0 {
+            return Err(WizardError::CorruptFrequencyTable("нулевая частота"));
+        }
+        if cumulative_start != cumulative_position {
+            return Err(WizardError::CorruptFrequencyTable(
+                "разрыв накопительных позиций",
+            ));
+        }
+
+        cumulative_position = cumulative_position.checked_add(frequency).ok_or(
+            WizardError::CorruptFrequencyTable("переполнение суммы частот"),
+        )?;
+        previous_symbol = Some(symbol_id);
+    }
+
+    if cumulative_position != total_frequency_mass {
+        return Err(WizardError::CorruptFrequencyTable(
+            "сумма частот не равна общей массе",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Проверяет таблицу фраз
+///
+/// Каждая фраза непуста и ссылается только на символы меньше своего,
+/// поэтому раскрытие фраз всегда завершается.
+pub(crate) fn validate_phrase_codex(
+    phrase_codex: &[Vec<u32>],
+    grimoire_len: usize,
+) -> WizardResult<()> {
+    for (phrase_index, phrase) in phrase_codex.iter().enumerate() {
+        let own_symbol = phrase_symbol(phrase_index, grimoire_len);
+        if phrase.is_empty() {
+            return Err(WizardError::CorruptPhraseTable("пустая фраза"));
+        }
+        if phrase.iter().any(|&element| element >= own_symbol) {
+            return Err(WizardError::CorruptPhraseTable(
+                "фраза ссылается на себя или более поздний символ",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Тесты охранных чар 🎯
+#[cfg(test)]
+mod decode_ward_tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_codex_passes() {
+        let codex = [(10u32, 2u64, 0u64), (20, 3, 2)];
+        assert!(validate_frequency_codex(&codex[..], 5, CoderPrecision::Bits32).is_ok());
+        assert!(validate_frequency_codex(&[][..], 0, CoderPrecision::Bits32).is_ok());
+    }
+
+    #[test]
+    fn test_inconsistent_codex_is_rejected() {
+        let gap = [(10u32, 2u64, 0u64), (20, 3, 3)];
+        let zero = [(10u32, 0u64, 0u64)];
+        let unsorted = [(20u32, 1u64, 0u64), (10, 1, 1)];
+        let huge = [(1u32, 1u64 << 40, 0u64)];
+
+        assert!(validate_frequency_codex(&gap[..], 6, CoderPrecision::Bits32).is_err());
+        assert!(validate_frequency_codex(&zero[..], 0, CoderPrecision::Bits32).is_err());
+        assert!(validate_frequency_codex(&unsorted[..], 2, CoderPrecision::Bits32).is_err());
+        assert!(validate_frequency_codex(&huge[..], 1 << 40, CoderPrecision::Bits32).is_err());
+        assert!(validate_frequency_codex(&[(1, 2, 0)][..], 3, CoderPrecision::Bits32).is_err());
+    }
+
+    #[test]
+    fn test_phrase_codex_validation() {
+        // Словарь из 2 слов: фразы начинаются с символа 260
+        assert!(validate_phrase_codex(&[vec![258, 257], vec![260, 65]], 2).is_ok());
+        assert!(validate_phrase_codex(&[vec![]], 2).is_err());
+        assert!(validate_phrase_codex(&[vec![260]], 2).is_err());
+        assert!(validate_phrase_codex(&[vec![256], vec![262]], 2).is_err());
+    }
+
+    #[test]
+    fn test_output_limit() {
+        let options = DecodeOptions::default().with_max_output_bytes(10);
+        assert!(options.check_output(10).is_ok());
+        assert!(matches!(
+            options.check_output(11),
+            Err(WizardError::LimitExceeded {
+                kind: LimitKind::OutputBytes,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_entry_limits() {
+        let options = DecodeOptions::default()
+            .with_max_dict_entries(4)
+            .with_max_table_entries(300);
+
+        assert!(options.check_dict_entries(4).is_ok());
+        assert!(matches!(
+            options.check_dict_entries(5),
+            Err(WizardError::LimitExceeded {
+                kind: LimitKind::DictEntries,
+                ..
+            })
+        ));
+        assert!(matches!(
+            options.check_table_entries(301),
+            Err(WizardError::LimitExceeded {
+                kind: LimitKind::TableEntries,
+                ..
+            })
+        ));
+        assert!(DecodeOptions::unlimited()
+            .check_table_entries(usize::MAX)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_word_byte_limits() {
+        let options = DecodeOptions::default()
+            .with_max_word_bytes(8)
+            .with_max_dictionary_bytes(12);
+
+        assert_eq!(options.check_word_bytes(0, 8).unwrap(), 8);
+        assert!(matches!(
+            options.check_word_bytes(0, 9),
+            Err(WizardError::LimitExceeded {
+                kind: LimitKind::WordBytes,
+                requested: 9,
+                allowed: 8,
+            })
+        ));
+        assert!(matches!(
+            options.check_word_bytes(8, 5),
+            Err(WizardError::LimitExceeded {
+                kind: LimitKind::DictionaryBytes,
+                requested: 13,
+                allowed: 12,
+            })
+        ));
+        assert!(DecodeOptions::unlimited()
+            .check_word_bytes(usize::MAX, u32::MAX as usize)
+            .is_ok());
+    }
+}
+
+/// Фаззинг проверяемого декодирования 🎲
+///
+/// Детерминированные мутации настоящих архивов и случайный мусор:
+/// `try_decompress_data` обязан вернуть результат или ошибку, но не паниковать.
+#[cfg(test)]
+mod decode_ward_fuzz_tests {
+    use super::*;
+    use crate::compression_engine::compressor_handle::{CompressorHandle, HandleArtifact};
+    use crate::compression_engine::engine_covenant::{EngineConfig, ParseStrategy};
+    use crate::compression_engine::engine_registry::EngineRegistry;
+    use crate::decompression_oracle::integrity_vigil::{validate_archive_with, ValidationDepth};
+    use crate::format::artifact_scroll::{parse_artifact, ScrollOptions};
+    use crate::format::container_runes::{ArchiveHeader, EngineKind, ModelKind};
+    use crate::format::identity_runes::content_id;
+    use crate::format::metadata_runes::read_metadata;
+    use crate::simple_api::{
+        compress_compact, compress_data, compress_data_in_blocks, compress_data_in_synced_blocks,
+        compress_data_with_options, compress_delta, compress_with_metadata, compress_with_parity,
+        compress_with_stats, decompress_lossy, try_compress_with_config, try_decompress_data,
+        try_decompress_delta, try_decompress_prefix,
+    };
+    use std::collections::HashMap;
+
+    /// Простой генератор xorshift64 (без внешних зависимостей)
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, bound: usize) -> usize {
+            (self.next() % bound.max(1) as u64) as usize
+        }
+    }
+
+    fn mutate(archive: &[u8], rng: &mut XorShift) -> Vec<u8> {
+        let mut mutated = archive.to_vec();
+        match rng.below(5) {
+            0 => {
+                // Переворот случайных битов
+                for _ in 0..=rng.below(4) {
+                    let position = rng.below(mutated.len());
+                    mutated[position] ^= 1 << rng.below(8);
+                }
+            }
+            1 => mutated.truncate(rng.below(mutated.len())),
+            2 => {
+                // Лживый 32-битный счетчик
+                let position = rng.below(mutated.len().saturating_sub(4));
+                let lie = (rng.next() as u32).to_le_bytes();
+                mutated[position..position + 4].copy_from_slice(&lie);
+            }
+            3 => {
+                let position = rng.below(mutated.len());
+                mutated[position] = rng.next() as u8;
+            }
+            _ => {
+                let len = rng.below(64);
+                mutated = (0..len).map(|_| rng.next() as u8).collect();
+            }
+        }
+        mutated
+    }
+
+    #[test]
+    fn test_mutated_archives_never_panic() {
+        let options = DecodeOptions::default().with_max_output_bytes(1 << 16);
+        let samples: [&[u8]; 4] = [
+            b"the quick brown fox jumps over the lazy dog the end",
+            b"aaaaaaaaaaaaaaaaaaaaaaaabbbbbbbb",
+            b"\x00\x01\x02\xff\xfe binary",
+            b"",
+        ];
+        let compact = ScrollOptions::default().with_compact_alphabet(true);
+        let mut rng = XorShift(0x9E37_79B9_7F4A_7C15);
+
+        let archives = samples.iter().flat_map(|&sample| {
+            [
+                (sample, compress_data(sample)),
+                (sample, compress_data_with_options(sample, &compact)),
+            ]
+        });
+        for (sample, archive) in archives {
+            assert_eq!(try_decompress_data(&archive, &options).unwrap(), sample);
+
+            for _ in 0..500 {
+                let mutated = mutate(&archive, &mut rng);
+                if let Ok(restored) = try_decompress_data(&mutated, &options) {
+                    assert!(restored.len() <= options.max_output_bytes);
+                }
+            }
+        }
+    }
+
+    /// Вход с повторяющимися словами и цепочками слов: словарь и фразы
+    fn prose() -> Vec<u8> {
+        let mut text = Vec::new();
+        for line in 0..40 {
+            text.extend_from_slice(
+                format!("the wizard reads the ancient scroll while the owl guards tower {line}. ")
+                    .as_bytes(),
+            );
+        }
+        text
+    }
+
+    /// Архивы каждого движка, модели и раздела заголовка
+    fn archive_corpus() -> Vec<(&'static str, Vec<u8>, Vec<u8>)> {
+        let text = prose();
+        let json: Vec<u8> = (0..60)
+            .flat_map(|index| {
+                format!("{{\"spell\": \"lumos\", \"power\": {index}}}\n").into_bytes()
+            })
+            .collect();
+        let logs: Vec<u8> = (0..60)
+            .flat_map(|index| {
+                format!("2024-05-01 12:00:{index:02} INFO wizard {index} cast lumos\n").into_bytes()
+            })
+            .collect();
+        let table: Vec<u8> = (0..200)
+            .flat_map(|index| format!("{},owl,lumos,{}\n", index % 7, index % 3).into_bytes())
+            .collect();
+        // Две одинаковые половины длиннее 4 КиБ: дедупликация и ссылки назад
+        let repeats = [&text[..], &text[..], &text[..], &text[..]].concat();
+
+        let mut corpus = Vec::new();
+        for engine in EngineRegistry::with_builtin_engines().engine_kinds() {
+            // Каждому движку — вход, который он сжимает по-своему
+            let (name, input) = match engine {
+                EngineKind::StructuredJson => ("json", &json),
+                EngineKind::LogTemplates => ("журнал", &logs),
+                EngineKind::Columnar => ("таблица", &table),
+                EngineKind::Dedup | EngineKind::LongRepeats => ("повторы", &repeats),
+                _ => ("текст", &text),
+            };
+            let config = EngineConfig::default().with_engine(engine);
+            let archive = try_compress_with_config(input, &config).unwrap();
+            // Кадр не заменен хранением: портится именно декодер движка
+            assert_eq!(ArchiveHeader::parse(&archive).unwrap().0.engine, engine);
+            corpus.push((name, input.clone(), archive));
+        }
+        for model in [
+            ModelKind::Adaptive { half_life: 64 },
+            ModelKind::HashedOrder2 { table_bits: 12 },
+        ] {
+            let config = EngineConfig::default().with_model(model);
+            corpus.push((
+                "модель",
+                text.clone(),
+                try_compress_with_config(&text, &config).unwrap(),
+            ));
+        }
+        let optimal = EngineConfig::default().with_parse_strategy(ParseStrategy::Optimal);
+        let stamped = EngineConfig::default().with_reproducible(false);
+        let metadata = HashMap::from([("owner".to_string(), "owl".to_string())]);
+        corpus.extend([
+            (
+                "разбор",
+                text.clone(),
+                try_compress_with_config(&text, &optimal).unwrap(),
+            ),
+            (
+                "штамп",
+                text.clone(),
+                try_compress_with_config(&text, &stamped).unwrap(),
+            ),
+            (
+                "метаданные",
+                text.clone(),
+                compress_with_metadata(&text, &metadata),
+            ),
+            ("статистика", text.clone(), compress_with_stats(&text)),
+            ("четность", text.clone(), compress_with_parity(&text)),
+            ("блоки", text.clone(), compress_data_in_blocks(&text, 700)),
+            (
+                "метки",
+                text.clone(),
+                compress_data_in_synced_blocks(&text, 700),
+            ),
+            ("компактный", b"owl".to_vec(), compress_compact(b"owl")),
+        ]);
+        corpus
+    }
+
+    #[test]
+    fn test_mutated_archives_of_every_kind_never_panic() {
+        let options = DecodeOptions::default().with_max_output_bytes(1 << 16);
+        let mut rng = XorShift(0x2545_F491_4F6C_DD1D);
+
+        let corpus = archive_corpus();
+        // Корпус действительно содержит словарь и фразы
+        let (artifact, _) = parse_artifact(&corpus[0].2).unwrap();
+        assert!(!artifact.mystical_word_grimoire.is_empty());
+        assert!(!artifact.mystical_phrase_codex.is_empty());
+
+        for (name, input, archive) in corpus {
+            assert_eq!(
+                try_decompress_data(&archive, &options).unwrap(),
+                input,
+                "{name}"
+            );
+
+            for _ in 0..100 {
+                let mutated = mutate(&archive, &mut rng);
+                if let Ok(restored) = try_decompress_data(&mutated, &options) {
+                    assert!(restored.len() <= options.max_output_bytes);
+                }
+                let _ = try_decompress_prefix(&mutated, 100, &options);
+                let _ = validate_archive_with(&mutated, ValidationDepth::Structure, &options);
+                let _ = read_metadata(&mutated);
+                let _ = content_id(&mutated);
+                let _ = decompress_lossy(&mutated);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mutated_shared_model_frames_and_patches_never_panic() {
+        let options = DecodeOptions::default().with_max_output_bytes(1 << 16);
+        let mut rng = XorShift(0x8CB9_2BA7_2F3D_8DD7);
+        let text = prose();
+
+        let handle = CompressorHandle::train(&text);
+        let frame = handle.compress(&text[..500]).to_bytes();
+        let base = &text[..1500];
+        let patch = compress_delta(base, &text);
+        for _ in 0..300 {
+            let mutated = mutate(&frame, &mut rng);
+            if let Some(artifact) = HandleArtifact::from_bytes(&mutated) {
+                let _ = handle.try_decompress(&artifact, &options);
+            }
+            let _ = try_decompress_delta(base, mutate(&patch, &mut rng), &options);
+        }
+    }
+
+    #[test]
+    fn test_claimed_huge_output_is_limited() {
+        let archive = compress_data(b"tiny");
+        let options = DecodeOptions::default().with_max_output_bytes(2);
+
+        assert!(matches!(
+            try_decompress_data(archive, &options),
+            Err(WizardError::LimitExceeded { .. })
+        ));
+    }
+}
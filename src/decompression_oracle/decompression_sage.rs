@@ -1,279 +1,1020 @@
-//! # Модуль Декомпрессии 🔮
-//!
-//! Этот модуль восстанавливает исходные данные из сжатых артефактов.
-//! Демонстрирует математическую связь между алгоритмами сжатия и декомпрессии.
-//!
-//! ## Как работает декомпрессия:
-//! Проходим те же интервалы вероятности, что и при сжатии, но в обратном порядке.
-//! Сжатый битовый поток указывает путь обратно к исходным символам.
-//!
-//! ## Возможности Rust в этом модуле:
-//! - **Pattern matching**: Восстановление символов с помощью match
-//! - **Итераторы**: Функциональная обработка данных
-//! - **Безопасность памяти**: Нет переполнений буфера
-//! - **Обработка ошибок**: Graceful fallback с Option/Result
-//! - **Ownership**: Эффективная передача данных
-//! - **Типобезопасность**: Предотвращение ошибок декомпрессии
-//! - **Точная арифметика**: Идеальное восстановление данных
-
-use crate::bit_wizardry::bit_manipulation_spells::{BitMagicReader, ARITHMETIC_PRECISION_LIMIT};
-use crate::compression_engine::compression_conjurer::CompressionArtifact;
-
-/// Основная функция декомпрессии 🔮
-///
-/// Восстанавливает исходные данные из сжатого артефакта.
-/// Функция принимает владение артефактом и возвращает исходные байты.
-///
-/// ## Алгоритм:
-/// 1. Инициализируем декодер из битового потока
-/// 2. Восстанавливаем символы по таблице частот
-/// 3. Навигируем по интервалам вероятности
-/// 4. Преобразуем символы обратно в байты
-///
-/// ## Важно:
-/// Эта функция должна быть точной математической противоположностью сжатия.
-/// Любая ошибка приведет к повреждению данных.
-///
-/// ## Параметры:
-/// - `enchanted_artifact`: Сжатый артефакт с данными для восстановления
-///
-/// ## Возвращает:
-/// - `Vec<u8>`: Восстановленная последовательность байтов
-pub fn unweave_compression_spell(enchanted_artifact: CompressionArtifact) -> Vec<u8> {
-    // Извлекаем компоненты артефакта
-    let CompressionArtifact {
-        mystical_frequency_codex,
-        total_frequency_essence,
-        compressed_bit_stream,
-        mystical_word_grimoire,
-    } = enchanted_artifact;
-
-    // Показываем таблицу частот для отладки
-    let original_size = total_frequency_essence as usize;
-    display_frequency_codex_wisdom(&mystical_frequency_codex, original_size);
-
-    // Создаем читатель битов (передаем владение данными)
-    let mut mystical_bit_reader = BitMagicReader::conjure_from_scroll(compressed_bit_stream);
-
-    // Инициализируем состояние арифметического кодирования
-    let mut interval_low = 0u32;
-    let mut interval_high = ARITHMETIC_PRECISION_LIMIT;
-
-    // Заранее резервируем память под результат
-    let mut decoded_symbols = Vec::with_capacity(total_frequency_essence as usize);
-
-    // Декодируем точно столько символов, сколько было закодировано
-    for _symbol_position in 0..total_frequency_essence {
-        // Определяем, какой символ соответствует текущей позиции в битовом потоке
-        let target_position = mystical_bit_reader.decode_mystical_target(
-            total_frequency_essence as u32,
-            interval_low,
-            interval_high,
-        );
-
-        // Ищем символ по позиции в таблице частот
-        let discovered_symbol = mystical_frequency_codex
-            .iter()
-            .find(|&&(_, symbol_frequency, cumulative_start)| {
-                let symbol_end = cumulative_start + symbol_frequency;
-                target_position >= cumulative_start as u32 && target_position < symbol_end as u32
-            })
-            .map(|&(symbol_id, _, _)| symbol_id)
-            .unwrap_or_else(|| {
-                // Если символ не найден, берем первый доступный
-                mystical_frequency_codex
-                    .first()
-                    .map(|&(symbol_id, _, _)| symbol_id)
-                    .unwrap_or(0)
-            });
-
-        // Обновляем интервалы кодирования для найденного символа
-        if let Some((_, symbol_frequency, cumulative_start)) = mystical_frequency_codex
-            .iter()
-            .find(|&&(symbol_id, _, _)| symbol_id == discovered_symbol)
-        {
-            let symbol_start = *cumulative_start as u32;
-            let symbol_end = (*cumulative_start + *symbol_frequency) as u32;
-            let total_mass = total_frequency_essence as u32;
-
-            // Обновляем состояние декодера
-            mystical_bit_reader.update_mystical_intervals(
-                &mut interval_low,
-                &mut interval_high,
-                symbol_start,
-                symbol_end,
-                total_mass,
-            );
-        }
-
-        // Добавляем декодированный символ в результат
-        decoded_symbols.push(discovered_symbol);
-    }
-
-    // Преобразуем символы обратно в исходные байты
-    reconstruct_original_manuscript(&decoded_symbols, &mystical_word_grimoire)
-}
-
-/// Восстанавливает исходные байты из символов 📜
-///
-/// Преобразует декодированные символы обратно в последовательность байтов.
-/// Использует ссылки на словарь для восстановления слов.
-///
-/// ## Типы символов:
-/// - 0-255: Обычные байты (копируются как есть)
-/// - 256+: Ссылки на слова из словаря
-///
-/// ## Параметры:
-/// - `decoded_mystical_symbols`: Декодированные символы
-/// - `word_grimoire`: Словарь слов для восстановления
-fn reconstruct_original_manuscript(
-    decoded_mystical_symbols: &[u32],
-    word_grimoire: &[String],
-) -> Vec<u8> {
-    // Создаем буфер для результата
-    let mut reconstructed_manuscript = Vec::new();
-
-    // Обрабатываем каждый символ
-    for &mystical_symbol in decoded_mystical_symbols {
-        // Определяем тип символа
-        match mystical_symbol {
-            // Обычный байт
-            0..=255 => {
-                // Добавляем байт как есть
-                reconstructed_manuscript.push(mystical_symbol as u8);
-            }
-            // Ссылка на слово из словаря
-            word_reference => {
-                // Вычисляем индекс в словаре
-                let grimoire_index = (word_reference - 256) as usize;
-
-                // Безопасно получаем слово из словаря
-                if let Some(enchanted_word) = word_grimoire.get(grimoire_index) {
-                    // Добавляем все байты слова в результат
-                    reconstructed_manuscript.extend_from_slice(enchanted_word.as_bytes());
-                }
-                // Недействительные ссылки игнорируются (защитное программирование)
-            }
-        }
-    }
-
-    reconstructed_manuscript
-}
-
-/// Отображает таблицу частот символов 📊
-///
-/// Показывает результаты анализа частот в табличном формате.
-/// Полезно для отладки и понимания эффективности сжатия.
-///
-/// ## Формат отображения:
-/// - ID символа: Числовой идентификатор (байт или ссылка на слово)
-/// - Частота: Количество появлений символа
-/// - Начало: Позиция начала в накопительной таблице
-/// - Конец: Позиция конца в накопительной таблице
-fn display_frequency_codex_wisdom(
-    mystical_frequency_codex: &[(u32, u64, u64)],
-    original_size: usize,
-) {
-    // Выводим заголовок таблицы
-    println!("📊 Original data: {} bytes", original_size);
-    println!("🔮 Mystical Frequency Codex:");
-    println!(
-        "{:<8} {:<12} {:<12} {}",
-        "Symbol", "Frequency", "Start", "End"
-    );
-    println!("{}", "━".repeat(45)); // Разделительная линия
-
-    // Сортируем по частоте и показываем только топ символов
-    let mut sorted_entries: Vec<_> = mystical_frequency_codex.iter().collect();
-    sorted_entries.sort_by_key(|(_, frequency_count, _)| std::cmp::Reverse(*frequency_count));
-
-    let max_entries = 20; // Показываем только топ-20 символов
-    let entries_to_show = sorted_entries.len().min(max_entries);
-
-    // Выводим записи таблицы
-    for &(symbol_id, frequency_count, cumulative_start) in
-        sorted_entries.iter().take(entries_to_show)
-    {
-        let cumulative_end = cumulative_start + frequency_count;
-
-        // Форматированный вывод строки таблицы
-        println!(
-            "{:<8} {:<12} {:<12} {}",
-            symbol_id, frequency_count, cumulative_start, cumulative_end
-        );
-    }
-
-    if mystical_frequency_codex.len() > max_entries {
-        println!(
-            "... and {} more symbols with lower frequencies",
-            mystical_frequency_codex.len() - max_entries
-        );
-    }
-
-    println!(); // Empty line for visual separation
-}
-
-/// Модульные тесты
-#[cfg(test)]
-mod decompression_sage_tests {
-    use super::*;
-    use crate::compression_engine::compression_conjurer::weave_compression_spell;
-
-    /// Тест полного цикла сжатие-декомпрессия
-    #[test]
-    fn test_compression_decompression_roundtrip() {
-        let original_data = b"Hello, magical world of Rust compression!";
-
-        // Выполняем сжатие и декомпрессию
-        let compressed_artifact = weave_compression_spell(original_data);
-        let reconstructed_data = unweave_compression_spell(compressed_artifact);
-
-        // Проверяем, что данные восстановлены точно
-        assert_eq!(original_data.as_slice(), reconstructed_data.as_slice());
-    }
-
-    #[test]
-    fn test_symbol_reconstruction_with_words() {
-        // Test data with repeated words for dictionary compression
-        let test_text = b"the quick brown fox jumps over the lazy dog";
-
-        let compressed = weave_compression_spell(test_text);
-        let reconstructed = unweave_compression_spell(compressed);
-
-        // Проверяем побайтовое равенство
-        assert_eq!(test_text.as_slice(), reconstructed.as_slice());
-    }
-
-    #[test]
-    fn test_empty_data_handling() {
-        let empty_data: &[u8] = b"";
-
-        let compressed = weave_compression_spell(empty_data);
-        let reconstructed = unweave_compression_spell(compressed);
-
-        // Проверяем обработку граничного случая
-        assert_eq!(empty_data, reconstructed.as_slice());
-    }
-
-    #[test]
-    fn test_single_byte_compression() {
-        let single_byte = b"A";
-
-        let compressed = weave_compression_spell(single_byte);
-        let reconstructed = unweave_compression_spell(compressed);
-
-        assert_eq!(single_byte.as_slice(), reconstructed.as_slice());
-    }
-
-    #[test]
-    fn test_non_ascii_character_preservation() {
-        // Include non-ASCII characters to test extended byte handling
-        let mixed_data = b"Caf\xc3\xa9 with non-breaking space\xa0here";
-
-        let compressed = weave_compression_spell(mixed_data);
-        let reconstructed = unweave_compression_spell(compressed);
-
-        // Проверяем сохранение расширенных символов
-        assert_eq!(mixed_data.as_slice(), reconstructed.as_slice());
-    }
-}
+//! # Модуль Декомпрессии 🔮
+//!
+//! Этот модуль восстанавливает исходные данные из сжатых артефактов.
+//! Демонстрирует математическую связь между алгоритмами сжатия и декомпрессии.
+//!
+//! ## Как работает декомпрессия:
+//! Проходим те же интервалы вероятности, что и при сжатии, но в обратном порядке.
+//! Сжатый битовый поток указывает путь обратно к исходным символам.
+//!
+//! ## Возможности Rust в этом модуле:
+//! - **Pattern matching**: Восстановление символов с помощью match
+//! - **Итераторы**: Функциональная обработка данных
+//! - **Безопасность памяти**: Нет переполнений буфера
+//! - **Обработка ошибок**: Graceful fallback с Option/Result
+//! - **Ownership**: Эффективная передача данных
+//! - **Типобезопасность**: Предотвращение ошибок декомпрессии
+//! - **Точная арифметика**: Идеальное восстановление данных
+
+use super::decode_wards::{validate_frequency_codex, validate_phrase_codex, DecodeOptions};
+use crate::bit_wizardry::bit_manipulation_spells::CoderPrecision;
+use crate::bit_wizardry::entropy_coder::ArithmeticDecoder;
+use crate::compression_engine::compression_conjurer::{
+    interpret_symbol, phrase_symbol, CompressionArtifact, SymbolMeaning,
+};
+use crate::error::{DecodePhase, WizardError, WizardResult};
+use crate::format::artifact_view::{ArtifactRef, CodexView};
+
+/// Сколько символов резервировать заранее (защита от лживых заголовков)
+const MAX_PREALLOCATED_SYMBOLS: u64 = 1 << 20;
+
+/// Число корзин указателя символов, бит
+const LOCATOR_BUCKET_BITS: u32 = 12;
+
+/// Сколько байтов результата резервировать заранее (защита от лживых таблиц)
+const MAX_PREALLOCATED_BYTES: u64 = 1 << 26;
+
+/// Сколько байтов копит [`try_unweave_with`] перед вызовом обработчика
+pub const UNWEAVE_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Основная функция декомпрессии 🔮
+///
+/// Восстанавливает исходные данные из сжатого артефакта.
+/// Функция принимает владение артефактом и возвращает исходные байты.
+///
+/// ## Алгоритм:
+/// 1. Инициализируем декодер из битового потока
+/// 2. Восстанавливаем символы по таблице частот
+/// 3. Навигируем по интервалам вероятности
+/// 4. Преобразуем символы обратно в байты
+///
+/// ## Важно:
+/// Эта функция должна быть точной математической противоположностью сжатия.
+/// Любая ошибка приведет к повреждению данных.
+///
+/// ## Параметры:
+/// - `enchanted_artifact`: Сжатый артефакт с данными для восстановления
+///
+/// ## Возвращает:
+/// - `Vec<u8>`: Восстановленная последовательность байтов
+pub fn unweave_compression_spell(enchanted_artifact: CompressionArtifact) -> Vec<u8> {
+    unweave_compression_spell_with_precision(enchanted_artifact, CoderPrecision::default())
+}
+
+/// Декомпрессия с явной разрядностью кодера
+///
+/// Нужна для артефактов из архивов версий 0 и 1, закодированных
+/// 24-битным кодером.
+pub fn unweave_compression_spell_with_precision(
+    enchanted_artifact: CompressionArtifact,
+    precision: CoderPrecision,
+) -> Vec<u8> {
+    try_unweave_compression_spell(enchanted_artifact, precision, &DecodeOptions::unlimited())
+        .expect("артефакт поврежден: используйте try_unweave_compression_spell")
+}
+
+/// Проверяемая декомпрессия для недоверенных артефактов 🛡️
+///
+/// Не паникует: несогласованная таблица частот, поврежденный битовый поток
+/// и превышение лимитов из `options` возвращаются как ошибки.
+pub fn try_unweave_compression_spell(
+    enchanted_artifact: CompressionArtifact,
+    precision: CoderPrecision,
+    options: &DecodeOptions,
+) -> WizardResult<Vec<u8>> {
+    // Извлекаем компоненты артефакта
+    let CompressionArtifact {
+        mystical_frequency_codex,
+        total_frequency_essence,
+        compressed_bit_stream,
+        mystical_word_grimoire,
+        mystical_phrase_codex,
+    } = enchanted_artifact;
+
+    let mut scribe = SymbolScribe::new(
+        &mystical_word_grimoire,
+        &mystical_phrase_codex,
+        usize::MAX,
+        options,
+    );
+    unweave_checked_parts(
+        &mystical_frequency_codex[..],
+        total_frequency_essence,
+        &compressed_bit_stream,
+        precision,
+        &mut scribe,
+    )?;
+    Ok(scribe.finish())
+}
+
+/// Проверяемая декомпрессия заимствованного артефакта 👁️
+///
+/// Слова, таблица частот и битовый поток читаются прямо из буфера архива,
+/// без промежуточного [`CompressionArtifact`]. Разрядность кодера берется
+/// из заголовка.
+pub fn try_unweave_artifact_ref(
+    artifact_ref: &ArtifactRef<'_>,
+    options: &DecodeOptions,
+) -> WizardResult<Vec<u8>> {
+    try_unweave_artifact_ref_prefix(artifact_ref, usize::MAX, options)
+}
+
+/// Распаковка только первых `limit` байтов заимствованного артефакта
+///
+/// Декодирование идет по порядку и останавливается, как только результат
+/// достиг `limit` байтов: остаток битового потока не читается.
+pub(crate) fn try_unweave_artifact_ref_prefix(
+    artifact_ref: &ArtifactRef<'_>,
+    limit: usize,
+    options: &DecodeOptions,
+) -> WizardResult<Vec<u8>> {
+    let mut scribe = SymbolScribe::new(
+        &artifact_ref.word_grimoire,
+        &artifact_ref.phrase_codex,
+        limit,
+        options,
+    );
+    unweave_checked_parts(
+        &artifact_ref.frequency_codex,
+        artifact_ref.total_frequency_essence,
+        artifact_ref.compressed_bit_stream,
+        artifact_ref.header.coder_precision(),
+        &mut scribe,
+    )?;
+    Ok(scribe.finish())
+}
+
+/// Проверяемая распаковка заимствованного артефакта в буфер вызывающего 📥
+///
+/// Байты дописываются в конец `output`, уже лежащие там данные не
+/// трогаются; возвращается число дописанных байтов. Буфер расширяется
+/// один раз на точную длину результата, поэтому при распаковке многих
+/// архивов подряд в один буфер память не перевыделяется. При ошибке
+/// `output` возвращается к прежней длине.
+///
+/// ```
+/// use arithmetic_compression_wizard::decompression_oracle::{try_unweave_artifact_ref_into, DecodeOptions};
+/// use arithmetic_compression_wizard::format::parse_artifact_ref;
+/// use arithmetic_compression_wizard::simple_api::compress_data;
+///
+/// let options = DecodeOptions::default();
+/// let mut output = b"> ".to_vec();
+/// let archive = compress_data("the owl and the wand and the owl");
+/// let artifact_ref = parse_artifact_ref(&archive, &options).unwrap();
+/// let written = try_unweave_artifact_ref_into(&artifact_ref, &mut output, &options).unwrap();
+/// assert_eq!(written, 32);
+/// assert_eq!(output, b"> the owl and the wand and the owl");
+/// ```
+pub fn try_unweave_artifact_ref_into(
+    artifact_ref: &ArtifactRef<'_>,
+    output: &mut Vec<u8>,
+    options: &DecodeOptions,
+) -> WizardResult<usize> {
+    let start = output.len();
+    let mut scribe = SymbolScribe::new(
+        &artifact_ref.word_grimoire,
+        &artifact_ref.phrase_codex,
+        usize::MAX,
+        options,
+    )
+    .appending_to(std::mem::take(output));
+    let result = unweave_checked_parts(
+        &artifact_ref.frequency_codex,
+        artifact_ref.total_frequency_essence,
+        artifact_ref.compressed_bit_stream,
+        artifact_ref.header.coder_precision(),
+        &mut scribe,
+    );
+    *output = scribe.finish();
+    if let Err(error) = result {
+        output.truncate(start);
+        return Err(error);
+    }
+    Ok(output.len() - start)
+}
+
+/// Распаковка артефакта кусками в обработчик 🪣
+///
+/// Паникующий вариант [`try_unweave_with`] для собственного артефакта
+/// (разрядность кодера по умолчанию, без лимитов).
+///
+/// ```
+/// use arithmetic_compression_wizard::decompression_oracle::unweave_with;
+/// use arithmetic_compression_wizard::weave_compression_spell;
+///
+/// let artifact = weave_compression_spell(b"{\"owl\": 1} ".repeat(100));
+/// let mut braces = 0;
+/// unweave_with(&artifact, |chunk| {
+///     braces += chunk.iter().filter(|&&byte| byte == b'{').count();
+///     true
+/// });
+/// assert_eq!(braces, 100);
+/// ```
+pub fn unweave_with(
+    artifact: &CompressionArtifact,
+    mut on_chunk: impl FnMut(&[u8]) -> bool,
+) -> u64 {
+    let options = DecodeOptions::unlimited();
+    let mut scribe = SymbolScribe::new(
+        &artifact.mystical_word_grimoire,
+        &artifact.mystical_phrase_codex,
+        usize::MAX,
+        &options,
+    )
+    .streaming_to(&mut on_chunk);
+    unweave_checked_parts(
+        &artifact.mystical_frequency_codex[..],
+        artifact.total_frequency_essence,
+        &artifact.compressed_bit_stream,
+        CoderPrecision::default(),
+        &mut scribe,
+    )
+    .expect("артефакт поврежден: используйте try_unweave_with");
+    scribe.finish_streaming()
+}
+
+/// Проверяемая распаковка кусками в обработчик 🪣
+///
+/// Вместо буфера на весь результат `on_chunk` получает восстановленные
+/// байты по порядку кусками примерно по [`UNWEAVE_CHUNK_BYTES`] (кусок
+/// может быть длиннее на одно слово словаря; последний короче) — так
+/// потоковый разборщик обрабатывает документ по мере распаковки.
+/// `false` из обработчика останавливает распаковку: остаток битового
+/// потока не декодируется. Возвращает число переданных байтов.
+///
+/// Лимит размера результата проверяется до первого куска. Порча,
+/// найденная посреди потока, дает ошибку уже после части кусков:
+/// обработчик должен быть готов отбросить полученное.
+///
+/// ```
+/// use arithmetic_compression_wizard::decompression_oracle::{try_unweave_with, DecodeOptions};
+/// use arithmetic_compression_wizard::format::parse_artifact_ref;
+/// use arithmetic_compression_wizard::simple_api::compress_data;
+///
+/// let options = DecodeOptions::default();
+/// let archive = compress_data("line one\nline two\nthe needle\nline four\n".repeat(10));
+/// let artifact_ref = parse_artifact_ref(&archive, &options).unwrap();
+/// let mut seen = Vec::new();
+/// // Нужна только первая строка с иглой: дальше можно не распаковывать
+/// try_unweave_with(&artifact_ref, &options, |chunk| {
+///     seen.extend_from_slice(chunk);
+///     !seen.windows(6).any(|window| window == b"needle")
+/// })
+/// .unwrap();
+/// assert!(String::from_utf8(seen).unwrap().contains("the needle"));
+/// ```
+pub fn try_unweave_with(
+    artifact_ref: &ArtifactRef<'_>,
+    options: &DecodeOptions,
+    mut on_chunk: impl FnMut(&[u8]) -> bool,
+) -> WizardResult<u64> {
+    let mut scribe = SymbolScribe::new(
+        &artifact_ref.word_grimoire,
+        &artifact_ref.phrase_codex,
+        usize::MAX,
+        options,
+    )
+    .streaming_to(&mut on_chunk);
+    unweave_checked_parts(
+        &artifact_ref.frequency_codex,
+        artifact_ref.total_frequency_essence,
+        artifact_ref.compressed_bit_stream,
+        artifact_ref.header.coder_precision(),
+        &mut scribe,
+    )?;
+    Ok(scribe.finish_streaming())
+}
+
+/// Общий путь проверяемой декомпрессии для владеющих и заимствованных артефактов
+///
+/// Словарь, фразы, лимиты и длину нужного префикса несет `scribe`.
+fn unweave_checked_parts(
+    frequency_codex: &(impl CodexView + ?Sized),
+    total_frequency_essence: u64,
+    compressed_bit_stream: &[u8],
+    precision: CoderPrecision,
+    scribe: &mut SymbolScribe<'_>,
+) -> WizardResult<()> {
+    // Лимиты проверяются до любых выделений памяти
+    let options = scribe.options;
+    options.check_dict_entries(scribe.word_slices.len())?;
+    options.check_dict_entries(scribe.phrase_codex.len())?;
+    options.check_table_entries(frequency_codex.entry_count())?;
+    validate_phrase_codex(scribe.phrase_codex, scribe.word_slices.len())?;
+
+    // Частоты статической модели — точные счетчики символов, поэтому
+    // длина проверенной таблицы известна до декодирования
+    validate_frequency_codex(frequency_codex, total_frequency_essence, precision)
+        .map_err(|error| error.located(DecodePhase::Table, 0))?;
+    let manuscript_len = scribe.manuscript_len(frequency_codex);
+    options.reserve_output(manuscript_len.min(scribe.limit as u64))?;
+
+    // Символы сразу раскрываются в байты, пока результат короче лимита
+    scribe.reserve_bytes(manuscript_len);
+    decode_symbols_while(
+        compressed_bit_stream,
+        frequency_codex,
+        total_frequency_essence,
+        total_frequency_essence,
+        precision,
+        |symbol| scribe.write(symbol),
+    )
+}
+
+/// Декодирует заданное число символов по таблице частот
+///
+/// Общая масса частот и число символов разделены: для общей модели
+/// (например, в [`CompressorHandle`](crate::compression_engine::CompressorHandle))
+/// они не совпадают. Таблица проверяется заранее, поэтому декодер
+/// не выходит за пределы интервала даже на мусорном битовом потоке.
+pub(crate) fn decode_symbol_stream(
+    compressed_bit_stream: &[u8],
+    frequency_codex: &(impl CodexView + ?Sized),
+    total_frequency_mass: u64,
+    symbol_count: u64,
+    precision: CoderPrecision,
+) -> WizardResult<Vec<u32>> {
+    // Заранее резервируем память под результат
+    let mut decoded_symbols =
+        Vec::with_capacity(symbol_count.min(MAX_PREALLOCATED_SYMBOLS) as usize);
+    decode_symbols_while(
+        compressed_bit_stream,
+        frequency_codex,
+        total_frequency_mass,
+        symbol_count,
+        precision,
+        |symbol| {
+            decoded_symbols.push(symbol);
+            Ok(true)
+        },
+    )?;
+    Ok(decoded_symbols)
+}
+
+/// Декодирует символы по одному, пока `accept` просит продолжать
+///
+/// `accept` получает очередной символ и возвращает `false`, когда
+/// символов достаточно: остаток потока не декодируется.
+pub(crate) fn decode_symbols_while(
+    compressed_bit_stream: &[u8],
+    frequency_codex: &(impl CodexView + ?Sized),
+    total_frequency_mass: u64,
+    symbol_count: u64,
+    precision: CoderPrecision,
+    mut accept: impl FnMut(u32) -> WizardResult<bool>,
+) -> WizardResult<()> {
+    // Порча таблицы видна до первого символа: место — начало потока
+    validate_frequency_codex(frequency_codex, total_frequency_mass, precision)
+        .map_err(|error| error.located(DecodePhase::Table, 0))?;
+    if symbol_count > 0 && total_frequency_mass == 0 {
+        return Err(WizardError::CorruptFrequencyTable("пустая таблица частот")
+            .located(DecodePhase::Table, 0));
+    }
+
+    // Декодер читает заимствованный поток без копирования
+    let mut entropy_decoder = ArithmeticDecoder::new(compressed_bit_stream, precision);
+    let total_mass = total_frequency_mass as u32;
+    let symbol_locator = SymbolLocator::new(frequency_codex, total_frequency_mass);
+
+    for _symbol_position in 0..symbol_count {
+        let decoded = decode_next_symbol(&mut entropy_decoder, &symbol_locator, total_mass)
+            .and_then(&mut accept)
+            // Место порчи — байт потока, на котором остановился декодер
+            .map_err(|error| {
+                error.located(DecodePhase::Payload, entropy_decoder.stream_position())
+            })?;
+        if !decoded {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Снимает с потока один символ по таблице частот
+fn decode_next_symbol(
+    entropy_decoder: &mut ArithmeticDecoder<'_>,
+    symbol_locator: &SymbolLocator,
+    total_mass: u32,
+) -> WizardResult<u32> {
+    // Определяем, какой символ соответствует текущей позиции в битовом потоке
+    let target_position = entropy_decoder.decode_target(total_mass)? as u64;
+
+    // Ищем символ по позиции в таблице частот
+    let (discovered_symbol, cumulative_start, cumulative_end) = symbol_locator
+        .locate(target_position)
+        .ok_or(WizardError::CorruptPayload)?;
+
+    // Обновляем состояние декодера
+    entropy_decoder.consume(cumulative_start as u32, cumulative_end as u32, total_mass)?;
+    Ok(discovered_symbol)
+}
+
+/// Поиск символа по позиции внутри общей массы частот 🧭
+///
+/// Границы интервалов копируются из таблицы один раз на кадр. Масса
+/// делится на `2^LOCATOR_BUCKET_BITS` равных корзин, и для каждой
+/// запоминается первая запись, задевающая корзину: поиск сводится к
+/// двоичному поиску среди записей одной корзины — обычно одной-двух.
+#[derive(Debug)]
+struct SymbolLocator {
+    /// Символы в порядке таблицы
+    symbols: Vec<u32>,
+    /// Накопительные позиции записей и общая масса в конце
+    boundaries: Vec<u64>,
+    /// Первая запись каждой корзины
+    bucket_starts: Vec<u32>,
+    /// Сдвиг позиции до номера корзины
+    bucket_shift: u32,
+}
+
+impl SymbolLocator {
+    /// Строит указатель по проверенной таблице частот
+    fn new(frequency_codex: &(impl CodexView + ?Sized), total_mass: u64) -> Self {
+        let entry_count = frequency_codex.entry_count();
+        let mut symbols = Vec::with_capacity(entry_count);
+        let mut boundaries = Vec::with_capacity(entry_count + 1);
+        for index in 0..entry_count {
+            let (symbol, _, cumulative_start) = frequency_codex.entry(index);
+            symbols.push(symbol);
+            boundaries.push(cumulative_start);
+        }
+        boundaries.push(total_mass);
+
+        let mass_bits = u64::BITS - total_mass.leading_zeros();
+        let bucket_shift = mass_bits.saturating_sub(LOCATOR_BUCKET_BITS);
+        let bucket_count = (total_mass >> bucket_shift) as usize + 1;
+        let mut bucket_starts = Vec::with_capacity(bucket_count);
+        let mut entry = 0usize;
+        for bucket in 0..bucket_count as u64 {
+            let bucket_position = bucket << bucket_shift;
+            while entry + 1 < entry_count && boundaries[entry + 1] <= bucket_position {
+                entry += 1;
+            }
+            bucket_starts.push(entry as u32);
+        }
+
+        Self {
+            symbols,
+            boundaries,
+            bucket_starts,
+            bucket_shift,
+        }
+    }
+
+    /// `(символ, начало, конец)` записи, чей интервал содержит позицию
+    fn locate(&self, target_position: u64) -> Option<(u32, u64, u64)> {
+        let bucket = usize::try_from(target_position >> self.bucket_shift).ok()?;
+        let first = *self.bucket_starts.get(bucket)? as usize;
+        let last = self
+            .bucket_starts
+            .get(bucket + 1)
+            .map_or(self.symbols.len(), |&next| next as usize + 1)
+            .min(self.symbols.len());
+
+        // Последняя запись корзины, начинающаяся не позже позиции
+        let candidates = self.boundaries.get(first..last)?;
+        let offset = candidates
+            .partition_point(|&start| start <= target_position)
+            .checked_sub(1)?;
+        let index = first + offset;
+        let (start, end) = (self.boundaries[index], self.boundaries[index + 1]);
+        (target_position < end).then(|| (self.symbols[index], start, end))
+    }
+}
+
+/// Восстанавливает исходные байты из символов 📜
+///
+/// Преобразует декодированные символы обратно в последовательность байтов.
+/// Использует ссылки на словарь для восстановления слов.
+///
+/// ## Типы символов:
+/// - 0-255: Обычные байты (копируются как есть)
+/// - 256..256+n: Ссылки на слова из словаря
+/// - 256+n..256+2n: Слово и пробел после него
+/// - 256+2n+: Фразы, раскрываемые рекурсивно
+///
+/// ## Параметры:
+/// - `decoded_mystical_symbols`: Декодированные символы
+/// - `word_grimoire`: Словарь слов для восстановления
+/// - `phrase_codex`: Фразы (элементы ссылаются только на меньшие символы)
+/// - `options`: Лимит размера результата
+pub(crate) fn reconstruct_original_manuscript<W: AsRef<[u8]>>(
+    decoded_mystical_symbols: &[u32],
+    word_grimoire: &[W],
+    phrase_codex: &[Vec<u32>],
+    options: &DecodeOptions,
+) -> WizardResult<Vec<u8>> {
+    let mut scribe = SymbolScribe::new(word_grimoire, phrase_codex, usize::MAX, options);
+    // Длина результата известна заранее: буфер выделяется один раз
+    let manuscript_len = scribe.symbols_len(decoded_mystical_symbols);
+    options.reserve_output(manuscript_len)?;
+    scribe.reserve_bytes(manuscript_len);
+    for &top_symbol in decoded_mystical_symbols {
+        scribe.write(top_symbol)?;
+    }
+    Ok(scribe.finish())
+}
+
+/// Обработчик кусков результата: `false` — остановить распаковку
+pub(crate) type ChunkSink<'a> = dyn FnMut(&[u8]) -> bool + 'a;
+
+/// Писец: раскрывает символы в байты, пока результат короче лимита ✍️
+pub(crate) struct SymbolScribe<'a> {
+    /// Байты слов, собранные один раз на весь кадр
+    word_slices: Vec<&'a [u8]>,
+    phrase_codex: &'a [Vec<u32>],
+    /// Длины фраз в байтах после полного раскрытия
+    phrase_lens: Vec<u64>,
+    options: &'a DecodeOptions,
+    /// Сколько байтов нужно; лишнее отрезает [`SymbolScribe::finish`]
+    limit: usize,
+    reconstructed_manuscript: Vec<u8>,
+    /// Длина буфера до первого записанного байта
+    start: usize,
+    /// Явный стек вместо рекурсии: глубокие цепочки фраз не переполнят стек
+    pending_symbols: Vec<u32>,
+    /// Обработчик кусков: буфер отдается ему и очищается
+    sink: Option<&'a mut ChunkSink<'a>>,
+    /// Сколько байтов уже отдано обработчику
+    drained: usize,
+}
+
+impl<'a> SymbolScribe<'a> {
+    /// Писец первых `limit` байтов (`usize::MAX` — всех)
+    pub(crate) fn new<W: AsRef<[u8]>>(
+        word_grimoire: &'a [W],
+        phrase_codex: &'a [Vec<u32>],
+        limit: usize,
+        options: &'a DecodeOptions,
+    ) -> Self {
+        let word_slices: Vec<&[u8]> = word_grimoire.iter().map(AsRef::as_ref).collect();
+
+        // Элементы фразы ссылаются только на меньшие символы, поэтому
+        // длины вложенных фраз уже посчитаны к моменту их использования
+        let mut phrase_lens = Vec::with_capacity(phrase_codex.len());
+        for (index, phrase) in phrase_codex.iter().enumerate() {
+            let own_symbol = phrase_symbol(index, word_slices.len());
+            let phrase_len = phrase
+                .iter()
+                .filter(|&&element| element < own_symbol)
+                .map(|&element| symbol_len(element, &word_slices, &phrase_lens))
+                .fold(0u64, u64::saturating_add);
+            phrase_lens.push(phrase_len);
+        }
+
+        Self {
+            word_slices,
+            phrase_codex,
+            phrase_lens,
+            options,
+            limit,
+            reconstructed_manuscript: Vec::new(),
+            start: 0,
+            pending_symbols: Vec::new(),
+            sink: None,
+            drained: 0,
+        }
+    }
+
+    /// Писец, дописывающий байты в конец готового буфера
+    pub(crate) fn appending_to(mut self, output: Vec<u8>) -> Self {
+        self.start = output.len();
+        self.reconstructed_manuscript = output;
+        self
+    }
+
+    /// Писец, отдающий байты обработчику кусками по [`UNWEAVE_CHUNK_BYTES`]
+    pub(crate) fn streaming_to(mut self, sink: &'a mut ChunkSink<'a>) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    /// Точная длина результата по таблице частот статической модели
+    ///
+    /// Каждый символ встречается ровно столько раз, сколько указано в
+    /// таблице. Для поврежденной таблицы это лишь оценка: лимиты все равно
+    /// проверяются при каждой записи.
+    pub(crate) fn manuscript_len(&self, frequency_codex: &(impl CodexView + ?Sized)) -> u64 {
+        (0..frequency_codex.entry_count())
+            .map(|index| {
+                let (symbol, symbol_frequency, _) = frequency_codex.entry(index);
+                symbol_frequency.saturating_mul(symbol_len(
+                    symbol,
+                    &self.word_slices,
+                    &self.phrase_lens,
+                ))
+            })
+            .fold(0u64, u64::saturating_add)
+    }
+
+    /// Длина раскрытой последовательности символов в байтах
+    pub(crate) fn symbols_len(&self, symbols: &[u32]) -> u64 {
+        symbols
+            .iter()
+            .map(|&symbol| symbol_len(symbol, &self.word_slices, &self.phrase_lens))
+            .fold(0u64, u64::saturating_add)
+    }
+
+    /// Заранее резервирует память под `symbol_count` символов
+    pub(crate) fn reserve(&mut self, symbol_count: u64) {
+        self.reserve_bytes(symbol_count);
+    }
+
+    /// Заранее резервирует память под `byte_count` байтов результата
+    pub(crate) fn reserve_bytes(&mut self, byte_count: u64) {
+        let mut capacity = (byte_count.min(MAX_PREALLOCATED_BYTES) as usize).min(self.limit);
+        if self.sink.is_some() {
+            capacity = capacity.min(UNWEAVE_CHUNK_BYTES);
+        }
+        self.reconstructed_manuscript.reserve(capacity);
+    }
+
+    /// Раскрывает символ; `false` — лимит набран и символы больше не нужны
+    pub(crate) fn write(&mut self, top_symbol: u32) -> WizardResult<bool> {
+        let word_slices = &self.word_slices;
+        let reconstructed_manuscript = &mut self.reconstructed_manuscript;
+        // Байты, уже отданные обработчику, тоже считаются записанными
+        let start = self.start;
+        let drained = self.drained;
+        self.pending_symbols.push(top_symbol);
+
+        while reconstructed_manuscript.len() + drained - start < self.limit {
+            let Some(mystical_symbol) = self.pending_symbols.pop() else {
+                break;
+            };
+            let written = reconstructed_manuscript.len() + drained - start;
+            // Определяем тип символа
+            match interpret_symbol(mystical_symbol, word_slices.len()) {
+                // Обычный байт
+                SymbolMeaning::Byte(byte) => {
+                    self.options.check_output(written as u64 + 1)?;
+                    // Добавляем байт как есть
+                    reconstructed_manuscript.push(byte);
+                }
+                // Ссылка на слово из словаря
+                SymbolMeaning::Word {
+                    index,
+                    trailing_space,
+                } => {
+                    let enchanted_word = word_slices[index];
+                    self.options.check_output(
+                        (written + enchanted_word.len() + trailing_space as usize) as u64,
+                    )?;
+                    // Добавляем все байты слова в результат
+                    reconstructed_manuscript.extend_from_slice(enchanted_word);
+                    if trailing_space {
+                        reconstructed_manuscript.push(b' ');
+                    }
+                }
+                // Фраза раскрывается в свои элементы по порядку
+                SymbolMeaning::Phrase { index } => {
+                    if let Some(phrase) = self.phrase_codex.get(index) {
+                        self.pending_symbols.extend(
+                            phrase
+                                .iter()
+                                .rev()
+                                .filter(|&&element| element < mystical_symbol),
+                        );
+                    }
+                    // Недействительные ссылки игнорируются (защитное программирование)
+                }
+            }
+        }
+
+        let wanted = reconstructed_manuscript.len() + drained - start < self.limit;
+        if self.reconstructed_manuscript.len() >= UNWEAVE_CHUNK_BYTES && !self.drain() {
+            return Ok(false);
+        }
+        Ok(wanted)
+    }
+
+    /// Отдает накопленные байты обработчику; `false` — он просит остановиться
+    fn drain(&mut self) -> bool {
+        let Some(sink) = self.sink.as_mut() else {
+            return true;
+        };
+        if self.reconstructed_manuscript.is_empty() {
+            return true;
+        }
+        let wanted = sink(&self.reconstructed_manuscript);
+        self.drained += self.reconstructed_manuscript.len();
+        self.reconstructed_manuscript.clear();
+        wanted
+    }
+
+    /// Отдает обработчику остаток и возвращает число отданных байтов
+    pub(crate) fn finish_streaming(mut self) -> u64 {
+        self.drain();
+        self.drained as u64
+    }
+
+    /// Восстановленные байты, не длиннее лимита
+    pub(crate) fn finish(mut self) -> Vec<u8> {
+        self.reconstructed_manuscript
+            .truncate(self.start.saturating_add(self.limit));
+        self.reconstructed_manuscript
+    }
+}
+
+/// Длина символа в байтах после раскрытия
+fn symbol_len(symbol: u32, word_slices: &[&[u8]], phrase_lens: &[u64]) -> u64 {
+    match interpret_symbol(symbol, word_slices.len()) {
+        SymbolMeaning::Byte(_) => 1,
+        SymbolMeaning::Word {
+            index,
+            trailing_space,
+        } => word_slices[index].len() as u64 + trailing_space as u64,
+        SymbolMeaning::Phrase { index } => phrase_lens.get(index).copied().unwrap_or(0),
+    }
+}
+
+/// Модульные тесты
+#[cfg(test)]
+mod decompression_sage_tests {
+    use super::*;
+    use crate::compression_engine::compression_conjurer::{
+        analyze_symbolic_frequencies, discover_profitable_word_enchantments, encode_symbol_stream,
+        transform_manuscript_to_symbols, weave_compression_spell,
+    };
+    use crate::compression_engine::grimoire_ward::DenyList;
+    use crate::compression_engine::word_sketch::WordCounting;
+
+    /// Тест полного цикла сжатие-декомпрессия
+    #[test]
+    fn test_compression_decompression_roundtrip() {
+        let original_data = b"Hello, magical world of Rust compression!";
+
+        // Выполняем сжатие и декомпрессию
+        let compressed_artifact = weave_compression_spell(original_data);
+        let reconstructed_data = unweave_compression_spell(compressed_artifact);
+
+        // Проверяем, что данные восстановлены точно
+        assert_eq!(original_data.as_slice(), reconstructed_data.as_slice());
+    }
+
+    #[test]
+    fn test_symbol_reconstruction_with_words() {
+        // Test data with repeated words for dictionary compression
+        let test_text = b"the quick brown fox jumps over the lazy dog";
+
+        let compressed = weave_compression_spell(test_text);
+        let reconstructed = unweave_compression_spell(compressed);
+
+        // Проверяем побайтовое равенство
+        assert_eq!(test_text.as_slice(), reconstructed.as_slice());
+    }
+
+    #[test]
+    fn test_empty_data_handling() {
+        let empty_data: &[u8] = b"";
+
+        let compressed = weave_compression_spell(empty_data);
+        let reconstructed = unweave_compression_spell(compressed);
+
+        // Проверяем обработку граничного случая
+        assert_eq!(empty_data, reconstructed.as_slice());
+    }
+
+    #[test]
+    fn test_single_byte_compression() {
+        let single_byte = b"A";
+
+        let compressed = weave_compression_spell(single_byte);
+        let reconstructed = unweave_compression_spell(compressed);
+
+        assert_eq!(single_byte.as_slice(), reconstructed.as_slice());
+    }
+
+    #[test]
+    fn test_non_ascii_character_preservation() {
+        // Include non-ASCII characters to test extended byte handling
+        let mixed_data = b"Caf\xc3\xa9 with non-breaking space\xa0here";
+
+        let compressed = weave_compression_spell(mixed_data);
+        let reconstructed = unweave_compression_spell(compressed);
+
+        // Проверяем сохранение расширенных символов
+        assert_eq!(mixed_data.as_slice(), reconstructed.as_slice());
+    }
+
+    #[test]
+    fn test_legacy_precision_roundtrip() {
+        let original_data = b"legacy archives were coded with 24-bit registers";
+        let words = discover_profitable_word_enchantments(
+            original_data,
+            &WordCounting::Exact,
+            &DenyList::default(),
+        );
+        let symbols = transform_manuscript_to_symbols(original_data, &words);
+        let analysis = analyze_symbolic_frequencies(&symbols).unwrap();
+        let stream = encode_symbol_stream(
+            &symbols,
+            &analysis.frequency_entries,
+            analysis.total_frequency_mass,
+            CoderPrecision::Bits24,
+        )
+        .unwrap();
+
+        let artifact = CompressionArtifact {
+            mystical_frequency_codex: analysis.frequency_entries,
+            total_frequency_essence: analysis.total_frequency_mass,
+            compressed_bit_stream: stream,
+            mystical_word_grimoire: words,
+            mystical_phrase_codex: Vec::new(),
+        };
+        let reconstructed =
+            unweave_compression_spell_with_precision(artifact, CoderPrecision::Bits24);
+
+        assert_eq!(original_data.as_slice(), reconstructed.as_slice());
+    }
+
+    #[test]
+    fn test_spaced_word_symbols_roundtrip() {
+        let original_data = b"wand wand, wand  wand\nwand";
+        let words = vec!["wand".to_string()];
+        let symbols = transform_manuscript_to_symbols(original_data, &words);
+        let analysis = analyze_symbolic_frequencies(&symbols).unwrap();
+        let stream = encode_symbol_stream(
+            &symbols,
+            &analysis.frequency_entries,
+            analysis.total_frequency_mass,
+            CoderPrecision::default(),
+        )
+        .unwrap();
+
+        let artifact = CompressionArtifact {
+            mystical_frequency_codex: analysis.frequency_entries,
+            total_frequency_essence: analysis.total_frequency_mass,
+            compressed_bit_stream: stream,
+            mystical_word_grimoire: words,
+            mystical_phrase_codex: Vec::new(),
+        };
+        let reconstructed = unweave_compression_spell(artifact);
+
+        assert!(symbols.contains(&257));
+        assert_eq!(original_data.as_slice(), reconstructed.as_slice());
+    }
+
+    #[test]
+    fn test_nested_phrase_expansion() {
+        // Словарь из 2 слов: фраза 0 = символ 260, фраза 1 = символ 261
+        let words = vec!["abra".to_string(), "cadabra".to_string()];
+        let phrases = vec![vec![258, 257], vec![260, b'!' as u32]];
+        let symbols = [261u32, b' ' as u32, 260];
+
+        let restored = reconstruct_original_manuscript(
+            &symbols,
+            &words,
+            &phrases,
+            &DecodeOptions::unlimited(),
+        )
+        .unwrap();
+        assert_eq!(restored, b"abra cadabra! abra cadabra");
+
+        let limited = DecodeOptions::default().with_max_output_bytes(8);
+        assert!(reconstruct_original_manuscript(&symbols, &words, &phrases, &limited).is_err());
+    }
+
+    #[test]
+    fn test_repeated_sentences_use_phrases() {
+        let original_data = "the wizard casts the spell. ".repeat(40);
+        let artifact = weave_compression_spell(&original_data);
+
+        assert!(!artifact.mystical_phrase_codex.is_empty());
+        let reconstructed = unweave_compression_spell(artifact);
+        assert_eq!(original_data.as_bytes(), reconstructed.as_slice());
+    }
+
+    #[test]
+    fn test_unweave_into_appends_with_exact_capacity() {
+        use crate::format::artifact_scroll::parse_artifact_ref;
+        use crate::simple_api::compress_data;
+
+        let options = DecodeOptions::default();
+        let original_data = "the wizard casts the spell. ".repeat(40);
+        let archive = compress_data(&original_data);
+        let artifact_ref = parse_artifact_ref(&archive, &options).unwrap();
+
+        // Длина по таблице частот совпадает с исходником
+        let scribe = SymbolScribe::new(
+            &artifact_ref.word_grimoire,
+            &artifact_ref.phrase_codex,
+            usize::MAX,
+            &options,
+        );
+        assert!(!artifact_ref.phrase_codex.is_empty());
+        assert_eq!(
+            scribe.manuscript_len(&artifact_ref.frequency_codex),
+            original_data.len() as u64
+        );
+
+        let mut output = b"header|".to_vec();
+        let written = try_unweave_artifact_ref_into(&artifact_ref, &mut output, &options).unwrap();
+        assert_eq!(written, original_data.len());
+        assert_eq!(&output[..7], b"header|");
+        assert_eq!(&output[7..], original_data.as_bytes());
+        assert_eq!(output.capacity(), output.len());
+
+        // Ошибка не оставляет в буфере половину результата
+        let limited = options.with_max_output_bytes(100);
+        let mut output = b"keep".to_vec();
+        assert!(matches!(
+            try_unweave_artifact_ref_into(&artifact_ref, &mut output, &limited),
+            Err(WizardError::LimitExceeded { .. })
+        ));
+        assert_eq!(output, b"keep");
+    }
+
+    #[test]
+    fn test_unweave_with_streams_chunks() {
+        use crate::format::artifact_scroll::parse_artifact_ref;
+        use crate::simple_api::compress_data;
+
+        let options = DecodeOptions::default();
+        let original_data = (0..12_000)
+            .map(|index| format!("{{\"owl\": {index}, \"tower\": \"north\"}}\n"))
+            .collect::<String>();
+        assert!(original_data.len() > 4 * UNWEAVE_CHUNK_BYTES);
+        let archive = compress_data(&original_data);
+        let artifact_ref = parse_artifact_ref(&archive, &options).unwrap();
+
+        let mut chunks: Vec<Vec<u8>> = Vec::new();
+        let delivered = try_unweave_with(&artifact_ref, &options, |chunk| {
+            chunks.push(chunk.to_vec());
+            true
+        })
+        .unwrap();
+        assert_eq!(delivered, original_data.len() as u64);
+        assert_eq!(chunks.concat(), original_data.as_bytes());
+        assert!(chunks.len() > 4);
+        let longest_word = artifact_ref
+            .word_grimoire
+            .iter()
+            .map(|word| word.len())
+            .max()
+            .unwrap_or(0);
+        assert!(chunks[..chunks.len() - 1].iter().all(|chunk| {
+            (UNWEAVE_CHUNK_BYTES..=UNWEAVE_CHUNK_BYTES + 3 * (longest_word + 1))
+                .contains(&chunk.len())
+        }));
+
+        let artifact = weave_compression_spell(&original_data);
+        let mut restored = Vec::new();
+        unweave_with(&artifact, |chunk| {
+            restored.extend_from_slice(chunk);
+            true
+        });
+        assert_eq!(restored, original_data.as_bytes());
+
+        // Обработчик останавливает распаковку после первого куска
+        let mut calls = 0;
+        let delivered = try_unweave_with(&artifact_ref, &options, |_| {
+            calls += 1;
+            false
+        })
+        .unwrap();
+        assert_eq!(calls, 1);
+        assert!(delivered < original_data.len() as u64);
+
+        // Лимит отвергает архив до первого куска
+        let limited = options.with_max_output_bytes(1000);
+        let mut called = false;
+        assert!(matches!(
+            try_unweave_with(&artifact_ref, &limited, |_| {
+                called = true;
+                true
+            }),
+            Err(WizardError::LimitExceeded { .. })
+        ));
+        assert!(!called);
+    }
+
+    #[test]
+    fn test_symbol_locator_matches_linear_scan() {
+        // Частоты от единицы до тысяч: корзины с одной и многими записями
+        let mut codex = Vec::new();
+        let mut cumulative = 0u64;
+        for symbol in 0..300u32 {
+            let frequency = u64::from(symbol % 7 == 0) * 5000 + u64::from(symbol % 3) + 1;
+            codex.push((symbol * 2, frequency, cumulative));
+            cumulative += frequency;
+        }
+        let locator = SymbolLocator::new(&codex[..], cumulative);
+        for target in (0..cumulative).step_by(3).chain([cumulative - 1]) {
+            let expected = codex
+                .iter()
+                .find(|&&(_, frequency, start)| target >= start && target < start + frequency)
+                .map(|&(symbol, frequency, start)| (symbol, start, start + frequency));
+            assert_eq!(locator.locate(target), expected, "{target}");
+        }
+        assert_eq!(locator.locate(cumulative), None);
+
+        let single = [(65u32, 1u64, 0u64)];
+        assert_eq!(
+            SymbolLocator::new(&single[..], 1).locate(0),
+            Some((65, 0, 1))
+        );
+        assert_eq!(SymbolLocator::new(&[][..], 0).locate(0), None);
+    }
+}
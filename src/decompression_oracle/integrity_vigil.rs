@@ -0,0 +1,246 @@
+//! Дозор целостности: проверка архива без выдачи данных 🕯️
+//!
+//! Хранилищам нужно регулярно убеждаться, что архивы на дисках целы, —
+//! задолго до того, как их попросят распаковать. [`validate_archive`]
+//! разбирает заголовки, реестр блоков с хешами, разделы четности и
+//! таблицы частот и распаковывает блоки по одному, сразу отбрасывая
+//! результат: в памяти никогда нет больше одного блока исходника.
+//! [`ValidationDepth::Structure`] обходится без распаковки — для быстрых
+//! обходов, где важна скорость, а не полная уверенность.
+
+use crate::compression_engine::micro_spell::is_micro_archive;
+use crate::decompression_oracle::block_conductor::decode_block;
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::error::WizardResult;
+use crate::format::artifact_scroll::parse_artifact_ref_body;
+use crate::format::block_ledger::{read_block_ledger, BlockRef};
+use crate::format::container_runes::{ArchiveHeader, EngineKind};
+use crate::format::parity_runes::restore_parity;
+use crate::simple_api::{try_decompress_data, try_decompress_frame};
+use std::borrow::Cow;
+
+/// Глубина проверки архива 🔎
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValidationDepth {
+    /// Заголовки, реестр блоков, хеши, четность и таблицы частот
+    /// арифметических кадров; тела кадров не декодируются
+    Structure,
+    /// Вдобавок каждый кадр распаковывается, а результат отбрасывается
+    #[default]
+    Decode,
+}
+
+/// Итог проверки целого архива 📋
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveSummary {
+    /// Длина архива в байтах
+    pub archive_len: usize,
+    /// Версия формата (`None` — микроархив без заголовка)
+    pub format_version: Option<u8>,
+    /// Движок заголовка (`None` — микроархив)
+    pub engine: Option<EngineKind>,
+    /// Число кадров: блоков или один кадр архива без блоков
+    pub block_count: usize,
+    /// Длина исходника, если она известна из реестра или распаковки
+    pub original_len: Option<u64>,
+    /// Кадры, в которых раздел четности исправил порчу
+    pub parity_repairs: usize,
+    /// Кадры были распакованы
+    pub decoded: bool,
+}
+
+/// Полная проверка архива с лимитами [`DecodeOptions::default`] 🕯️
+///
+/// То же, что [`validate_archive_with`] с [`ValidationDepth::Decode`].
+///
+/// ```
+/// use arithmetic_compression_wizard::decompression_oracle::validate_archive;
+/// use arithmetic_compression_wizard::simple_api::compress_data_in_blocks;
+///
+/// let archive = compress_data_in_blocks(b"scrub me nightly ".repeat(200), 1000);
+/// let summary = validate_archive(&archive).unwrap();
+/// assert_eq!(summary.block_count, 4);
+/// assert_eq!(summary.original_len, Some(3400));
+///
+/// let mut damaged = archive.clone();
+/// let middle = damaged.len() / 2;
+/// damaged[middle] ^= 0xFF;
+/// assert!(validate_archive(&damaged).is_err());
+/// ```
+pub fn validate_archive(archive_bytes: impl AsRef<[u8]>) -> WizardResult<ArchiveSummary> {
+    validate_archive_with(
+        archive_bytes,
+        ValidationDepth::Decode,
+        &DecodeOptions::default(),
+    )
+}
+
+/// Проверка архива выбранной глубины с лимитами `options`
+///
+/// Ошибка — та же, что дала бы распаковка
+/// [`try_decompress_data`](crate::simple_api::try_decompress_data), если
+/// порча видна на выбранной глубине. Кадры движков, кроме
+/// арифметического со своей таблицей, на глубине
+/// [`ValidationDepth::Structure`] проверяются только по заголовку.
+pub fn validate_archive_with(
+    archive_bytes: impl AsRef<[u8]>,
+    depth: ValidationDepth,
+    options: &DecodeOptions,
+) -> WizardResult<ArchiveSummary> {
+    let original_bytes = archive_bytes.as_ref();
+    let archive_bytes = restore_parity(original_bytes);
+    let mut summary = ArchiveSummary {
+        archive_len: original_bytes.len(),
+        format_version: None,
+        engine: None,
+        block_count: 1,
+        original_len: None,
+        parity_repairs: usize::from(matches!(archive_bytes, Cow::Owned(_))),
+        decoded: depth == ValidationDepth::Decode,
+    };
+
+    if is_micro_archive(&archive_bytes) {
+        let restored = try_decompress_data(&archive_bytes, options)?;
+        summary.original_len = Some(restored.len() as u64);
+        summary.decoded = true;
+        return Ok(summary);
+    }
+    let (header, body_offset) = ArchiveHeader::try_parse(&archive_bytes)?;
+    summary.format_version = Some(header.format_version);
+    summary.engine = Some(header.engine);
+
+    if !header.block_framed {
+        if depth == ValidationDepth::Decode {
+            let restored = try_decompress_frame(&archive_bytes, options)?;
+            summary.original_len = Some(restored.len() as u64);
+        } else {
+            check_frame_tables(&header, &archive_bytes[body_offset..], options)
+                .map_err(|error| error.shifted(body_offset))?;
+        }
+        return Ok(summary);
+    }
+
+    let blocks = read_block_ledger(&header, &archive_bytes[body_offset..], options)
+        .map_err(|error| error.shifted(body_offset))?;
+    summary.block_count = blocks.len();
+    summary.original_len = Some(blocks.iter().map(|block| block.original_len).sum());
+    options.check_output(summary.original_len.unwrap_or_default())?;
+    for block in &blocks {
+        summary.parity_repairs += validate_block(block, depth, options)?;
+    }
+    Ok(summary)
+}
+
+/// Проверяет один блок; `1`, если четность исправила его кадр
+fn validate_block(
+    block: &BlockRef<'_>,
+    depth: ValidationDepth,
+    options: &DecodeOptions,
+) -> WizardResult<usize> {
+    let frame = restore_parity(block.frame);
+    let repaired = usize::from(matches!(frame, Cow::Owned(_)));
+    if depth == ValidationDepth::Decode {
+        // Исходник блока сразу отбрасывается
+        decode_block(block, options, &try_decompress_frame)?;
+        return Ok(repaired);
+    }
+    let (header, body_offset) = ArchiveHeader::try_parse(&frame)?;
+    check_frame_tables(&header, &frame[body_offset..], options)
+        .map_err(|error| error.shifted(body_offset))?;
+    Ok(repaired)
+}
+
+/// Разбирает словарь и таблицу частот арифметического кадра
+///
+/// Кадры прочих движков, адаптивных моделей и общих словарей хранят
+/// модель иначе: их проверяет только распаковка.
+fn check_frame_tables(
+    header: &ArchiveHeader,
+    body: &[u8],
+    options: &DecodeOptions,
+) -> WizardResult<()> {
+    let has_own_table = header.engine == EngineKind::Arithmetic
+        && header.dictionary_id.is_none()
+        && !header.model_kind.is_adaptive();
+    if has_own_table {
+        parse_artifact_ref_body(header, body, options)?;
+    }
+    Ok(())
+}
+
+/// Тесты дозора целостности 🎯
+#[cfg(test)]
+mod integrity_vigil_tests {
+    use super::*;
+    use crate::error::WizardError;
+    use crate::simple_api::{
+        compress_compact, compress_data, compress_data_in_synced_blocks, compress_with_parity,
+    };
+
+    #[test]
+    fn test_summary_of_each_layout() {
+        let data = b"the vault keeper counts the scrolls. ".repeat(50);
+
+        let summary = validate_archive(compress_data(&data)).unwrap();
+        assert_eq!(summary.format_version, Some(crate::format::FORMAT_VERSION));
+        assert_eq!(summary.engine, Some(EngineKind::Arithmetic));
+        assert_eq!(summary.block_count, 1);
+        assert_eq!(summary.original_len, Some(data.len() as u64));
+        assert!(summary.decoded);
+
+        let shallow = validate_archive_with(
+            compress_data(&data),
+            ValidationDepth::Structure,
+            &DecodeOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(shallow.original_len, None);
+        assert!(!shallow.decoded);
+
+        let summary = validate_archive(compress_compact(b"owl")).unwrap();
+        assert_eq!(summary.format_version, None);
+        assert_eq!(summary.original_len, Some(3));
+
+        let synced = compress_data_in_synced_blocks(&data, 400);
+        for depth in [ValidationDepth::Structure, ValidationDepth::Decode] {
+            let summary = validate_archive_with(&synced, depth, &DecodeOptions::default()).unwrap();
+            assert_eq!(summary.block_count, data.len().div_ceil(400));
+            assert_eq!(summary.original_len, Some(data.len() as u64));
+        }
+    }
+
+    #[test]
+    fn test_damage_is_found_and_parity_repairs_counted() {
+        let data = b"scrubbing jobs read every archive at night. ".repeat(40);
+
+        // Порча сигнатуры исправима: заголовок под защитой четности
+        let mut protected = compress_with_parity(&data);
+        protected[1] ^= 0x10;
+        let summary = validate_archive(&protected).unwrap();
+        assert_eq!(summary.parity_repairs, 1);
+
+        // Порча хеша синхронизированного блока видна и без распаковки
+        let mut synced = compress_data_in_synced_blocks(&data, 500);
+        let middle = synced.len() / 2;
+        synced[middle] ^= 0xFF;
+        assert!(validate_archive_with(
+            &synced,
+            ValidationDepth::Structure,
+            &DecodeOptions::default()
+        )
+        .is_err());
+
+        assert_eq!(
+            validate_archive(b"garbage").unwrap_err().root_cause(),
+            &WizardError::UnrecognizedHeader
+        );
+        assert!(matches!(
+            validate_archive_with(
+                compress_data(&data),
+                ValidationDepth::Decode,
+                &DecodeOptions::default().with_max_output_bytes(10)
+            ),
+            Err(WizardError::LimitExceeded { .. })
+        ));
+    }
+}
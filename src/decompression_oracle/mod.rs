@@ -1,10 +1,34 @@
-//! Модуль оракула декомпрессии 🔮
-//! Восстановление данных из сжатых артефактов
-
-pub mod decompression_sage;
-
-// Экспорт основной функции декомпрессии
-
-pub use decompression_sage::{
-    unweave_compression_spell, // Восстановление из артефакта
-};
+//! Модуль оракула декомпрессии 🔮
+//! Восстановление данных из сжатых артефактов
+
+pub(crate) mod block_conductor;
+pub(crate) mod block_salvage;
+pub mod decode_wards;
+pub mod decompression_sage;
+pub mod integrity_vigil;
+pub mod resource_ledger;
+
+// Экспорт основной функции декомпрессии
+
+pub use block_salvage::BlockError; // Потерянный участок поврежденного архива
+pub use decode_wards::DecodeOptions; // Лимиты проверяемого декодирования
+pub use decompression_sage::{
+    try_unweave_artifact_ref,                 // Восстановление без копирования
+    try_unweave_artifact_ref_into,            // Восстановление в буфер вызывающего
+    try_unweave_compression_spell,            // Проверяемое восстановление
+    try_unweave_with,                         // Восстановление кусками в обработчик
+    unweave_compression_spell,                // Восстановление из артефакта
+    unweave_compression_spell_with_precision, // Восстановление с явной разрядностью
+    unweave_with,                             // Кусками, без проверок
+    UNWEAVE_CHUNK_BYTES,                      // Размер куска обработчика
+};
+pub use integrity_vigil::{
+    validate_archive,      // Полная проверка архива без выдачи данных
+    validate_archive_with, // Проверка выбранной глубины
+    ArchiveSummary,        // Итог проверки архива
+    ValidationDepth,       // Глубина проверки
+};
+pub use resource_ledger::{
+    ResourceAccountant, // Учетчик выделений и хода распаковки
+    ResourceHook,       // Учетчик в параметрах декодирования
+};
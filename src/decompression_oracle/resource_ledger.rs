@@ -0,0 +1,230 @@
+//! Книга расхода ресурсов распаковки 🧾
+//!
+//! Статические лимиты [`DecodeOptions`](super::DecodeOptions) одинаковы
+//! для всех архивов. Песочнице же нужен свой бюджет: общий на все
+//! распаковки арендатора, зависящий от нагрузки или от того, кто прислал
+//! архив. [`ResourceAccountant`] получает от декодера каждое выделение
+//! памяти по заявленным в архиве размерам — до того, как память выделена,
+//! — и может отказать: распаковка завершается
+//! [`WizardError::BudgetDenied`](crate::error::WizardError::BudgetDenied).
+//! Так бомба распаковки отклоняется политикой вызывающего, а не только
+//! лимитом.
+//!
+//! Учетчик вызывается только после того, как запрос прошел статический
+//! лимит, и может вызываться из нескольких потоков распаковки блоков
+//! одновременно.
+
+use crate::error::LimitKind;
+use std::fmt;
+use std::sync::Arc;
+
+/// Учетчик ресурсов распаковки 🧾
+///
+/// Замыкание `Fn(LimitKind, u64) -> bool` — учетчик без отчета о ходе.
+///
+/// ```
+/// use arithmetic_compression_wizard::decompression_oracle::DecodeOptions;
+/// use arithmetic_compression_wizard::error::{LimitKind, WizardError};
+/// use arithmetic_compression_wizard::simple_api::{compress_data, try_decompress_data};
+///
+/// // Песочница разрешает выделить под результат не больше 1000 байтов
+/// let options = DecodeOptions::default().with_accountant(|kind, amount| {
+///     kind != LimitKind::OutputBytes || amount <= 1000
+/// });
+/// let archive = compress_data(b"zip bomb ".repeat(1000));
+/// assert!(matches!(
+///     try_decompress_data(&archive, &options),
+///     Err(WizardError::BudgetDenied { kind: LimitKind::OutputBytes, requested: 9000 })
+/// ));
+/// ```
+pub trait ResourceAccountant: Send + Sync {
+    /// Декодер собирается выделить `amount` под `kind`; `false` — отказ
+    ///
+    /// `amount` — в единицах лимита: байты для
+    /// [`LimitKind::OutputBytes`] и [`LimitKind::WordBytes`] (одно слово
+    /// словаря), записи для [`LimitKind::DictEntries`] и
+    /// [`LimitKind::TableEntries`]. Каждый вызов — отдельное выделение;
+    /// освобождения не сообщаются, поэтому сумма вызовов — верхняя
+    /// оценка пика.
+    fn on_alloc(&self, kind: LimitKind, amount: u64) -> bool;
+
+    /// Восстановлено `restored_bytes` байтов текущей распаковки
+    ///
+    /// Вызывается после каждого блока архива из блоков и один раз после
+    /// архива из одного кадра. По умолчанию ничего не делает.
+    fn on_progress(&self, restored_bytes: u64) {
+        let _ = restored_bytes;
+    }
+}
+
+impl<F> ResourceAccountant for F
+where
+    F: Fn(LimitKind, u64) -> bool + Send + Sync,
+{
+    fn on_alloc(&self, kind: LimitKind, amount: u64) -> bool {
+        self(kind, amount)
+    }
+}
+
+/// Учетчик в параметрах декодирования 🔗
+///
+/// Копии параметров делят одного учетчика; два крючка равны, если это
+/// один и тот же учетчик.
+#[derive(Clone)]
+pub struct ResourceHook(Arc<dyn ResourceAccountant>);
+
+impl ResourceHook {
+    /// Крючок с учетчиком `accountant`
+    pub fn new(accountant: impl ResourceAccountant + 'static) -> Self {
+        Self(Arc::new(accountant))
+    }
+
+    /// Крючок с учетчиком, которым вызывающий владеет сам
+    pub fn shared(accountant: Arc<dyn ResourceAccountant>) -> Self {
+        Self(accountant)
+    }
+
+    /// Учетчик крючка
+    pub fn accountant(&self) -> &dyn ResourceAccountant {
+        self.0.as_ref()
+    }
+}
+
+impl fmt::Debug for ResourceHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ResourceHook(..)")
+    }
+}
+
+impl PartialEq for ResourceHook {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for ResourceHook {}
+
+/// Тесты книги расхода 🎯
+#[cfg(test)]
+mod resource_ledger_tests {
+    use super::*;
+    use crate::decompression_oracle::decode_wards::DecodeOptions;
+    use crate::error::WizardError;
+    use crate::format::block_stream::BlockStreamReader;
+    use crate::simple_api::{compress_data, compress_data_in_blocks, try_decompress_data};
+    use std::io::Read;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Mutex;
+
+    /// Бюджет арендатора: общий на все распаковки
+    #[derive(Default)]
+    struct TenantBudget {
+        allocated: AtomicU64,
+        limit: u64,
+        events: Mutex<Vec<(LimitKind, u64)>>,
+        progress: Mutex<Vec<u64>>,
+    }
+
+    impl ResourceAccountant for TenantBudget {
+        fn on_alloc(&self, kind: LimitKind, amount: u64) -> bool {
+            self.events.lock().unwrap().push((kind, amount));
+            let before = self.allocated.fetch_add(amount, Ordering::Relaxed);
+            before + amount <= self.limit
+        }
+
+        fn on_progress(&self, restored_bytes: u64) {
+            self.progress.lock().unwrap().push(restored_bytes);
+        }
+    }
+
+    #[test]
+    fn test_allocations_and_progress_are_reported() {
+        let data = b"the owl counts every feather it spends. ".repeat(100);
+        let budget = Arc::new(TenantBudget {
+            limit: u64::MAX,
+            ..TenantBudget::default()
+        });
+        let options = DecodeOptions::default()
+            .with_decode_threads(1)
+            .with_resource_hook(ResourceHook::shared(budget.clone()));
+
+        let archive = compress_data_in_blocks(&data, 1000);
+        assert_eq!(try_decompress_data(&archive, &options).unwrap(), data);
+        let events = budget.events.lock().unwrap().clone();
+        assert_eq!(events[0], (LimitKind::OutputBytes, data.len() as u64));
+        assert!(events
+            .iter()
+            .any(|&(kind, _)| kind == LimitKind::TableEntries));
+        let progress = budget.progress.lock().unwrap().clone();
+        assert_eq!(progress.len(), data.len().div_ceil(1000));
+        assert!(progress.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(progress.last(), Some(&(data.len() as u64)));
+
+        // Архив из одного кадра: один отчет о ходе
+        budget.progress.lock().unwrap().clear();
+        assert_eq!(
+            try_decompress_data(compress_data(&data), &options).unwrap(),
+            data
+        );
+        assert_eq!(*budget.progress.lock().unwrap(), [data.len() as u64]);
+
+        // Потоковый читатель сообщает о каждом блоке
+        budget.progress.lock().unwrap().clear();
+        let mut reader = BlockStreamReader::new(&archive[..], options).unwrap();
+        let mut restored = Vec::new();
+        reader.read_to_end(&mut restored).unwrap();
+        assert_eq!(restored, data);
+        assert_eq!(
+            budget.progress.lock().unwrap().last(),
+            Some(&(data.len() as u64))
+        );
+    }
+
+    #[test]
+    fn test_shared_budget_rejects_across_decompressions() {
+        let data = b"one tenant, one budget, many archives. ".repeat(50);
+        let archive = compress_data(&data);
+        let budget = Arc::new(TenantBudget {
+            limit: 3 * data.len() as u64,
+            ..TenantBudget::default()
+        });
+        let options =
+            DecodeOptions::default().with_resource_hook(ResourceHook::shared(budget.clone()));
+
+        // Бюджет кончается на одной из распаковок, а не на первой
+        let outcomes: Vec<bool> = (0..4)
+            .map(|_| try_decompress_data(&archive, &options).is_ok())
+            .collect();
+        assert!(outcomes[0]);
+        assert!(!outcomes[3]);
+        assert!(matches!(
+            try_decompress_data(&archive, &options),
+            Err(WizardError::BudgetDenied { .. })
+        ));
+
+        // Статический лимит проверяется раньше учетчика
+        let generous = Arc::new(TenantBudget {
+            limit: u64::MAX,
+            ..TenantBudget::default()
+        });
+        let strict = DecodeOptions::default()
+            .with_max_output_bytes(10)
+            .with_resource_hook(ResourceHook::shared(generous.clone()));
+        assert!(matches!(
+            try_decompress_data(&archive, &strict),
+            Err(WizardError::LimitExceeded { .. })
+        ));
+        assert!(!generous
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|&(kind, _)| kind == LimitKind::OutputBytes));
+
+        assert_eq!(options.clone(), options);
+        assert_ne!(
+            options,
+            DecodeOptions::default().with_accountant(|_: LimitKind, _: u64| true)
+        );
+    }
+}
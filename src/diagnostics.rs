@@ -0,0 +1,284 @@
+//! Диагностика расхождений после распаковки 🩺
+//!
+//! Когда восстановленные байты не совпадают с исходными, «не совпало»
+//! мало что говорит сопровождающему. [`diff_roundtrip`] находит первые
+//! различающиеся смещения и вырезает вокруг них окна из обоих буферов;
+//! [`RoundtripDiff`] печатается как шестнадцатеричный дамп, который можно
+//! вставить в отчет об ошибке целиком. Окна маленькие, так что отчет
+//! показывает только байты рядом с повреждением, а не весь файл.
+
+use std::fmt;
+
+/// Сколько различий перечисляет [`diff_roundtrip`]
+pub const DIFF_MAX_MISMATCHES: usize = 16;
+
+/// Байтов контекста с каждой стороны от различия
+pub const DIFF_CONTEXT_BYTES: usize = 8;
+
+/// Байтов в строке дампа
+const DUMP_ROW_BYTES: usize = 16;
+
+/// Различающийся байт 🔍
+///
+/// За концом более короткого буфера байта нет: `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ByteMismatch {
+    /// Смещение от начала данных
+    pub offset: u64,
+    /// Байт исходных данных
+    pub expected: Option<u8>,
+    /// Байт восстановленных данных
+    pub actual: Option<u8>,
+}
+
+/// Окно байтов вокруг различий, одно и то же в обоих буферах
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ContextWindow {
+    /// Смещение первого байта окна
+    pub start: u64,
+    /// Байты исходных данных (короче окна у конца буфера)
+    pub original: Vec<u8>,
+    /// Байты восстановленных данных (короче окна у конца буфера)
+    pub restored: Vec<u8>,
+}
+
+/// Расхождение исходных и восстановленных данных 🩺
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RoundtripDiff {
+    /// Длина исходных данных
+    pub original_len: u64,
+    /// Длина восстановленных данных
+    pub restored_len: u64,
+    /// Число всех различающихся смещений, включая хвост длинного буфера
+    pub mismatch_count: u64,
+    /// Первые различия по возрастанию смещения
+    pub mismatches: Vec<ByteMismatch>,
+    /// Окна контекста вокруг [`RoundtripDiff::mismatches`]; пересекающиеся
+    /// окна слиты
+    pub windows: Vec<ContextWindow>,
+}
+
+impl RoundtripDiff {
+    /// Буферы совпадают
+    pub fn is_identical(&self) -> bool {
+        self.mismatch_count == 0
+    }
+
+    /// Смещение первого различия
+    pub fn first_mismatch(&self) -> Option<u64> {
+        self.mismatches.first().map(|mismatch| mismatch.offset)
+    }
+}
+
+/// Первые [`DIFF_MAX_MISMATCHES`] различий с контекстом [`DIFF_CONTEXT_BYTES`] 🩺
+///
+/// ```
+/// use arithmetic_compression_wizard::diagnostics::diff_roundtrip;
+///
+/// let original = b"the owl flies at dawn".to_vec();
+/// let mut restored = original.clone();
+/// restored[8] = b'F';
+///
+/// let diff = diff_roundtrip(&original, &restored);
+/// assert_eq!(diff.first_mismatch(), Some(8));
+/// assert_eq!(diff.mismatch_count, 1);
+/// // Готовый текст для отчета об ошибке
+/// assert!(diff.to_string().contains("00000000"));
+/// assert!(diff_roundtrip(&original, &original).is_identical());
+/// ```
+pub fn diff_roundtrip(original: impl AsRef<[u8]>, restored: impl AsRef<[u8]>) -> RoundtripDiff {
+    diff_roundtrip_with(original, restored, DIFF_MAX_MISMATCHES, DIFF_CONTEXT_BYTES)
+}
+
+/// Сравнение с выбранным числом различий и шириной контекста
+///
+/// Все различия считаются в [`RoundtripDiff::mismatch_count`], но
+/// перечисляются только первые `max_mismatches`.
+pub fn diff_roundtrip_with(
+    original: impl AsRef<[u8]>,
+    restored: impl AsRef<[u8]>,
+    max_mismatches: usize,
+    context_bytes: usize,
+) -> RoundtripDiff {
+    let (original, restored) = (original.as_ref(), restored.as_ref());
+    let common_len = original.len().min(restored.len());
+    let longer_len = original.len().max(restored.len());
+
+    let differing_offsets = (0..common_len)
+        .filter(|&offset| original[offset] != restored[offset])
+        .chain(common_len..longer_len);
+    let mut mismatch_count = 0u64;
+    let mut mismatches = Vec::new();
+    for offset in differing_offsets {
+        mismatch_count += 1;
+        if mismatches.len() < max_mismatches {
+            mismatches.push(ByteMismatch {
+                offset: offset as u64,
+                expected: original.get(offset).copied(),
+                actual: restored.get(offset).copied(),
+            });
+        }
+    }
+
+    RoundtripDiff {
+        original_len: original.len() as u64,
+        restored_len: restored.len() as u64,
+        mismatch_count,
+        windows: context_windows(original, restored, &mismatches, context_bytes),
+        mismatches,
+    }
+}
+
+/// Окна вокруг различий; соседние окна сливаются
+fn context_windows(
+    original: &[u8],
+    restored: &[u8],
+    mismatches: &[ByteMismatch],
+    context_bytes: usize,
+) -> Vec<ContextWindow> {
+    let longer_len = original.len().max(restored.len());
+    let mut spans: Vec<(usize, usize)> = Vec::new();
+    for mismatch in mismatches {
+        let offset = mismatch.offset as usize;
+        let start = offset.saturating_sub(context_bytes);
+        let end = offset.saturating_add(context_bytes + 1).min(longer_len);
+        match spans.last_mut() {
+            Some((_, last_end)) if start <= *last_end => *last_end = (*last_end).max(end),
+            _ => spans.push((start, end)),
+        }
+    }
+
+    let clip = |bytes: &[u8], start: usize, end: usize| {
+        bytes[start.min(bytes.len())..end.min(bytes.len())].to_vec()
+    };
+    spans
+        .into_iter()
+        .map(|(start, end)| ContextWindow {
+            start: start as u64,
+            original: clip(original, start, end),
+            restored: clip(restored, start, end),
+        })
+        .collect()
+}
+
+impl fmt::Display for RoundtripDiff {
+    /// Текст для отчета об ошибке: длины, первые различия и дамп окон
+    ///
+    /// В дампе `orig` и `rest` — строки исходных и восстановленных байтов,
+    /// `^^` отмечает различия, `--` — байт за концом буфера.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_identical() {
+            return writeln!(f, "данные совпадают ({} байт)", self.original_len);
+        }
+        writeln!(
+            f,
+            "исходных {} байт, восстановлено {} байт, различий {}",
+            self.original_len, self.restored_len, self.mismatch_count
+        )?;
+        let byte = |value: Option<u8>| value.map_or("--".to_string(), |b| format!("{b:02x}"));
+        for mismatch in &self.mismatches {
+            writeln!(
+                f,
+                "  0x{:08x}: {} -> {}",
+                mismatch.offset,
+                byte(mismatch.expected),
+                byte(mismatch.actual)
+            )?;
+        }
+        if self.mismatch_count > self.mismatches.len() as u64 {
+            writeln!(
+                f,
+                "  ... и еще {}",
+                self.mismatch_count - self.mismatches.len() as u64
+            )?;
+        }
+
+        for window in &self.windows {
+            writeln!(f)?;
+            let window_len = window.original.len().max(window.restored.len());
+            for row_start in (0..window_len).step_by(DUMP_ROW_BYTES) {
+                let row = row_start..(row_start + DUMP_ROW_BYTES).min(window_len);
+                let offset = window.start + row_start as u64;
+                let (mut original, mut restored, mut marks) =
+                    (String::new(), String::new(), String::new());
+                for index in row {
+                    let (expected, actual) =
+                        (window.original.get(index), window.restored.get(index));
+                    original.push_str(&format!(" {}", byte(expected.copied())));
+                    restored.push_str(&format!(" {}", byte(actual.copied())));
+                    marks.push_str(if expected == actual { "   " } else { " ^^" });
+                }
+                writeln!(f, "{offset:08x} orig{original}")?;
+                writeln!(f, "{offset:08x} rest{restored}")?;
+                if !marks.trim().is_empty() {
+                    writeln!(f, "{:13}{}", "", marks.trim_end())?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Тесты диагностики 🎯
+#[cfg(test)]
+mod diagnostics_tests {
+    use super::*;
+    use crate::simple_api::{compress_data, decompress_data};
+
+    #[test]
+    fn test_mismatches_are_listed_with_context() {
+        let original: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+        let mut restored = original.clone();
+        for offset in [100, 103, 700] {
+            restored[offset] ^= 0xff;
+        }
+
+        let diff = diff_roundtrip(&original, &restored);
+        assert_eq!(diff.mismatch_count, 3);
+        assert_eq!(
+            diff.mismatches[0],
+            ByteMismatch {
+                offset: 100,
+                expected: Some(100),
+                actual: Some(!100),
+            }
+        );
+        // Окна у 100 и 103 слиты, окно у 700 отдельно
+        assert_eq!(diff.windows.len(), 2);
+        assert_eq!(diff.windows[0].start, 92);
+        assert_eq!(diff.windows[0].original, &original[92..112]);
+        assert_eq!(diff.windows[0].restored, &restored[92..112]);
+        assert_eq!(diff.windows[1].start, 692);
+
+        let report = diff.to_string();
+        assert!(report.contains("различий 3"));
+        assert!(report.contains("0x00000064: 64 -> 9b"));
+        assert!(report.contains("0000005c orig 5c 5d"));
+
+        // Сжатие и распаковка без повреждений
+        assert!(
+            diff_roundtrip(&original, decompress_data(compress_data(&original))).is_identical()
+        );
+    }
+
+    #[test]
+    fn test_length_mismatch_and_limits() {
+        let original = b"abracadabra";
+        let diff = diff_roundtrip(original, b"abra");
+        assert_eq!(diff.mismatch_count, 7);
+        assert_eq!(diff.first_mismatch(), Some(4));
+        assert_eq!(diff.mismatches[0].actual, None);
+        assert_eq!(diff.windows[0].restored, b"abra");
+        assert!(diff.to_string().contains("0x00000004: 63 -> --"));
+
+        let noisy: Vec<u8> = original.iter().map(|byte| !byte).collect();
+        let diff = diff_roundtrip_with(original, &noisy, 2, 0);
+        assert_eq!(diff.mismatch_count, 11);
+        assert_eq!(diff.mismatches.len(), 2);
+        assert_eq!(diff.windows[0].original, b"ab");
+        assert!(diff.to_string().contains("и еще 9"));
+
+        assert!(diff_roundtrip(b"", b"").is_identical());
+        assert_eq!(diff_roundtrip(b"", b"x").first_mismatch(), Some(0));
+    }
+}
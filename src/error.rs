@@ -0,0 +1,491 @@
+//! Проклятия (ошибки) библиотеки 💀
+//!
+//! Единый тип ошибок для проверяемых операций. Функции с префиксом `try_`
+//! возвращают [`WizardResult`] вместо паники.
+
+use std::fmt;
+
+/// Результат проверяемой операции
+pub type WizardResult<T> = Result<T, WizardError>;
+
+/// Какой лимит превышен
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitKind {
+    /// Размер восстановленных данных
+    OutputBytes,
+    /// Число слов в словаре
+    DictEntries,
+    /// Число записей в таблице частот
+    TableEntries,
+    /// Общая масса частот таблицы (точность кодера)
+    FrequencyMass,
+    /// Длина одного слова словаря в байтах
+    WordBytes,
+    /// Суммарный размер слов словаря в байтах
+    DictionaryBytes,
+}
+
+/// Фаза декодирования, на которой найдена порча
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodePhase {
+    /// Заголовок архива или кадра
+    Header,
+    /// Таблицы: словарь, таблица частот, реестр блоков
+    Table,
+    /// Битовый поток и тело движка
+    Payload,
+}
+
+impl fmt::Display for DecodePhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DecodePhase::Header => "заголовок",
+            DecodePhase::Table => "таблицы",
+            DecodePhase::Payload => "битовый поток",
+        })
+    }
+}
+
+/// Место ошибки декодирования в архиве 📍
+///
+/// По смещению ошибку можно сопоставить с отчетом хранилища о
+/// поврежденных секторах. Битовый поток декодер читает с опережением
+/// на разрядность регистров, поэтому смещение в фазе
+/// [`DecodePhase::Payload`] может быть на несколько байтов дальше порчи.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorLocation {
+    /// Смещение в байтах от начала архива
+    pub offset: usize,
+    /// Номер кадра в архиве из блоков (`None` — архив из одного кадра)
+    pub frame: Option<usize>,
+    /// Фаза декодирования
+    pub phase: DecodePhase,
+}
+
+/// Ошибка библиотеки 💥
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WizardError {
+    /// Архив обрывается раньше, чем ожидалось
+    Truncated {
+        /// Сколько байтов требовалось
+        needed: usize,
+        /// Сколько байтов осталось
+        available: usize,
+    },
+    /// Заголовок архива не распознан
+    UnrecognizedHeader,
+    /// Версия формата новее поддерживаемой
+    UnsupportedVersion(u8),
+    /// Архив закодирован движком, который не умеет этот декодер
+    UnsupportedEngine(u8),
+    /// Движок кадра не умеет модель с этим кодом
+    UnsupportedModel(u8),
+    /// Архив закодирован внешним словарем с указанным идентификатором
+    ExternalDictionaryRequired(u64),
+    /// Таблица частот не согласована
+    CorruptFrequencyTable(&'static str),
+    /// Таблица фраз не согласована
+    CorruptPhraseTable(&'static str),
+    /// Реестр блоков не согласован
+    CorruptBlockTable(&'static str),
+    /// Раздел метаданных не согласован
+    CorruptMetadata(&'static str),
+    /// Команды патча не согласованы
+    CorruptDelta(&'static str),
+    /// Патч построен для другой базы (указан id базы патча)
+    DeltaBaseMismatch(u64),
+    /// Битовый поток не согласуется с моделью
+    CorruptPayload,
+    /// Распределение, переданное низкоуровневому кодеру, некорректно
+    InvalidDistribution(&'static str),
+    /// Контрольная распаковка свежего архива не совпала с входом
+    VerificationFailed,
+    /// Вход или поле кадра не умещается в поле длины формата
+    InputTooLarge {
+        /// Длина, которую нужно записать
+        len: u64,
+        /// Наибольшая длина, которую вмещает поле
+        limit: u64,
+    },
+    /// Оглавление архива записей не согласовано
+    CorruptEntryIndex(&'static str),
+    /// Ошибка декодирования с местом в архиве (своего кода нет, см. [`code`](Self::code))
+    Located {
+        /// Где декодер остановился
+        location: ErrorLocation,
+        /// Исходная ошибка
+        cause: Box<WizardError>,
+    },
+    /// Превышен лимит декодирования
+    LimitExceeded {
+        /// Вид лимита
+        kind: LimitKind,
+        /// Запрошенное значение
+        requested: u64,
+        /// Разрешенный максимум
+        allowed: u64,
+    },
+    /// Учетчик ресурсов вызывающего отказал в выделении
+    ///
+    /// См. [`ResourceAccountant`](crate::decompression_oracle::ResourceAccountant).
+    BudgetDenied {
+        /// Вид выделения
+        kind: LimitKind,
+        /// Запрошенное значение в единицах лимита
+        requested: u64,
+    },
+    /// Срок сжатия [`EngineConfig::deadline`](crate::compression_engine::EngineConfig::deadline)
+    /// истек посреди кадра
+    ///
+    /// Встроенные движки и
+    /// [`try_compress_with_config`](crate::simple_api::try_compress_with_config)
+    /// не возвращают эту ошибку: они завершают кадр хранением.
+    DeadlineExceeded,
+    /// Текст отладочного снимка символов не разобран
+    ///
+    /// См. [`parse_dump`](crate::debug::parse_dump).
+    CorruptSymbolDump(&'static str),
+    /// JSON модели не разобран
+    ///
+    /// См. [`CompressorHandle::from_model_json`](crate::compression_engine::CompressorHandle::from_model_json).
+    CorruptModelJson(&'static str),
+}
+
+impl WizardError {
+    /// Стабильный числовой код ошибки 🔢
+    ///
+    /// Коды не меняются между версиями и не переиспользуются: по ним
+    /// сервисы и привязки к другим языкам различают ошибки, не разбирая
+    /// текст сообщения. Новые варианты получают следующие свободные коды.
+    ///
+    /// | Код | Вариант |
+    /// |-----|---------|
+    /// | 1 | `Truncated` |
+    /// | 2 | `UnrecognizedHeader` |
+    /// | 3 | `UnsupportedVersion` |
+    /// | 4 | `UnsupportedEngine` |
+    /// | 5 | `UnsupportedModel` |
+    /// | 6 | `ExternalDictionaryRequired` |
+    /// | 7 | `CorruptFrequencyTable` |
+    /// | 8 | `CorruptPhraseTable` |
+    /// | 9 | `CorruptBlockTable` |
+    /// | 10 | `CorruptMetadata` |
+    /// | 11 | `CorruptDelta` |
+    /// | 12 | `DeltaBaseMismatch` |
+    /// | 13 | `CorruptPayload` |
+    /// | 14 | `InvalidDistribution` |
+    /// | 15 | `LimitExceeded` |
+    /// | 16 | `VerificationFailed` |
+    /// | 17 | `InputTooLarge` |
+    /// | 18 | `CorruptEntryIndex` |
+    /// | 19 | `BudgetDenied` |
+    /// | 20 | `DeadlineExceeded` |
+    /// | 21 | `CorruptSymbolDump` |
+    /// | 22 | `CorruptModelJson` |
+    ///
+    /// `Located` возвращает код исходной ошибки: место не меняет ее вид.
+    pub fn code(&self) -> u16 {
+        match self {
+            WizardError::Truncated { .. } => 1,
+            WizardError::UnrecognizedHeader => 2,
+            WizardError::UnsupportedVersion(_) => 3,
+            WizardError::UnsupportedEngine(_) => 4,
+            WizardError::UnsupportedModel(_) => 5,
+            WizardError::ExternalDictionaryRequired(_) => 6,
+            WizardError::CorruptFrequencyTable(_) => 7,
+            WizardError::CorruptPhraseTable(_) => 8,
+            WizardError::CorruptBlockTable(_) => 9,
+            WizardError::CorruptMetadata(_) => 10,
+            WizardError::CorruptDelta(_) => 11,
+            WizardError::DeltaBaseMismatch(_) => 12,
+            WizardError::CorruptPayload => 13,
+            WizardError::InvalidDistribution(_) => 14,
+            WizardError::LimitExceeded { .. } => 15,
+            WizardError::VerificationFailed => 16,
+            WizardError::InputTooLarge { .. } => 17,
+            WizardError::CorruptEntryIndex(_) => 18,
+            WizardError::BudgetDenied { .. } => 19,
+            WizardError::DeadlineExceeded => 20,
+            WizardError::CorruptSymbolDump(_) => 21,
+            WizardError::CorruptModelJson(_) => 22,
+            WizardError::Located { cause, .. } => cause.code(),
+        }
+    }
+
+    /// Место ошибки в архиве, если декодер его знает 📍
+    pub fn location(&self) -> Option<ErrorLocation> {
+        match self {
+            WizardError::Located { location, .. } => Some(*location),
+            _ => None,
+        }
+    }
+
+    /// Исходная ошибка без места в архиве
+    pub fn root_cause(&self) -> &WizardError {
+        match self {
+            WizardError::Located { cause, .. } => cause.root_cause(),
+            error => error,
+        }
+    }
+
+    /// Привязывает ошибку к смещению `offset` в фазе `phase`
+    ///
+    /// Уже привязанная ошибка не меняется: внутренний слой знает место точнее.
+    /// Превышение лимита и отказ учетчика не привязываются: это не порча
+    /// архива.
+    pub(crate) fn located(self, phase: DecodePhase, offset: usize) -> Self {
+        match self {
+            WizardError::Located { .. }
+            | WizardError::LimitExceeded { .. }
+            | WizardError::BudgetDenied { .. } => self,
+            cause => WizardError::Located {
+                location: ErrorLocation {
+                    offset,
+                    frame: None,
+                    phase,
+                },
+                cause: Box::new(cause),
+            },
+        }
+    }
+
+    /// Переводит смещение из вложенного буфера, начинающегося с `base`
+    pub(crate) fn shifted(self, base: usize) -> Self {
+        match self {
+            WizardError::Located {
+                mut location,
+                cause,
+            } => {
+                location.offset = location.offset.saturating_add(base);
+                WizardError::Located { location, cause }
+            }
+            error => error,
+        }
+    }
+
+    /// Помечает ошибку номером кадра в архиве из блоков
+    ///
+    /// Смещение непривязанной ошибки отсчитывается от начала кадра.
+    /// Номер блока внешнего архива заменяет номер во вложенном.
+    pub(crate) fn in_frame(self, index: usize) -> Self {
+        match self.located(DecodePhase::Payload, 0) {
+            WizardError::Located {
+                mut location,
+                cause,
+            } => {
+                location.frame = Some(index);
+                WizardError::Located { location, cause }
+            }
+            error => error,
+        }
+    }
+}
+
+impl fmt::Display for WizardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WizardError::Truncated { needed, available } => write!(
+                f,
+                "архив обрывается: нужно {} байт, доступно {}",
+                needed, available
+            ),
+            WizardError::UnrecognizedHeader => write!(f, "заголовок архива не распознан"),
+            WizardError::UnsupportedVersion(version) => {
+                write!(f, "версия формата {} не поддерживается", version)
+            }
+            WizardError::UnsupportedEngine(engine_id) => {
+                write!(f, "движок {} не зарегистрирован", engine_id)
+            }
+            WizardError::UnsupportedModel(model_id) => {
+                write!(f, "модель {} не поддерживается движком кадра", model_id)
+            }
+            WizardError::ExternalDictionaryRequired(dictionary_id) => {
+                write!(f, "архив требует внешний словарь {:#018x}", dictionary_id)
+            }
+            WizardError::CorruptFrequencyTable(reason) => {
+                write!(f, "таблица частот повреждена: {}", reason)
+            }
+            WizardError::CorruptPhraseTable(reason) => {
+                write!(f, "таблица фраз повреждена: {}", reason)
+            }
+            WizardError::CorruptBlockTable(reason) => {
+                write!(f, "реестр блоков поврежден: {}", reason)
+            }
+            WizardError::CorruptMetadata(reason) => {
+                write!(f, "метаданные повреждены: {}", reason)
+            }
+            WizardError::CorruptDelta(reason) => {
+                write!(f, "патч поврежден: {}", reason)
+            }
+            WizardError::DeltaBaseMismatch(base_id) => {
+                write!(f, "патч построен для другой базы {:#018x}", base_id)
+            }
+            WizardError::CorruptPayload => write!(f, "битовый поток поврежден"),
+            WizardError::InvalidDistribution(reason) => {
+                write!(f, "некорректное распределение: {}", reason)
+            }
+            WizardError::VerificationFailed => {
+                write!(f, "контрольная распаковка не совпала с исходными данными")
+            }
+            WizardError::InputTooLarge { len, limit } => write!(
+                f,
+                "длина {} не умещается в кадр (не больше {}): сжимайте блоками",
+                len, limit
+            ),
+            WizardError::CorruptEntryIndex(reason) => {
+                write!(f, "оглавление архива записей повреждено: {}", reason)
+            }
+            WizardError::Located { location, cause } => {
+                write!(
+                    f,
+                    "{} ({}, смещение {}",
+                    cause, location.phase, location.offset
+                )?;
+                if let Some(frame) = location.frame {
+                    write!(f, ", кадр {}", frame)?;
+                }
+                write!(f, ")")
+            }
+            WizardError::LimitExceeded {
+                kind,
+                requested,
+                allowed,
+            } => write!(
+                f,
+                "превышен лимит {:?}: запрошено {}, разрешено {}",
+                kind, requested, allowed
+            ),
+            WizardError::BudgetDenied { kind, requested } => write!(
+                f,
+                "учетчик ресурсов отказал в выделении {:?}: запрошено {}",
+                kind, requested
+            ),
+            WizardError::DeadlineExceeded => write!(f, "срок сжатия истек"),
+            WizardError::CorruptSymbolDump(reason) => {
+                write!(f, "снимок символов поврежден: {}", reason)
+            }
+            WizardError::CorruptModelJson(reason) => {
+                write!(f, "JSON модели поврежден: {}", reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for WizardError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WizardError::Located { cause, .. } => Some(cause.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<WizardError> for std::io::Error {
+    /// Ошибка формата в потоковом вводе-выводе (`InvalidData`)
+    fn from(error: WizardError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, error)
+    }
+}
+
+/// Тесты ошибок 🎯
+#[cfg(test)]
+mod error_tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes_are_stable() {
+        let errors = [
+            WizardError::Truncated {
+                needed: 4,
+                available: 1,
+            },
+            WizardError::UnrecognizedHeader,
+            WizardError::UnsupportedVersion(9),
+            WizardError::UnsupportedEngine(9),
+            WizardError::UnsupportedModel(9),
+            WizardError::ExternalDictionaryRequired(9),
+            WizardError::CorruptFrequencyTable(""),
+            WizardError::CorruptPhraseTable(""),
+            WizardError::CorruptBlockTable(""),
+            WizardError::CorruptMetadata(""),
+            WizardError::CorruptDelta(""),
+            WizardError::DeltaBaseMismatch(9),
+            WizardError::CorruptPayload,
+            WizardError::InvalidDistribution(""),
+            WizardError::LimitExceeded {
+                kind: LimitKind::OutputBytes,
+                requested: 2,
+                allowed: 1,
+            },
+            WizardError::VerificationFailed,
+            WizardError::InputTooLarge { len: 2, limit: 1 },
+            WizardError::CorruptEntryIndex(""),
+            WizardError::BudgetDenied {
+                kind: LimitKind::OutputBytes,
+                requested: 1,
+            },
+            WizardError::DeadlineExceeded,
+            WizardError::CorruptSymbolDump(""),
+            WizardError::CorruptModelJson(""),
+        ];
+        // Коды записаны в документации и не меняются
+        let codes: Vec<u16> = errors.iter().map(WizardError::code).collect();
+        assert_eq!(codes, (1..=22).collect::<Vec<u16>>());
+    }
+
+    #[test]
+    fn test_decode_errors_report_location() {
+        use crate::decompression_oracle::decode_wards::DecodeOptions;
+        use crate::decompression_oracle::decompression_sage::decode_symbols_while;
+        use crate::format::artifact_scroll::parse_artifact_ref;
+        use crate::simple_api::{compress_data, try_decompress_data};
+
+        let text = "the owl carries scrolls between towers. ".repeat(50);
+        let archive = compress_data(&text);
+        let options = DecodeOptions::default();
+
+        // Неизвестный движок: разбор заголовка остановился на его байте
+        let mut header_damage = archive.clone();
+        header_damage[4] = 0x7F;
+        let error = try_decompress_data(&header_damage, &options).unwrap_err();
+        assert_eq!(error.root_cause(), &WizardError::UnrecognizedHeader);
+        assert_eq!(error.code(), WizardError::UnrecognizedHeader.code());
+        assert_eq!(
+            error.location(),
+            Some(ErrorLocation {
+                offset: 4,
+                frame: None,
+                phase: DecodePhase::Header,
+            })
+        );
+        assert!(error.to_string().contains("смещение 4"));
+
+        // Ошибка в потоке привязана к байту, до которого дочитал декодер
+        let artifact_ref = parse_artifact_ref(&archive, &options).unwrap();
+        let stream = artifact_ref.compressed_bit_stream;
+        let mut decoded = 0;
+        let error = decode_symbols_while(
+            stream,
+            &artifact_ref.frequency_codex,
+            artifact_ref.total_frequency_essence,
+            artifact_ref.total_frequency_essence,
+            artifact_ref.header.coder_precision(),
+            |_| {
+                decoded += 1;
+                match decoded {
+                    1..100 => Ok(true),
+                    _ => Err(WizardError::CorruptPayload),
+                }
+            },
+        )
+        .unwrap_err();
+        let location = error.location().unwrap();
+        assert_eq!(location.phase, DecodePhase::Payload);
+        assert!((1..stream.len()).contains(&location.offset));
+
+        // Лимит — не порча архива: места у него нет
+        let error = try_decompress_data(&archive, &options.with_max_output_bytes(10)).unwrap_err();
+        assert!(matches!(error, WizardError::LimitExceeded { .. }));
+        assert_eq!(error.location(), None);
+    }
+}
@@ -0,0 +1,127 @@
+//! Атлас алфавита 🗺️
+//!
+//! Сжатие алфавита для разреженных входов: ДНК, base64, hex-дампы
+//! используют лишь несколько десятков различных байтов.
+//!
+//! Атлас — битовая карта использованных байтов (32 байта в заголовке).
+//! Использованные байты получают плотные коды `0..k` по возрастанию,
+//! ссылки на словарь идут следом: символ `256 + i` получает код `k + i`.
+//! С атласом таблица частот хранит только частоту для каждого плотного кода,
+//! а id символов и накопительные позиции выводятся при разборе.
+
+/// Атлас применяется, только если использовано меньше байтов
+pub const ALPHABET_COMPACTION_LIMIT: usize = 64;
+
+/// Битовая карта использованных байтов 🗺️
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AlphabetMap {
+    used_bytes: [u8; 32],
+}
+
+impl AlphabetMap {
+    /// Размер атласа в заголовке
+    pub const ENCODED_LEN: usize = 32;
+
+    /// Атлас байтов, встречающихся в таблице частот
+    pub fn from_frequency_codex(frequency_codex: &[(u32, u64, u64)]) -> Self {
+        let mut used_bytes = [0u8; 32];
+        for &(symbol_id, _, _) in frequency_codex {
+            if symbol_id < 256 {
+                used_bytes[symbol_id as usize / 8] |= 1 << (symbol_id % 8);
+            }
+        }
+        Self { used_bytes }
+    }
+
+    /// Атлас из битовой карты заголовка
+    pub fn from_bytes(used_bytes: [u8; 32]) -> Self {
+        Self { used_bytes }
+    }
+
+    /// Битовая карта для заголовка
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.used_bytes
+    }
+
+    /// Использован ли байт
+    pub fn contains(&self, byte: u8) -> bool {
+        self.used_bytes[byte as usize / 8] & (1 << (byte % 8)) != 0
+    }
+
+    /// Сколько различных байтов использовано
+    pub fn len(&self) -> usize {
+        self.used_bytes
+            .iter()
+            .map(|bits| bits.count_ones() as usize)
+            .sum()
+    }
+
+    /// Нет ни одного использованного байта
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Плотный код символа (`None` — байт вне атласа)
+    pub fn dense_code(&self, symbol_id: u32) -> Option<u32> {
+        if symbol_id >= 256 {
+            return Some(self.len() as u32 + (symbol_id - 256));
+        }
+        let byte = symbol_id as u8;
+        if !self.contains(byte) {
+            return None;
+        }
+        let rank = (0..byte).filter(|&lower| self.contains(lower)).count();
+        Some(rank as u32)
+    }
+
+    /// Символ по плотному коду
+    pub fn symbol_for(&self, dense_code: u32) -> u32 {
+        let used = self.len() as u32;
+        if dense_code >= used {
+            return 256u32.saturating_add(dense_code - used);
+        }
+        (0..=255u8)
+            .filter(|&byte| self.contains(byte))
+            .nth(dense_code as usize)
+            .map(u32::from)
+            .unwrap_or_default()
+    }
+}
+
+/// Тесты атласа алфавита 🎯
+#[cfg(test)]
+mod alphabet_atlas_tests {
+    use super::*;
+
+    #[test]
+    fn test_dense_codes_roundtrip() {
+        let codex = [
+            (b'A' as u32, 3, 0),
+            (b'C' as u32, 1, 3),
+            (b'T' as u32, 2, 4),
+            (257, 1, 6),
+        ];
+        let atlas = AlphabetMap::from_frequency_codex(&codex);
+
+        assert_eq!(atlas.len(), 3);
+        assert_eq!(atlas.dense_code(b'C' as u32), Some(1));
+        assert_eq!(atlas.dense_code(b'G' as u32), None);
+        assert_eq!(atlas.dense_code(257), Some(4));
+
+        for &(symbol_id, _, _) in &codex {
+            let dense = atlas.dense_code(symbol_id).unwrap();
+            assert_eq!(atlas.symbol_for(dense), symbol_id);
+        }
+    }
+
+    #[test]
+    fn test_bitmap_roundtrip() {
+        let atlas = AlphabetMap::from_frequency_codex(&[(0, 1, 0), (255, 1, 1)]);
+        let restored = AlphabetMap::from_bytes(*atlas.as_bytes());
+
+        assert_eq!(restored, atlas);
+        assert!(restored.contains(0) && restored.contains(255));
+        assert!(!AlphabetMap::from_bytes([0; 32]).contains(7));
+        assert!(AlphabetMap::from_bytes([0; 32]).is_empty());
+    }
+}
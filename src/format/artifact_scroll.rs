@@ -0,0 +1,692 @@
+//! Свиток артефакта 📜
+//!
+//! Сериализация [`CompressionArtifact`] в единый поток байтов и
+//! проверяемый разбор обратно.
+//!
+//! Формат: `[заголовок][словарь][фразы][таблица_частот][общая_частота][данные]`
+//! - словарь: `[кол-во u32]` + `[длина u32][байты]` на каждое слово
+//! - фразы (с версии 5): `[кол-во u32]` + `[длина u8][символ u32 × длина]`
+//! - таблица частот: `[кол-во u32]` + `[символ u32][частота u64][начало u64]`,
+//!   а с атласом алфавита — `[кол-во u32]` + `[частота u32]` на каждый плотный код
+//! - общая частота: `u64`
+//! - данные: `[длина u32][битовый поток]`
+//!
+//! Необязательные сведения об архиве (словарь, атлас, метаданные) лежат в
+//! разделах заголовка (см. [`container_runes`](super::container_runes)),
+//! тело — за движком.
+
+use super::alphabet_atlas::{AlphabetMap, ALPHABET_COMPACTION_LIMIT};
+use super::artifact_view::{ArtifactRef, FrequencyCodexRef, FREQUENCY_ENTRY_LEN};
+use super::container_runes::{
+    ArchiveHeader, EngineKind, ModelKind, FORMAT_VERSION, PHRASE_FORMAT_VERSION,
+};
+use crate::compression_engine::compression_conjurer::CompressionArtifact;
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::error::{DecodePhase, WizardError, WizardResult};
+
+/// Параметры сериализации ⚙️
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScrollOptions {
+    /// Сжимать алфавит разреженных входов (см. [`AlphabetMap`])
+    pub compact_alphabet: bool,
+}
+
+impl ScrollOptions {
+    /// Включает сжатие алфавита
+    pub fn with_compact_alphabet(mut self, compact_alphabet: bool) -> Self {
+        self.compact_alphabet = compact_alphabet;
+        self
+    }
+}
+
+/// Наибольшее значение поля длины `u32` в кадре
+///
+/// Вход длиннее не умещается в один кадр: его сжимают блоками.
+pub const MAX_LENGTH_FIELD: u64 = u32::MAX as u64;
+
+/// Проверяет, что длина умещается в поле `u32`
+pub(crate) fn check_length_field(len: usize) -> WizardResult<()> {
+    if len as u64 > MAX_LENGTH_FIELD {
+        return Err(WizardError::InputTooLarge {
+            len: len as u64,
+            limit: MAX_LENGTH_FIELD,
+        });
+    }
+    Ok(())
+}
+
+/// Сериализует артефакт в единый поток байтов
+///
+/// Паникует, если поле длины не умещается в `u32` — см.
+/// [`try_serialize_artifact`].
+pub fn serialize_artifact(artifact: &CompressionArtifact) -> Vec<u8> {
+    try_serialize_artifact(artifact)
+        .expect("поле длины больше u32: используйте try_serialize_artifact")
+}
+
+/// Проверяемая сериализация артефакта 🛡️
+///
+/// Длины словаря, фраз, таблицы частот и битового потока записываются
+/// полями `u32`; не умещающаяся длина дает [`WizardError::InputTooLarge`]
+/// вместо молча обрезанного поля.
+pub fn try_serialize_artifact(artifact: &CompressionArtifact) -> WizardResult<Vec<u8>> {
+    check_length_field(artifact.compressed_bit_stream.len())?;
+    check_length_field(artifact.mystical_frequency_codex.len())?;
+    check_length_field(artifact.mystical_word_grimoire.len())?;
+    check_length_field(artifact.mystical_phrase_codex.len())?;
+    for word in &artifact.mystical_word_grimoire {
+        check_length_field(word.len())?;
+    }
+    Ok(serialize_artifact_with_options(
+        artifact,
+        &ScrollOptions::default(),
+    ))
+}
+
+/// Сериализует артефакт с явными параметрами
+///
+/// Атлас алфавита записывается, только если он включен, входные данные
+/// используют меньше [`ALPHABET_COMPACTION_LIMIT`] различных байтов
+/// и все частоты умещаются в `u32`.
+pub fn serialize_artifact_with_options(
+    artifact: &CompressionArtifact,
+    options: &ScrollOptions,
+) -> Vec<u8> {
+    let (header, body) = serialize_artifact_parts(artifact, options);
+    let mut result = Vec::with_capacity(header.encoded_len() + body.len());
+    header.write_to(&mut result);
+    result.extend_from_slice(&body);
+    result
+}
+
+/// Сериализует артефакт в заголовок и тело кадра
+pub(crate) fn serialize_artifact_parts(
+    artifact: &CompressionArtifact,
+    options: &ScrollOptions,
+) -> (ArchiveHeader, Vec<u8>) {
+    let alphabet_map = options
+        .compact_alphabet
+        .then(|| AlphabetMap::from_frequency_codex(&artifact.mystical_frequency_codex))
+        .filter(|atlas| atlas.len() < ALPHABET_COMPACTION_LIMIT)
+        .filter(|_| {
+            artifact
+                .mystical_frequency_codex
+                .iter()
+                .all(|&(_, freq, _)| freq <= u32::MAX as u64)
+        });
+
+    let mut result = Vec::new();
+    let mut header = ArchiveHeader::current(ModelKind::StaticOrder0, None);
+    if let Some(atlas) = alphabet_map {
+        header = header.with_alphabet_map(atlas);
+    }
+
+    write_grimoire_sections(
+        &mut result,
+        &artifact.mystical_word_grimoire,
+        &artifact.mystical_phrase_codex,
+    );
+
+    // Таблица частот
+    match alphabet_map {
+        Some(atlas) => write_dense_codex(&mut result, &artifact.mystical_frequency_codex, &atlas),
+        None => write_frequency_section(&mut result, &artifact.mystical_frequency_codex),
+    }
+
+    write_coded_stream(
+        &mut result,
+        artifact.total_frequency_essence,
+        &artifact.compressed_bit_stream,
+    );
+
+    (header, result)
+}
+
+/// Записывает словарь и таблицу фраз
+pub(crate) fn write_grimoire_sections(
+    output: &mut Vec<u8>,
+    word_grimoire: &[String],
+    phrase_codex: &[Vec<u32>],
+) {
+    // Словарь
+    output.extend_from_slice(&(word_grimoire.len() as u32).to_le_bytes());
+    for word in word_grimoire {
+        output.extend_from_slice(&(word.len() as u32).to_le_bytes());
+        output.extend_from_slice(word.as_bytes());
+    }
+
+    // Фразы
+    output.extend_from_slice(&(phrase_codex.len() as u32).to_le_bytes());
+    for phrase in phrase_codex {
+        output.push(phrase.len() as u8);
+        for &element in phrase {
+            output.extend_from_slice(&element.to_le_bytes());
+        }
+    }
+}
+
+/// Записывает таблицу частот полными записями
+pub(crate) fn write_frequency_section(output: &mut Vec<u8>, frequency_codex: &[(u32, u64, u64)]) {
+    output.extend_from_slice(&(frequency_codex.len() as u32).to_le_bytes());
+    for &(symbol, freq, start) in frequency_codex {
+        output.extend_from_slice(&symbol.to_le_bytes());
+        output.extend_from_slice(&freq.to_le_bytes());
+        output.extend_from_slice(&start.to_le_bytes());
+    }
+}
+
+/// Записывает общую частоту и битовый поток
+pub(crate) fn write_coded_stream(
+    output: &mut Vec<u8>,
+    total_frequency_mass: u64,
+    compressed_bit_stream: &[u8],
+) {
+    // Общая частота
+    output.extend_from_slice(&total_frequency_mass.to_le_bytes());
+
+    // Сжатые данные
+    output.extend_from_slice(&(compressed_bit_stream.len() as u32).to_le_bytes());
+    output.extend_from_slice(compressed_bit_stream);
+}
+
+/// Записывает таблицу частот по плотным кодам атласа
+///
+/// Отсутствующие символы получают нулевую частоту.
+fn write_dense_codex(
+    output: &mut Vec<u8>,
+    frequency_codex: &[(u32, u64, u64)],
+    atlas: &AlphabetMap,
+) {
+    let dense_count = frequency_codex
+        .iter()
+        .filter_map(|&(symbol_id, _, _)| atlas.dense_code(symbol_id))
+        .max()
+        .map_or(0, |highest| highest as usize + 1);
+
+    let mut dense_frequencies = vec![0u32; dense_count];
+    for &(symbol_id, freq, _) in frequency_codex {
+        if let Some(dense) = atlas.dense_code(symbol_id) {
+            dense_frequencies[dense as usize] = freq as u32;
+        }
+    }
+
+    output.extend_from_slice(&(dense_count as u32).to_le_bytes());
+    for freq in dense_frequencies {
+        output.extend_from_slice(&freq.to_le_bytes());
+    }
+}
+
+/// Разбирает сериализованный артефакт без паники
+///
+/// Все длины проверяются по оставшимся байтам, поэтому лживые счетчики
+/// в заголовке не приводят к большим выделениям памяти.
+/// Применяются лимиты [`DecodeOptions::default`].
+pub fn parse_artifact(archive_bytes: &[u8]) -> WizardResult<(CompressionArtifact, ArchiveHeader)> {
+    parse_artifact_with_options(archive_bytes, &DecodeOptions::default())
+}
+
+/// Разбирает артефакт с явными лимитами
+///
+/// Заявленные размеры словаря и таблицы частот сверяются с `options`
+/// до чтения записей.
+pub fn parse_artifact_with_options(
+    archive_bytes: &[u8],
+    options: &DecodeOptions,
+) -> WizardResult<(CompressionArtifact, ArchiveHeader)> {
+    let (header, body_offset) = ArchiveHeader::try_parse(archive_bytes)?;
+    let artifact = parse_artifact_body(&header, &archive_bytes[body_offset..], options)
+        .map_err(|error| error.shifted(body_offset))?;
+    Ok((artifact, header))
+}
+
+/// Разбирает тело кадра арифметического движка во владеющий артефакт
+pub(crate) fn parse_artifact_body(
+    header: &ArchiveHeader,
+    body: &[u8],
+    options: &DecodeOptions,
+) -> WizardResult<CompressionArtifact> {
+    parse_artifact_ref_body(header, body, options).map(|artifact_ref| artifact_ref.to_artifact())
+}
+
+/// Разбирает архив в заимствованное представление без копирования
+///
+/// Слова, таблица частот и битовый поток остаются срезами `archive_bytes`.
+/// Проверки и лимиты те же, что у [`parse_artifact_with_options`].
+pub fn parse_artifact_ref<'a>(
+    archive_bytes: &'a [u8],
+    options: &DecodeOptions,
+) -> WizardResult<ArtifactRef<'a>> {
+    let (header, body_offset) = ArchiveHeader::try_parse(archive_bytes)?;
+    parse_artifact_ref_body(&header, &archive_bytes[body_offset..], options)
+        .map_err(|error| error.shifted(body_offset))
+}
+
+/// Разбирает тело кадра в заимствованное представление
+pub(crate) fn parse_artifact_ref_body<'a>(
+    header: &ArchiveHeader,
+    body: &'a [u8],
+    options: &DecodeOptions,
+) -> WizardResult<ArtifactRef<'a>> {
+    check_body_header(header, EngineKind::Arithmetic)?;
+
+    let mut cursor = ScrollCursor::new(body, 0);
+    read_artifact_sections(&mut cursor, header, options)
+        // Курсор стоит на поле, которое не удалось прочитать
+        .map_err(|error| error.located(DecodePhase::Table, cursor.position()))
+}
+
+/// Читает словарь, таблицу частот и битовый поток тела кадра
+fn read_artifact_sections<'a>(
+    cursor: &mut ScrollCursor<'a>,
+    header: &ArchiveHeader,
+    options: &DecodeOptions,
+) -> WizardResult<ArtifactRef<'a>> {
+    let (word_grimoire, phrase_codex) = read_grimoire_sections(cursor, header, options)?;
+
+    // Таблица частот
+    let frequency_codex = match header.alphabet_map {
+        Some(atlas) => FrequencyCodexRef::Owned(read_dense_codex(cursor, &atlas, options)?),
+        None => read_frequency_section(cursor, options)?,
+    };
+
+    let (total_frequency, compressed_bit_stream) = read_coded_stream(cursor)?;
+
+    Ok(ArtifactRef {
+        header: *header,
+        word_grimoire,
+        phrase_codex,
+        frequency_codex,
+        total_frequency_essence: total_frequency,
+        compressed_bit_stream,
+    })
+}
+
+/// Проверяет, что тело кадра можно разобрать этой версией для движка `engine`
+///
+/// Тела со статической таблицей частот; адаптивные модели проверяются
+/// в [`adaptive_tide`](crate::compression_engine::adaptive_tide) и
+/// [`context_loom`](crate::compression_engine::context_loom).
+pub(crate) fn check_body_header(header: &ArchiveHeader, engine: EngineKind) -> WizardResult<()> {
+    check_frame_layout(header, engine)?;
+    if header.model_kind.is_adaptive() {
+        return Err(WizardError::UnsupportedModel(header.model_kind.to_byte()));
+    }
+    Ok(())
+}
+
+/// Проверяет версию, движок и отсутствие блоков и внешнего словаря
+pub(crate) fn check_frame_layout(header: &ArchiveHeader, engine: EngineKind) -> WizardResult<()> {
+    if header.format_version > FORMAT_VERSION {
+        return Err(WizardError::UnsupportedVersion(header.format_version));
+    }
+    if header.block_framed {
+        return Err(WizardError::CorruptBlockTable(
+            "архив разбит на блоки: используйте try_decompress_data",
+        ));
+    }
+    if header.engine != engine {
+        return Err(WizardError::UnsupportedEngine(header.engine.to_byte()));
+    }
+    if let Some(dictionary_id) = header.dictionary_id {
+        return Err(WizardError::ExternalDictionaryRequired(dictionary_id));
+    }
+    Ok(())
+}
+
+/// Словарь (срезами буфера) и таблица фраз
+pub(crate) type GrimoireSections<'a> = (Vec<&'a [u8]>, Vec<Vec<u32>>);
+
+/// Читает словарь (срезами буфера) и таблицу фраз
+pub(crate) fn read_grimoire_sections<'a>(
+    cursor: &mut ScrollCursor<'a>,
+    header: &ArchiveHeader,
+    options: &DecodeOptions,
+) -> WizardResult<GrimoireSections<'a>> {
+    // Словарь
+    let word_count = cursor.read_u32()? as usize;
+    options.check_dict_entries(word_count)?;
+    let mut word_grimoire = Vec::with_capacity(word_count.min(cursor.remaining() / 4));
+    let mut grimoire_bytes = 0;
+    for _ in 0..word_count {
+        let word_len = cursor.read_u32()? as usize;
+        grimoire_bytes = options.check_word_bytes(grimoire_bytes, word_len)?;
+        word_grimoire.push(cursor.read_slice(word_len)?);
+    }
+
+    // Фразы
+    let mut phrase_codex = Vec::new();
+    if header.format_version >= PHRASE_FORMAT_VERSION {
+        let phrase_count = cursor.read_u32()? as usize;
+        options.check_dict_entries(phrase_count)?;
+        phrase_codex.reserve(phrase_count.min(cursor.remaining()));
+        for _ in 0..phrase_count {
+            let phrase_len = cursor.read_slice(1)?[0] as usize;
+            let phrase = (0..phrase_len)
+                .map(|_| cursor.read_u32())
+                .collect::<WizardResult<Vec<u32>>>()?;
+            phrase_codex.push(phrase);
+        }
+    }
+
+    Ok((word_grimoire, phrase_codex))
+}
+
+/// Читает таблицу частот из полных записей без копирования
+pub(crate) fn read_frequency_section<'a>(
+    cursor: &mut ScrollCursor<'a>,
+    options: &DecodeOptions,
+) -> WizardResult<FrequencyCodexRef<'a>> {
+    let freq_count = cursor.read_u32()? as usize;
+    options.check_table_entries(freq_count)?;
+    let records_len =
+        freq_count
+            .checked_mul(FREQUENCY_ENTRY_LEN)
+            .ok_or(WizardError::Truncated {
+                needed: usize::MAX,
+                available: cursor.remaining(),
+            })?;
+    Ok(FrequencyCodexRef::Borrowed(cursor.read_slice(records_len)?))
+}
+
+/// Читает общую частоту и битовый поток
+pub(crate) fn read_coded_stream<'a>(
+    cursor: &mut ScrollCursor<'a>,
+) -> WizardResult<(u64, &'a [u8])> {
+    // Общая частота
+    let total_frequency = cursor.read_u64()?;
+
+    // Сжатые данные
+    let compressed_len = cursor.read_u32()? as usize;
+    Ok((total_frequency, cursor.read_slice(compressed_len)?))
+}
+
+/// Читает таблицу частот по плотным кодам атласа
+///
+/// Id символов и накопительные позиции восстанавливаются из атласа.
+fn read_dense_codex(
+    cursor: &mut ScrollCursor<'_>,
+    atlas: &AlphabetMap,
+    options: &DecodeOptions,
+) -> WizardResult<Vec<(u32, u64, u64)>> {
+    let dense_count = cursor.read_u32()?;
+    options.check_table_entries(dense_count as usize)?;
+
+    let mut frequency_codex =
+        Vec::with_capacity((dense_count as usize).min(cursor.remaining() / 4));
+    let mut cumulative_position = 0u64;
+    for dense_code in 0..dense_count {
+        let freq = cursor.read_u32()? as u64;
+        if freq == 0 {
+            continue;
+        }
+        frequency_codex.push((atlas.symbol_for(dense_code), freq, cumulative_position));
+        cumulative_position += freq;
+    }
+    Ok(frequency_codex)
+}
+
+/// Смещение среза `inner` от начала буфера `outer`
+///
+/// `inner` должен быть заимствован из `outer`: так место ошибки во
+/// вложенном кадре переводится в смещение от начала архива.
+pub(crate) fn offset_within(outer: &[u8], inner: &[u8]) -> usize {
+    (inner.as_ptr() as usize).saturating_sub(outer.as_ptr() as usize)
+}
+
+/// Курсор чтения с проверкой границ 🧭
+#[derive(Debug, Clone)]
+pub(crate) struct ScrollCursor<'scroll> {
+    bytes: &'scroll [u8],
+    position: usize,
+}
+
+impl<'scroll> ScrollCursor<'scroll> {
+    /// Курсор, начинающийся с позиции `position`
+    pub(crate) fn new(bytes: &'scroll [u8], position: usize) -> Self {
+        Self { bytes, position }
+    }
+
+    /// Текущая позиция
+    pub(crate) fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Сколько байтов осталось
+    pub(crate) fn remaining(&self) -> usize {
+        self.bytes.len().saturating_sub(self.position)
+    }
+
+    /// Читает `len` байтов
+    pub(crate) fn read_slice(&mut self, len: usize) -> WizardResult<&'scroll [u8]> {
+        if len > self.remaining() {
+            return Err(WizardError::Truncated {
+                needed: len,
+                available: self.remaining(),
+            });
+        }
+
+        let slice = &self.bytes[self.position..self.position + len];
+        self.position += len;
+        Ok(slice)
+    }
+
+    /// Читает u32 (little-endian)
+    pub(crate) fn read_u32(&mut self) -> WizardResult<u32> {
+        let mut buffer = [0u8; 4];
+        buffer.copy_from_slice(self.read_slice(4)?);
+        Ok(u32::from_le_bytes(buffer))
+    }
+
+    /// Читает u64 (little-endian)
+    pub(crate) fn read_u64(&mut self) -> WizardResult<u64> {
+        let mut buffer = [0u8; 8];
+        buffer.copy_from_slice(self.read_slice(8)?);
+        Ok(u64::from_le_bytes(buffer))
+    }
+}
+
+/// Тесты свитка артефакта 🎯
+#[cfg(test)]
+mod artifact_scroll_tests {
+    use super::*;
+    use crate::compression_engine::compression_conjurer::weave_compression_spell;
+    use crate::error::LimitKind;
+
+    #[test]
+    fn test_serialize_parse_roundtrip() {
+        let artifact = weave_compression_spell(b"the scroll and the wand and the hat");
+        let bytes = serialize_artifact(&artifact);
+
+        let (parsed, header) = parse_artifact(&bytes).unwrap();
+        assert_eq!(header.format_version, FORMAT_VERSION);
+        assert_eq!(
+            parsed.mystical_word_grimoire,
+            artifact.mystical_word_grimoire
+        );
+        assert_eq!(
+            parsed.mystical_frequency_codex,
+            artifact.mystical_frequency_codex
+        );
+        assert_eq!(parsed.compressed_bit_stream, artifact.compressed_bit_stream);
+    }
+
+    #[test]
+    fn test_length_fields_around_4_gib() {
+        assert_eq!(check_length_field(0), Ok(()));
+        assert_eq!(check_length_field(u32::MAX as usize), Ok(()));
+        #[cfg(target_pointer_width = "64")]
+        {
+            let four_gib = 1usize << 32;
+            assert_eq!(
+                check_length_field(four_gib),
+                Err(WizardError::InputTooLarge {
+                    len: four_gib as u64,
+                    limit: MAX_LENGTH_FIELD,
+                })
+            );
+            assert_eq!(
+                check_length_field(four_gib + 1).unwrap_err().code(),
+                WizardError::InputTooLarge { len: 0, limit: 0 }.code()
+            );
+        }
+
+        // Обычный артефакт проходит проверку без изменений байтов
+        let artifact = weave_compression_spell("the owl and the cat. ".repeat(40));
+        assert_eq!(
+            try_serialize_artifact(&artifact).unwrap(),
+            serialize_artifact(&artifact)
+        );
+    }
+
+    #[test]
+    fn test_phrase_codex_roundtrip() {
+        let artifact = weave_compression_spell("the owl and the cat. ".repeat(40));
+        assert!(!artifact.mystical_phrase_codex.is_empty());
+
+        let (parsed, _) = parse_artifact(&serialize_artifact(&artifact)).unwrap();
+        assert_eq!(parsed.mystical_phrase_codex, artifact.mystical_phrase_codex);
+    }
+
+    #[test]
+    fn test_artifact_ref_borrows_archive_buffer() {
+        let artifact = weave_compression_spell("the owl and the cat. ".repeat(40));
+        let bytes = serialize_artifact(&artifact);
+        let buffer = bytes.as_ptr_range();
+
+        let artifact_ref = parse_artifact_ref(&bytes, &DecodeOptions::default()).unwrap();
+        assert!(matches!(
+            artifact_ref.frequency_codex,
+            FrequencyCodexRef::Borrowed(_)
+        ));
+        assert!(buffer.contains(&artifact_ref.compressed_bit_stream.as_ptr()));
+        assert!(artifact_ref
+            .word_grimoire
+            .iter()
+            .all(|word| buffer.contains(&word.as_ptr())));
+
+        let owned = artifact_ref.to_artifact();
+        assert_eq!(
+            owned.mystical_word_grimoire,
+            artifact.mystical_word_grimoire
+        );
+        assert_eq!(owned.mystical_phrase_codex, artifact.mystical_phrase_codex);
+        assert_eq!(
+            owned.mystical_frequency_codex,
+            artifact.mystical_frequency_codex
+        );
+        assert_eq!(owned.compressed_bit_stream, artifact.compressed_bit_stream);
+    }
+
+    #[test]
+    fn test_compact_alphabet_roundtrip() {
+        let artifact = weave_compression_spell(b"GATTACAGATTACACCGGTTAAGATTACA");
+        let plain = serialize_artifact(&artifact);
+        let compact = serialize_artifact_with_options(
+            &artifact,
+            &ScrollOptions::default().with_compact_alphabet(true),
+        );
+
+        let (parsed, header) = parse_artifact(&compact).unwrap();
+        assert_eq!(header.alphabet_map.unwrap().len(), 4);
+        assert_eq!(
+            parsed.mystical_frequency_codex,
+            artifact.mystical_frequency_codex
+        );
+        assert_eq!(parsed.compressed_bit_stream, artifact.compressed_bit_stream);
+        assert!(compact.len() < plain.len());
+    }
+
+    #[test]
+    fn test_wide_alphabet_is_not_compacted() {
+        let every_byte: Vec<u8> = (0..=255).collect();
+        let artifact = weave_compression_spell(&every_byte);
+        let bytes = serialize_artifact_with_options(
+            &artifact,
+            &ScrollOptions::default().with_compact_alphabet(true),
+        );
+
+        let (_, header) = parse_artifact(&bytes).unwrap();
+        assert!(header.alphabet_map.is_none());
+    }
+
+    #[test]
+    fn test_lying_counts_are_truncation_errors() {
+        let mut bytes = Vec::new();
+        ArchiveHeader::current(ModelKind::StaticOrder0, None).write_to(&mut bytes);
+        bytes.extend_from_slice(&u32::MAX.to_le_bytes()); // миллиарды слов
+        bytes.extend_from_slice(&u32::MAX.to_le_bytes()); // слово длиной 4 ГиБ
+
+        // Даже без лимитов лживые счетчики не приводят к выделениям
+        let error = parse_artifact_with_options(&bytes, &DecodeOptions::unlimited()).unwrap_err();
+        assert!(matches!(error.root_cause(), WizardError::Truncated { .. }));
+        // Разбор остановился на слове, которого нет в архиве
+        let location = error.location().unwrap();
+        assert_eq!(location.phase, DecodePhase::Table);
+        assert_eq!(location.offset, bytes.len());
+    }
+
+    #[test]
+    fn test_claimed_counts_respect_limits() {
+        let mut bytes = Vec::new();
+        ArchiveHeader::current(ModelKind::StaticOrder0, None).write_to(&mut bytes);
+        bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+
+        assert!(matches!(
+            parse_artifact(&bytes),
+            Err(WizardError::LimitExceeded {
+                kind: LimitKind::DictEntries,
+                ..
+            })
+        ));
+
+        // Слово длиной 4 ГиБ отклоняется до чтения самого слова
+        let mut bytes = Vec::new();
+        ArchiveHeader::current(ModelKind::StaticOrder0, None).write_to(&mut bytes);
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            parse_artifact(&bytes),
+            Err(WizardError::LimitExceeded {
+                kind: LimitKind::WordBytes,
+                ..
+            })
+        ));
+
+        let artifact =
+            weave_compression_spell("the wizard and the owl watched the tower. ".repeat(30));
+        let bytes = serialize_artifact(&artifact);
+        let longest = artifact
+            .mystical_word_grimoire
+            .iter()
+            .map(String::len)
+            .max()
+            .unwrap();
+        let total: usize = artifact
+            .mystical_word_grimoire
+            .iter()
+            .map(String::len)
+            .sum();
+        let fitting = DecodeOptions::default()
+            .with_max_word_bytes(longest)
+            .with_max_dictionary_bytes(total);
+        assert!(parse_artifact_with_options(&bytes, &fitting).is_ok());
+        assert!(matches!(
+            parse_artifact_with_options(&bytes, &fitting.with_max_dictionary_bytes(total - 1)),
+            Err(WizardError::LimitExceeded {
+                kind: LimitKind::DictionaryBytes,
+                ..
+            })
+        ));
+
+        let artifact = weave_compression_spell(b"abcdefgh");
+        let bytes = serialize_artifact(&artifact);
+        let strict = DecodeOptions::default().with_max_table_entries(3);
+        assert!(matches!(
+            parse_artifact_with_options(&bytes, &strict),
+            Err(WizardError::LimitExceeded {
+                kind: LimitKind::TableEntries,
+                ..
+            })
+        ));
+    }
+}
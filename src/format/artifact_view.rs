@@ -0,0 +1,158 @@
+//! Взгляд на артефакт 👁️
+//!
+//! Заимствованное представление сериализованного архива: слова словаря,
+//! таблица частот и битовый поток остаются срезами исходного буфера,
+//! поэтому распаковка не создает отдельных выделений под каждое слово
+//! и каждую запись таблицы.
+
+use super::container_runes::ArchiveHeader;
+use crate::compression_engine::compression_conjurer::CompressionArtifact;
+
+/// Размер одной записи таблицы частот в архиве
+pub(crate) const FREQUENCY_ENTRY_LEN: usize = 4 + 8 + 8;
+
+/// Доступ к записям таблицы частот по индексу
+///
+/// Позволяет декодеру читать таблицу как из `Vec`, так и прямо из архива.
+pub(crate) trait CodexView {
+    /// Число записей
+    fn entry_count(&self) -> usize;
+
+    /// Запись `(символ, частота, накопительная позиция)`
+    fn entry(&self, index: usize) -> (u32, u64, u64);
+}
+
+impl CodexView for [(u32, u64, u64)] {
+    fn entry_count(&self) -> usize {
+        self.len()
+    }
+
+    fn entry(&self, index: usize) -> (u32, u64, u64) {
+        self[index]
+    }
+}
+
+/// Таблица частот: записи в буфере архива или восстановленные 📊
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrequencyCodexRef<'a> {
+    /// Записи `[символ u32][частота u64][начало u64]` прямо в буфере архива
+    Borrowed(&'a [u8]),
+    /// Таблица, восстановленная из компактного вида (атлас алфавита)
+    Owned(Vec<(u32, u64, u64)>),
+}
+
+impl FrequencyCodexRef<'_> {
+    /// Число записей
+    pub fn len(&self) -> usize {
+        match self {
+            FrequencyCodexRef::Borrowed(records) => records.len() / FREQUENCY_ENTRY_LEN,
+            FrequencyCodexRef::Owned(entries) => entries.len(),
+        }
+    }
+
+    /// Таблица пуста
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Запись по индексу
+    pub fn get(&self, index: usize) -> Option<(u32, u64, u64)> {
+        (index < self.len()).then(|| CodexView::entry(self, index))
+    }
+
+    /// Все записи по порядку
+    pub fn iter(&self) -> impl Iterator<Item = (u32, u64, u64)> + '_ {
+        (0..self.len()).map(|index| CodexView::entry(self, index))
+    }
+}
+
+impl CodexView for FrequencyCodexRef<'_> {
+    fn entry_count(&self) -> usize {
+        self.len()
+    }
+
+    fn entry(&self, index: usize) -> (u32, u64, u64) {
+        match self {
+            FrequencyCodexRef::Borrowed(records) => {
+                let record = &records[index * FREQUENCY_ENTRY_LEN..][..FREQUENCY_ENTRY_LEN];
+                let mut symbol = [0u8; 4];
+                let mut frequency = [0u8; 8];
+                let mut start = [0u8; 8];
+                symbol.copy_from_slice(&record[..4]);
+                frequency.copy_from_slice(&record[4..12]);
+                start.copy_from_slice(&record[12..]);
+                (
+                    u32::from_le_bytes(symbol),
+                    u64::from_le_bytes(frequency),
+                    u64::from_le_bytes(start),
+                )
+            }
+            FrequencyCodexRef::Owned(entries) => entries[index],
+        }
+    }
+}
+
+/// Заимствованный артефакт 🔍
+///
+/// Фразы хранятся отдельно: они короткие (2–3 символа) и их мало.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactRef<'a> {
+    /// Заголовок архива
+    pub header: ArchiveHeader,
+    /// Слова словаря как срезы буфера (байты без проверки UTF-8)
+    pub word_grimoire: Vec<&'a [u8]>,
+    /// Фразы
+    pub phrase_codex: Vec<Vec<u32>>,
+    /// Таблица частот
+    pub frequency_codex: FrequencyCodexRef<'a>,
+    /// Общая масса частот
+    pub total_frequency_essence: u64,
+    /// Битовый поток как срез буфера
+    pub compressed_bit_stream: &'a [u8],
+}
+
+impl ArtifactRef<'_> {
+    /// Копирует данные во владеющий артефакт
+    pub fn to_artifact(&self) -> CompressionArtifact {
+        CompressionArtifact {
+            mystical_frequency_codex: self.frequency_codex.iter().collect(),
+            total_frequency_essence: self.total_frequency_essence,
+            compressed_bit_stream: self.compressed_bit_stream.to_vec(),
+            mystical_word_grimoire: self
+                .word_grimoire
+                .iter()
+                .map(|word| String::from_utf8_lossy(word).into_owned())
+                .collect(),
+            mystical_phrase_codex: self.phrase_codex.clone(),
+        }
+    }
+}
+
+/// Тесты взгляда на артефакт 🎯
+#[cfg(test)]
+mod artifact_view_tests {
+    use super::*;
+
+    #[test]
+    fn test_borrowed_records_match_owned() {
+        let entries = vec![(65u32, 3u64, 0u64), (300, 2, 3)];
+        let mut records = Vec::new();
+        for &(symbol, frequency, start) in &entries {
+            records.extend_from_slice(&symbol.to_le_bytes());
+            records.extend_from_slice(&frequency.to_le_bytes());
+            records.extend_from_slice(&start.to_le_bytes());
+        }
+
+        let borrowed = FrequencyCodexRef::Borrowed(&records);
+        assert_eq!(borrowed.len(), 2);
+        assert_eq!(borrowed.get(1), Some((300, 2, 3)));
+        assert_eq!(borrowed.get(2), None);
+        assert_eq!(borrowed.iter().collect::<Vec<_>>(), entries);
+        assert_eq!(
+            FrequencyCodexRef::Owned(entries.clone())
+                .iter()
+                .collect::<Vec<_>>(),
+            entries
+        );
+    }
+}
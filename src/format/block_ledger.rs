@@ -0,0 +1,390 @@
+//! Реестр блоков 🧱
+//!
+//! Большой вход можно сжать независимыми блоками: каждый блок — полноценный
+//! архив со своим заголовком, словарем и таблицей частот. Блоки не зависят
+//! друг от друга, поэтому распаковываются параллельно
+//! (см. [`DecodeOptions::decode_threads`]).
+//!
+//! Раскладка: `[заголовок с флагом блоков][кол-во блоков u32]`,
+//! затем `[исходная длина u64][длина кадра u32]` на каждый блок
+//! и сами кадры подряд в том же порядке.
+//!
+//! Потоковая запись (см. [`BlockStreamWriter`](super::block_stream::BlockStreamWriter))
+//! не знает числа блоков заранее: вместо него пишется
+//! [`STREAMED_BLOCK_COUNT`], каждая запись реестра идет прямо перед своим
+//! кадром, а поток завершает запись с нулевыми длинами.
+//!
+//! Раскладка с маркерами синхронизации ([`SYNCED_BLOCK_COUNT`]) устроена
+//! так же, но каждая запись начинается с [`SYNC_MARKER`] и заканчивается
+//! хешем длин и кадра. По маркеру распаковщик находит следующий блок после
+//! поврежденного участка (см. [`decompress_lossy`](crate::simple_api::decompress_lossy)),
+//! а хеш отличает целый блок от поврежденного.
+
+use super::artifact_scroll::ScrollCursor;
+use super::container_runes::{ArchiveHeader, ModelKind, FORMAT_VERSION};
+use super::parity_runes::restore_parity;
+use crate::bit_wizardry::checksum_runes::RuneHasher;
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::error::{DecodePhase, WizardError, WizardResult};
+use std::borrow::Cow;
+
+/// Размер блока по умолчанию (1 МиБ)
+pub const DEFAULT_BLOCK_SIZE: usize = 1 << 20;
+
+/// Размер записи реестра: исходная длина u64 + длина кадра u32
+pub(crate) const BLOCK_ENTRY_LEN: usize = 8 + 4;
+
+/// Число блоков потоковой записи: записи реестра чередуются с кадрами
+pub(crate) const STREAMED_BLOCK_COUNT: u32 = u32::MAX;
+
+/// Число блоков раскладки с маркерами синхронизации
+pub(crate) const SYNCED_BLOCK_COUNT: u32 = u32::MAX - 1;
+
+/// Маркер синхронизации перед каждой записью реестра 🔖
+pub const SYNC_MARKER: [u8; 8] = [0xAC, 0x57, 0x5E, 0x4E, 0xC0, 0xB1, 0x0C, 0x4B];
+
+/// Размер записи с маркером: маркер + исходная длина u64 + длина кадра u32 + хеш u64
+pub(crate) const SYNCED_ENTRY_LEN: usize = SYNC_MARKER.len() + BLOCK_ENTRY_LEN + 8;
+
+/// Блок в буфере архива 🧱
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockRef<'a> {
+    /// Длина исходных данных блока
+    pub original_len: u64,
+    /// Кадр блока: архив с собственным заголовком
+    pub frame: &'a [u8],
+}
+
+/// Собирает архив из кадров `(исходная длина, кадр)`
+pub(crate) fn write_block_archive(blocks: &[(u64, Vec<u8>)]) -> Vec<u8> {
+    let frames_len: usize = blocks.iter().map(|(_, frame)| frame.len()).sum();
+    let mut output = Vec::with_capacity(64 + blocks.len() * BLOCK_ENTRY_LEN + frames_len);
+
+    ArchiveHeader::current(ModelKind::StaticOrder0, None)
+        .with_block_framing()
+        .write_to(&mut output);
+    output.extend_from_slice(&(blocks.len() as u32).to_le_bytes());
+    for (original_len, frame) in blocks {
+        output.extend_from_slice(&original_len.to_le_bytes());
+        output.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+    }
+    for (_, frame) in blocks {
+        output.extend_from_slice(frame);
+    }
+
+    output
+}
+
+/// Собирает архив из кадров с маркерами синхронизации между блоками
+pub(crate) fn write_synced_block_archive(blocks: &[(u64, Vec<u8>)]) -> Vec<u8> {
+    let frames_len: usize = blocks.iter().map(|(_, frame)| frame.len()).sum();
+    let mut output = Vec::with_capacity(64 + (blocks.len() + 1) * SYNCED_ENTRY_LEN + frames_len);
+
+    ArchiveHeader::current(ModelKind::StaticOrder0, None)
+        .with_block_framing()
+        .write_to(&mut output);
+    output.extend_from_slice(&SYNCED_BLOCK_COUNT.to_le_bytes());
+    for (original_len, frame) in blocks {
+        write_synced_entry(&mut output, *original_len, frame);
+    }
+    // Завершающая запись: маркер и нулевые поля
+    write_synced_entry(&mut output, 0, &[]);
+
+    output
+}
+
+/// Пишет запись с маркером и кадр (пустой кадр — завершающая запись)
+fn write_synced_entry(output: &mut Vec<u8>, original_len: u64, frame: &[u8]) {
+    let check = if frame.is_empty() {
+        0
+    } else {
+        synced_block_check(original_len, frame)
+    };
+    output.extend_from_slice(&SYNC_MARKER);
+    output.extend_from_slice(&original_len.to_le_bytes());
+    output.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+    output.extend_from_slice(&check.to_le_bytes());
+    output.extend_from_slice(frame);
+}
+
+/// Хеш записи: исходная длина, длина кадра и сам кадр
+fn synced_block_check(original_len: u64, frame: &[u8]) -> u64 {
+    let mut hasher = RuneHasher::conjure_new();
+    hasher.absorb(&original_len.to_le_bytes());
+    hasher.absorb(&(frame.len() as u32).to_le_bytes());
+    hasher.absorb(frame);
+    hasher.finish()
+}
+
+/// Читает запись с маркером и ее кадр; `None` — завершающая запись
+///
+/// Запись без маркера или с неверным хешем — [`WizardError::CorruptBlockTable`].
+pub(crate) fn read_synced_entry<'a>(
+    cursor: &mut ScrollCursor<'a>,
+) -> WizardResult<Option<BlockRef<'a>>> {
+    if cursor.read_slice(SYNC_MARKER.len())? != SYNC_MARKER {
+        return Err(WizardError::CorruptBlockTable("нет маркера синхронизации"));
+    }
+    let original_len = cursor.read_u64()?;
+    let frame_len = cursor.read_u32()? as usize;
+    let check = cursor.read_u64()?;
+    if frame_len == 0 {
+        check_stream_end(original_len)?;
+        return Ok(None);
+    }
+
+    let frame = cursor.read_slice(frame_len)?;
+    if !synced_block_matches(original_len, frame, check) {
+        return Err(WizardError::CorruptBlockTable("хеш блока не совпадает"));
+    }
+    Ok(Some(BlockRef {
+        original_len,
+        frame,
+    }))
+}
+
+/// Сверяет хеш записи с кадром
+///
+/// Кадр с четностью сверяется и после исправления: порчу, которую
+/// исправит распаковка кадра, хеш блока не считает потерей.
+pub(crate) fn synced_block_matches(original_len: u64, frame: &[u8], check: u64) -> bool {
+    synced_block_check(original_len, frame) == check
+        || matches!(restore_parity(frame), Cow::Owned(repaired)
+            if synced_block_check(original_len, &repaired) == check)
+}
+
+/// Позиция следующего маркера синхронизации в `bytes` начиная с `from`
+pub(crate) fn find_sync_marker(bytes: &[u8], from: usize) -> Option<usize> {
+    bytes
+        .get(from..)?
+        .windows(SYNC_MARKER.len())
+        .position(|window| window == SYNC_MARKER)
+        .map(|offset| from + offset)
+}
+
+/// Разбирает реестр блоков архива без копирования кадров 🔍
+///
+/// Суммарная исходная длина проверяется лимитом `max_output_bytes`
+/// до распаковки первого блока.
+pub fn parse_block_ledger<'a>(
+    archive_bytes: &'a [u8],
+    options: &DecodeOptions,
+) -> WizardResult<Vec<BlockRef<'a>>> {
+    let (header, body_offset) = ArchiveHeader::try_parse(archive_bytes)?;
+    read_block_ledger(&header, &archive_bytes[body_offset..], options)
+        .map_err(|error| error.shifted(body_offset))
+}
+
+/// Разбирает тело архива с флагом блоков
+pub(crate) fn read_block_ledger<'a>(
+    header: &ArchiveHeader,
+    body: &'a [u8],
+    options: &DecodeOptions,
+) -> WizardResult<Vec<BlockRef<'a>>> {
+    if header.format_version > FORMAT_VERSION {
+        return Err(WizardError::UnsupportedVersion(header.format_version));
+    }
+    if !header.block_framed {
+        return Err(WizardError::CorruptBlockTable("архив не разбит на блоки"));
+    }
+    if header.dictionary_id.is_some() || header.alphabet_map.is_some() {
+        return Err(WizardError::CorruptBlockTable(
+            "параметры кадра в заголовке реестра",
+        ));
+    }
+
+    let mut cursor = ScrollCursor::new(body, 0);
+    read_ledger_entries(&mut cursor, options)
+        // Курсор стоит на записи, которую не удалось прочитать
+        .map_err(|error| error.located(DecodePhase::Table, cursor.position()))
+}
+
+/// Читает записи реестра любой раскладки
+fn read_ledger_entries<'a>(
+    cursor: &mut ScrollCursor<'a>,
+    options: &DecodeOptions,
+) -> WizardResult<Vec<BlockRef<'a>>> {
+    let block_count = cursor.read_u32()?;
+    if block_count == STREAMED_BLOCK_COUNT {
+        return read_streamed_blocks(cursor, options);
+    }
+    if block_count == SYNCED_BLOCK_COUNT {
+        return read_synced_blocks(cursor, options);
+    }
+    let block_count = block_count as usize;
+    let mut entries = Vec::with_capacity(block_count.min(cursor.remaining() / BLOCK_ENTRY_LEN));
+    let mut total_len = 0u64;
+    for _ in 0..block_count {
+        let original_len = cursor.read_u64()?;
+        let frame_len = cursor.read_u32()? as usize;
+        total_len = total_len
+            .checked_add(original_len)
+            .ok_or(WizardError::CorruptBlockTable(
+                "переполнение суммарной длины",
+            ))?;
+        entries.push((original_len, frame_len));
+    }
+    options.check_output(total_len)?;
+
+    let blocks = entries
+        .into_iter()
+        .map(|(original_len, frame_len)| {
+            Ok(BlockRef {
+                original_len,
+                frame: cursor.read_slice(frame_len)?,
+            })
+        })
+        .collect::<WizardResult<Vec<_>>>()?;
+
+    if cursor.remaining() != 0 {
+        return Err(WizardError::CorruptBlockTable(
+            "байты после последнего блока",
+        ));
+    }
+    Ok(blocks)
+}
+
+/// Разбирает потоковую запись: записи реестра чередуются с кадрами
+fn read_streamed_blocks<'a>(
+    cursor: &mut ScrollCursor<'a>,
+    options: &DecodeOptions,
+) -> WizardResult<Vec<BlockRef<'a>>> {
+    let mut blocks = Vec::new();
+    let mut total_len = 0u64;
+    loop {
+        let original_len = cursor.read_u64()?;
+        let frame_len = cursor.read_u32()? as usize;
+        if frame_len == 0 {
+            check_stream_end(original_len)?;
+            break;
+        }
+        total_len = total_len
+            .checked_add(original_len)
+            .ok_or(WizardError::CorruptBlockTable(
+                "переполнение суммарной длины",
+            ))?;
+        options.check_output(total_len)?;
+        blocks.push(BlockRef {
+            original_len,
+            frame: cursor.read_slice(frame_len)?,
+        });
+    }
+
+    if cursor.remaining() != 0 {
+        return Err(WizardError::CorruptBlockTable(
+            "байты после последнего блока",
+        ));
+    }
+    Ok(blocks)
+}
+
+/// Разбирает раскладку с маркерами синхронизации
+fn read_synced_blocks<'a>(
+    cursor: &mut ScrollCursor<'a>,
+    options: &DecodeOptions,
+) -> WizardResult<Vec<BlockRef<'a>>> {
+    let mut blocks = Vec::new();
+    let mut total_len = 0u64;
+    while let Some(block) = read_synced_entry(cursor)? {
+        total_len =
+            total_len
+                .checked_add(block.original_len)
+                .ok_or(WizardError::CorruptBlockTable(
+                    "переполнение суммарной длины",
+                ))?;
+        options.check_output(total_len)?;
+        blocks.push(block);
+    }
+
+    if cursor.remaining() != 0 {
+        return Err(WizardError::CorruptBlockTable(
+            "байты после последнего блока",
+        ));
+    }
+    Ok(blocks)
+}
+
+/// Проверяет завершающую запись потока (нулевая длина кадра)
+pub(crate) fn check_stream_end(original_len: u64) -> WizardResult<()> {
+    if original_len != 0 {
+        return Err(WizardError::CorruptBlockTable("блок без кадра"));
+    }
+    Ok(())
+}
+
+/// Тесты реестра блоков 🎯
+#[cfg(test)]
+mod block_ledger_tests {
+    use super::*;
+    use crate::error::LimitKind;
+
+    #[test]
+    fn test_block_ledger_roundtrip() {
+        let blocks = vec![
+            (5, b"first".to_vec()),
+            (0, Vec::new()),
+            (9, b"third".to_vec()),
+        ];
+        let archive = write_block_archive(&blocks);
+
+        let parsed = parse_block_ledger(&archive, &DecodeOptions::default()).unwrap();
+        assert_eq!(parsed.len(), 3);
+        for (block, (original_len, frame)) in parsed.iter().zip(&blocks) {
+            assert_eq!(block.original_len, *original_len);
+            assert_eq!(block.frame, &frame[..]);
+        }
+
+        let empty = write_block_archive(&[]);
+        assert!(parse_block_ledger(&empty, &DecodeOptions::default())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_inconsistent_ledger_is_rejected() {
+        let archive = write_block_archive(&[(3, b"abc".to_vec())]);
+        let options = DecodeOptions::default();
+
+        let truncated = &archive[..archive.len() - 1];
+        assert!(matches!(
+            parse_block_ledger(truncated, &options).map_err(|error| error.root_cause().clone()),
+            Err(WizardError::Truncated { .. })
+        ));
+
+        let mut trailing = archive.clone();
+        trailing.push(0);
+        let error = parse_block_ledger(&trailing, &options).unwrap_err();
+        assert!(matches!(
+            error.root_cause(),
+            WizardError::CorruptBlockTable(_)
+        ));
+        assert_eq!(error.location().unwrap().offset, archive.len());
+
+        assert!(matches!(
+            parse_block_ledger(&archive, &options.clone().with_max_output_bytes(2)),
+            Err(WizardError::LimitExceeded {
+                kind: LimitKind::OutputBytes,
+                ..
+            })
+        ));
+
+        let synced = write_synced_block_archive(&[(3, b"abc".to_vec()), (2, b"de".to_vec())]);
+        let parsed = parse_block_ledger(&synced, &options).unwrap();
+        assert_eq!(parsed[1].frame, b"de");
+        let mut damaged = synced.clone();
+        let frame_offset = find_sync_marker(&synced, 0).unwrap() + SYNCED_ENTRY_LEN;
+        damaged[frame_offset] ^= 1;
+        assert_eq!(
+            parse_block_ledger(&damaged, &options).map_err(|error| error.root_cause().clone()),
+            Err(WizardError::CorruptBlockTable("хеш блока не совпадает"))
+        );
+
+        let mut single_frame = Vec::new();
+        ArchiveHeader::current(ModelKind::StaticOrder0, None).write_to(&mut single_frame);
+        single_frame.extend_from_slice(&[0; 4]);
+        assert_eq!(
+            parse_block_ledger(&single_frame, &options),
+            Err(WizardError::CorruptBlockTable("архив не разбит на блоки"))
+        );
+    }
+}
@@ -0,0 +1,704 @@
+//! Потоковые архивы из блоков 🌊
+//!
+//! [`BlockStreamWriter`] сжимает данные по мере записи: как только набран
+//! блок, он сжимается и уходит во внутренний писатель. [`BlockStreamReader`]
+//! читает архив из блоков блок за блоком. В памяти одновременно находится
+//! не больше одного блока, поэтому так можно упаковать многогигабайтный
+//! поток (например, tar-архив каталога).
+//!
+//! Кадры блоков писатель сжимает как [`compress_data`] или, если задан
+//! [`EngineConfig`] ([`BlockStreamWriter::with_config`]), как
+//! [`try_compress_with_config`].
+//!
+//! Писатель создает потоковую раскладку реестра
+//! (см. [`block_ledger`](super::block_ledger)); читатель понимает все три.
+//! Результат — обычный архив: его распаковывает и
+//! [`try_decompress_data`](crate::simple_api::try_decompress_data).
+
+use super::artifact_scroll::check_length_field;
+use super::block_ledger::{
+    check_stream_end, synced_block_matches, BlockRef, BLOCK_ENTRY_LEN, STREAMED_BLOCK_COUNT,
+    SYNCED_BLOCK_COUNT, SYNC_MARKER,
+};
+use super::container_runes::{
+    ArchiveHeader, ModelKind, ARCHIVE_MAGIC, FLAG_METADATA, FORMAT_VERSION, SECTIONS_OFFSET,
+    SECTION_END, TLV_FORMAT_VERSION,
+};
+use crate::compression_engine::engine_covenant::EngineConfig;
+use crate::decompression_oracle::block_conductor::decode_block;
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::error::{WizardError, WizardResult};
+use crate::simple_api::{compress_data, try_compress_with_config, try_decompress_frame};
+use std::io::{self, Read, Write};
+
+/// Сжимающий писатель архива из блоков 🌊
+///
+/// Архив завершает [`BlockStreamWriter::finish`]; без него последний
+/// блок и завершающая запись не попадут в поток.
+pub struct BlockStreamWriter<W: Write> {
+    inner: W,
+    block_size: usize,
+    pending_block: Vec<u8>,
+    config: Option<EngineConfig>,
+}
+
+impl<W: Write> BlockStreamWriter<W> {
+    /// Начинает архив: сразу пишет заголовок
+    ///
+    /// Паникует при нулевом `block_size`.
+    pub fn new(inner: W, block_size: usize) -> io::Result<Self> {
+        Self::start(inner, block_size, None)
+    }
+
+    /// Начинает архив, блоки которого сжимаются с параметрами `config` ⚙️
+    ///
+    /// Ошибка сжатия блока приходит как [`io::ErrorKind::InvalidInput`]
+    /// с [`WizardError`] внутри. Паникует при нулевом `block_size`.
+    pub fn with_config(inner: W, block_size: usize, config: EngineConfig) -> io::Result<Self> {
+        Self::start(inner, block_size, Some(config))
+    }
+
+    /// Пишет заголовок и реестр потоковой раскладки
+    fn start(mut inner: W, block_size: usize, config: Option<EngineConfig>) -> io::Result<Self> {
+        assert!(block_size > 0, "размер блока должен быть положительным");
+
+        let mut preamble = Vec::new();
+        ArchiveHeader::current(ModelKind::StaticOrder0, None)
+            .with_block_framing()
+            .write_to(&mut preamble);
+        preamble.extend_from_slice(&STREAMED_BLOCK_COUNT.to_le_bytes());
+        inner.write_all(&preamble)?;
+
+        Ok(Self {
+            inner,
+            block_size,
+            pending_block: Vec::with_capacity(block_size),
+            config,
+        })
+    }
+
+    /// Дописывает последний блок и завершающую запись, возвращает писатель
+    pub fn finish(mut self) -> io::Result<W> {
+        if !self.pending_block.is_empty() {
+            self.write_pending_block()?;
+        }
+        self.inner.write_all(&[0; BLOCK_ENTRY_LEN])?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    /// Сжимает набранный блок и отправляет его в поток
+    fn write_pending_block(&mut self) -> io::Result<()> {
+        let frame = match &self.config {
+            Some(config) => {
+                try_compress_with_config(&self.pending_block, config).map_err(compression_fault)?
+            }
+            None => compress_data(&self.pending_block),
+        };
+        check_length_field(frame.len()).map_err(compression_fault)?;
+        let frame_len = frame.len() as u32;
+
+        self.inner
+            .write_all(&(self.pending_block.len() as u64).to_le_bytes())?;
+        self.inner.write_all(&frame_len.to_le_bytes())?;
+        self.inner.write_all(&frame)?;
+        self.pending_block.clear();
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for BlockStreamWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let accepted = buf.len().min(self.block_size - self.pending_block.len());
+        self.pending_block.extend_from_slice(&buf[..accepted]);
+        if self.pending_block.len() == self.block_size {
+            self.write_pending_block()?;
+        }
+        Ok(accepted)
+    }
+
+    /// Сбрасывает внутренний писатель; неполный блок остается в буфере
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Ошибка сжатия блока в виде ошибки записи
+fn compression_fault(error: WizardError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, error)
+}
+
+/// Ошибка сжатия из ошибки писателя, пишущего в память
+///
+/// Запись в `Vec<u8>` не отказывает, поэтому такой писатель возвращает
+/// только ошибки сжатия блоков.
+pub(crate) fn into_compression_error(error: io::Error) -> WizardError {
+    error
+        .into_inner()
+        .and_then(|inner| inner.downcast::<WizardError>().ok())
+        .map(|error| *error)
+        .expect("писатель в память отказывает только ошибками сжатия")
+}
+
+/// Сколько байтов читатель запрашивает у внутреннего читателя за раз
+const INPUT_CHUNK: usize = 64 * 1024;
+
+/// Реестр читаемого архива
+enum LedgerLayout {
+    /// Записи прочитаны заранее, осталось столько-то кадров
+    Counted(std::vec::IntoIter<(u64, usize)>),
+    /// Запись идет перед каждым кадром
+    Streamed,
+    /// Запись с маркером и хешем идет перед каждым кадром
+    Synced,
+}
+
+/// Запись реестра очередного блока
+#[derive(Clone, Copy)]
+struct BlockEntry {
+    /// Исходная длина блока
+    original_len: u64,
+    /// Длина кадра
+    frame_len: usize,
+    /// Хеш блока синхронизированной раскладки
+    check: Option<u64>,
+    /// Длина записи перед кадром (0 — запись в реестре заранее)
+    entry_len: usize,
+}
+
+/// Распаковывающий читатель архива из блоков 🌊
+///
+/// Ошибки формата приходят как [`io::ErrorKind::InvalidData`] с
+/// [`WizardError`] внутри.
+///
+/// Читатель готов к частично загруженным архивам (HTTP range-запросы,
+/// неблокирующие сокеты): если внутренний читатель отвечает
+/// [`io::ErrorKind::WouldBlock`], `read` возвращает ту же ошибку —
+/// «нужно больше данных», — а уже полученные байты остаются во
+/// внутреннем буфере. Когда данные пришли, `read` продолжает с того же
+/// места. Буфер не длиннее одной записи реестра с кадром: у внутреннего
+/// читателя запрашивается ровно столько, сколько нужно следующей части.
+pub struct BlockStreamReader<R: Read> {
+    inner: R,
+    options: DecodeOptions,
+    /// Реестр; `None` — заголовок и реестр еще не прочитаны
+    layout: Option<LedgerLayout>,
+    /// Полученные, но еще не разобранные байты архива
+    input: Vec<u8>,
+    restored_total: u64,
+    current_block: Vec<u8>,
+    position: usize,
+    finished: bool,
+}
+
+impl<R: Read> BlockStreamReader<R> {
+    /// Читает заголовок и реестр архива из блоков
+    ///
+    /// Суммарный размер проверяется лимитом `options.max_output_bytes`
+    /// по мере чтения, размер каждого блока — записью реестра.
+    pub fn new(inner: R, options: DecodeOptions) -> io::Result<Self> {
+        let mut reader = Self::progressive(inner, options);
+        reader.read_preamble()?;
+        Ok(reader)
+    }
+
+    /// Читатель для архива, который еще загружается 📶
+    ///
+    /// В отличие от [`BlockStreamReader::new`], ничего не читает сразу:
+    /// заголовок и реестр разбираются первым `read`, поэтому
+    /// [`io::ErrorKind::WouldBlock`] внутреннего читателя не теряет данных
+    /// и на этом шаге.
+    pub fn progressive(inner: R, options: DecodeOptions) -> Self {
+        Self {
+            inner,
+            options,
+            layout: None,
+            input: Vec::new(),
+            restored_total: 0,
+            current_block: Vec::new(),
+            position: 0,
+            finished: false,
+        }
+    }
+
+    /// Возвращает внутренний читатель
+    ///
+    /// Байты, полученные для незаконченной записи, остаются в буфере
+    /// читателя и пропадают.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Дочитывает внутренний читатель, пока в буфере меньше `needed` байтов
+    ///
+    /// `WouldBlock` возвращается как есть, прочитанное остается в буфере.
+    /// Конец потока — [`WizardError::Truncated`].
+    fn fill_to(&mut self, needed: usize) -> io::Result<()> {
+        while self.input.len() < needed {
+            let filled = self.input.len();
+            let wanted = (needed - filled).min(INPUT_CHUNK);
+            self.input.resize(filled + wanted, 0);
+            let read = self.inner.read(&mut self.input[filled..]);
+            self.input.truncate(filled + *read.as_ref().unwrap_or(&0));
+            match read {
+                Ok(0) => {
+                    return Err(WizardError::Truncated {
+                        needed,
+                        available: filled,
+                    }
+                    .into())
+                }
+                Ok(_) => {}
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(())
+    }
+
+    /// Разбирает заголовок и реестр, когда они получены целиком
+    ///
+    /// Буфер освобождается только после разбора, поэтому после
+    /// `WouldBlock` разбор начинается заново с тех же байтов.
+    fn read_preamble(&mut self) -> io::Result<()> {
+        let header_len = loop {
+            match stream_header_len(&self.input)? {
+                Ok(header_len) => break header_len,
+                Err(needed) => self.fill_to(needed)?,
+            }
+        };
+        let (header, _) = ArchiveHeader::parse(&self.input[..header_len])
+            .ok_or(WizardError::UnrecognizedHeader)?;
+        if header.format_version > FORMAT_VERSION {
+            return Err(WizardError::UnsupportedVersion(header.format_version).into());
+        }
+        if !header.block_framed {
+            return Err(WizardError::CorruptBlockTable("архив не разбит на блоки").into());
+        }
+
+        self.fill_to(header_len + 4)?;
+        let block_count = le_u32(&self.input[header_len..]);
+        let mut ledger_end = header_len + 4;
+        let layout =
+            if block_count == STREAMED_BLOCK_COUNT {
+                LedgerLayout::Streamed
+            } else if block_count == SYNCED_BLOCK_COUNT {
+                LedgerLayout::Synced
+            } else {
+                let mut entries = Vec::new();
+                let mut total_len = 0u64;
+                for _ in 0..block_count {
+                    self.fill_to(ledger_end + BLOCK_ENTRY_LEN)?;
+                    let original_len = le_u64(&self.input[ledger_end..]);
+                    let frame_len = le_u32(&self.input[ledger_end + 8..]) as usize;
+                    total_len = total_len.checked_add(original_len).ok_or(
+                        WizardError::CorruptBlockTable("переполнение суммарной длины"),
+                    )?;
+                    self.options.check_output(total_len)?;
+                    entries.push((original_len, frame_len));
+                    ledger_end += BLOCK_ENTRY_LEN;
+                }
+                LedgerLayout::Counted(entries.into_iter())
+            };
+
+        self.input.drain(..ledger_end);
+        self.layout = Some(layout);
+        Ok(())
+    }
+
+    /// Запись потокового реестра перед кадром
+    ///
+    /// `None` — завершающая запись (она снимается с буфера).
+    fn read_inline_entry(&mut self, synced: bool) -> io::Result<Option<BlockEntry>> {
+        let marker_len = if synced { SYNC_MARKER.len() } else { 0 };
+        let entry_len = marker_len + BLOCK_ENTRY_LEN + if synced { 8 } else { 0 };
+        self.fill_to(entry_len)?;
+
+        if self.input[..marker_len] != SYNC_MARKER[..marker_len] {
+            return Err(WizardError::CorruptBlockTable("нет маркера синхронизации").into());
+        }
+        let original_len = le_u64(&self.input[marker_len..]);
+        let frame_len = le_u32(&self.input[marker_len + 8..]) as usize;
+        let check = synced.then(|| le_u64(&self.input[marker_len + BLOCK_ENTRY_LEN..]));
+        if frame_len == 0 {
+            check_stream_end(original_len)?;
+            self.input.drain(..entry_len);
+            return Ok(None);
+        }
+        Ok(Some(BlockEntry {
+            original_len,
+            frame_len,
+            check,
+            entry_len,
+        }))
+    }
+
+    /// Распаковывает следующий блок; `false` — архив закончился
+    ///
+    /// Запись и кадр снимаются с буфера только после распаковки блока.
+    fn load_next_block(&mut self) -> io::Result<bool> {
+        if self.layout.is_none() {
+            self.read_preamble()?;
+        }
+
+        let entry = match &self.layout {
+            Some(LedgerLayout::Counted(entries)) => {
+                entries
+                    .as_slice()
+                    .first()
+                    .map(|&(original_len, frame_len)| BlockEntry {
+                        original_len,
+                        frame_len,
+                        check: None,
+                        entry_len: 0,
+                    })
+            }
+            layout => self.read_inline_entry(matches!(layout, Some(LedgerLayout::Synced)))?,
+        };
+        let Some(BlockEntry {
+            original_len,
+            frame_len,
+            check,
+            entry_len,
+        }) = entry
+        else {
+            self.finished = true;
+            return Ok(false);
+        };
+
+        let restored_total =
+            self.restored_total
+                .checked_add(original_len)
+                .ok_or(WizardError::CorruptBlockTable(
+                    "переполнение суммарной длины",
+                ))?;
+        self.options.check_output(restored_total)?;
+
+        self.fill_to(entry_len + frame_len)?;
+        let frame = &self.input[entry_len..entry_len + frame_len];
+        if check.is_some_and(|check| !synced_block_matches(original_len, frame, check)) {
+            return Err(WizardError::CorruptBlockTable("хеш блока не совпадает").into());
+        }
+        let block = BlockRef {
+            original_len,
+            frame,
+        };
+        self.current_block = decode_block(&block, &self.options, &try_decompress_frame)?;
+        self.input.drain(..entry_len + frame_len);
+        if let Some(LedgerLayout::Counted(entries)) = &mut self.layout {
+            entries.next();
+        }
+        self.restored_total = restored_total;
+        self.options.report_progress(restored_total);
+        self.position = 0;
+        Ok(true)
+    }
+}
+
+impl<R: Read> Read for BlockStreamReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.current_block.len() {
+            if self.finished || !self.load_next_block()? {
+                return Ok(0);
+            }
+        }
+
+        let available = &self.current_block[self.position..];
+        let copied = available.len().min(buf.len());
+        buf[..copied].copy_from_slice(&available[..copied]);
+        self.position += copied;
+        Ok(copied)
+    }
+}
+
+/// Длина заголовка архива из блоков: фиксированная часть и метаданные
+///
+/// `Ok(Err(n))` — для ответа нужно хотя бы `n` байтов. Параметров кадра
+/// (словарь, атлас) у архива из блоков нет. С версии 6 проходятся все
+/// разделы до завершающего, включая незнакомые.
+fn stream_header_len(bytes: &[u8]) -> WizardResult<Result<usize, usize>> {
+    if bytes.len() < SECTIONS_OFFSET {
+        return Ok(Err(SECTIONS_OFFSET));
+    }
+    if !bytes.starts_with(&ARCHIVE_MAGIC) {
+        return Err(WizardError::UnrecognizedHeader);
+    }
+
+    let mut cursor = SECTIONS_OFFSET;
+    let section_len = |cursor: usize| match bytes.get(cursor..cursor + 4) {
+        Some(len_bytes) => Ok(le_u32(len_bytes) as usize),
+        None => Err(cursor + 4),
+    };
+    let header_len = if bytes[ARCHIVE_MAGIC.len()] >= TLV_FORMAT_VERSION {
+        loop {
+            let Some(&section_kind) = bytes.get(cursor) else {
+                return Ok(Err(cursor + 1));
+            };
+            cursor += 1;
+            if section_kind == SECTION_END {
+                break cursor;
+            }
+            match section_len(cursor) {
+                Ok(len) => cursor += 4 + len,
+                Err(needed) => return Ok(Err(needed)),
+            }
+        }
+    } else {
+        let Some(&flags) = bytes.get(cursor) else {
+            return Ok(Err(cursor + 1));
+        };
+        cursor += 1;
+        if flags & FLAG_METADATA != 0 {
+            match section_len(cursor) {
+                Ok(len) => cursor += 4 + len,
+                Err(needed) => return Ok(Err(needed)),
+            }
+        }
+        cursor
+    };
+
+    if bytes.len() < header_len {
+        return Ok(Err(header_len));
+    }
+    Ok(Ok(header_len))
+}
+
+fn le_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes(bytes[..4].try_into().expect("четыре байта"))
+}
+
+fn le_u64(bytes: &[u8]) -> u64 {
+    u64::from_le_bytes(bytes[..8].try_into().expect("восемь байтов"))
+}
+
+/// Тесты потоковых архивов 🎯
+#[cfg(test)]
+mod block_stream_tests {
+    use super::*;
+    use crate::format::container_runes::EngineKind;
+    use crate::format::metadata_runes::attach_metadata;
+    use crate::simple_api::{
+        compress_data_in_blocks, compress_data_in_synced_blocks, try_decompress_data,
+    };
+    use std::collections::HashMap;
+
+    fn sample() -> Vec<u8> {
+        "the owl carries scrolls between towers. "
+            .repeat(40)
+            .into_bytes()
+    }
+
+    fn stream_compress(data: &[u8], block_size: usize) -> Vec<u8> {
+        let mut writer = BlockStreamWriter::new(Vec::new(), block_size).unwrap();
+        // Запись кусками, не совпадающими с границами блоков
+        for piece in data.chunks(77) {
+            writer.write_all(piece).unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    fn stream_decompress(archive: &[u8], options: DecodeOptions) -> io::Result<Vec<u8>> {
+        let mut restored = Vec::new();
+        BlockStreamReader::new(archive, options)?.read_to_end(&mut restored)?;
+        Ok(restored)
+    }
+
+    #[test]
+    fn test_stream_roundtrip_in_both_layouts() {
+        let data = sample();
+        let streamed = stream_compress(&data, 500);
+        assert_eq!(
+            try_decompress_data(&streamed, &DecodeOptions::default()).unwrap(),
+            data
+        );
+        assert_eq!(
+            stream_decompress(&streamed, DecodeOptions::default()).unwrap(),
+            data
+        );
+
+        let counted = compress_data_in_blocks(&data, 500);
+        assert_eq!(
+            stream_decompress(&counted, DecodeOptions::default()).unwrap(),
+            data
+        );
+
+        let synced = compress_data_in_synced_blocks(&data, 500);
+        assert_eq!(
+            stream_decompress(&synced, DecodeOptions::default()).unwrap(),
+            data
+        );
+        assert_eq!(
+            try_decompress_data(&synced, &DecodeOptions::default()).unwrap(),
+            data
+        );
+
+        let tagged = attach_metadata(
+            &streamed,
+            &HashMap::from([("name".to_string(), "scrolls.txt".to_string())]),
+        )
+        .unwrap();
+        assert_eq!(
+            stream_decompress(&tagged, DecodeOptions::default()).unwrap(),
+            data
+        );
+
+        let empty = stream_compress(&[], 500);
+        assert!(stream_decompress(&empty, DecodeOptions::default())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_damaged_stream_is_invalid_data() {
+        let data = sample();
+        let streamed = stream_compress(&data, 500);
+
+        let truncated = &streamed[..streamed.len() - BLOCK_ENTRY_LEN];
+        let error = stream_decompress(truncated, DecodeOptions::default()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(matches!(
+            try_decompress_data(truncated, &DecodeOptions::default())
+                .map_err(|error| error.root_cause().clone()),
+            Err(WizardError::Truncated { .. })
+        ));
+
+        let limited = DecodeOptions::default().with_max_output_bytes(600);
+        let error = stream_decompress(&streamed, limited).unwrap_err();
+        assert!(matches!(
+            error.get_ref().and_then(|inner| inner.downcast_ref()),
+            Some(WizardError::LimitExceeded { .. })
+        ));
+
+        let error = stream_decompress(&compress_data(&data), DecodeOptions::default()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    /// Источник, которому байты архива приходят частями 📶
+    struct Trickle<'a> {
+        archive: &'a [u8],
+        arrived: usize,
+        position: usize,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.position == self.archive.len() {
+                return Ok(0);
+            }
+            if self.position == self.arrived {
+                return Err(io::ErrorKind::WouldBlock.into());
+            }
+            let copied = buf.len().min(self.arrived - self.position);
+            buf[..copied].copy_from_slice(&self.archive[self.position..self.position + copied]);
+            self.position += copied;
+            Ok(copied)
+        }
+    }
+
+    /// Распаковка по мере прихода кусков по `step` байтов
+    ///
+    /// Возвращает результат и сколько байтов было распаковано к моменту,
+    /// когда пришла половина архива.
+    fn progressive_decompress(archive: &[u8], step: usize) -> (Vec<u8>, usize) {
+        let source = Trickle {
+            archive,
+            arrived: 0,
+            position: 0,
+        };
+        let mut reader = BlockStreamReader::progressive(source, DecodeOptions::default());
+        let (mut restored, mut at_half) = (Vec::new(), 0);
+        let mut buf = [0; 256];
+        loop {
+            match reader.read(&mut buf) {
+                Ok(0) => return (restored, at_half),
+                Ok(read) => restored.extend_from_slice(&buf[..read]),
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => {
+                    let source = &mut reader.inner;
+                    if source.arrived <= archive.len() / 2 {
+                        at_half = restored.len();
+                    }
+                    source.arrived = (source.arrived + step).min(archive.len());
+                }
+                Err(error) => panic!("распаковка прервалась: {error}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_progressive_decode_pauses_for_data() {
+        let data = sample();
+        for archive in [
+            stream_compress(&data, 300),
+            compress_data_in_blocks(&data, 300),
+            compress_data_in_synced_blocks(&data, 300),
+        ] {
+            for step in [1, 7, 500] {
+                let (restored, at_half) = progressive_decompress(&archive, step);
+                assert_eq!(restored, data);
+                // Первые блоки отданы до того, как архив загружен целиком
+                assert!(at_half > 0 && at_half < data.len());
+            }
+        }
+
+        // Оборванный архив по-прежнему — Truncated, а не вечное ожидание
+        let streamed = stream_compress(&data, 300);
+        let mut reader = BlockStreamReader::progressive(
+            &streamed[..streamed.len() / 2],
+            DecodeOptions::default(),
+        );
+        let error = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        assert!(matches!(
+            error.get_ref().and_then(|inner| inner.downcast_ref()),
+            Some(WizardError::Truncated { .. })
+        ));
+    }
+
+    #[test]
+    fn test_writer_with_config_uses_engine() {
+        let data = sample();
+        let mut writer = BlockStreamWriter::with_config(
+            Vec::new(),
+            500,
+            EngineConfig::default().with_engine(EngineKind::Stored),
+        )
+        .unwrap();
+        writer.write_all(&data).unwrap();
+        let stored = writer.finish().unwrap();
+        assert!(stored.len() > data.len());
+        assert_eq!(
+            stream_decompress(&stored, DecodeOptions::default()).unwrap(),
+            data
+        );
+
+        let mut writer = BlockStreamWriter::with_config(
+            Vec::new(),
+            500,
+            EngineConfig::default().with_engine(EngineKind::Custom(200)),
+        )
+        .unwrap();
+        let error = writer.write_all(&data).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(
+            into_compression_error(error),
+            WizardError::UnsupportedEngine(200)
+        );
+    }
+    #[test]
+    fn test_writer_stores_blocks_after_deadline() {
+        let data = sample().repeat(5);
+        let mut writer = BlockStreamWriter::with_config(
+            Vec::new(),
+            4000,
+            EngineConfig::default().with_deadline(std::time::Instant::now()),
+        )
+        .unwrap();
+        writer.write_all(&data).unwrap();
+        let late = writer.finish().unwrap();
+        assert!(late.len() > data.len());
+        assert_eq!(
+            stream_decompress(&late, DecodeOptions::default()).unwrap(),
+            data
+        );
+        assert!(late.len() > stream_compress(&data, 4000).len());
+    }
+}
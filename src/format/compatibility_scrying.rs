@@ -0,0 +1,142 @@
+//! Проверка совместимости архивов 🔭
+//!
+//! Сравнивает заголовки двух архивов и определяет, может ли одна
+//! конфигурация декодера прочитать оба. Полезно, когда архивы создаются
+//! разными версиями библиотеки.
+
+use super::container_runes::{ArchiveHeader, FORMAT_VERSION};
+
+/// Сторона сравнения
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveSide {
+    /// Первый (старый) архив
+    Old,
+    /// Второй (новый) архив
+    New,
+}
+
+/// Причина несовместимости ⚠️
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Incompatibility {
+    /// Заголовок не распознан
+    UnrecognizedArchive(ArchiveSide),
+    /// Версия формата новее поддерживаемой
+    UnsupportedVersion(ArchiveSide, u8),
+    /// Разные движки сжатия
+    EngineMismatch,
+    /// Разные виды моделей
+    ModelMismatch,
+    /// Архивы ссылаются на разные внешние словари
+    DictionaryMismatch(u64, u64),
+}
+
+/// Отчет о совместимости двух архивов 📋
+#[derive(Debug, Clone)]
+pub struct CompatibilityReport {
+    /// Заголовок первого архива
+    pub old_header: Option<ArchiveHeader>,
+    /// Заголовок второго архива
+    pub new_header: Option<ArchiveHeader>,
+    /// Найденные несовместимости
+    pub incompatibilities: Vec<Incompatibility>,
+}
+
+impl CompatibilityReport {
+    /// Может ли одна конфигурация декодера прочитать оба архива
+    pub fn single_decoder_suffices(&self) -> bool {
+        self.incompatibilities.is_empty()
+    }
+}
+
+/// Сравнивает заголовки двух архивов
+pub fn compatibility_report(
+    old_bytes: impl AsRef<[u8]>,
+    new_bytes: impl AsRef<[u8]>,
+) -> CompatibilityReport {
+    let old_header = ArchiveHeader::parse(old_bytes.as_ref()).map(|(header, _)| header);
+    let new_header = ArchiveHeader::parse(new_bytes.as_ref()).map(|(header, _)| header);
+    let mut incompatibilities = Vec::new();
+
+    for (side, header) in [
+        (ArchiveSide::Old, old_header),
+        (ArchiveSide::New, new_header),
+    ] {
+        match header {
+            None => incompatibilities.push(Incompatibility::UnrecognizedArchive(side)),
+            Some(header) if header.format_version > FORMAT_VERSION => incompatibilities.push(
+                Incompatibility::UnsupportedVersion(side, header.format_version),
+            ),
+            Some(_) => {}
+        }
+    }
+
+    if let (Some(old), Some(new)) = (old_header, new_header) {
+        if old.engine != new.engine {
+            incompatibilities.push(Incompatibility::EngineMismatch);
+        }
+        if old.model_kind != new.model_kind {
+            incompatibilities.push(Incompatibility::ModelMismatch);
+        }
+        if let (Some(old_id), Some(new_id)) = (old.dictionary_id, new.dictionary_id) {
+            if old_id != new_id {
+                incompatibilities.push(Incompatibility::DictionaryMismatch(old_id, new_id));
+            }
+        }
+    }
+
+    CompatibilityReport {
+        old_header,
+        new_header,
+        incompatibilities,
+    }
+}
+
+/// Тесты проверки совместимости 🎯
+#[cfg(test)]
+mod compatibility_scrying_tests {
+    use super::*;
+    use crate::compression_engine::CompressorHandle;
+    use crate::simple_api::compress_data;
+
+    #[test]
+    fn test_same_configuration_is_compatible() {
+        let first = compress_data(b"first archive");
+        let second = compress_data(b"second archive with other text");
+
+        let report = compatibility_report(&first, &second);
+        assert!(report.single_decoder_suffices());
+        assert_eq!(report.old_header.unwrap().format_version, FORMAT_VERSION);
+    }
+
+    #[test]
+    fn test_different_models_and_dictionaries_are_reported() {
+        let plain = compress_data(b"plain archive");
+        let first = CompressorHandle::train(b"alpha beta gamma alpha beta gamma alpha beta");
+        let second = CompressorHandle::train(b"delta epsilon delta epsilon delta epsilon");
+
+        let report = compatibility_report(&plain, first.compress(b"alpha").to_bytes());
+        assert!(report
+            .incompatibilities
+            .contains(&Incompatibility::ModelMismatch));
+
+        let first_bytes = first.compress(b"alpha").to_bytes();
+        let second_bytes = second.compress(b"delta").to_bytes();
+        let report = compatibility_report(&first_bytes, &second_bytes);
+        assert_eq!(
+            report.incompatibilities,
+            vec![Incompatibility::DictionaryMismatch(
+                first.dictionary_id(),
+                second.dictionary_id()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_unrecognized_archive() {
+        let report = compatibility_report(b"??", compress_data(b"ok"));
+        assert_eq!(
+            report.incompatibilities,
+            vec![Incompatibility::UnrecognizedArchive(ArchiveSide::Old)]
+        );
+    }
+}
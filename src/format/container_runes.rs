@@ -0,0 +1,819 @@
+//! Руны контейнера 📜
+//!
+//! Заголовок сериализованных архивов: сигнатура, версия формата,
+//! движок, вид модели и ссылка на внешний словарь.
+//!
+//! Раскладка заголовка (версия 6+):
+//! `[b"ACW"][версия u8][движок u8][модель u8]`, затем разделы
+//! `[тип u8][длина u32][значение]` и завершающий байт `0x00`.
+//! Раздел со сброшенным старшим битом типа необязателен: декодер, который
+//! его не знает, пропускает раздел по длине. Раздел с установленным битом
+//! меняет смысл тела, и незнакомый такой раздел отвергает архив. Так новые
+//! разделы (контрольная сумма, индекс блоков) добавляются без новой версии.
+//!
+//! | Тип    | Раздел                                   |
+//! |--------|------------------------------------------|
+//! | `0x01` | метаданные (пары ключ–значение)          |
+//! | `0x02` | контрольная сумма (зарезервирован)       |
+//! | `0x03` | статистика сжатия                        |
+//! | `0x81` | id внешнего словаря u64                  |
+//! | `0x82` | атлас алфавита, 32 байта                 |
+//! | `0x83` | тело — реестр блоков (пустое значение)   |
+//! | `0x84` | период полураспада адаптивной модели u32 |
+//!
+//! Раскладка версий 1–5:
+//! `[b"ACW"][версия u8][движок u8][модель u8][флаги u8][id словаря u64, если флаг]`
+//! `[атлас алфавита 32 байта, если флаг]`
+//!
+//! Архивы без сигнатуры считаются устаревшим форматом версии 0
+//! (позиционная сериализация без заголовка).
+//!
+//! Версии 0 и 1 закодированы 24-битным кодером, начиная с версии 2 — 32-битным.
+//! С версии 3 алфавит содержит символы «слово + пробел».
+//! С версии 4 неизвестные флаги отвергаются, а флаг атласа включает
+//! компактную таблицу частот (см. [`AlphabetMap`]).
+//! С версии 5 за словарем следует таблица фраз.
+//! С версии 6 байт флагов заменен разделами TLV.
+//!
+//! Флаг блоков (понятен декодерам с версии 5) и раздел `0x83` означают, что тело — реестр
+//! независимых кадров (см. [`block_ledger`](super::block_ledger)); старые
+//! декодеры отвергают такой архив как нераспознанный.
+//! Флаг метаданных (и раздел `0x01`) добавляет в заголовок раздел пользовательских пар
+//! ключ–значение (см. [`metadata_runes`](super::metadata_runes)); в
+//! [`ArchiveHeader`] он не хранится, разбор заголовка его пропускает.
+
+use super::alphabet_atlas::AlphabetMap;
+use super::artifact_scroll::check_length_field;
+use crate::bit_wizardry::bit_manipulation_spells::CoderPrecision;
+use crate::compression_engine::micro_spell::is_micro_archive;
+use crate::error::{DecodePhase, WizardError, WizardResult};
+
+/// Сигнатура архива
+pub const ARCHIVE_MAGIC: [u8; 3] = *b"ACW";
+
+/// Текущая версия формата контейнера
+pub const FORMAT_VERSION: u8 = 6;
+
+/// Первая версия формата с 32-битным кодером
+pub const PRECISION_32_FORMAT_VERSION: u8 = 2;
+
+/// Первая версия формата с символами «слово + пробел»
+///
+/// Старые декодеры молча пропустили бы такие символы, поэтому
+/// новые архивы помечаются версией, которую они отвергают.
+pub const SPACED_WORD_FORMAT_VERSION: u8 = 3;
+
+/// Первая версия формата с атласом алфавита
+pub const ALPHABET_MAP_FORMAT_VERSION: u8 = 4;
+
+/// Первая версия формата с таблицей фраз
+pub const PHRASE_FORMAT_VERSION: u8 = 5;
+
+/// Первая версия формата с разделами заголовка TLV
+pub const TLV_FORMAT_VERSION: u8 = 6;
+
+/// Версия устаревших архивов без заголовка
+pub const LEGACY_FORMAT_VERSION: u8 = 0;
+
+/// Флаг: архив ссылается на внешний словарь
+const FLAG_EXTERNAL_DICTIONARY: u8 = 0b0000_0001;
+
+/// Флаг: за заголовком следует атлас алфавита
+const FLAG_ALPHABET_MAP: u8 = 0b0000_0010;
+
+/// Флаг: тело архива — реестр независимых блоков
+const FLAG_BLOCK_FRAMED: u8 = 0b0000_0100;
+
+/// Флаг: заголовок завершается разделом метаданных
+pub(crate) const FLAG_METADATA: u8 = 0b0000_1000;
+
+/// Все флаги, понятные этой версии
+const KNOWN_FLAGS: u8 =
+    FLAG_EXTERNAL_DICTIONARY | FLAG_ALPHABET_MAP | FLAG_BLOCK_FRAMED | FLAG_METADATA;
+
+/// Смещение байта движка от начала архива
+const ENGINE_OFFSET: usize = ARCHIVE_MAGIC.len() + 1;
+
+/// Смещение байта модели от начала архива
+const MODEL_OFFSET: usize = ARCHIVE_MAGIC.len() + 2;
+
+/// Смещение байта флагов от начала архива (версии 1–5)
+pub(crate) const FLAGS_OFFSET: usize = ARCHIVE_MAGIC.len() + 3;
+
+/// Смещение первого раздела от начала архива (версия 6+)
+pub(crate) const SECTIONS_OFFSET: usize = ARCHIVE_MAGIC.len() + 3;
+
+/// Бит типа раздела: декодер, не знающий раздел, должен отвергнуть архив
+pub(crate) const SECTION_CRITICAL: u8 = 0b1000_0000;
+
+/// Раздел-терминатор: за ним начинается тело
+pub(crate) const SECTION_END: u8 = 0x00;
+
+/// Раздел пользовательских метаданных (необязательный)
+pub(crate) const SECTION_METADATA: u8 = 0x01;
+
+/// Раздел контрольной суммы данных (необязательный)
+///
+/// Тип зарезервирован: текущие писатели раздел не создают, а
+/// [`peek_header`](super::peek_header) сообщает о его наличии.
+pub(crate) const SECTION_CHECKSUM: u8 = 0x02;
+
+/// Раздел статистики сжатия (необязательный, см. [`ArchiveStats`](super::ArchiveStats))
+pub(crate) const SECTION_STATS: u8 = 0x03;
+
+/// Раздел четности заголовка и модели (необязательный, всегда первый,
+/// см. [`attach_parity`](super::attach_parity))
+pub(crate) const SECTION_PARITY: u8 = 0x04;
+
+/// Раздел времени создания и программы-производителя кадра (необязательный,
+/// см. [`Provenance`](super::Provenance))
+pub(crate) const SECTION_PROVENANCE: u8 = 0x05;
+
+/// Раздел идентификатора внешнего словаря
+pub(crate) const SECTION_DICTIONARY_ID: u8 = SECTION_CRITICAL | 0x01;
+
+/// Раздел атласа алфавита
+pub(crate) const SECTION_ALPHABET_MAP: u8 = SECTION_CRITICAL | 0x02;
+
+/// Раздел-признак: тело — реестр блоков (пустое значение)
+pub(crate) const SECTION_BLOCK_FRAMED: u8 = SECTION_CRITICAL | 0x03;
+
+/// Раздел параметров модели (период полураспада адаптивной модели)
+pub(crate) const SECTION_MODEL_PARAMS: u8 = SECTION_CRITICAL | 0x04;
+
+/// Минимальный размер устаревшего архива: словарь + таблица + масса + длина данных
+const LEGACY_MIN_LEN: usize = 4 + 4 + 8 + 4;
+
+/// Первый код, доступный сторонним движкам
+pub const CUSTOM_ENGINE_BASE: u8 = 128;
+
+/// Движок, которым закодированы данные ⚙️
+///
+/// Коды 1–127 зарезервированы за встроенными движками,
+/// 128–255 — за сторонними (см. `EngineRegistry`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum EngineKind {
+    /// Арифметическое кодирование
+    #[default]
+    Arithmetic,
+    /// Два арифметических потока: литералы и ссылки на словарь
+    SplitStreams,
+    /// Структурный режим JSON: ключи, строки, числа и пунктуация раздельно
+    StructuredJson,
+    /// Шаблоны строк журналов и их переменные поля
+    LogTemplates,
+    /// Таблицы с разделителем, транспонированные по столбцам
+    Columnar,
+    /// Хранение без сжатия: тело — исходные байты
+    Stored,
+    /// Пары байтов little-endian как символы (UTF-16, 16-битные отсчеты)
+    Wide16,
+    /// Битовые плоскости, каждая своей двоичной моделью
+    BitPlanes,
+    /// Дедупликация фрагментов, уникальные сжаты словарным движком
+    Dedup,
+    /// Побитное смешение литеральной модели и модели слов (экспериментально)
+    Mix,
+    /// Кодовые точки UTF-8 как символы, недопустимые байты как есть
+    Utf8,
+    /// Длинные повторы внутри входа как ссылки назад, прочее — словарным движком
+    LongRepeats,
+    /// Сторонний движок с кодом 128–255
+    Custom(u8),
+}
+
+impl EngineKind {
+    /// Сторонний движок (`None`, если код из зарезервированного диапазона)
+    pub fn custom(engine_id: u8) -> Option<Self> {
+        (engine_id >= CUSTOM_ENGINE_BASE).then_some(EngineKind::Custom(engine_id))
+    }
+
+    /// Код движка в заголовке
+    pub fn to_byte(self) -> u8 {
+        match self {
+            EngineKind::Arithmetic => 1,
+            EngineKind::SplitStreams => 2,
+            EngineKind::StructuredJson => 3,
+            EngineKind::LogTemplates => 4,
+            EngineKind::Columnar => 5,
+            EngineKind::Stored => 6,
+            EngineKind::Wide16 => 7,
+            EngineKind::BitPlanes => 8,
+            EngineKind::Dedup => 9,
+            EngineKind::Mix => 10,
+            EngineKind::Utf8 => 11,
+            EngineKind::LongRepeats => 12,
+            EngineKind::Custom(engine_id) => engine_id,
+        }
+    }
+
+    /// Движок по коду из заголовка
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            1 => Some(EngineKind::Arithmetic),
+            2 => Some(EngineKind::SplitStreams),
+            3 => Some(EngineKind::StructuredJson),
+            4 => Some(EngineKind::LogTemplates),
+            5 => Some(EngineKind::Columnar),
+            6 => Some(EngineKind::Stored),
+            7 => Some(EngineKind::Wide16),
+            8 => Some(EngineKind::BitPlanes),
+            9 => Some(EngineKind::Dedup),
+            10 => Some(EngineKind::Mix),
+            11 => Some(EngineKind::Utf8),
+            12 => Some(EngineKind::LongRepeats),
+            _ => Self::custom(byte),
+        }
+    }
+}
+
+/// Код адаптивной модели в заголовке
+const ADAPTIVE_MODEL_CODE: u8 = 2;
+
+/// Код хешированной модели второго порядка в заголовке
+const HASHED_ORDER2_MODEL_CODE: u8 = 3;
+
+/// Наибольший размер таблицы контекстов модели второго порядка, бит
+pub const MAX_CONTEXT_TABLE_BITS: u8 = 24;
+
+/// Вид вероятностной модели 🎲
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ModelKind {
+    /// Статическая модель нулевого порядка, таблица частот в архиве
+    #[default]
+    StaticOrder0,
+    /// Общая обученная модель (см. `CompressorHandle`), таблица вне архива
+    SharedOrder0,
+    /// Адаптивная модель нулевого порядка со старением, таблицы в архиве нет
+    ///
+    /// Вес каждого встреченного символа вдвое падает за `half_life`
+    /// закодированных символов, поэтому модель следует за сменой
+    /// распределения (код, за которым идут base64-блоки).
+    Adaptive {
+        /// Период полураспада в символах (не меньше 1)
+        half_life: u32,
+    },
+    /// Адаптивная модель второго порядка с уходом к младшим порядкам
+    ///
+    /// Символ предсказывается по двум предыдущим; незнакомый в этом
+    /// контексте символ кодируется уходом (escape) к контексту из одного
+    /// символа, а затем к модели нулевого порядка. Контексты хешируются
+    /// в таблицу из `2^table_bits` ячеек: больше ячеек — меньше
+    /// вытеснений и лучше сжатие ценой памяти. Таблицы в архиве нет.
+    HashedOrder2 {
+        /// Размер таблицы контекстов, бит (1..=[`MAX_CONTEXT_TABLE_BITS`])
+        table_bits: u8,
+    },
+}
+
+impl ModelKind {
+    /// Код модели в заголовке
+    pub fn to_byte(self) -> u8 {
+        match self {
+            ModelKind::StaticOrder0 => 0,
+            ModelKind::SharedOrder0 => 1,
+            ModelKind::Adaptive { .. } => ADAPTIVE_MODEL_CODE,
+            ModelKind::HashedOrder2 { .. } => HASHED_ORDER2_MODEL_CODE,
+        }
+    }
+
+    /// Модель учится по ходу кодирования, таблицы частот в архиве нет
+    pub fn is_adaptive(self) -> bool {
+        matches!(
+            self,
+            ModelKind::Adaptive { .. } | ModelKind::HashedOrder2 { .. }
+        )
+    }
+
+    /// Модель с параметрами по коду и значению раздела параметров модели
+    fn from_params(code: u8, params: &[u8]) -> Option<Self> {
+        match code {
+            ADAPTIVE_MODEL_CODE => match u32::from_le_bytes(params.try_into().ok()?) {
+                0 => None,
+                half_life => Some(ModelKind::Adaptive { half_life }),
+            },
+            HASHED_ORDER2_MODEL_CODE => match *params {
+                [table_bits @ 1..=MAX_CONTEXT_TABLE_BITS] => {
+                    Some(ModelKind::HashedOrder2 { table_bits })
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Модель без параметров по коду из заголовка
+    ///
+    /// Параметры адаптивной модели и модели второго порядка хранятся
+    /// отдельно, их читает [`ArchiveHeader::parse`], поэтому для кодов
+    /// этих моделей здесь возвращается `None`.
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(ModelKind::StaticOrder0),
+            1 => Some(ModelKind::SharedOrder0),
+            _ => None,
+        }
+    }
+}
+
+/// Заголовок архива 🏷️
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArchiveHeader {
+    /// Версия формата (0 — устаревший архив без заголовка)
+    pub format_version: u8,
+    /// Движок сжатия
+    pub engine: EngineKind,
+    /// Вид модели
+    pub model_kind: ModelKind,
+    /// Идентификатор внешнего словаря (`None` — словарь внутри архива)
+    pub dictionary_id: Option<u64>,
+    /// Атлас алфавита (`Some` — таблица частот в компактном виде)
+    pub alphabet_map: Option<AlphabetMap>,
+    /// Тело — реестр независимых блоков, каждый со своим заголовком
+    pub block_framed: bool,
+}
+
+impl ArchiveHeader {
+    /// Заголовок текущей версии
+    pub fn current(model_kind: ModelKind, dictionary_id: Option<u64>) -> Self {
+        Self {
+            format_version: FORMAT_VERSION,
+            engine: EngineKind::Arithmetic,
+            model_kind,
+            dictionary_id,
+            alphabet_map: None,
+            block_framed: false,
+        }
+    }
+
+    /// Заменяет движок
+    pub fn with_engine(mut self, engine: EngineKind) -> Self {
+        self.engine = engine;
+        self
+    }
+
+    /// Добавляет атлас алфавита
+    pub fn with_alphabet_map(mut self, alphabet_map: AlphabetMap) -> Self {
+        self.alphabet_map = Some(alphabet_map);
+        self
+    }
+
+    /// Помечает тело как реестр блоков
+    pub fn with_block_framing(mut self) -> Self {
+        self.block_framed = true;
+        self
+    }
+
+    /// Разрядность кодера, которой закодированы данные архива
+    pub fn coder_precision(&self) -> CoderPrecision {
+        if self.format_version >= PRECISION_32_FORMAT_VERSION {
+            CoderPrecision::Bits32
+        } else {
+            CoderPrecision::Bits24
+        }
+    }
+
+    /// Записывает заголовок в конец буфера
+    ///
+    /// Раскладка выбирается по версии: с версии 6 — разделы TLV,
+    /// раньше — байт флагов и позиционные поля.
+    pub fn write_to(&self, output: &mut Vec<u8>) {
+        output.extend_from_slice(&ARCHIVE_MAGIC);
+        output.push(self.format_version);
+        output.push(self.engine.to_byte());
+        output.push(self.model_kind.to_byte());
+
+        if self.format_version >= TLV_FORMAT_VERSION {
+            self.write_sections(output);
+            output.push(SECTION_END);
+        } else {
+            self.write_flagged_fields(output);
+        }
+    }
+
+    /// Разделы TLV заголовка (без завершающего `SECTION_END`)
+    fn write_sections(&self, output: &mut Vec<u8>) {
+        match self.model_kind {
+            ModelKind::Adaptive { half_life } => {
+                write_header_section(output, SECTION_MODEL_PARAMS, &half_life.to_le_bytes());
+            }
+            ModelKind::HashedOrder2 { table_bits } => {
+                write_header_section(output, SECTION_MODEL_PARAMS, &[table_bits]);
+            }
+            ModelKind::StaticOrder0 | ModelKind::SharedOrder0 => {}
+        }
+        if let Some(dictionary_id) = self.dictionary_id {
+            write_header_section(output, SECTION_DICTIONARY_ID, &dictionary_id.to_le_bytes());
+        }
+        if let Some(alphabet_map) = &self.alphabet_map {
+            write_header_section(output, SECTION_ALPHABET_MAP, alphabet_map.as_bytes());
+        }
+        if self.block_framed {
+            write_header_section(output, SECTION_BLOCK_FRAMED, &[]);
+        }
+    }
+
+    /// Байт флагов и позиционные поля версий 1–5
+    fn write_flagged_fields(&self, output: &mut Vec<u8>) {
+        let mut flags = 0;
+        if self.dictionary_id.is_some() {
+            flags |= FLAG_EXTERNAL_DICTIONARY;
+        }
+        if self.alphabet_map.is_some() {
+            flags |= FLAG_ALPHABET_MAP;
+        }
+        if self.block_framed {
+            flags |= FLAG_BLOCK_FRAMED;
+        }
+        output.push(flags);
+
+        if let ModelKind::Adaptive { half_life } = self.model_kind {
+            output.extend_from_slice(&half_life.to_le_bytes());
+        }
+        if let Some(dictionary_id) = self.dictionary_id {
+            output.extend_from_slice(&dictionary_id.to_le_bytes());
+        }
+        if let Some(alphabet_map) = &self.alphabet_map {
+            output.extend_from_slice(alphabet_map.as_bytes());
+        }
+    }
+
+    /// Размер заголовка в байтах (без раздела метаданных)
+    pub fn encoded_len(&self) -> usize {
+        if self.format_version == LEGACY_FORMAT_VERSION {
+            return 0;
+        }
+        let mut encoded = Vec::new();
+        self.write_to(&mut encoded);
+        encoded.len()
+    }
+
+    /// Разбирает заголовок в начале архива
+    ///
+    /// Возвращает заголовок и смещение начала тела архива
+    /// (раздел метаданных, если есть, пропускается).
+    /// Данные без сигнатуры распознаются как устаревший формат версии 0
+    /// (кроме микроархивов — у них нет заголовка, см. [`micro_spell`](crate::compression_engine::micro_spell)).
+    /// Неизвестные необязательные разделы пропускаются, неизвестный
+    /// обязательный раздел делает заголовок нераспознанным.
+    pub fn parse(archive_bytes: &[u8]) -> Option<(Self, usize)> {
+        Self::parse_at(archive_bytes).ok()
+    }
+
+    /// Проверяемый разбор заголовка 📍
+    ///
+    /// То же, что [`parse`](Self::parse), но нераспознанный заголовок дает
+    /// [`WizardError::UnrecognizedHeader`] со смещением байта, на котором
+    /// разбор остановился.
+    pub fn try_parse(archive_bytes: &[u8]) -> WizardResult<(Self, usize)> {
+        Self::parse_at(archive_bytes)
+            .map_err(|offset| WizardError::UnrecognizedHeader.located(DecodePhase::Header, offset))
+    }
+
+    /// Разбор заголовка; ошибка — смещение байта, на котором разбор остановился
+    fn parse_at(archive_bytes: &[u8]) -> Result<(Self, usize), usize> {
+        if !archive_bytes.starts_with(&ARCHIVE_MAGIC) {
+            if archive_bytes.len() < LEGACY_MIN_LEN || is_micro_archive(archive_bytes) {
+                return Err(0);
+            }
+            let legacy = Self {
+                format_version: LEGACY_FORMAT_VERSION,
+                engine: EngineKind::Arithmetic,
+                model_kind: ModelKind::StaticOrder0,
+                dictionary_id: None,
+                alphabet_map: None,
+                block_framed: false,
+            };
+            return Ok((legacy, 0));
+        }
+
+        let fixed = archive_bytes
+            .get(ARCHIVE_MAGIC.len()..SECTIONS_OFFSET)
+            .ok_or(archive_bytes.len())?;
+        let format_version = fixed[0];
+        let engine = EngineKind::from_byte(fixed[1]).ok_or(ENGINE_OFFSET)?;
+        if format_version >= TLV_FORMAT_VERSION {
+            Self::parse_sections(archive_bytes, format_version, engine, fixed[2])
+        } else {
+            Self::parse_flagged_fields(archive_bytes, format_version, engine, fixed[2])
+        }
+    }
+
+    /// Разбор разделов TLV (версия 6+)
+    fn parse_sections(
+        archive_bytes: &[u8],
+        format_version: u8,
+        engine: EngineKind,
+        model_code: u8,
+    ) -> Result<(Self, usize), usize> {
+        let mut header = Self {
+            format_version,
+            engine,
+            model_kind: ModelKind::StaticOrder0,
+            dictionary_id: None,
+            alphabet_map: None,
+            block_framed: false,
+        };
+        let mut model_params = None;
+        // Без выделения памяти: заголовок разбирается на каждый кадр
+        let mut seen_kinds = [false; 256];
+        let body_offset = walk_header_sections_at(archive_bytes, |section| {
+            if std::mem::replace(&mut seen_kinds[section.kind as usize], true) {
+                return None;
+            }
+
+            match section.kind {
+                SECTION_MODEL_PARAMS => model_params = Some(section.value),
+                SECTION_DICTIONARY_ID => {
+                    header.dictionary_id = Some(u64::from_le_bytes(section.value.try_into().ok()?));
+                }
+                SECTION_ALPHABET_MAP => {
+                    header.alphabet_map =
+                        Some(AlphabetMap::from_bytes(section.value.try_into().ok()?));
+                }
+                SECTION_BLOCK_FRAMED if section.value.is_empty() => header.block_framed = true,
+                kind if kind & SECTION_CRITICAL == 0 => {}
+                _ => return None,
+            }
+            Some(())
+        })?;
+
+        header.model_kind = match model_params {
+            Some(params) => ModelKind::from_params(model_code, params),
+            None => ModelKind::from_byte(model_code),
+        }
+        .ok_or(MODEL_OFFSET)?;
+        Ok((header, body_offset))
+    }
+
+    /// Разбор байта флагов и позиционных полей (версии 1–5)
+    fn parse_flagged_fields(
+        archive_bytes: &[u8],
+        format_version: u8,
+        engine: EngineKind,
+        model_code: u8,
+    ) -> Result<(Self, usize), usize> {
+        let flags = *archive_bytes.get(FLAGS_OFFSET).ok_or(archive_bytes.len())?;
+        if format_version >= ALPHABET_MAP_FORMAT_VERSION && flags & !KNOWN_FLAGS != 0 {
+            return Err(FLAGS_OFFSET);
+        }
+
+        // Поле, не уместившееся в архив, обрывает разбор на его начале
+        let field = |start: usize, len: usize| archive_bytes.get(start..start + len).ok_or(start);
+        let mut cursor = FLAGS_OFFSET + 1;
+        let model_kind = if model_code == ADAPTIVE_MODEL_CODE {
+            let half_life_bytes: [u8; 4] = field(cursor, 4)?.try_into().map_err(|_| cursor)?;
+            match u32::from_le_bytes(half_life_bytes) {
+                0 => return Err(cursor),
+                half_life => {
+                    cursor += 4;
+                    ModelKind::Adaptive { half_life }
+                }
+            }
+        } else {
+            ModelKind::from_byte(model_code).ok_or(MODEL_OFFSET)?
+        };
+        let dictionary_id = if flags & FLAG_EXTERNAL_DICTIONARY != 0 {
+            let id_bytes: [u8; 8] = field(cursor, 8)?.try_into().map_err(|_| cursor)?;
+            cursor += 8;
+            Some(u64::from_le_bytes(id_bytes))
+        } else {
+            None
+        };
+        let alphabet_map = if flags & FLAG_ALPHABET_MAP != 0 {
+            let map_bytes = field(cursor, AlphabetMap::ENCODED_LEN)?;
+            let map = AlphabetMap::from_bytes(map_bytes.try_into().map_err(|_| cursor)?);
+            cursor += AlphabetMap::ENCODED_LEN;
+            Some(map)
+        } else {
+            None
+        };
+        if flags & FLAG_METADATA != 0 {
+            let len_bytes: [u8; 4] = field(cursor, 4)?.try_into().map_err(|_| cursor)?;
+            let section_len = u32::from_le_bytes(len_bytes) as usize;
+            let section_end = cursor.checked_add(4 + section_len).ok_or(cursor)?;
+            if section_end > archive_bytes.len() {
+                return Err(cursor);
+            }
+            cursor = section_end;
+        }
+
+        let header = Self {
+            format_version,
+            engine,
+            model_kind,
+            dictionary_id,
+            alphabet_map,
+            block_framed: flags & FLAG_BLOCK_FRAMED != 0,
+        };
+        Ok((header, cursor))
+    }
+}
+
+/// Раздел заголовка версии 6+: тип и значение 🧩
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct HeaderSection<'a> {
+    /// Тип раздела (старший бит — раздел обязателен)
+    pub(crate) kind: u8,
+    /// Значение раздела
+    pub(crate) value: &'a [u8],
+}
+
+/// Байты раздела сверх значения: тип и длина `u32`
+pub(crate) const HEADER_SECTION_OVERHEAD: usize = 1 + 4;
+
+/// Дописывает раздел `[тип u8][длина u32][значение]`
+pub(crate) fn write_header_section(output: &mut Vec<u8>, kind: u8, value: &[u8]) {
+    output.push(kind);
+    output.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    output.extend_from_slice(value);
+}
+
+/// Заменяет необязательный раздел заголовка версии 6+ 🔁
+///
+/// Прежний раздел `kind` удаляется, новый (если `value` задано)
+/// дописывается последним; остальные разделы, в том числе незнакомые,
+/// и тело архива сохраняются. Раздел четности удаляется всегда: он
+/// защищает прежние байты заголовка.
+pub(crate) fn replace_header_section(
+    archive_bytes: &[u8],
+    kind: u8,
+    value: Option<&[u8]>,
+) -> WizardResult<Vec<u8>> {
+    let (sections, body_offset) =
+        read_header_sections(archive_bytes).ok_or(WizardError::UnrecognizedHeader)?;
+
+    let mut result = Vec::with_capacity(
+        archive_bytes.len() + value.map_or(0, <[u8]>::len) + HEADER_SECTION_OVERHEAD,
+    );
+    result.extend_from_slice(&archive_bytes[..SECTIONS_OFFSET]);
+    for section in sections
+        .iter()
+        .filter(|section| section.kind != kind && section.kind != SECTION_PARITY)
+    {
+        write_header_section(&mut result, section.kind, section.value);
+    }
+    if let Some(value) = value {
+        check_length_field(value.len())?;
+        write_header_section(&mut result, kind, value);
+    }
+    result.push(SECTION_END);
+    result.extend_from_slice(&archive_bytes[body_offset..]);
+    Ok(result)
+}
+
+/// Читает разделы заголовка версии 6+ до `SECTION_END`
+///
+/// Возвращает разделы по порядку и смещение тела архива.
+pub(crate) fn read_header_sections(
+    archive_bytes: &[u8],
+) -> Option<(Vec<HeaderSection<'_>>, usize)> {
+    let mut sections = Vec::new();
+    let body_offset = walk_header_sections(archive_bytes, |section| {
+        sections.push(section);
+        Some(())
+    })?;
+    Some((sections, body_offset))
+}
+
+/// Обходит разделы заголовка версии 6+ без выделения памяти
+///
+/// `visit` прерывает обход, вернув `None`. Возвращает смещение тела архива.
+pub(crate) fn walk_header_sections<'a>(
+    archive_bytes: &'a [u8],
+    visit: impl FnMut(HeaderSection<'a>) -> Option<()>,
+) -> Option<usize> {
+    walk_header_sections_at(archive_bytes, visit).ok()
+}
+
+/// Обход разделов; ошибка — смещение раздела, на котором обход прервался
+fn walk_header_sections_at<'a>(
+    archive_bytes: &'a [u8],
+    mut visit: impl FnMut(HeaderSection<'a>) -> Option<()>,
+) -> Result<usize, usize> {
+    let mut cursor = SECTIONS_OFFSET;
+    loop {
+        let section_start = cursor;
+        let kind = *archive_bytes.get(cursor).ok_or(section_start)?;
+        cursor += 1;
+        if kind == SECTION_END {
+            return Ok(cursor);
+        }
+        let len_bytes: [u8; 4] = archive_bytes
+            .get(cursor..cursor + 4)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(section_start)?;
+        let value_len = u32::from_le_bytes(len_bytes) as usize;
+        let value_start = cursor + 4;
+        let value = value_start
+            .checked_add(value_len)
+            .and_then(|value_end| archive_bytes.get(value_start..value_end))
+            .ok_or(section_start)?;
+        visit(HeaderSection { kind, value }).ok_or(section_start)?;
+        cursor = value_start + value_len;
+    }
+}
+
+/// Тесты заголовка контейнера 🎯
+#[cfg(test)]
+mod container_rune_tests {
+    use super::*;
+    use crate::compression_engine::micro_spell::MICRO_MARKER;
+
+    #[test]
+    fn test_header_roundtrip() {
+        let headers = [
+            ArchiveHeader::current(ModelKind::StaticOrder0, None),
+            ArchiveHeader::current(ModelKind::SharedOrder0, Some(0xDEAD_BEEF)),
+            ArchiveHeader::current(ModelKind::StaticOrder0, None)
+                .with_alphabet_map(AlphabetMap::from_bytes([0x0F; 32])),
+            ArchiveHeader::current(ModelKind::StaticOrder0, None).with_block_framing(),
+            ArchiveHeader::current(ModelKind::Adaptive { half_life: 4096 }, None)
+                .with_alphabet_map(AlphabetMap::from_bytes([0x0F; 32])),
+        ];
+
+        for header in headers {
+            let mut bytes = Vec::new();
+            header.write_to(&mut bytes);
+            bytes.extend_from_slice(b"payload");
+
+            let (parsed, body_offset) = ArchiveHeader::parse(&bytes).unwrap();
+            assert_eq!(parsed, header);
+            assert_eq!(body_offset, header.encoded_len());
+            assert_eq!(&bytes[body_offset..], b"payload");
+        }
+    }
+
+    #[test]
+    fn test_legacy_and_garbage_detection() {
+        let legacy = vec![0u8; LEGACY_MIN_LEN];
+        let (header, body_offset) = ArchiveHeader::parse(&legacy).unwrap();
+        assert_eq!(header.format_version, LEGACY_FORMAT_VERSION);
+        assert_eq!(header.coder_precision(), CoderPrecision::Bits24);
+        assert_eq!(body_offset, 0);
+
+        assert!(ArchiveHeader::parse(b"short").is_none());
+        assert!(ArchiveHeader::parse(&[MICRO_MARKER; LEGACY_MIN_LEN]).is_none());
+        assert!(ArchiveHeader::parse(b"ACW\x01\x63\x00\x00").is_none());
+        assert!(ArchiveHeader::parse(b"ACW\x04\x01\x00\x80payload").is_none());
+        assert!(ArchiveHeader::parse(b"ACW\x05\x01\x00\x08payload").is_none());
+        assert!(ArchiveHeader::parse(b"ACW\x05\x01\x02\x00\x00\x00\x00\x00").is_none());
+        assert!(ArchiveHeader::parse(b"ACW\x05\x01\x02\x00\x10").is_none());
+        assert_eq!(ModelKind::from_byte(2), None);
+    }
+
+    #[test]
+    fn test_unknown_sections() {
+        let header = ArchiveHeader::current(ModelKind::SharedOrder0, Some(7));
+        let mut bytes = Vec::new();
+        header.write_to(&mut bytes);
+        bytes.pop();
+
+        // Необязательный раздел будущей версии пропускается по длине
+        let mut optional = bytes.clone();
+        write_header_section(&mut optional, 0x42, b"checksum");
+        optional.push(SECTION_END);
+        optional.extend_from_slice(b"payload");
+        let (parsed, body_offset) = ArchiveHeader::parse(&optional).unwrap();
+        assert_eq!(parsed, header);
+        assert_eq!(&optional[body_offset..], b"payload");
+
+        // Незнакомый обязательный, повторенный или обрезанный раздел — нет
+        let mut critical = bytes.clone();
+        write_header_section(&mut critical, SECTION_CRITICAL | 0x42, &[]);
+        critical.push(SECTION_END);
+        assert!(ArchiveHeader::parse(&critical).is_none());
+        // Проверяемый разбор указывает на начало отвергнутого раздела
+        let error = ArchiveHeader::try_parse(&critical).unwrap_err();
+        assert_eq!(error.root_cause(), &WizardError::UnrecognizedHeader);
+        assert_eq!(error.location().unwrap().offset, bytes.len());
+
+        let mut repeated = bytes.clone();
+        write_header_section(&mut repeated, SECTION_DICTIONARY_ID, &[0; 8]);
+        repeated.push(SECTION_END);
+        assert!(ArchiveHeader::parse(&repeated).is_none());
+
+        assert!(ArchiveHeader::parse(&bytes).is_none());
+        assert!(ArchiveHeader::parse(b"ACW\x06\x01\x02\x00").is_none());
+    }
+
+    #[test]
+    fn test_engine_codes() {
+        assert_eq!(EngineKind::from_byte(1), Some(EngineKind::Arithmetic));
+        assert_eq!(EngineKind::from_byte(200), Some(EngineKind::Custom(200)));
+        assert_eq!(EngineKind::from_byte(2), Some(EngineKind::SplitStreams));
+        assert_eq!(EngineKind::from_byte(3), Some(EngineKind::StructuredJson));
+        assert_eq!(EngineKind::from_byte(4), Some(EngineKind::LogTemplates));
+        assert_eq!(EngineKind::from_byte(5), Some(EngineKind::Columnar));
+        assert_eq!(EngineKind::from_byte(6), Some(EngineKind::Stored));
+        assert_eq!(EngineKind::from_byte(7), Some(EngineKind::Wide16));
+        assert_eq!(EngineKind::from_byte(8), Some(EngineKind::BitPlanes));
+        assert_eq!(EngineKind::from_byte(9), Some(EngineKind::Dedup));
+        assert_eq!(EngineKind::from_byte(10), Some(EngineKind::Mix));
+        assert_eq!(EngineKind::from_byte(11), Some(EngineKind::Utf8));
+        assert_eq!(EngineKind::from_byte(12), Some(EngineKind::LongRepeats));
+        assert_eq!(EngineKind::from_byte(13), None);
+        assert_eq!(EngineKind::custom(5), None);
+        assert_eq!(EngineKind::Custom(150).to_byte(), 150);
+    }
+}
@@ -0,0 +1,189 @@
+//! Свиток словаря 📖
+//!
+//! Словарь архива хранится открытым текстом: любая подстрока, ставшая
+//! словом словаря, видна без распаковки. [`read_dictionary`] перечисляет
+//! такие записи, не декодируя поток, чтобы перед публикацией архива
+//! проверить, не попали ли в словарь пароли, адреса или ключи.
+
+use super::artifact_scroll::{
+    check_body_header, check_frame_layout, parse_artifact_ref_body, read_grimoire_sections,
+    ScrollCursor,
+};
+use super::block_ledger::read_block_ledger;
+use super::container_runes::{ArchiveHeader, EngineKind};
+use crate::compression_engine::log_templates::read_templates;
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::error::{WizardError, WizardResult};
+use std::fmt;
+
+/// Запись словаря архива 📖
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DictEntry {
+    /// Байты записи, как они лежат в архиве
+    pub bytes: Vec<u8>,
+    /// Номер записи в словаре своего кадра
+    pub index: u32,
+}
+
+impl DictEntry {
+    /// Байты записи печатным ASCII, остальное — `\xNN`
+    pub fn escaped(&self) -> String {
+        self.bytes.escape_ascii().to_string()
+    }
+}
+
+impl fmt::Display for DictEntry {
+    /// `   12  "wizard"`
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{:>6}  \"{}\"", self.index, self.escaped())
+    }
+}
+
+/// Перечисляет словарь архива без распаковки данных 🔍
+///
+/// Открытым текстом хранятся слова словаря (арифметический движок с
+/// любой моделью и раздельные потоки) и литералы шаблонов режима журналов. В архивах из
+/// блоков записи идут блок за блоком, номера — внутри своего кадра.
+/// Архив с внешним словарем, JSON, столбцовый режим и хранение без
+/// сжатия открытых словарей не несут и дают пустой список. Применяются лимиты
+/// [`DecodeOptions::default`].
+pub fn read_dictionary(archive_bytes: impl AsRef<[u8]>) -> WizardResult<Vec<DictEntry>> {
+    let mut entries = Vec::new();
+    collect_frame_dictionary(
+        archive_bytes.as_ref(),
+        &DecodeOptions::default(),
+        &mut entries,
+    )?;
+    Ok(entries)
+}
+
+/// Добавляет записи словаря кадра (или всех кадров архива из блоков)
+fn collect_frame_dictionary(
+    frame: &[u8],
+    options: &DecodeOptions,
+    entries: &mut Vec<DictEntry>,
+) -> WizardResult<()> {
+    let (header, body_offset) =
+        ArchiveHeader::parse(frame).ok_or(WizardError::UnrecognizedHeader)?;
+    let body = &frame[body_offset..];
+
+    if header.block_framed {
+        for block in read_block_ledger(&header, body, options)? {
+            collect_frame_dictionary(block.frame, options, entries)?;
+        }
+        return Ok(());
+    }
+    if header.dictionary_id.is_some() {
+        return Ok(());
+    }
+
+    let plaintext: Vec<&[u8]> = match header.engine {
+        EngineKind::Arithmetic if header.model_kind.is_adaptive() => {
+            check_frame_layout(&header, EngineKind::Arithmetic)?;
+            let mut cursor = ScrollCursor::new(body, 0);
+            read_grimoire_sections(&mut cursor, &header, options)?.0
+        }
+        EngineKind::Arithmetic => parse_artifact_ref_body(&header, body, options)?.word_grimoire,
+        EngineKind::SplitStreams => {
+            check_body_header(&header, EngineKind::SplitStreams)?;
+            let mut cursor = ScrollCursor::new(body, 0);
+            read_grimoire_sections(&mut cursor, &header, options)?.0
+        }
+        EngineKind::LogTemplates => {
+            check_body_header(&header, EngineKind::LogTemplates)?;
+            let mut cursor = ScrollCursor::new(body, 0);
+            let model_len = cursor.read_u32()? as usize;
+            read_templates(cursor.read_slice(model_len)?, options)?
+                .into_iter()
+                .flatten()
+                .filter(|literal| !literal.is_empty())
+                .collect()
+        }
+        EngineKind::StructuredJson
+        | EngineKind::Columnar
+        | EngineKind::Stored
+        | EngineKind::Wide16
+        | EngineKind::BitPlanes
+        | EngineKind::Dedup
+        | EngineKind::Mix
+        | EngineKind::Utf8
+        | EngineKind::LongRepeats => Vec::new(),
+        EngineKind::Custom(engine_id) => return Err(WizardError::UnsupportedEngine(engine_id)),
+    };
+
+    entries.extend(
+        plaintext
+            .into_iter()
+            .enumerate()
+            .map(|(index, bytes)| DictEntry {
+                bytes: bytes.to_vec(),
+                index: index as u32,
+            }),
+    );
+    Ok(())
+}
+
+/// Тесты свитка словаря 🎯
+#[cfg(test)]
+mod dictionary_scroll_tests {
+    use super::*;
+    use crate::compression_engine::engine_covenant::StructuredMode;
+    use crate::simple_api::{
+        compress_data, compress_data_adaptive, compress_data_in_blocks, compress_structured,
+    };
+
+    /// Повторы, ради которых слова окупают строку словаря
+    fn secret_text() -> String {
+        "login admin swordfish ".repeat(12) + &"token ".repeat(20)
+    }
+
+    #[test]
+    fn test_dictionary_lists_words_without_decoding() {
+        let secret_text = secret_text();
+        let entries = read_dictionary(compress_data(&secret_text)).unwrap();
+        assert!(entries.iter().any(|entry| entry.bytes == b"swordfish"));
+        assert!(entries
+            .iter()
+            .enumerate()
+            .all(|(position, entry)| entry.index == position as u32));
+
+        let blocks = read_dictionary(compress_data_in_blocks(
+            secret_text.repeat(3),
+            secret_text.len(),
+        ))
+        .unwrap();
+        assert!(blocks.iter().filter(|entry| entry.index == 0).count() > 1);
+    }
+
+    #[test]
+    fn test_dictionary_of_other_engines_and_damage() {
+        let log = "12:00 login ok\n12:01 login ok\n".repeat(3);
+        let templates =
+            read_dictionary(compress_structured(&log, StructuredMode::LogTemplates)).unwrap();
+        assert!(templates.iter().any(|entry| entry.bytes == b" login ok\n"));
+
+        let secret_text = secret_text();
+        let adaptive = read_dictionary(compress_data_adaptive(&secret_text, 64)).unwrap();
+        assert!(adaptive.iter().any(|entry| entry.bytes == b"swordfish"));
+
+        let json = compress_structured("{\"secret\": 1}", StructuredMode::Json);
+        assert!(read_dictionary(json).unwrap().is_empty());
+
+        let mut archive = compress_data(&secret_text);
+        archive.truncate(12);
+        assert!(read_dictionary(&archive).is_err());
+        assert_eq!(
+            read_dictionary(b"not an archive"),
+            Err(WizardError::UnrecognizedHeader)
+        );
+    }
+
+    #[test]
+    fn test_entry_pretty_print() {
+        let entry = DictEntry {
+            bytes: b"tab\there \xff\"".to_vec(),
+            index: 7,
+        };
+        assert_eq!(entry.to_string(), "     7  \"tab\\there \\xff\\\"\"");
+    }
+}
@@ -0,0 +1,151 @@
+//! Осколки архива: разрезание и склейка по блокам 🔪
+//!
+//! Блоки архива независимы (см. [`block_ledger`](super::block_ledger)),
+//! поэтому большой архив можно разложить по частям хранилища объектов и
+//! собрать обратно, не распаковывая ни одного блока. [`split`] делает из
+//! каждого блока самостоятельный архив из одного блока — его можно
+//! распаковать отдельно, — а [`concat`] сводит реестры частей в один.
+//!
+//! Разделы заголовка исходного архива (метаданные, статистика) в части
+//! не переносятся: они описывают архив целиком.
+
+use super::block_ledger::{read_block_ledger, write_block_archive, SYNCED_BLOCK_COUNT};
+use crate::compression_engine::engine_covenant::Frame;
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::error::{WizardError, WizardResult};
+
+/// Разрезает архив на части по блокам 🔪
+///
+/// Каждая часть — архив из одного блока. Архив без блоков возвращается
+/// единственной частью как есть. Кадры блоков не проверяются.
+///
+/// ```
+/// use arithmetic_compression_wizard::format::{concat, split};
+/// use arithmetic_compression_wizard::simple_api::{compress_data_in_blocks, decompress_data};
+///
+/// let archive = compress_data_in_blocks(b"owl post ".repeat(100), 256);
+/// let parts = split(&archive).unwrap();
+/// assert_eq!(parts.len(), 4);
+/// assert_eq!(decompress_data(parts[0].to_bytes()), &b"owl post ".repeat(100)[..256]);
+/// assert_eq!(concat(&parts), archive);
+/// ```
+pub fn split(archive_bytes: impl AsRef<[u8]>) -> WizardResult<Vec<Frame>> {
+    let archive_bytes = archive_bytes.as_ref();
+    let frame = Frame::from_bytes(archive_bytes)?;
+    if !frame.header.block_framed {
+        return Ok(vec![frame]);
+    }
+
+    read_block_ledger(&frame.header, &frame.body, &DecodeOptions::unlimited())?
+        .into_iter()
+        .map(|block| {
+            Frame::from_bytes(&write_block_archive(&[(
+                block.original_len,
+                block.frame.to_vec(),
+            )]))
+        })
+        .collect()
+}
+
+/// Склеивает части в один архив 🧩
+///
+/// Паникует на частях, которые нельзя склеить (см. [`try_concat`]).
+pub fn concat(frames: &[Frame]) -> Vec<u8> {
+    try_concat(frames).expect("части не склеиваются: используйте try_concat")
+}
+
+/// Проверяемая склейка частей
+///
+/// Блоки всех частей с реестром идут подряд в порядке частей. Часть без
+/// реестра склеивается только сама с собой — ее исходная длина не
+/// записана, а распаковывать ее ради длины склейка не должна.
+pub fn try_concat(frames: &[Frame]) -> WizardResult<Vec<u8>> {
+    if let [frame] = frames {
+        if !frame.header.block_framed {
+            return Ok(frame.to_bytes());
+        }
+    }
+
+    let mut blocks = Vec::new();
+    for frame in frames {
+        if !frame.header.block_framed {
+            return Err(WizardError::CorruptBlockTable("часть без реестра блоков"));
+        }
+        for block in read_block_ledger(&frame.header, &frame.body, &DecodeOptions::unlimited())? {
+            blocks.push((block.original_len, block.frame.to_vec()));
+        }
+    }
+
+    if blocks.len() >= SYNCED_BLOCK_COUNT as usize {
+        return Err(WizardError::CorruptBlockTable("слишком много блоков"));
+    }
+    Ok(write_block_archive(&blocks))
+}
+
+/// Тесты осколков 🎯
+#[cfg(test)]
+mod frame_shards_tests {
+    use super::*;
+    use crate::format::block_stream::BlockStreamWriter;
+    use crate::simple_api::{compress_data, compress_data_in_blocks, decompress_data};
+    use std::io::Write;
+
+    #[test]
+    fn test_split_and_regroup() {
+        let data = "scroll of many blocks ".repeat(300);
+        let archive = compress_data_in_blocks(&data, 1000);
+        let parts = split(&archive).unwrap();
+        assert_eq!(parts.len(), 7);
+
+        let mut restored = Vec::new();
+        for part in &parts {
+            assert!(part.header.block_framed);
+            restored.extend(decompress_data(part.to_bytes()));
+        }
+        assert_eq!(restored, data.as_bytes());
+
+        // Склейка групп дает тот же архив, что склейка всех частей
+        let left = Frame::from_bytes(&concat(&parts[..3])).unwrap();
+        let right = Frame::from_bytes(&concat(&parts[3..])).unwrap();
+        assert_eq!(concat(&[left, right]), archive);
+        assert!(decompress_data(concat(&[])).is_empty());
+    }
+
+    #[test]
+    fn test_streamed_archive_gets_a_ledger() {
+        let data = "streamed owl post ".repeat(100);
+        let mut streamed = Vec::new();
+        let mut writer = BlockStreamWriter::new(&mut streamed, 500).unwrap();
+        writer.write_all(data.as_bytes()).unwrap();
+        writer.finish().unwrap();
+
+        let parts = split(&streamed).unwrap();
+        assert_eq!(parts.len(), 4);
+        let rebuilt = concat(&parts);
+        assert_ne!(rebuilt, streamed);
+        assert_eq!(decompress_data(&rebuilt), data.as_bytes());
+    }
+
+    #[test]
+    fn test_plain_frames_and_garbage() {
+        let plain = compress_data(b"a single plain frame");
+        let parts = split(&plain).unwrap();
+        assert_eq!(parts.len(), 1);
+        assert_eq!(concat(&parts), plain);
+
+        let blocked = split(compress_data_in_blocks(b"owl", 2)).unwrap();
+        assert_eq!(
+            try_concat(&[parts[0].clone(), blocked[0].clone()]),
+            Err(WizardError::CorruptBlockTable("часть без реестра блоков"))
+        );
+        assert_eq!(
+            split(b"short").map_err(|error| error.root_cause().clone()),
+            Err(WizardError::UnrecognizedHeader)
+        );
+        let archive = compress_data_in_blocks(b"owl post", 4);
+        assert!(matches!(
+            split(&archive[..archive.len() - 1]).map_err(|error| error.root_cause().clone()),
+            Err(WizardError::Truncated { .. })
+        ));
+    }
+}
@@ -0,0 +1,174 @@
+//! Беглый взгляд на заголовок 👁️
+//!
+//! Маршрутизаторам и хранилищам, которые раскладывают тысячи архивов в
+//! секунду, нужны версия, движок и размер — без словаря, таблиц и тела.
+//! [`peek_header`] читает только заголовок и поля фиксированной длины за
+//! ним и не выделяет память, поэтому ему достаточно начала архива.
+
+use super::artifact_scroll::ScrollCursor;
+use super::block_ledger::{BLOCK_ENTRY_LEN, STREAMED_BLOCK_COUNT, SYNCED_BLOCK_COUNT};
+use super::container_runes::{
+    walk_header_sections, ArchiveHeader, EngineKind, SECTION_CHECKSUM, TLV_FORMAT_VERSION,
+};
+use crate::error::{WizardError, WizardResult};
+
+/// Сводка заголовка архива 👁️
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeaderInfo {
+    /// Версия формата (0 — устаревший архив без заголовка)
+    pub version: u8,
+    /// Движок кадра (для архива из блоков — встроенный арифметический)
+    pub engine: EngineKind,
+    /// Длина исходных данных, если она записана в начале архива
+    ///
+    /// Известна для архивов из блоков (сумма реестра, если реестр целиком
+    /// в переданных байтах) и кадров битовых плоскостей.
+    pub original_len: Option<u64>,
+    /// Число блоков (`None` — архив не из блоков или записан потоком)
+    pub block_count: Option<u32>,
+    /// В заголовке есть раздел контрольной суммы
+    pub has_checksum: bool,
+}
+
+/// Сводка заголовка без разбора тела и без выделения памяти 👁️
+///
+/// `archive_bytes` может быть только началом архива: нужен заголовок, а
+/// длина архива из блоков — еще и реестр. Заголовок проверяется так же,
+/// как при распаковке; тело не проверяется вовсе.
+///
+/// ```
+/// use arithmetic_compression_wizard::format::{peek_header, FORMAT_VERSION};
+/// use arithmetic_compression_wizard::simple_api::compress_data_in_blocks;
+///
+/// let archive = compress_data_in_blocks(b"owl post ".repeat(100), 256);
+/// let info = peek_header(&archive).unwrap();
+/// assert_eq!(info.version, FORMAT_VERSION);
+/// assert_eq!(info.block_count, Some(4));
+/// assert_eq!(info.original_len, Some(900));
+/// ```
+pub fn peek_header(archive_bytes: &[u8]) -> WizardResult<HeaderInfo> {
+    let (header, body_offset) =
+        ArchiveHeader::parse(archive_bytes).ok_or(WizardError::UnrecognizedHeader)?;
+    let body = &archive_bytes[body_offset..];
+
+    let has_checksum = header.format_version >= TLV_FORMAT_VERSION && {
+        let mut found = false;
+        walk_header_sections(archive_bytes, |section| {
+            found |= section.kind == SECTION_CHECKSUM;
+            Some(())
+        });
+        found
+    };
+
+    let (original_len, block_count) = if header.block_framed {
+        peek_block_ledger(body)
+    } else if matches!(
+        header.engine,
+        EngineKind::BitPlanes | EngineKind::Dedup | EngineKind::Mix | EngineKind::LongRepeats
+    ) {
+        (ScrollCursor::new(body, 0).read_u64().ok(), None)
+    } else {
+        (None, None)
+    };
+
+    Ok(HeaderInfo {
+        version: header.format_version,
+        engine: header.engine,
+        original_len,
+        block_count,
+        has_checksum,
+    })
+}
+
+/// Число блоков и суммарная длина из реестра (без проверки кадров)
+fn peek_block_ledger(body: &[u8]) -> (Option<u64>, Option<u32>) {
+    let mut cursor = ScrollCursor::new(body, 0);
+    let block_count = match cursor.read_u32() {
+        Ok(STREAMED_BLOCK_COUNT | SYNCED_BLOCK_COUNT) | Err(_) => return (None, None),
+        Ok(block_count) => block_count,
+    };
+    if cursor.remaining() / BLOCK_ENTRY_LEN < block_count as usize {
+        return (None, Some(block_count));
+    }
+
+    let original_len = (0..block_count).try_fold(0u64, |total, _| {
+        let block_len = cursor.read_u64().ok()?;
+        cursor.read_u32().ok()?;
+        total.checked_add(block_len)
+    });
+    (original_len, Some(block_count))
+}
+
+/// Тесты беглого взгляда 🎯
+#[cfg(test)]
+mod header_glimpse_tests {
+    use super::*;
+    use crate::compression_engine::engine_covenant::{PipelineStage, StructuredMode};
+    use crate::format::block_stream::BlockStreamWriter;
+    use crate::format::container_runes::{write_header_section, FORMAT_VERSION, SECTIONS_OFFSET};
+    use crate::simple_api::{
+        compress_data, compress_data_in_blocks, compress_structured, compress_with_stage,
+    };
+    use std::io::Write;
+
+    #[test]
+    fn test_peek_single_frames() {
+        let info = peek_header(&compress_data(b"the wizard and the owl")).unwrap();
+        assert_eq!(
+            info,
+            HeaderInfo {
+                version: FORMAT_VERSION,
+                engine: EngineKind::Arithmetic,
+                original_len: None,
+                block_count: None,
+                has_checksum: false,
+            }
+        );
+
+        let json = compress_structured(b"{\"a\": 1}", StructuredMode::Json);
+        assert_eq!(
+            peek_header(&json).unwrap().engine,
+            EngineKind::StructuredJson
+        );
+
+        let planes = compress_with_stage([1u8; 300], PipelineStage::BitPlanes);
+        let header_len = ArchiveHeader::parse(&planes).unwrap().1;
+        // Хватает заголовка и длины входа
+        let info = peek_header(&planes[..header_len + 8]).unwrap();
+        assert_eq!(info.original_len, Some(300));
+    }
+
+    #[test]
+    fn test_peek_block_archives() {
+        let data = "scroll of many blocks ".repeat(200);
+        let archive = compress_data_in_blocks(&data, 1000);
+        let info = peek_header(&archive).unwrap();
+        assert_eq!(info.block_count, Some(5));
+        assert_eq!(info.original_len, Some(data.len() as u64));
+
+        // Обрезанный реестр: число блоков известно, длина — нет
+        let header_len = ArchiveHeader::parse(&archive).unwrap().1;
+        let info = peek_header(&archive[..header_len + 4 + BLOCK_ENTRY_LEN]).unwrap();
+        assert_eq!((info.block_count, info.original_len), (Some(5), None));
+
+        let mut streamed = Vec::new();
+        let mut writer = BlockStreamWriter::new(&mut streamed, 64).unwrap();
+        writer.write_all(data.as_bytes()).unwrap();
+        writer.finish().unwrap();
+        let info = peek_header(&streamed).unwrap();
+        assert_eq!((info.block_count, info.original_len), (None, None));
+    }
+
+    #[test]
+    fn test_peek_checksum_and_garbage() {
+        let archive = compress_data(b"checked scroll");
+        let mut with_checksum = archive[..SECTIONS_OFFSET].to_vec();
+        write_header_section(&mut with_checksum, SECTION_CHECKSUM, &[0xAB; 4]);
+        with_checksum.extend_from_slice(&archive[SECTIONS_OFFSET..]);
+        assert!(peek_header(&with_checksum).unwrap().has_checksum);
+
+        assert_eq!(peek_header(&[0u8; 24]).unwrap().version, 0);
+        assert_eq!(peek_header(b"ACW"), Err(WizardError::UnrecognizedHeader));
+        assert_eq!(peek_header(b"short"), Err(WizardError::UnrecognizedHeader));
+    }
+}
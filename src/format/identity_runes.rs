@@ -0,0 +1,208 @@
+//! Руны тождества: идентификатор содержимого архива 🪪
+//!
+//! Каталог резервных копий узнает уже сохраненный архив по хешу, но хеш
+//! байтов архива меняется от каждого прогона: штамп происхождения несет
+//! время создания, метаданные — имя файла, а статистика и четность
+//! добавляются по желанию. [`content_id`] хеширует канонический вид
+//! контейнера: заголовок без необязательных разделов (метаданные,
+//! контрольная сумма, статистика, четность, происхождение — и незнакомые
+//! необязательные разделы будущих версий) и тело как есть. Обязательные
+//! разделы (внешний словарь, атлас алфавита, параметры модели) меняют
+//! смысл тела и входят в хеш.
+//!
+//! У архива из блоков вместо тела хешируются исходные длины и
+//! идентификаторы кадров блоков: штампы кадров не мешают, а архив,
+//! записанный [`BlockStreamWriter`](super::BlockStreamWriter), получает тот
+//! же идентификатор, что и собранный целиком из тех же кадров.
+//!
+//! Хеш — FNV-1a 64 бита, как у идентификаторов словарей; одинаковые байты
+//! дают одинаковый идентификатор на любой платформе и в любой версии
+//! библиотеки.
+
+use super::block_ledger::read_block_ledger;
+use super::container_runes::{
+    read_header_sections, ArchiveHeader, FLAGS_OFFSET, FLAG_METADATA, LEGACY_FORMAT_VERSION,
+    SECTIONS_OFFSET, SECTION_CRITICAL, SECTION_END, TLV_FORMAT_VERSION,
+};
+use crate::bit_wizardry::checksum_runes::RuneHasher;
+use crate::compression_engine::compression_conjurer::CompressionArtifact;
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::error::{WizardError, WizardResult};
+
+/// Идентификатор содержимого архива без несмысловых разделов 🪪
+///
+/// Тело не распаковывается; у архива из блоков разбирается реестр.
+/// Нераспознанный заголовок или реестр дают ошибку разбора, кадр блока,
+/// который сам разбит на блоки, — [`WizardError::CorruptBlockTable`].
+///
+/// ```
+/// use arithmetic_compression_wizard::compression_engine::EngineConfig;
+/// use arithmetic_compression_wizard::format::content_id;
+/// use arithmetic_compression_wizard::simple_api::try_compress_with_config;
+///
+/// // Штамп происхождения с временем создания в каждом архиве
+/// let config = EngineConfig::default().with_reproducible(false);
+/// let monday = try_compress_with_config(b"nightly backup of the tower", &config).unwrap();
+/// let tuesday = try_compress_with_config(b"nightly backup of the tower", &config).unwrap();
+/// assert_eq!(content_id(&monday).unwrap(), content_id(&tuesday).unwrap());
+/// ```
+pub fn content_id(archive_bytes: impl AsRef<[u8]>) -> WizardResult<u64> {
+    let mut hasher = RuneHasher::conjure_new();
+    absorb_canonical(archive_bytes.as_ref(), &mut hasher)?;
+    Ok(hasher.finish())
+}
+
+/// Добавляет к хешу канонический вид архива
+fn absorb_canonical(archive_bytes: &[u8], hasher: &mut RuneHasher) -> WizardResult<()> {
+    let (header, body_offset) = ArchiveHeader::try_parse(archive_bytes)?;
+    if header.format_version >= TLV_FORMAT_VERSION {
+        let (sections, _) =
+            read_header_sections(archive_bytes).ok_or(WizardError::UnrecognizedHeader)?;
+        hasher.absorb(&archive_bytes[..SECTIONS_OFFSET]);
+        for section in sections
+            .iter()
+            .filter(|section| section.kind & SECTION_CRITICAL != 0)
+        {
+            hasher.absorb(&[section.kind]);
+            hasher.absorb(&(section.value.len() as u32).to_le_bytes());
+            hasher.absorb(section.value);
+        }
+        hasher.absorb(&[SECTION_END]);
+    } else if header.format_version != LEGACY_FORMAT_VERSION {
+        // Версии 1–5: раздел метаданных идет после позиционных полей
+        let mut prefix = archive_bytes[..header.encoded_len()].to_vec();
+        prefix[FLAGS_OFFSET] &= !FLAG_METADATA;
+        hasher.absorb(&prefix);
+    }
+
+    let body = &archive_bytes[body_offset..];
+    if !header.block_framed {
+        hasher.absorb(body);
+        return Ok(());
+    }
+    for block in read_block_ledger(&header, body, &DecodeOptions::unlimited())? {
+        // Как и при распаковке: кадр блока сам из блоков не состоит, так
+        // что рекурсия не глубже одного уровня
+        if ArchiveHeader::try_parse(block.frame)?.0.block_framed {
+            return Err(WizardError::CorruptBlockTable(
+                "кадр блока сам разбит на блоки",
+            ));
+        }
+        hasher.absorb(&block.original_len.to_le_bytes());
+        hasher.absorb(&content_id(block.frame)?.to_le_bytes());
+    }
+    Ok(())
+}
+
+impl CompressionArtifact {
+    /// Идентификатор содержимого архива этого артефакта 🪪
+    ///
+    /// Совпадает с [`content_id`] архива
+    /// [`serialize_artifact`](super::serialize_artifact) — и, значит,
+    /// [`compress_data`](crate::simple_api::compress_data) — с любыми
+    /// метаданными и штампами.
+    pub fn content_id(&self) -> u64 {
+        content_id(super::serialize_artifact(self)).expect("сериализованный артефакт разбирается")
+    }
+}
+
+/// Тесты рун тождества 🎯
+#[cfg(test)]
+mod identity_runes_tests {
+    use super::*;
+    use crate::compression_engine::compression_conjurer::weave_compression_spell;
+    use crate::compression_engine::engine_covenant::EngineConfig;
+    use crate::format::block_ledger::write_block_archive;
+    use crate::format::block_stream::BlockStreamWriter;
+    use crate::format::{attach_metadata, attach_parity, attach_provenance, Provenance};
+    use crate::simple_api::{compress_data, compress_data_in_blocks, try_compress_with_config};
+    use std::collections::HashMap;
+    use std::io::Write;
+
+    fn sample() -> Vec<u8> {
+        b"the catalog keeps one copy of every scroll. ".repeat(60)
+    }
+
+    #[test]
+    fn test_non_semantic_sections_are_ignored() {
+        let data = sample();
+        let archive = compress_data(&data);
+        let id = content_id(&archive).unwrap();
+        assert_eq!(weave_compression_spell(&data).content_id(), id);
+
+        let stamped = attach_provenance(
+            &archive,
+            &Provenance {
+                created_unix_secs: 1_700_000_000,
+                producer: "nightly".to_string(),
+            },
+        )
+        .unwrap();
+        let restamped = attach_provenance(
+            &archive,
+            &Provenance {
+                created_unix_secs: 1_800_000_000,
+                producer: "nightly".to_string(),
+            },
+        )
+        .unwrap();
+        let metadata = HashMap::from([("name".to_string(), "scroll.txt".to_string())]);
+        let labeled = attach_metadata(&stamped, &metadata).unwrap();
+        let protected = attach_parity(&labeled).unwrap();
+        assert_ne!(stamped, restamped);
+        for variant in [&stamped, &restamped, &labeled, &protected] {
+            assert_eq!(content_id(variant).unwrap(), id);
+        }
+
+        // Другое тело или другой движок — другой идентификатор
+        let mut other = sample();
+        other[0] = b'T';
+        assert_ne!(content_id(compress_data(&other)).unwrap(), id);
+        let stored = try_compress_with_config(
+            &data,
+            &EngineConfig::default().with_engine(crate::format::EngineKind::Stored),
+        )
+        .unwrap();
+        assert_ne!(content_id(&stored).unwrap(), id);
+
+        assert!(content_id(b"not an archive").is_err());
+    }
+
+    #[test]
+    fn test_block_layouts_and_frame_stamps_share_id() {
+        let data = sample();
+        let stream = |config: EngineConfig| {
+            let mut writer = BlockStreamWriter::with_config(Vec::new(), 700, config).unwrap();
+            writer.write_all(&data).unwrap();
+            writer.finish().unwrap()
+        };
+        let plain = stream(EngineConfig::default());
+        let stamped = stream(EngineConfig::default().with_reproducible(false));
+        assert_ne!(stamped, plain);
+        assert_eq!(content_id(&stamped).unwrap(), content_id(&plain).unwrap());
+
+        // Потоковая и обычная раскладки реестра из тех же кадров
+        let mut writer = BlockStreamWriter::new(Vec::new(), 700).unwrap();
+        writer.write_all(&data).unwrap();
+        let streamed = writer.finish().unwrap();
+        let blocks = compress_data_in_blocks(&data, 700);
+        assert_ne!(streamed, blocks);
+        assert_eq!(content_id(&streamed).unwrap(), content_id(&blocks).unwrap());
+
+        // Реестр внутри реестра отвергается сразу, на любой глубине
+        let mut nested = blocks.clone();
+        for _ in 0..200 {
+            nested = write_block_archive(&[(data.len() as u64, nested)]);
+        }
+        assert!(matches!(
+            content_id(&nested),
+            Err(WizardError::CorruptBlockTable(_))
+        ));
+
+        let regrouped = compress_data_in_blocks(&data, 500);
+        assert_ne!(
+            content_id(&regrouped).unwrap(),
+            content_id(&blocks).unwrap()
+        );
+    }
+}
@@ -0,0 +1,204 @@
+//! Руны метаданных 🏷️
+//!
+//! Пользовательские пары ключ–значение в заголовке архива: тип содержимого,
+//! имя исходного файла, время создания. Раздел завершает заголовок, поэтому
+//! читается без распаковки тела.
+//!
+//! Пары записываются как `[длина ключа u32][ключ UTF-8][длина значения u32][значение UTF-8]`,
+//! ключи по возрастанию. С версии 6 они лежат в необязательном разделе
+//! заголовка `0x01` (остальные разделы, в том числе незнакомые,
+//! сохраняются при замене метаданных); в версиях 1–5 раздел
+//! `[длина раздела u32][пары]` завершает заголовок под флагом метаданных.
+//!
+//! Раздел не входит в [`ArchiveHeader`]: заголовок остается `Copy`, а
+//! кадры движков метаданных не несут. Метаданные относятся ко всему
+//! архиву, в том числе к архиву из блоков.
+
+use super::artifact_scroll::ScrollCursor;
+use super::container_runes::{
+    read_header_sections, replace_header_section, ArchiveHeader, FLAGS_OFFSET, FLAG_METADATA,
+    LEGACY_FORMAT_VERSION, SECTION_METADATA, TLV_FORMAT_VERSION,
+};
+use crate::error::{WizardError, WizardResult};
+use std::collections::HashMap;
+
+/// Прикрепляет метаданные к архиву 🏷️
+///
+/// Прежние метаданные заменяются; пустая таблица удаляет раздел.
+/// Устаревшие архивы без заголовка метаданных не несут.
+pub fn attach_metadata(
+    archive_bytes: impl AsRef<[u8]>,
+    metadata: &HashMap<String, String>,
+) -> WizardResult<Vec<u8>> {
+    let archive_bytes = archive_bytes.as_ref();
+    let (header, body_offset) =
+        ArchiveHeader::parse(archive_bytes).ok_or(WizardError::UnrecognizedHeader)?;
+    if header.format_version == LEGACY_FORMAT_VERSION {
+        return Err(WizardError::UnsupportedVersion(LEGACY_FORMAT_VERSION));
+    }
+
+    if header.format_version >= TLV_FORMAT_VERSION {
+        let pairs = (!metadata.is_empty())
+            .then(|| encode_pairs(metadata))
+            .transpose()?;
+        return replace_header_section(archive_bytes, SECTION_METADATA, pairs.as_deref());
+    }
+
+    let mut result = Vec::with_capacity(archive_bytes.len() + 64);
+    let section_start = header.encoded_len();
+    result.extend_from_slice(&archive_bytes[..section_start]);
+    if metadata.is_empty() {
+        result[FLAGS_OFFSET] &= !FLAG_METADATA;
+    } else {
+        result[FLAGS_OFFSET] |= FLAG_METADATA;
+        let pairs = encode_pairs(metadata)?;
+        result.extend_from_slice(&(pairs.len() as u32).to_le_bytes());
+        result.extend_from_slice(&pairs);
+    }
+    result.extend_from_slice(&archive_bytes[body_offset..]);
+
+    Ok(result)
+}
+
+/// Читает метаданные архива без распаковки 🔍
+///
+/// Архив без раздела метаданных дает пустую таблицу.
+pub fn read_metadata(archive_bytes: impl AsRef<[u8]>) -> WizardResult<HashMap<String, String>> {
+    let archive_bytes = archive_bytes.as_ref();
+    let (header, body_offset) =
+        ArchiveHeader::parse(archive_bytes).ok_or(WizardError::UnrecognizedHeader)?;
+
+    let mut metadata = HashMap::new();
+    let section = if header.format_version >= TLV_FORMAT_VERSION {
+        let (sections, _) =
+            read_header_sections(archive_bytes).ok_or(WizardError::UnrecognizedHeader)?;
+        match sections
+            .into_iter()
+            .find(|section| section.kind == SECTION_METADATA)
+        {
+            Some(section) => section.value,
+            None => return Ok(metadata),
+        }
+    } else if header.format_version == LEGACY_FORMAT_VERSION
+        || archive_bytes[FLAGS_OFFSET] & FLAG_METADATA == 0
+    {
+        return Ok(metadata);
+    } else {
+        // Длина раздела уже проверена разбором заголовка
+        &archive_bytes[header.encoded_len() + 4..body_offset]
+    };
+
+    let mut cursor = ScrollCursor::new(section, 0);
+    while cursor.remaining() > 0 {
+        let key = read_text(&mut cursor)?;
+        let value = read_text(&mut cursor)?;
+        if metadata.insert(key, value).is_some() {
+            return Err(WizardError::CorruptMetadata("ключ повторяется"));
+        }
+    }
+
+    Ok(metadata)
+}
+
+/// Кодирует пары с ключами по возрастанию
+fn encode_pairs(metadata: &HashMap<String, String>) -> WizardResult<Vec<u8>> {
+    let mut pairs: Vec<(&String, &String)> = metadata.iter().collect();
+    pairs.sort_unstable();
+
+    let mut section = Vec::new();
+    for (key, value) in pairs {
+        write_text(&mut section, key)?;
+        write_text(&mut section, value)?;
+    }
+
+    u32::try_from(section.len())
+        .map_err(|_| WizardError::CorruptMetadata("раздел длиннее 4 ГиБ"))?;
+    Ok(section)
+}
+
+/// Строка с длиной u32
+fn write_text(output: &mut Vec<u8>, text: &str) -> WizardResult<()> {
+    let text_len = u32::try_from(text.len())
+        .map_err(|_| WizardError::CorruptMetadata("строка длиннее 4 ГиБ"))?;
+    output.extend_from_slice(&text_len.to_le_bytes());
+    output.extend_from_slice(text.as_bytes());
+    Ok(())
+}
+
+/// Читает строку с длиной u32
+fn read_text(cursor: &mut ScrollCursor<'_>) -> WizardResult<String> {
+    let text_len = cursor.read_u32()? as usize;
+    let text_bytes = cursor.read_slice(text_len)?;
+    String::from_utf8(text_bytes.to_vec())
+        .map_err(|_| WizardError::CorruptMetadata("строка не в UTF-8"))
+}
+
+/// Тесты рун метаданных 🎯
+#[cfg(test)]
+mod metadata_runes_tests {
+    use super::*;
+    use crate::format::container_runes::{write_header_section, SECTIONS_OFFSET};
+    use crate::simple_api::{compress_data, decompress_data};
+
+    fn sample_metadata() -> HashMap<String, String> {
+        HashMap::from([
+            ("content-type".to_string(), "text/plain".to_string()),
+            ("filename".to_string(), "заклинание.txt".to_string()),
+            ("created".to_string(), "2024-05-01T12:00:00Z".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_metadata_roundtrip() {
+        let archive = compress_data("the owl keeps the scroll");
+        assert!(read_metadata(&archive).unwrap().is_empty());
+
+        let tagged = attach_metadata(&archive, &sample_metadata()).unwrap();
+        assert_eq!(read_metadata(&tagged).unwrap(), sample_metadata());
+        assert_eq!(decompress_data(&tagged), b"the owl keeps the scroll");
+
+        // Замена и удаление раздела
+        let retagged =
+            attach_metadata(&tagged, &HashMap::from([("k".into(), "v".into())])).unwrap();
+        assert_eq!(read_metadata(&retagged).unwrap().len(), 1);
+        assert_eq!(
+            attach_metadata(&retagged, &HashMap::new()).unwrap(),
+            archive
+        );
+    }
+
+    #[test]
+    fn test_unknown_sections_survive_retagging() {
+        let archive = compress_data("the owl keeps the scroll");
+        let mut extended = archive[..SECTIONS_OFFSET].to_vec();
+        write_header_section(&mut extended, 0x42, b"future");
+        extended.extend_from_slice(&archive[SECTIONS_OFFSET..]);
+
+        let tagged = attach_metadata(&extended, &sample_metadata()).unwrap();
+        assert_eq!(read_metadata(&tagged).unwrap(), sample_metadata());
+        assert_eq!(decompress_data(&tagged), b"the owl keeps the scroll");
+        assert_eq!(attach_metadata(&tagged, &HashMap::new()).unwrap(), extended);
+    }
+
+    #[test]
+    fn test_damaged_metadata_is_reported() {
+        let tagged = attach_metadata(compress_data("abc"), &sample_metadata()).unwrap();
+        let section_start = ArchiveHeader::parse(&tagged).unwrap().0.encoded_len();
+
+        // Длина раздела больше архива: заголовок не распознается
+        let mut oversized = tagged.clone();
+        oversized[section_start..section_start + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            read_metadata(&oversized),
+            Err(WizardError::UnrecognizedHeader)
+        );
+
+        // Первый байт ключа вне UTF-8
+        let mut invalid = tagged.clone();
+        invalid[section_start + 8] = 0xFF;
+        assert_eq!(
+            read_metadata(&invalid),
+            Err(WizardError::CorruptMetadata("строка не в UTF-8"))
+        );
+    }
+}
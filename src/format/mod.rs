@@ -0,0 +1,109 @@
+//! Модуль формата контейнера 📦
+//! Заголовки архивов и проверка совместимости
+
+pub mod alphabet_atlas;
+pub mod artifact_scroll;
+pub mod artifact_view;
+pub mod block_ledger;
+pub mod block_stream;
+pub mod compatibility_scrying;
+pub mod container_runes;
+pub mod dictionary_scroll;
+pub mod frame_shards;
+pub mod header_glimpse;
+pub mod identity_runes;
+pub mod metadata_runes;
+pub mod parity_runes;
+pub mod provenance_runes;
+pub mod sealed_scroll;
+pub mod stats_runes;
+pub mod tome_binding;
+
+// Экспорт основных типов и функций
+
+pub use alphabet_atlas::{
+    AlphabetMap,               // Атлас алфавита
+    ALPHABET_COMPACTION_LIMIT, // Порог сжатия алфавита
+};
+pub use artifact_scroll::{
+    parse_artifact,                  // Проверяемый разбор артефакта
+    parse_artifact_ref,              // Разбор без копирования
+    parse_artifact_with_options,     // Разбор с явными лимитами
+    serialize_artifact,              // Сериализация артефакта
+    serialize_artifact_with_options, // Сериализация с параметрами
+    try_serialize_artifact,          // Проверяемая сериализация
+    ScrollOptions,                   // Параметры сериализации
+    MAX_LENGTH_FIELD,                // Наибольшее поле длины кадра
+};
+pub use artifact_view::{
+    ArtifactRef,       // Заимствованный артефакт
+    FrequencyCodexRef, // Таблица частот в буфере архива
+};
+pub use block_ledger::{
+    parse_block_ledger, // Разбор реестра блоков
+    BlockRef,           // Блок в буфере архива
+    DEFAULT_BLOCK_SIZE, // Размер блока по умолчанию
+    SYNC_MARKER,        // Маркер синхронизации между блоками
+};
+pub use block_stream::{
+    BlockStreamReader, // Распаковка блоков из потока
+    BlockStreamWriter, // Сжатие блоков при записи
+};
+pub use compatibility_scrying::{
+    compatibility_report, // Сравнение двух архивов
+    ArchiveSide,          // Сторона сравнения
+    CompatibilityReport,  // Отчет о совместимости
+    Incompatibility,      // Причина несовместимости
+};
+pub use container_runes::{
+    ArchiveHeader,      // Заголовок архива
+    EngineKind,         // Движок сжатия
+    ModelKind,          // Вид модели
+    ARCHIVE_MAGIC,      // Сигнатура архива
+    CUSTOM_ENGINE_BASE, // Первый код сторонних движков
+    FORMAT_VERSION,     // Текущая версия формата
+};
+pub use dictionary_scroll::{
+    read_dictionary, // Словарь архива без распаковки
+    DictEntry,       // Запись словаря
+};
+pub use frame_shards::{
+    concat,     // Склейка частей в один архив
+    split,      // Разрезание архива по блокам
+    try_concat, // Проверяемая склейка частей
+};
+pub use header_glimpse::{
+    peek_header, // Сводка заголовка без разбора тела
+    HeaderInfo,  // Сводка заголовка
+};
+pub use identity_runes::content_id; // Идентификатор содержимого без штампов
+pub use metadata_runes::{
+    attach_metadata, // Прикрепление метаданных
+    read_metadata,   // Чтение метаданных без распаковки
+};
+pub use parity_runes::{
+    attach_parity,  // Прикрепление четности заголовка и модели
+    repair_parity,  // Исправление порчи без распаковки
+    PARITY_STRIPES, // Число полос защищенной области
+};
+pub use provenance_runes::{
+    attach_provenance, // Прикрепление штампа происхождения
+    read_provenance,   // Чтение штампа без распаковки
+    Provenance,        // Время создания и производитель кадра
+    PRODUCER,          // Имя и версия библиотеки
+};
+pub use sealed_scroll::{
+    AtomicFile,  // Файл, появляющийся только целиком
+    FsyncPolicy, // Когда сбрасывать запись на диск
+};
+pub use stats_runes::{
+    attach_stats, // Прикрепление статистики сжатия
+    read_stats,   // Чтение статистики без распаковки
+    ArchiveStats, // Сводка сжатия в заголовке
+};
+pub use tome_binding::{
+    ArchiveReader, // Чтение записей тома по оглавлению
+    ArchiveWriter, // Дописывание записей в том
+    EntryInfo,     // Запись оглавления тома
+    TOME_MAGIC,    // Сигнатура тома
+};
@@ -0,0 +1,367 @@
+//! Руны четности: защита заголовка и модели 🛡️
+//!
+//! Один перевернутый бит в словаре или таблице частот портит весь поток:
+//! декодер читает другую модель. Необязательный раздел заголовка `0x04`
+//! хранит четность этих байтов, и распаковка исправляет такую порчу
+//! сама, до разбора заголовка.
+//!
+//! Защищенная область — кадр без раздела четности от начала до битового
+//! потока: заголовок, словарь, фразы, таблица частот и поля потока. У
+//! кадров других движков защищается только заголовок, у архива из блоков —
+//! заголовок и реестр, а кадр каждого блока получает свой раздел. Область
+//! делится на [`PARITY_STRIPES`] полос подряд; раздел хранит хеш каждой
+//! полосы и их XOR. Полоса с неверным хешем собирается заново из
+//! четности и остальных полос, поэтому исправляется любая порча в
+//! пределах одной полосы — от бита до пачки байтов.
+//!
+//! Раздел всегда первый, чтобы найти его можно было и в поврежденном
+//! заголовке. Значение: `[версия u8][длина области u32][число полос u8]
+//! [хеш полосы u32 × число][XOR полос]`. Старые декодеры раздел
+//! пропускают. Замена метаданных или статистики удаляет раздел: он
+//! защищает прежние байты.
+
+use super::artifact_scroll::{parse_artifact_ref_body, ScrollCursor};
+use super::block_ledger::{
+    read_block_ledger, write_block_archive, write_synced_block_archive, BLOCK_ENTRY_LEN,
+    STREAMED_BLOCK_COUNT, SYNCED_BLOCK_COUNT,
+};
+use super::container_runes::{
+    replace_header_section, write_header_section, ArchiveHeader, SECTIONS_OFFSET, SECTION_PARITY,
+    TLV_FORMAT_VERSION,
+};
+use crate::bit_wizardry::checksum_runes::rune_hash;
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::error::{WizardError, WizardResult};
+use std::borrow::Cow;
+
+/// Число полос защищенной области
+pub const PARITY_STRIPES: usize = 8;
+
+/// Версия раскладки раздела четности
+const PARITY_VERSION: u8 = 1;
+
+/// Тип и длина раздела перед его значением
+const SECTION_PREFIX_LEN: usize = 1 + 4;
+
+/// Прикрепляет четность к архиву 🛡️
+///
+/// Прежняя четность заменяется. У архива из блоков раздел получает и
+/// кадр каждого блока, а потоковый реестр становится обычным. Архивы
+/// версий до 6 дают
+/// [`WizardError::UnsupportedVersion`].
+///
+/// ```
+/// use arithmetic_compression_wizard::format::attach_parity;
+/// use arithmetic_compression_wizard::simple_api::{compress_data, decompress_data};
+///
+/// let mut archive = attach_parity(compress_data(b"the owl and the scroll")).unwrap();
+/// archive[20] ^= 0b100; // бит в защищенной области
+/// assert_eq!(decompress_data(&archive), b"the owl and the scroll");
+/// ```
+pub fn attach_parity(archive_bytes: impl AsRef<[u8]>) -> WizardResult<Vec<u8>> {
+    let archive_bytes = archive_bytes.as_ref();
+    let (header, body_offset) =
+        ArchiveHeader::parse(archive_bytes).ok_or(WizardError::UnrecognizedHeader)?;
+    if header.format_version < TLV_FORMAT_VERSION {
+        return Err(WizardError::UnsupportedVersion(header.format_version));
+    }
+    if !header.block_framed {
+        return protect_frame(archive_bytes);
+    }
+
+    // Кадры блоков получают свои разделы, реестр — новые длины
+    let body = &archive_bytes[body_offset..];
+    let protected = read_block_ledger(&header, body, &DecodeOptions::unlimited())?
+        .iter()
+        .map(|block| Ok((block.original_len, protect_frame(block.frame)?)))
+        .collect::<WizardResult<Vec<_>>>()?;
+    let relaid = if body.starts_with(&SYNCED_BLOCK_COUNT.to_le_bytes()) {
+        write_synced_block_archive(&protected)
+    } else {
+        write_block_archive(&protected)
+    };
+    let (_, relaid_offset) = ArchiveHeader::parse(&relaid).expect("свежий реестр имеет заголовок");
+
+    let mut rebuilt = archive_bytes[..body_offset].to_vec();
+    rebuilt.extend_from_slice(&relaid[relaid_offset..]);
+    protect_frame(&rebuilt)
+}
+
+/// Исправляет порчу, которую покрывает четность, без распаковки 🩹
+///
+/// Возвращает архив с исправленными кадрами (архив без четности — как
+/// есть). Порча больше одной полосы кадра не исправляется; такой кадр
+/// остается прежним, и ошибку сообщит распаковка.
+pub fn repair_parity(archive_bytes: impl AsRef<[u8]>) -> WizardResult<Vec<u8>> {
+    let mut repaired = restore_parity(archive_bytes.as_ref()).into_owned();
+    let (header, body_offset) =
+        ArchiveHeader::parse(&repaired).ok_or(WizardError::UnrecognizedHeader)?;
+    if !header.block_framed {
+        return Ok(repaired);
+    }
+
+    // Исправление сохраняет длины, поэтому кадры заменяются на месте
+    let fixes: Vec<(usize, Vec<u8>)> = read_block_ledger(
+        &header,
+        &repaired[body_offset..],
+        &DecodeOptions::unlimited(),
+    )?
+    .iter()
+    .filter_map(|block| match restore_parity(block.frame) {
+        Cow::Owned(frame) => Some((
+            block.frame.as_ptr() as usize - repaired.as_ptr() as usize,
+            frame,
+        )),
+        Cow::Borrowed(_) => None,
+    })
+    .collect();
+    for (offset, frame) in fixes {
+        repaired[offset..offset + frame.len()].copy_from_slice(&frame);
+    }
+    Ok(repaired)
+}
+
+/// Дописывает раздел четности одному кадру (прежний раздел удаляется)
+fn protect_frame(frame_bytes: &[u8]) -> WizardResult<Vec<u8>> {
+    let unprotected = replace_header_section(frame_bytes, SECTION_PARITY, None)?;
+    let (header, body_offset) =
+        ArchiveHeader::parse(&unprotected).ok_or(WizardError::UnrecognizedHeader)?;
+    let protected_len = body_offset + protected_body_len(&header, &unprotected[body_offset..]);
+    let region = &unprotected[..protected_len];
+
+    let stripe_len = protected_len.div_ceil(PARITY_STRIPES);
+    let mut value = Vec::with_capacity(6 + PARITY_STRIPES * 4 + stripe_len);
+    value.push(PARITY_VERSION);
+    value.extend_from_slice(&(protected_len as u32).to_le_bytes());
+    value.push(PARITY_STRIPES as u8);
+    for stripe in stripes(region, stripe_len) {
+        value.extend_from_slice(&stripe_check(stripe).to_le_bytes());
+    }
+    value.extend(xor_stripes(stripes(region, stripe_len), stripe_len));
+
+    let mut result = Vec::with_capacity(unprotected.len() + SECTION_PREFIX_LEN + value.len());
+    result.extend_from_slice(&unprotected[..SECTIONS_OFFSET]);
+    write_header_section(&mut result, SECTION_PARITY, &value);
+    result.extend_from_slice(&unprotected[SECTIONS_OFFSET..]);
+    Ok(result)
+}
+
+/// Сколько байтов тела защищается: модель кадра или реестр блоков
+fn protected_body_len(header: &ArchiveHeader, body: &[u8]) -> usize {
+    if header.block_framed {
+        let mut cursor = ScrollCursor::new(body, 0);
+        return match cursor.read_u32() {
+            Ok(STREAMED_BLOCK_COUNT | SYNCED_BLOCK_COUNT) => 4,
+            Ok(block_count) => (4 + block_count as usize * BLOCK_ENTRY_LEN).min(body.len()),
+            Err(_) => 0,
+        };
+    }
+    match parse_artifact_ref_body(header, body, &DecodeOptions::unlimited()) {
+        Ok(artifact_ref) => {
+            artifact_ref.compressed_bit_stream.as_ptr() as usize - body.as_ptr() as usize
+        }
+        Err(_) => 0,
+    }
+}
+
+/// Кадр с исправленной порчей одной полосы 🩹
+///
+/// Кадр без раздела четности, без порчи или с неисправимой порчей
+/// возвращается как есть. Полоса заменяется, только если ее хеш после
+/// сборки совпал с записанным.
+pub(crate) fn restore_parity(frame_bytes: &[u8]) -> Cow<'_, [u8]> {
+    try_restore_parity(frame_bytes).map_or(Cow::Borrowed(frame_bytes), Cow::Owned)
+}
+
+/// Исправленный кадр или `None`, если исправлять нечего или нечем
+fn try_restore_parity(frame_bytes: &[u8]) -> Option<Vec<u8>> {
+    if *frame_bytes.get(SECTIONS_OFFSET)? != SECTION_PARITY {
+        return None;
+    }
+    let mut cursor = ScrollCursor::new(frame_bytes, SECTIONS_OFFSET + 1);
+    let value_len = cursor.read_u32().ok()? as usize;
+    let value = cursor.read_slice(value_len).ok()?;
+    let section_end = cursor.position();
+
+    let mut value_cursor = ScrollCursor::new(value, 0);
+    if value_cursor.read_slice(1).ok()?[0] != PARITY_VERSION {
+        return None;
+    }
+    let protected_len = value_cursor.read_u32().ok()? as usize;
+    let stripe_count = value_cursor.read_slice(1).ok()?[0] as usize;
+    if stripe_count == 0 {
+        return None;
+    }
+    let checks = value_cursor.read_slice(stripe_count * 4).ok()?;
+    let stripe_len = protected_len.div_ceil(stripe_count);
+    let parity = value_cursor.read_slice(stripe_len).ok()?;
+
+    // Область без раздела четности
+    let mut region = frame_bytes[..SECTIONS_OFFSET].to_vec();
+    region.extend_from_slice(frame_bytes.get(section_end..)?);
+    region.truncate(protected_len);
+    if region.len() != protected_len {
+        return None;
+    }
+
+    let stored_check =
+        |index: usize| u32::from_le_bytes(checks[index * 4..index * 4 + 4].try_into().unwrap());
+    let mut damaged = stripes(&region, stripe_len)
+        .enumerate()
+        .filter(|&(index, stripe)| stripe_check(stripe) != stored_check(index))
+        .map(|(index, _)| index);
+    let (Some(index), None) = (damaged.next(), damaged.next()) else {
+        return None;
+    };
+
+    let start = index * stripe_len;
+    let end = (start + stripe_len).min(protected_len);
+    let others = stripes(&region, stripe_len)
+        .enumerate()
+        .filter(|&(other, _)| other != index)
+        .map(|(_, stripe)| stripe)
+        .chain([parity]);
+    let rebuilt = xor_stripes(others, stripe_len);
+    let rebuilt = &rebuilt[..end - start];
+    if stripe_check(rebuilt) != stored_check(index) {
+        return None;
+    }
+
+    // Полоса на месте в кадре: до раздела или после него
+    let mut repaired = frame_bytes.to_vec();
+    for (position, &byte) in (start..end).zip(rebuilt) {
+        let target = if position < SECTIONS_OFFSET {
+            position
+        } else {
+            position - SECTIONS_OFFSET + section_end
+        };
+        repaired[target] = byte;
+    }
+    Some(repaired)
+}
+
+/// Полосы области подряд (последняя может быть короче)
+fn stripes(region: &[u8], stripe_len: usize) -> impl Iterator<Item = &[u8]> {
+    region.chunks(stripe_len.max(1))
+}
+
+/// XOR полос, дополненных нулями до `stripe_len`
+fn xor_stripes<'a>(stripes: impl Iterator<Item = &'a [u8]>, stripe_len: usize) -> Vec<u8> {
+    let mut parity = vec![0u8; stripe_len];
+    for stripe in stripes {
+        for (acc, &byte) in parity.iter_mut().zip(stripe) {
+            *acc ^= byte;
+        }
+    }
+    parity
+}
+
+/// Хеш полосы
+fn stripe_check(stripe: &[u8]) -> u32 {
+    rune_hash(stripe) as u32
+}
+
+/// Тесты рун четности 🎯
+#[cfg(test)]
+mod parity_runes_tests {
+    use super::*;
+    use crate::format::metadata_runes::{attach_metadata, read_metadata};
+    use crate::simple_api::{
+        compress_data, compress_data_in_blocks, compress_data_in_synced_blocks, decompress_data,
+        try_decompress_data,
+    };
+    use std::collections::HashMap;
+
+    fn sample() -> String {
+        "the wizard and the owl read the ancient scroll. ".repeat(40)
+    }
+
+    #[test]
+    fn test_any_single_flip_in_model_is_repaired() {
+        let data = sample();
+        let plain = compress_data(&data);
+        let archive = attach_parity(&plain).unwrap();
+        assert_eq!(decompress_data(&archive), data.as_bytes());
+        assert_eq!(repair_parity(&archive).unwrap(), archive);
+
+        // Каждый байт заголовка и модели вне раздела четности
+        let (header, body_offset) = ArchiveHeader::parse(&plain).unwrap();
+        let model_end = body_offset + protected_body_len(&header, &plain[body_offset..]);
+        let parity_len = archive.len() - plain.len();
+        for position in
+            (0..SECTIONS_OFFSET).chain(SECTIONS_OFFSET + parity_len..model_end + parity_len)
+        {
+            let mut damaged = archive.clone();
+            damaged[position] ^= 0x10;
+            assert_eq!(
+                try_decompress_data(&damaged, &DecodeOptions::default()).as_deref(),
+                Ok(data.as_bytes()),
+                "байт {position}"
+            );
+            assert_eq!(repair_parity(&damaged).unwrap(), archive);
+        }
+    }
+
+    #[test]
+    fn test_blocks_are_protected_one_by_one() {
+        let data = sample();
+        for archive in [
+            compress_data_in_blocks(&data, 500),
+            compress_data_in_synced_blocks(&data, 500),
+        ] {
+            let protected = attach_parity(&archive).unwrap();
+            let (header, body_offset) = ArchiveHeader::parse(&protected).unwrap();
+            let blocks = read_block_ledger(
+                &header,
+                &protected[body_offset..],
+                &DecodeOptions::default(),
+            )
+            .unwrap();
+            assert_eq!(blocks.len(), 4);
+            assert!(blocks
+                .iter()
+                .all(|block| block.frame[SECTIONS_OFFSET] == SECTION_PARITY));
+
+            // По перевернутому биту в заголовке архива и в словаре третьего блока
+            let third = blocks[2].frame.as_ptr() as usize - protected.as_ptr() as usize;
+            let mut damaged = protected.clone();
+            damaged[1] ^= 1;
+            damaged[third + blocks[2].frame.len() / 4] ^= 1;
+            assert_eq!(decompress_data(&damaged), data.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_unrepairable_and_invalidated_parity() {
+        let data = sample();
+        let archive = attach_parity(compress_data(&data)).unwrap();
+
+        // Порча первой и последней полос: кадр остается прежним
+        let plain = compress_data(&data);
+        let (header, body_offset) = ArchiveHeader::parse(&plain).unwrap();
+        let model_end = body_offset + protected_body_len(&header, &plain[body_offset..]);
+        let mut damaged = archive.clone();
+        damaged[0] ^= 1;
+        damaged[model_end - 1 + archive.len() - plain.len()] ^= 1;
+        assert_eq!(repair_parity(&damaged).unwrap(), damaged);
+        assert!(try_decompress_data(&damaged, &DecodeOptions::default()).is_err());
+
+        // Метаданные заменяют заголовок и снимают четность
+        let tagged = attach_metadata(
+            &archive,
+            &HashMap::from([("name".to_string(), "scroll.txt".to_string())]),
+        )
+        .unwrap();
+        assert_ne!(tagged[SECTIONS_OFFSET], SECTION_PARITY);
+        assert_eq!(read_metadata(&tagged).unwrap()["name"], "scroll.txt");
+        assert_eq!(
+            decompress_data(attach_parity(&tagged).unwrap()),
+            data.as_bytes()
+        );
+
+        assert_eq!(
+            attach_parity(b"legacy archive without magic!!"),
+            Err(WizardError::UnsupportedVersion(0))
+        );
+    }
+}
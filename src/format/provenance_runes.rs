@@ -0,0 +1,203 @@
+//! Руны происхождения 🕰️
+//!
+//! Необязательный раздел заголовка `0x05`: когда и какой программой
+//! создан кадр. Службам соответствия нужно знать, откуда взялся архив,
+//! а сборкам и кэшам — одинаковые байты для одинаковых данных, поэтому
+//! штамп ставится только с `EngineConfig::with_reproducible(false)`;
+//! по умолчанию кадры воспроизводимы и раздела не несут.
+//!
+//! Значение раздела: `[версия u8][время создания u64, секунды Unix]
+//! [длина имени u32][имя производителя UTF-8]`. Следующие версии только
+//! дописывают поля в конец. Старые декодеры пропускают весь раздел.
+
+use super::artifact_scroll::ScrollCursor;
+use super::container_runes::{
+    read_header_sections, replace_header_section, ArchiveHeader, HEADER_SECTION_OVERHEAD,
+    SECTION_PROVENANCE, TLV_FORMAT_VERSION,
+};
+use crate::error::{WizardError, WizardResult};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Версия раскладки раздела происхождения
+const PROVENANCE_VERSION: u8 = 1;
+
+/// Имя и версия этой библиотеки — производитель по умолчанию
+pub const PRODUCER: &str = concat!("arithmetic-compression-wizard/", env!("CARGO_PKG_VERSION"));
+
+/// Длина раздела штампа [`Provenance::now`]: версия, время, длина и
+/// имя производителя
+pub(crate) const PROVENANCE_SECTION_LEN: usize =
+    HEADER_SECTION_OVERHEAD + 1 + 8 + 4 + PRODUCER.len();
+
+/// Штамп происхождения кадра 🕰️
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Provenance {
+    /// Время создания, секунды от эпохи Unix
+    pub created_unix_secs: u64,
+    /// Программа-производитель, например [`PRODUCER`]
+    pub producer: String,
+}
+
+impl Provenance {
+    /// Штамп «сейчас, этой библиотекой»
+    pub fn now() -> Self {
+        let created_unix_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        Self {
+            created_unix_secs,
+            producer: PRODUCER.to_string(),
+        }
+    }
+
+    /// Байты значения раздела
+    fn encode(&self) -> Vec<u8> {
+        let mut value = Vec::with_capacity(1 + 8 + 4 + self.producer.len());
+        value.push(PROVENANCE_VERSION);
+        value.extend_from_slice(&self.created_unix_secs.to_le_bytes());
+        value.extend_from_slice(&(self.producer.len() as u32).to_le_bytes());
+        value.extend_from_slice(self.producer.as_bytes());
+        value
+    }
+
+    /// Разбирает значение раздела (поля новых версий пропускаются)
+    fn decode(value: &[u8]) -> WizardResult<Self> {
+        let mut cursor = ScrollCursor::new(value, 0);
+        if cursor.read_slice(1)?[0] < PROVENANCE_VERSION {
+            return Err(WizardError::CorruptMetadata(
+                "неизвестная версия происхождения",
+            ));
+        }
+        let created_unix_secs = cursor.read_u64()?;
+        let producer_len = cursor.read_u32()? as usize;
+        let producer = std::str::from_utf8(cursor.read_slice(producer_len)?)
+            .map_err(|_| WizardError::CorruptMetadata("имя производителя не UTF-8"))?;
+
+        Ok(Self {
+            created_unix_secs,
+            producer: producer.to_string(),
+        })
+    }
+}
+
+/// Прикрепляет штамп происхождения к архиву 🕰️
+///
+/// Прежний штамп заменяется. Раздел есть только у архивов с заголовком
+/// из разделов (версия 6+); более старые дают
+/// [`WizardError::UnsupportedVersion`].
+pub fn attach_provenance(
+    archive_bytes: impl AsRef<[u8]>,
+    provenance: &Provenance,
+) -> WizardResult<Vec<u8>> {
+    let archive_bytes = archive_bytes.as_ref();
+    let (header, _) = ArchiveHeader::parse(archive_bytes).ok_or(WizardError::UnrecognizedHeader)?;
+    if header.format_version < TLV_FORMAT_VERSION {
+        return Err(WizardError::UnsupportedVersion(header.format_version));
+    }
+    replace_header_section(
+        archive_bytes,
+        SECTION_PROVENANCE,
+        Some(&provenance.encode()),
+    )
+}
+
+/// Читает штамп происхождения без распаковки 🔍
+///
+/// Воспроизводимый архив (и любой архив версий до 6) дает `None`.
+pub fn read_provenance(archive_bytes: impl AsRef<[u8]>) -> WizardResult<Option<Provenance>> {
+    let archive_bytes = archive_bytes.as_ref();
+    let (header, _) = ArchiveHeader::parse(archive_bytes).ok_or(WizardError::UnrecognizedHeader)?;
+    if header.format_version < TLV_FORMAT_VERSION {
+        return Ok(None);
+    }
+
+    let (sections, _) =
+        read_header_sections(archive_bytes).ok_or(WizardError::UnrecognizedHeader)?;
+    sections
+        .iter()
+        .find(|section| section.kind == SECTION_PROVENANCE)
+        .map(|section| Provenance::decode(section.value))
+        .transpose()
+}
+
+/// Тесты рун происхождения 🎯
+#[cfg(test)]
+mod provenance_runes_tests {
+    use super::*;
+    use crate::compression_engine::engine_covenant::EngineConfig;
+    use crate::format::metadata_runes::{attach_metadata, read_metadata};
+    use crate::simple_api::{compress_data, decompress_data, try_compress_with_config};
+    use std::collections::HashMap;
+
+    const SCROLL: &[u8] = b"the archivist stamps every scroll leaving the tower";
+
+    #[test]
+    fn test_stamp_only_without_reproducible() {
+        let reproducible = try_compress_with_config(SCROLL, &EngineConfig::default()).unwrap();
+        assert_eq!(read_provenance(&reproducible).unwrap(), None);
+        assert_eq!(
+            reproducible,
+            try_compress_with_config(SCROLL, &EngineConfig::default()).unwrap()
+        );
+
+        let before = Provenance::now().created_unix_secs;
+        let stamped =
+            try_compress_with_config(SCROLL, &EngineConfig::default().with_reproducible(false))
+                .unwrap();
+        let provenance = read_provenance(&stamped).unwrap().unwrap();
+        assert_eq!(provenance.producer, PRODUCER);
+        assert!(provenance.created_unix_secs >= before);
+        assert_eq!(decompress_data(&stamped), SCROLL);
+
+        // Тело то же: штамп живет только в заголовке
+        let body_offset = ArchiveHeader::parse(&reproducible).unwrap().1;
+        assert!(stamped.ends_with(&reproducible[body_offset..]));
+    }
+
+    #[test]
+    fn test_stamp_replaces_and_coexists_with_metadata() {
+        let archive = compress_data(SCROLL);
+        let provenance = Provenance {
+            created_unix_secs: 1_700_000_000,
+            producer: "backup-daemon/2.1".to_string(),
+        };
+        let once = attach_provenance(&archive, &provenance).unwrap();
+        assert_eq!(attach_provenance(&once, &provenance).unwrap(), once);
+
+        let metadata = HashMap::from([("owner".to_string(), "tower".to_string())]);
+        let tagged = attach_metadata(&once, &metadata).unwrap();
+        assert_eq!(read_provenance(&tagged).unwrap(), Some(provenance.clone()));
+        assert_eq!(read_metadata(&tagged).unwrap(), metadata);
+
+        // Поля будущих версий пропускаются
+        let mut longer = provenance.encode();
+        longer.extend_from_slice(&[0xEE; 5]);
+        let extended = replace_header_section(&archive, SECTION_PROVENANCE, Some(&longer)).unwrap();
+        assert_eq!(read_provenance(&extended).unwrap(), Some(provenance));
+        assert_eq!(decompress_data(&extended), SCROLL);
+    }
+
+    #[test]
+    fn test_damaged_stamps_rejected() {
+        let archive = compress_data(SCROLL);
+        let with_value = |value: &[u8]| {
+            replace_header_section(&archive, SECTION_PROVENANCE, Some(value)).unwrap()
+        };
+        assert!(matches!(
+            read_provenance(with_value(&[PROVENANCE_VERSION, 1, 2])),
+            Err(WizardError::Truncated { .. })
+        ));
+
+        let mut version_zero = Provenance::now().encode();
+        version_zero[0] = 0;
+        let mut not_utf8 = Provenance::now().encode();
+        *not_utf8.last_mut().unwrap() = 0xFF;
+        for value in [version_zero, not_utf8] {
+            assert!(matches!(
+                read_provenance(with_value(&value)),
+                Err(WizardError::CorruptMetadata(_))
+            ));
+        }
+        assert_eq!(read_provenance([0u8; 24]).unwrap(), None);
+    }
+}
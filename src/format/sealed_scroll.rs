@@ -0,0 +1,234 @@
+//! Запечатанный свиток: файл появляется целиком или не появляется 🔏
+//!
+//! Задание, упавшее посреди записи, оставляет на диске обрубок архива,
+//! который спустя месяцы не распаковывается с невнятной ошибкой.
+//! [`AtomicFile`] пишет во временный файл рядом с целевым и только в
+//! [`AtomicFile::commit`] переименовывает его на место: переименование
+//! в пределах каталога атомарно, поэтому под целевым именем всегда либо
+//! прежний файл, либо новый целиком. Незавершенная запись при сбросе
+//! удаляет временный файл.
+//!
+//! Переименование защищает от падения процесса, но не от сбоя питания:
+//! для этого [`FsyncPolicy`] сбрасывает на диск данные файла, а
+//! [`FsyncPolicy::FileAndDirectory`] — и запись каталога о новом имени.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Когда сбрасывать записанное на диск 💾
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum FsyncPolicy {
+    /// Без fsync: после сбоя питания файл может оказаться пустым
+    Never,
+    /// fsync данных файла до переименования
+    #[default]
+    File,
+    /// Вдобавок fsync каталога после переименования: новое имя переживает
+    /// сбой питания (на Unix; на прочих системах — как [`FsyncPolicy::File`])
+    FileAndDirectory,
+}
+
+/// Номер временного файла, уникальный в пределах процесса
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Файл, который появляется под своим именем только после
+/// [`commit`](Self::commit) 🔏
+///
+/// ```
+/// use arithmetic_compression_wizard::format::AtomicFile;
+/// use std::io::Write;
+///
+/// let path = std::env::temp_dir().join(format!("acw-doc-{}.acw", std::process::id()));
+/// let mut file = AtomicFile::create(&path).unwrap();
+/// file.write_all(b"half an archive").unwrap();
+/// drop(file); // задание упало: под именем ничего не появилось
+/// assert!(!path.exists());
+///
+/// let mut file = AtomicFile::create(&path).unwrap();
+/// file.write_all(b"the whole archive").unwrap();
+/// file.commit().unwrap();
+/// assert_eq!(std::fs::read(&path).unwrap(), b"the whole archive");
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct AtomicFile {
+    /// Буферизованный временный файл (`None` после фиксации)
+    file: Option<BufWriter<File>>,
+    temp_path: PathBuf,
+    final_path: PathBuf,
+    fsync: FsyncPolicy,
+}
+
+impl AtomicFile {
+    /// Начинает запись в `path` с [`FsyncPolicy::File`]
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::create_with(path, FsyncPolicy::default())
+    }
+
+    /// Начинает запись в `path` с выбранной политикой fsync
+    ///
+    /// Временный файл создается в том же каталоге (скрытое имя с номером
+    /// процесса): переименование между файловыми системами не атомарно.
+    pub fn create_with(path: impl AsRef<Path>, fsync: FsyncPolicy) -> io::Result<Self> {
+        let final_path = path.as_ref().to_path_buf();
+        let file_name = final_path.file_name().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} не имя файла", final_path.display()),
+            )
+        })?;
+        let temp_name = format!(
+            ".{}.{}-{}.tmp",
+            file_name.to_string_lossy(),
+            std::process::id(),
+            TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        );
+        let temp_path = final_path.with_file_name(temp_name);
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)?;
+        Ok(Self {
+            file: Some(BufWriter::new(file)),
+            temp_path,
+            final_path,
+            fsync,
+        })
+    }
+
+    /// Целевой путь файла
+    pub fn path(&self) -> &Path {
+        &self.final_path
+    }
+
+    /// Дописывает буфер, сбрасывает данные по политике и ставит файл на
+    /// место, заменяя прежний
+    ///
+    /// При ошибке временный файл удаляется, а прежний файл остается.
+    pub fn commit(mut self) -> io::Result<()> {
+        let file = self.file.take().expect("файл фиксируется один раз");
+        let file = file.into_inner().map_err(io::IntoInnerError::into_error)?;
+        if self.fsync != FsyncPolicy::Never {
+            file.sync_all()?;
+        }
+        drop(file);
+        fs::rename(&self.temp_path, &self.final_path)?;
+        // Временного файла больше нет: сбросу нечего удалять
+        self.temp_path.clear();
+        if self.fsync == FsyncPolicy::FileAndDirectory {
+            sync_parent_directory(&self.final_path)?;
+        }
+        Ok(())
+    }
+
+    fn buffer(&mut self) -> &mut BufWriter<File> {
+        self.file.as_mut().expect("файл уже зафиксирован")
+    }
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.buffer().flush()
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        drop(self.file.take());
+        if !self.temp_path.as_os_str().is_empty() {
+            // Ошибку удалить некуда вернуть; имя целевого файла не тронуто
+            let _ = fs::remove_file(&self.temp_path);
+        }
+    }
+}
+
+/// fsync каталога, в котором лежит `path`
+#[cfg(unix)]
+fn sync_parent_directory(path: &Path) -> io::Result<()> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    File::open(parent)?.sync_all()
+}
+
+/// Каталоги вне Unix не открываются как файлы: fsync каталога пропускается
+#[cfg(not(unix))]
+fn sync_parent_directory(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Тесты запечатанного свитка 🎯
+#[cfg(test)]
+mod sealed_scroll_tests {
+    use super::*;
+    use crate::compression_engine::engine_covenant::EngineConfig;
+    use crate::simple_api::{compress_file, compress_file_with, decompress_data};
+    use crate::testkit::scratch_dir::ScratchDir;
+
+    #[test]
+    fn test_old_file_survives_until_commit() {
+        let dir = ScratchDir::new("sealed-commit");
+        let path = dir.0.join("nightly.acw");
+        fs::write(&path, b"yesterday").unwrap();
+
+        for fsync in [
+            FsyncPolicy::Never,
+            FsyncPolicy::File,
+            FsyncPolicy::FileAndDirectory,
+        ] {
+            let mut file = AtomicFile::create_with(&path, fsync).unwrap();
+            file.write_all(b"half of today").unwrap();
+            file.flush().unwrap();
+            assert_eq!(fs::read(&path).unwrap(), b"yesterday");
+            assert_eq!(dir.entries().len(), 2);
+            drop(file);
+            assert_eq!(dir.entries(), ["nightly.acw"]);
+        }
+
+        let mut first = AtomicFile::create(&path).unwrap();
+        let mut second = AtomicFile::create(&path).unwrap();
+        first.write_all(b"first").unwrap();
+        second.write_all(b"second").unwrap();
+        first.commit().unwrap();
+        second.commit().unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"second");
+        assert_eq!(dir.entries(), ["nightly.acw"]);
+
+        assert_eq!(
+            AtomicFile::create(dir.0.join("..")).unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+    }
+
+    #[test]
+    fn test_compress_file_leaves_nothing_on_failure() {
+        let dir = ScratchDir::new("sealed-compress");
+        let input = dir.0.join("scroll.txt");
+        let output = dir.0.join("scroll.txt.acw");
+        let text = b"the apprentice copies the scroll. ".repeat(3000);
+        fs::write(&input, &text).unwrap();
+
+        compress_file(&input, &output, &EngineConfig::default()).unwrap();
+        assert_eq!(decompress_data(fs::read(&output).unwrap()), text);
+
+        // Вход пропал: прежний архив на месте, временных файлов нет
+        let missing = dir.0.join("missing.txt");
+        let error = compress_file_with(
+            &missing,
+            &output,
+            &EngineConfig::default(),
+            FsyncPolicy::FileAndDirectory,
+        )
+        .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert_eq!(decompress_data(fs::read(&output).unwrap()), text);
+        assert_eq!(dir.entries(), ["scroll.txt", "scroll.txt.acw"]);
+    }
+}
@@ -0,0 +1,283 @@
+//! Руны статистики 📈
+//!
+//! Небольшой необязательный раздел заголовка `0x03` со сводкой сжатия:
+//! размеры, энтропия входа, вклад словаря и параметры движка. Аналитика
+//! собирает эффективность сжатия по всему парку архивов, читая только
+//! заголовки, — без распаковки и повторного анализа данных.
+//!
+//! Значение раздела: `[версия u8][длина входа u64][длина тела u64]
+//! [энтропия f32][слова u32][фразы u32][экономия словаря f32][движок u8]
+//! [модель u8][период полураспада u32]`. Следующие версии только дописывают
+//! поля в конец, поэтому читатель разбирает известное начало и пропускает
+//! остальное. Старые декодеры пропускают весь раздел.
+
+use super::artifact_scroll::ScrollCursor;
+use super::container_runes::{
+    read_header_sections, replace_header_section, ArchiveHeader, EngineKind, ModelKind,
+    SECTION_STATS, TLV_FORMAT_VERSION,
+};
+use crate::compression_engine::compression_conjurer::CompressionArtifact;
+use crate::compression_engine::frequency_census::shannon_entropy;
+use crate::error::{WizardError, WizardResult};
+
+/// Версия раскладки раздела статистики
+const STATS_VERSION: u8 = 1;
+
+/// Длина значения раздела версии 1
+const STATS_V1_LEN: usize = 1 + 8 + 8 + 4 + 4 + 4 + 4 + 1 + 1 + 4;
+
+/// Сводка сжатия в заголовке архива 📈
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArchiveStats {
+    /// Длина исходных данных
+    pub original_len: u64,
+    /// Длина тела архива (без заголовка)
+    pub payload_len: u64,
+    /// Энтропия Шеннона входа, бит на байт
+    pub entropy_bits_per_byte: f32,
+    /// Число слов словаря
+    pub dictionary_words: u32,
+    /// Число фраз словаря
+    pub dictionary_phrases: u32,
+    /// Экономия словаря в байтах, измеренная после кодирования
+    pub dictionary_savings: f32,
+    /// Движок, сжавший данные
+    pub engine: EngineKind,
+    /// Модель движка
+    pub model_kind: ModelKind,
+}
+
+impl ArchiveStats {
+    /// Сводка для архива встроенного арифметического движка
+    pub(crate) fn measure(
+        original: &[u8],
+        artifact: &CompressionArtifact,
+        payload_len: usize,
+    ) -> Self {
+        Self {
+            original_len: original.len() as u64,
+            payload_len: payload_len as u64,
+            entropy_bits_per_byte: shannon_entropy(original) as f32,
+            dictionary_words: artifact.mystical_word_grimoire.len() as u32,
+            dictionary_phrases: artifact.mystical_phrase_codex.len() as u32,
+            dictionary_savings: artifact
+                .word_effectiveness()
+                .iter()
+                .map(|entry| entry.bytes_saved)
+                .sum::<f64>() as f32,
+            engine: EngineKind::Arithmetic,
+            model_kind: ModelKind::StaticOrder0,
+        }
+    }
+
+    /// Байты значения раздела
+    fn encode(&self) -> Vec<u8> {
+        // Параметр модели: период полураспада или размер таблицы контекстов
+        let model_param = match self.model_kind {
+            ModelKind::Adaptive { half_life } => half_life,
+            ModelKind::HashedOrder2 { table_bits } => table_bits as u32,
+            _ => 0,
+        };
+
+        let mut value = Vec::with_capacity(STATS_V1_LEN);
+        value.push(STATS_VERSION);
+        value.extend_from_slice(&self.original_len.to_le_bytes());
+        value.extend_from_slice(&self.payload_len.to_le_bytes());
+        value.extend_from_slice(&self.entropy_bits_per_byte.to_le_bytes());
+        value.extend_from_slice(&self.dictionary_words.to_le_bytes());
+        value.extend_from_slice(&self.dictionary_phrases.to_le_bytes());
+        value.extend_from_slice(&self.dictionary_savings.to_le_bytes());
+        value.push(self.engine.to_byte());
+        value.push(self.model_kind.to_byte());
+        value.extend_from_slice(&model_param.to_le_bytes());
+        value
+    }
+
+    /// Разбирает значение раздела (поля новых версий пропускаются)
+    fn decode(value: &[u8]) -> WizardResult<Self> {
+        let mut cursor = ScrollCursor::new(value, 0);
+        if cursor.read_slice(1)?[0] < STATS_VERSION {
+            return Err(WizardError::CorruptMetadata(
+                "неизвестная версия статистики",
+            ));
+        }
+        let original_len = cursor.read_u64()?;
+        let payload_len = cursor.read_u64()?;
+        let entropy_bits_per_byte = f32::from_bits(cursor.read_u32()?);
+        let dictionary_words = cursor.read_u32()?;
+        let dictionary_phrases = cursor.read_u32()?;
+        let dictionary_savings = f32::from_bits(cursor.read_u32()?);
+        let engine = EngineKind::from_byte(cursor.read_slice(1)?[0]).ok_or(
+            WizardError::CorruptMetadata("неизвестный движок в статистике"),
+        )?;
+        let model_code = cursor.read_slice(1)?[0];
+        let model_param = cursor.read_u32()?;
+        let adaptive = ModelKind::Adaptive {
+            half_life: model_param,
+        };
+        let model_kind = match ModelKind::from_byte(model_code) {
+            Some(model_kind) => model_kind,
+            None if model_code == adaptive.to_byte() => adaptive,
+            None if model_code == (ModelKind::HashedOrder2 { table_bits: 1 }).to_byte() => {
+                ModelKind::HashedOrder2 {
+                    table_bits: u8::try_from(model_param).map_err(|_| {
+                        WizardError::CorruptMetadata("размер таблицы контекстов в статистике")
+                    })?,
+                }
+            }
+            None => {
+                return Err(WizardError::CorruptMetadata(
+                    "неизвестная модель в статистике",
+                ))
+            }
+        };
+
+        Ok(Self {
+            original_len,
+            payload_len,
+            entropy_bits_per_byte,
+            dictionary_words,
+            dictionary_phrases,
+            dictionary_savings,
+            engine,
+            model_kind,
+        })
+    }
+}
+
+/// Прикрепляет статистику к архиву 📈
+///
+/// Прежняя статистика заменяется. Раздел есть только у архивов с
+/// заголовком из разделов (версия 6+); более старые дают
+/// [`WizardError::UnsupportedVersion`].
+pub fn attach_stats(
+    archive_bytes: impl AsRef<[u8]>,
+    stats: &ArchiveStats,
+) -> WizardResult<Vec<u8>> {
+    let archive_bytes = archive_bytes.as_ref();
+    let (header, _) = ArchiveHeader::parse(archive_bytes).ok_or(WizardError::UnrecognizedHeader)?;
+    if header.format_version < TLV_FORMAT_VERSION {
+        return Err(WizardError::UnsupportedVersion(header.format_version));
+    }
+    replace_header_section(archive_bytes, SECTION_STATS, Some(&stats.encode()))
+}
+
+/// Читает статистику архива без распаковки 🔍
+///
+/// Архив без раздела статистики (в том числе любой архив версий до 6)
+/// дает `None`.
+pub fn read_stats(archive_bytes: impl AsRef<[u8]>) -> WizardResult<Option<ArchiveStats>> {
+    let archive_bytes = archive_bytes.as_ref();
+    let (header, _) = ArchiveHeader::parse(archive_bytes).ok_or(WizardError::UnrecognizedHeader)?;
+    if header.format_version < TLV_FORMAT_VERSION {
+        return Ok(None);
+    }
+
+    let (sections, _) =
+        read_header_sections(archive_bytes).ok_or(WizardError::UnrecognizedHeader)?;
+    sections
+        .iter()
+        .find(|section| section.kind == SECTION_STATS)
+        .map(|section| ArchiveStats::decode(section.value))
+        .transpose()
+}
+
+/// Тесты рун статистики 🎯
+#[cfg(test)]
+mod stats_runes_tests {
+    use super::*;
+    use crate::format::metadata_runes::{attach_metadata, read_metadata};
+    use crate::simple_api::{compress_data, compress_with_stats, decompress_data};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_stats_travel_in_header() {
+        let data = "the wizard and the owl read the ancient scroll. ".repeat(40);
+        let archive = compress_with_stats(&data);
+        assert_eq!(decompress_data(&archive), data.as_bytes());
+
+        let stats = read_stats(&archive).unwrap().unwrap();
+        let body_offset = ArchiveHeader::parse(&archive).unwrap().1;
+        assert_eq!(stats.original_len, data.len() as u64);
+        assert_eq!(stats.payload_len, (archive.len() - body_offset) as u64);
+        assert!(stats.entropy_bits_per_byte > 3.0 && stats.entropy_bits_per_byte < 5.0);
+        assert!(stats.dictionary_words > 0 && stats.dictionary_savings > 0.0);
+        assert_eq!(
+            (stats.engine, stats.model_kind),
+            (EngineKind::Arithmetic, ModelKind::StaticOrder0)
+        );
+
+        // Тело то же, что у обычного архива; метаданные не трогают статистику
+        assert!(compress_data(&data).ends_with(&archive[body_offset..]));
+        let metadata = HashMap::from([("owner".to_string(), "tower".to_string())]);
+        let tagged = attach_metadata(&archive, &metadata).unwrap();
+        assert_eq!(read_stats(&tagged).unwrap(), Some(stats));
+        assert_eq!(read_metadata(&tagged).unwrap(), metadata);
+    }
+
+    fn sample_stats() -> ArchiveStats {
+        ArchiveStats {
+            original_len: 7,
+            payload_len: 5,
+            entropy_bits_per_byte: 2.5,
+            dictionary_words: 0,
+            dictionary_phrases: 0,
+            dictionary_savings: 0.0,
+            engine: EngineKind::Custom(200),
+            model_kind: ModelKind::Adaptive { half_life: 512 },
+        }
+    }
+
+    #[test]
+    fn test_stats_replace_and_extend() {
+        let archive = compress_data(b"plain scroll without stats");
+        assert_eq!(read_stats(&archive).unwrap(), None);
+
+        let stats = sample_stats();
+        let once = attach_stats(&archive, &stats).unwrap();
+        let twice = attach_stats(&once, &stats).unwrap();
+        assert_eq!(once, twice);
+        assert_eq!(read_stats(&twice).unwrap(), Some(stats));
+
+        // Поля будущих версий пропускаются
+        let mut longer = stats.encode();
+        longer.extend_from_slice(&[0xEE; 6]);
+        let extended = replace_header_section(&archive, SECTION_STATS, Some(&longer)).unwrap();
+        assert_eq!(read_stats(&extended).unwrap(), Some(stats));
+        assert_eq!(decompress_data(&extended), b"plain scroll without stats");
+
+        let order2 = ArchiveStats {
+            model_kind: ModelKind::HashedOrder2 { table_bits: 18 },
+            ..stats
+        };
+        let stamped = attach_stats(&archive, &order2).unwrap();
+        assert_eq!(read_stats(&stamped).unwrap(), Some(order2));
+    }
+
+    #[test]
+    fn test_damaged_stats_and_old_archives() {
+        let archive = compress_data(b"scroll");
+        let with_value =
+            |value: &[u8]| replace_header_section(&archive, SECTION_STATS, Some(value)).unwrap();
+        assert!(matches!(
+            read_stats(with_value(&[STATS_VERSION, 1, 2, 3])),
+            Err(WizardError::Truncated { .. })
+        ));
+
+        // Версия 0, движок 13 и модель 7 не существуют
+        for (offset, byte) in [(0, 0), (STATS_V1_LEN - 6, 13), (STATS_V1_LEN - 5, 7)] {
+            let mut value = sample_stats().encode();
+            value[offset] = byte;
+            assert!(matches!(
+                read_stats(with_value(&value)),
+                Err(WizardError::CorruptMetadata(_))
+            ));
+        }
+
+        assert_eq!(read_stats([0u8; 24]).unwrap(), None);
+        assert_eq!(
+            attach_stats([0u8; 24], &sample_stats()),
+            Err(WizardError::UnsupportedVersion(0))
+        );
+    }
+}
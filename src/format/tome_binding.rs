@@ -0,0 +1,927 @@
+//! Переплет тома: архив из многих записей 📚
+//!
+//! Задания резервного копирования узнают о файлах по ходу работы, поэтому
+//! [`ArchiveWriter`] дописывает записи по одной, а оглавление пишет
+//! последним, в [`ArchiveWriter::finish`]. Каждая запись сжимается
+//! [`BlockStreamWriter`] по мере чтения, так что в памяти держится один
+//! блок, а не вся запись. [`ArchiveReader`] находит оглавление по хвосту
+//! тома и распаковывает любую запись, не читая остальные.
+//!
+//! У каждой записи свои параметры сжатия
+//! ([`ArchiveWriter::add_entry_with_config`]): уже сжатые JPEG и ZIP
+//! хранятся как есть, текст кодируется арифметически, JSON — с пресетом.
+//! Выбранные движок и словарь записываются в оглавление, и читатель
+//! проверяет их до распаковки записи.
+//!
+//! Запись из файла ([`ArchiveWriter::add_file`]) хранит и права доступа,
+//! а [`ArchiveReader::extract_entry`] восстанавливает файл под каталогом
+//! назначения: имя записи — относительный путь через `/`.
+//!
+//! Раскладка тома:
+//!
+//! ```text
+//! [TOME_MAGIC 4][версия u8]
+//! [запись: потоковый архив из блоков]…
+//! [оглавление: число записей u32, затем для каждой
+//!  длина имени u16, имя UTF-8, движок u8, есть словарь u8,
+//!  идентификатор словаря u64, есть права u8, права u32,
+//!  смещение u64, длина архива u64, длина u64]
+//! [смещение оглавления u64][TOME_MAGIC 4]
+//! ```
+//!
+//! В томах версии 1 движка и словаря в оглавлении нет: их записи сжаты
+//! арифметическим движком без внешнего словаря. В томах версии 2 нет
+//! прав доступа.
+//!
+//! Запись — обычный архив из блоков: ее байты распаковывает и
+//! [`try_decompress_data`](crate::simple_api::try_decompress_data).
+
+use super::block_ledger::DEFAULT_BLOCK_SIZE;
+use super::block_stream::{BlockStreamReader, BlockStreamWriter};
+use super::container_runes::EngineKind;
+use super::sealed_scroll::{AtomicFile, FsyncPolicy};
+use crate::compression_engine::engine_covenant::EngineConfig;
+use crate::compression_engine::engine_registry::EngineRegistry;
+use crate::compression_engine::preset_tomes::Preset;
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::error::WizardError;
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Сигнатура тома (в начале и в самом конце)
+pub const TOME_MAGIC: [u8; 4] = *b"ACWT";
+
+/// Версия раскладки тома
+const TOME_VERSION: u8 = 3;
+
+/// Первая версия с движком и словарем записи в оглавлении
+const ENTRY_SETTINGS_VERSION: u8 = 2;
+
+/// Первая версия с правами доступа записи в оглавлении
+const ENTRY_MODE_VERSION: u8 = 3;
+
+/// Длина начала тома: сигнатура и версия
+const TOME_PREAMBLE_LEN: u64 = 5;
+
+/// Длина хвоста: смещение оглавления и сигнатура
+const TOME_TRAILER_LEN: u64 = 12;
+
+/// Длина записи оглавления версии 1 без имени
+const LEGACY_INDEX_ENTRY_FIXED_LEN: usize = 2 + 8 + 8 + 8;
+
+/// Длина параметров записи версии 2: движок, признак и идентификатор словаря
+const V2_ENTRY_SETTINGS_LEN: usize = 1 + 1 + 8;
+
+/// Длина параметров записи: вдобавок признак и биты прав доступа
+const ENTRY_SETTINGS_LEN: usize = V2_ENTRY_SETTINGS_LEN + 1 + 4;
+
+/// Биты прав доступа, которые хранит том (без типа файла)
+const MODE_MASK: u32 = 0o7777;
+
+/// Запись оглавления тома 🔖
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryInfo {
+    /// Имя записи
+    pub name: String,
+    /// Длина исходных данных
+    pub original_len: u64,
+    /// Длина сжатой записи в томе
+    pub archive_len: u64,
+    /// Движок, выбранный для записи
+    ///
+    /// Блок, который не сжался, хранится как есть при любом движке
+    /// (см. [`max_compressed_len`](crate::simple_api::max_compressed_len)).
+    pub engine: EngineKind,
+    /// Идентификатор встроенного словаря записи (`None` — словарь в кадрах)
+    pub dictionary_id: Option<u64>,
+    /// Права доступа исходного файла, биты `0o7777` (`None` — запись не
+    /// из файла или том версии 1–2)
+    pub mode: Option<u32>,
+    /// Смещение сжатой записи от начала тома
+    offset: u64,
+}
+
+/// Писатель тома: записи добавляются по одной 🖋️
+///
+/// Том завершает [`ArchiveWriter::finish`]; без него оглавления нет и
+/// [`ArchiveReader`] том не откроет.
+pub struct ArchiveWriter<W: Write> {
+    inner: W,
+    block_size: usize,
+    position: u64,
+    entries: Vec<EntryInfo>,
+    names: HashSet<String>,
+}
+
+impl ArchiveWriter<AtomicFile> {
+    /// Начинает файл тома с [`FsyncPolicy::File`]
+    ///
+    /// Том пишется во временный файл рядом с `path` и встает на место
+    /// только в [`AtomicFile::commit`] после [`finish`](Self::finish):
+    /// упавшее задание не оставляет тома без оглавления, а прежний файл
+    /// под этим именем до фиксации не тронут.
+    ///
+    /// ```no_run
+    /// use arithmetic_compression_wizard::format::ArchiveWriter;
+    ///
+    /// let mut writer = ArchiveWriter::create("backup.acwt")?;
+    /// writer.add_entry("notes.txt", std::fs::File::open("notes.txt")?)?;
+    /// writer.finish()?.commit()?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::create_with(path, FsyncPolicy::default())
+    }
+
+    /// Начинает файл тома с выбранной политикой fsync
+    pub fn create_with(path: impl AsRef<Path>, fsync: FsyncPolicy) -> io::Result<Self> {
+        Self::new(AtomicFile::create_with(path, fsync)?)
+    }
+}
+
+impl<W: Write> ArchiveWriter<W> {
+    /// Начинает том в писателе `inner`: сразу пишет сигнатуру
+    pub fn new(mut inner: W) -> io::Result<Self> {
+        inner.write_all(&TOME_MAGIC)?;
+        inner.write_all(&[TOME_VERSION])?;
+        Ok(Self {
+            inner,
+            block_size: DEFAULT_BLOCK_SIZE,
+            position: TOME_PREAMBLE_LEN,
+            entries: Vec::new(),
+            names: HashSet::new(),
+        })
+    }
+
+    /// Размер блока сжатия следующих записей
+    ///
+    /// Паникует при нулевом `block_size`.
+    pub fn with_block_size(mut self, block_size: usize) -> Self {
+        assert!(block_size > 0, "размер блока должен быть положительным");
+        self.block_size = block_size;
+        self
+    }
+
+    /// Сжимает все данные `reader` в запись `name`
+    ///
+    /// Повторное имя и имя длиннее 65535 байтов отклоняются
+    /// ([`io::ErrorKind::InvalidInput`]) до записи данных. После ошибки
+    /// чтения том можно продолжать: недописанная запись не попадет в
+    /// оглавление.
+    pub fn add_entry(&mut self, name: &str, reader: impl Read) -> io::Result<()> {
+        self.write_entry(name, reader, None, None)
+    }
+
+    /// Сжимает запись с параметрами `config` ⚙️
+    ///
+    /// Движок `config.engine` и словарь пресета `config.preset`
+    /// попадают в оглавление. Ошибка сжатия блока (например, движок вне
+    /// встроенных) приходит как [`io::ErrorKind::InvalidInput`] с
+    /// [`WizardError`] внутри.
+    ///
+    /// ```
+    /// use arithmetic_compression_wizard::compression_engine::EngineConfig;
+    /// use arithmetic_compression_wizard::decompression_oracle::DecodeOptions;
+    /// use arithmetic_compression_wizard::format::{ArchiveReader, ArchiveWriter, EngineKind};
+    /// use std::io::Cursor;
+    ///
+    /// let photo: Vec<u8> = (0..4000u32)
+    ///     .map(|step| (step.wrapping_mul(2_654_435_761) >> 11) as u8)
+    ///     .collect();
+    /// let stored = EngineConfig::default().with_engine(EngineKind::Stored);
+    /// let mut writer = ArchiveWriter::new(Vec::new()).unwrap();
+    /// writer.add_entry_with_config("photo.jpg", &photo[..], &stored).unwrap();
+    /// writer.add_entry("notes.txt", &b"remember the owl food"[..]).unwrap();
+    /// let tome = writer.finish().unwrap();
+    ///
+    /// let mut reader = ArchiveReader::new(Cursor::new(tome), DecodeOptions::default()).unwrap();
+    /// assert_eq!(reader.entries()[0].engine, EngineKind::Stored);
+    /// assert_eq!(reader.read_entry("photo.jpg").unwrap(), photo);
+    /// ```
+    pub fn add_entry_with_config(
+        &mut self,
+        name: &str,
+        reader: impl Read,
+        config: &EngineConfig,
+    ) -> io::Result<()> {
+        self.write_entry(name, reader, Some(config), None)
+    }
+
+    /// Сжимает файл `path` в запись `name` вместе с его правами доступа 📄
+    ///
+    /// `name` — путь, под которым файл восстановит
+    /// [`ArchiveReader::extract_entry`]: относительный, с `/` между
+    /// каталогами. Вне Unix хранится только признак «только чтение»
+    /// (`0o444` или `0o644`).
+    pub fn add_file(
+        &mut self,
+        name: &str,
+        path: impl AsRef<Path>,
+        config: &EngineConfig,
+    ) -> io::Result<()> {
+        let file = File::open(path)?;
+        let mode = file_mode(&file.metadata()?);
+        self.write_entry(name, BufReader::new(file), Some(config), Some(mode))
+    }
+
+    /// Сжимает запись параметрами `config` или параметрами по умолчанию
+    fn write_entry(
+        &mut self,
+        name: &str,
+        mut reader: impl Read,
+        config: Option<&EngineConfig>,
+        mode: Option<u32>,
+    ) -> io::Result<()> {
+        if u16::try_from(name.len()).is_err() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "имя записи длиннее 65535 байтов",
+            ));
+        }
+        if self.names.contains(name) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("запись {name:?} уже есть в томе"),
+            ));
+        }
+
+        let mut counter = CountingWriter {
+            inner: &mut self.inner,
+            written: 0,
+        };
+        let block_writer = match config {
+            Some(config) => {
+                BlockStreamWriter::with_config(&mut counter, self.block_size, config.clone())
+            }
+            None => BlockStreamWriter::new(&mut counter, self.block_size),
+        };
+        let written = block_writer.and_then(|mut block_writer| {
+            let original_len = io::copy(&mut reader, &mut block_writer)?;
+            block_writer.finish()?;
+            Ok(original_len)
+        });
+        // Недописанная запись остается в томе мертвыми байтами вне оглавления
+        let entry_offset = self.position;
+        self.position += counter.written;
+        let original_len = written?;
+
+        self.entries.push(EntryInfo {
+            name: name.to_owned(),
+            original_len,
+            archive_len: counter.written,
+            engine: config.map_or(EngineKind::Arithmetic, |config| config.engine),
+            dictionary_id: config.and_then(|config| config.preset.map(Preset::dictionary_id)),
+            mode,
+            offset: entry_offset,
+        });
+        self.names.insert(name.to_owned());
+        Ok(())
+    }
+
+    /// Записи, добавленные до сих пор
+    pub fn entries(&self) -> &[EntryInfo] {
+        &self.entries
+    }
+
+    /// Дописывает оглавление и хвост, возвращает писатель
+    pub fn finish(mut self) -> io::Result<W> {
+        let mut index = Vec::new();
+        index.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+        for entry in &self.entries {
+            index.extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+            index.extend_from_slice(entry.name.as_bytes());
+            index.push(entry.engine.to_byte());
+            index.push(u8::from(entry.dictionary_id.is_some()));
+            index.extend_from_slice(&entry.dictionary_id.unwrap_or(0).to_le_bytes());
+            index.push(u8::from(entry.mode.is_some()));
+            index.extend_from_slice(&entry.mode.unwrap_or(0).to_le_bytes());
+            index.extend_from_slice(&entry.offset.to_le_bytes());
+            index.extend_from_slice(&entry.archive_len.to_le_bytes());
+            index.extend_from_slice(&entry.original_len.to_le_bytes());
+        }
+        index.extend_from_slice(&self.position.to_le_bytes());
+        index.extend_from_slice(&TOME_MAGIC);
+
+        self.inner.write_all(&index)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+/// Писатель, считающий прошедшие через него байты
+struct CountingWriter<W: Write> {
+    inner: W,
+    written: u64,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Читатель тома: оглавление и распаковка записей по имени 📖
+///
+/// Ошибки формата приходят как [`io::ErrorKind::InvalidData`] с
+/// [`WizardError`] внутри.
+pub struct ArchiveReader<R: Read + Seek> {
+    inner: R,
+    options: DecodeOptions,
+    entries: Vec<EntryInfo>,
+}
+
+impl ArchiveReader<BufReader<File>> {
+    /// Открывает файл тома
+    ///
+    /// Размер записи не ограничен; остальные лимиты —
+    /// [`DecodeOptions::default`].
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let options = DecodeOptions::default().with_max_output_bytes(usize::MAX);
+        Self::new(BufReader::new(File::open(path)?), options)
+    }
+}
+
+impl<R: Read + Seek> ArchiveReader<R> {
+    /// Читает оглавление с конца тома
+    ///
+    /// Длина каждой записи проверяется лимитом `options.max_output_bytes`
+    /// при ее открытии.
+    pub fn new(mut inner: R, options: DecodeOptions) -> io::Result<Self> {
+        let tome_len = inner.seek(SeekFrom::End(0))?;
+        if tome_len < TOME_PREAMBLE_LEN + TOME_TRAILER_LEN {
+            return Err(WizardError::Truncated {
+                needed: (TOME_PREAMBLE_LEN + TOME_TRAILER_LEN) as usize,
+                available: tome_len as usize,
+            }
+            .into());
+        }
+
+        inner.seek(SeekFrom::Start(0))?;
+        let mut preamble = [0u8; TOME_PREAMBLE_LEN as usize];
+        inner.read_exact(&mut preamble)?;
+        if preamble[..4] != TOME_MAGIC {
+            return Err(WizardError::UnrecognizedHeader.into());
+        }
+        if preamble[4] > TOME_VERSION {
+            return Err(WizardError::UnsupportedVersion(preamble[4]).into());
+        }
+
+        let index_end = tome_len - TOME_TRAILER_LEN;
+        inner.seek(SeekFrom::Start(index_end))?;
+        let mut trailer = [0u8; TOME_TRAILER_LEN as usize];
+        inner.read_exact(&mut trailer)?;
+        if trailer[8..] != TOME_MAGIC {
+            return Err(WizardError::CorruptEntryIndex("нет хвоста: том не завершен").into());
+        }
+        let index_offset = u64::from_le_bytes(trailer[..8].try_into().expect("8 байтов"));
+        if !(TOME_PREAMBLE_LEN..=index_end).contains(&index_offset) {
+            return Err(WizardError::CorruptEntryIndex("смещение оглавления вне тома").into());
+        }
+
+        inner.seek(SeekFrom::Start(index_offset))?;
+        let mut index = vec![0u8; (index_end - index_offset) as usize];
+        inner.read_exact(&mut index)?;
+        let entries = parse_index(&index, index_offset, preamble[4])?;
+
+        Ok(Self {
+            inner,
+            options,
+            entries,
+        })
+    }
+
+    /// Оглавление в порядке добавления записей
+    pub fn entries(&self) -> &[EntryInfo] {
+        &self.entries
+    }
+
+    /// Распаковывающий читатель записи `name`
+    ///
+    /// Отсутствующая запись — [`io::ErrorKind::NotFound`]. Запись
+    /// движка, которого нет среди встроенных, или словаря, не включенного
+    /// фичами сборки, отклоняется до чтения ее байтов
+    /// ([`WizardError::UnsupportedEngine`],
+    /// [`WizardError::ExternalDictionaryRequired`]).
+    pub fn open_entry(&mut self, name: &str) -> io::Result<BlockStreamReader<io::Take<&mut R>>> {
+        let entry = self
+            .entries
+            .iter()
+            .find(|entry| entry.name == name)
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, format!("нет записи {name:?}"))
+            })?;
+        self.options.check_output(entry.original_len)?;
+        if EngineRegistry::with_builtin_engines()
+            .get(entry.engine)
+            .is_none()
+        {
+            return Err(WizardError::UnsupportedEngine(entry.engine.to_byte()).into());
+        }
+        if let Some(dictionary_id) = entry.dictionary_id {
+            if Preset::from_dictionary_id(dictionary_id).is_none() {
+                return Err(WizardError::ExternalDictionaryRequired(dictionary_id).into());
+            }
+        }
+
+        self.inner.seek(SeekFrom::Start(entry.offset))?;
+        let archive = (&mut self.inner).take(entry.archive_len);
+        BlockStreamReader::new(archive, self.options.clone())
+    }
+
+    /// Распаковывает запись `name` целиком
+    pub fn read_entry(&mut self, name: &str) -> io::Result<Vec<u8>> {
+        let mut restored = Vec::new();
+        self.open_entry(name)?.read_to_end(&mut restored)?;
+        Ok(restored)
+    }
+
+    /// Восстанавливает запись `name` файлом под каталогом `root` 📂
+    ///
+    /// Недостающие каталоги создаются, файл пишется через [`AtomicFile`]
+    /// и получает права из оглавления, если они есть. Имя, которое вывело
+    /// бы файл из `root` (абсолютный путь, `..`, пустой каталог), —
+    /// [`io::ErrorKind::InvalidData`] до записи чего-либо. Возвращает путь
+    /// восстановленного файла.
+    ///
+    /// ```
+    /// use arithmetic_compression_wizard::decompression_oracle::DecodeOptions;
+    /// use arithmetic_compression_wizard::format::{ArchiveReader, ArchiveWriter};
+    /// use std::io::Cursor;
+    ///
+    /// let mut writer = ArchiveWriter::new(Vec::new()).unwrap();
+    /// writer.add_entry("spells/fire.txt", &b"ignis"[..]).unwrap();
+    /// writer.add_entry("../escape.txt", &b"nope"[..]).unwrap();
+    /// let tome = writer.finish().unwrap();
+    ///
+    /// let root = std::env::temp_dir().join(format!("acw-extract-doc-{}", std::process::id()));
+    /// let mut reader = ArchiveReader::new(Cursor::new(tome), DecodeOptions::default()).unwrap();
+    /// let path = reader.extract_entry("spells/fire.txt", &root).unwrap();
+    /// assert_eq!(std::fs::read(&path).unwrap(), b"ignis");
+    /// assert!(reader.extract_entry("../escape.txt", &root).is_err());
+    /// # std::fs::remove_dir_all(&root).unwrap();
+    /// ```
+    pub fn extract_entry(&mut self, name: &str, root: impl AsRef<Path>) -> io::Result<PathBuf> {
+        let path = entry_path(root.as_ref(), name)?;
+        let mode = self
+            .entries
+            .iter()
+            .find(|entry| entry.name == name)
+            .and_then(|entry| entry.mode);
+        let mut entry_reader = self.open_entry(name)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = AtomicFile::create(&path)?;
+        io::copy(&mut entry_reader, &mut file)?;
+        file.commit()?;
+        if let Some(mode) = mode {
+            set_file_mode(&path, mode)?;
+        }
+        Ok(path)
+    }
+}
+
+/// Путь записи `name` под `root`; имя не выводит за пределы `root`
+fn entry_path(root: &Path, name: &str) -> io::Result<PathBuf> {
+    let unsafe_name = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("имя записи {name:?} не относительный путь внутри каталога"),
+        )
+    };
+    let mut path = root.to_path_buf();
+    for component in name.split('/') {
+        let forbidden = component.is_empty()
+            || component == "."
+            || component == ".."
+            || component.contains(['\\', ':', '\0']);
+        if forbidden {
+            return Err(unsafe_name());
+        }
+        path.push(component);
+    }
+    Ok(path)
+}
+
+/// Права доступа файла для оглавления
+#[cfg(unix)]
+fn file_mode(metadata: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & MODE_MASK
+}
+
+/// Права доступа файла для оглавления: вне Unix известно только «только чтение»
+#[cfg(not(unix))]
+fn file_mode(metadata: &fs::Metadata) -> u32 {
+    if metadata.permissions().readonly() {
+        0o444
+    } else {
+        0o644
+    }
+}
+
+/// Ставит восстановленному файлу права из оглавления
+#[cfg(unix)]
+fn set_file_mode(path: &Path, mode: u32) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode & MODE_MASK))
+}
+
+/// Ставит восстановленному файлу права из оглавления: вне Unix — «только чтение»
+#[cfg(not(unix))]
+fn set_file_mode(path: &Path, mode: u32) -> io::Result<()> {
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_readonly(mode & 0o222 == 0);
+    fs::set_permissions(path, permissions)
+}
+
+/// Разбирает оглавление; записи идут по порядку, не перекрываясь, до оглавления
+fn parse_index(
+    index: &[u8],
+    index_offset: u64,
+    tome_version: u8,
+) -> Result<Vec<EntryInfo>, WizardError> {
+    let corrupt = WizardError::CorruptEntryIndex;
+    let settings_len = match tome_version {
+        version if version >= ENTRY_MODE_VERSION => ENTRY_SETTINGS_LEN,
+        version if version >= ENTRY_SETTINGS_VERSION => V2_ENTRY_SETTINGS_LEN,
+        _ => 0,
+    };
+    let (count, mut rest) = index
+        .split_first_chunk::<4>()
+        .ok_or(corrupt("оглавление короче числа записей"))?;
+    let entry_count = u32::from_le_bytes(*count) as usize;
+    if entry_count > rest.len() / (LEGACY_INDEX_ENTRY_FIXED_LEN + settings_len) {
+        return Err(corrupt("записей больше, чем умещает оглавление"));
+    }
+
+    let mut entries = Vec::with_capacity(entry_count);
+    let mut expected_offset = TOME_PREAMBLE_LEN;
+    for _ in 0..entry_count {
+        let (name_len, tail) = rest
+            .split_first_chunk::<2>()
+            .ok_or(corrupt("оглавление обрывается"))?;
+        let name_len = u16::from_le_bytes(*name_len) as usize;
+        if tail.len() < name_len + settings_len + 24 {
+            return Err(corrupt("оглавление обрывается"));
+        }
+        let (name, tail) = tail.split_at(name_len);
+        let name = std::str::from_utf8(name).map_err(|_| corrupt("имя записи не UTF-8"))?;
+        let (settings, tail) = tail.split_at(settings_len);
+        let (engine, dictionary_id, mode) = parse_entry_settings(settings)?;
+        let field = |at: usize| u64::from_le_bytes(tail[at..at + 8].try_into().expect("8 байтов"));
+        let (offset, archive_len, original_len) = (field(0), field(8), field(16));
+        rest = &tail[24..];
+
+        // Между записями могут остаться байты недописанной записи
+        if offset < expected_offset {
+            return Err(corrupt("записи перекрываются"));
+        }
+        expected_offset = offset
+            .checked_add(archive_len)
+            .filter(|&end| end <= index_offset)
+            .ok_or(corrupt("запись выходит за оглавление"))?;
+        entries.push(EntryInfo {
+            name: name.to_owned(),
+            original_len,
+            archive_len,
+            engine,
+            dictionary_id,
+            mode,
+            offset,
+        });
+    }
+    if !rest.is_empty() {
+        return Err(corrupt("лишние байты в оглавлении"));
+    }
+    Ok(entries)
+}
+
+/// Движок, словарь и права записи; пустые параметры — запись тома
+/// версии 1, без прав — версии 2
+fn parse_entry_settings(
+    settings: &[u8],
+) -> Result<(EngineKind, Option<u64>, Option<u32>), WizardError> {
+    let corrupt = WizardError::CorruptEntryIndex;
+    let Some((&[engine, has_dictionary], rest)) = settings.split_first_chunk::<2>() else {
+        return Ok((EngineKind::Arithmetic, None, None));
+    };
+    let engine = EngineKind::from_byte(engine).ok_or(corrupt("неизвестный движок записи"))?;
+    let (dictionary_id, mode_settings) = rest.split_at(8);
+    let dictionary_id = u64::from_le_bytes(dictionary_id.try_into().expect("8 байтов"));
+    let dictionary_id = match (has_dictionary, dictionary_id) {
+        (0, 0) => None,
+        (1, _) => Some(dictionary_id),
+        _ => return Err(corrupt("неверный признак словаря записи")),
+    };
+    let mode = match mode_settings.split_first() {
+        None => None,
+        Some((&has_mode, mode)) => {
+            let mode = u32::from_le_bytes(mode.try_into().expect("4 байта"));
+            match (has_mode, mode) {
+                (0, 0) => None,
+                (1, mode) if mode & !MODE_MASK == 0 => Some(mode),
+                _ => return Err(corrupt("неверные права записи")),
+            }
+        }
+    };
+    Ok((engine, dictionary_id, mode))
+}
+
+/// Тесты переплета тома 🎯
+#[cfg(test)]
+mod tome_binding_tests {
+    use super::*;
+    use crate::simple_api::try_decompress_data;
+    use crate::testkit::scratch_dir::ScratchDir;
+    use std::io::Cursor;
+
+    /// Источник, который обрывается ошибкой
+    struct BrokenSource;
+
+    impl Read for BrokenSource {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("диск отвалился"))
+        }
+    }
+
+    /// Том из трех записей в памяти
+    fn sample_tome() -> Vec<u8> {
+        let mut writer = ArchiveWriter::new(Vec::new()).unwrap().with_block_size(300);
+        writer
+            .add_entry("logs/owl.log", "owl post delivered\n".repeat(50).as_bytes())
+            .unwrap();
+        writer.add_entry("empty", io::empty()).unwrap();
+        writer
+            .add_entry("спеллы/огонь.txt", "ignis ".repeat(10).as_bytes())
+            .unwrap();
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn test_entries_roundtrip() {
+        let tome = sample_tome();
+        let mut reader = ArchiveReader::new(Cursor::new(&tome), DecodeOptions::default()).unwrap();
+        let names: Vec<&str> = reader.entries().iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["logs/owl.log", "empty", "спеллы/огонь.txt"]);
+        assert_eq!(reader.entries()[0].original_len, 950);
+
+        // Записи читаются в любом порядке
+        assert_eq!(
+            reader.read_entry("спеллы/огонь.txt").unwrap(),
+            "ignis ".repeat(10).as_bytes()
+        );
+        assert_eq!(reader.read_entry("empty").unwrap(), b"");
+        let owl = reader.read_entry("logs/owl.log").unwrap();
+        assert_eq!(owl, "owl post delivered\n".repeat(50).as_bytes());
+        assert_eq!(
+            reader.read_entry("missing").unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+
+        // Байты записи — обычный архив из блоков
+        let entry = reader.entries()[0].clone();
+        let entry_bytes = &tome[entry.offset as usize..(entry.offset + entry.archive_len) as usize];
+        assert_eq!(
+            try_decompress_data(entry_bytes, &DecodeOptions::default()).unwrap(),
+            owl
+        );
+    }
+
+    #[test]
+    fn test_per_entry_settings() {
+        let photo: Vec<u8> = (0..3000u32)
+            .map(|step| (step.wrapping_mul(2_654_435_761) >> 13) as u8)
+            .collect();
+        let text = "the owl carries letters and parcels. ".repeat(60);
+        let mut writer = ArchiveWriter::new(Vec::new())
+            .unwrap()
+            .with_block_size(1000);
+        let stored = EngineConfig::default().with_engine(EngineKind::Stored);
+        writer
+            .add_entry_with_config("photo.jpg", &photo[..], &stored)
+            .unwrap();
+        let mixed = EngineConfig::default().with_engine(EngineKind::Mix);
+        writer
+            .add_entry_with_config("letters.txt", text.as_bytes(), &mixed)
+            .unwrap();
+        writer.add_entry("plain.txt", text.as_bytes()).unwrap();
+
+        // Движок вне встроенных отклоняется при сжатии
+        let custom = EngineConfig::default().with_engine(EngineKind::Custom(200));
+        let rejected = writer
+            .add_entry_with_config("custom.bin", text.as_bytes(), &custom)
+            .unwrap_err();
+        assert_eq!(rejected.kind(), io::ErrorKind::InvalidInput);
+        let tome = writer.finish().unwrap();
+
+        let mut reader = ArchiveReader::new(Cursor::new(&tome), DecodeOptions::default()).unwrap();
+        let settings: Vec<(EngineKind, Option<u64>)> = reader
+            .entries()
+            .iter()
+            .map(|entry| (entry.engine, entry.dictionary_id))
+            .collect();
+        assert_eq!(
+            settings,
+            [
+                (EngineKind::Stored, None),
+                (EngineKind::Mix, None),
+                (EngineKind::Arithmetic, None)
+            ]
+        );
+        assert!(reader.entries()[0].archive_len > photo.len() as u64);
+        assert!(reader.entries()[1].archive_len < text.len() as u64 / 2);
+        assert_eq!(reader.read_entry("photo.jpg").unwrap(), photo);
+        assert_eq!(reader.read_entry("letters.txt").unwrap(), text.as_bytes());
+
+        // Чужой движок в оглавлении отклоняется до чтения записи
+        let mut foreign = tome.clone();
+        let index_offset = tome.len() - 12;
+        let index_start =
+            u64::from_le_bytes(tome[index_offset..index_offset + 8].try_into().unwrap()) as usize;
+        foreign[index_start + 4 + 2 + "photo.jpg".len()] = 200;
+        let mut reader =
+            ArchiveReader::new(Cursor::new(foreign), DecodeOptions::default()).unwrap();
+        let error = reader.read_entry("photo.jpg").unwrap_err();
+        assert_eq!(
+            *error
+                .into_inner()
+                .unwrap()
+                .downcast::<WizardError>()
+                .unwrap(),
+            WizardError::UnsupportedEngine(200)
+        );
+    }
+
+    #[test]
+    fn test_version_one_tomes_still_open() {
+        let text = b"an old tome from the first shelf".repeat(20);
+        let entry =
+            crate::simple_api::compress_iter(text.iter().copied(), &EngineConfig::default());
+        let mut tome = TOME_MAGIC.to_vec();
+        tome.push(1);
+        tome.extend_from_slice(&entry);
+        let index_offset = tome.len() as u64;
+        tome.extend_from_slice(&1u32.to_le_bytes());
+        tome.extend_from_slice(&3u16.to_le_bytes());
+        tome.extend_from_slice(b"old");
+        tome.extend_from_slice(&TOME_PREAMBLE_LEN.to_le_bytes());
+        tome.extend_from_slice(&(entry.len() as u64).to_le_bytes());
+        tome.extend_from_slice(&(text.len() as u64).to_le_bytes());
+        tome.extend_from_slice(&index_offset.to_le_bytes());
+        tome.extend_from_slice(&TOME_MAGIC);
+
+        let mut reader = ArchiveReader::new(Cursor::new(tome), DecodeOptions::default()).unwrap();
+        assert_eq!(reader.entries()[0].engine, EngineKind::Arithmetic);
+        assert_eq!(reader.entries()[0].dictionary_id, None);
+        assert_eq!(reader.read_entry("old").unwrap(), text);
+    }
+
+    #[test]
+    fn test_writer_rejects_bad_names_and_uses_files() {
+        let mut writer = ArchiveWriter::new(Vec::new()).unwrap();
+        writer.add_entry("twice", &b"one"[..]).unwrap();
+        let duplicate = writer.add_entry("twice", &b"two"[..]).unwrap_err();
+        assert_eq!(duplicate.kind(), io::ErrorKind::InvalidInput);
+        let long_name = "x".repeat(70_000);
+        assert!(writer.add_entry(&long_name, &b""[..]).is_err());
+
+        // Оборванный источник не портит следующие записи
+        let broken = "half a scroll ".repeat(40).into_bytes();
+        let broken = broken.as_slice().chain(BrokenSource);
+        assert!(writer.add_entry("broken", broken).is_err());
+        writer.add_entry("after", &b"still fine"[..]).unwrap();
+        assert_eq!(writer.entries().len(), 2);
+        let tome = writer.finish().unwrap();
+        let mut reader = ArchiveReader::new(Cursor::new(tome), DecodeOptions::default()).unwrap();
+        assert_eq!(reader.read_entry("after").unwrap(), b"still fine");
+
+        let scratch = ScratchDir::new("tome-create");
+        let path = scratch.0.join("nightly.acwt");
+        let mut writer = ArchiveWriter::create(&path).unwrap();
+        writer
+            .add_entry("night.bak", &b"nightly backup"[..])
+            .unwrap();
+        let file = writer.finish().unwrap();
+        // До фиксации тома под его именем нет
+        assert!(!path.exists());
+        file.commit().unwrap();
+        let mut reader = ArchiveReader::open(&path).unwrap();
+        assert_eq!(reader.read_entry("night.bak").unwrap(), b"nightly backup");
+    }
+
+    #[test]
+    fn test_files_keep_paths_and_modes() {
+        let (source, target) = (
+            ScratchDir::new("tome-files-source"),
+            ScratchDir::new("tome-files-target"),
+        );
+        let script = source.0.join("cast.sh");
+        fs::write(&script, "echo ignis\n".repeat(30)).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&script, fs::Permissions::from_mode(0o750)).unwrap();
+        }
+
+        let mut writer = ArchiveWriter::new(Vec::new()).unwrap();
+        writer
+            .add_file("bin/cast.sh", &script, &EngineConfig::default())
+            .unwrap();
+        writer.add_entry("notes.txt", &b"no mode here"[..]).unwrap();
+        writer.add_entry("/etc/passwd", &b"absolute"[..]).unwrap();
+        let tome = writer.finish().unwrap();
+
+        let mut reader = ArchiveReader::new(Cursor::new(&tome), DecodeOptions::default()).unwrap();
+        let modes: Vec<Option<u32>> = reader.entries().iter().map(|entry| entry.mode).collect();
+        #[cfg(unix)]
+        assert_eq!(modes, [Some(0o750), None, None]);
+        assert!(modes[0].is_some());
+
+        let restored = reader.extract_entry("bin/cast.sh", &target.0).unwrap();
+        assert_eq!(restored, target.0.join("bin").join("cast.sh"));
+        assert_eq!(fs::read(&restored).unwrap(), fs::read(&script).unwrap());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&restored).unwrap().permissions().mode();
+            assert_eq!(mode & MODE_MASK, 0o750);
+        }
+        reader.extract_entry("notes.txt", &target.0).unwrap();
+
+        // Имя, выводящее из каталога, отклоняется до записи
+        let escape = reader.extract_entry("/etc/passwd", &target.0).unwrap_err();
+        assert_eq!(escape.kind(), io::ErrorKind::InvalidData);
+        for name in ["../up", "a//b", "./here", "c:\\windows"] {
+            assert!(entry_path(&target.0, name).is_err(), "{name}");
+        }
+        assert_eq!(target.entries(), ["bin", "notes.txt"]);
+    }
+
+    #[test]
+    fn test_damaged_tomes_are_rejected() {
+        let tome = sample_tome();
+        let open = |bytes: &[u8]| {
+            ArchiveReader::new(Cursor::new(bytes.to_vec()), DecodeOptions::default())
+        };
+        let wizard_error = |result: io::Result<ArchiveReader<Cursor<Vec<u8>>>>| {
+            *result
+                .err()
+                .unwrap()
+                .into_inner()
+                .unwrap()
+                .downcast::<WizardError>()
+                .unwrap()
+        };
+
+        // Незавершенный том: нет оглавления
+        let unfinished = &tome[..tome.len() - 40];
+        assert!(matches!(
+            wizard_error(open(unfinished)),
+            WizardError::CorruptEntryIndex(_)
+        ));
+        assert_eq!(
+            wizard_error(open(&tome[..10])),
+            WizardError::Truncated {
+                needed: 17,
+                available: 10
+            }
+        );
+        let mut foreign = tome.clone();
+        foreign[0] = b'X';
+        assert_eq!(
+            wizard_error(open(&foreign)),
+            WizardError::UnrecognizedHeader
+        );
+
+        // Длина записи в оглавлении не сходится со смещениями
+        let mut shifted = tome.clone();
+        let index_offset = tome.len() - 12;
+        let index_start =
+            u64::from_le_bytes(tome[index_offset..index_offset + 8].try_into().unwrap()) as usize;
+        shifted[index_start + 4 + 2 + 12 + ENTRY_SETTINGS_LEN + 8 + 1] ^= 0x80;
+        assert!(matches!(
+            wizard_error(open(&shifted)),
+            WizardError::CorruptEntryIndex(_)
+        ));
+
+        // Лимит размера записи
+        let mut reader = ArchiveReader::new(
+            Cursor::new(&tome),
+            DecodeOptions::default().with_max_output_bytes(100),
+        )
+        .unwrap();
+        assert_eq!(
+            reader.read_entry("logs/owl.log").unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
@@ -2,9 +2,22 @@
 //! Библиотека для эффективного сжатия данных
 
 // Экспорт основных модулей
+pub mod benchmark;
 pub mod bit_wizardry;
 pub mod compression_engine;
+#[cfg(feature = "std")]
+pub mod concurrency;
+pub mod debug;
 pub mod decompression_oracle;
+pub mod diagnostics;
+pub mod error;
+pub mod format;
+pub mod spec;
+pub mod statistics;
+#[cfg(feature = "tar")]
+pub mod tar_satchel;
+pub mod telemetry;
+pub mod testkit;
 
 // Основной API
 pub use compression_engine::compression_conjurer::{weave_compression_spell, CompressionArtifact};
@@ -14,112 +27,820 @@ pub use decompression_oracle::decompression_sage::unweave_compression_spell;
 /// Простой интерфейс без работы с внутренними структурами
 pub mod simple_api {
     use super::*;
+    use crate::compression_engine::adaptive_tide::{
+        try_unweave_adaptive_body, try_unweave_adaptive_prefix, try_weave_adaptive_frame,
+    };
+    use crate::compression_engine::code_point_glyphs::CodePointSymbolizer;
+    use crate::compression_engine::column_weave::{try_unweave_columnar_body, ColumnarEngine};
+    use crate::compression_engine::compression_conjurer::{
+        try_weave_compression_spell, weave_compression_spell_with_deny_list,
+    };
+    use crate::compression_engine::context_loom::{
+        try_unweave_context_body, try_unweave_context_prefix,
+    };
+    use crate::compression_engine::dedup::try_undedup_body;
+    use crate::compression_engine::delta_forge::{forge_delta, try_apply_delta};
+    use crate::compression_engine::engine_covenant::{
+        CompressionEngine, EngineConfig, PipelineStage, StructuredMode,
+    };
+    use crate::compression_engine::engine_registry::EngineRegistry;
+    use crate::compression_engine::grimoire_ward::DenyList;
+    use crate::compression_engine::json_lens::try_unweave_json_body;
+    use crate::compression_engine::log_templates::LogTemplateSymbolizer;
+    use crate::compression_engine::micro_spell::{
+        is_micro_archive, try_unweave_micro_archive, weave_micro_archive,
+    };
+    use crate::compression_engine::model_augury::{
+        probe_model, ModelChoice, AUTO_MODEL_METADATA_KEY,
+    };
+    use crate::compression_engine::model_blend::try_unweave_mix_body;
+    use crate::compression_engine::plane_sieve::try_unweave_bit_planes_body;
+    use crate::compression_engine::preset_tomes::try_unweave_preset_body;
+    use crate::compression_engine::repeat_echo::try_unweave_repeats_body;
+    use crate::compression_engine::split_stream_engine::try_unweave_split_body;
+    use crate::compression_engine::stored_vault::{
+        try_unstore_body, CompressOutcome, StoredEngine,
+    };
+    use crate::compression_engine::symbolizer::try_unweave_symbolized_body;
+    use crate::compression_engine::wide_glyphs::WideSymbolizer;
+    use crate::decompression_oracle::block_conductor::{decode_block, try_decode_blocks};
+    use crate::decompression_oracle::block_salvage::{salvage_archive, BlockError};
+    use crate::decompression_oracle::decode_wards::DecodeOptions;
+    use crate::decompression_oracle::decompression_sage::{
+        try_unweave_artifact_ref, try_unweave_artifact_ref_prefix,
+    };
+    use crate::error::DecodePhase;
+    use crate::error::WizardResult;
+    use crate::format::artifact_scroll::{
+        offset_within, parse_artifact_ref_body, serialize_artifact,
+        serialize_artifact_with_options, try_serialize_artifact, ScrollOptions,
+    };
+    use crate::format::block_ledger::{
+        read_block_ledger, write_block_archive, write_synced_block_archive, DEFAULT_BLOCK_SIZE,
+    };
+    use crate::format::block_stream::{into_compression_error, BlockStreamWriter};
+    use crate::format::container_runes::{
+        ArchiveHeader, EngineKind, ModelKind, HEADER_SECTION_OVERHEAD, SECTIONS_OFFSET,
+    };
+    use crate::format::metadata_runes::{attach_metadata, read_metadata};
+    use crate::format::parity_runes::{attach_parity, restore_parity};
+    use crate::format::provenance_runes::{attach_provenance, Provenance, PROVENANCE_SECTION_LEN};
+    use crate::format::sealed_scroll::{AtomicFile, FsyncPolicy};
+    use crate::format::stats_runes::{attach_stats, ArchiveStats};
+    use crate::telemetry::{record_decompression, Stopwatch};
+    use std::collections::HashMap;
+    use std::fs::File;
+    use std::io::{self, Write};
+    use std::path::Path;
 
     /// Простая функция сжатия
     ///
-    /// Возвращает только сжатые байты, скрывая детали реализации
-    pub fn compress_data(original: &[u8]) -> Vec<u8> {
+    /// Возвращает только сжатые байты, скрывая детали реализации.
+    /// Принимает любой буфер байтов: `&[u8]`, `Vec<u8>`, `String`, `Bytes`...
+    /// Паникует на входах больше точности кодера — см. [`try_compress_data`].
+    pub fn compress_data(original: impl AsRef<[u8]>) -> Vec<u8> {
+        serialize_artifact(&weave_compression_spell(original))
+    }
+
+    /// Проверяемое сжатие 🛡️
+    ///
+    /// Вход, чья таблица частот не умещается в точность кодера (порядка
+    /// 2^30 символов, то есть гигабайты), дает ошибку вместо паники, как и
+    /// вход или битовый поток длиннее поля `u32` кадра
+    /// ([`WizardError::InputTooLarge`](crate::error::WizardError::InputTooLarge)). Такие входы сжимайте блоками:
+    /// [`compress_data_in_blocks`].
+    pub fn try_compress_data(original: impl AsRef<[u8]>) -> WizardResult<Vec<u8>> {
+        try_serialize_artifact(&try_weave_compression_spell(original)?)
+    }
+
+    /// Сжатие, только если оно окупается ⚖️
+    ///
+    /// Данные сжимаются и сравниваются с порогом `min_ratio` (исходный
+    /// размер к размеру архива): `1.25` требует экономии в пятую часть.
+    /// Ниже порога — или если вход не сжимается вовсе — возвращаются
+    /// исходные байты в кадре хранения. Оба итога распаковывает обычная
+    /// [`decompress_data`], поэтому хранилищу не нужен свой флаг «сжато».
+    pub fn compress_if_beneficial(original: impl AsRef<[u8]>, min_ratio: f64) -> CompressOutcome {
+        let original = original.as_ref();
+        if let Ok(archive) = try_compress_data(original) {
+            if original.len() as f64 >= archive.len() as f64 * min_ratio {
+                return CompressOutcome::Compressed(archive);
+            }
+        }
+        CompressOutcome::Stored(
+            StoredEngine
+                .compress(original, &EngineConfig::default())
+                .to_bytes(),
+        )
+    }
+
+    /// Сжатие без запрещенных слов в словаре 🚫
+    ///
+    /// Словарь хранится в заголовке открытым текстом, даже если архив
+    /// потом шифруется. Слова из `deny_list` (пароли, ключи, адреса)
+    /// кодируются байтами внутри потока; распаковка — обычная
+    /// [`decompress_data`].
+    pub fn compress_data_redacted(original: impl AsRef<[u8]>, deny_list: &DenyList) -> Vec<u8> {
+        serialize_artifact(&weave_compression_spell_with_deny_list(original, deny_list))
+    }
+
+    /// Сжатие адаптивной моделью со старением 🌊
+    ///
+    /// Для длинных файлов, чье распределение меняется по ходу (код, за
+    /// которым идут base64-блоки): вес прошлых символов вдвое падает за
+    /// `half_life` символов (0 считается за 1). Таблица частот в архив не
+    /// пишется; разумное начало — [`DEFAULT_HALF_LIFE`](crate::compression_engine::DEFAULT_HALF_LIFE).
+    /// Распаковка — обычная [`decompress_data`].
+    pub fn compress_data_adaptive(original: impl AsRef<[u8]>, half_life: u32) -> Vec<u8> {
+        try_weave_adaptive_frame(original.as_ref(), half_life, &EngineConfig::default())
+            .expect("словарь больше точности кодера")
+            .to_bytes()
+    }
+
+    /// Компактное сжатие коротких сообщений 🐜
+    ///
+    /// Сообщения до [`MICRO_INPUT_LIMIT`](crate::compression_engine::MICRO_INPUT_LIMIT)
+    /// байтов (MQTT, ключи кэша) кодируются микроархивом: один байт-метка
+    /// и поток встроенной адаптивной модели, без словаря и таблицы
+    /// частот. Более длинные входы — обычный [`compress_data`], если он
+    /// короче. Распаковка — обычная [`decompress_data`].
+    pub fn compress_compact(original: impl AsRef<[u8]>) -> Vec<u8> {
+        let original = original.as_ref();
+        let archive = compress_data(original);
+        match weave_micro_archive(original) {
+            Some(micro) if micro.len() < archive.len() => micro,
+            _ => archive,
+        }
+    }
+
+    /// Сжатие с параметрами сериализации
+    ///
+    /// Например, `ScrollOptions::default().with_compact_alphabet(true)`
+    /// уменьшает таблицу частот для ДНК, base64 и hex-дампов.
+    pub fn compress_data_with_options(
+        original: impl AsRef<[u8]>,
+        options: &ScrollOptions,
+    ) -> Vec<u8> {
+        serialize_artifact_with_options(&weave_compression_spell(original), options)
+    }
+
+    /// Сжатие с пользовательскими метаданными 🏷️
+    ///
+    /// Пары ключ–значение (тип содержимого, имя файла, время) попадают в
+    /// заголовок и читаются [`read_metadata`](crate::format::read_metadata)
+    /// без распаковки.
+    pub fn compress_with_metadata(
+        original: impl AsRef<[u8]>,
+        metadata: &HashMap<String, String>,
+    ) -> Vec<u8> {
+        attach_metadata(compress_data(original), metadata)
+            .expect("свежий архив имеет заголовок, а метаданные умещаются в 4 ГиБ")
+    }
+
+    /// Сжатие со сводкой статистики в заголовке 📈
+    ///
+    /// Размеры, энтропия входа, вклад словаря и параметры движка
+    /// читаются [`read_stats`](crate::format::read_stats) без распаковки —
+    /// для аналитики по всему хранилищу. Тело архива то же, что у
+    /// [`compress_data`].
+    pub fn compress_with_stats(original: impl AsRef<[u8]>) -> Vec<u8> {
+        let original = original.as_ref();
         let artifact = weave_compression_spell(original);
+        let archive = serialize_artifact(&artifact);
+        let body_offset = ArchiveHeader::parse(&archive)
+            .expect("свежий архив имеет заголовок")
+            .1;
+        let stats = ArchiveStats::measure(original, &artifact, archive.len() - body_offset);
+        attach_stats(archive, &stats).expect("свежий архив текущей версии")
+    }
+
+    /// Сжатие с четностью заголовка и модели 🛡️
+    ///
+    /// Для долгого хранения: порча словаря или таблицы частот в пределах
+    /// одной полосы (см. [`attach_parity`](crate::format::attach_parity))
+    /// исправляется при распаковке. Тело архива то же, что у
+    /// [`compress_data`].
+    pub fn compress_with_parity(original: impl AsRef<[u8]>) -> Vec<u8> {
+        attach_parity(compress_data(original)).expect("свежий архив текущей версии")
+    }
 
-        // Сериализация в единый поток
-        // Формат: [словарь][таблица_частот][общая_частота][данные]
-        let mut result = Vec::new();
+    /// Сжатие независимыми блоками по `block_size` байтов 🧱
+    ///
+    /// Блоки распаковываются параллельно (см. [`DecodeOptions::decode_threads`]).
+    /// Каждый блок несет свой словарь, поэтому мелкие блоки сжимаются хуже;
+    /// разумный размер — от [`DEFAULT_BLOCK_SIZE`](crate::format::DEFAULT_BLOCK_SIZE).
+    ///
+    /// Паникует при нулевом `block_size`.
+    pub fn compress_data_in_blocks(original: impl AsRef<[u8]>, block_size: usize) -> Vec<u8> {
+        assert!(block_size > 0, "размер блока должен быть положительным");
+        let blocks: Vec<(u64, Vec<u8>)> = original
+            .as_ref()
+            .chunks(block_size)
+            .map(|block| (block.len() as u64, compress_data(block)))
+            .collect();
+        write_block_archive(&blocks)
+    }
+
+    /// Сжатие блоками с маркерами синхронизации 🔖
+    ///
+    /// Как [`compress_data_in_blocks`], но перед каждым блоком стоит маркер
+    /// и хеш блока (28 байтов на блок). Обычная распаковка такого архива
+    /// не меняется, а [`decompress_lossy`] после поврежденного участка
+    /// находит следующий блок по маркеру.
+    ///
+    /// Паникует при нулевом `block_size`.
+    pub fn compress_data_in_synced_blocks(
+        original: impl AsRef<[u8]>,
+        block_size: usize,
+    ) -> Vec<u8> {
+        assert!(block_size > 0, "размер блока должен быть положительным");
+        let blocks: Vec<(u64, Vec<u8>)> = original
+            .as_ref()
+            .chunks(block_size)
+            .map(|block| (block.len() as u64, compress_data(block)))
+            .collect();
+        write_synced_block_archive(&blocks)
+    }
 
-        // Словарь
-        result.extend_from_slice(&(artifact.mystical_word_grimoire.len() as u32).to_le_bytes());
-        for word in &artifact.mystical_word_grimoire {
-            result.extend_from_slice(&(word.len() as u32).to_le_bytes());
-            result.extend_from_slice(word.as_bytes());
+    /// Сжатие со структурной предобработкой 🧩
+    ///
+    /// `StructuredMode::Json` раскладывает ключи, строковые значения, числа
+    /// и пунктуацию по отдельным потокам: ключи, повторяющиеся в каждой
+    /// записи (JSON Lines, выгрузки API), почти ничего не стоят.
+    /// `StructuredMode::LogTemplates` кодирует повторяющиеся строки журналов
+    /// номером шаблона и переменными полями. `StructuredMode::Delimited`
+    /// транспонирует CSV/TSV и сжимает каждый столбец своей моделью
+    /// (разделитель угадывает [`detect_delimiter`](crate::compression_engine::detect_delimiter)).
+    /// `StructuredMode::Wide16` читает вход парами байтов little-endian
+    /// (UTF-16, 16-битные отсчеты); хвост нечетной длины хранится как есть.
+    /// `StructuredMode::Utf8` моделирует кодовые точки UTF-8 вместо байтов
+    /// (кириллица, CJK); недопустимые байты хранятся как есть.
+    /// Любой вход восстанавливается без потерь обычной [`decompress_data`].
+    pub fn compress_structured(original: impl AsRef<[u8]>, mode: StructuredMode) -> Vec<u8> {
+        match mode {
+            StructuredMode::Plain => compress_data(original),
+            StructuredMode::Delimited { delimiter } => ColumnarEngine::new(delimiter)
+                .compress(original.as_ref(), &EngineConfig::default())
+                .to_bytes(),
+            _ => EngineRegistry::with_builtin_engines()
+                .compress(mode.engine_kind(), original, &EngineConfig::default())
+                .expect("структурные режимы — встроенные движки")
+                .to_bytes(),
         }
+    }
+
+    /// Сжатие с выбранной стадией предобработки 🪜
+    ///
+    /// `PipelineStage::BitPlanes` делит байты на восемь битовых плоскостей
+    /// и кодирует каждую своей адаптивной моделью: выгодно для битовых
+    /// карт, фильтров Блума и булевых столбцов. `PipelineStage::Dedup`
+    /// хранит повторяющиеся фрагменты входа один раз — для образов дисков
+    /// и резервных копий, а `PipelineStage::LongRepeats` заменяет блоки от
+    /// 4 КиБ, уже встреченные во входе, ссылками назад. Распаковка —
+    /// обычная [`decompress_data`].
+    pub fn compress_with_stage(original: impl AsRef<[u8]>, stage: PipelineStage) -> Vec<u8> {
+        EngineRegistry::with_builtin_engines()
+            .compress(stage.engine_kind(), original, &EngineConfig::default())
+            .expect("стадии конвейера — встроенные движки")
+            .to_bytes()
+    }
 
-        // Таблица частот
-        result.extend_from_slice(&(artifact.mystical_frequency_codex.len() as u32).to_le_bytes());
-        for &(symbol, freq, start) in &artifact.mystical_frequency_codex {
-            result.extend_from_slice(&symbol.to_le_bytes());
-            result.extend_from_slice(&freq.to_le_bytes());
-            result.extend_from_slice(&start.to_le_bytes());
+    /// Сжатие встроенным движком `config.engine` с параметрами `config` ⚙️
+    ///
+    /// С `verify_roundtrip` архив перед возвратом распаковывается и
+    /// сверяется со входом — режим для резервных копий:
+    ///
+    /// ```
+    /// use arithmetic_compression_wizard::compression_engine::EngineConfig;
+    /// use arithmetic_compression_wizard::simple_api::{decompress_data, try_compress_with_config};
+    ///
+    /// let config = EngineConfig::default().with_verify_roundtrip(true);
+    /// let archive = try_compress_with_config(b"nightly backup", &config).unwrap();
+    /// assert_eq!(decompress_data(&archive), b"nightly backup");
+    /// ```
+    ///
+    /// Несовпадение дает [`WizardError::VerificationFailed`](crate::error::WizardError::VerificationFailed).
+    /// Без [`EngineConfig::reproducible`] кадр получает штамп
+    /// [`Provenance::now`] — время создания и версию библиотеки.
+    ///
+    /// Кадр, который вышел длиннее кадра хранения, заменяется кадром
+    /// хранения: архив никогда не длиннее [`max_compressed_len`]. Кроме
+    /// кадров со словарем — пресетом, внешним словарем или
+    /// [`EngineConfig::grimoire`]: их словарь и `dictionary_id` важнее
+    /// нескольких байтов, и они остаются как есть.
+    ///
+    /// С [`EngineConfig::auto_model`] движок и модель выбирает
+    /// [`probe_model`](crate::compression_engine::probe_model), а решение
+    /// записывается в метаданные архива.
+    ///
+    /// Если [`EngineConfig::deadline`] уже истек, вход сразу уходит кадром
+    /// хранения; истекший посреди кадра срок завершает кадр хранением там
+    /// же, в движке.
+    pub fn try_compress_with_config(
+        original: impl AsRef<[u8]>,
+        config: &EngineConfig,
+    ) -> WizardResult<Vec<u8>> {
+        let original = original.as_ref();
+        let choice = config.auto_model.then(|| probe_model(original, config));
+        let chosen_config = choice.map(|choice| choice.apply(config));
+        let config = chosen_config.as_ref().unwrap_or(config);
+        // Срок истек: кадр хранения без работы движка
+        let mut frame = if config.deadline_passed() {
+            StoredEngine.compress(original, config).to_bytes()
+        } else {
+            EngineRegistry::with_builtin_engines()
+                .compress(config.engine, original, config)?
+                .to_bytes()
+        };
+        if frame.len() > STORED_FRAME_OVERHEAD + original.len()
+            && !carries_dictionary(&frame, config)
+        {
+            frame = StoredEngine.compress(original, config).to_bytes();
+        }
+        if let Some(choice) = choice {
+            frame = attach_auto_model_note(frame, &choice)?;
+        }
+        if config.reproducible {
+            return Ok(frame);
         }
+        attach_provenance(frame, &Provenance::now())
+    }
+
+    /// Верхняя граница длины архива [`try_compress_with_config`] 📏
+    ///
+    /// Граница — кадр хранения (заголовок и вход как есть) плюс штамп
+    /// происхождения, если `config` не [`EngineConfig::reproducible`], и
+    /// запись решения с [`EngineConfig::auto_model`]. Кадры со словарем
+    /// (пресет, [`EngineConfig::grimoire`]) кадром хранения не заменяются,
+    /// и граница к ним не относится.
+    /// По ней заранее выделяют буфер или слот хранилища:
+    ///
+    /// ```
+    /// use arithmetic_compression_wizard::compression_engine::EngineConfig;
+    /// use arithmetic_compression_wizard::simple_api::{max_compressed_len, try_compress_with_config};
+    ///
+    /// let config = EngineConfig::default();
+    /// let noise: Vec<u8> = (0..64u32).map(|step| (step * 151 % 256) as u8).collect();
+    /// let archive = try_compress_with_config(&noise, &config).unwrap();
+    /// assert!(archive.len() <= max_compressed_len(noise.len(), &config));
+    /// ```
+    pub fn max_compressed_len(input_len: usize, config: &EngineConfig) -> usize {
+        let provenance_len = if config.reproducible {
+            0
+        } else {
+            PROVENANCE_SECTION_LEN
+        };
+        // Раздел метаданных с одной парой: длина и текст ключа и значения
+        let auto_model_len = if config.auto_model {
+            HEADER_SECTION_OVERHEAD
+                + 4
+                + AUTO_MODEL_METADATA_KEY.len()
+                + 4
+                + ModelChoice::widest().describe().len()
+        } else {
+            0
+        };
+        STORED_FRAME_OVERHEAD
+            .saturating_add(provenance_len)
+            .saturating_add(auto_model_len)
+            .saturating_add(input_len)
+    }
+
+    /// Записывает решение автоматического выбора в метаданные кадра
+    fn attach_auto_model_note(frame: Vec<u8>, choice: &ModelChoice) -> WizardResult<Vec<u8>> {
+        let note = HashMap::from([(AUTO_MODEL_METADATA_KEY.to_string(), choice.describe())]);
+        attach_metadata(frame, &note)
+    }
 
-        // Общая частота
-        result.extend_from_slice(&artifact.total_frequency_essence.to_le_bytes());
+    /// Длина кадра хранения сверх самих данных: заголовок без разделов
+    const STORED_FRAME_OVERHEAD: usize = SECTIONS_OFFSET + 1;
 
-        // Сжатые данные
-        result.extend_from_slice(&(artifact.compressed_bit_stream.len() as u32).to_le_bytes());
-        result.extend_from_slice(&artifact.compressed_bit_stream);
+    /// Кадр несет словарь, который нельзя заменить кадром хранения
+    fn carries_dictionary(frame: &[u8], config: &EngineConfig) -> bool {
+        config
+            .grimoire
+            .as_ref()
+            .is_some_and(|grimoire| !grimoire.is_empty())
+            || ArchiveHeader::parse(frame).is_some_and(|(header, _)| header.dictionary_id.is_some())
+    }
 
-        result
+    /// Сжатие файла в файл без обрубков при сбоях 📁
+    ///
+    /// То же, что [`compress_file_with`] с [`FsyncPolicy::File`].
+    pub fn compress_file(
+        input: impl AsRef<Path>,
+        output: impl AsRef<Path>,
+        config: &EngineConfig,
+    ) -> io::Result<()> {
+        compress_file_with(input, output, config, FsyncPolicy::default())
+    }
+
+    /// Сжатие файла в файл с выбранной политикой fsync
+    ///
+    /// Вход читается потоком и сжимается блоками по
+    /// [`DEFAULT_BLOCK_SIZE`](crate::format::DEFAULT_BLOCK_SIZE) с
+    /// параметрами `config` во временный файл [`AtomicFile`], который
+    /// встает на место `output` только после успешного сжатия. При любой
+    /// ошибке под именем `output` остается прежний файл (или ничего).
+    /// Ошибка сжатия блока приходит как [`io::ErrorKind::InvalidInput`] с
+    /// [`WizardError`](crate::error::WizardError) внутри. Результат — архив
+    /// из блоков для [`decompress_data`].
+    pub fn compress_file_with(
+        input: impl AsRef<Path>,
+        output: impl AsRef<Path>,
+        config: &EngineConfig,
+        fsync: FsyncPolicy,
+    ) -> io::Result<()> {
+        let mut source = File::open(input)?;
+        let target = AtomicFile::create_with(output, fsync)?;
+        let mut session =
+            BlockStreamWriter::with_config(target, DEFAULT_BLOCK_SIZE, config.clone())?;
+        io::copy(&mut source, &mut session)?;
+        session.finish()?.commit()
+    }
+
+    /// Сжатие байтов итератора без сбора входа в `Vec` 🌊
+    ///
+    /// Для производителей, порождающих данные на лету (сериализаторы,
+    /// генераторы): байты набираются в блоки по
+    /// [`DEFAULT_BLOCK_SIZE`](crate::format::DEFAULT_BLOCK_SIZE) и сжимаются
+    /// потоковым писателем [`BlockStreamWriter`](crate::format::BlockStreamWriter)
+    /// с параметрами `config`, так что в памяти находится не больше одного
+    /// блока входа. Результат — архив из блоков для [`decompress_data`]:
+    ///
+    /// ```
+    /// use arithmetic_compression_wizard::compression_engine::EngineConfig;
+    /// use arithmetic_compression_wizard::simple_api::{compress_iter, decompress_data};
+    ///
+    /// let rows = (0..100).flat_map(|row| format!("row {row};").into_bytes());
+    /// let archive = compress_iter(rows, &EngineConfig::default());
+    /// assert!(decompress_data(&archive).starts_with(b"row 0;row 1;"));
+    /// ```
+    ///
+    /// Паникует там же, где [`try_compress_iter`].
+    pub fn compress_iter(bytes: impl IntoIterator<Item = u8>, config: &EngineConfig) -> Vec<u8> {
+        try_compress_iter(bytes, config)
+            .expect("блок не сжат с параметрами config: используйте try_compress_iter")
+    }
+
+    /// Проверяемое сжатие байтов итератора
+    ///
+    /// Ошибка сжатия блока — как у [`try_compress_with_config`].
+    pub fn try_compress_iter(
+        bytes: impl IntoIterator<Item = u8>,
+        config: &EngineConfig,
+    ) -> WizardResult<Vec<u8>> {
+        // Итератор отдает байты кусками: писатель принимает срезы
+        const ITERATOR_CHUNK: usize = 8 * 1024;
+
+        let mut session =
+            BlockStreamWriter::with_config(Vec::new(), DEFAULT_BLOCK_SIZE, config.clone())
+                .map_err(into_compression_error)?;
+        let mut bytes = bytes.into_iter();
+        let mut chunk = Vec::with_capacity(ITERATOR_CHUNK);
+        loop {
+            chunk.clear();
+            chunk.extend(bytes.by_ref().take(ITERATOR_CHUNK));
+            if chunk.is_empty() {
+                break;
+            }
+            session.write_all(&chunk).map_err(into_compression_error)?;
+        }
+        session.finish().map_err(into_compression_error)
+    }
+
+    /// Перекодирует архив другим движком или с другими параметрами 🔁
+    ///
+    /// Данные не покидают библиотеку: каждый кадр распаковывается и сразу
+    /// сжимается встроенным движком `target.engine`. Границы блоков
+    /// сохраняются. Архив проверяется лимитами [`DecodeOptions::default`].
+    pub fn transcode(
+        archive_bytes: impl AsRef<[u8]>,
+        target: EngineConfig,
+    ) -> WizardResult<Vec<u8>> {
+        EngineRegistry::with_builtin_engines().transcode(
+            archive_bytes,
+            &target,
+            &DecodeOptions::default(),
+        )
+    }
+
+    /// Сжатие новой версии данных относительно базы 🩹
+    ///
+    /// Совпадающие с `base` участки становятся копиями со смещением в
+    /// базе, поэтому патч мал, если `new` — правка `base`. Распаковка
+    /// требует ту же базу: [`decompress_delta`].
+    pub fn compress_delta(base: impl AsRef<[u8]>, new: impl AsRef<[u8]>) -> Vec<u8> {
+        forge_delta(base.as_ref(), new.as_ref())
+    }
+
+    /// Восстанавливает данные из базы и патча [`compress_delta`]
+    ///
+    /// Паникует на чужой базе или поврежденном патче — для недоверенного
+    /// ввода используйте [`try_decompress_delta`].
+    pub fn decompress_delta(base: impl AsRef<[u8]>, patch: impl AsRef<[u8]>) -> Vec<u8> {
+        try_decompress_delta(base, patch, &DecodeOptions::unlimited())
+            .expect("патч поврежден или база другая: используйте try_decompress_delta")
+    }
+
+    /// Проверяемое применение патча
+    ///
+    /// Чужая база дает [`WizardError::DeltaBaseMismatch`](crate::error::WizardError::DeltaBaseMismatch).
+    pub fn try_decompress_delta(
+        base: impl AsRef<[u8]>,
+        patch: impl AsRef<[u8]>,
+        options: &DecodeOptions,
+    ) -> WizardResult<Vec<u8>> {
+        try_apply_delta(base.as_ref(), patch.as_ref(), options)
     }
 
     /// Простая функция декомпрессии
     /// Восстанавливает данные, сжатые через `compress_data()`
-    pub fn decompress_data(compressed: Vec<u8>) -> Vec<u8> {
-        let mut cursor = 0;
-
-        // Безопасное чтение байтов
-        let read_u32 = |cursor: &mut usize| -> u32 {
-            let result = u32::from_le_bytes([
-                compressed[*cursor],
-                compressed[*cursor + 1],
-                compressed[*cursor + 2],
-                compressed[*cursor + 3],
-            ]);
-            *cursor += 4;
-            result
-        };
+    ///
+    /// Понимает и устаревшие архивы без заголовка.
+    /// Паникует на поврежденных данных — для недоверенного ввода
+    /// используйте [`try_decompress_data`].
+    pub fn decompress_data(compressed: impl AsRef<[u8]>) -> Vec<u8> {
+        try_decompress_data(compressed, &DecodeOptions::unlimited())
+            .expect("архив поврежден: используйте try_decompress_data")
+    }
 
-        let read_u64 = |cursor: &mut usize| -> u64 {
-            let result = u64::from_le_bytes([
-                compressed[*cursor],
-                compressed[*cursor + 1],
-                compressed[*cursor + 2],
-                compressed[*cursor + 3],
-                compressed[*cursor + 4],
-                compressed[*cursor + 5],
-                compressed[*cursor + 6],
-                compressed[*cursor + 7],
-            ]);
-            *cursor += 8;
-            result
+    /// Проверяемая декомпрессия для недоверенных данных 🛡️
+    ///
+    /// Гарантирует отсутствие паники на любых входных байтах и
+    /// ограничивает размер результата, словаря и таблицы частот лимитами `options`.
+    /// Архивы из блоков распаковываются на `options.decode_threads` потоках.
+    pub fn try_decompress_data(
+        compressed: impl AsRef<[u8]>,
+        options: &DecodeOptions,
+    ) -> WizardResult<Vec<u8>> {
+        let compressed = compressed.as_ref();
+        let stopwatch = Stopwatch::start();
+        let result = try_decompress_archive(compressed, options);
+        record_decompression(
+            stopwatch,
+            compressed.len(),
+            result.as_ref().ok().map(Vec::len),
+        );
+        result
+    }
+
+    /// Распаковка архива любой раскладки без замеров
+    fn try_decompress_archive(compressed: &[u8], options: &DecodeOptions) -> WizardResult<Vec<u8>> {
+        let compressed = restore_parity(compressed);
+        let compressed = &compressed[..];
+        let restored = if is_micro_archive(compressed) {
+            try_unweave_micro_archive(compressed, options)?
+        } else {
+            let (header, body_offset) = ArchiveHeader::try_parse(compressed)?;
+            let body = &compressed[body_offset..];
+
+            if header.block_framed {
+                // Ход распаковки блоков сообщает дирижер
+                let blocks = read_block_ledger(&header, body, options)
+                    .map_err(|error| error.shifted(body_offset))?;
+                return try_decode_blocks(compressed, &blocks, options, try_decompress_frame);
+            }
+            try_decompress_frame_body(&header, body, options)
+                .map_err(|error| error.shifted(body_offset))?
         };
+        options.report_progress(restored.len() as u64);
+        Ok(restored)
+    }
+
+    /// Распаковка поврежденного архива с пропуском потерянных блоков 🛟
+    ///
+    /// Для восстановления данных и экспертизы: вместо первой ошибки
+    /// возвращается все, что удалось распаковать, и список потерь
+    /// [`BlockError`] в порядке архива. Потерянные блоки в результат не
+    /// попадают — их места указывает `output_offset`. Архив из
+    /// [`compress_data_in_synced_blocks`] переживает порчу любого блока
+    /// и даже заголовка; в прочих архивах из блоков теряются блоки с
+    /// поврежденными кадрами, а порча реестра или архива без блоков
+    /// стоит всего результата. Лимиты — [`DecodeOptions::default`].
+    ///
+    /// ```
+    /// use arithmetic_compression_wizard::simple_api::{compress_data_in_synced_blocks, decompress_lossy};
+    ///
+    /// let data = b"owl post ".repeat(100);
+    /// let mut archive = compress_data_in_synced_blocks(&data, 300);
+    /// let middle = archive.len() / 2;
+    /// archive[middle] ^= 0xFF;
+    ///
+    /// let (restored, errors) = decompress_lossy(&archive);
+    /// assert_eq!(errors.len(), 1);
+    /// assert_eq!(restored.len(), data.len() - 300);
+    /// ```
+    pub fn decompress_lossy(archive_bytes: impl AsRef<[u8]>) -> (Vec<u8>, Vec<BlockError>) {
+        salvage_archive(archive_bytes.as_ref(), &DecodeOptions::default())
+    }
 
-        // Словарь
-        let word_count = read_u32(&mut cursor) as usize;
-        let mut word_grimoire = Vec::with_capacity(word_count);
+    /// Распаковка только первых `n` байтов исходника 👀
+    ///
+    /// Для определения типа содержимого и превью без полной распаковки:
+    /// арифметический поток декодируется по порядку и останавливается,
+    /// как только набрано `n` байтов. В архиве из блоков распаковываются
+    /// только блоки, покрывающие префикс. Архивы прочих движков
+    /// распаковываются целиком и обрезаются. Короткий исходник
+    /// возвращается полностью.
+    ///
+    /// ```
+    /// use arithmetic_compression_wizard::simple_api::{compress_data, decompress_prefix};
+    ///
+    /// let archive = compress_data("%PDF-1.7 the rest of a long document ".repeat(100));
+    /// assert_eq!(decompress_prefix(&archive, 8), b"%PDF-1.7");
+    /// ```
+    pub fn decompress_prefix(compressed: impl AsRef<[u8]>, n: usize) -> Vec<u8> {
+        try_decompress_prefix(compressed, n, &DecodeOptions::unlimited())
+            .expect("архив поврежден: используйте try_decompress_prefix")
+    }
+
+    /// Проверяемая распаковка первых `n` байтов для недоверенных данных
+    ///
+    /// Повреждение за пределами прочитанной части архива не обнаруживается:
+    /// контрольные суммы блоков проверяются только у блоков, распакованных
+    /// целиком.
+    pub fn try_decompress_prefix(
+        compressed: impl AsRef<[u8]>,
+        n: usize,
+        options: &DecodeOptions,
+    ) -> WizardResult<Vec<u8>> {
+        let compressed = restore_parity(compressed.as_ref());
+        let compressed = &compressed[..];
+        if is_micro_archive(compressed) {
+            let mut restored = try_unweave_micro_archive(compressed, options)?;
+            restored.truncate(n);
+            return Ok(restored);
+        }
+        let (header, body_offset) = ArchiveHeader::try_parse(compressed)?;
+        let body = &compressed[body_offset..];
+        if !header.block_framed {
+            return try_decompress_frame_body_prefix(&header, body, n, options);
+        }
 
-        for _ in 0..word_count {
-            let word_len = read_u32(&mut cursor) as usize;
-            let word_bytes = &compressed[cursor..cursor + word_len];
-            word_grimoire.push(String::from_utf8_lossy(word_bytes).into_owned());
-            cursor += word_len;
+        let mut restored = Vec::new();
+        for block in &read_block_ledger(&header, body, options)? {
+            let remaining = n - restored.len();
+            if remaining == 0 {
+                break;
+            }
+            if block.original_len <= remaining as u64 {
+                restored.extend_from_slice(&decode_block(block, options, &try_decompress_frame)?);
+            } else {
+                // Последний нужный блок распаковывается лишь до конца префикса
+                let frame = restore_parity(block.frame);
+                let (frame_header, frame_offset) = ArchiveHeader::try_parse(&frame)?;
+                restored.extend_from_slice(&try_decompress_frame_body_prefix(
+                    &frame_header,
+                    &frame[frame_offset..],
+                    remaining,
+                    options,
+                )?);
+                break;
+            }
         }
+        Ok(restored)
+    }
+
+    /// Проверяемая декомпрессия вместе с метаданными архива
+    pub fn try_decompress_with_metadata(
+        compressed: impl AsRef<[u8]>,
+        options: &DecodeOptions,
+    ) -> WizardResult<(Vec<u8>, HashMap<String, String>)> {
+        let compressed = compressed.as_ref();
+        let metadata = read_metadata(compressed)?;
+        Ok((try_decompress_data(compressed, options)?, metadata))
+    }
+
+    /// Распаковка одиночного кадра (архив из блоков сюда не попадает)
+    pub(crate) fn try_decompress_frame(
+        frame: &[u8],
+        options: &DecodeOptions,
+    ) -> WizardResult<Vec<u8>> {
+        let frame = restore_parity(frame);
+        let (header, body_offset) = ArchiveHeader::try_parse(&frame)?;
+        try_decompress_frame_body(&header, &frame[body_offset..], options)
+            .map_err(|error| error.shifted(body_offset))
+    }
 
-        // Таблица частот
-        let freq_count = read_u32(&mut cursor) as usize;
-        let mut frequency_codex = Vec::with_capacity(freq_count);
+    /// Распаковка тела кадра выбранным движком
+    ///
+    /// Смещения в ошибках отсчитываются от начала тела; ошибка движка,
+    /// не знающего место порчи, привязывается к началу тела.
+    fn try_decompress_frame_body(
+        header: &ArchiveHeader,
+        body: &[u8],
+        options: &DecodeOptions,
+    ) -> WizardResult<Vec<u8>> {
+        try_unweave_frame_body(header, body, options)
+            .map_err(|error| error.located(DecodePhase::Payload, 0))
+    }
 
-        for _ in 0..freq_count {
-            let symbol = read_u32(&mut cursor);
-            let freq = read_u64(&mut cursor);
-            let start = read_u64(&mut cursor);
-            frequency_codex.push((symbol, freq, start));
+    /// Выбор движка по заголовку кадра
+    fn try_unweave_frame_body(
+        header: &ArchiveHeader,
+        body: &[u8],
+        options: &DecodeOptions,
+    ) -> WizardResult<Vec<u8>> {
+        if header.dictionary_id.is_some() {
+            // Кадр общей модели распаковывается только встроенным пресетом
+            return try_unweave_preset_body(header, body, options);
         }
+        match header.engine {
+            EngineKind::SplitStreams => try_unweave_split_body(header, body, options),
+            EngineKind::StructuredJson => try_unweave_json_body(header, body, options),
+            EngineKind::LogTemplates => try_unweave_symbolized_body(
+                header,
+                body,
+                EngineKind::LogTemplates,
+                &LogTemplateSymbolizer,
+                options,
+            ),
+            EngineKind::Columnar => try_unweave_columnar_body(header, body, options),
+            EngineKind::Stored => try_unstore_body(header, body, options),
+            EngineKind::BitPlanes => try_unweave_bit_planes_body(header, body, options),
+            EngineKind::Dedup => try_undedup_body(header, body, options),
+            EngineKind::LongRepeats => try_unweave_repeats_body(header, body, options),
+            EngineKind::Mix => try_unweave_mix_body(header, body, options),
+            EngineKind::Wide16 => try_unweave_symbolized_body(
+                header,
+                body,
+                EngineKind::Wide16,
+                &WideSymbolizer,
+                options,
+            ),
+            EngineKind::Utf8 => try_unweave_symbolized_body(
+                header,
+                body,
+                EngineKind::Utf8,
+                &CodePointSymbolizer,
+                options,
+            ),
+            _ if matches!(header.model_kind, ModelKind::Adaptive { .. }) => {
+                try_unweave_adaptive_body(header, body, options)
+            }
+            _ if matches!(header.model_kind, ModelKind::HashedOrder2 { .. }) => {
+                try_unweave_context_body(header, body, options)
+            }
+            _ => {
+                // Словарь и таблица читаются прямо из буфера архива
+                let artifact_ref = parse_artifact_ref_body(header, body, options)?;
+                let stream_offset = offset_within(body, artifact_ref.compressed_bit_stream);
+                try_unweave_artifact_ref(&artifact_ref, options).map_err(|error| {
+                    error
+                        .shifted(stream_offset)
+                        .located(DecodePhase::Payload, stream_offset)
+                })
+            }
+        }
+    }
 
-        // Общая частота
-        let total_frequency = read_u64(&mut cursor);
+    /// Распаковка первых `limit` байтов тела кадра
+    ///
+    /// Останавливаться умеет арифметический движок со статической и
+    /// адаптивными моделями; остальные кадры распаковываются целиком.
+    fn try_decompress_frame_body_prefix(
+        header: &ArchiveHeader,
+        body: &[u8],
+        limit: usize,
+        options: &DecodeOptions,
+    ) -> WizardResult<Vec<u8>> {
+        if header.dictionary_id.is_none() && header.engine == EngineKind::Arithmetic {
+            match header.model_kind {
+                ModelKind::Adaptive { .. } => {
+                    return try_unweave_adaptive_prefix(header, body, limit, options);
+                }
+                ModelKind::HashedOrder2 { .. } => {
+                    return try_unweave_context_prefix(header, body, limit, options);
+                }
+                ModelKind::StaticOrder0 | ModelKind::SharedOrder0 => {}
+            }
+            let artifact_ref = parse_artifact_ref_body(header, body, options)?;
+            return try_unweave_artifact_ref_prefix(&artifact_ref, limit, options);
+        }
+        let mut restored = try_decompress_frame_body(header, body, options)?;
+        restored.truncate(limit);
+        Ok(restored)
+    }
 
-        // Сжатые данные
-        let compressed_len = read_u32(&mut cursor) as usize;
-        let compressed_data = compressed[cursor..cursor + compressed_len].to_vec();
+    /// Сжатие в `Bytes` без копирования результата (фича `bytes`)
+    #[cfg(feature = "bytes")]
+    pub fn compress_bytes(original: impl AsRef<[u8]>) -> bytes::Bytes {
+        bytes::Bytes::from(compress_data(original))
+    }
 
-        // Восстановление артефакта
-        let artifact = CompressionArtifact {
-            mystical_frequency_codex: frequency_codex,
-            total_frequency_essence: total_frequency,
-            compressed_bit_stream: compressed_data,
-            mystical_word_grimoire: word_grimoire,
-        };
+    /// Декомпрессия в `Bytes` без копирования результата (фича `bytes`)
+    #[cfg(feature = "bytes")]
+    pub fn decompress_bytes(compressed: impl AsRef<[u8]>) -> bytes::Bytes {
+        bytes::Bytes::from(decompress_data(compressed))
+    }
 
-        unweave_compression_spell(artifact)
+    /// Декомпрессия в изменяемый `BytesMut` без копирования (фича `bytes`)
+    #[cfg(feature = "bytes")]
+    pub fn decompress_bytes_mut(compressed: impl AsRef<[u8]>) -> bytes::BytesMut {
+        decompress_bytes(compressed)
+            .try_into_mut()
+            .unwrap_or_else(|shared| bytes::BytesMut::from(&shared[..]))
     }
 }
 
@@ -129,81 +850,70 @@ pub mod prelude {
     pub use crate::compression_engine::compression_conjurer::{
         weave_compression_spell, CompressionArtifact,
     };
+    pub use crate::compression_engine::compressor_handle::{CompressorHandle, HandleArtifact};
+    pub use crate::compression_engine::engine_covenant::{PipelineStage, StructuredMode};
+    pub use crate::compression_engine::grimoire_ward::DenyList;
+    pub use crate::compression_engine::stored_vault::CompressOutcome;
+    pub use crate::decompression_oracle::decode_wards::DecodeOptions;
     pub use crate::decompression_oracle::decompression_sage::unweave_compression_spell;
-    pub use crate::simple_api::{compress_data, decompress_data};
+    pub use crate::error::{WizardError, WizardResult};
+    pub use crate::format::artifact_scroll::ScrollOptions;
+    #[cfg(feature = "bytes")]
+    pub use crate::simple_api::{compress_bytes, decompress_bytes, decompress_bytes_mut};
+    pub use crate::simple_api::{
+        compress_compact, compress_data, compress_data_adaptive, compress_data_in_blocks,
+        compress_data_redacted, compress_data_with_options, compress_delta, compress_if_beneficial,
+        compress_iter, compress_structured, compress_with_metadata, compress_with_stage,
+        decompress_data, decompress_delta, decompress_prefix, try_compress_data,
+        try_decompress_data, try_decompress_delta, try_decompress_prefix,
+        try_decompress_with_metadata,
+    };
 }
 
-/// Статистика сжатия 📊
-/// Анализ эффективности и метрики
-pub mod statistics {
-    use crate::prelude::*;
-    use std::collections::HashMap;
-
-    /// Результаты анализа сжатия
-    #[derive(Debug, Clone)]
-    pub struct CompressionAnalysis {
-        /// Размер исходных данных в байтах
-        pub original_size: usize,
-        /// Размер сжатых данных в байтах
-        pub compressed_size: usize,
-        /// Коэффициент сжатия в процентах
-        pub compression_ratio: f64,
-        /// Энтропия Шеннона исходных данных
-        pub shannon_entropy: f64,
-        /// Достигнутая плотность сжатия
-        pub compression_density: f64,
-        /// Количество слов в словаре
-        pub word_dictionary_size: usize,
-        /// Наиболее частые символы
-        pub top_symbols: Vec<(u32, u64)>,
-    }
-
-    /// Анализирует эффективность сжатия
-    pub fn analyze_compression(data: &[u8]) -> CompressionAnalysis {
-        let artifact = weave_compression_spell(data);
-
-        // Энтропия Шеннона
-        let mut freq = HashMap::new();
-        for &byte in data {
-            *freq.entry(byte).or_insert(0u64) += 1;
-        }
-
-        let total = data.len() as f64;
-        let mut entropy = 0.0;
-        for count in freq.values() {
-            let p = (*count as f64) / total;
-            entropy -= p * p.log2();
-        }
-
-        let compressed_size = artifact.compressed_bit_stream.len();
-        let compression_ratio = (1.0 - compressed_size as f64 / data.len() as f64) * 100.0;
-        let compression_density = compressed_size as f64 * 8.0 / data.len() as f64;
+/// Простые имена API 🏷️
+///
+/// Тематические имена (`weave_compression_spell`, `CompressionArtifact`)
+/// остаются, а здесь те же функции и типы собраны под обычными именами.
+/// Имена этого модуля — обещание совместимости: внутренние модули можно
+/// переименовывать, а `stable::compress` и остальные остаются на месте
+/// с той же сигнатурой до смены старшей версии.
+///
+/// ```
+/// use arithmetic_compression_wizard::stable;
+///
+/// let archive = stable::compress(b"plain names for plain work");
+/// assert_eq!(stable::decompress(&archive), b"plain names for plain work");
+///
+/// let artifact: stable::Artifact = stable::compress_artifact(b"abracadabra");
+/// let table: &stable::FrequencyTable = &artifact.mystical_frequency_codex;
+/// assert!(!table.is_empty());
+/// assert_eq!(stable::decompress_artifact(artifact), b"abracadabra");
+/// ```
+pub mod stable {
+    pub use crate::compression_engine::compression_conjurer::{
+        try_weave_compression_spell as try_compress_artifact,
+        weave_compression_spell as compress_artifact, CompressionArtifact as Artifact,
+    };
+    pub use crate::compression_engine::engine_covenant::EngineConfig as Config;
+    pub use crate::decompression_oracle::decode_wards::DecodeOptions;
+    pub use crate::decompression_oracle::decompression_sage::unweave_compression_spell as decompress_artifact;
+    pub use crate::error::{WizardError as Error, WizardResult as Result};
+    pub use crate::simple_api::{
+        compress_data as compress, decompress_data as decompress, max_compressed_len,
+        try_compress_data as try_compress, try_compress_with_config as compress_with_config,
+        try_decompress_data as try_decompress,
+    };
 
-        // Топ символов
-        let mut symbol_freq: Vec<_> = artifact
-            .mystical_frequency_codex
-            .iter()
-            .map(|&(symbol, freq, _)| (symbol, freq))
-            .collect();
-        symbol_freq.sort_by_key(|&(_, freq)| std::cmp::Reverse(freq));
-        symbol_freq.truncate(10);
-
-        CompressionAnalysis {
-            original_size: data.len(),
-            compressed_size,
-            compression_ratio,
-            shannon_entropy: entropy,
-            compression_density,
-            word_dictionary_size: artifact.mystical_word_grimoire.len(),
-            top_symbols: symbol_freq,
-        }
-    }
+    /// Таблица частот артефакта: символ, частота и накопительная позиция
+    pub type FrequencyTable = Vec<(u32, u64, u64)>;
 }
 
 #[cfg(test)]
 mod comprehensive_tests {
     use super::simple_api::*;
     use super::statistics::*;
+    use crate::compression_engine::engine_covenant::StructuredMode;
+    use crate::decompression_oracle::decode_wards::DecodeOptions;
 
     #[test]
     fn test_round_trip_compression() {
@@ -255,6 +965,165 @@ mod comprehensive_tests {
         assert!(!analysis.top_symbols.is_empty());
     }
 
+    #[test]
+    fn test_as_ref_inputs() {
+        let owned: Vec<u8> = b"owned buffer".to_vec();
+        let text = String::from("string input");
+
+        assert_eq!(decompress_data(compress_data(&owned)), owned);
+        assert_eq!(decompress_data(compress_data(&text)), text.as_bytes());
+        assert_eq!(decompress_data(compress_data(b"array")), b"array");
+    }
+
+    #[test]
+    fn test_structured_json_roundtrip() {
+        use crate::prelude::StructuredMode;
+
+        let records = r#"{"id": 1, "name": "owl"}
+{"id": 2, "name": "wand"}
+{"id": -3.5e2, "name": "hat \"tall\""}
+"#;
+        for mode in [
+            StructuredMode::Plain,
+            StructuredMode::Json,
+            StructuredMode::LogTemplates,
+            StructuredMode::Delimited { delimiter: b',' },
+            StructuredMode::Wide16,
+            StructuredMode::Utf8,
+        ] {
+            let compressed = compress_structured(records, mode);
+            assert_eq!(decompress_data(&compressed), records.as_bytes());
+        }
+        assert_eq!(
+            decompress_data(compress_structured("", StructuredMode::Json)),
+            b""
+        );
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn test_bytes_interop() {
+        let original = bytes::Bytes::from_static(b"network buffer network buffer");
+
+        let compressed = compress_bytes(&original);
+        assert_eq!(decompress_bytes(&compressed), original);
+        assert_eq!(&decompress_bytes_mut(compressed)[..], &original[..]);
+    }
+
+    #[test]
+    fn test_prefix_matches_full_decode() {
+        let data = "<html><head><title>owl post</title></head> the owl flies. ".repeat(60);
+        let data = data.as_bytes();
+        for archive in [
+            compress_data(data),
+            compress_data_adaptive(data, 512),
+            compress_data_in_blocks(data, 700),
+            compress_compact(data),
+            compress_structured(data, StructuredMode::Wide16),
+        ] {
+            for n in [0, 1, 6, 699, 700, 701, 1500, data.len(), data.len() + 10] {
+                assert_eq!(
+                    decompress_prefix(&archive, n),
+                    &data[..n.min(data.len())],
+                    "n = {n}"
+                );
+            }
+        }
+        assert_eq!(decompress_prefix(compress_data(b""), 4), b"");
+    }
+
+    #[test]
+    fn test_prefix_reads_only_what_it_needs() {
+        let data = "the owl carries scrolls between towers. ".repeat(100);
+        let data = data.as_bytes();
+
+        // Порча последнего блока не мешает префиксу из первого
+        let mut blocks = compress_data_in_blocks(data, 1000);
+        let last = blocks.len() - 3;
+        blocks[last] ^= 0xFF;
+        assert!(try_decompress_data(&blocks, &DecodeOptions::default()).is_err());
+        assert_eq!(decompress_prefix(&blocks, 1500), &data[..1500]);
+
+        // Лимит результата относится к префиксу, а не к исходнику
+        let archive = compress_data(data);
+        let options = DecodeOptions::default().with_max_output_bytes(64);
+        assert_eq!(
+            try_decompress_prefix(&archive, 16, &options).unwrap(),
+            &data[..16]
+        );
+        assert!(try_decompress_prefix(&archive, 4000, &options).is_err());
+        assert!(try_decompress_prefix(b"not an archive", 4, &options).is_err());
+    }
+
+    #[test]
+    fn test_compress_iter_streams_blocks() {
+        use crate::compression_engine::engine_covenant::EngineConfig;
+        use crate::format::container_runes::EngineKind;
+        use crate::format::DEFAULT_BLOCK_SIZE;
+
+        // Больше одного блока, без промежуточного Vec у производителя
+        let rows = (0..120_000u32).flat_map(|row| format!("row {row} ok\n").into_bytes());
+        let expected: Vec<u8> = rows.clone().collect();
+        assert!(expected.len() > DEFAULT_BLOCK_SIZE);
+        let archive = compress_iter(rows, &EngineConfig::default());
+        assert_eq!(decompress_data(&archive), expected);
+
+        assert!(decompress_data(compress_iter([], &EngineConfig::default())).is_empty());
+        assert_eq!(
+            try_compress_iter(
+                b"data".iter().copied(),
+                &EngineConfig::default().with_engine(EngineKind::Custom(200))
+            ),
+            Err(crate::error::WizardError::UnsupportedEngine(200))
+        );
+    }
+
+    #[test]
+    fn test_max_compressed_len_bounds_every_engine() {
+        use crate::compression_engine::engine_covenant::EngineConfig;
+        use crate::compression_engine::engine_registry::EngineRegistry;
+        use crate::format::container_runes::{ArchiveHeader, EngineKind};
+
+        let mut state = 0x2545_F491u32;
+        let noise: Vec<u8> = (0..3000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect();
+        let text = b"{\"spell\": \"lumos\", \"power\": 7}\n".repeat(40);
+        for engine in EngineRegistry::with_builtin_engines().engine_kinds() {
+            for reproducible in [false, true] {
+                let config = EngineConfig::default()
+                    .with_engine(engine)
+                    .with_reproducible(reproducible);
+                for input in [&b""[..], b"x", &noise[..7], &noise[..], &text[..]] {
+                    let archive = try_compress_with_config(input, &config).unwrap();
+                    assert!(archive.len() <= max_compressed_len(input.len(), &config));
+                    assert_eq!(decompress_data(&archive), input);
+                }
+            }
+        }
+
+        // Несжимаемый вход хранится как есть
+        let config = EngineConfig::default();
+        let archive = try_compress_with_config(&noise, &config).unwrap();
+        assert_eq!(archive.len(), max_compressed_len(noise.len(), &config));
+        let (header, _) = ArchiveHeader::parse(&archive).unwrap();
+        assert_eq!(header.engine, EngineKind::Stored);
+        assert_eq!(max_compressed_len(usize::MAX, &config), usize::MAX);
+
+        // Накладные расходы из констант совпадают с настоящими разделами
+        let stamped = config.clone().with_reproducible(false);
+        let archive = try_compress_with_config(&noise, &stamped).unwrap();
+        assert_eq!(archive.len(), max_compressed_len(noise.len(), &stamped));
+        let probed = config.with_auto_model(true);
+        let archive = try_compress_with_config(&noise, &probed).unwrap();
+        assert!(archive.len() <= max_compressed_len(noise.len(), &probed));
+    }
+
     #[test]
     fn test_empty_data() {
         let empty: &[u8] = b"";
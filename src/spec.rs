@@ -0,0 +1,628 @@
+//! Спецификация формата в векторах 📐
+//!
+//! Альтернативным реализациям (например, будущему декодеру на JS) нужен
+//! набор эталонных архивов: каждый встроенный движок, обе модели
+//! арифметического движка, словарь слов и без него, одиночный кадр и
+//! архив из блоков, общая модель с внешним словарем. [`canonical_vectors`]
+//! порождает такие архивы из небольших фиксированных входов, а
+//! [`SpecDocument::to_json`] выгружает их вместе с таблицами кодов
+//! формата.
+//!
+//! Проверка чужого декодера — [`validate_decoder`] (декодер вызывается
+//! прямо из Rust) или [`validate_outputs`] (результаты, собранные
+//! внешней программой, по именам векторов).
+
+use crate::compression_engine::compressor_handle::CompressorHandle;
+use crate::compression_engine::engine_covenant::EngineConfig;
+use crate::compression_engine::engine_registry::EngineRegistry;
+use crate::format::block_ledger::write_block_archive;
+use crate::format::container_runes::{EngineKind, ModelKind, ARCHIVE_MAGIC, FORMAT_VERSION};
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+
+/// Период полураспада адаптивных векторов
+const VECTOR_HALF_LIFE: u32 = 64;
+
+/// Размер таблицы контекстов векторов второго порядка (малая таблица — есть вытеснения)
+const VECTOR_CONTEXT_TABLE_BITS: u8 = 6;
+
+/// Эталонный архив и его вход 📐
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestVector {
+    /// Имя вектора: `<движок>[-<модель>-<словарь>]-<frame|blocks>`
+    pub name: String,
+    /// Движок кадров
+    pub engine: EngineKind,
+    /// Модель арифметического движка (у прочих движков — статическая)
+    pub model: ModelKind,
+    /// Поиск словаря слов включен (только арифметический движок)
+    pub word_dictionary: bool,
+    /// Архив из блоков (два кадра в реестре)
+    pub blocks: bool,
+    /// Исходные данные
+    pub input: Vec<u8>,
+    /// Архив
+    pub archive: Vec<u8>,
+    /// Файл внешнего словаря (`.acwd`) для общей модели
+    pub external_dictionary: Option<Vec<u8>>,
+}
+
+/// Документ спецификации: коды формата и векторы 📜
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpecDocument {
+    /// Текущая версия формата
+    pub format_version: u8,
+    /// Сигнатура архива
+    pub magic: [u8; 3],
+    /// Коды и имена встроенных движков
+    pub engines: Vec<(u8, &'static str)>,
+    /// Коды моделей
+    pub models: Vec<(u8, &'static str)>,
+    /// Эталонные векторы
+    pub vectors: Vec<TestVector>,
+}
+
+impl SpecDocument {
+    /// Документ текущей версии формата со всеми векторами
+    pub fn current() -> Self {
+        let registry = EngineRegistry::with_builtin_engines();
+        let engines = registry
+            .engine_kinds()
+            .into_iter()
+            .filter_map(|kind| Some((kind.to_byte(), registry.get(kind)?.name())))
+            .collect();
+        let models = vec![
+            (ModelKind::StaticOrder0.to_byte(), "static-order0"),
+            (ModelKind::SharedOrder0.to_byte(), "shared-order0"),
+            (
+                ModelKind::Adaptive { half_life: 1 }.to_byte(),
+                "adaptive-order0",
+            ),
+            (
+                ModelKind::HashedOrder2 { table_bits: 1 }.to_byte(),
+                "hashed-order2",
+            ),
+        ];
+
+        Self {
+            format_version: FORMAT_VERSION,
+            magic: ARCHIVE_MAGIC,
+            engines,
+            models,
+            vectors: canonical_vectors(),
+        }
+    }
+
+    /// JSON-документ для сторонних реализаций
+    ///
+    /// Байтовые поля (`input`, `archive`, `external_dictionary`) — строки
+    /// в шестнадцатеричном виде.
+    pub fn to_json(&self) -> String {
+        let mut document = String::from("{");
+        let _ = write!(
+            document,
+            "\"format_version\":{},\"magic\":\"{}\",",
+            self.format_version,
+            hex(&self.magic)
+        );
+
+        let code_table = |entries: &[(u8, &str)]| {
+            let entries: Vec<String> = entries
+                .iter()
+                .map(|(code, name)| format!("{{\"code\":{code},\"name\":\"{name}\"}}"))
+                .collect();
+            format!("[{}]", entries.join(","))
+        };
+        let _ = write!(
+            document,
+            "\"engines\":{},\"models\":{},\"vectors\":[",
+            code_table(&self.engines),
+            code_table(&self.models)
+        );
+
+        for (position, vector) in self.vectors.iter().enumerate() {
+            if position > 0 {
+                document.push(',');
+            }
+            let external_dictionary = match &vector.external_dictionary {
+                Some(dictionary) => format!("\"{}\"", hex(dictionary)),
+                None => "null".to_string(),
+            };
+            let _ = write!(
+                document,
+                "{{\"name\":\"{}\",\"engine\":{},\"model\":{},\"word_dictionary\":{},\"blocks\":{},\
+                 \"input\":\"{}\",\"archive\":\"{}\",\"external_dictionary\":{}}}",
+                vector.name,
+                vector.engine.to_byte(),
+                vector.model.to_byte(),
+                vector.word_dictionary,
+                vector.blocks,
+                hex(&vector.input),
+                hex(&vector.archive),
+                external_dictionary
+            );
+        }
+        document.push_str("]}");
+        document
+    }
+}
+
+/// Эталонные векторы всех сочетаний возможностей 📐
+///
+/// Арифметический движок: статическая, адаптивная и контекстная модели × словарь
+/// слов и без него × кадр и архив из блоков; общая модель с внешним
+/// словарем. Прочие встроенные движки: кадр и архив из блоков на
+/// подходящем им входе. Кадры воспроизводимы (без штампа происхождения).
+pub fn canonical_vectors() -> Vec<TestVector> {
+    let mut vectors = Vec::new();
+
+    for model in [
+        ModelKind::StaticOrder0,
+        ModelKind::Adaptive {
+            half_life: VECTOR_HALF_LIFE,
+        },
+        ModelKind::HashedOrder2 {
+            table_bits: VECTOR_CONTEXT_TABLE_BITS,
+        },
+    ] {
+        for word_dictionary in [true, false] {
+            let mut config = EngineConfig::default().with_model(model);
+            if !word_dictionary {
+                config = config.with_dictionary_budget_bytes(0);
+            }
+            for blocks in [false, true] {
+                vectors.push(build_vector(
+                    EngineKind::Arithmetic,
+                    &config,
+                    word_dictionary,
+                    blocks,
+                    sample_prose(),
+                ));
+            }
+        }
+    }
+
+    let input = sample_prose();
+    let handle = CompressorHandle::train(&input);
+    vectors.push(TestVector {
+        name: "arithmetic-shared-dict".to_string(),
+        engine: EngineKind::Arithmetic,
+        model: ModelKind::SharedOrder0,
+        word_dictionary: false,
+        blocks: false,
+        archive: handle.compress(&input).to_bytes(),
+        input,
+        external_dictionary: Some(handle.to_dictionary_bytes()),
+    });
+
+    let engine_inputs = [
+        (EngineKind::SplitStreams, sample_prose()),
+        (EngineKind::StructuredJson, sample_json()),
+        (EngineKind::LogTemplates, sample_logs()),
+        (EngineKind::Columnar, sample_csv()),
+        (EngineKind::Stored, sample_binary()),
+        (EngineKind::Wide16, sample_wide()),
+        (EngineKind::BitPlanes, sample_binary()),
+        (EngineKind::Dedup, sample_prose()),
+        (EngineKind::Mix, sample_prose()),
+        (EngineKind::Utf8, sample_utf8()),
+        (EngineKind::LongRepeats, sample_sectors()),
+    ];
+    for (engine, input) in engine_inputs {
+        let config = EngineConfig::default().with_engine(engine);
+        for blocks in [false, true] {
+            vectors.push(build_vector(engine, &config, false, blocks, input.clone()));
+        }
+    }
+    vectors
+}
+
+/// Вектор одного сочетания: кадр или два блока
+fn build_vector(
+    engine: EngineKind,
+    config: &EngineConfig,
+    word_dictionary: bool,
+    blocks: bool,
+    input: Vec<u8>,
+) -> TestVector {
+    let config = &config.clone().with_engine(engine);
+    let registry = EngineRegistry::with_builtin_engines();
+    // Кадр именно этого движка: короткий вход не заменяется кадром хранения
+    let compress = |part: &[u8]| {
+        registry
+            .compress(engine, part, config)
+            .expect("эталонный вход сжимается встроенным движком")
+            .to_bytes()
+    };
+    let archive = if blocks {
+        let (first, second) = input.split_at(input.len() / 2);
+        write_block_archive(&[
+            (first.len() as u64, compress(first)),
+            (second.len() as u64, compress(second)),
+        ])
+    } else {
+        compress(&input)
+    };
+
+    let engine_name = registry
+        .get(engine)
+        .map_or("custom", |engine| engine.name());
+    let framing_name = if blocks { "blocks" } else { "frame" };
+    let (name, model) = if engine == EngineKind::Arithmetic {
+        let model_name = match config.model {
+            ModelKind::Adaptive { .. } => "adaptive",
+            ModelKind::HashedOrder2 { .. } => "order2",
+            _ => "static",
+        };
+        let dictionary_name = if word_dictionary { "dict" } else { "nodict" };
+        (
+            format!("{engine_name}-{model_name}-{dictionary_name}-{framing_name}"),
+            config.model,
+        )
+    } else {
+        (
+            format!("{engine_name}-{framing_name}"),
+            ModelKind::StaticOrder0,
+        )
+    };
+
+    TestVector {
+        name,
+        engine,
+        model,
+        word_dictionary,
+        blocks,
+        input,
+        archive,
+        external_dictionary: None,
+    }
+}
+
+/// Почему вектор не пройден ❌
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FailureReason {
+    /// Для вектора нет результата
+    Missing,
+    /// Декодер сообщил об ошибке
+    DecoderError(String),
+    /// Результат отличается от входа вектора
+    Mismatch {
+        /// Первая отличающаяся позиция
+        first_difference: usize,
+        /// Длина входа вектора
+        expected_len: usize,
+        /// Длина результата декодера
+        actual_len: usize,
+    },
+}
+
+impl fmt::Display for FailureReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing => write!(f, "нет результата"),
+            Self::DecoderError(message) => write!(f, "ошибка декодера: {message}"),
+            Self::Mismatch {
+                first_difference,
+                expected_len,
+                actual_len,
+            } => write!(
+                f,
+                "расхождение с байта {first_difference} (ожидалось {expected_len} байт, получено {actual_len})"
+            ),
+        }
+    }
+}
+
+/// Непройденный вектор
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VectorFailure {
+    /// Имя вектора
+    pub name: String,
+    /// Причина
+    pub reason: FailureReason,
+}
+
+/// Итог проверки декодера 🧾
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConformanceReport {
+    /// Сколько векторов пройдено
+    pub passed: usize,
+    /// Непройденные векторы по порядку
+    pub failures: Vec<VectorFailure>,
+}
+
+impl ConformanceReport {
+    /// Все векторы пройдены
+    pub fn is_conformant(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Учитывает результат одного вектора
+    fn record(&mut self, vector: &TestVector, outcome: Result<&[u8], FailureReason>) {
+        let reason = match outcome {
+            Ok(restored) if restored == vector.input => {
+                self.passed += 1;
+                return;
+            }
+            Ok(restored) => FailureReason::Mismatch {
+                first_difference: restored
+                    .iter()
+                    .zip(&vector.input)
+                    .position(|(actual, expected)| actual != expected)
+                    .unwrap_or(restored.len().min(vector.input.len())),
+                expected_len: vector.input.len(),
+                actual_len: restored.len(),
+            },
+            Err(reason) => reason,
+        };
+        self.failures.push(VectorFailure {
+            name: vector.name.clone(),
+            reason,
+        });
+    }
+}
+
+/// Проверяет декодер, вызывая его на каждом векторе 🧪
+///
+/// Декодер получает вектор целиком: архив и, для общей модели, файл
+/// внешнего словаря.
+pub fn validate_decoder<E: fmt::Display>(
+    vectors: &[TestVector],
+    mut decode: impl FnMut(&TestVector) -> Result<Vec<u8>, E>,
+) -> ConformanceReport {
+    let mut report = ConformanceReport::default();
+    for vector in vectors {
+        match decode(vector) {
+            Ok(restored) => report.record(vector, Ok(&restored)),
+            Err(error) => {
+                report.record(vector, Err(FailureReason::DecoderError(error.to_string())))
+            }
+        }
+    }
+    report
+}
+
+/// Проверяет результаты внешнего декодера по именам векторов
+pub fn validate_outputs(
+    vectors: &[TestVector],
+    outputs: &HashMap<String, Vec<u8>>,
+) -> ConformanceReport {
+    let mut report = ConformanceReport::default();
+    for vector in vectors {
+        match outputs.get(&vector.name) {
+            Some(restored) => report.record(vector, Ok(restored)),
+            None => report.record(vector, Err(FailureReason::Missing)),
+        }
+    }
+    report
+}
+
+/// Байты в шестнадцатеричном виде
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut text, byte| {
+        let _ = write!(text, "{byte:02x}");
+        text
+    })
+}
+
+/// Текст со словами, которые стоит вынести в словарь
+///
+/// Каждая половина (блок) длиннее 1000 байтов: на меньших входах поиск
+/// словаря не запускается.
+fn sample_prose() -> Vec<u8> {
+    "the wizard and the owl read the ancient scroll, and the owl wrote a new scroll. "
+        .repeat(32)
+        .into_bytes()
+}
+
+fn sample_json() -> Vec<u8> {
+    (0..24)
+        .map(|id| {
+            let status = ["active", "blocked"][id % 2];
+            format!("{{\"id\":{id},\"status\":\"{status}\",\"owner\":\"tower\"}}\n")
+        })
+        .collect::<String>()
+        .into_bytes()
+}
+
+fn sample_logs() -> Vec<u8> {
+    (0..24)
+        .map(|request| {
+            format!(
+                "2024-05-01T12:00:{request:02}Z INFO request {request} served in {}ms\n",
+                request * 7
+            )
+        })
+        .collect::<String>()
+        .into_bytes()
+}
+
+fn sample_csv() -> Vec<u8> {
+    let rows: String = (0..24)
+        .map(|row| {
+            format!(
+                "{row},{},{}\n",
+                ["oslo", "rome", "lima"][row % 3],
+                row * 3 % 17
+            )
+        })
+        .collect();
+    format!("id,city,temp\n{rows}").into_bytes()
+}
+
+fn sample_wide() -> Vec<u8> {
+    "свиток совы и свиток мага "
+        .repeat(8)
+        .encode_utf16()
+        .flat_map(u16::to_le_bytes)
+        .collect()
+}
+
+fn sample_utf8() -> Vec<u8> {
+    let mut text = "свиток совы 巻物 и свиток мага ".repeat(8).into_bytes();
+    // Недопустимый байт UTF-8 хранится как есть
+    text.push(0xFF);
+    text
+}
+
+/// Сектор в 4 КиБ, повторенный после вставки и вплотную
+///
+/// Повторы видны только кадру целиком: половины короче двух секторов.
+fn sample_sectors() -> Vec<u8> {
+    let sector: Vec<u8> = sample_binary().repeat(8);
+    [&sector[..], b"owl", &sector, &sector].concat()
+}
+
+/// Псевдослучайные байты xorshift32 (одинаковые на любой платформе)
+fn sample_binary() -> Vec<u8> {
+    let mut state = 0x2545_F491u32;
+    (0..512)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            (state >> 24) as u8
+        })
+        .collect()
+}
+
+/// Тесты спецификации 🎯
+#[cfg(test)]
+mod spec_tests {
+    use super::*;
+    use crate::compression_engine::grimoire_library::DictionaryRegistry;
+    use crate::decompression_oracle::decode_wards::DecodeOptions;
+    use crate::format::artifact_scroll::parse_artifact;
+    use crate::format::block_ledger::parse_block_ledger;
+    use crate::format::container_runes::ArchiveHeader;
+
+    /// Эталонный декодер: реестр словарей поверх обычной распаковки
+    fn reference_decode(vector: &TestVector) -> crate::error::WizardResult<Vec<u8>> {
+        let registry = DictionaryRegistry::new();
+        if let Some(dictionary) = &vector.external_dictionary {
+            registry.register_bytes(dictionary)?;
+        }
+        registry.try_decompress(&vector.archive, &DecodeOptions::default())
+    }
+
+    #[test]
+    fn test_vectors_cover_every_combination() {
+        let vectors = canonical_vectors();
+        let registry = EngineRegistry::with_builtin_engines();
+        for engine in registry.engine_kinds() {
+            for blocks in [false, true] {
+                assert!(vectors
+                    .iter()
+                    .any(|vector| vector.engine == engine && vector.blocks == blocks));
+            }
+        }
+        for (model, word_dictionary, blocks) in [
+            (ModelKind::StaticOrder0, true, true),
+            (ModelKind::StaticOrder0, false, false),
+            (
+                ModelKind::Adaptive {
+                    half_life: VECTOR_HALF_LIFE,
+                },
+                true,
+                false,
+            ),
+            (
+                ModelKind::Adaptive {
+                    half_life: VECTOR_HALF_LIFE,
+                },
+                false,
+                true,
+            ),
+            (
+                ModelKind::HashedOrder2 {
+                    table_bits: VECTOR_CONTEXT_TABLE_BITS,
+                },
+                true,
+                true,
+            ),
+        ] {
+            assert!(vectors.iter().any(|vector| vector.model == model
+                && vector.word_dictionary == word_dictionary
+                && vector.blocks == blocks));
+        }
+        assert!(vectors
+            .iter()
+            .any(|vector| vector.external_dictionary.is_some()));
+
+        let mut names: Vec<&str> = vectors.iter().map(|vector| vector.name.as_str()).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), vectors.len());
+
+        for vector in &vectors {
+            let (header, _) = ArchiveHeader::parse(&vector.archive).unwrap();
+            assert_eq!(header.block_framed, vector.blocks, "{}", vector.name);
+            if !vector.blocks {
+                assert_eq!(header.engine, vector.engine, "{}", vector.name);
+            }
+            if vector.model == ModelKind::StaticOrder0 && vector.engine == EngineKind::Arithmetic {
+                let frame = match vector.blocks {
+                    false => &vector.archive[..],
+                    true => {
+                        parse_block_ledger(&vector.archive, &DecodeOptions::default()).unwrap()[0]
+                            .frame
+                    }
+                };
+                let words = parse_artifact(frame).unwrap().0.mystical_word_grimoire;
+                assert_eq!(!words.is_empty(), vector.word_dictionary, "{}", vector.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_reference_decoder_is_conformant() {
+        let vectors = canonical_vectors();
+        let report = validate_decoder(&vectors, reference_decode);
+        assert!(report.is_conformant(), "{:?}", report.failures);
+        assert_eq!(report.passed, vectors.len());
+
+        let document = SpecDocument::current();
+        let json = document.to_json();
+        assert!(json.starts_with(&format!("{{\"format_version\":{FORMAT_VERSION},")));
+        assert!(json.contains("\"name\":\"arithmetic-shared-dict\""));
+        assert_eq!(json.matches("\"archive\":").count(), document.vectors.len());
+    }
+
+    #[test]
+    fn test_foreign_failures_reported() {
+        let vectors = canonical_vectors();
+        let mut outputs: HashMap<String, Vec<u8>> = vectors
+            .iter()
+            .map(|vector| (vector.name.clone(), vector.input.clone()))
+            .collect();
+        assert!(validate_outputs(&vectors, &outputs).is_conformant());
+
+        outputs.get_mut("stored-frame").unwrap()[10] ^= 1;
+        outputs.remove("dedup-blocks");
+        let report = validate_outputs(&vectors, &outputs);
+        assert_eq!(report.passed, vectors.len() - 2);
+        assert_eq!(
+            report.failures,
+            vec![
+                VectorFailure {
+                    name: "stored-frame".to_string(),
+                    reason: FailureReason::Mismatch {
+                        first_difference: 10,
+                        expected_len: 512,
+                        actual_len: 512,
+                    },
+                },
+                VectorFailure {
+                    name: "dedup-blocks".to_string(),
+                    reason: FailureReason::Missing,
+                },
+            ]
+        );
+
+        let report = validate_decoder(&vectors[..1], |_| Err("нет движка"));
+        assert_eq!(
+            report.failures[0].reason,
+            FailureReason::DecoderError("нет движка".to_string())
+        );
+    }
+}
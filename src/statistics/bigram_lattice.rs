@@ -0,0 +1,218 @@
+//! Решетка пар байтов: сколько даст модель первого порядка 🔗
+//!
+//! Модель нулевого порядка знает только частоты байтов, контекстная —
+//! еще и предыдущий байт. Выигрыш контекста заранее виден по парам
+//! соседних байтов: [`BigramMatrix::conditional_entropy`] — сколько бит
+//! на байт остается, если предыдущий байт известен, а
+//! [`BigramMatrix::order1_gain`] — разница с нулевым порядком. Если даже
+//! предыдущий байт почти ничего не подсказывает, контекстной модели
+//! (`ModelKind::HashedOrder2`) трудно окупить свою память и время.
+//!
+//! Матрица хранится по строкам и только ненулевыми ячейками: текст
+//! занимает малую долю из 65536 пар.
+
+/// Счетчики пар соседних байтов в разреженном виде 🔗
+///
+/// ```
+/// use arithmetic_compression_wizard::statistics::bigram_matrix;
+///
+/// let matrix = bigram_matrix(b"abababab");
+/// assert_eq!(matrix.count(b'a', b'b'), 4);
+/// assert_eq!(matrix.count(b'b', b'a'), 3);
+/// assert_eq!(matrix.conditional_entropy(), 0.0);
+/// assert!(matrix.order1_gain() > 0.98);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigramMatrix {
+    /// Строка на каждый предыдущий байт: (следующий байт, число пар)
+    /// по возрастанию следующего байта
+    rows: Vec<Vec<(u8, u32)>>,
+    /// Всего пар
+    pair_count: u64,
+}
+
+impl BigramMatrix {
+    /// Сколько раз за байтом `prev` шел байт `next`
+    pub fn count(&self, prev: u8, next: u8) -> u32 {
+        let row = &self.rows[prev as usize];
+        row.binary_search_by_key(&next, |&(byte, _)| byte)
+            .map_or(0, |position| row[position].1)
+    }
+
+    /// Ненулевые ячейки строки `prev` по возрастанию следующего байта
+    pub fn row(&self, prev: u8) -> &[(u8, u32)] {
+        &self.rows[prev as usize]
+    }
+
+    /// Всего пар соседних байтов: длина входа без единицы
+    pub fn pair_count(&self) -> u64 {
+        self.pair_count
+    }
+
+    /// Число встретившихся различных пар
+    pub fn distinct_pairs(&self) -> usize {
+        self.rows.iter().map(Vec::len).sum()
+    }
+
+    /// Плотная матрица `[предыдущий][следующий]` (256 КиБ в куче)
+    pub fn to_dense(&self) -> Box<[[u32; 256]; 256]> {
+        let mut dense = Box::new([[0u32; 256]; 256]);
+        for (prev, row) in self.rows.iter().enumerate() {
+            for &(next, count) in row {
+                dense[prev][next as usize] = count;
+            }
+        }
+        dense
+    }
+
+    /// Энтропия нулевого порядка вторых байтов пар, бит на байт
+    ///
+    /// Считается по тем же байтам, что и
+    /// [`conditional_entropy`](Self::conditional_entropy), чтобы их
+    /// разность не была отрицательной.
+    pub fn order0_entropy(&self) -> f64 {
+        let mut next_counts = [0u64; 256];
+        for &(next, count) in self.rows.iter().flatten() {
+            next_counts[next as usize] += count as u64;
+        }
+        entropy_of(next_counts.iter().copied(), self.pair_count)
+    }
+
+    /// Условная энтропия байта при известном предыдущем, бит на байт
+    ///
+    /// Оценка тела модели первого порядка без стоимости ее таблиц; ноль
+    /// без пар.
+    pub fn conditional_entropy(&self) -> f64 {
+        if self.pair_count == 0 {
+            return 0.0;
+        }
+        self.rows
+            .iter()
+            .map(|row| {
+                let row_total: u64 = row.iter().map(|&(_, count)| count as u64).sum();
+                let row_entropy = entropy_of(row.iter().map(|&(_, count)| count as u64), row_total);
+                row_total as f64 / self.pair_count as f64 * row_entropy
+            })
+            .sum()
+    }
+
+    /// Выигрыш модели первого порядка, бит на байт
+    ///
+    /// Разность [`order0_entropy`](Self::order0_entropy) и
+    /// [`conditional_entropy`](Self::conditional_entropy). На коротких
+    /// входах завышен: таблица на каждый контекст стоит дороже, чем
+    /// учтено в оценке.
+    pub fn order1_gain(&self) -> f64 {
+        (self.order0_entropy() - self.conditional_entropy()).max(0.0)
+    }
+}
+
+/// Пары соседних байтов `data` 🔗
+///
+/// Счетчик пары не переполняется: после `u32::MAX` он остается на месте.
+pub fn bigram_matrix(data: impl AsRef<[u8]>) -> BigramMatrix {
+    let data = data.as_ref();
+    let mut dense = vec![0u32; 256 * 256];
+    for pair in data.windows(2) {
+        let cell = &mut dense[(pair[0] as usize) << 8 | pair[1] as usize];
+        *cell = cell.saturating_add(1);
+    }
+
+    let rows = dense
+        .chunks_exact(256)
+        .map(|row| {
+            row.iter()
+                .enumerate()
+                .filter(|&(_, &count)| count > 0)
+                .map(|(next, &count)| (next as u8, count))
+                .collect()
+        })
+        .collect();
+    BigramMatrix {
+        rows,
+        pair_count: data.len().saturating_sub(1) as u64,
+    }
+}
+
+/// Энтропия Шеннона счетчиков с суммой `total`, бит на символ
+fn entropy_of(counts: impl Iterator<Item = u64>, total: u64) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    counts
+        .filter(|&count| count > 0)
+        .map(|count| {
+            // p·log2(1/p): без знака минус ноль не становится -0
+            let p = count as f64 / total as f64;
+            p * (total as f64 / count as f64).log2()
+        })
+        .sum()
+}
+
+/// Тесты решетки пар 🎯
+#[cfg(test)]
+mod bigram_lattice_tests {
+    use super::*;
+    use crate::compression_engine::frequency_census::shannon_entropy;
+
+    #[test]
+    fn test_counts_and_dense_agree() {
+        let text = b"the cat sat on the mat with the hat";
+        let matrix = bigram_matrix(text);
+        assert_eq!(matrix.pair_count(), text.len() as u64 - 1);
+        assert_eq!(matrix.count(b't', b'h'), 4);
+        assert_eq!(matrix.count(b'z', b'q'), 0);
+        assert!(matrix
+            .row(b'a')
+            .windows(2)
+            .all(|pair| pair[0].0 < pair[1].0));
+
+        let dense = matrix.to_dense();
+        let dense_total: u64 = dense.iter().flatten().map(|&count| count as u64).sum();
+        assert_eq!(dense_total, matrix.pair_count());
+        assert_eq!(
+            dense[b'a' as usize][b't' as usize],
+            matrix.count(b'a', b't')
+        );
+        assert_eq!(
+            matrix.distinct_pairs(),
+            dense.iter().flatten().filter(|&&count| count > 0).count()
+        );
+
+        for tiny in [&b""[..], b"x"] {
+            let matrix = bigram_matrix(tiny);
+            assert_eq!(matrix.pair_count(), 0);
+            assert_eq!(matrix.conditional_entropy(), 0.0);
+            assert_eq!(matrix.order1_gain(), 0.0);
+        }
+    }
+
+    #[test]
+    fn test_context_gain_separates_text_from_noise() {
+        // Каждый байт определяется предыдущим: весь выигрыш у контекста
+        let cycle: Vec<u8> = (0..4000u32)
+            .map(|step| b"wizard"[step as usize % 6])
+            .collect();
+        let matrix = bigram_matrix(&cycle);
+        assert_eq!(matrix.conditional_entropy(), 0.0);
+        assert!((matrix.order0_entropy() - shannon_entropy(&cycle)).abs() < 0.01);
+
+        // Независимые байты: контекст почти ничего не дает
+        let mut state = 0x1234_5678u32;
+        let noise: Vec<u8> = (0..200_000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                (state >> 24) as u8 & 0x0F
+            })
+            .collect();
+        let matrix = bigram_matrix(&noise);
+        assert!((matrix.order0_entropy() - 4.0).abs() < 0.01);
+        assert!(matrix.order1_gain() < 0.01);
+
+        let prose = "the quick brown fox jumps over the lazy dog and the owl ".repeat(30);
+        let prose_gain = bigram_matrix(&prose).order1_gain();
+        assert!(prose_gain > 1.0, "{prose_gain}");
+    }
+}
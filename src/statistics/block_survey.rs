@@ -0,0 +1,151 @@
+//! Обзор блоков архива 🗺️
+//!
+//! В архиве из блоков каждый блок сжимается сам по себе, и плохо
+//! сжимаемые участки (встроенные картинки, уже сжатые данные) видны по
+//! блокам: у них энтропия близка к восьми битам на байт, а кадр почти не
+//! короче исходника. [`per_block_report`] перечисляет размеры, энтропию и
+//! движок каждого блока — по нему удобно выбрать порог хранения без
+//! сжатия и размер блока.
+
+use crate::compression_engine::frequency_census::shannon_entropy;
+use crate::compression_engine::micro_spell::is_micro_archive;
+use crate::decompression_oracle::block_conductor::decode_block;
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::error::WizardResult;
+use crate::format::block_ledger::read_block_ledger;
+use crate::format::container_runes::{ArchiveHeader, EngineKind};
+use crate::format::parity_runes::restore_parity;
+use crate::simple_api::{try_decompress_data, try_decompress_frame};
+
+/// Сводка одного блока архива 🧱
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockReport {
+    /// Номер блока в архиве
+    pub index: usize,
+    /// Длина исходных данных блока
+    pub original_len: u64,
+    /// Длина кадра блока в архиве
+    pub frame_len: usize,
+    /// Движок кадра (`None` — микроархив без заголовка)
+    pub engine: Option<EngineKind>,
+    /// Энтропия Шеннона исходных байтов блока, бит на байт
+    pub entropy_bits_per_byte: f64,
+}
+
+impl BlockReport {
+    /// Доля кадра от исходника: больше единицы — блок вырос
+    ///
+    /// Для пустого блока — ноль.
+    pub fn compression_ratio(&self) -> f64 {
+        if self.original_len == 0 {
+            return 0.0;
+        }
+        self.frame_len as f64 / self.original_len as f64
+    }
+}
+
+/// Размеры, энтропия и движок каждого блока архива 📊
+///
+/// Блоки распаковываются по одному (энтропия считается по исходным
+/// байтам), с лимитами [`DecodeOptions::default`]. Архив без блоков дает
+/// один блок на весь архив.
+///
+/// ```
+/// use arithmetic_compression_wizard::simple_api::compress_data_in_blocks;
+/// use arithmetic_compression_wizard::statistics::per_block_report;
+///
+/// let mut data = b"the owl post arrives at dawn. ".repeat(40);
+/// data.extend((0..1200u32).map(|step| (step.wrapping_mul(2_654_435_761) >> 13) as u8));
+/// let archive = compress_data_in_blocks(&data, 1200);
+///
+/// let report = per_block_report(&archive).unwrap();
+/// assert_eq!(report.len(), 2);
+/// assert!(report[0].entropy_bits_per_byte < report[1].entropy_bits_per_byte);
+/// assert!(report[0].compression_ratio() < report[1].compression_ratio());
+/// ```
+pub fn per_block_report(archive_bytes: impl AsRef<[u8]>) -> WizardResult<Vec<BlockReport>> {
+    let options = DecodeOptions::default();
+    let archive_bytes = restore_parity(archive_bytes.as_ref());
+    let archive_bytes = &archive_bytes[..];
+
+    let whole_archive = |engine| {
+        let restored = try_decompress_data(archive_bytes, &options)?;
+        Ok(vec![BlockReport {
+            index: 0,
+            original_len: restored.len() as u64,
+            frame_len: archive_bytes.len(),
+            engine,
+            entropy_bits_per_byte: shannon_entropy(&restored),
+        }])
+    };
+    if is_micro_archive(archive_bytes) {
+        return whole_archive(None);
+    }
+    let (header, body_offset) = ArchiveHeader::try_parse(archive_bytes)?;
+    if !header.block_framed {
+        return whole_archive(Some(header.engine));
+    }
+
+    let blocks = read_block_ledger(&header, &archive_bytes[body_offset..], &options)
+        .map_err(|error| error.shifted(body_offset))?;
+    blocks
+        .iter()
+        .enumerate()
+        .map(|(index, block)| {
+            let restored = decode_block(block, &options, &try_decompress_frame)?;
+            let frame = restore_parity(block.frame);
+            let (frame_header, _) = ArchiveHeader::try_parse(&frame)?;
+            Ok(BlockReport {
+                index,
+                original_len: block.original_len,
+                frame_len: block.frame.len(),
+                engine: Some(frame_header.engine),
+                entropy_bits_per_byte: shannon_entropy(&restored),
+            })
+        })
+        .collect()
+}
+
+/// Тесты обзора блоков 🎯
+#[cfg(test)]
+mod block_survey_tests {
+    use super::*;
+    use crate::simple_api::{compress_compact, compress_data, compress_data_in_synced_blocks};
+
+    #[test]
+    fn test_single_frame_and_micro_archives() {
+        let data = b"scroll of many spells and many scrolls ".repeat(30);
+        let report = per_block_report(compress_data(&data)).unwrap();
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0].original_len, data.len() as u64);
+        assert_eq!(report[0].engine, Some(EngineKind::Arithmetic));
+        assert!(report[0].compression_ratio() < 1.0);
+
+        let micro = compress_compact(b"owl");
+        let report = per_block_report(&micro).unwrap();
+        assert_eq!(report[0].engine, None);
+        assert_eq!(report[0].frame_len, micro.len());
+    }
+
+    #[test]
+    fn test_synced_blocks_and_damage() {
+        let data = b"abracadabra ".repeat(200);
+        let archive = compress_data_in_synced_blocks(&data, 500);
+        let report = per_block_report(&archive).unwrap();
+        assert_eq!(report.len(), data.len().div_ceil(500));
+        assert_eq!(
+            report.iter().map(|block| block.original_len).sum::<u64>(),
+            data.len() as u64
+        );
+        assert!(report
+            .iter()
+            .enumerate()
+            .all(|(index, block)| block.index == index));
+
+        let mut damaged = archive.clone();
+        let middle = damaged.len() / 2;
+        damaged[middle] ^= 0xFF;
+        assert!(per_block_report(&damaged).is_err());
+        assert!(per_block_report(b"not an archive").is_err());
+    }
+}
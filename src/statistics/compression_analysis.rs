@@ -0,0 +1,111 @@
+//! Анализ эффективности сжатия 🔬
+//!
+//! Энтропия, коэффициенты сжатия, частоты символов и вклад словаря.
+
+use crate::compression_engine::frequency_census::shannon_entropy;
+use crate::format::artifact_scroll::serialize_artifact;
+use crate::prelude::*;
+
+/// Результаты анализа сжатия
+#[derive(Debug, Clone)]
+pub struct CompressionAnalysis {
+    /// Размер исходных данных в байтах
+    pub original_size: usize,
+    /// Размер сжатых данных в байтах
+    pub compressed_size: usize,
+    /// Коэффициент сжатия в процентах
+    pub compression_ratio: f64,
+    /// Энтропия Шеннона исходных данных
+    pub shannon_entropy: f64,
+    /// Достигнутая плотность сжатия
+    pub compression_density: f64,
+    /// Количество слов в словаре
+    pub word_dictionary_size: usize,
+    /// Количество фраз (цепочек слов)
+    pub phrase_dictionary_size: usize,
+    /// Наиболее частые символы
+    pub top_symbols: Vec<(u32, u64)>,
+    /// Частоты всех символов (по возрастанию id символа)
+    pub symbol_counts: Vec<(u32, u64)>,
+    /// Статистика каждого слова словаря
+    pub dictionary_entries: Vec<DictionaryEntryStats>,
+    /// Накладные расходы сериализованного архива (все, кроме битового потока)
+    pub header_overhead: usize,
+}
+
+/// Статистика одного слова словаря 📖
+#[derive(Debug, Clone, PartialEq)]
+pub struct DictionaryEntryStats {
+    /// Слово
+    pub word: String,
+    /// Сколько раз слово заменено ссылкой (с пробелом и без)
+    pub occurrences: u64,
+    /// Сколько из них поглотили пробел после слова
+    pub with_trailing_space: u64,
+    /// Оценка экономии в байтах: длина × вхождения + пробелы − (длина + 4)
+    pub estimated_savings: i64,
+    /// Экономия, измеренная после кодирования (см. `word_effectiveness`)
+    pub measured_savings: f64,
+}
+
+/// Анализирует эффективность сжатия
+pub fn analyze_compression(data: impl AsRef<[u8]>) -> CompressionAnalysis {
+    let data = data.as_ref();
+    let artifact = weave_compression_spell(data);
+
+    // Энтропия Шеннона
+    let entropy = shannon_entropy(data);
+
+    let compressed_size = artifact.compressed_bit_stream.len();
+    let compression_ratio = (1.0 - compressed_size as f64 / data.len() as f64) * 100.0;
+    let compression_density = compressed_size as f64 * 8.0 / data.len() as f64;
+
+    // Все символы
+    let symbol_counts: Vec<(u32, u64)> = artifact
+        .mystical_frequency_codex
+        .iter()
+        .map(|&(symbol, freq, _)| (symbol, freq))
+        .collect();
+
+    // Топ символов
+    let mut symbol_freq = symbol_counts.clone();
+    symbol_freq.sort_by_key(|&(_, freq)| std::cmp::Reverse(freq));
+    symbol_freq.truncate(10);
+
+    // Вклад словаря (с учетом слов внутри фраз)
+    let dictionary_entries = artifact
+        .word_effectiveness()
+        .into_iter()
+        .zip(&artifact.mystical_word_grimoire)
+        .map(|(effectiveness, word)| {
+            let word_len = word.len() as i64;
+            let occurrences = effectiveness.occurrences;
+            let with_trailing_space = effectiveness.with_trailing_space;
+
+            DictionaryEntryStats {
+                word: word.clone(),
+                occurrences,
+                with_trailing_space,
+                estimated_savings: word_len * occurrences as i64 + with_trailing_space as i64
+                    - (word_len + 4),
+                measured_savings: effectiveness.bytes_saved,
+            }
+        })
+        .collect();
+
+    let header_overhead = serialize_artifact(&artifact).len() - compressed_size;
+
+    CompressionAnalysis {
+        original_size: data.len(),
+        compressed_size,
+        compression_ratio,
+        shannon_entropy: entropy,
+        compression_density,
+        word_dictionary_size: artifact.mystical_word_grimoire.len(),
+        phrase_dictionary_size: artifact.mystical_phrase_codex.len(),
+        top_symbols: symbol_freq,
+        symbol_counts,
+        dictionary_entries,
+        header_overhead,
+    }
+}
@@ -0,0 +1,36 @@
+//! Статистика сжатия 📊
+//! Анализ эффективности и метрики
+
+pub mod bigram_lattice;
+pub mod block_survey;
+pub mod compression_analysis;
+pub mod model_portrait;
+pub mod report_scribe;
+pub mod stream_gauge;
+
+// Экспорт основных типов и функций
+
+pub use bigram_lattice::{
+    bigram_matrix, // Пары соседних байтов и выигрыш контекста
+    BigramMatrix,  // Разреженная матрица пар
+};
+pub use block_survey::{
+    per_block_report, // Размеры и энтропия каждого блока архива
+    BlockReport,      // Сводка блока
+};
+pub use compression_analysis::{
+    analyze_compression,  // Анализ эффективности сжатия
+    CompressionAnalysis,  // Результаты анализа
+    DictionaryEntryStats, // Статистика слова словаря
+};
+pub use model_portrait::{
+    ModelReport, // Отчет о модели артефакта
+    SymbolEntry, // Символ таблицы частот
+    WordEntry,   // Слово словаря и его вклад
+    TABLE_ROWS,  // Строк в текстовой таблице
+};
+pub use report_scribe::{
+    export_report, // Машиночитаемый отчет
+    ReportFormat,  // Формат отчета
+};
+pub use stream_gauge::StreamingAnalyzer; // Потоковый анализ сжимаемости
@@ -0,0 +1,243 @@
+//! Портрет модели 🖼️
+//!
+//! [`ModelReport`] — таблица частот и словарь артефакта в виде данных:
+//! самые частые символы, вклад каждого слова и готовая текстовая
+//! таблица. Отчет строится по запросу, распаковка ничего не печатает.
+
+use crate::compression_engine::compression_conjurer::{
+    interpret_symbol, CompressionArtifact, SymbolMeaning,
+};
+use crate::error::WizardResult;
+use crate::format::artifact_scroll::parse_artifact;
+use std::cmp::Reverse;
+use std::fmt::Write;
+
+/// Сколько символов показывает [`ModelReport::to_table_string`]
+pub const TABLE_ROWS: usize = 20;
+
+/// Символ таблицы частот 🔣
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolEntry {
+    /// Id символа (байт, ссылка на слово или фразу)
+    pub symbol: u32,
+    /// Читаемый вид: `'a'`, `0x0a`, `"word"`, `"word "` или `phrase #3`
+    pub label: String,
+    /// Частота символа
+    pub frequency: u64,
+    /// Начало интервала в накопительной таблице
+    pub cumulative_start: u64,
+}
+
+impl SymbolEntry {
+    /// Конец интервала в накопительной таблице
+    pub fn cumulative_end(&self) -> u64 {
+        self.cumulative_start + self.frequency
+    }
+}
+
+/// Слово словаря и его вклад 📖
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordEntry {
+    /// Слово
+    pub word: String,
+    /// Сколько раз слово заменено ссылкой (включая фразы)
+    pub occurrences: u64,
+    /// Сколько из них поглотили пробел после слова
+    pub with_trailing_space: u64,
+    /// Итоговая экономия в байтах (см. `word_effectiveness`)
+    pub bytes_saved: f64,
+}
+
+/// Отчет о модели артефакта 🖼️
+///
+/// ```
+/// use arithmetic_compression_wizard::prelude::*;
+/// use arithmetic_compression_wizard::statistics::ModelReport;
+///
+/// let artifact = weave_compression_spell(b"abracadabra");
+/// let report = ModelReport::from_artifact(&artifact);
+/// assert_eq!(report.top_symbols(1)[0].label, "'a'");
+/// println!("{}", report.to_table_string());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelReport {
+    /// Символы в порядке таблицы частот (по возрастанию id)
+    symbols: Vec<SymbolEntry>,
+    /// Слова в порядке словаря
+    words: Vec<WordEntry>,
+    /// Суммарная частота — число закодированных символов
+    total_frequency: u64,
+}
+
+impl ModelReport {
+    /// Отчет о модели артефакта
+    pub fn from_artifact(artifact: &CompressionArtifact) -> Self {
+        let grimoire = &artifact.mystical_word_grimoire;
+        let symbols = artifact
+            .mystical_frequency_codex
+            .iter()
+            .map(|&(symbol, frequency, cumulative_start)| SymbolEntry {
+                symbol,
+                label: symbol_label(symbol, grimoire),
+                frequency,
+                cumulative_start,
+            })
+            .collect();
+        let words = artifact
+            .word_effectiveness()
+            .into_iter()
+            .zip(grimoire)
+            .map(|(effectiveness, word)| WordEntry {
+                word: word.clone(),
+                occurrences: effectiveness.occurrences,
+                with_trailing_space: effectiveness.with_trailing_space,
+                bytes_saved: effectiveness.bytes_saved,
+            })
+            .collect();
+
+        Self {
+            symbols,
+            words,
+            total_frequency: artifact.total_frequency_essence,
+        }
+    }
+
+    /// Отчет о модели архива статического движка
+    pub fn from_archive(archive_bytes: &[u8]) -> WizardResult<Self> {
+        let (artifact, _) = parse_artifact(archive_bytes)?;
+        Ok(Self::from_artifact(&artifact))
+    }
+
+    /// `n` самых частых символов; при равной частоте — по возрастанию id
+    pub fn top_symbols(&self, n: usize) -> Vec<&SymbolEntry> {
+        let mut ranked: Vec<&SymbolEntry> = self.symbols.iter().collect();
+        ranked.sort_by_key(|entry| Reverse(entry.frequency));
+        ranked.truncate(n);
+        ranked
+    }
+
+    /// Все символы таблицы частот по возрастанию id
+    pub fn symbol_entries(&self) -> &[SymbolEntry] {
+        &self.symbols
+    }
+
+    /// Слова словаря в порядке словаря
+    pub fn word_entries(&self) -> &[WordEntry] {
+        &self.words
+    }
+
+    /// Число закодированных символов
+    pub fn total_frequency(&self) -> u64 {
+        self.total_frequency
+    }
+
+    /// Текстовая таблица [`TABLE_ROWS`] самых частых символов 📊
+    pub fn to_table_string(&self) -> String {
+        let mut table = String::new();
+        let _ = writeln!(table, "📊 Coded symbols: {}", self.total_frequency);
+        let _ = writeln!(table, "🔮 Mystical Frequency Codex:");
+        let _ = writeln!(
+            table,
+            "{:<8} {:<12} {:<12} {:<12} Label",
+            "Symbol", "Frequency", "Start", "End"
+        );
+        let _ = writeln!(table, "{}", "━".repeat(58));
+
+        for entry in self.top_symbols(TABLE_ROWS) {
+            let _ = writeln!(
+                table,
+                "{:<8} {:<12} {:<12} {:<12} {}",
+                entry.symbol,
+                entry.frequency,
+                entry.cumulative_start,
+                entry.cumulative_end(),
+                entry.label
+            );
+        }
+        if self.symbols.len() > TABLE_ROWS {
+            let _ = writeln!(
+                table,
+                "... and {} more symbols with lower frequencies",
+                self.symbols.len() - TABLE_ROWS
+            );
+        }
+        table
+    }
+}
+
+/// Читаемый вид символа
+fn symbol_label(symbol: u32, grimoire: &[String]) -> String {
+    match interpret_symbol(symbol, grimoire.len()) {
+        SymbolMeaning::Byte(byte) if byte.is_ascii_graphic() || byte == b' ' => {
+            format!("'{}'", byte as char)
+        }
+        SymbolMeaning::Byte(byte) => format!("0x{byte:02x}"),
+        SymbolMeaning::Word {
+            index,
+            trailing_space,
+        } => {
+            let space = if trailing_space { " " } else { "" };
+            format!("\"{}{space}\"", grimoire[index])
+        }
+        SymbolMeaning::Phrase { index } => format!("phrase #{index}"),
+    }
+}
+
+/// Тесты портрета модели 🎯
+#[cfg(test)]
+mod model_portrait_tests {
+    use super::*;
+    use crate::compression_engine::compression_conjurer::weave_compression_spell;
+    use crate::simple_api::compress_data;
+
+    const SCROLL: &[u8] = b"the owl and the cat and the owl and the cat watched the owl";
+
+    #[test]
+    fn test_top_symbols_ranked_by_frequency() {
+        let artifact = weave_compression_spell(b"abracadabra\n");
+        let report = ModelReport::from_artifact(&artifact);
+
+        let top: Vec<(&str, u64)> = report
+            .top_symbols(3)
+            .iter()
+            .map(|entry| (entry.label.as_str(), entry.frequency))
+            .collect();
+        assert_eq!(top, vec![("'a'", 5), ("'b'", 2), ("'r'", 2)]);
+        assert_eq!(report.symbol_entries()[0].label, "0x0a");
+        assert_eq!(report.total_frequency(), 12);
+        assert_eq!(report.top_symbols(100).len(), report.symbol_entries().len());
+    }
+
+    #[test]
+    fn test_word_entries_follow_grimoire() {
+        let artifact = weave_compression_spell(SCROLL);
+        let report = ModelReport::from_artifact(&artifact);
+
+        let words: Vec<&str> = report
+            .word_entries()
+            .iter()
+            .map(|entry| entry.word.as_str())
+            .collect();
+        assert_eq!(words, artifact.mystical_word_grimoire);
+        assert!(report
+            .word_entries()
+            .iter()
+            .all(|entry| entry.occurrences > 0));
+        assert_eq!(
+            ModelReport::from_archive(&compress_data(SCROLL)).unwrap(),
+            report
+        );
+        assert!(ModelReport::from_archive(b"not an archive").is_err());
+    }
+
+    #[test]
+    fn test_table_lists_top_rows() {
+        let text: Vec<u8> = (0u8..40).map(|offset| b'0' + offset).collect();
+        let report = ModelReport::from_artifact(&weave_compression_spell(&text));
+        let table = report.to_table_string();
+
+        assert!(table.starts_with("📊 Coded symbols: 40\n"));
+        assert_eq!(table.lines().count(), 4 + TABLE_ROWS + 1);
+        assert!(table.ends_with("... and 20 more symbols with lower frequencies\n"));
+    }
+}
@@ -0,0 +1,200 @@
+//! Писарь отчетов 🪶
+//!
+//! Машиночитаемый экспорт [`CompressionAnalysis`] для дашбордов и
+//! демонстрационных инструментов. Без внешних зависимостей: JSON и CSV
+//! формируются вручную.
+
+use super::compression_analysis::CompressionAnalysis;
+use std::fmt::Write;
+
+/// Формат отчета
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// Один JSON-объект
+    Json,
+    /// Таблица `section,key,count,savings`
+    Csv,
+}
+
+/// Экспортирует анализ сжатия в машиночитаемом виде
+///
+/// Отчет содержит сводку, частоты всех символов, слова словаря
+/// с оценкой экономии и накладные расходы заголовка.
+pub fn export_report(analysis: &CompressionAnalysis, format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Json => export_json(analysis),
+        ReportFormat::Csv => export_csv(analysis),
+    }
+}
+
+/// JSON-отчет
+fn export_json(analysis: &CompressionAnalysis) -> String {
+    let mut report = String::from("{");
+
+    let _ = write!(
+        report,
+        "\"original_size\":{},\"compressed_size\":{},\"header_overhead\":{},",
+        analysis.original_size, analysis.compressed_size, analysis.header_overhead
+    );
+    let _ = write!(
+        report,
+        "\"compression_ratio\":{},\"shannon_entropy\":{},\"compression_density\":{},",
+        json_number(analysis.compression_ratio),
+        json_number(analysis.shannon_entropy),
+        json_number(analysis.compression_density)
+    );
+
+    report.push_str("\"symbols\":[");
+    for (position, &(symbol, count)) in analysis.symbol_counts.iter().enumerate() {
+        if position > 0 {
+            report.push(',');
+        }
+        let _ = write!(report, "{{\"symbol\":{},\"count\":{}}}", symbol, count);
+    }
+
+    report.push_str("],\"dictionary\":[");
+    for (position, entry) in analysis.dictionary_entries.iter().enumerate() {
+        if position > 0 {
+            report.push(',');
+        }
+        let _ = write!(
+            report,
+            "{{\"word\":{},\"occurrences\":{},\"with_trailing_space\":{},\"estimated_savings\":{},\"measured_savings\":{}}}",
+            json_string(&entry.word),
+            entry.occurrences,
+            entry.with_trailing_space,
+            entry.estimated_savings,
+            json_number(entry.measured_savings)
+        );
+    }
+    report.push_str("]}");
+
+    report
+}
+
+/// CSV-отчет
+fn export_csv(analysis: &CompressionAnalysis) -> String {
+    let mut report = String::from("section,key,count,savings\n");
+
+    let summary = [
+        ("original_size", analysis.original_size.to_string()),
+        ("compressed_size", analysis.compressed_size.to_string()),
+        ("header_overhead", analysis.header_overhead.to_string()),
+        (
+            "compression_ratio",
+            format!("{:.4}", analysis.compression_ratio),
+        ),
+        (
+            "shannon_entropy",
+            format!("{:.4}", analysis.shannon_entropy),
+        ),
+        (
+            "compression_density",
+            format!("{:.4}", analysis.compression_density),
+        ),
+    ];
+    for (key, value) in summary {
+        let _ = writeln!(report, "summary,{},{},", key, value);
+    }
+
+    for &(symbol, count) in &analysis.symbol_counts {
+        let _ = writeln!(report, "symbol,{},{},", symbol, count);
+    }
+
+    for entry in &analysis.dictionary_entries {
+        let _ = writeln!(
+            report,
+            "word,{},{},{}",
+            csv_field(&entry.word),
+            entry.occurrences,
+            entry.estimated_savings
+        );
+    }
+
+    report
+}
+
+/// Число JSON (NaN и бесконечности превращаются в `null`)
+fn json_number(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+/// Строка JSON с экранированием
+pub(crate) fn json_string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    for character in text.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            control if (control as u32) < 0x20 => {
+                let _ = write!(escaped, "\\u{:04x}", control as u32);
+            }
+            other => escaped.push(other),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Поле CSV с кавычками при необходимости
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Тесты экспорта отчетов 🎯
+#[cfg(test)]
+mod report_scribe_tests {
+    use super::*;
+    use crate::statistics::analyze_compression;
+
+    /// Повторы, ради которых «the» окупает строку словаря
+    fn sample() -> Vec<u8> {
+        b"the cat and the dog and the bird and the fish and the end. ".repeat(10)
+    }
+
+    #[test]
+    fn test_json_report_contains_all_sections() {
+        let sample = sample();
+        let analysis = analyze_compression(&sample);
+        let json = export_report(&analysis, ReportFormat::Json);
+
+        assert!(json.starts_with('{') && json.ends_with('}'));
+        assert!(json.contains(&format!("\"original_size\":{}", sample.len())));
+        assert!(json.contains("\"symbols\":[{\"symbol\":"));
+        assert!(json.contains("\"word\":\"the\""));
+        assert!(json.contains("\"measured_savings\":"));
+        assert!(json.contains(&format!("\"header_overhead\":{}", analysis.header_overhead)));
+    }
+
+    #[test]
+    fn test_csv_report_rows() {
+        let analysis = analyze_compression(sample());
+        let csv = export_report(&analysis, ReportFormat::Csv);
+
+        let symbol_rows = csv
+            .lines()
+            .filter(|line| line.starts_with("symbol,"))
+            .count();
+        assert_eq!(symbol_rows, analysis.symbol_counts.len());
+        assert!(csv.lines().any(|line| line.starts_with("word,the,")));
+    }
+
+    #[test]
+    fn test_escaping_and_non_finite_numbers() {
+        assert_eq!(json_string("a\"b\n"), "\"a\\\"b\\n\"");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(json_number(f64::NAN), "null");
+    }
+}
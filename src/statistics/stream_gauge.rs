@@ -0,0 +1,193 @@
+//! Датчик потока: статистика без буферизации 🌊
+//!
+//! Агенту мониторинга нужно знать, насколько сжимаемы данные, которые
+//! текут через него, — но не хранить их. [`StreamingAnalyzer`] копит
+//! только гистограмму байтов: куски подаются по мере прихода, а
+//! [`finalize`](StreamingAnalyzer::finalize) дает [`CompressionAnalysis`]
+//! с оценкой сжатия по энтропии.
+
+use super::compression_analysis::CompressionAnalysis;
+use crate::compression_engine::frequency_census::{byte_histogram, histogram_entropy};
+use std::io;
+
+/// Сколько самых частых байтов попадает в `top_symbols`
+const TOP_SYMBOLS: usize = 10;
+
+/// Потоковый анализ сжимаемости 🌊
+///
+/// ```
+/// use arithmetic_compression_wizard::statistics::StreamingAnalyzer;
+///
+/// let mut analyzer = StreamingAnalyzer::new();
+/// for chunk in [&b"the wizard "[..], b"and the owl"] {
+///     analyzer.update(chunk);
+/// }
+/// let analysis = analyzer.finalize();
+/// assert_eq!(analysis.original_size, 22);
+/// assert!(analysis.compressed_size < 22);
+/// ```
+///
+/// Анализатор реализует [`io::Write`], так что поток можно передать
+/// через `io::copy`.
+#[derive(Debug, Clone)]
+pub struct StreamingAnalyzer {
+    histogram: [u64; 256],
+    bytes_seen: u64,
+}
+
+impl StreamingAnalyzer {
+    /// Анализатор без данных
+    pub fn new() -> Self {
+        Self {
+            histogram: [0; 256],
+            bytes_seen: 0,
+        }
+    }
+
+    /// Учитывает очередной кусок потока
+    pub fn update(&mut self, chunk: &[u8]) {
+        for (total, count) in self.histogram.iter_mut().zip(byte_histogram(chunk)) {
+            *total += count;
+        }
+        self.bytes_seen += chunk.len() as u64;
+    }
+
+    /// Сколько байтов учтено
+    pub fn bytes_seen(&self) -> u64 {
+        self.bytes_seen
+    }
+
+    /// Гистограмма учтенных байтов
+    pub fn histogram(&self) -> &[u64; 256] {
+        &self.histogram
+    }
+
+    /// Текущая энтропия Шеннона, бит на байт (ноль без данных)
+    pub fn entropy(&self) -> f64 {
+        histogram_entropy(&self.histogram)
+    }
+
+    /// Итоговый анализ 📊
+    ///
+    /// Данные не сжимаются, поэтому `compressed_size` — оценка тела
+    /// статической модели порядка 0: `⌈длина × энтропия / 8⌉` байт.
+    /// Словарь и заголовок не оцениваются: их поля пусты, а
+    /// `symbol_counts` — частоты байтов.
+    pub fn finalize(self) -> CompressionAnalysis {
+        let original_size = self.bytes_seen as usize;
+        let shannon_entropy = self.entropy();
+        let compressed_size = (self.bytes_seen as f64 * shannon_entropy / 8.0).ceil() as usize;
+        let (compression_ratio, compression_density) = if original_size == 0 {
+            (0.0, 0.0)
+        } else {
+            (
+                (1.0 - compressed_size as f64 / original_size as f64) * 100.0,
+                compressed_size as f64 * 8.0 / original_size as f64,
+            )
+        };
+
+        let symbol_counts: Vec<(u32, u64)> = (0u32..)
+            .zip(self.histogram)
+            .filter(|&(_, count)| count > 0)
+            .collect();
+        let mut top_symbols = symbol_counts.clone();
+        top_symbols.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+        top_symbols.truncate(TOP_SYMBOLS);
+
+        CompressionAnalysis {
+            original_size,
+            compressed_size,
+            compression_ratio,
+            shannon_entropy,
+            compression_density,
+            word_dictionary_size: 0,
+            phrase_dictionary_size: 0,
+            top_symbols,
+            symbol_counts,
+            dictionary_entries: Vec::new(),
+            header_overhead: 0,
+        }
+    }
+}
+
+impl Default for StreamingAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl io::Write for StreamingAnalyzer {
+    fn write(&mut self, chunk: &[u8]) -> io::Result<usize> {
+        self.update(chunk);
+        Ok(chunk.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Тесты датчика потока 🎯
+#[cfg(test)]
+mod stream_gauge_tests {
+    use super::*;
+    use crate::compression_engine::frequency_census::shannon_entropy;
+    use crate::statistics::analyze_compression;
+
+    #[test]
+    fn test_chunks_match_whole_input() {
+        let data = "the wizard and the owl read the ancient scroll. ".repeat(50);
+        let mut analyzer = StreamingAnalyzer::new();
+        for chunk in data.as_bytes().chunks(7) {
+            analyzer.update(chunk);
+        }
+        assert_eq!(analyzer.bytes_seen(), data.len() as u64);
+        assert!((analyzer.entropy() - shannon_entropy(data.as_bytes())).abs() < 1e-12);
+
+        let streamed = analyzer.finalize();
+        let buffered = analyze_compression(&data);
+        assert_eq!(streamed.original_size, buffered.original_size);
+        assert!((streamed.shannon_entropy - buffered.shannon_entropy).abs() < 1e-12);
+        assert_eq!(streamed.top_symbols[0], (b' ' as u32, 450));
+        assert_eq!(
+            streamed
+                .symbol_counts
+                .iter()
+                .map(|&(_, count)| count)
+                .sum::<u64>(),
+            2400
+        );
+    }
+
+    #[test]
+    fn test_estimate_follows_entropy() {
+        let mut uniform = StreamingAnalyzer::new();
+        uniform.update(&(0..=255).collect::<Vec<u8>>());
+        let uniform = uniform.finalize();
+        assert_eq!(uniform.compressed_size, 256);
+        assert_eq!(uniform.compression_ratio, 0.0);
+        assert_eq!(uniform.top_symbols.len(), TOP_SYMBOLS);
+
+        let mut constant = StreamingAnalyzer::default();
+        constant.update(&[7; 1000]);
+        let constant = constant.finalize();
+        assert_eq!(
+            (constant.compressed_size, constant.compression_ratio),
+            (0, 100.0)
+        );
+        assert_eq!(constant.symbol_counts, [(7, 1000)]);
+    }
+
+    #[test]
+    fn test_empty_stream_and_io_copy() {
+        let empty = StreamingAnalyzer::new().finalize();
+        assert_eq!((empty.original_size, empty.compressed_size), (0, 0));
+        assert_eq!(empty.compression_ratio, 0.0);
+        assert!(empty.symbol_counts.is_empty());
+
+        let mut analyzer = StreamingAnalyzer::new();
+        let copied = io::copy(&mut &b"owl post owl post"[..], &mut analyzer).unwrap();
+        assert_eq!(copied, 17);
+        assert_eq!(analyzer.histogram()[b'o' as usize], 4);
+    }
+}
@@ -0,0 +1,103 @@
+//! Сума для каталогов 🎒 (фича `tar`)
+//!
+//! Упаковывает дерево каталогов в `.tar.acw`: tar-поток сразу уходит в
+//! [`BlockStreamWriter`], поэтому tar-архив целиком в памяти не
+//! собирается — только текущий блок. Распаковка так же читает архив
+//! блок за блоком через [`BlockStreamReader`].
+
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::format::block_ledger::DEFAULT_BLOCK_SIZE;
+use crate::format::block_stream::{BlockStreamReader, BlockStreamWriter};
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+/// Упаковывает каталог `dir` в `.tar.acw` 🎒
+///
+/// Пути в tar-архиве относительны `dir`. Возвращает писатель `out`.
+pub fn archive_dir<W: Write>(dir: impl AsRef<Path>, out: W) -> io::Result<W> {
+    let mut tar_builder = tar::Builder::new(BlockStreamWriter::new(out, DEFAULT_BLOCK_SIZE)?);
+    tar_builder.follow_symlinks(false);
+    tar_builder.append_dir_all(".", dir)?;
+    tar_builder.into_inner()?.finish()
+}
+
+/// Распаковывает `.tar.acw` в каталог `dest` 📂
+///
+/// Действуют лимиты [`DecodeOptions::default`], в том числе на общий
+/// размер tar-потока; снять его можно через
+/// [`extract_archive_with_options`]. Пути, выходящие за `dest`, tar
+/// отклоняет.
+pub fn extract_archive(archive: impl Read, dest: impl AsRef<Path>) -> io::Result<()> {
+    extract_archive_with_options(archive, dest, DecodeOptions::default())
+}
+
+/// Распаковка `.tar.acw` с явными лимитами
+///
+/// `options.max_output_bytes` ограничивает общий размер tar-потока.
+pub fn extract_archive_with_options(
+    archive: impl Read,
+    dest: impl AsRef<Path>,
+    options: DecodeOptions,
+) -> io::Result<()> {
+    tar::Archive::new(BlockStreamReader::new(archive, options)?).unpack(dest)
+}
+
+/// Тесты сумы для каталогов 🎯
+#[cfg(test)]
+mod tar_satchel_tests {
+    use super::*;
+    use crate::testkit::scratch_dir::ScratchDir;
+    use std::fs;
+
+    #[test]
+    fn test_directory_roundtrip() {
+        let source = ScratchDir::new("tar-source");
+        fs::create_dir_all(source.0.join("spells/ancient")).unwrap();
+        fs::write(source.0.join("readme.txt"), "the owl guards the tower\n").unwrap();
+        fs::write(
+            source.0.join("spells/ancient/fire.txt"),
+            "ignis ".repeat(5000),
+        )
+        .unwrap();
+        fs::write(source.0.join("spells/empty.bin"), b"").unwrap();
+
+        let archive = archive_dir(&source.0, Vec::new()).unwrap();
+
+        let target = ScratchDir::new("tar-target");
+        extract_archive(archive.as_slice(), &target.0).unwrap();
+        assert_eq!(
+            fs::read(target.0.join("readme.txt")).unwrap(),
+            b"the owl guards the tower\n"
+        );
+        assert_eq!(
+            fs::read_to_string(target.0.join("spells/ancient/fire.txt")).unwrap(),
+            "ignis ".repeat(5000)
+        );
+        assert!(fs::read(target.0.join("spells/empty.bin"))
+            .unwrap()
+            .is_empty());
+
+        let damaged = &archive[..archive.len() / 2];
+        let error = extract_archive(damaged, &target.0).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_output_limit_applies_to_whole_stream() {
+        let source = ScratchDir::new("tar-limit-source");
+        fs::write(source.0.join("fire.txt"), "ignis ".repeat(5000)).unwrap();
+        let archive = archive_dir(&source.0, Vec::new()).unwrap();
+
+        let target = ScratchDir::new("tar-limit-target");
+        let tight = DecodeOptions::default().with_max_output_bytes(1024);
+        let error = extract_archive_with_options(archive.as_slice(), &target.0, tight).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        let lifted = DecodeOptions::default().with_max_output_bytes(usize::MAX);
+        extract_archive_with_options(archive.as_slice(), &target.0, lifted).unwrap();
+        assert_eq!(
+            fs::read_to_string(target.0.join("fire.txt")).unwrap(),
+            "ignis ".repeat(5000)
+        );
+    }
+}
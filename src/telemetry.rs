@@ -0,0 +1,282 @@
+//! Телеметрия кодека 📡
+//!
+//! С фичей `metrics` библиотека сообщает счетчики и гистограммы через
+//! фасад [`metrics`](https://docs.rs/metrics): сервис подключает любой
+//! экспортер (Prometheus, StatsD), и сжатие видно в мониторинге без
+//! обертки вокруг каждого вызова. Без фичи замеры не делаются вовсе.
+//!
+//! Сжатие измеряется в словарном арифметическом движке — через него идут
+//! `compress_data`, архивы из блоков (по кадру на блок), адаптивная модель
+//! и [`ArithmeticEngine`](crate::compression_engine::ArithmeticEngine).
+//! Метки: `model` — `static` или `adaptive`. Распаковка измеряется в
+//! [`try_decompress_data`](crate::simple_api::try_decompress_data) и
+//! [`decompress_data`](crate::simple_api::decompress_data), целым архивом.
+//!
+//! | Метрика | Вид | Смысл |
+//! |---|---|---|
+//! | [`COMPRESS_BYTES_IN`] | счетчик | байты входа |
+//! | [`COMPRESS_BYTES_OUT`] | счетчик | байты битового потока (без заголовка и модели) |
+//! | [`COMPRESS_SECONDS`] | гистограмма | время кодирования |
+//! | [`DICTIONARY_HIT_RATIO`] | гистограмма | доля символов потока, ставших словами словаря |
+//! | [`DECOMPRESS_BYTES_IN`] | счетчик | байты архива |
+//! | [`DECOMPRESS_BYTES_OUT`] | счетчик | восстановленные байты |
+//! | [`DECOMPRESS_SECONDS`] | гистограмма | время распаковки |
+//! | [`DECOMPRESS_ERRORS`] | счетчик | отвергнутые архивы |
+
+#[cfg(feature = "metrics")]
+use crate::compression_engine::compression_conjurer::WORD_SYMBOL_BASE;
+#[cfg(feature = "metrics")]
+use std::time::Instant;
+
+/// Байты входа сжатия
+pub const COMPRESS_BYTES_IN: &str = "acw_compress_bytes_in";
+/// Байты битового потока после сжатия
+pub const COMPRESS_BYTES_OUT: &str = "acw_compress_bytes_out";
+/// Время кодирования, секунды
+pub const COMPRESS_SECONDS: &str = "acw_compress_seconds";
+/// Доля символов потока, ставших словами словаря
+pub const DICTIONARY_HIT_RATIO: &str = "acw_dictionary_hit_ratio";
+/// Байты распаковываемых архивов
+pub const DECOMPRESS_BYTES_IN: &str = "acw_decompress_bytes_in";
+/// Восстановленные байты
+pub const DECOMPRESS_BYTES_OUT: &str = "acw_decompress_bytes_out";
+/// Время распаковки, секунды
+pub const DECOMPRESS_SECONDS: &str = "acw_decompress_seconds";
+/// Архивы, которые распаковка отвергла
+pub const DECOMPRESS_ERRORS: &str = "acw_decompress_errors";
+
+/// Описывает метрики в установленном регистраторе (фича `metrics`)
+///
+/// Вызывается один раз после установки экспортера, чтобы у метрик были
+/// единицы и описания.
+#[cfg(feature = "metrics")]
+pub fn describe_metrics() {
+    use metrics::{describe_counter, describe_histogram, Unit};
+
+    describe_counter!(COMPRESS_BYTES_IN, Unit::Bytes, "Байты входа сжатия");
+    describe_counter!(COMPRESS_BYTES_OUT, Unit::Bytes, "Байты битового потока");
+    describe_histogram!(COMPRESS_SECONDS, Unit::Seconds, "Время кодирования");
+    describe_histogram!(
+        DICTIONARY_HIT_RATIO,
+        Unit::Count,
+        "Доля символов-слов в потоке"
+    );
+    describe_counter!(DECOMPRESS_BYTES_IN, Unit::Bytes, "Байты архивов");
+    describe_counter!(DECOMPRESS_BYTES_OUT, Unit::Bytes, "Восстановленные байты");
+    describe_histogram!(DECOMPRESS_SECONDS, Unit::Seconds, "Время распаковки");
+    describe_counter!(DECOMPRESS_ERRORS, Unit::Count, "Отвергнутые архивы");
+}
+
+/// Начало замера (без фичи `metrics` — пустышка)
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Stopwatch {
+    #[cfg(feature = "metrics")]
+    started: Instant,
+}
+
+impl Stopwatch {
+    /// Запускает замер
+    pub(crate) fn start() -> Self {
+        Self {
+            #[cfg(feature = "metrics")]
+            started: Instant::now(),
+        }
+    }
+}
+
+/// Сообщает о закодированном кадре
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn record_compression(
+    model: &'static str,
+    stopwatch: Stopwatch,
+    bytes_in: usize,
+    symbols: &[u32],
+    bytes_out: usize,
+) {
+    #[cfg(feature = "metrics")]
+    {
+        let seconds = stopwatch.started.elapsed().as_secs_f64();
+        metrics::counter!(COMPRESS_BYTES_IN, "model" => model).increment(bytes_in as u64);
+        metrics::counter!(COMPRESS_BYTES_OUT, "model" => model).increment(bytes_out as u64);
+        metrics::histogram!(COMPRESS_SECONDS, "model" => model).record(seconds);
+        if !symbols.is_empty() {
+            let hits = symbols
+                .iter()
+                .filter(|&&symbol| symbol >= WORD_SYMBOL_BASE)
+                .count();
+            metrics::histogram!(DICTIONARY_HIT_RATIO, "model" => model)
+                .record(hits as f64 / symbols.len() as f64);
+        }
+    }
+}
+
+/// Сообщает об итоге распаковки архива
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn record_decompression(
+    stopwatch: Stopwatch,
+    bytes_in: usize,
+    bytes_out: Option<usize>,
+) {
+    #[cfg(feature = "metrics")]
+    {
+        metrics::counter!(DECOMPRESS_BYTES_IN).increment(bytes_in as u64);
+        match bytes_out {
+            Some(bytes_out) => {
+                metrics::counter!(DECOMPRESS_BYTES_OUT).increment(bytes_out as u64);
+                metrics::histogram!(DECOMPRESS_SECONDS)
+                    .record(stopwatch.started.elapsed().as_secs_f64());
+            }
+            None => metrics::counter!(DECOMPRESS_ERRORS).increment(1),
+        }
+    }
+}
+
+/// Тесты телеметрии 🎯
+#[cfg(all(test, feature = "metrics"))]
+mod telemetry_tests {
+    use super::*;
+    use crate::decompression_oracle::decode_wards::DecodeOptions;
+    use crate::simple_api::{compress_data, compress_data_adaptive, try_decompress_data};
+    use metrics::{
+        Counter, CounterFn, Gauge, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder,
+        SharedString, Unit,
+    };
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    /// Значения метрик по имени и меткам
+    type Readings = Arc<Mutex<HashMap<String, Vec<f64>>>>;
+
+    /// Регистратор, складывающий все значения в таблицу
+    #[derive(Default)]
+    struct TallyRecorder {
+        readings: Readings,
+    }
+
+    struct Tally {
+        name: String,
+        readings: Readings,
+    }
+
+    impl Tally {
+        fn push(&self, value: f64) {
+            self.readings
+                .lock()
+                .unwrap()
+                .entry(self.name.clone())
+                .or_default()
+                .push(value);
+        }
+    }
+
+    impl CounterFn for Tally {
+        fn increment(&self, value: u64) {
+            self.push(value as f64);
+        }
+
+        fn absolute(&self, value: u64) {
+            self.push(value as f64);
+        }
+    }
+
+    impl HistogramFn for Tally {
+        fn record(&self, value: f64) {
+            self.push(value);
+        }
+    }
+
+    impl TallyRecorder {
+        fn tally(&self, key: &Key) -> Arc<Tally> {
+            let labels: Vec<String> = key
+                .labels()
+                .map(|label| format!("{}={}", label.key(), label.value()))
+                .collect();
+            Arc::new(Tally {
+                name: format!("{}{{{}}}", key.name(), labels.join(",")),
+                readings: self.readings.clone(),
+            })
+        }
+
+        fn sum(&self, name: &str) -> f64 {
+            self.readings
+                .lock()
+                .unwrap()
+                .get(name)
+                .map_or(0.0, |values| values.iter().sum())
+        }
+
+        fn count(&self, name: &str) -> usize {
+            self.readings.lock().unwrap().get(name).map_or(0, Vec::len)
+        }
+    }
+
+    impl Recorder for TallyRecorder {
+        fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+            Counter::from_arc(self.tally(key))
+        }
+
+        fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge {
+            Gauge::noop()
+        }
+
+        fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+            Histogram::from_arc(self.tally(key))
+        }
+    }
+
+    #[test]
+    fn test_compression_is_measured_per_model() {
+        let recorder = TallyRecorder::default();
+        let data = "the wizard and the owl read the ancient scroll. ".repeat(40);
+        metrics::with_local_recorder(&recorder, || {
+            compress_data(&data);
+            compress_data_adaptive(&data, 4096);
+            describe_metrics();
+        });
+
+        for model in ["static", "adaptive"] {
+            let labels = format!("{{model={model}}}");
+            assert_eq!(
+                recorder.sum(&format!("{COMPRESS_BYTES_IN}{labels}")),
+                data.len() as f64
+            );
+            let bytes_out = recorder.sum(&format!("{COMPRESS_BYTES_OUT}{labels}"));
+            assert!(bytes_out > 0.0 && bytes_out < data.len() as f64 / 2.0);
+            assert_eq!(recorder.count(&format!("{COMPRESS_SECONDS}{labels}")), 1);
+            let hit_ratio = recorder.sum(&format!("{DICTIONARY_HIT_RATIO}{labels}"));
+            assert!(hit_ratio > 0.1 && hit_ratio < 1.0, "{model}: {hit_ratio}");
+        }
+    }
+
+    #[test]
+    fn test_decompression_and_errors_are_measured() {
+        let recorder = TallyRecorder::default();
+        let archive = compress_data(b"owl post owl post");
+        metrics::with_local_recorder(&recorder, || {
+            try_decompress_data(&archive, &DecodeOptions::default()).unwrap();
+            try_decompress_data(b"garbage", &DecodeOptions::default()).unwrap_err();
+        });
+
+        assert_eq!(
+            recorder.sum(&format!("{DECOMPRESS_BYTES_IN}{{}}")),
+            (archive.len() + 7) as f64
+        );
+        assert_eq!(recorder.sum(&format!("{DECOMPRESS_BYTES_OUT}{{}}")), 17.0);
+        assert_eq!(recorder.count(&format!("{DECOMPRESS_SECONDS}{{}}")), 1);
+        assert_eq!(recorder.sum(&format!("{DECOMPRESS_ERRORS}{{}}")), 1.0);
+    }
+
+    #[test]
+    fn test_nothing_is_reported_without_recorder() {
+        // Без регистратора вызовы — пустые операции фасада
+        let archive = compress_data(b"quiet owl");
+        assert_eq!(
+            try_decompress_data(&archive, &DecodeOptions::default()).unwrap(),
+            b"quiet owl"
+        );
+    }
+}
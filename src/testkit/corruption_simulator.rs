@@ -0,0 +1,193 @@
+//! Симулятор повреждений 💥
+//!
+//! Портит сериализованные архивы так, как это делают диски и сети:
+//! переворачивает биты, обрезает хвост, подменяет таблицу частот.
+//! Тесты модуля проверяют, что на каждый класс повреждений
+//! [`try_decompress_data`](crate::simple_api::try_decompress_data)
+//! отвечает типизированной ошибкой, а не паникой.
+//!
+//! ## Ограничение
+//! В архиве нет контрольной суммы: перевернутый бит внутри битового потока
+//! может дать неверные данные без ошибки. Гарантия для таких повреждений —
+//! отсутствие паники и лимиты [`DecodeOptions`].
+
+use crate::decompression_oracle::decode_wards::DecodeOptions;
+use crate::format::artifact_scroll::{read_grimoire_sections, ScrollCursor};
+use crate::format::artifact_view::FREQUENCY_ENTRY_LEN;
+use crate::format::container_runes::{ArchiveHeader, ModelKind};
+
+/// Вид повреждения 💥
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CorruptionKind {
+    /// Переворачивает бит `bit % 8` в байте `offset` (по модулю длины архива)
+    BitFlip { offset: usize, bit: u8 },
+    /// Оставляет только первые `keep` байтов
+    Truncate { keep: usize },
+    /// Увеличивает на единицу частоту записи `entry` (по модулю числа записей)
+    ///
+    /// Архивы без встроенной таблицы частот (общая модель, внешний словарь)
+    /// и архивы из блоков не меняются: портите кадр блока отдельно.
+    FrequencyTamper { entry: usize },
+}
+
+/// Возвращает копию архива с повреждением `kind`
+pub fn corrupt(artifact_bytes: impl AsRef<[u8]>, kind: CorruptionKind) -> Vec<u8> {
+    let mut damaged = artifact_bytes.as_ref().to_vec();
+
+    match kind {
+        CorruptionKind::BitFlip { offset, bit } => {
+            if !damaged.is_empty() {
+                let position = offset % damaged.len();
+                damaged[position] ^= 1 << (bit % 8);
+            }
+        }
+        CorruptionKind::Truncate { keep } => damaged.truncate(keep),
+        CorruptionKind::FrequencyTamper { entry } => {
+            if let Some((offset, field_len)) = frequency_field(&damaged, entry) {
+                let field = &mut damaged[offset..offset + field_len];
+                let mut buffer = [0u8; 8];
+                buffer[..field_len].copy_from_slice(field);
+                let tampered = u64::from_le_bytes(buffer).wrapping_add(1);
+                field.copy_from_slice(&tampered.to_le_bytes()[..field_len]);
+            }
+        }
+    }
+
+    damaged
+}
+
+/// Смещение и ширина поля частоты записи `entry`
+///
+/// Первая таблица тела кадра: у арифметического движка она единственная,
+/// у движка раздельных потоков это таблица литералов.
+fn frequency_field(archive_bytes: &[u8], entry: usize) -> Option<(usize, usize)> {
+    let (header, body_offset) = ArchiveHeader::parse(archive_bytes)?;
+    if header.model_kind != ModelKind::StaticOrder0
+        || header.dictionary_id.is_some()
+        || header.block_framed
+    {
+        return None;
+    }
+
+    let mut cursor = ScrollCursor::new(&archive_bytes[body_offset..], 0);
+    read_grimoire_sections(&mut cursor, &header, &DecodeOptions::unlimited()).ok()?;
+    let records_offset = body_offset + cursor.position() + 4;
+    let entry_count = cursor.read_u32().ok()? as usize;
+    if entry_count == 0 {
+        return None;
+    }
+
+    // С атласом запись — одна частота u32, иначе [символ u32][частота u64][начало u64]
+    let (record_len, field_start, field_len) = match header.alphabet_map {
+        Some(_) => (4, 0, 4),
+        None => (FREQUENCY_ENTRY_LEN, 4, 8),
+    };
+    let offset = records_offset + (entry % entry_count) * record_len + field_start;
+    (offset + field_len <= archive_bytes.len()).then_some((offset, field_len))
+}
+
+/// Тесты симулятора повреждений 🎯
+#[cfg(test)]
+mod corruption_simulator_tests {
+    use super::*;
+    use crate::error::WizardError;
+    use crate::format::artifact_scroll::{parse_artifact_ref, ScrollOptions};
+    use crate::simple_api::{compress_data, compress_data_with_options, try_decompress_data};
+
+    fn sample_archives() -> Vec<Vec<u8>> {
+        let prose = "the wizard and the owl read the scroll. ".repeat(8);
+        vec![
+            compress_data(&prose),
+            compress_data_with_options(
+                b"GATTACAGATTACACCGGTTAAGATTACA",
+                &ScrollOptions::default().with_compact_alphabet(true),
+            ),
+        ]
+    }
+
+    /// Распаковка с исходной ошибкой без места в архиве
+    fn decode(archive: &[u8]) -> Result<Vec<u8>, WizardError> {
+        try_decompress_data(archive, &DecodeOptions::default())
+            .map_err(|error| error.root_cause().clone())
+    }
+
+    fn table_entry_count(archive: &[u8]) -> usize {
+        parse_artifact_ref(archive, &DecodeOptions::default())
+            .unwrap()
+            .frequency_codex
+            .len()
+    }
+
+    #[test]
+    fn test_truncation_is_reported() {
+        for archive in sample_archives() {
+            for keep in 0..archive.len() {
+                let damaged = corrupt(&archive, CorruptionKind::Truncate { keep });
+                assert!(
+                    matches!(
+                        decode(&damaged),
+                        Err(WizardError::Truncated { .. } | WizardError::UnrecognizedHeader)
+                    ),
+                    "обрезка до {keep} байтов"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_frequency_tamper_is_reported() {
+        for archive in sample_archives() {
+            let entry_count = table_entry_count(&archive);
+            assert!(entry_count > 1);
+
+            for entry in 0..entry_count {
+                let damaged = corrupt(&archive, CorruptionKind::FrequencyTamper { entry });
+                assert_ne!(damaged, archive);
+                assert!(
+                    matches!(decode(&damaged), Err(WizardError::CorruptFrequencyTable(_))),
+                    "запись {entry}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_bit_flips_never_panic() {
+        for archive in sample_archives() {
+            for offset in 0..archive.len() {
+                for bit in 0..8 {
+                    let damaged = corrupt(&archive, CorruptionKind::BitFlip { offset, bit });
+                    assert_ne!(damaged, archive);
+                    let _ = decode(&damaged);
+                }
+            }
+
+            // Старший бит версии дает версию из будущего
+            let future = corrupt(&archive, CorruptionKind::BitFlip { offset: 3, bit: 7 });
+            assert!(matches!(
+                decode(&future),
+                Err(WizardError::UnsupportedVersion(_))
+            ));
+
+            // Младший бит частоты ломает сумму таблицы
+            let (offset, _) = frequency_field(&archive, 0).unwrap();
+            let skewed = corrupt(&archive, CorruptionKind::BitFlip { offset, bit: 0 });
+            assert!(matches!(
+                decode(&skewed),
+                Err(WizardError::CorruptFrequencyTable(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_tables_outside_archive_are_left_alone() {
+        assert_eq!(
+            corrupt(
+                b"not an archive",
+                CorruptionKind::FrequencyTamper { entry: 0 }
+            ),
+            b"not an archive"
+        );
+        assert!(corrupt(b"", CorruptionKind::BitFlip { offset: 7, bit: 1 }).is_empty());
+    }
+}
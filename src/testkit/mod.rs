@@ -0,0 +1,13 @@
+//! Набор испытаний 🧪
+//! Инструменты для проверки устойчивости декодера
+
+pub mod corruption_simulator;
+#[cfg(test)]
+pub(crate) mod scratch_dir;
+
+// Экспорт симулятора повреждений
+
+pub use corruption_simulator::{
+    corrupt,        // Портит архив заданным способом
+    CorruptionKind, // Вид повреждения
+};
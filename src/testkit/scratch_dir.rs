@@ -0,0 +1,38 @@
+//! Временные каталоги тестов 🗂️
+//!
+//! [`ScratchDir`] создает чистый каталог во временной папке и удаляет его
+//! вместе с содержимым, когда тест заканчивается, даже паникой.
+
+use std::fs;
+use std::path::PathBuf;
+
+/// Временный каталог теста, удаляемый при выходе
+///
+/// Тесты одного процесса идут параллельно, поэтому имя должно быть
+/// уникальным во всем крейте: начинайте его с имени модуля.
+pub(crate) struct ScratchDir(pub(crate) PathBuf);
+
+impl ScratchDir {
+    pub(crate) fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("acw-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    /// Имена файлов каталога по алфавиту
+    pub(crate) fn entries(&self) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(&self.0)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
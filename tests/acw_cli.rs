@@ -0,0 +1,285 @@
+//! Утилита acw 🧙‍♂️
+//!
+//! Сквозная проверка: обучение словаря, сжатие с ним и распаковка, том
+//! каталога и его восстановление.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Временный каталог, удаляемый после теста
+struct Workspace(PathBuf);
+
+impl Workspace {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("acw_{}_{}", name, std::process::id()));
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    fn file(&self, name: &str) -> String {
+        self.0.join(name).to_string_lossy().into_owned()
+    }
+}
+
+impl Drop for Workspace {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn acw(arguments: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_acw"))
+        .args(arguments)
+        .output()
+        .expect("запуск acw")
+}
+
+fn write_corpus(path: &Path, seed: usize) {
+    let records: String = (0..40)
+        .map(|index| {
+            format!(
+                "{{\"user\":\"user{}\",\"status\":\"active\",\"region\":\"north\"}}\n",
+                index * 7 + seed
+            )
+        })
+        .collect();
+    fs::write(path, records).unwrap();
+}
+
+#[test]
+fn test_train_compress_decompress_with_dictionary() {
+    let workspace = Workspace::new("dictionary");
+    let (corpus_a, corpus_b) = (workspace.file("a.jsonl"), workspace.file("b.jsonl"));
+    write_corpus(Path::new(&corpus_a), 1);
+    write_corpus(Path::new(&corpus_b), 2);
+    let dictionary = workspace.file("dict.acwd");
+
+    let trained = acw(&["train", "--out", &dictionary, &corpus_a, &corpus_b]);
+    assert!(trained.status.success(), "{:?}", trained);
+
+    let message = workspace.file("message.json");
+    fs::write(&message, "{\"user\":\"user999\",\"status\":\"active\"}").unwrap();
+    let (archive, restored) = (workspace.file("message.acw"), workspace.file("restored"));
+
+    let compressed = acw(&["compress", "--dict", &dictionary, &message, &archive]);
+    assert!(compressed.status.success(), "{:?}", compressed);
+
+    // Без словаря такой архив не распаковать
+    let refused = acw(&["decompress", &archive, &restored]);
+    assert_eq!(refused.status.code(), Some(1));
+
+    let decompressed = acw(&["decompress", "--dict", &dictionary, &archive, &restored]);
+    assert!(decompressed.status.success(), "{:?}", decompressed);
+    assert_eq!(fs::read(&restored).unwrap(), fs::read(&message).unwrap());
+}
+
+#[test]
+fn test_compress_without_dictionary_and_usage_errors() {
+    let workspace = Workspace::new("plain");
+    let (input, archive, restored) = (
+        workspace.file("input"),
+        workspace.file("input.acw"),
+        workspace.file("restored"),
+    );
+    fs::write(&input, "plain archive without a dictionary").unwrap();
+
+    assert!(acw(&["compress", &input, &archive]).status.success());
+    assert!(acw(&["decompress", &archive, &restored]).status.success());
+    assert_eq!(fs::read(&restored).unwrap(), fs::read(&input).unwrap());
+
+    assert_eq!(acw(&[]).status.code(), Some(2));
+    assert_eq!(acw(&["train", &input]).status.code(), Some(2));
+    assert_eq!(acw(&["compress", "--level", "9"]).status.code(), Some(2));
+}
+
+#[test]
+fn test_inspect_prints_dictionary() {
+    let workspace = Workspace::new("inspect");
+    let (input, archive) = (workspace.file("input.jsonl"), workspace.file("input.acw"));
+    write_corpus(Path::new(&input), 3);
+    assert!(acw(&["compress", &input, &archive]).status.success());
+
+    let header_only = acw(&["inspect", &archive]);
+    assert!(header_only.status.success(), "{:?}", header_only);
+    assert!(!String::from_utf8_lossy(&header_only.stdout).contains("Словарь"));
+
+    let with_dictionary = acw(&["inspect", "--dict", &archive]);
+    assert!(with_dictionary.status.success(), "{:?}", with_dictionary);
+    let listing = String::from_utf8_lossy(&with_dictionary.stdout);
+    assert!(listing.contains("\"status\""), "{}", listing);
+
+    let garbage = workspace.file("garbage");
+    fs::write(&garbage, "x").unwrap();
+    assert_eq!(acw(&["inspect", &garbage]).status.code(), Some(1));
+    assert_eq!(acw(&["inspect", "--dict"]).status.code(), Some(2));
+}
+
+#[test]
+fn test_recursive_compress_and_extract() {
+    let workspace = Workspace::new("recursive");
+    let source = workspace.0.join("site");
+    fs::create_dir_all(source.join("docs/guides")).unwrap();
+    fs::create_dir_all(source.join("cache")).unwrap();
+    fs::write(source.join("index.html"), "<p>owl post</p>\n".repeat(40)).unwrap();
+    fs::write(source.join("docs/guides/spells.md"), "# ignis\n".repeat(30)).unwrap();
+    fs::write(source.join("docs/logo.png"), [0x89, b'P', b'N', b'G']).unwrap();
+    fs::write(source.join("cache/stale.html"), "stale").unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let script = source.join("deploy.sh");
+        fs::write(&script, "echo deploy\n").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    let (tome, target) = (workspace.file("site.acwt"), workspace.file("restored"));
+    let source_path = source.to_string_lossy().into_owned();
+    let compressed = acw(&[
+        "compress",
+        "-r",
+        &source_path,
+        "--exclude",
+        "*.png",
+        "--exclude",
+        "cache",
+        &tome,
+    ]);
+    assert!(compressed.status.success(), "{:?}", compressed);
+
+    let extracted = acw(&["extract", &tome, &target]);
+    assert!(extracted.status.success(), "{:?}", extracted);
+    let target = Path::new(&target);
+    for relative in ["index.html", "docs/guides/spells.md"] {
+        assert_eq!(
+            fs::read(target.join(relative)).unwrap(),
+            fs::read(source.join(relative)).unwrap()
+        );
+    }
+    assert!(!target.join("docs/logo.png").exists());
+    assert!(!target.join("cache").exists());
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(target.join("deploy.sh"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o755);
+    }
+
+    // --include оставляет только подходящие файлы
+    let markdown = workspace.file("markdown.acwt");
+    let only_markdown = acw(&[
+        "compress",
+        "-r",
+        "--include",
+        "*.md",
+        &source_path,
+        &markdown,
+    ]);
+    assert!(only_markdown.status.success(), "{:?}", only_markdown);
+    let listing = workspace.file("markdown");
+    assert!(acw(&["extract", &markdown, &listing]).status.success());
+    let restored: Vec<_> = fs::read_dir(&listing).unwrap().collect();
+    assert_eq!(restored.len(), 1);
+    assert!(Path::new(&listing).join("docs/guides/spells.md").exists());
+
+    assert_eq!(
+        acw(&["compress", "--exclude", "*.png", &source_path, &tome])
+            .status
+            .code(),
+        Some(2)
+    );
+    let plain = workspace.file("plain.acw");
+    fs::write(&plain, "not a tome").unwrap();
+    assert_eq!(acw(&["extract", &plain, &listing]).status.code(), Some(1));
+}
+
+#[test]
+fn test_recursive_compress_skips_bad_names_and_survives_star_heavy_globs() {
+    let workspace = Workspace::new("recursive-names");
+    let source = workspace.0.join("site");
+    fs::create_dir_all(&source).unwrap();
+    let long_name = format!("{}.txt", "a".repeat(200));
+    fs::write(source.join(&long_name), "owl post\n").unwrap();
+    fs::write(source.join("index.html"), "<p>owl post</p>\n").unwrap();
+    #[cfg(unix)]
+    {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+        fs::write(source.join(OsStr::from_bytes(b"bad\xff.txt")), "latin-1").unwrap();
+    }
+
+    // Шаблон со многими звездами не перебирает разбиения имени
+    let (tome, target) = (workspace.file("site.acwt"), workspace.file("restored"));
+    let source_path = source.to_string_lossy().into_owned();
+    let star_heavy = format!("{}b", "*a".repeat(20));
+    let compressed = acw(&[
+        "compress",
+        "-r",
+        "--exclude",
+        &star_heavy,
+        &source_path,
+        &tome,
+    ]);
+    assert!(compressed.status.success(), "{:?}", compressed);
+    #[cfg(unix)]
+    assert!(String::from_utf8_lossy(&compressed.stderr).contains("имя не в UTF-8"));
+
+    assert!(acw(&["extract", &tome, &target]).status.success());
+    let mut restored: Vec<String> = fs::read_dir(&target)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    restored.sort();
+    assert_eq!(restored, [long_name, "index.html".to_string()]);
+}
+
+#[test]
+fn test_bench_prints_a_row_per_configuration() {
+    let workspace = Workspace::new("bench");
+    let input = workspace.file("corpus.jsonl");
+    write_corpus(Path::new(&input), 4);
+
+    let bench = acw(&[
+        "bench",
+        "--engines",
+        "arithmetic,stored",
+        "--models",
+        "static,order2",
+        &input,
+    ]);
+    assert!(bench.status.success(), "{:?}", bench);
+    let table = String::from_utf8_lossy(&bench.stdout);
+    let rows: Vec<&str> = table
+        .lines()
+        .filter(|line| line.contains("МиБ/с"))
+        .collect();
+    assert_eq!(rows.len(), 3, "{}", table);
+    assert!(rows[0].starts_with("arithmetic") && rows[0].contains("static"));
+    assert!(rows[1].contains("order2"));
+    assert!(rows[2].starts_with("stored"));
+
+    assert_eq!(
+        acw(&["bench", "--engines", "huff", &input]).status.code(),
+        Some(2)
+    );
+    assert_eq!(acw(&["bench"]).status.code(), Some(2));
+}
+
+#[test]
+fn test_bench_rejects_levels_with_a_hint() {
+    let workspace = Workspace::new("bench-levels");
+    let input = workspace.file("corpus.jsonl");
+    write_corpus(Path::new(&input), 1);
+
+    // Уровней сжатия у мастера нет: вместо них предлагаются движки и модели
+    let bench = acw(&["bench", "--levels", "1..9", &input]);
+    assert_eq!(bench.status.code(), Some(2));
+    assert!(bench.stdout.is_empty());
+    let message = String::from_utf8_lossy(&bench.stderr);
+    assert!(message
+        .starts_with("уровней сжатия нет: сравните движки (--engines) и модели (--models)\n"));
+    assert!(message.contains("acw bench"));
+}
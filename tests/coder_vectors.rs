@@ -0,0 +1,186 @@
+//! Векторы переносимости кодера 🌐
+//!
+//! Архив, записанный на одной машине, должен читаться и воспроизводиться
+//! на любой другой. Здесь для фиксированных входов записаны длина и хеш
+//! FNV-1a потока, полученные на x86_64; те же тесты, собранные для
+//! 32-битной (`i686`) и big-endian (`powerpc`, `s390x`) платформ через
+//! `cross test --target <цель> --test coder_vectors`, доказывают, что
+//! кодер и формат дают там те же байты. Отбор слов словаря опирается на
+//! `f64::log2` библиотеки платформы — его расхождение тоже поймает вектор
+//! `static`.
+//!
+//! Векторы, как и золотые архивы, не перезаписываются: несовпадение
+//! означает, что архивы стали зависеть от платформы или версии.
+//!
+//! Намеренное изменение вывода кодировщика (например, другой отбор слов
+//! словаря) получает новую ревизию векторов архивов: [`ARCHIVE_REVISIONS`]
+//! растет, прежние ревизии остаются. Сжатие сверяется с последней
+//! ревизией, а архивы прежних ревизий лежат в `tests/golden/vectors/`
+//! (`<вектор>.r<ревизия>.acw`): их хеши сверяются с записанными векторами,
+//! а распаковка — с исходным входом на каждой платформе.
+
+use arithmetic_compression_wizard::bit_wizardry::{
+    rune_hash, ArithmeticDecoder, ArithmeticEncoder, CoderPrecision,
+};
+use arithmetic_compression_wizard::prelude::*;
+
+/// Псевдослучайные байты xorshift32 (одинаковые на любой платформе)
+fn xorshift_bytes(seed: u32, len: usize) -> Vec<u8> {
+    let mut state = seed;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            (state >> 24) as u8
+        })
+        .collect()
+}
+
+fn sample_bytes() -> Vec<u8> {
+    xorshift_bytes(0x2545_F491, 20_000)
+}
+
+fn sample_prose() -> String {
+    "the wizard and the owl read the ancient scroll, and the owl wrote a new scroll. ".repeat(60)
+}
+
+/// Длина и хеш потока
+fn fingerprint(stream: &[u8]) -> (usize, u64) {
+    (stream.len(), rune_hash(stream))
+}
+
+#[test]
+fn test_coder_streams_match_vectors() {
+    let skewed = [0u32, 1, 3, 7, 4096];
+    let uniform: Vec<u32> = (0..=256).collect();
+    let vectors = [
+        (
+            CoderPrecision::Bits24,
+            &skewed[..],
+            (20543, 0x12e2_ff69_4f07_1dbf),
+        ),
+        (
+            CoderPrecision::Bits24,
+            &uniform[..],
+            (20001, 0xa04f_6142_6217_81f4),
+        ),
+        (
+            CoderPrecision::Bits32,
+            &skewed[..],
+            (20543, 0xc551_3903_0e6e_a318),
+        ),
+        (
+            CoderPrecision::Bits32,
+            &uniform[..],
+            (20001, 0xa04f_6142_6217_81f4),
+        ),
+    ];
+
+    let data = sample_bytes();
+    for (precision, cumulative, expected) in vectors {
+        let alphabet = cumulative.len() - 1;
+        let symbols: Vec<usize> = data.iter().map(|&byte| byte as usize % alphabet).collect();
+
+        let mut stream = Vec::new();
+        let mut encoder = ArithmeticEncoder::new(&mut stream, precision);
+        for &symbol in &symbols {
+            encoder.encode_symbol(cumulative, symbol).unwrap();
+        }
+        encoder.finish();
+        assert_eq!(
+            fingerprint(&stream),
+            expected,
+            "{precision:?}, {alphabet} символов"
+        );
+
+        let mut decoder = ArithmeticDecoder::new(&stream, precision);
+        for &symbol in &symbols {
+            assert_eq!(decoder.decode_symbol(cumulative).unwrap(), symbol);
+        }
+    }
+}
+
+/// Длина и хеш архива вектора
+type ArchiveVector = (&'static str, (usize, u64));
+
+/// Векторы архивов `sample_prose` по ревизиям кодировщика, от старой к новой
+///
+/// 1 — отбор слов по независимой оценке каждого слова; 2 — точный расчет
+/// заголовка кадра и совместная переоценка отобранных слов.
+const ARCHIVE_REVISIONS: [(u32, [ArchiveVector; 3]); 2] = [
+    (
+        1,
+        [
+            ("static", (656, 0x05b1_ae80_81e4_237a)),
+            ("adaptive", (477, 0xaa04_99e6_65f6_addb)),
+            ("blocks", (3427, 0x592c_c0e9_0493_acac)),
+        ],
+    ),
+    (
+        2,
+        [
+            ("static", (656, 0xdb2f_997a_9147_b91c)),
+            ("adaptive", (477, 0x6d40_c3dc_e9e3_adbd)),
+            ("blocks", (3416, 0x5d25_ffe5_42f2_46cb)),
+        ],
+    ),
+];
+
+/// Архив вектора текущим кодировщиком
+fn compress_vector(name: &str, prose: &str) -> Vec<u8> {
+    match name {
+        "static" => compress_data(prose),
+        "adaptive" => compress_data_adaptive(prose, 4096),
+        "blocks" => compress_data_in_blocks(prose, 1024),
+        _ => unreachable!("неизвестный вектор {name}"),
+    }
+}
+
+#[test]
+fn test_archives_match_vectors() {
+    let prose = sample_prose();
+    let (_, latest) = ARCHIVE_REVISIONS[ARCHIVE_REVISIONS.len() - 1];
+    for (name, expected) in latest {
+        assert_eq!(
+            fingerprint(&compress_vector(name, &prose)),
+            expected,
+            "{name}"
+        );
+    }
+
+    // Битовые плоскости не зависят от отбора слов: ревизий у них нет
+    let planes = compress_with_stage(sample_bytes(), PipelineStage::BitPlanes);
+    assert_eq!(fingerprint(&planes), (20033, 0xf5b2_3e00_b2e5_14de));
+}
+
+#[test]
+fn test_previous_revision_archives_decode() {
+    let prose = sample_prose();
+    let vectors_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden/vectors");
+    for (revision, vectors) in &ARCHIVE_REVISIONS[..ARCHIVE_REVISIONS.len() - 1] {
+        for (name, expected) in vectors {
+            let path = vectors_dir.join(format!("{name}.r{revision}.acw"));
+            let archive = std::fs::read(&path).expect("архив прежней ревизии");
+            assert_eq!(fingerprint(&archive), *expected, "{}", path.display());
+            assert_eq!(
+                decompress_data(&archive),
+                prose.as_bytes(),
+                "{}",
+                path.display()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_archives_roundtrip_on_this_target() {
+    let prose = sample_prose();
+    let data = sample_bytes();
+    assert_eq!(decompress_data(compress_data(&prose)), prose.as_bytes());
+    assert_eq!(decompress_data(compress_data_adaptive(&data, 4096)), data);
+    assert_eq!(
+        decompress_data(compress_with_stage(&data, PipelineStage::BitPlanes)),
+        data
+    );
+}
@@ -0,0 +1,106 @@
+//! Золотые архивы 🏆
+//!
+//! `tests/golden/` хранит архивы, созданные прошлыми версиями библиотеки
+//! для фиксированных входов `<имя>.in`. Имя архива — `<имя>.v<версия>.acw`,
+//! необязательный суффикс после версии (`-atlas`, `-split`) описывает
+//! параметры сжатия.
+//!
+//! Золотые файлы никогда не перезаписываются: изменение кодера или формата,
+//! которое ломает их распаковку, ломает и архивы пользователей. Новая версия
+//! формата добавляет свои файлы рядом со старыми.
+
+use arithmetic_compression_wizard::format::{ArchiveHeader, FORMAT_VERSION};
+use arithmetic_compression_wizard::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Входы, сжатие которых не зависит от порядка обхода `HashMap`
+const DETERMINISTIC_INPUTS: [&str; 3] = ["short_ascii", "binary", "dna"];
+
+/// Золотой архив и его вход
+struct GoldenArchive {
+    path: PathBuf,
+    input_stem: String,
+    format_version: u8,
+}
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+fn read_input(stem: &str) -> Vec<u8> {
+    fs::read(golden_dir().join(format!("{stem}.in"))).expect("вход золотого архива")
+}
+
+/// Все архивы каталога, разобранные по имени файла
+fn golden_archives() -> Vec<GoldenArchive> {
+    let mut archives: Vec<GoldenArchive> = fs::read_dir(golden_dir())
+        .expect("каталог tests/golden")
+        .map(|entry| entry.expect("запись каталога").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "acw"))
+        .map(|path| {
+            let file_stem = path.file_stem().unwrap().to_string_lossy().into_owned();
+            let (input_stem, tag) = file_stem.split_once(".v").expect("имя <вход>.v<версия>");
+            let version_digits: String = tag.chars().take_while(char::is_ascii_digit).collect();
+            GoldenArchive {
+                input_stem: input_stem.to_string(),
+                format_version: version_digits.parse().expect("номер версии"),
+                path,
+            }
+        })
+        .collect();
+    archives.sort_by(|a, b| a.path.cmp(&b.path));
+    archives
+}
+
+#[test]
+fn test_golden_archives_decode() {
+    let archives = golden_archives();
+    assert!(!archives.is_empty());
+
+    for archive in &archives {
+        let bytes = fs::read(&archive.path).unwrap();
+        let expected = read_input(&archive.input_stem);
+
+        let (header, _) = ArchiveHeader::parse(&bytes).expect("заголовок золотого архива");
+        assert_eq!(
+            header.format_version,
+            archive.format_version,
+            "{}",
+            archive.path.display()
+        );
+
+        let restored = try_decompress_data(&bytes, &DecodeOptions::default())
+            .unwrap_or_else(|error| panic!("{}: {error}", archive.path.display()));
+        assert_eq!(restored, expected, "{}", archive.path.display());
+    }
+}
+
+#[test]
+fn test_every_format_version_is_covered() {
+    let archives = golden_archives();
+    for version in 0..=FORMAT_VERSION {
+        assert!(
+            archives
+                .iter()
+                .any(|archive| archive.format_version == version),
+            "нет золотых архивов версии {version}"
+        );
+    }
+}
+
+#[test]
+fn test_current_encoder_matches_golden_bytes() {
+    for stem in DETERMINISTIC_INPUTS {
+        let golden_path = golden_dir().join(format!("{stem}.v{FORMAT_VERSION}.acw"));
+        let golden = fs::read(&golden_path)
+            .unwrap_or_else(|_| panic!("нет золотого архива {}", golden_path.display()));
+
+        assert_eq!(
+            compress_data(read_input(stem)),
+            golden,
+            "кодер изменил байты {}",
+            golden_path.display()
+        );
+    }
+}